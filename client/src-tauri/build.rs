@@ -1,3 +1,13 @@
 fn main() {
+    // Compile the gRPC schema with a pure-Rust protobuf parser so builds do
+    // not need `protoc` installed.
+    println!("cargo:rerun-if-changed=proto");
+    let fds = protox::compile(["proto/cost_optimizer.proto"], ["proto"])
+        .expect("failed to parse proto/cost_optimizer.proto");
+    tonic_build::configure()
+        .build_client(false)
+        .compile_fds(fds)
+        .expect("failed to generate gRPC bindings");
+
     tauri_build::build()
 }
@@ -0,0 +1,254 @@
+//! Streams a long analysis's progress from the backend to the webview. The
+//! backend answers a GET with server-sent events or newline-delimited JSON
+//! chunks; each is re-emitted as `analysis://{id}/progress` as it arrives,
+//! and the stream ends with `analysis://{id}/complete` or
+//! `analysis://{id}/error`.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::backend;
+use crate::error::{AppError, CommandResult};
+
+/// Streams running now, by id, with the flag that stops each.
+static STREAMS: Mutex<HashMap<String, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+
+/// One event from the stream.
+#[derive(Debug, PartialEq, Eq)]
+struct StreamEvent {
+    /// `progress`, `complete` or `error`; plain SSE messages and JSON lines
+    /// count as progress.
+    kind: String,
+    data: String,
+}
+
+/// Splits the bytes of a stream into events. A line that is not an SSE field
+/// is taken as one JSON chunk, so chunked replies work as well.
+#[derive(Default)]
+struct EventParser {
+    line: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl EventParser {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        for &byte in bytes {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let raw = std::mem::take(&mut self.line);
+            let line = String::from_utf8_lossy(&raw);
+            if let Some(event) = self.line_done(line.trim_end_matches('\r')) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn line_done(&mut self, line: &str) -> Option<StreamEvent> {
+        if line.is_empty() {
+            let kind = self.event.take().unwrap_or_else(|| "progress".into());
+            if self.data.is_empty() {
+                return None;
+            }
+            let data = std::mem::take(&mut self.data).join("\n");
+            return Some(StreamEvent { kind, data });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" | "retry" => {}
+            _ => {
+                return Some(StreamEvent {
+                    kind: "progress".into(),
+                    data: line.to_string(),
+                })
+            }
+        }
+        None
+    }
+}
+
+/// The payload of `analysis://{id}/error`.
+#[derive(Serialize, Clone, Debug)]
+pub struct AnalysisError {
+    pub message: String,
+}
+
+/// Event data as JSON where it is, else as a string.
+fn payload(data: &str) -> serde_json::Value {
+    serde_json::from_str(data).unwrap_or_else(|_| serde_json::Value::String(data.into()))
+}
+
+/// The message of an `error` event, from FastAPI's `detail` when present.
+fn error_message(data: &str) -> String {
+    match payload(data) {
+        serde_json::Value::Object(body) => match body.get("detail") {
+            Some(serde_json::Value::String(detail)) => detail.clone(),
+            Some(detail) => detail.to_string(),
+            None => data.to_string(),
+        },
+        serde_json::Value::String(message) => message,
+        other => other.to_string(),
+    }
+}
+
+/// Reads the stream until it ends, emitting each event. Errs with the reason
+/// the analysis did not complete; `Ok` once `complete` arrived or the stream
+/// was stopped.
+fn pump(app: &AppHandle, id: &str, path: &str, stop: &AtomicBool) -> Result<(), String> {
+    let (status, mut body) = backend::open_stream(path).map_err(|e| e.to_string())?;
+    if !(200..300).contains(&status) {
+        let mut reply = String::new();
+        let _ = body.read_to_string(&mut reply);
+        return Err(backend::sidecar_error(status, &reply).to_string());
+    }
+    let mut parser = EventParser::default();
+    let mut buf = [0u8; 8192];
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let read = match body.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(format!("The analysis stream broke off: {err}")),
+        };
+        for event in parser.feed(&buf[..read]) {
+            match event.kind.as_str() {
+                "complete" => {
+                    let _ = app.emit(&format!("analysis://{id}/complete"), payload(&event.data));
+                    return Ok(());
+                }
+                "error" => return Err(error_message(&event.data)),
+                _ => {
+                    let _ = app.emit(&format!("analysis://{id}/progress"), payload(&event.data));
+                }
+            }
+        }
+    }
+    Err("The backend ended the stream before the analysis finished".into())
+}
+
+/// Ids end up in event names, which allow only some characters.
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Starts streaming the analysis at `path` (e.g.
+/// `/optimizer/scan/{scan_id}/events`) under `id`, returning at once. Listen
+/// for the events before calling.
+#[tauri::command]
+pub fn stream_analysis(app: AppHandle, id: String, path: String) -> CommandResult<()> {
+    if !valid_id(&id) {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid analysis id {id:?}"
+        )));
+    }
+    backend::check_path(&path)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let mut streams = STREAMS.lock().unwrap_or_else(|e| e.into_inner());
+        if streams.contains_key(&id) {
+            return Err(AppError::Conflict(format!(
+                "Analysis {id} is already being streamed"
+            )));
+        }
+        streams.insert(id.clone(), stop.clone());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(message) = pump(&app, &id, &path, &stop) {
+            let _ = app.emit(&format!("analysis://{id}/error"), AnalysisError { message });
+        }
+        STREAMS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id);
+    });
+    Ok(())
+}
+
+/// Stops relaying a stream; the analysis itself carries on. Takes effect
+/// when the next chunk arrives.
+#[tauri::command]
+pub fn stop_analysis_stream(id: String) {
+    if let Some(stop) = STREAMS.lock().unwrap_or_else(|e| e.into_inner()).get(&id) {
+        stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: &str, data: &str) -> StreamEvent {
+        StreamEvent {
+            kind: kind.into(),
+            data: data.into(),
+        }
+    }
+
+    #[test]
+    fn sse_events_are_split_across_chunks() {
+        let mut parser = EventParser::default();
+        assert!(parser
+            .feed(b"event: progress\r\ndata: {\"percent\"")
+            .is_empty());
+        assert_eq!(
+            parser.feed(b":5}\r\n\r\n: keepalive\n\nevent: complete\ndata: {}\n\n"),
+            vec![
+                event("progress", r#"{"percent":5}"#),
+                event("complete", "{}"),
+            ]
+        );
+        assert_eq!(
+            parser.feed(b"data: a\ndata: b\n\n"),
+            vec![event("progress", "a\nb")]
+        );
+    }
+
+    #[test]
+    fn json_lines_are_progress() {
+        let mut parser = EventParser::default();
+        assert_eq!(
+            parser.feed(b"{\"percent\":10}\n{\"percent\":20}\n"),
+            vec![
+                event("progress", r#"{"percent":10}"#),
+                event("progress", r#"{"percent":20}"#),
+            ]
+        );
+    }
+
+    #[test]
+    fn error_events_carry_the_detail() {
+        assert_eq!(
+            error_message(r#"{"detail":"Scan 'a' was cancelled."}"#),
+            "Scan 'a' was cancelled."
+        );
+        assert_eq!(error_message("boom"), "boom");
+        assert!(valid_id("scan-1_a"));
+        assert!(!valid_id("a/b") && !valid_id(""));
+    }
+}
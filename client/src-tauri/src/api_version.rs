@@ -0,0 +1,126 @@
+//! Schema handshake between the webview and the shell. A partial update can
+//! leave webview assets from one release talking to a shell from another, so
+//! the webview asks for [`get_api_version`] before its first command and
+//! stops with an "update incomplete" notice when the shell's schema is outside
+//! the range it understands, instead of rendering payloads it misreads.
+//! Command errors carry the schema version too.
+//!
+//! The sidecar is checked the same way once it answers: its `/version` must
+//! report [`BACKEND_API_VERSION`]. A mismatch is emitted as
+//! `backend-incompatible` and reported by [`get_api_version`], and the
+//! webview then shows the notice instead of its pages.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::backend;
+use crate::error::AppError;
+
+/// Bumped whenever a command's arguments or response shape change in a way
+/// an older webview would misread.
+pub const SCHEMA_VERSION: u32 = 1;
+/// Oldest webview schema this shell still answers correctly.
+pub const MIN_CLIENT_SCHEMA_VERSION: u32 = 1;
+/// Sidecar HTTP API this shell is built against. Keep in step with
+/// `API_VERSION` in `server/app/api/routes/health.py`.
+pub const BACKEND_API_VERSION: u32 = 1;
+#[cfg_attr(dev, allow(dead_code))]
+pub const BACKEND_INCOMPATIBLE_EVENT: &str = "backend-incompatible";
+
+/// Set while the running sidecar is from another release.
+static BACKEND_MISMATCH: Mutex<Option<BackendMismatch>> = Mutex::new(None);
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ApiVersion {
+    pub schema_version: u32,
+    pub min_client_schema_version: u32,
+    pub app_version: &'static str,
+    /// Set when the sidecar does not speak [`BACKEND_API_VERSION`].
+    pub backend_mismatch: Option<BackendMismatch>,
+}
+
+/// What the sidecar's `/version` returns.
+#[cfg_attr(dev, allow(dead_code))]
+#[derive(Deserialize)]
+struct BackendVersion {
+    api_version: u32,
+    app_version: String,
+}
+
+/// Payload of `backend-incompatible`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BackendMismatch {
+    pub expected: u32,
+    /// `None` for a sidecar that predates `/version`.
+    pub found: Option<u32>,
+    pub backend_version: Option<String>,
+}
+
+/// The mismatch a `/version` reply amounts to, if any. A sidecar that could
+/// not be asked is not held to be incompatible.
+#[cfg_attr(dev, allow(dead_code))]
+fn mismatch(reply: Result<BackendVersion, AppError>) -> Option<BackendMismatch> {
+    match reply {
+        Ok(version) if version.api_version == BACKEND_API_VERSION => None,
+        Ok(version) => Some(BackendMismatch {
+            expected: BACKEND_API_VERSION,
+            found: Some(version.api_version),
+            backend_version: Some(version.app_version),
+        }),
+        Err(AppError::NotFound(_)) => Some(BackendMismatch {
+            expected: BACKEND_API_VERSION,
+            found: None,
+            backend_version: None,
+        }),
+        Err(_) => None,
+    }
+}
+
+/// Asks the sidecar for its API version, announcing a mismatch.
+#[cfg_attr(dev, allow(dead_code))]
+pub(crate) fn check_backend(app: &AppHandle) {
+    let found = mismatch(backend::get_json("/version"));
+    *BACKEND_MISMATCH.lock().unwrap_or_else(|e| e.into_inner()) = found.clone();
+    if let Some(found) = found {
+        eprintln!("sidecar API version mismatch: {found:?}");
+        let _ = app.emit(BACKEND_INCOMPATIBLE_EVENT, found);
+    }
+}
+
+#[tauri::command]
+pub fn get_api_version() -> ApiVersion {
+    ApiVersion {
+        schema_version: SCHEMA_VERSION,
+        min_client_schema_version: MIN_CLIENT_SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION"),
+        backend_mismatch: BACKEND_MISMATCH
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_expected_backend_version_passes() {
+        let reply = |api_version| {
+            Ok(BackendVersion {
+                api_version,
+                app_version: "1.0.0".into(),
+            })
+        };
+        assert_eq!(mismatch(reply(BACKEND_API_VERSION)), None);
+        assert_eq!(
+            mismatch(reply(BACKEND_API_VERSION + 1)).and_then(|m| m.found),
+            Some(BACKEND_API_VERSION + 1)
+        );
+        let missing = mismatch(Err(AppError::NotFound("Not Found".into())));
+        assert_eq!(missing.map(|m| m.found), Some(None));
+        assert_eq!(mismatch(Err(AppError::Sidecar("down".into()))), None);
+    }
+}
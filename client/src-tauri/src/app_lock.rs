@@ -0,0 +1,378 @@
+//! Application lock for shared machines. When enabled, the app starts locked
+//! and locks again after a period without activity (or on `lock_app`); while
+//! locked, commands that touch credentials or hand out local API tokens fail
+//! with a `locked` error. Unlocking takes the lock passphrase, or OS
+//! confirmation when that is allowed. The passphrase is kept only as a PBKDF2
+//! hash in the secret store; each wrong one doubles the wait before the next
+//! attempt.
+//!
+//! Besides the checks in individual commands, the invoke handler refuses
+//! every command that is not on `ALLOWED_WHILE_LOCKED` while locked, so new
+//! commands are covered without opting in.
+
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use ring::pbkdf2;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::{keyring_entry_for, os_auth, secret_store, settings};
+
+pub const LOCKED_EVENT: &str = "locked";
+pub(crate) const VERIFIER_ACCOUNT: &str = "app-lock-passphrase";
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const MIN_PASSPHRASE_LEN: usize = 8;
+/// Wait after the first wrong passphrase; it doubles with each further one.
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// Commands the webview may call while locked: the lock's own, and status
+/// reads that reveal nothing about accounts or spend.
+const ALLOWED_WHILE_LOCKED: &[&str] = &[
+    "get_app_lock_status",
+    "lock_app",
+    "unlock_app",
+    "record_app_activity",
+    "get_app_health",
+    "get_api_version",
+    "get_backend_phase",
+    "get_backend_status",
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AppLockSettings {
+    pub enabled: bool,
+    /// Minutes without activity before the app locks; 0 disables auto-lock.
+    pub idle_minutes: u32,
+    /// Accept OS confirmation (Touch ID, Windows Hello, polkit) to unlock.
+    pub allow_os_auth: bool,
+}
+
+impl Default for AppLockSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            idle_minutes: 15,
+            allow_os_auth: true,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AppLockStatus {
+    pub settings: AppLockSettings,
+    pub locked: bool,
+    pub has_passphrase: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AppLocked {
+    /// `launch`, `idle` or `manual`.
+    pub reason: &'static str,
+}
+
+pub struct AppLockState {
+    locked: AtomicBool,
+    last_activity: Mutex<Instant>,
+    /// Wrong passphrases since the last unlock.
+    failures: AtomicU32,
+    /// No passphrase is checked before this.
+    retry_at: Mutex<Option<Instant>>,
+}
+
+impl Default for AppLockState {
+    fn default() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            last_activity: Mutex::new(Instant::now()),
+            failures: AtomicU32::new(0),
+            retry_at: Mutex::new(None),
+        }
+    }
+}
+
+impl AppLockState {
+    fn touch(&self) {
+        if let Ok(mut last) = self.last_activity.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    fn idle_for(&self) -> Duration {
+        self.last_activity
+            .lock()
+            .map(|last| last.elapsed())
+            .unwrap_or_default()
+    }
+
+    /// Errs while the wait after a wrong passphrase has not passed.
+    fn check_retry(&self) -> Result<(), AppError> {
+        let retry_at = *self.retry_at.lock().unwrap_or_else(|e| e.into_inner());
+        match retry_at.map(|at| at.saturating_duration_since(Instant::now())) {
+            Some(wait) if !wait.is_zero() => Err(AppError::Locked(format!(
+                "Too many wrong passphrases; try again in {} s",
+                wait.as_secs().max(1)
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn record_failure(&self) {
+        let failures = self.failures.fetch_add(1, Ordering::SeqCst) + 1;
+        *self.retry_at.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some(Instant::now() + retry_delay(failures));
+    }
+
+    fn clear_failures(&self) {
+        self.failures.store(0, Ordering::SeqCst);
+        *self.retry_at.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Wait before the next attempt after `failures` wrong passphrases.
+fn retry_delay(failures: u32) -> Duration {
+    let doublings = failures.saturating_sub(1).min(16);
+    (FIRST_RETRY_DELAY * 2u32.pow(doublings)).min(MAX_RETRY_DELAY)
+}
+
+/// PBKDF2 hash of the lock passphrase.
+#[derive(Serialize, Deserialize)]
+struct Verifier {
+    iterations: u32,
+    salt: String,
+    hash: String,
+}
+
+fn hash_passphrase(passphrase: &str, salt: Vec<u8>, iterations: u32) -> Verifier {
+    let hash = secret_store::derive_key(passphrase, &salt, iterations);
+    Verifier {
+        iterations,
+        salt: BASE64.encode(salt),
+        hash: BASE64.encode(hash),
+    }
+}
+
+fn matches(verifier: &Verifier, passphrase: &str) -> bool {
+    let (Ok(salt), Ok(hash), Some(iterations)) = (
+        BASE64.decode(&verifier.salt),
+        BASE64.decode(&verifier.hash),
+        NonZeroU32::new(verifier.iterations),
+    ) else {
+        return false;
+    };
+    pbkdf2::verify(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        passphrase.as_bytes(),
+        &hash,
+    )
+    .is_ok()
+}
+
+fn read_verifier() -> Option<Verifier> {
+    let raw = keyring_entry_for(VERIFIER_ACCOUNT)
+        .ok()?
+        .get_password()
+        .ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+fn write_verifier(passphrase: &str) -> Result<(), AppError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Use a passphrase of at least {MIN_PASSPHRASE_LEN} characters"
+        )));
+    }
+    let salt = secret_store::random::<16>()?.to_vec();
+    let verifier = hash_passphrase(passphrase, salt, secret_store::ITERATIONS);
+    keyring_entry_for(VERIFIER_ACCOUNT)?
+        .set_password(&serde_json::to_string(&verifier).map_err(|e| e.to_string())?)
+        .map_err(|e| AppError::Credentials(e.to_string()))
+}
+
+fn lock(app: &AppHandle, reason: &'static str) {
+    let state = app.state::<AppLockState>();
+    if !state.locked.swap(true, Ordering::SeqCst) {
+        let _ = app.emit(LOCKED_EVENT, AppLocked { reason });
+    }
+}
+
+/// Fails while the app is locked; otherwise counts as activity. Called first
+/// by every command that exposes credentials or tokens.
+pub(crate) fn ensure_unlocked(app: &AppHandle) -> Result<(), AppError> {
+    let state = app.state::<AppLockState>();
+    if state.locked.load(Ordering::SeqCst) {
+        return Err(AppError::Locked("The app is locked".into()));
+    }
+    state.touch();
+    Ok(())
+}
+
+/// Whether the invoke handler refuses `command` now: while locked, every
+/// command not on the allow-list is.
+pub(crate) fn refuses(app: &AppHandle, command: &str) -> bool {
+    app.state::<AppLockState>().locked.load(Ordering::SeqCst)
+        && !ALLOWED_WHILE_LOCKED.contains(&command)
+}
+
+fn status(app: &AppHandle) -> AppLockStatus {
+    AppLockStatus {
+        settings: settings::load(app).app_lock,
+        locked: app.state::<AppLockState>().locked.load(Ordering::SeqCst),
+        has_passphrase: read_verifier().is_some(),
+    }
+}
+
+/// Locks at launch when the lock is enabled, then watches for idleness.
+pub fn spawn_idle_lock(app: AppHandle) {
+    if settings::load(&app).app_lock.enabled {
+        lock(&app, "launch");
+    }
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let cfg = settings::load(&app).app_lock;
+            let idle = Duration::from_secs(u64::from(cfg.idle_minutes) * 60);
+            if cfg.enabled && cfg.idle_minutes > 0 && app.state::<AppLockState>().idle_for() >= idle
+            {
+                lock(&app, "idle");
+            }
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_app_lock_status(app: AppHandle) -> AppLockStatus {
+    status(&app)
+}
+
+/// Changes the lock settings, and the passphrase when one is given. Needs
+/// the app unlocked; enabling needs a passphrase or OS confirmation.
+#[tauri::command]
+pub async fn save_app_lock_settings(
+    app: AppHandle,
+    config: AppLockSettings,
+    passphrase: Option<String>,
+) -> CommandResult<AppLockStatus> {
+    ensure_unlocked(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(passphrase) = passphrase.filter(|p| !p.is_empty()) {
+            write_verifier(&passphrase)?;
+        }
+        if config.enabled && !config.allow_os_auth && read_verifier().is_none() {
+            return Err(AppError::InvalidInput(
+                "Set a passphrase or allow OS confirmation to enable the lock".into(),
+            ));
+        }
+        let mut all = settings::load(&app);
+        all.app_lock = config;
+        settings::save(&app, &all)?;
+        Ok(status(&app))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn lock_app(app: AppHandle) -> CommandResult<()> {
+    if !settings::load(&app).app_lock.enabled {
+        return Err(AppError::InvalidInput("The app lock is not enabled".into()));
+    }
+    lock(&app, "manual");
+    Ok(())
+}
+
+/// Unlocks with the passphrase or, without one, OS confirmation.
+#[tauri::command]
+pub async fn unlock_app(app: AppHandle, passphrase: Option<String>) -> CommandResult<()> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let cfg = settings::load(&app).app_lock;
+        let state = app.state::<AppLockState>();
+        match passphrase.filter(|p| !p.is_empty()) {
+            Some(passphrase) => {
+                state.check_retry()?;
+                let verifier = read_verifier()
+                    .ok_or_else(|| AppError::InvalidInput("No lock passphrase is set".into()))?;
+                if !matches(&verifier, &passphrase) {
+                    state.record_failure();
+                    return Err(AppError::InvalidInput("Wrong passphrase".into()));
+                }
+            }
+            None if cfg.allow_os_auth => os_auth::confirm_user()?,
+            None => return Err(AppError::InvalidInput("Enter the lock passphrase".into())),
+        }
+        state.clear_failures();
+        state.touch();
+        state.locked.store(false, Ordering::SeqCst);
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Resets the idle timer; the UI calls this on user input.
+#[tauri::command]
+pub fn record_app_activity(app: AppHandle) {
+    let state = app.state::<AppLockState>();
+    if !state.locked.load(Ordering::SeqCst) {
+        state.touch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passphrases_verify_against_their_hash() {
+        let verifier = hash_passphrase("finance team", b"0123456789abcdef".to_vec(), 10);
+        assert!(matches(&verifier, "finance team"));
+        assert!(!matches(&verifier, "finance-team"));
+        assert!(!matches(
+            &Verifier {
+                iterations: 0,
+                ..verifier
+            },
+            "finance team"
+        ));
+    }
+
+    #[test]
+    fn retry_delays_double_up_to_a_cap() {
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(5), Duration::from_secs(16));
+        assert_eq!(retry_delay(20), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn wrong_passphrases_hold_off_the_next_attempt() {
+        let state = AppLockState::default();
+        assert!(state.check_retry().is_ok());
+        state.record_failure();
+        assert!(matches!(state.check_retry(), Err(AppError::Locked(_))));
+        state.clear_failures();
+        assert!(state.check_retry().is_ok());
+    }
+
+    #[test]
+    fn only_lock_and_status_commands_run_while_locked() {
+        assert!(ALLOWED_WHILE_LOCKED.contains(&"unlock_app"));
+        for command in ["unlock_secret_store", "query_costs", "save_proxy_settings"] {
+            assert!(!ALLOWED_WHILE_LOCKED.contains(&command));
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Native AWS SDK access from the shell, configured from the stored
+//! credentials (the same ones injected into the sidecar).
+//!
+//! Every SDK call goes through [`send`] (or [`send_with_failover`]), which
+//! owns retrying: the SDK's own retry layer is disabled so throttling and
+//! transient failures are handled the same way everywhere. The attempt
+//! budget and per-attempt timeout come from `crate::resilience`.
+//!
+//! Tests and `mock` builds swap the SDK's HTTP client for a replay client
+//! (see `crate::mock`), so the same code paths run without credentials.
+
+use std::future::Future;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use aws_config::provider_config::ProviderConfig;
+use aws_config::retry::RetryConfig;
+use aws_config::timeout::TimeoutConfig;
+use aws_config::{BehaviorVersion, Region, SdkConfig};
+use aws_credential_types::Credentials;
+use aws_smithy_runtime_api::client::http::SharedHttpClient;
+use aws_smithy_runtime_api::client::result::SdkError;
+use aws_smithy_runtime_api::http::Response;
+use aws_smithy_types::error::metadata::ProvideErrorMetadata;
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::partition::Partition;
+use crate::{perf, read_credentials, resilience, AwsCredentials};
+
+/// Replaces the SDK's HTTPS client when set.
+static HTTP_CLIENT: RwLock<Option<SharedHttpClient>> = RwLock::new(None);
+
+/// Sends every SDK request through `client`; `None` restores the default.
+#[cfg(feature = "mock")]
+pub fn set_http_client(client: Option<SharedHttpClient>) {
+    *HTTP_CLIENT.write().unwrap_or_else(|e| e.into_inner()) = client;
+}
+
+/// Builds an SDK config for the stored credentials and region.
+pub async fn sdk_config(app: &AppHandle) -> Result<SdkConfig, AppError> {
+    let creds = read_credentials(app)
+        .ok_or_else(|| AppError::Credentials("No AWS credentials saved".into()))?;
+    Ok(sdk_config_from(creds).await)
+}
+
+/// Builds an SDK config for explicit credentials, e.g. a CLI profile.
+pub async fn sdk_config_from(creds: AwsCredentials) -> SdkConfig {
+    let provider = Credentials::new(
+        creds.access_key_id.clone(),
+        creds.secret_access_key().to_string(),
+        creds.session_token().map(str::to_string),
+        None,
+        "aws-cost-optimizer",
+    );
+    let loader = loader(creds.region.clone()).credentials_provider(provider);
+    match creds.endpoint_url() {
+        Some(url) => loader.endpoint_url(url),
+        None => loader,
+    }
+    .load()
+    .await
+}
+
+/// Custom endpoints (LocalStack, moto) are plain HTTP(S) base URLs.
+pub(crate) fn validate_endpoint_url(url: Option<&str>) -> Result<(), AppError> {
+    match url {
+        Some(url)
+            if !(url.starts_with("https://") || url.starts_with("http://"))
+                || url.contains(char::is_whitespace) =>
+        {
+            Err(AppError::InvalidInput(format!(
+                "Endpoint URLs start with http:// or https://: {url}"
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Settings for the SDK's own credential providers (instance metadata,
+/// container endpoint), sharing the replaced HTTP client if there is one.
+pub(crate) fn provider_config() -> ProviderConfig {
+    match http_client() {
+        Some(client) => ProviderConfig::default().with_http_client(client),
+        None => ProviderConfig::default(),
+    }
+}
+
+/// Builds an SDK config without credentials, for APIs that authenticate with
+/// a bearer token instead of request signing (SSO, SSO OIDC).
+pub async fn unsigned_config(region: &str) -> SdkConfig {
+    loader(region.to_string()).no_credentials().load().await
+}
+
+fn loader(region: String) -> aws_config::ConfigLoader {
+    let loader = aws_config::defaults(BehaviorVersion::latest())
+        .region(Region::new(region))
+        .retry_config(RetryConfig::disabled())
+        .timeout_config(
+            TimeoutConfig::builder()
+                .operation_attempt_timeout(resilience::current().aws_attempt_timeout())
+                .build(),
+        );
+    match http_client() {
+        Some(client) => loader.http_client(client),
+        None => loader,
+    }
+}
+
+/// The replaced client, else one trusting extra CA certificates if any.
+fn http_client() -> Option<SharedHttpClient> {
+    HTTP_CLIENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .or_else(crate::ca_trust::sdk_http_client)
+}
+
+const CREDENTIAL_CODES: &[&str] = &[
+    "ExpiredToken",
+    "ExpiredTokenException",
+    "InvalidClientTokenId",
+    "InvalidAccessKeyId",
+    "SignatureDoesNotMatch",
+    "UnrecognizedClientException",
+];
+
+const ACCESS_DENIED_CODES: &[&str] = &[
+    "AccessDenied",
+    "AccessDeniedException",
+    "UnauthorizedOperation",
+    "UnauthorizedException",
+    "AuthorizationError",
+];
+
+/// Classifies an SDK error by its code and formats it with its full source
+/// chain; the plain `Display` of SDK errors is just "service error".
+pub fn sdk_error<E>(err: SdkError<E, Response>) -> AppError
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    let failure = classify(&err);
+    let code = err
+        .as_service_error()
+        .and_then(|e| e.code())
+        .map(str::to_string);
+    let message = aws_smithy_types::error::display::DisplayErrorContext(err).to_string();
+    match code.as_deref() {
+        Some(c) if CREDENTIAL_CODES.contains(&c) => return AppError::Credentials(message),
+        Some(c) if ACCESS_DENIED_CODES.contains(&c) => return AppError::AccessDenied(message),
+        Some("ValidationException" | "InvalidParameterValue" | "InvalidParameterCombination") => {
+            return AppError::InvalidInput(message)
+        }
+        _ => {}
+    }
+    match failure {
+        Failure::Throttled => AppError::Throttled(message),
+        Failure::Transient => AppError::Network(message),
+        _ => AppError::Aws(message),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Retry middleware
+// ---------------------------------------------------------------------------
+
+const BASE_DELAY: Duration = Duration::from_millis(250);
+/// Throttling backs off from a higher floor than transient errors.
+const THROTTLE_BASE_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(20);
+
+const THROTTLING_CODES: &[&str] = &[
+    "Throttling",
+    "ThrottlingException",
+    "ThrottledException",
+    "RequestThrottled",
+    "RequestThrottledException",
+    "TooManyRequestsException",
+    "ProvisionedThroughputExceededException",
+    "RequestLimitExceeded",
+    "BandwidthLimitExceeded",
+    "SlowDown",
+    // Cost Explorer reports its request rate limit this way.
+    "LimitExceededException",
+];
+
+const TRANSIENT_CODES: &[&str] = &[
+    "RequestTimeout",
+    "RequestTimeoutException",
+    "InternalError",
+    "InternalFailure",
+    "InternalServerError",
+    "ServiceUnavailable",
+    "ServiceUnavailableException",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Failure {
+    Throttled,
+    /// Timeouts, connection failures and 5xx responses.
+    Transient,
+    Permanent,
+}
+
+fn classify<E: ProvideErrorMetadata>(err: &SdkError<E, Response>) -> Failure {
+    match err {
+        SdkError::TimeoutError(_) => return Failure::Transient,
+        SdkError::DispatchFailure(failure) if !failure.is_user() => return Failure::Transient,
+        _ => {}
+    }
+    let code = err.as_service_error().and_then(|e| e.code());
+    if code.is_some_and(|c| THROTTLING_CODES.contains(&c)) {
+        return Failure::Throttled;
+    }
+    if code.is_some_and(|c| TRANSIENT_CODES.contains(&c)) {
+        return Failure::Transient;
+    }
+    match err.raw_response().map(|r| r.status().as_u16()) {
+        Some(429) => Failure::Throttled,
+        Some(500 | 502 | 503 | 504) => Failure::Transient,
+        _ => Failure::Permanent,
+    }
+}
+
+/// Full-jitter exponential backoff for the given 1-based attempt.
+fn delay(failure: Failure, attempt: u32) -> Duration {
+    let base = match failure {
+        Failure::Throttled => THROTTLE_BASE_DELAY,
+        _ => BASE_DELAY,
+    };
+    let cap = (base * 2u32.pow(attempt - 1)).min(MAX_DELAY);
+    Duration::from_millis(fastrand::u64(0..=cap.as_millis() as u64))
+}
+
+/// Runs an SDK call, retrying throttling and transient failures with
+/// backoff. Use for calls bound to one region (or to a global endpoint).
+/// The last error is returned as-is so callers can still match on codes.
+pub async fn send<T, E, F, Fut>(mut call: F) -> Result<T, SdkError<E, Response>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, SdkError<E, Response>>>,
+    E: ProvideErrorMetadata,
+{
+    let max_attempts = resilience::current().aws_max_attempts;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        perf::count_aws_call();
+        match call().await {
+            Ok(out) => return Ok(out),
+            Err(err) => match classify(&err) {
+                failure @ (Failure::Throttled | Failure::Transient) if attempt < max_attempts => {
+                    tokio::time::sleep(delay(failure, attempt)).await;
+                }
+                _ => return Err(err),
+            },
+        }
+    }
+}
+
+/// Like [`send`], but when the config's region keeps failing transiently the
+/// call is repeated against each of `fallback_regions` in turn. Only for
+/// calls whose answer does not depend on the region, e.g. STS or
+/// account-level lookups. `call` builds its client from the config it is given.
+pub async fn send_with_failover<T, E, F, Fut>(
+    config: &SdkConfig,
+    fallback_regions: &[&str],
+    mut call: F,
+) -> Result<T, SdkError<E, Response>>
+where
+    F: FnMut(SdkConfig) -> Fut,
+    Fut: Future<Output = Result<T, SdkError<E, Response>>>,
+    E: ProvideErrorMetadata,
+{
+    let max_attempts = resilience::current().aws_max_attempts;
+    let mut regional = config.clone();
+    let mut fallbacks = fallback_regions.iter();
+    loop {
+        let mut attempt = 0;
+        let err = loop {
+            attempt += 1;
+            perf::count_aws_call();
+            match call(regional.clone()).await {
+                Ok(out) => return Ok(out),
+                Err(err) => match classify(&err) {
+                    failure @ (Failure::Throttled | Failure::Transient)
+                        if attempt < max_attempts =>
+                    {
+                        tokio::time::sleep(delay(failure, attempt)).await;
+                    }
+                    failure => break (failure, err),
+                },
+            }
+        };
+        // Throttling is per account, so another region would not help.
+        match (err.0, fallbacks.next()) {
+            (Failure::Transient, Some(region)) => {
+                eprintln!(
+                    "AWS call failing in {:?}, failing over to {region}",
+                    regional.region()
+                );
+                regional = regional
+                    .into_builder()
+                    .region(Region::new(region.to_string()))
+                    .build();
+            }
+            _ => return Err(err.1),
+        }
+    }
+}
+
+/// STS answers the same in every region of a partition, so it may fail over
+/// to these, chosen from the config's partition.
+pub(crate) fn sts_fallback_regions(config: &SdkConfig) -> &'static [&'static str] {
+    let region = config.region().map(|r| r.as_ref()).unwrap_or_default();
+    Partition::of_region(region).sts_fallback_regions()
+}
+
+/// Account the config's credentials belong to.
+pub async fn account_id(config: &SdkConfig) -> Result<String, AppError> {
+    let identity = send_with_failover(config, sts_fallback_regions(config), |config| async move {
+        aws_sdk_sts::Client::new(&config)
+            .get_caller_identity()
+            .send()
+            .await
+    })
+    .await
+    .map_err(sdk_error)?;
+    identity
+        .account()
+        .map(str::to_string)
+        .ok_or_else(|| AppError::Aws("STS returned no account id".into()))
+}
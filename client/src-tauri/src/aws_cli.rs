@@ -0,0 +1,558 @@
+//! Interop with the AWS CLI's shared files (`~/.aws/credentials` and
+//! `~/.aws/config`). The optional sync mode keeps the app's credentials in
+//! step with one named CLI profile by polling the files for changes. The app
+//! only writes to the CLI files when explicitly asked. Profiles with static
+//! keys can also be imported once as app credential profiles; the files are
+//! then watched, and `aws-cli-profiles-changed` names imported profiles whose
+//! keys on disk no longer match the app's copy, so they can be re-imported.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::{app_lock, apply_credentials, profiles, read_credentials, settings, AwsCredentials};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+pub const CHANGED_EVENT: &str = "aws-cli-profiles-changed";
+/// Editors write in bursts (temp file, rename, chmod); one check follows.
+const SETTLE: Duration = Duration::from_millis(500);
+/// How often a missing `~/.aws` directory is looked for again.
+const WATCH_RETRY: Duration = Duration::from_secs(30);
+const FALLBACK_REGION: &str = "us-east-1";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ProfileSyncSettings {
+    pub enabled: bool,
+    /// CLI profile the app's credentials follow.
+    pub profile: String,
+}
+
+impl Default for ProfileSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            profile: "default".into(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CliProfile {
+    pub name: String,
+    pub region: Option<String>,
+    /// Whether the profile holds an access key pair (as opposed to SSO or
+    /// role configuration the app cannot follow yet).
+    pub has_static_keys: bool,
+    /// Whether the profile gets its keys from a `credential_process` helper.
+    pub has_credential_process: bool,
+}
+
+/// Outcome of importing one CLI profile.
+#[derive(Serialize, Clone, Debug)]
+pub struct ProfileImport {
+    pub name: String,
+    pub region: Option<String>,
+    pub imported: bool,
+    pub error: Option<String>,
+}
+
+/// Payload of the `aws-cli-profiles-changed` event.
+#[derive(Serialize, Clone, Debug)]
+pub struct CliProfilesChanged {
+    pub profiles: Vec<String>,
+}
+
+type Sections = BTreeMap<String, BTreeMap<String, String>>;
+
+// ---------------------------------------------------------------------------
+// Shared file parsing
+// ---------------------------------------------------------------------------
+
+fn aws_file(app: &AppHandle, env_var: &str, name: &str) -> Result<PathBuf, String> {
+    if let Some(path) = std::env::var_os(env_var).filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    let home = app.path().home_dir().map_err(|e| e.to_string())?;
+    Ok(home.join(".aws").join(name))
+}
+
+fn credentials_file(app: &AppHandle) -> Result<PathBuf, String> {
+    aws_file(app, "AWS_SHARED_CREDENTIALS_FILE", "credentials")
+}
+
+fn config_file(app: &AppHandle) -> Result<PathBuf, String> {
+    aws_file(app, "AWS_CONFIG_FILE", "config")
+}
+
+/// Section header as written in each file: the config file prefixes every
+/// profile except `default` with `profile `.
+fn section_header(profile: &str, config: bool) -> String {
+    if config && profile != "default" {
+        format!("[profile {profile}]")
+    } else {
+        format!("[{profile}]")
+    }
+}
+
+fn parse_sections(content: &str, config: bool) -> Sections {
+    let mut sections = Sections::new();
+    let mut current: Option<String> = None;
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let name = name.trim();
+            let name = match (config, name.strip_prefix("profile ")) {
+                (true, Some(profile)) => profile.trim(),
+                _ => name,
+            };
+            sections.entry(name.to_string()).or_default();
+            current = Some(name.to_string());
+        } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    sections
+}
+
+fn read_sections(app: &AppHandle) -> Result<(Sections, Sections), String> {
+    let read = |path: PathBuf| std::fs::read_to_string(path).unwrap_or_default();
+    Ok((
+        parse_sections(&read(credentials_file(app)?), false),
+        parse_sections(&read(config_file(app)?), true),
+    ))
+}
+
+fn list_profiles(app: &AppHandle) -> Result<Vec<CliProfile>, String> {
+    let (credentials, config) = read_sections(app)?;
+    let mut names: Vec<&String> = credentials.keys().chain(config.keys()).collect();
+    names.sort();
+    names.dedup();
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let keys = credentials.get(name).or_else(|| config.get(name));
+            CliProfile {
+                name: name.clone(),
+                region: config.get(name).and_then(|s| s.get("region")).cloned(),
+                has_static_keys: keys.is_some_and(|k| k.contains_key("aws_access_key_id")),
+                has_credential_process: config
+                    .get(name)
+                    .is_some_and(|s| s.contains_key("credential_process")),
+            }
+        })
+        .collect())
+}
+
+/// Static credentials of a CLI profile. Keys may live in either file; the
+/// region comes from the config file.
+pub(crate) fn profile_credentials(
+    app: &AppHandle,
+    profile: &str,
+) -> Result<AwsCredentials, String> {
+    let (credentials, config) = read_sections(app)?;
+    let empty = BTreeMap::new();
+    let cred_section = credentials.get(profile).unwrap_or(&empty);
+    let config_section = config.get(profile).unwrap_or(&empty);
+    let get = |key: &str| {
+        cred_section
+            .get(key)
+            .or_else(|| config_section.get(key))
+            .filter(|v| !v.is_empty())
+            .cloned()
+    };
+
+    let (Some(access_key_id), Some(secret_access_key)) =
+        (get("aws_access_key_id"), get("aws_secret_access_key"))
+    else {
+        return Err(format!("Profile '{profile}' has no static access keys"));
+    };
+    Ok(AwsCredentials {
+        access_key_id,
+        secret_access_key: secret_access_key.into(),
+        region: get("region").unwrap_or_else(|| FALLBACK_REGION.into()),
+        session_token: get("aws_session_token").map(Into::into),
+        expires_at: None,
+        partition: None,
+        endpoint_url: get("endpoint_url"),
+    })
+}
+
+/// A profile's `credential_process` command line and region.
+pub(crate) fn credential_process(
+    app: &AppHandle,
+    profile: &str,
+) -> Result<(String, String), String> {
+    let (_, config) = read_sections(app)?;
+    let section = config.get(profile);
+    let command = section
+        .and_then(|s| s.get("credential_process"))
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| format!("Profile '{profile}' has no credential_process"))?;
+    let region = section
+        .and_then(|s| s.get("region"))
+        .filter(|r| !r.is_empty())
+        .cloned()
+        .unwrap_or_else(|| FALLBACK_REGION.into());
+    Ok((command.clone(), region))
+}
+
+// ---------------------------------------------------------------------------
+// Write-back
+// ---------------------------------------------------------------------------
+
+/// Sets (or, for `None`, removes) keys inside one section of an INI file,
+/// leaving every other line untouched. Appends the section if it is missing.
+fn upsert_section(content: &str, header: &str, values: &[(&str, Option<&str>)]) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut in_section = false;
+    let mut found = false;
+    // Keys go after the section's last non-blank line.
+    let flush = |out: &mut Vec<String>| {
+        let mut at = out.len();
+        while at > 0 && out[at - 1].trim().is_empty() {
+            at -= 1;
+        }
+        for (key, value) in values.iter().rev() {
+            if let Some(value) = value {
+                out.insert(at, format!("{key} = {value}"));
+            }
+        }
+    };
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            if in_section {
+                flush(&mut out);
+            }
+            in_section = trimmed == header;
+            found |= in_section;
+        } else if in_section {
+            let key = trimmed
+                .split_once('=')
+                .map(|(k, _)| k.trim().to_lowercase());
+            if key.is_some_and(|k| values.iter().any(|(name, _)| *name == k)) {
+                continue;
+            }
+        }
+        out.push(line.to_string());
+    }
+    if in_section {
+        flush(&mut out);
+    }
+    if !found {
+        if out.last().is_some_and(|l| !l.trim().is_empty()) {
+            out.push(String::new());
+        }
+        out.push(header.to_string());
+        flush(&mut out);
+    }
+    out.join("\n") + "\n"
+}
+
+fn update_file(path: PathBuf, header: &str, values: &[(&str, Option<&str>)]) -> Result<(), String> {
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&path, upsert_section(&content, header, values)).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Sync
+// ---------------------------------------------------------------------------
+
+/// Pulls the profile into the app. Returns whether the credentials changed.
+fn sync_profile(app: &AppHandle, profile: &str) -> Result<bool, String> {
+    let creds = profile_credentials(app, profile)?;
+    let unchanged = read_credentials(app).is_some_and(|current| current.same_keys(&creds));
+    if unchanged {
+        return Ok(false);
+    }
+    apply_credentials(app, &creds)?;
+    Ok(true)
+}
+
+fn modified(path: Result<PathBuf, String>) -> Option<SystemTime> {
+    std::fs::metadata(path.ok()?).ok()?.modified().ok()
+}
+
+/// Polls the CLI files and re-syncs whenever they (or the followed profile)
+/// change while sync is enabled.
+pub fn spawn_profile_sync(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_seen = None;
+        loop {
+            let cfg = settings::load(&app).profile_sync;
+            if cfg.enabled {
+                let stamp = (
+                    cfg.profile.clone(),
+                    modified(credentials_file(&app)),
+                    modified(config_file(&app)),
+                );
+                if last_seen.as_ref() != Some(&stamp) {
+                    if let Err(err) = sync_profile(&app, &cfg.profile) {
+                        eprintln!("profile sync failed: {err}");
+                    }
+                    last_seen = Some(stamp);
+                }
+            } else {
+                last_seen = None;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Watch
+// ---------------------------------------------------------------------------
+
+/// Imported profiles whose keys in the CLI files differ from the stored
+/// ones. Profiles gone from the files are left alone.
+fn changed_profiles(
+    imported: &BTreeSet<String>,
+    on_disk: impl Fn(&str) -> Option<AwsCredentials>,
+    stored: impl Fn(&str) -> Option<AwsCredentials>,
+) -> Vec<String> {
+    imported
+        .iter()
+        .filter(|name| match (on_disk(name), stored(name)) {
+            (Some(disk), Some(stored)) => !disk.same_keys(&stored),
+            _ => false,
+        })
+        .cloned()
+        .collect()
+}
+
+fn changed(app: &AppHandle) -> Vec<String> {
+    changed_profiles(
+        &settings::load(app).credential_profiles.imported,
+        |name| profile_credentials(app, name).ok(),
+        |name| profiles::read_profile(name).ok(),
+    )
+}
+
+/// Watches the directories holding the CLI files (editors replace files
+/// rather than write them in place) until the watcher stops.
+fn watch(app: &AppHandle) -> Result<(), String> {
+    let files = [credentials_file(app)?, config_file(app)?];
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+    let dirs: BTreeSet<&Path> = files
+        .iter()
+        .filter_map(|f| f.parent())
+        .filter(|dir| dir.is_dir())
+        .collect();
+    if dirs.is_empty() {
+        // Nothing to watch until the CLI creates `~/.aws`.
+        return Ok(());
+    }
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("{}: {e}", dir.display()))?;
+    }
+
+    let mut reported = Vec::new();
+    let touches = |event: notify::Result<notify::Event>| {
+        event.is_ok_and(|event| event.paths.iter().any(|path| files.contains(path)))
+    };
+    while let Ok(event) = rx.recv() {
+        if !touches(event) {
+            continue;
+        }
+        while rx.recv_timeout(SETTLE).is_ok() {}
+        let profiles = changed(app);
+        if !profiles.is_empty() && profiles != reported {
+            let _ = app.emit(
+                CHANGED_EVENT,
+                CliProfilesChanged {
+                    profiles: profiles.clone(),
+                },
+            );
+        }
+        reported = profiles;
+    }
+    Err("file watcher stopped".into())
+}
+
+/// Watches the CLI files for the lifetime of the app, raising
+/// `aws-cli-profiles-changed` when an imported profile's keys change there.
+pub fn spawn_file_watch(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        if let Err(err) = watch(&app) {
+            eprintln!("AWS CLI file watch: {err}");
+        }
+        std::thread::sleep(WATCH_RETRY);
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn list_aws_cli_profiles(app: AppHandle) -> CommandResult<Vec<CliProfile>> {
+    list_profiles(&app).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn get_profile_sync_settings(app: AppHandle) -> ProfileSyncSettings {
+    settings::load(&app).profile_sync
+}
+
+/// Saves sync settings; when enabled, pulls the profile immediately.
+#[tauri::command]
+pub async fn save_profile_sync_settings(
+    app: AppHandle,
+    config: ProfileSyncSettings,
+) -> CommandResult<()> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut all = settings::load(&app);
+        all.profile_sync = config.clone();
+        settings::save(&app, &all)?;
+        if config.enabled {
+            sync_profile(&app, &config.profile)?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Imported profiles whose keys in the CLI files have changed since, e.g.
+/// after a rotation made outside the app.
+#[tauri::command]
+pub fn list_changed_cli_profiles(app: AppHandle) -> Vec<String> {
+    changed(&app)
+}
+
+/// Copies the selected CLI profiles into the keychain as app credential
+/// profiles of the same name; importing one again picks up its current keys.
+/// Profiles without static keys are reported, not imported; one failure does
+/// not stop the rest.
+#[tauri::command]
+pub fn import_aws_cli_profiles(
+    app: AppHandle,
+    profiles: Vec<String>,
+) -> CommandResult<Vec<ProfileImport>> {
+    app_lock::ensure_unlocked(&app)?;
+    let (_, config) = read_sections(&app)?;
+    let imports: Vec<ProfileImport> = profiles
+        .into_iter()
+        .map(|name| {
+            let result = profile_credentials(&app, &name)
+                .map_err(AppError::from)
+                .and_then(|creds| profiles::store(&app, &name, &creds, None));
+            ProfileImport {
+                region: config.get(&name).and_then(|s| s.get("region")).cloned(),
+                imported: result.is_ok(),
+                error: result.err().map(|e| e.to_string()),
+                name,
+            }
+        })
+        .collect();
+    let mut all = settings::load(&app);
+    all.credential_profiles.imported.extend(
+        imports
+            .iter()
+            .filter(|import| import.imported)
+            .map(|import| import.name.clone()),
+    );
+    settings::save(&app, &all)?;
+    Ok(imports)
+}
+
+/// Writes the app's current credentials into a CLI profile. This is the only
+/// path that puts secrets into the CLI files.
+#[tauri::command]
+pub fn write_credentials_to_cli_profile(app: AppHandle, profile: String) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    let creds = read_credentials(&app)
+        .ok_or_else(|| AppError::Credentials("No credentials saved".into()))?;
+    if profile.trim().is_empty() {
+        return Err(AppError::InvalidInput("Profile name is required".into()));
+    }
+    update_file(
+        credentials_file(&app)?,
+        &section_header(&profile, false),
+        &[
+            ("aws_access_key_id", Some(&creds.access_key_id)),
+            ("aws_secret_access_key", Some(creds.secret_access_key())),
+            ("aws_session_token", creds.session_token()),
+        ],
+    )?;
+    update_file(
+        config_file(&app)?,
+        &section_header(&profile, true),
+        &[("region", Some(&creds.region))],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_sections_drop_the_profile_prefix() {
+        let config = parse_sections(
+            "[default]\nregion = us-east-1\n\n[profile work]\n# comment\nRegion=eu-west-1\n",
+            true,
+        );
+        assert_eq!(config.keys().collect::<Vec<_>>(), ["default", "work"]);
+        assert_eq!(config["work"]["region"], "eu-west-1");
+
+        let credentials = parse_sections("[profile work]\naws_access_key_id = AKID\n", false);
+        assert!(credentials.contains_key("profile work"));
+    }
+
+    #[test]
+    fn only_imported_profiles_with_new_keys_change() {
+        let keys = |id: &str| AwsCredentials {
+            access_key_id: id.into(),
+            secret_access_key: "secret".to_string().into(),
+            region: "us-east-1".into(),
+            session_token: None,
+            expires_at: None,
+            partition: None,
+            endpoint_url: None,
+        };
+        let imported: BTreeSet<String> = ["gone", "rotated", "same"].map(String::from).into();
+        let on_disk = |name: &str| match name {
+            "rotated" => Some(keys("AKIANEW")),
+            "same" | "not-imported" => Some(keys("AKIAOLD")),
+            _ => None,
+        };
+        let stored = |_: &str| Some(keys("AKIAOLD"));
+        assert_eq!(changed_profiles(&imported, on_disk, stored), ["rotated"]);
+    }
+
+    #[test]
+    fn upsert_replaces_keys_in_place() {
+        let content = "[a]\nx = 1\n\n[b]\ny = 2\n";
+        let updated = upsert_section(content, "[a]", &[("x", Some("3")), ("z", None)]);
+        assert_eq!(updated, "[a]\nx = 3\n\n[b]\ny = 2\n");
+        let appended = upsert_section(content, "[c]", &[("k", Some("v"))]);
+        assert!(appended.ends_with("\n\n[c]\nk = v\n"), "{appended}");
+    }
+}
@@ -0,0 +1,938 @@
+//! Minimal HTTP client for the FastAPI sidecar, plus the subset of its
+//! response models the Rust shell needs to read.
+
+use std::io::Read;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock, RwLock, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use secrecy::zeroize::Zeroizing;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::request_queue::{self, RequestPriority};
+use crate::{app_lock, tasks, AwsCredentials};
+
+/// Where a separately run dev server listens. The bundled sidecar listens on
+/// a Unix socket instead, or on Windows on a free port picked at each start.
+pub const DEFAULT_PORT: u16 = 8000;
+pub(crate) const API_PREFIX: &str = "/api/v1";
+
+/// Where the sidecar listens.
+#[derive(Clone, Debug)]
+pub enum Endpoint {
+    /// A loopback TCP port.
+    Tcp(u16),
+    /// A Unix domain socket in a directory private to the user.
+    #[cfg(unix)]
+    Socket(PathBuf),
+}
+
+static ENDPOINT: RwLock<Endpoint> = RwLock::new(Endpoint::Tcp(DEFAULT_PORT));
+
+pub(crate) fn endpoint() -> Endpoint {
+    ENDPOINT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Where the sidecar's API answers, for display.
+pub fn location() -> String {
+    if let Some(remote) = crate::remote_backend::active() {
+        return format!("{}{API_PREFIX}", remote.base_url());
+    }
+    match endpoint() {
+        Endpoint::Tcp(port) => format!("http://127.0.0.1:{port}{API_PREFIX}"),
+        #[cfg(unix)]
+        Endpoint::Socket(path) => format!("unix:{}", path.display()),
+    }
+}
+
+/// A loopback port nothing listens on right now, for the next sidecar.
+#[cfg(not(unix))]
+pub(crate) fn free_port() -> std::io::Result<u16> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+    Ok(listener.local_addr()?.port())
+}
+
+/// Points every later request at a sidecar listening on `endpoint`, removing
+/// the previous sidecar's socket.
+#[cfg_attr(dev, allow(dead_code))]
+pub(crate) fn set_endpoint(endpoint: Endpoint) {
+    let previous = std::mem::replace(
+        &mut *ENDPOINT.write().unwrap_or_else(|e| e.into_inner()),
+        endpoint,
+    );
+    #[cfg(unix)]
+    if let Endpoint::Socket(path) = previous {
+        crate::sidecar_socket::remove(&path);
+    }
+    #[cfg(not(unix))]
+    drop(previous);
+}
+
+/// Forgets the stopped sidecar's endpoint, removing its socket.
+pub(crate) fn release_endpoint() {
+    set_endpoint(Endpoint::Tcp(DEFAULT_PORT));
+}
+
+/// Bearer token the running sidecar requires on every request. Unset for a
+/// dev server run by hand, which requires none.
+static API_TOKEN: RwLock<Option<Zeroizing<String>>> = RwLock::new(None);
+
+/// Remembers the token the next sidecar was started with.
+#[cfg_attr(dev, allow(dead_code))]
+pub(crate) fn set_api_token(token: Zeroizing<String>) {
+    *API_TOKEN.write().unwrap_or_else(|e| e.into_inner()) = Some(token);
+}
+
+fn authorization() -> Option<Zeroizing<String>> {
+    let token = API_TOKEN.read().unwrap_or_else(|e| e.into_inner());
+    token
+        .as_ref()
+        .map(|token| Zeroizing::new(format!("Bearer {}", token.as_str())))
+}
+
+/// Sends one request to wherever the sidecar listens, with its token, or to
+/// the remote backend when one is configured.
+fn send_http(
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<(u16, String), AppError> {
+    if let Some(remote) = crate::remote_backend::active() {
+        return remote.send(method, path, body, timeout);
+    }
+    let authorization = authorization();
+    send_to(
+        &endpoint(),
+        authorization.as_ref().map(|value| value.as_str()),
+        method,
+        path,
+        body,
+        timeout,
+    )
+}
+
+/// Sends one request to a sidecar listening on `endpoint`.
+pub(crate) fn send_to(
+    endpoint: &Endpoint,
+    authorization: Option<&str>,
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<(u16, String), AppError> {
+    let (status, body, _) = exchange(endpoint, authorization, None, method, path, body, timeout)?;
+    Ok((status, body))
+}
+
+/// [`send_to`], with `If-None-Match` when an `etag` is given, also returning
+/// the reply's `ETag`.
+fn exchange(
+    endpoint: &Endpoint,
+    authorization: Option<&str>,
+    etag: Option<&str>,
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<(u16, String, Option<String>), AppError> {
+    let headers: Vec<(&str, &str)> = authorization
+        .map(|value| ("Authorization", value))
+        .into_iter()
+        .chain(etag.map(|tag| ("If-None-Match", tag)))
+        .collect();
+    let port = match endpoint {
+        Endpoint::Tcp(port) => *port,
+        #[cfg(unix)]
+        Endpoint::Socket(socket) => {
+            let target = format!("{API_PREFIX}{path}");
+            return crate::sidecar_socket::send_tagged(
+                socket, method, &target, &headers, body, timeout,
+            );
+        }
+    };
+    let mut request = ureq::request(
+        method,
+        &format!("http://127.0.0.1:{port}{API_PREFIX}{path}"),
+    )
+    .set("Content-Type", "application/json");
+    for (name, value) in &headers {
+        request = request.set(name, value);
+    }
+    if let Some(timeout) = timeout {
+        request = request.timeout(timeout);
+    }
+    call_tagged(request, body)
+}
+
+/// Sends a prepared request and returns its status and body, including
+/// non-2xx responses.
+pub(crate) fn call(request: ureq::Request, body: Option<&str>) -> Result<(u16, String), AppError> {
+    let (status, body, _) = call_tagged(request, body)?;
+    Ok((status, body))
+}
+
+/// [`call`], also returning the reply's `ETag`.
+pub(crate) fn call_tagged(
+    request: ureq::Request,
+    body: Option<&str>,
+) -> Result<(u16, String, Option<String>), AppError> {
+    let result = match body {
+        Some(body) => request.send_string(body),
+        None => request.call(),
+    };
+    let response = match result {
+        Ok(response) => response,
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(err) => {
+            return Err(AppError::Sidecar(format!("Backend unreachable: {err}")));
+        }
+    };
+    let status = response.status();
+    let etag = response.header("ETag").map(str::to_owned);
+    let body = response.into_string()?;
+    Ok((status, body, etag))
+}
+
+/// A reply body read as it arrives.
+pub(crate) type ReplyStream = Box<dyn Read + Send>;
+
+/// Sends a prepared request and returns its status and a reader of its body,
+/// including non-2xx responses.
+pub(crate) fn open_request(request: ureq::Request) -> Result<(u16, ReplyStream), AppError> {
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(err) => {
+            return Err(AppError::Sidecar(format!("Backend unreachable: {err}")));
+        }
+    };
+    Ok((response.status(), Box::new(response.into_reader())))
+}
+
+/// Opens a GET whose reply is streamed, as server-sent events or chunks, on
+/// wherever the request would go. Streams do not hold back a sidecar
+/// restart; a restart ends them.
+pub(crate) fn open_stream(path: &str) -> Result<(u16, ReplyStream), AppError> {
+    open_stream_with(path, &[])
+}
+
+/// [`open_stream`] with extra request headers, e.g. a `Range`. Installed
+/// transports ignore them.
+pub(crate) fn open_stream_with(
+    path: &str,
+    extra: &[(&str, &str)],
+) -> Result<(u16, ReplyStream), AppError> {
+    let installed = TRANSPORT.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(transport) = installed {
+        let (status, body) = transport.send("GET", path, None)?;
+        return Ok((status, Box::new(std::io::Cursor::new(body))));
+    }
+    if let Some(remote) = crate::remote_backend::active() {
+        return remote.open(path, extra);
+    }
+    crate::sidecar_idle::wake();
+    let (endpoint, authorization) = match crate::sidecar_workers::stream_target(path) {
+        Some((endpoint, authorization)) => (endpoint, Some(authorization)),
+        None => (endpoint(), authorization()),
+    };
+    let authorization = authorization.as_ref().map(|value| value.as_str());
+    match &endpoint {
+        Endpoint::Tcp(port) => {
+            let mut request = ureq::get(&format!("http://127.0.0.1:{port}{API_PREFIX}{path}"));
+            if let Some(value) = authorization {
+                request = request.set("Authorization", value);
+            }
+            for (name, value) in extra {
+                request = request.set(name, value);
+            }
+            open_request(request)
+        }
+        #[cfg(unix)]
+        Endpoint::Socket(socket) => {
+            let headers: Vec<(&str, &str)> = authorization
+                .map(|value| ("Authorization", value))
+                .into_iter()
+                .chain(extra.iter().copied())
+                .collect();
+            let target = format!("{API_PREFIX}{path}");
+            crate::sidecar_socket::open(socket, "GET", &target, &headers)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CredentialsBody<'a> {
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+    session_token: Option<&'a str>,
+    region: &'a str,
+    endpoint_url: Option<&'a str>,
+}
+
+/// Hands the running sidecar new keys in place of a restart. Goes straight
+/// to the sidecar rather than through the transport: only a real one started
+/// by this app has the endpoint.
+pub(crate) fn push_credentials(creds: &AwsCredentials) -> Result<(), AppError> {
+    let Some(authorization) = authorization() else {
+        return Err(AppError::Sidecar(
+            "The backend was not started by this app".into(),
+        ));
+    };
+    push_credentials_to(&endpoint(), &authorization, creds)
+}
+
+/// [`push_credentials`] for the sidecar on `endpoint`.
+pub(crate) fn push_credentials_to(
+    endpoint: &Endpoint,
+    authorization: &str,
+    creds: &AwsCredentials,
+) -> Result<(), AppError> {
+    let body = Zeroizing::new(
+        serde_json::to_string(&CredentialsBody {
+            access_key_id: &creds.access_key_id,
+            secret_access_key: creds.secret_access_key(),
+            session_token: creds.session_token(),
+            region: &creds.region,
+            endpoint_url: creds.endpoint_url(),
+        })
+        .map_err(|e| AppError::Internal(e.to_string()))?,
+    );
+    let (status, reply) = send_to(
+        endpoint,
+        Some(authorization),
+        "POST",
+        "/internal/credentials",
+        Some(&body),
+        Some(Duration::from_secs(5)),
+    )?;
+    if !(200..300).contains(&status) {
+        return Err(sidecar_error(status, &reply));
+    }
+    Ok(())
+}
+
+/// Statuses the sidecar answers `path` with, without its token and with it,
+/// for the self-test. `None` for a server started without a token.
+pub(crate) fn probe_token(path: &str) -> Result<Option<(u16, u16)>, AppError> {
+    let Some(authorization) = authorization() else {
+        return Ok(None);
+    };
+    let endpoint = endpoint();
+    let timeout = Some(crate::health_check::current().request_timeout());
+    let (without, _) = send_to(&endpoint, None, "GET", path, None, timeout)?;
+    let (with, _) = send_to(
+        &endpoint,
+        Some(authorization.as_str()),
+        "GET",
+        path,
+        None,
+        timeout,
+    )?;
+    Ok(Some((without, with)))
+}
+
+// ---------------------------------------------------------------------------
+// Transport
+// ---------------------------------------------------------------------------
+
+/// How requests reach the sidecar. The app talks HTTP to the bundled server;
+/// tests and `mock` builds install a fixture-backed transport instead.
+pub trait Sidecar: Send + Sync {
+    /// Sends one request and returns its status and body, including non-2xx
+    /// responses. Errs only when the sidecar could not be reached.
+    fn send(&self, method: &str, path: &str, body: Option<&str>)
+        -> Result<(u16, String), AppError>;
+
+    /// Sends a health check GET, giving up after `timeout` where the
+    /// transport can.
+    fn probe(&self, path: &str, timeout: Duration) -> Result<(u16, String), AppError> {
+        let _ = timeout;
+        self.send("GET", path, None)
+    }
+}
+
+struct HttpSidecar;
+
+/// Held shared for the length of every request the app sends, so the
+/// sidecar can be replaced at a moment with none in flight. Health checks
+/// do not take it.
+static REQUESTS: RwLock<()> = RwLock::new(());
+
+/// Holds back new requests until the guard is dropped, if none is in flight
+/// now.
+#[cfg_attr(dev, allow(dead_code))]
+pub(crate) fn try_hold_requests() -> Option<RwLockWriteGuard<'static, ()>> {
+    REQUESTS.try_write().ok()
+}
+
+impl Sidecar for HttpSidecar {
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+    ) -> Result<(u16, String), AppError> {
+        crate::resilience::call(method, path, |timeout| {
+            if let Some(remote) = crate::remote_backend::active() {
+                return remote.send(method, path, body, Some(timeout));
+            }
+            crate::sidecar_idle::wake();
+            let _in_flight = REQUESTS.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(reply) = crate::sidecar_workers::dispatch(method, path, body, Some(timeout))
+            {
+                return reply;
+            }
+            send_http(method, path, body, Some(timeout))
+        })
+    }
+
+    fn probe(&self, path: &str, timeout: Duration) -> Result<(u16, String), AppError> {
+        send_http("GET", path, None, Some(timeout))
+    }
+}
+
+static TRANSPORT: RwLock<Option<Arc<dyn Sidecar>>> = RwLock::new(None);
+
+fn transport() -> Arc<dyn Sidecar> {
+    TRANSPORT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| Arc::new(HttpSidecar))
+}
+
+/// Routes every sidecar call through `sidecar`; `None` restores HTTP.
+#[cfg(any(test, feature = "mock"))]
+pub fn set_transport(sidecar: Option<Arc<dyn Sidecar>>) {
+    *TRANSPORT.write().unwrap_or_else(|e| e.into_inner()) = sidecar;
+}
+
+/// Maps a non-2xx sidecar reply to an error kind, using FastAPI's `detail`
+/// as the message.
+pub(crate) fn sidecar_error(status: u16, body: &str) -> AppError {
+    let detail = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| match v.get("detail")? {
+            serde_json::Value::String(detail) => Some(detail.clone()),
+            other => Some(other.to_string()),
+        })
+        .unwrap_or_else(|| format!("Backend returned HTTP {status}"));
+    match status {
+        400 | 422 => AppError::InvalidInput(detail),
+        404 => AppError::NotFound(detail),
+        409 => AppError::Conflict(detail),
+        429 => AppError::Throttled(detail),
+        _ => AppError::Sidecar(detail),
+    }
+}
+
+/// Sends through the transport. A change that went through clears the
+/// response cache, as it may have changed what reads return.
+fn send(method: &str, path: &str, body: Option<&str>) -> Result<(u16, String), AppError> {
+    let reply = transport().send(method, path, body)?;
+    if method != "GET" && (200..300).contains(&reply.0) {
+        crate::response_cache::clear();
+    }
+    Ok(reply)
+}
+
+fn request<T: DeserializeOwned>(
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+) -> Result<T, AppError> {
+    let (status, body) = send(method, path, body)?;
+    if !(200..300).contains(&status) {
+        return Err(sidecar_error(status, &body));
+    }
+    serde_json::from_str(&body).map_err(|e| AppError::Sidecar(e.to_string()))
+}
+
+/// The most recent health check.
+#[derive(Clone, Copy, Debug)]
+pub struct HealthProbe {
+    pub at: DateTime<Utc>,
+    pub latency: Duration,
+    pub ok: bool,
+}
+
+static LAST_HEALTH: Mutex<Option<HealthProbe>> = Mutex::new(None);
+/// Async client for [`healthy_async`] over TCP.
+static HTTP: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Whether the sidecar answers its health check.
+pub fn healthy() -> bool {
+    let config = crate::health_check::current();
+    let started = Instant::now();
+    let ok = transport()
+        .probe(&config.path, config.request_timeout())
+        .is_ok_and(|(status, _)| (200..300).contains(&status));
+    record_health(started, ok)
+}
+
+/// [`healthy`] for async callers, so waiting on the sidecar holds no thread.
+/// An installed transport or a remote backend is still called on a blocking
+/// thread.
+pub(crate) async fn healthy_async() -> bool {
+    let installed = TRANSPORT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some();
+    if installed || crate::remote_backend::active().is_some() {
+        return tauri::async_runtime::spawn_blocking(healthy)
+            .await
+            .unwrap_or(false);
+    }
+    let config = crate::health_check::current();
+    let target = format!("{API_PREFIX}{}", config.path);
+    let authorization = authorization();
+    let started = Instant::now();
+    let ok = match endpoint() {
+        Endpoint::Tcp(port) => {
+            let mut request = HTTP
+                .get_or_init(|| {
+                    // Loopback never goes through a proxy from the environment.
+                    reqwest::Client::builder()
+                        .no_proxy()
+                        .build()
+                        .unwrap_or_default()
+                })
+                .get(format!("http://127.0.0.1:{port}{target}"))
+                .timeout(config.request_timeout());
+            if let Some(value) = &authorization {
+                request = request.header("Authorization", value.as_str());
+            }
+            request
+                .send()
+                .await
+                .is_ok_and(|response| response.status().is_success())
+        }
+        #[cfg(unix)]
+        Endpoint::Socket(socket) => {
+            let headers: Vec<(&str, &str)> = authorization
+                .iter()
+                .map(|value| ("Authorization", value.as_str()))
+                .collect();
+            crate::sidecar_socket::send_async(
+                &socket,
+                "GET",
+                &target,
+                &headers,
+                config.request_timeout(),
+            )
+            .await
+            .is_ok_and(|(status, _)| (200..300).contains(&status))
+        }
+    };
+    record_health(started, ok)
+}
+
+fn record_health(started: Instant, ok: bool) -> bool {
+    *LAST_HEALTH.lock().unwrap_or_else(|e| e.into_inner()) = Some(HealthProbe {
+        at: Utc::now(),
+        latency: started.elapsed(),
+        ok,
+    });
+    ok
+}
+
+/// The last check made by [`healthy`] or [`healthy_async`], if any.
+pub fn last_health() -> Option<HealthProbe> {
+    *LAST_HEALTH.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Issues a GET against the sidecar API and decodes the JSON body.
+pub fn get_json<T: DeserializeOwned>(path: &str) -> Result<T, AppError> {
+    request("GET", path, None)
+}
+
+/// Issues a POST with a JSON body against the sidecar API and decodes the reply.
+pub fn post_json<B: Serialize, T: DeserializeOwned>(path: &str, body: &B) -> Result<T, AppError> {
+    let body = serde_json::to_string(body).map_err(|e| AppError::Internal(e.to_string()))?;
+    request("POST", path, Some(&body))
+}
+
+/// [`post_json`] with a body serialized by the caller, e.g. into a buffer
+/// that is wiped because it carries keys.
+pub(crate) fn post_raw<T: DeserializeOwned>(path: &str, body: &str) -> Result<T, AppError> {
+    request("POST", path, Some(body))
+}
+
+/// Issues a PUT with a JSON body against the sidecar API and decodes the reply.
+pub fn put_json<B: Serialize, T: DeserializeOwned>(path: &str, body: &B) -> Result<T, AppError> {
+    let body = serde_json::to_string(body).map_err(|e| AppError::Internal(e.to_string()))?;
+    request("PUT", path, Some(&body))
+}
+
+/// Sends a raw request to the sidecar and returns its status and body,
+/// including non-2xx responses.
+pub fn forward(method: &str, path: &str, body: Option<&str>) -> Result<(u16, String), AppError> {
+    send(method, path, body)
+}
+
+/// Sends a GET under the same policy as [`forward`], with `If-None-Match`
+/// when an `etag` is given, and returns its status, body and `ETag`. A 304
+/// has no body. Installed transports answer without tags.
+pub(crate) fn get_tagged(
+    path: &str,
+    etag: Option<&str>,
+) -> Result<(u16, String, Option<String>), AppError> {
+    let installed = TRANSPORT.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(transport) = installed {
+        let (status, body) = transport.send("GET", path, None)?;
+        return Ok((status, body, None));
+    }
+    let mut tag = None;
+    let (status, body) = crate::resilience::call("GET", path, |timeout| {
+        tag = None;
+        let (status, body, etag) = match crate::remote_backend::active() {
+            Some(remote) => remote.get_tagged(path, etag, timeout)?,
+            None => {
+                crate::sidecar_idle::wake();
+                let _in_flight = REQUESTS.read().unwrap_or_else(|e| e.into_inner());
+                if let Some(reply) =
+                    crate::sidecar_workers::dispatch("GET", path, None, Some(timeout))
+                {
+                    return reply;
+                }
+                let authorization = authorization();
+                exchange(
+                    &endpoint(),
+                    authorization.as_ref().map(|value| value.as_str()),
+                    etag,
+                    "GET",
+                    path,
+                    None,
+                    Some(timeout),
+                )?
+            }
+        };
+        tag = etag;
+        Ok((status, body))
+    })?;
+    Ok((status, body, tag))
+}
+
+/// Where the sidecar's API answers, for display.
+#[tauri::command]
+pub fn get_backend_url() -> String {
+    location()
+}
+
+/// A sidecar reply as the webview sees it.
+#[derive(Serialize, Debug)]
+pub struct BackendResponse {
+    pub status: u16,
+    pub body: String,
+    /// Set for non-2xx replies: the error the shell's own calls would raise,
+    /// with FastAPI's `detail` as the message.
+    pub error: Option<AppError>,
+}
+
+/// Sidecar routes only the shell may call, such as `/internal/credentials`;
+/// the webview reaching them would bypass credential checks and the app lock.
+const SHELL_ONLY: &[&str] = &["internal"];
+
+/// Decodes `%XX` escapes, leaving malformed ones as they are.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The first segment of `path` as the sidecar would route it: decoded,
+/// however often it was encoded, with empty, `.` and `..` segments resolved.
+fn first_segment(path: &str) -> String {
+    let mut path = path
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    loop {
+        let decoded = percent_decode(&path);
+        if decoded == path {
+            break;
+        }
+        path = decoded;
+    }
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments
+        .first()
+        .map(|s| s.to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// Refuses a path from the webview that cannot go into a request line as is,
+/// or that leads to a route only the shell may call.
+pub(crate) fn check_path(path: &str) -> Result<(), AppError> {
+    if !path.starts_with('/') || path.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid backend path {path:?}"
+        )));
+    }
+    if SHELL_ONLY.contains(&first_segment(path).as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "Backend path {path:?} is not open to the webview"
+        )));
+    }
+    Ok(())
+}
+
+/// Gives a scan request a `scan_id` if it has none, so the sidecar can be
+/// told to stop it. Returns the body to send and the scan's id.
+fn tag_scan(method: &str, path: &str, body: Option<String>) -> (Option<String>, Option<String>) {
+    if method != "POST" || path != "/optimizer/scan" {
+        return (body, None);
+    }
+    let parsed = body
+        .as_deref()
+        .and_then(|body| serde_json::from_str::<serde_json::Value>(body).ok());
+    let Some(serde_json::Value::Object(mut request)) = parsed else {
+        return (body, None);
+    };
+    let scan_id = match request.get("scan_id").and_then(|id| id.as_str()) {
+        Some(id) => id.to_owned(),
+        None => {
+            let id = uuid::Uuid::new_v4().to_string();
+            request.insert("scan_id".into(), id.clone().into());
+            id
+        }
+    };
+    let body = serde_json::Value::Object(request).to_string();
+    (Some(body), Some(scan_id))
+}
+
+/// Forwards one webview request to the sidecar with the app's token, so the
+/// webview never talks to it itself and needs no network access, under the
+/// same retry and timeout policy as the shell's own calls. GETs are answered
+/// from the response cache where it applies; the rest wait their turn in the
+/// request queue by `priority`, interactive by default. With a `request_id`
+/// the request can be cancelled with [`cancel_request`]; a cancelled scan is
+/// also stopped on the sidecar.
+#[tauri::command]
+pub async fn backend_request(
+    app: AppHandle,
+    method: String,
+    path: String,
+    body: Option<String>,
+    request_id: Option<String>,
+    priority: Option<RequestPriority>,
+) -> CommandResult<BackendResponse> {
+    app_lock::ensure_unlocked(&app)?;
+    if !matches!(method.as_str(), "GET" | "POST" | "PUT" | "PATCH" | "DELETE") {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported method {method}"
+        )));
+    }
+    check_path(&path)?;
+    if method == "GET" {
+        if let Some(body) = crate::response_cache::fresh(&path) {
+            return Ok(BackendResponse {
+                status: 200,
+                body,
+                error: None,
+            });
+        }
+    }
+    let request_id = request_id.filter(|id| !id.is_empty());
+    let (body, scan_id) = match request_id {
+        Some(_) => tag_scan(&method, &path, body),
+        None => (body, None),
+    };
+    let (cancel, _guard) = tasks::register(&app, request_id)?;
+    let outcome = cancel
+        .run(async move {
+            let slot = request_queue::acquire(priority.unwrap_or_default()).await;
+            // The slot goes with the request, which runs on if cancelled.
+            tauri::async_runtime::spawn_blocking(move || {
+                let _slot = slot;
+                match method.as_str() {
+                    "GET" => crate::response_cache::get(&path, |etag| get_tagged(&path, etag)),
+                    _ => forward(&method, &path, body.as_deref()),
+                }
+            })
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?
+        })
+        .await;
+    if let (Err(AppError::Cancelled), Some(scan_id)) = (&outcome, scan_id) {
+        // The dropped request would otherwise scan on to the end.
+        tauri::async_runtime::spawn_blocking(move || {
+            let path = format!("/optimizer/scan/{scan_id}/cancel");
+            if let Err(err) = forward("POST", &path, None) {
+                eprintln!("could not cancel scan {scan_id}: {err}");
+            }
+        });
+    }
+    let (status, body) = outcome?;
+    let error = (!(200..300).contains(&status)).then(|| sidecar_error(status, &body));
+    Ok(BackendResponse {
+        status,
+        body,
+        error,
+    })
+}
+
+/// Cancels a webview request started with this `request_id`. Returns `false`
+/// if none is in flight.
+#[tauri::command]
+pub fn cancel_request(app: AppHandle, request_id: String) -> bool {
+    tasks::cancel(&app, &request_id)
+}
+
+// ---------------------------------------------------------------------------
+// Response models (mirror server/app/models/contracts.py)
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Recommendation {
+    pub id: String,
+    pub bucket: String,
+    pub key: Option<String>,
+    pub recommendation_type: String,
+    pub risk_level: String,
+    pub reason: String,
+    pub recommended_action: String,
+    pub estimated_monthly_savings: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub status: String,
+    pub recommendation_count: u64,
+    pub estimated_monthly_savings: f64,
+    pub updated_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavingsEstimate {
+    pub recommendation_id: String,
+    pub current_monthly_cost: f64,
+    pub projected_monthly_cost: f64,
+    pub monthly_savings: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RunDetails {
+    pub run_id: String,
+    pub status: String,
+    pub recommendations: Vec<Recommendation>,
+    /// Empty until the run has been scored.
+    #[serde(default)]
+    pub savings_details: Vec<SavingsEstimate>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A call that failed in an otherwise completed scan.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScanError {
+    pub region: String,
+    pub service: String,
+    pub bucket: Option<String>,
+    pub operation: String,
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScanResponse {
+    pub run_id: String,
+    pub recommendations: Vec<Recommendation>,
+    pub estimated_monthly_savings: f64,
+    /// Non-empty when the scan is partial.
+    #[serde(default)]
+    pub errors: Vec<ScanError>,
+}
+
+/// Lists run summaries, most recently updated first.
+pub fn list_runs() -> Result<Vec<RunSummary>, AppError> {
+    get_json("/optimizer/runs")
+}
+
+/// Fetches a run with its recommendations from the sidecar.
+pub fn get_run(run_id: &str) -> Result<RunDetails, AppError> {
+    get_json(&format!("/optimizer/runs/{run_id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_are_tagged_so_they_can_be_stopped() {
+        let (body, scan_id) = tag_scan("POST", "/optimizer/scan", Some("{}".into()));
+        let scan_id = scan_id.unwrap();
+        let body: serde_json::Value = serde_json::from_str(&body.unwrap()).unwrap();
+        assert_eq!(body["scan_id"], scan_id.as_str());
+
+        let given = Some(r#"{"scan_id":"mine"}"#.to_string());
+        assert_eq!(
+            tag_scan("POST", "/optimizer/scan", given).1.as_deref(),
+            Some("mine")
+        );
+        let other = tag_scan("POST", "/optimizer/score", Some("{}".into()));
+        assert_eq!(other, (Some("{}".into()), None));
+    }
+
+    #[test]
+    fn webview_paths_cannot_reach_internal_routes() {
+        assert!(check_path("/optimizer/runs?limit=5").is_ok());
+        assert!(check_path("/optimizer/internal").is_ok());
+        for path in [
+            "/internal/credentials",
+            "//internal/credentials",
+            "/./internal/credentials",
+            "/optimizer/../internal/credentials",
+            "/%69nternal/credentials",
+            "/%2Finternal/credentials",
+            "/%252e/internal/credentials",
+            "/Internal/credentials",
+            "/\\internal/credentials",
+        ] {
+            assert!(check_path(path).is_err(), "{path} was let through");
+        }
+    }
+
+    #[test]
+    fn error_replies_map_to_command_errors() {
+        let err = sidecar_error(404, r#"{"detail":"Run not found"}"#);
+        assert!(matches!(err, AppError::NotFound(ref detail) if detail == "Run not found"));
+        let err = sidecar_error(502, "<html>Bad Gateway</html>");
+        assert!(matches!(err, AppError::Sidecar(ref detail) if detail.contains("HTTP 502")));
+    }
+}
@@ -0,0 +1,206 @@
+//! Extra root certificates, for networks that inspect TLS with a private CA.
+//! The PEM file given in the settings is trusted on top of the usual roots
+//! by the app's own HTTPS clients: the ureq agents, the AWS SDK, the updater
+//! and a remote backend. The sidecar gets its path and adds it to botocore's
+//! bundle.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use aws_smithy_http_client::tls::{self, rustls_provider::CryptoMode, TlsContext, TrustStore};
+use aws_smithy_runtime_api::client::http::SharedHttpClient;
+use rustls::pki_types::CertificateDer;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::{read_credentials, settings, sso};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CaTrustSettings {
+    /// PEM file of CA certificates to trust besides the usual roots.
+    pub ca_bundle_path: Option<String>,
+}
+
+/// The certificates of a PEM file, which must hold at least one.
+fn parse_pem(pem: &[u8], path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = rustls_pemfile::certs(&mut &*pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Cannot parse {}: {e}", path.display()))?;
+    if certs.is_empty() {
+        return Err(format!("{} holds no PEM certificates", path.display()));
+    }
+    Ok(certs)
+}
+
+/// Reads a PEM file of CA certificates, returning its bytes and certificates.
+pub(crate) fn read_pem(path: &Path) -> Result<(Vec<u8>, Vec<CertificateDer<'static>>), String> {
+    let pem = std::fs::read(path).map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+    let certs = parse_pem(&pem, path)?;
+    Ok((pem, certs))
+}
+
+/// The configured bundle, loaded.
+struct Trust {
+    path: PathBuf,
+    pem: Vec<u8>,
+    certs: Vec<CertificateDer<'static>>,
+    tls: Arc<rustls::ClientConfig>,
+    sdk: SharedHttpClient,
+}
+
+static CURRENT: RwLock<Option<Arc<Trust>>> = RwLock::new(None);
+
+fn current() -> Option<Arc<Trust>> {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// A client config trusting the public roots, the configured bundle and
+/// `extra`.
+pub(crate) fn client_config(
+    extra: &[CertificateDer<'static>],
+) -> Result<Arc<rustls::ClientConfig>, String> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let configured = current();
+    let configured = configured.iter().flat_map(|trust| trust.certs.iter());
+    for cert in configured.chain(extra) {
+        roots
+            .add(cert.clone())
+            .map_err(|e| format!("Cannot trust a certificate: {e}"))?;
+    }
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|e| e.to_string())?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// An agent builder trusting the configured bundle. Every outbound ureq
+/// request starts from it.
+pub(crate) fn agent_builder() -> ureq::AgentBuilder {
+    let builder = ureq::AgentBuilder::new();
+    match current() {
+        Some(trust) => builder.tls_config(trust.tls.clone()),
+        None => builder,
+    }
+}
+
+/// [`agent_builder`] with nothing else set.
+pub(crate) fn agent() -> ureq::Agent {
+    agent_builder().build()
+}
+
+/// The AWS SDK's HTTPS client, when a bundle is configured; it also keeps
+/// the OS roots the default client trusts.
+pub(crate) fn sdk_http_client() -> Option<SharedHttpClient> {
+    current().map(|trust| trust.sdk.clone())
+}
+
+/// The configured PEM, for clients that take the bundle whole.
+pub(crate) fn pem() -> Option<Vec<u8>> {
+    current().map(|trust| trust.pem.clone())
+}
+
+/// The bundle's path for the sidecar's environment, which adds it to the
+/// roots botocore trusts.
+pub(crate) fn env_vars() -> Vec<(&'static str, String)> {
+    let path = current()
+        .map(|trust| trust.path.display().to_string())
+        .unwrap_or_default();
+    vec![("EXTRA_CA_BUNDLE", path)]
+}
+
+fn load(path: &Path) -> Result<Trust, String> {
+    let (pem, certs) = read_pem(path)?;
+    let context = TlsContext::builder()
+        .with_trust_store(TrustStore::default().with_pem_certificate(pem.as_slice()))
+        .build()
+        .map_err(|e| format!("Cannot trust {}: {e}", path.display()))?;
+    let sdk = aws_smithy_http_client::Builder::new()
+        .tls_provider(tls::Provider::Rustls(CryptoMode::Ring))
+        .tls_context(context)
+        .build_https();
+    let tls = client_config(&certs)?;
+    Ok(Trust {
+        path: path.to_path_buf(),
+        pem,
+        certs,
+        tls,
+        sdk,
+    })
+}
+
+/// Loads the configured bundle again. Errs, trusting nothing extra, when it
+/// cannot be read.
+pub(crate) fn apply(app: &AppHandle) -> Result<(), String> {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = None;
+    let Some(path) = settings::load(app).ca_trust.ca_bundle_path else {
+        return Ok(());
+    };
+    let trust = load(Path::new(&path))?;
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(trust));
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_ca_trust_settings(app: AppHandle) -> CaTrustSettings {
+    settings::load(&app).ca_trust
+}
+
+/// Saves the bundle path after checking the file, applies it, and restarts
+/// a running sidecar so it trusts the bundle too.
+#[tauri::command]
+pub async fn save_ca_trust_settings(app: AppHandle, config: CaTrustSettings) -> CommandResult<()> {
+    let config = CaTrustSettings {
+        ca_bundle_path: config
+            .ca_bundle_path
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty()),
+    };
+    if let Some(path) = &config.ca_bundle_path {
+        read_pem(Path::new(path)).map_err(AppError::InvalidInput)?;
+    }
+    let mut all = settings::load(&app);
+    all.ca_trust = config;
+    settings::save(&app, &all)?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
+        apply(&app)?;
+        crate::remote_backend::apply(&app)?;
+        let running = app
+            .state::<crate::SidecarState>()
+            .0
+            .lock()
+            .is_ok_and(|child| child.is_some());
+        let creds = read_credentials(&app).filter(|_| sso::access_granted(&app));
+        match creds {
+            Some(creds) if running => crate::restart_sidecar(&app, &creds),
+            _ => Ok(()),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundles_without_certificates_are_refused() {
+        let path = Path::new("corp.pem");
+        let err = parse_pem(b"not a certificate\n", path).unwrap_err();
+        assert!(err.contains("no PEM certificates"), "{err}");
+        let garbled = b"-----BEGIN CERTIFICATE-----\n!!!\n-----END CERTIFICATE-----\n";
+        assert!(parse_pem(garbled, path).is_err());
+    }
+}
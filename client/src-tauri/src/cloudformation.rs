@@ -0,0 +1,105 @@
+//! CloudFormation stack attribution: groups a run's buckets, their current
+//! storage cost, and the estimated savings by owning stack, so forgotten
+//! stacks stand out.
+
+use std::collections::{BTreeMap, HashMap};
+
+use aws_sdk_cloudformation::error::ProvideErrorMetadata;
+use serde::Serialize;
+use tauri::AppHandle;
+use tokio::task::JoinSet;
+
+use crate::aws::{sdk_config, sdk_error, send};
+use crate::error::{AppError, CommandResult};
+use crate::{backend, perf};
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct StackCost {
+    /// `None` groups buckets that no stack owns.
+    pub stack_name: Option<String>,
+    pub buckets: Vec<String>,
+    pub recommendation_count: usize,
+    /// Sum of scored current monthly cost; zero for unscored runs.
+    pub current_monthly_cost: f64,
+    pub estimated_monthly_savings: f64,
+}
+
+/// Returns the stack that owns `bucket`, or `None` if it is not stack-managed.
+async fn owning_stack(
+    client: &aws_sdk_cloudformation::Client,
+    bucket: &str,
+) -> Result<Option<String>, AppError> {
+    let lookup = send(|| {
+        client
+            .describe_stack_resources()
+            .physical_resource_id(bucket)
+            .send()
+    })
+    .await;
+    match lookup {
+        Ok(out) => Ok(out
+            .stack_resources()
+            .first()
+            .and_then(|r| r.stack_name().map(str::to_string))),
+        // CloudFormation answers "Stack for <id> does not exist" as a validation error.
+        Err(err) if err.code() == Some("ValidationError") => Ok(None),
+        Err(err) => Err(sdk_error(err)),
+    }
+}
+
+async fn stack_costs(app: &AppHandle, run_id: String) -> Result<Vec<StackCost>, AppError> {
+    let run = tauri::async_runtime::spawn_blocking(move || backend::get_run(&run_id))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    let client = aws_sdk_cloudformation::Client::new(&sdk_config(app).await?);
+
+    let mut lookups = JoinSet::new();
+    let mut buckets: Vec<String> = run
+        .recommendations
+        .iter()
+        .map(|r| r.bucket.clone())
+        .collect();
+    buckets.sort();
+    buckets.dedup();
+    for bucket in buckets {
+        let client = client.clone();
+        lookups.spawn(perf::propagate(async move {
+            let stack = owning_stack(&client, &bucket).await;
+            (bucket, stack)
+        }));
+    }
+    let mut stack_of: HashMap<String, Option<String>> = HashMap::new();
+    while let Some(joined) = lookups.join_next().await {
+        let (bucket, stack) = joined.map_err(|e| AppError::Internal(e.to_string()))?;
+        stack_of.insert(bucket, stack?);
+    }
+
+    let cost_of: HashMap<&str, f64> = run
+        .savings_details
+        .iter()
+        .map(|s| (s.recommendation_id.as_str(), s.current_monthly_cost))
+        .collect();
+    let mut stacks: BTreeMap<Option<String>, StackCost> = BTreeMap::new();
+    for rec in &run.recommendations {
+        let stack = stack_of.get(&rec.bucket).cloned().flatten();
+        let entry = stacks.entry(stack.clone()).or_insert_with(|| StackCost {
+            stack_name: stack,
+            ..Default::default()
+        });
+        if !entry.buckets.contains(&rec.bucket) {
+            entry.buckets.push(rec.bucket.clone());
+        }
+        entry.recommendation_count += 1;
+        entry.current_monthly_cost += cost_of.get(rec.id.as_str()).copied().unwrap_or(0.0);
+        entry.estimated_monthly_savings += rec.estimated_monthly_savings;
+    }
+
+    let mut result: Vec<StackCost> = stacks.into_values().collect();
+    result.sort_by(|a, b| b.current_monthly_cost.total_cmp(&a.current_monthly_cost));
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_stack_costs(app: AppHandle, run_id: String) -> CommandResult<Vec<StackCost>> {
+    perf::measure(&app, "get_stack_costs", stack_costs(&app, run_id)).await
+}
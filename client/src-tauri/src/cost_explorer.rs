@@ -0,0 +1,1073 @@
+//! Native Cost Explorer queries. The sidecar only analyzes S3, so account
+//! spend comes straight from Cost Explorer.
+//!
+//! A query is split into calendar-month chunks that are fetched concurrently
+//! (pages within a chunk stay sequential, since each page token comes from
+//! the previous page) and merged into one result. Every chunk is cached in
+//! `app_data/cost_explorer_cache.json`: closed months for a day, the current
+//! month for an hour, because Cost Explorer charges per request. Identical
+//! queries that arrive while one is already running share its result.
+//!
+//! Billable requests are counted per calendar month. With a request cap
+//! configured, queries fall back to expired cache entries once usage nears
+//! the cap, and are refused when their uncached chunks would pass it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use aws_config::Region;
+use aws_sdk_costexplorer::types::{
+    DateInterval, GroupDefinition as SdkGroupDefinition, GroupDefinitionType, ResultByTime,
+};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::OnceCell;
+use tokio::task::JoinSet;
+
+use crate::aws::{account_id, sdk_config, sdk_error, send};
+use crate::error::{AppError, CommandResult};
+use crate::partition::Partition;
+use crate::{perf, read_credentials, settings};
+
+const CLOSED_TTL_SECS: i64 = 24 * 3600;
+const OPEN_TTL_SECS: i64 = 3600;
+/// Cost Explorer keeps revising recent days; treat them as open.
+const SETTLE_DAYS: i64 = 3;
+const FORECAST_TTL_SECS: i64 = 6 * 3600;
+/// Expired chunks are kept this long for cache-first answers near the cap.
+const STALE_KEEP_SECS: i64 = 31 * 24 * 3600;
+/// Months of request counts kept in the ledger.
+const LEDGER_MONTHS: usize = 12;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    Daily,
+    Monthly,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupKind {
+    Dimension,
+    Tag,
+    CostCategory,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GroupBy {
+    pub kind: GroupKind,
+    /// e.g. `SERVICE`, `LINKED_ACCOUNT`, or a tag key.
+    pub key: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CostQuery {
+    /// Inclusive, `YYYY-MM-DD`.
+    pub start: NaiveDate,
+    /// Exclusive, `YYYY-MM-DD`.
+    pub end: NaiveDate,
+    pub granularity: Granularity,
+    #[serde(default = "default_metrics")]
+    pub metrics: Vec<String>,
+    /// At most two, as in Cost Explorer.
+    #[serde(default)]
+    pub group_by: Vec<GroupBy>,
+}
+
+fn default_metrics() -> Vec<String> {
+    vec!["UnblendedCost".into()]
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MetricValue {
+    pub amount: f64,
+    pub unit: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GroupCost {
+    pub keys: Vec<String>,
+    pub metrics: BTreeMap<String, MetricValue>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PeriodCost {
+    pub start: String,
+    pub end: String,
+    /// Cost Explorer has not finalized this period yet.
+    pub estimated: bool,
+    pub total: BTreeMap<String, MetricValue>,
+    pub groups: Vec<GroupCost>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct CostQueryResult {
+    pub periods: Vec<PeriodCost>,
+    pub chunks: usize,
+    pub cache_hits: usize,
+    /// Chunks served from expired cache entries because the request budget
+    /// is nearly spent.
+    pub stale_hits: usize,
+    /// Billable Cost Explorer requests made for this query.
+    pub pages_fetched: usize,
+    /// Served by an identical query that was already in flight.
+    pub coalesced: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedChunk {
+    fetched_at: i64,
+    ttl_secs: i64,
+    periods: Vec<PeriodCost>,
+}
+
+type Shared = Arc<OnceCell<Result<CostQueryResult, AppError>>>;
+
+#[derive(Default)]
+pub struct CostCacheState {
+    chunks: OnceLock<ChunkCache>,
+    ledger: OnceLock<Arc<RequestLedger>>,
+    in_flight: Mutex<HashMap<String, Shared>>,
+}
+
+// ---------------------------------------------------------------------------
+// Cache
+// ---------------------------------------------------------------------------
+
+fn cache_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("cost_explorer_cache.json")
+}
+
+/// Keyed by account too, so switching credentials never serves another
+/// account's costs.
+fn cache_key(account: &str, chunk: &CostQuery) -> String {
+    format!(
+        "{account}:{}",
+        serde_json::to_string(chunk).unwrap_or_default()
+    )
+}
+
+/// Cached chunks, persisted as JSON at `path` and loaded on first use.
+pub struct ChunkCache {
+    path: PathBuf,
+    chunks: Mutex<Option<HashMap<String, CachedChunk>>>,
+}
+
+impl ChunkCache {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            chunks: Mutex::new(None),
+        }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut HashMap<String, CachedChunk>) -> T) -> T {
+        let mut guard = self.chunks.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = guard.get_or_insert_with(|| {
+            std::fs::read_to_string(&self.path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default()
+        });
+        f(cache)
+    }
+
+    fn get(&self, key: &str) -> Option<Vec<PeriodCost>> {
+        let now = chrono::Utc::now().timestamp();
+        let hit = self.with(|cache| {
+            cache
+                .get(key)
+                .filter(|entry| now - entry.fetched_at < entry.ttl_secs)
+                .map(|entry| entry.periods.clone())
+        });
+        perf::count_cache(hit.is_some());
+        hit
+    }
+
+    /// An entry regardless of its TTL.
+    fn get_stale(&self, key: &str) -> Option<Vec<PeriodCost>> {
+        self.with(|cache| cache.get(key).map(|entry| entry.periods.clone()))
+    }
+
+    fn put(&self, key: String, ttl_secs: i64, periods: &[PeriodCost]) {
+        let entry = CachedChunk {
+            fetched_at: chrono::Utc::now().timestamp(),
+            ttl_secs,
+            periods: periods.to_vec(),
+        };
+        self.with(|cache| {
+            let now = entry.fetched_at;
+            cache.retain(|_, e| now - e.fetched_at < e.ttl_secs.max(STALE_KEEP_SECS));
+            cache.insert(key, entry);
+            let _ = self.write(cache);
+        });
+    }
+
+    fn remove(&self, key: &str) -> std::io::Result<()> {
+        self.with(|cache| {
+            cache.remove(key);
+            self.write(cache)
+        })
+    }
+
+    fn write(&self, cache: &HashMap<String, CachedChunk>) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(cache)?;
+        std::fs::write(&self.path, json)
+    }
+
+    /// Writes a marker entry, reads it back from disk, and removes it again.
+    fn round_trip(&self) -> Result<(), AppError> {
+        const MARKER: &str = "selftest";
+        self.put(MARKER.into(), 60, &[]);
+        let content = std::fs::read_to_string(&self.path)?;
+        let on_disk: HashMap<String, CachedChunk> =
+            serde_json::from_str(&content).map_err(|e| AppError::Internal(e.to_string()))?;
+        let found = on_disk.contains_key(MARKER) && self.get_stale(MARKER).is_some();
+        self.remove(MARKER)?;
+        if found {
+            Ok(())
+        } else {
+            Err(AppError::Internal(format!(
+                "The cache at {} did not keep a written entry",
+                self.path.display()
+            )))
+        }
+    }
+
+    fn clear(&self) -> std::io::Result<()> {
+        self.with(|cache| cache.clear());
+        match std::fs::remove_file(&self.path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn chunk_cache(app: &AppHandle) -> &ChunkCache {
+    app.state::<CostCacheState>()
+        .inner()
+        .chunks
+        .get_or_init(|| ChunkCache::new(cache_path(app)))
+}
+
+/// Checks that the chunk cache can be written and read back.
+pub(crate) fn check_cache(app: &AppHandle) -> Result<(), AppError> {
+    chunk_cache(app).round_trip()
+}
+
+fn chunk_ttl(chunk: &CostQuery) -> i64 {
+    let today = chrono::Utc::now().date_naive();
+    if (today - chunk.end).num_days() >= SETTLE_DAYS {
+        CLOSED_TTL_SECS
+    } else {
+        OPEN_TTL_SECS
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Request budget
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CostExplorerBudgetSettings {
+    /// Billable requests allowed per calendar month (UTC); `None` for no cap.
+    pub monthly_request_cap: Option<u32>,
+    /// USD per request, for the cost estimate.
+    pub cost_per_request: f64,
+    /// Percent of the cap after which expired cache entries are served
+    /// instead of refetching.
+    pub cache_first_percent: u32,
+}
+
+impl Default for CostExplorerBudgetSettings {
+    fn default() -> Self {
+        Self {
+            monthly_request_cap: None,
+            cost_per_request: 0.01,
+            cache_first_percent: 80,
+        }
+    }
+}
+
+fn usage_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("cost_explorer_usage.json")
+}
+
+fn current_month() -> String {
+    chrono::Utc::now().format("%Y-%m").to_string()
+}
+
+/// Billable requests per month (`YYYY-MM`), persisted as JSON at `path`.
+pub struct RequestLedger {
+    path: PathBuf,
+    months: Mutex<Option<BTreeMap<String, u32>>>,
+}
+
+impl RequestLedger {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            months: Mutex::new(None),
+        }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut BTreeMap<String, u32>) -> T) -> T {
+        let mut guard = self.months.lock().unwrap_or_else(|e| e.into_inner());
+        let months = guard.get_or_insert_with(|| {
+            std::fs::read_to_string(&self.path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default()
+        });
+        f(months)
+    }
+
+    /// Requests made this month.
+    fn used(&self) -> u32 {
+        self.with(|months| months.get(&current_month()).copied().unwrap_or_default())
+    }
+
+    fn record(&self, requests: u32) {
+        self.with(|months| {
+            *months.entry(current_month()).or_default() += requests;
+            while months.len() > LEDGER_MONTHS {
+                months.pop_first();
+            }
+            if let Some(dir) = self.path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            if let Ok(json) = serde_json::to_string(months) {
+                let _ = std::fs::write(&self.path, json);
+            }
+        });
+    }
+}
+
+/// The ledger together with the configured cap.
+#[derive(Clone)]
+struct Budget {
+    ledger: Arc<RequestLedger>,
+    settings: CostExplorerBudgetSettings,
+}
+
+impl Budget {
+    /// Usage has reached the cache-first share of the cap.
+    fn cache_first(&self) -> bool {
+        self.settings.monthly_request_cap.is_some_and(|cap| {
+            u64::from(self.ledger.used()) * 100
+                >= u64::from(cap) * u64::from(self.settings.cache_first_percent)
+        })
+    }
+
+    /// Refuses work needing at least `requests` more requests when that
+    /// would pass the cap. Extra pages of a chunk may overshoot it slightly.
+    fn allow(&self, requests: u32) -> Result<(), AppError> {
+        let Some(cap) = self.settings.monthly_request_cap else {
+            return Ok(());
+        };
+        let used = self.ledger.used();
+        if used.saturating_add(requests) > cap {
+            return Err(AppError::BudgetExceeded(format!(
+                "Cost Explorer request cap reached: {used} of {cap} requests used this month, \
+                 {requests} more needed"
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn budget(app: &AppHandle) -> Budget {
+    let ledger = app
+        .state::<CostCacheState>()
+        .inner()
+        .ledger
+        .get_or_init(|| Arc::new(RequestLedger::new(usage_path(app))))
+        .clone();
+    Budget {
+        ledger,
+        settings: settings::load(app).cost_explorer_budget,
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CostExplorerUsage {
+    /// `YYYY-MM`, UTC.
+    pub month: String,
+    pub requests: u32,
+    pub estimated_cost: f64,
+    pub monthly_request_cap: Option<u32>,
+    /// Expired cache entries are being served instead of refetching.
+    pub cache_first: bool,
+    pub cap_reached: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Fetching
+// ---------------------------------------------------------------------------
+
+fn first_of_next_month(date: NaiveDate) -> Option<NaiveDate> {
+    if date.month() == 12 {
+        NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+    }
+}
+
+/// Splits the range at month boundaries.
+fn month_chunks(query: &CostQuery) -> Vec<CostQuery> {
+    let mut chunks = Vec::new();
+    let mut start = query.start;
+    while start < query.end {
+        let end = first_of_next_month(start)
+            .unwrap_or(query.end)
+            .min(query.end);
+        chunks.push(CostQuery {
+            start,
+            end,
+            ..query.clone()
+        });
+        start = end;
+    }
+    chunks
+}
+
+fn metric_map(
+    metrics: Option<&HashMap<String, aws_sdk_costexplorer::types::MetricValue>>,
+) -> BTreeMap<String, MetricValue> {
+    metrics
+        .into_iter()
+        .flatten()
+        .map(|(name, value)| {
+            (
+                name.clone(),
+                MetricValue {
+                    amount: value
+                        .amount()
+                        .and_then(|a| a.parse().ok())
+                        .unwrap_or_default(),
+                    unit: value.unit().unwrap_or_default().to_string(),
+                },
+            )
+        })
+        .collect()
+}
+
+fn period_cost(result: &ResultByTime) -> PeriodCost {
+    let interval = result.time_period();
+    PeriodCost {
+        start: interval.map(|i| i.start().to_string()).unwrap_or_default(),
+        end: interval.map(|i| i.end().to_string()).unwrap_or_default(),
+        estimated: result.estimated(),
+        total: metric_map(result.total()),
+        groups: result
+            .groups()
+            .iter()
+            .map(|group| GroupCost {
+                keys: group.keys().to_vec(),
+                metrics: metric_map(group.metrics()),
+            })
+            .collect(),
+    }
+}
+
+/// Grouped queries repeat a period across pages; fold those into one entry.
+fn merge_periods(periods: Vec<PeriodCost>) -> Vec<PeriodCost> {
+    let mut merged: BTreeMap<String, PeriodCost> = BTreeMap::new();
+    for period in periods {
+        match merged.get_mut(&period.start) {
+            Some(existing) => {
+                existing.groups.extend(period.groups);
+                if existing.total.is_empty() {
+                    existing.total = period.total;
+                }
+                existing.estimated |= period.estimated;
+            }
+            None => {
+                merged.insert(period.start.clone(), period);
+            }
+        }
+    }
+    merged.into_values().collect()
+}
+
+async fn fetch_chunk(
+    client: &aws_sdk_costexplorer::Client,
+    ledger: &RequestLedger,
+    chunk: &CostQuery,
+) -> Result<(Vec<PeriodCost>, usize), AppError> {
+    let interval = DateInterval::builder()
+        .start(chunk.start.format("%Y-%m-%d").to_string())
+        .end(chunk.end.format("%Y-%m-%d").to_string())
+        .build()
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let groups: Vec<SdkGroupDefinition> = chunk
+        .group_by
+        .iter()
+        .map(|g| {
+            SdkGroupDefinition::builder()
+                .r#type(match g.kind {
+                    GroupKind::Dimension => GroupDefinitionType::Dimension,
+                    GroupKind::Tag => GroupDefinitionType::Tag,
+                    GroupKind::CostCategory => GroupDefinitionType::CostCategory,
+                })
+                .key(&g.key)
+                .build()
+        })
+        .collect();
+
+    let mut periods = Vec::new();
+    let mut pages = 0;
+    let mut token: Option<String> = None;
+    loop {
+        let out = send(|| {
+            client
+                .get_cost_and_usage()
+                .time_period(interval.clone())
+                .granularity(match chunk.granularity {
+                    Granularity::Daily => aws_sdk_costexplorer::types::Granularity::Daily,
+                    Granularity::Monthly => aws_sdk_costexplorer::types::Granularity::Monthly,
+                })
+                .set_metrics(Some(chunk.metrics.clone()))
+                .set_group_by((!groups.is_empty()).then(|| groups.clone()))
+                .set_next_page_token(token.clone())
+                .send()
+        })
+        .await
+        .map_err(sdk_error)?;
+        ledger.record(1);
+        pages += 1;
+        periods.extend(out.results_by_time().iter().map(period_cost));
+        match out.next_page_token() {
+            Some(next) if !next.is_empty() => token = Some(next.to_string()),
+            _ => break,
+        }
+    }
+    Ok((periods, pages))
+}
+
+/// Cost Explorer is served from one region per partition.
+fn client(config: &aws_config::SdkConfig) -> aws_sdk_costexplorer::Client {
+    let region = config.region().map(|r| r.as_ref()).unwrap_or_default();
+    let ce_config = aws_sdk_costexplorer::config::Builder::from(config)
+        .region(Region::new(
+            Partition::of_region(region).cost_explorer_region(),
+        ))
+        .build();
+    aws_sdk_costexplorer::Client::from_conf(ce_config)
+}
+
+/// Runs a query, joining an identical one that is already in flight.
+pub async fn query(app: &AppHandle, query: &CostQuery) -> Result<CostQueryResult, AppError> {
+    let key = format!(
+        "{}:{}",
+        read_credentials(app)
+            .map(|c| c.access_key_id)
+            .unwrap_or_default(),
+        serde_json::to_string(query).unwrap_or_default()
+    );
+    let state = app.state::<CostCacheState>();
+    let (cell, leader) = {
+        let mut in_flight = state.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        match in_flight.get(&key) {
+            Some(cell) => (cell.clone(), false),
+            None => {
+                let cell = Shared::default();
+                in_flight.insert(key.clone(), cell.clone());
+                (cell, true)
+            }
+        }
+    };
+
+    let result = cell.get_or_init(|| run_query(app, query)).await.clone();
+    // Whoever finishes first retires the entry, so a cancelled leader cannot
+    // leave a finished result behind for later queries.
+    let mut in_flight = state.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+    if in_flight.get(&key).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+        in_flight.remove(&key);
+    }
+    drop(in_flight);
+    result.map(|mut result| {
+        result.coalesced = !leader;
+        result
+    })
+}
+
+/// Runs a query through the chunk cache, fetching missing chunks concurrently.
+async fn run_query(app: &AppHandle, query: &CostQuery) -> Result<CostQueryResult, AppError> {
+    let config = sdk_config(app).await?;
+    run_query_with(&config, chunk_cache(app), &budget(app), query).await
+}
+
+async fn run_query_with(
+    config: &aws_config::SdkConfig,
+    cache: &ChunkCache,
+    budget: &Budget,
+    query: &CostQuery,
+) -> Result<CostQueryResult, AppError> {
+    if query.start >= query.end {
+        return Err(AppError::InvalidInput(
+            "Query start must be before its end".into(),
+        ));
+    }
+    if query.group_by.len() > 2 {
+        return Err(AppError::InvalidInput(
+            "Cost Explorer supports at most two group-by keys".into(),
+        ));
+    }
+
+    let account = account_id(config).await?;
+    let chunks = month_chunks(query);
+    let mut result = CostQueryResult {
+        chunks: chunks.len(),
+        ..Default::default()
+    };
+    let mut periods = Vec::new();
+    let mut missing = Vec::new();
+    let cache_first = budget.cache_first();
+    for chunk in chunks {
+        let key = cache_key(&account, &chunk);
+        if let Some(hit) = cache.get(&key) {
+            result.cache_hits += 1;
+            periods.extend(hit);
+        } else if let Some(stale) = cache_first.then(|| cache.get_stale(&key)).flatten() {
+            result.stale_hits += 1;
+            periods.extend(stale);
+        } else {
+            missing.push(chunk);
+        }
+    }
+
+    if !missing.is_empty() {
+        budget.allow(missing.len() as u32)?;
+        let client = client(config);
+        let mut fetches = JoinSet::new();
+        for chunk in missing {
+            let client = client.clone();
+            let ledger = budget.ledger.clone();
+            fetches.spawn(perf::propagate(async move {
+                let fetched = fetch_chunk(&client, &ledger, &chunk).await;
+                (chunk, fetched)
+            }));
+        }
+        while let Some(joined) = fetches.join_next().await {
+            let (chunk, fetched) = joined.map_err(|e| AppError::Internal(e.to_string()))?;
+            let (chunk_periods, pages) = fetched?;
+            result.pages_fetched += pages;
+            cache.put(
+                cache_key(&account, &chunk),
+                chunk_ttl(&chunk),
+                &chunk_periods,
+            );
+            periods.extend(chunk_periods);
+        }
+    }
+
+    result.periods = merge_periods(periods);
+    Ok(result)
+}
+
+/// The current month up to and including today (UTC, as Cost Explorer).
+pub fn month_to_date(granularity: Granularity, group_by: Vec<GroupBy>) -> CostQuery {
+    let today = chrono::Utc::now().date_naive();
+    CostQuery {
+        start: today.with_day(1).unwrap_or(today),
+        end: today.succ_opt().unwrap_or(today),
+        granularity,
+        metrics: default_metrics(),
+        group_by,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Forecast
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CostForecast {
+    pub start: String,
+    /// Exclusive.
+    pub end: String,
+    pub mean: f64,
+    /// 80% prediction interval.
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+    pub unit: String,
+}
+
+fn forecast_period(forecast: &CostForecast) -> PeriodCost {
+    let metric = |amount: f64| MetricValue {
+        amount,
+        unit: forecast.unit.clone(),
+    };
+    let mut total = BTreeMap::from([("mean".to_string(), metric(forecast.mean))]);
+    if let Some(lower) = forecast.lower {
+        total.insert("lower".into(), metric(lower));
+    }
+    if let Some(upper) = forecast.upper {
+        total.insert("upper".into(), metric(upper));
+    }
+    PeriodCost {
+        start: forecast.start.clone(),
+        end: forecast.end.clone(),
+        estimated: true,
+        total,
+        groups: Vec::new(),
+    }
+}
+
+fn period_forecast(period: &PeriodCost) -> Option<CostForecast> {
+    let mean = period.total.get("mean")?;
+    Some(CostForecast {
+        start: period.start.clone(),
+        end: period.end.clone(),
+        mean: mean.amount,
+        lower: period.total.get("lower").map(|m| m.amount),
+        upper: period.total.get("upper").map(|m| m.amount),
+        unit: mean.unit.clone(),
+    })
+}
+
+/// Forecast spend for the rest of the current month, cached for a few hours.
+pub async fn forecast(app: &AppHandle) -> Result<CostForecast, AppError> {
+    let today = chrono::Utc::now().date_naive();
+    let end = first_of_next_month(today).ok_or("Date out of range")?;
+    let config = sdk_config(app).await?;
+    let key = format!("{}:forecast:{today}:{end}", account_id(&config).await?);
+    let cache = chunk_cache(app);
+    let budget = budget(app);
+    let hit = cache.get(&key).or_else(|| {
+        budget
+            .cache_first()
+            .then(|| cache.get_stale(&key))
+            .flatten()
+    });
+    if let Some(hit) = hit.and_then(|p| p.first().and_then(period_forecast)) {
+        return Ok(hit);
+    }
+    budget.allow(1)?;
+
+    let client = client(&config);
+    let interval = DateInterval::builder()
+        .start(today.format("%Y-%m-%d").to_string())
+        .end(end.format("%Y-%m-%d").to_string())
+        .build()
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    let out = send(|| {
+        client
+            .get_cost_forecast()
+            .time_period(interval.clone())
+            .metric(aws_sdk_costexplorer::types::Metric::UnblendedCost)
+            .granularity(aws_sdk_costexplorer::types::Granularity::Monthly)
+            .prediction_interval_level(80)
+            .send()
+    })
+    .await
+    .map_err(sdk_error)?;
+    budget.ledger.record(1);
+
+    let total = out.total();
+    let amount = |v: Option<&str>| v.and_then(|a| a.parse::<f64>().ok());
+    let results = out.forecast_results_by_time();
+    let sum = |f: fn(&aws_sdk_costexplorer::types::ForecastResult) -> Option<&str>| {
+        let values: Vec<f64> = results.iter().filter_map(|r| amount(f(r))).collect();
+        (!values.is_empty()).then(|| values.iter().sum())
+    };
+    let forecast = CostForecast {
+        start: today.format("%Y-%m-%d").to_string(),
+        end: end.format("%Y-%m-%d").to_string(),
+        mean: amount(total.and_then(|t| t.amount())).unwrap_or_default(),
+        lower: sum(|r| r.prediction_interval_lower_bound()),
+        upper: sum(|r| r.prediction_interval_upper_bound()),
+        unit: total.and_then(|t| t.unit()).unwrap_or("USD").to_string(),
+    };
+    cache.put(key, FORECAST_TTL_SECS, &[forecast_period(&forecast)]);
+    Ok(forecast)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn query_costs(app: AppHandle, query: CostQuery) -> CommandResult<CostQueryResult> {
+    perf::measure(&app, "query_costs", self::query(&app, &query)).await
+}
+
+#[tauri::command]
+pub async fn get_cost_forecast(app: AppHandle) -> CommandResult<CostForecast> {
+    perf::measure(&app, "get_cost_forecast", forecast(&app)).await
+}
+
+/// This month's Cost Explorer requests and their estimated cost.
+#[tauri::command]
+pub fn get_cost_explorer_usage(app: AppHandle) -> CostExplorerUsage {
+    let budget = budget(&app);
+    let requests = budget.ledger.used();
+    let cap = budget.settings.monthly_request_cap;
+    CostExplorerUsage {
+        month: current_month(),
+        requests,
+        estimated_cost: f64::from(requests) * budget.settings.cost_per_request,
+        monthly_request_cap: cap,
+        cache_first: budget.cache_first(),
+        cap_reached: cap.is_some_and(|cap| requests >= cap),
+    }
+}
+
+#[tauri::command]
+pub fn get_cost_explorer_budget(app: AppHandle) -> CostExplorerBudgetSettings {
+    settings::load(&app).cost_explorer_budget
+}
+
+#[tauri::command]
+pub fn save_cost_explorer_budget(
+    app: AppHandle,
+    config: CostExplorerBudgetSettings,
+) -> CommandResult<()> {
+    if !(1..=100).contains(&config.cache_first_percent) {
+        return Err(AppError::InvalidInput(
+            "Cache-first threshold must be between 1 and 100 percent".into(),
+        ));
+    }
+    if !config.cost_per_request.is_finite() || config.cost_per_request < 0.0 {
+        return Err(AppError::InvalidInput(
+            "Cost per request must be zero or more".into(),
+        ));
+    }
+    let mut all = settings::load(&app);
+    all.cost_explorer_budget = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
+
+/// Drops every cached chunk so the next query refetches from Cost Explorer.
+#[tauri::command]
+pub fn clear_cost_cache(app: AppHandle) -> CommandResult<()> {
+    Ok(chunk_cache(&app).clear()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, ReplayClient};
+
+    fn temp_cache() -> ChunkCache {
+        ChunkCache::new(
+            std::env::temp_dir().join(format!("ce-cache-{}.json", uuid::Uuid::new_v4())),
+        )
+    }
+
+    fn by_service(start: &str, end: &str) -> CostQuery {
+        CostQuery {
+            start: start.parse().unwrap(),
+            end: end.parse().unwrap(),
+            granularity: Granularity::Monthly,
+            metrics: default_metrics(),
+            group_by: vec![GroupBy {
+                kind: GroupKind::Dimension,
+                key: "SERVICE".into(),
+            }],
+        }
+    }
+
+    fn capped(cap: Option<u32>) -> Budget {
+        Budget {
+            ledger: Arc::new(RequestLedger::new(
+                std::env::temp_dir().join(format!("ce-usage-{}.json", uuid::Uuid::new_v4())),
+            )),
+            settings: CostExplorerBudgetSettings {
+                monthly_request_cap: cap,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn cost_calls(aws: &ReplayClient) -> usize {
+        aws.operations()
+            .iter()
+            .filter(|op| *op == "GetCostAndUsage")
+            .count()
+    }
+
+    #[tokio::test]
+    async fn query_merges_month_chunks_and_pages() {
+        let aws = ReplayClient::load("aws/cost_explorer.json");
+        let config = mock::sdk_config(&aws).await;
+        let budget = capped(None);
+        let result = run_query_with(
+            &config,
+            &temp_cache(),
+            &budget,
+            &by_service("2025-01-01", "2025-03-01"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(budget.ledger.used(), 3);
+
+        assert_eq!(result.chunks, 2);
+        assert_eq!(result.cache_hits, 0);
+        assert_eq!(result.pages_fetched, 3);
+        assert_eq!(cost_calls(&aws), 3);
+        let starts: Vec<&str> = result.periods.iter().map(|p| p.start.as_str()).collect();
+        assert_eq!(starts, ["2025-01-01", "2025-02-01"]);
+        let february: Vec<&str> = result.periods[1]
+            .groups
+            .iter()
+            .map(|g| g.keys[0].as_str())
+            .collect();
+        assert_eq!(february, ["Amazon Simple Storage Service", "AWS Lambda"]);
+    }
+
+    #[tokio::test]
+    async fn cached_chunks_are_served_without_requests() {
+        let aws = ReplayClient::load("aws/cost_explorer.json");
+        let config = mock::sdk_config(&aws).await;
+        let cache = temp_cache();
+        let budget = capped(None);
+        let query = by_service("2025-01-01", "2025-03-01");
+        run_query_with(&config, &cache, &budget, &query)
+            .await
+            .unwrap();
+
+        let again = run_query_with(&config, &cache, &budget, &query)
+            .await
+            .unwrap();
+        assert_eq!(again.cache_hits, 2);
+        assert_eq!(again.pages_fetched, 0);
+        assert_eq!(cost_calls(&aws), 3);
+
+        // A fresh cache over the same file picks the chunks up from disk.
+        let reloaded = ChunkCache::new(cache.path.clone());
+        let from_disk = run_query_with(&config, &reloaded, &budget, &query)
+            .await
+            .unwrap();
+        assert_eq!(from_disk.cache_hits, 2);
+        assert_eq!(from_disk.periods.len(), 2);
+
+        cache.clear().unwrap();
+        assert!(!cache.path.exists());
+    }
+
+    #[tokio::test]
+    async fn chunk_errors_keep_their_kind() {
+        let aws = ReplayClient::load("aws/cost_explorer.json");
+        let config = mock::sdk_config(&aws).await;
+        let err = run_query_with(
+            &config,
+            &temp_cache(),
+            &capped(None),
+            &by_service("2025-03-01", "2025-04-01"),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::AccessDenied(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn invalid_queries_make_no_requests() {
+        let aws = ReplayClient::load("aws/cost_explorer.json");
+        let config = mock::sdk_config(&aws).await;
+        let err = run_query_with(
+            &config,
+            &temp_cache(),
+            &capped(None),
+            &by_service("2025-03-01", "2025-03-01"),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)));
+        assert!(aws.operations().is_empty());
+    }
+
+    #[tokio::test]
+    async fn queries_past_the_cap_are_refused() {
+        let aws = ReplayClient::load("aws/cost_explorer.json");
+        let config = mock::sdk_config(&aws).await;
+        let budget = capped(Some(10));
+        budget.ledger.record(9);
+        let err = run_query_with(
+            &config,
+            &temp_cache(),
+            &budget,
+            &by_service("2025-01-01", "2025-03-01"),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, AppError::BudgetExceeded(_)), "{err:?}");
+        assert_eq!(cost_calls(&aws), 0);
+        assert_eq!(budget.ledger.used(), 9);
+    }
+
+    #[tokio::test]
+    async fn expired_chunks_are_served_near_the_cap() {
+        let aws = ReplayClient::load("aws/cost_explorer.json");
+        let config = mock::sdk_config(&aws).await;
+        let account = account_id(&config).await.unwrap();
+        let cache = temp_cache();
+        let query = by_service("2025-01-01", "2025-02-01");
+        let period = PeriodCost {
+            start: "2025-01-01".into(),
+            end: "2025-02-01".into(),
+            estimated: false,
+            total: BTreeMap::new(),
+            groups: Vec::new(),
+        };
+        cache.put(cache_key(&account, &query), 0, &[period]);
+
+        let budget = capped(Some(10));
+        budget.ledger.record(8);
+        let result = run_query_with(&config, &cache, &budget, &query)
+            .await
+            .unwrap();
+        assert_eq!((result.cache_hits, result.stale_hits), (0, 1));
+        assert_eq!(cost_calls(&aws), 0);
+
+        // Below the threshold the expired entry is refetched.
+        let relaxed = capped(Some(100));
+        let result = run_query_with(&config, &cache, &relaxed, &query)
+            .await
+            .unwrap();
+        assert_eq!(result.stale_hits, 0);
+        assert_eq!(cost_calls(&aws), 1);
+    }
+
+    #[test]
+    fn ranges_split_at_month_boundaries() {
+        let chunks = month_chunks(&by_service("2024-12-15", "2025-02-10"));
+        let bounds: Vec<String> = chunks
+            .iter()
+            .map(|c| format!("{}..{}", c.start, c.end))
+            .collect();
+        assert_eq!(
+            bounds,
+            [
+                "2024-12-15..2025-01-01",
+                "2025-01-01..2025-02-01",
+                "2025-02-01..2025-02-10"
+            ]
+        );
+    }
+
+    #[test]
+    fn cache_round_trip_leaves_no_marker() {
+        let cache = temp_cache();
+        cache.round_trip().unwrap();
+        assert!(cache.get_stale("selftest").is_none());
+        let content = std::fs::read_to_string(&cache.path).unwrap();
+        assert_eq!(content, "{}");
+        let _ = std::fs::remove_file(&cache.path);
+    }
+}
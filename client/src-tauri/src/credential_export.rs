@@ -0,0 +1,195 @@
+//! Copies the current credentials to the clipboard for use in a terminal,
+//! as `export AWS_…` lines or as `credential_process` JSON. The text never
+//! reaches the webview, the same OS confirmation as revealing the secret key
+//! applies, and the clipboard is cleared after a short while unless
+//! something else has been copied since.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use secrecy::zeroize::Zeroizing;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::error::{AppError, CommandResult};
+use crate::{app_lock, os_auth, read_credentials, settings, AwsCredentials};
+
+const DEFAULT_CLEAR_SECS: u64 = 30;
+const CLEAR_SECS: std::ops::RangeInclusive<u64> = 5..=600;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// POSIX shell `export` lines.
+    Shell,
+    /// The AWS CLI's `credential_process` output.
+    Json,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CopiedCredentials {
+    pub format: ExportFormat,
+    /// When the clipboard is cleared.
+    pub clears_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Borrows the secrets, so the only copy made is the zeroized output.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ProcessOutput<'a> {
+    version: u32,
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_token: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration: Option<DateTime<Utc>>,
+}
+
+/// Appends `value` single-quoted for POSIX shells.
+fn push_quoted(out: &mut String, value: &str) {
+    out.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => out.push_str(r"'\''"),
+            c => out.push(c),
+        }
+    }
+    out.push('\'');
+}
+
+fn render(creds: &AwsCredentials, format: ExportFormat) -> Zeroizing<String> {
+    match format {
+        ExportFormat::Shell => {
+            let mut vars = vec![
+                ("AWS_ACCESS_KEY_ID", creds.access_key_id.as_str()),
+                ("AWS_SECRET_ACCESS_KEY", creds.secret_access_key()),
+            ];
+            if let Some(token) = creds.session_token() {
+                vars.push(("AWS_SESSION_TOKEN", token));
+            }
+            vars.push(("AWS_REGION", &creds.region));
+            vars.push(("AWS_DEFAULT_REGION", &creds.region));
+            if let Some(url) = creds.endpoint_url() {
+                vars.push(("AWS_ENDPOINT_URL", url));
+            }
+            let expiration = creds.expires_at.map(|at| at.to_rfc3339());
+            if let Some(at) = &expiration {
+                vars.push(("AWS_CREDENTIAL_EXPIRATION", at));
+            }
+            // Sized up front so growing never leaves copies behind.
+            let mut text = Zeroizing::new(String::with_capacity(4096));
+            for (name, value) in vars {
+                text.push_str("export ");
+                text.push_str(name);
+                text.push('=');
+                push_quoted(&mut text, value);
+                text.push('\n');
+            }
+            text
+        }
+        ExportFormat::Json => {
+            let output = ProcessOutput {
+                version: 1,
+                access_key_id: &creds.access_key_id,
+                secret_access_key: creds.secret_access_key(),
+                session_token: creds.session_token(),
+                expiration: creds.expires_at,
+            };
+            Zeroizing::new(serde_json::to_string(&output).unwrap_or_default())
+        }
+    }
+}
+
+/// Clears the clipboard after `after`, if it still holds `text`.
+fn clear_later(app: AppHandle, text: Zeroizing<String>, after: Duration) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(after).await;
+        let clipboard = app.clipboard();
+        if clipboard
+            .read_text()
+            .is_ok_and(|current| *current == **text)
+        {
+            if let Err(err) = clipboard.write_text(String::new()) {
+                eprintln!("could not clear the clipboard: {err}");
+            }
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Copies the current credentials and clears the clipboard after
+/// `clear_after_secs` (default 30, at most 10 minutes).
+#[tauri::command]
+pub async fn copy_credentials(
+    app: AppHandle,
+    format: ExportFormat,
+    clear_after_secs: Option<u64>,
+) -> CommandResult<CopiedCredentials> {
+    app_lock::ensure_unlocked(&app)?;
+    let after = clear_after_secs.unwrap_or(DEFAULT_CLEAR_SECS);
+    if !CLEAR_SECS.contains(&after) {
+        return Err(AppError::InvalidInput(
+            "The clipboard is cleared after 5 to 600 seconds".into(),
+        ));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let creds = read_credentials(&app)
+            .ok_or_else(|| AppError::Credentials("No credentials saved".into()))?;
+        if settings::load(&app).reveal.require_os_auth {
+            os_auth::confirm_user()?;
+        }
+        let text = render(&creds, format);
+        app.clipboard()
+            .write_text(text.as_str())
+            .map_err(|e| AppError::Internal(format!("Could not copy to the clipboard: {e}")))?;
+        clear_later(app, text, Duration::from_secs(after));
+        Ok(CopiedCredentials {
+            format,
+            clears_at: Utc::now() + chrono::Duration::seconds(after as i64),
+            expires_at: creds.expires_at,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "ASIAEXAMPLE".into(),
+            secret_access_key: "it's secret".into(),
+            region: "eu-west-1".into(),
+            session_token: Some("token".into()),
+            expires_at: Some("2025-03-10T13:00:00Z".parse().unwrap()),
+            partition: None,
+            endpoint_url: None,
+        }
+    }
+
+    #[test]
+    fn shell_lines_are_quoted() {
+        let text = render(&session(), ExportFormat::Shell);
+        assert!(text.contains("export AWS_SECRET_ACCESS_KEY='it'\\''s secret'\n"));
+        assert!(text.contains("export AWS_SESSION_TOKEN='token'\n"));
+        assert!(text.contains("export AWS_CREDENTIAL_EXPIRATION='2025-03-10T13:00:00+00:00'\n"));
+    }
+
+    #[test]
+    fn json_follows_the_credential_process_contract() {
+        let text = render(&session(), ExportFormat::Json);
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["Version"], 1);
+        assert_eq!(json["AccessKeyId"], "ASIAEXAMPLE");
+        assert_eq!(json["SessionToken"], "token");
+        assert_eq!(json["Expiration"], "2025-03-10T13:00:00Z");
+    }
+}
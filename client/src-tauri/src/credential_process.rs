@@ -0,0 +1,339 @@
+//! Credentials from an AWS CLI `credential_process` helper (aws-vault,
+//! saml2aws, ...). The helper runs with the command line from
+//! `~/.aws/config`, and the keys it prints are kept in memory only: they go to
+//! the sidecar and the app's AWS clients but never to the keychain. Settings
+//! remember which CLI profile to use, so the helper runs again at launch and
+//! whenever its keys are about to expire.
+
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use secrecy::zeroize::Zeroizing;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::plugins::read_capped;
+use crate::{
+    app_lock, aws_cli, instance_credentials, read_credentials, reload_sidecar, settings, sso,
+    stop_sidecar, AwsCredentials,
+};
+
+/// Helpers may wait for a hardware key or a browser sign-in.
+const TIMEOUT: Duration = Duration::from_secs(120);
+const MAX_OUTPUT_BYTES: u64 = 64 * 1024;
+const MAX_STDERR_BYTES: u64 = 16 * 1024;
+
+/// The helper's keys, while a profile is in use.
+static CURRENT: Mutex<Option<AwsCredentials>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CredentialProcessSettings {
+    /// CLI profile whose helper supplies the app's credentials.
+    pub profile: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProcessCredentials {
+    pub profile: String,
+    pub access_key_id: String,
+    pub region: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// What the helper prints, per the AWS CLI's `credential_process` contract.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ProcessOutput {
+    version: u32,
+    access_key_id: String,
+    secret_access_key: SecretString,
+    session_token: Option<SecretString>,
+    expiration: Option<DateTime<Utc>>,
+}
+
+/// Splits a command line into words the way a POSIX shell would for plain
+/// words, single and double quotes, and backslash escapes.
+fn split_command(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated quote in credential_process".into()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => break,
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated quote in credential_process".into()),
+                    }
+                }
+            }
+            '\\' => {
+                let word = word.get_or_insert_with(String::new);
+                if let Some(c) = chars.next() {
+                    word.push(c);
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    if words.is_empty() {
+        return Err("credential_process is empty".into());
+    }
+    Ok(words)
+}
+
+fn parse_output(stdout: &[u8], region: String) -> Result<AwsCredentials, String> {
+    let output: ProcessOutput = serde_json::from_slice(stdout)
+        .map_err(|e| format!("Invalid credential_process output: {e}"))?;
+    if output.version != 1 {
+        return Err(format!(
+            "Unsupported credential_process output version {}",
+            output.version
+        ));
+    }
+    if output.access_key_id.is_empty() || output.secret_access_key.expose_secret().is_empty() {
+        return Err("credential_process returned no access keys".into());
+    }
+    Ok(AwsCredentials {
+        access_key_id: output.access_key_id,
+        secret_access_key: output.secret_access_key,
+        region,
+        session_token: output.session_token,
+        expires_at: output.expiration,
+        partition: None,
+        endpoint_url: None,
+    })
+}
+
+/// Runs the profile's helper and returns its keys.
+fn run(app: &AppHandle, profile: &str) -> Result<AwsCredentials, String> {
+    let (line, region) = aws_cli::credential_process(app, profile)?;
+    let words = split_command(&line)?;
+    let mut child = Command::new(&words[0])
+        .args(&words[1..])
+        .env("AWS_PROFILE", profile)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Cannot run {}: {e}", words[0]))?;
+    let stdout = read_capped(child.stdout.take().ok_or("no stdout")?, MAX_OUTPUT_BYTES);
+    let stderr = read_capped(child.stderr.take().ok_or("no stderr")?, MAX_STDERR_BYTES);
+
+    let deadline = Instant::now() + TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!(
+                "credential_process timed out after {}s",
+                TIMEOUT.as_secs()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+
+    let stdout = Zeroizing::new(stdout.join().unwrap_or_default());
+    let stderr = stderr.join().unwrap_or_default();
+    if !status.success() {
+        let detail = String::from_utf8_lossy(&stderr);
+        return Err(format!(
+            "credential_process exited with {status}: {}",
+            detail.trim()
+        ));
+    }
+    if stdout.len() as u64 > MAX_OUTPUT_BYTES {
+        return Err("credential_process output is too large".into());
+    }
+    parse_output(&stdout, region)
+}
+
+/// The helper's keys, if a profile is in use.
+pub(crate) fn current() -> Option<AwsCredentials> {
+    CURRENT.lock().ok()?.clone()
+}
+
+fn set_current(creds: Option<AwsCredentials>) {
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = creds;
+    }
+}
+
+/// CLI profile in use, if any.
+pub(crate) fn active_profile(app: &AppHandle) -> Option<String> {
+    settings::load(app).credential_process.profile
+}
+
+/// Runs the helper and points the sidecar at its keys.
+fn load(app: &AppHandle, profile: &str) -> Result<AwsCredentials, String> {
+    let creds = run(app, profile)?;
+    set_current(Some(creds.clone()));
+    if sso::access_granted(app) {
+        reload_sidecar(app, &creds)?;
+    }
+    Ok(creds)
+}
+
+/// Fetches fresh keys for the profile in use, e.g. before the current ones
+/// expire.
+pub(crate) fn refresh(app: &AppHandle) -> Result<(), AppError> {
+    let profile = active_profile(app)
+        .ok_or_else(|| AppError::Credentials("No credential_process profile in use".into()))?;
+    load(app, &profile)?;
+    Ok(())
+}
+
+/// Runs the remembered helper at launch, before the sidecar starts.
+pub fn resume(app: &AppHandle) {
+    if let Some(profile) = active_profile(app) {
+        match run(app, &profile) {
+            Ok(creds) => set_current(Some(creds)),
+            Err(err) => eprintln!("credential_process for '{profile}' failed: {err}"),
+        }
+    }
+}
+
+/// Stops using the helper; called whenever other credentials are applied.
+pub(crate) fn clear(app: &AppHandle) -> Result<(), String> {
+    set_current(None);
+    let mut all = settings::load(app);
+    if all.credential_process.profile.take().is_some() {
+        settings::save(app, &all)?;
+    }
+    Ok(())
+}
+
+fn summary(profile: String, creds: &AwsCredentials) -> ProcessCredentials {
+    ProcessCredentials {
+        profile,
+        access_key_id: creds.access_key_id.clone(),
+        region: creds.region.clone(),
+        expires_at: creds.expires_at,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_credential_process_status(app: AppHandle) -> Option<ProcessCredentials> {
+    let profile = active_profile(&app)?;
+    current().map(|creds| summary(profile, &creds))
+}
+
+/// Switches the app to the keys of a CLI profile's `credential_process`.
+/// Stored profiles stay untouched; the active one is used again once the
+/// helper is no longer.
+#[tauri::command]
+pub async fn use_credential_process(
+    app: AppHandle,
+    profile: String,
+) -> CommandResult<ProcessCredentials> {
+    app_lock::ensure_unlocked(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let creds = load(&app, &profile)?;
+        instance_credentials::clear(&app)?;
+        let mut all = settings::load(&app);
+        all.credential_process.profile = Some(profile.clone());
+        settings::save(&app, &all)?;
+        Ok(summary(profile, &creds))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Stops using the helper and falls back to the active profile, if any.
+#[tauri::command]
+pub async fn stop_credential_process(app: AppHandle) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        clear(&app)?;
+        match read_credentials(&app).filter(|_| sso::access_granted(&app)) {
+            Some(creds) => reload_sidecar(&app, &creds),
+            None => stop_sidecar(&app),
+        }
+        .map_err(AppError::from)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_lines_split_like_a_shell() {
+        assert_eq!(
+            split_command(r#"aws-vault exec  work --json"#).unwrap(),
+            ["aws-vault", "exec", "work", "--json"]
+        );
+        assert_eq!(
+            split_command(r#"'/opt/my tools/helper' --role "Admin \"ops\"" a\ b"#).unwrap(),
+            ["/opt/my tools/helper", "--role", "Admin \"ops\"", "a b"]
+        );
+        assert!(split_command("helper 'open").is_err());
+        assert!(split_command("   ").is_err());
+    }
+
+    #[test]
+    fn output_follows_the_cli_contract() {
+        let creds = parse_output(
+            br#"{"Version": 1, "AccessKeyId": "ASIAEXAMPLE", "SecretAccessKey": "secret",
+                 "SessionToken": "token", "Expiration": "2025-03-10T13:00:00Z"}"#,
+            "eu-west-1".into(),
+        )
+        .unwrap();
+        assert_eq!(creds.access_key_id, "ASIAEXAMPLE");
+        assert_eq!(creds.session_token(), Some("token"));
+        assert_eq!(creds.region, "eu-west-1");
+        assert_eq!(
+            creds.expires_at,
+            Some("2025-03-10T13:00:00Z".parse().unwrap())
+        );
+
+        let long_term = parse_output(
+            br#"{"Version": 1, "AccessKeyId": "AKIAEXAMPLE", "SecretAccessKey": "secret"}"#,
+            "us-east-1".into(),
+        )
+        .unwrap();
+        assert!(long_term.expires_at.is_none());
+
+        assert!(parse_output(
+            br#"{"Version": 2, "AccessKeyId": "A", "SecretAccessKey": "s"}"#,
+            "us-east-1".into()
+        )
+        .is_err());
+    }
+}
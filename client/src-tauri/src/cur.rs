@@ -0,0 +1,780 @@
+//! Cost and Usage Report ingestion. CUR files for a payer account run to
+//! several gigabytes, so they are streamed: CSV (optionally gzipped) record by
+//! record and Parquet row group by row group, folding every line item into a
+//! small month × account × service aggregate of unblended and amortized cost.
+//! Only the aggregate is kept, in `app_data/cur_summary.json`; progress is
+//! emitted as `cur-ingest-progress`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::perf;
+use crate::tasks::{self, CancelToken};
+
+const PROGRESS_EVENT: &str = "cur-ingest-progress";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+const USAGE_START: &str = "line_item_usage_start_date";
+const ACCOUNT: &str = "line_item_usage_account_id";
+const SERVICE: &str = "line_item_product_code";
+const COST: &str = "line_item_unblended_cost";
+const LINE_ITEM_TYPE: &str = "line_item_line_item_type";
+const RESERVATION_ARN: &str = "reservation_reservation_a_r_n";
+const SP_EFFECTIVE_COST: &str = "savings_plan_savings_plan_effective_cost";
+const SP_COMMITMENT: &str = "savings_plan_total_commitment_to_date";
+const SP_USED_COMMITMENT: &str = "savings_plan_used_commitment";
+const RI_EFFECTIVE_COST: &str = "reservation_effective_cost";
+const RI_UNUSED_UPFRONT: &str = "reservation_unused_amortized_upfront_fee_for_billing_period";
+const RI_UNUSED_RECURRING: &str = "reservation_unused_recurring_fee";
+/// Savings Plan and reservation amounts amortized cost is derived from.
+const AMOUNTS: [&str; 6] = [
+    SP_EFFECTIVE_COST,
+    SP_COMMITMENT,
+    SP_USED_COMMITMENT,
+    RI_EFFECTIVE_COST,
+    RI_UNUSED_UPFRONT,
+    RI_UNUSED_RECURRING,
+];
+const TEXT_COLUMNS: [&str; 5] = [
+    USAGE_START,
+    ACCOUNT,
+    SERVICE,
+    LINE_ITEM_TYPE,
+    RESERVATION_ARN,
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CurLine {
+    /// `YYYY-MM` of the usage.
+    pub month: String,
+    pub account_id: String,
+    pub service: String,
+    pub unblended_cost: f64,
+    /// Savings Plan and reservation fees spread over the usage they cover.
+    #[serde(default)]
+    pub amortized_cost: f64,
+    pub line_items: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CurSource {
+    pub path: String,
+    pub ingested_at: String,
+    pub rows: u64,
+    pub months: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CurSummary {
+    pub sources: Vec<CurSource>,
+    pub lines: Vec<CurLine>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CurIngestResult {
+    pub path: String,
+    pub rows: u64,
+    /// Rows without a usable date or cost.
+    pub skipped_rows: u64,
+    pub months: Vec<String>,
+    pub total_unblended_cost: f64,
+    pub total_amortized_cost: f64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CurProgress {
+    pub path: String,
+    pub rows: u64,
+    pub percent: f64,
+    pub done: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Aggregation
+// ---------------------------------------------------------------------------
+
+/// Legacy CUR headers (`lineItem/UsageAccountId`) and CUR 2.0 / Parquet
+/// columns (`line_item_usage_account_id`) both normalize to the latter.
+fn normalize_column(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 8);
+    for c in name.chars() {
+        match c {
+            '/' | ' ' | '-' => out.push('_'),
+            c if c.is_ascii_uppercase() => {
+                if !out.is_empty() && !out.ends_with('_') {
+                    out.push('_');
+                }
+                out.push(c.to_ascii_lowercase());
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether a normalized column is one the aggregate reads.
+fn wanted(column: &str) -> bool {
+    column == COST || TEXT_COLUMNS.contains(&column) || AMOUNTS.contains(&column)
+}
+
+/// The fields of one line item the aggregate reads.
+#[derive(Default)]
+struct LineItem {
+    usage_start: Option<String>,
+    account: String,
+    service: String,
+    line_item_type: String,
+    reservation_arn: String,
+    unblended_cost: Option<f64>,
+    /// Present [`AMOUNTS`] columns.
+    amounts: BTreeMap<&'static str, f64>,
+}
+
+impl LineItem {
+    fn set_text(&mut self, column: &str, value: String) {
+        match column {
+            USAGE_START => self.usage_start = Some(value).filter(|d| !d.is_empty()),
+            ACCOUNT => self.account = value,
+            SERVICE => self.service = value,
+            LINE_ITEM_TYPE => self.line_item_type = value,
+            RESERVATION_ARN => self.reservation_arn = value,
+            _ => {}
+        }
+    }
+
+    fn set_amount(&mut self, column: &str, value: f64) {
+        if let Some(column) = AMOUNTS.iter().find(|amount| **amount == column) {
+            self.amounts.insert(column, value);
+        }
+    }
+
+    /// The cost as Cost Explorer's amortized view shows it: covered usage at
+    /// its effective rate, and only the unused part of commitment fees. Lines
+    /// whose amounts the report lacks keep their unblended cost.
+    fn amortized_cost(&self, unblended: f64) -> f64 {
+        let amount = |column| self.amounts.get(column).copied();
+        match self.line_item_type.as_str() {
+            "SavingsPlanCoveredUsage" => amount(SP_EFFECTIVE_COST).unwrap_or(unblended),
+            "DiscountedUsage" => amount(RI_EFFECTIVE_COST).unwrap_or(unblended),
+            // Already counted through the covered usage.
+            "SavingsPlanNegation" | "SavingsPlanUpfrontFee" => 0.0,
+            "Fee" if !self.reservation_arn.is_empty() => 0.0,
+            "SavingsPlanRecurringFee" => {
+                match (amount(SP_COMMITMENT), amount(SP_USED_COMMITMENT)) {
+                    (Some(total), Some(used)) => total - used,
+                    _ => unblended,
+                }
+            }
+            "RIFee" => match (amount(RI_UNUSED_UPFRONT), amount(RI_UNUSED_RECURRING)) {
+                (None, None) => unblended,
+                (upfront, recurring) => upfront.unwrap_or(0.0) + recurring.unwrap_or(0.0),
+            },
+            _ => unblended,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Aggregate {
+    /// Unblended cost, amortized cost and line items by month, account and
+    /// service.
+    totals: BTreeMap<(String, String, String), (f64, f64, u64)>,
+    rows: u64,
+    skipped: u64,
+}
+
+impl Aggregate {
+    fn add(&mut self, item: LineItem) {
+        self.rows += 1;
+        let (Some(month), Some(unblended)) = (
+            item.usage_start
+                .as_deref()
+                .and_then(|d| d.get(..7).map(str::to_string)),
+            item.unblended_cost,
+        ) else {
+            self.skipped += 1;
+            return;
+        };
+        let amortized = item.amortized_cost(unblended);
+        let entry = self
+            .totals
+            .entry((month, item.account, item.service))
+            .or_default();
+        entry.0 += unblended;
+        entry.1 += amortized;
+        entry.2 += 1;
+    }
+}
+
+struct Progress<'a> {
+    app: &'a AppHandle,
+    path: &'a str,
+    cancel: &'a CancelToken,
+    last: Instant,
+}
+
+impl Progress<'_> {
+    /// Also the cancellation checkpoint, once per row.
+    fn report(&mut self, rows: u64, fraction: f64) -> Result<(), String> {
+        self.cancel.check()?;
+        if self.last.elapsed() >= PROGRESS_INTERVAL {
+            self.last = Instant::now();
+            self.emit(rows, fraction, false);
+        }
+        Ok(())
+    }
+
+    fn emit(&self, rows: u64, fraction: f64, done: bool) {
+        let _ = self.app.emit(
+            PROGRESS_EVENT,
+            CurProgress {
+                path: self.path.to_string(),
+                rows,
+                percent: (fraction * 100.0).clamp(0.0, 100.0),
+                done,
+            },
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CSV
+// ---------------------------------------------------------------------------
+
+/// Counts bytes pulled from the file, i.e. compressed bytes for `.gz`.
+struct Counting<R> {
+    inner: R,
+    read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// Reports rows read and the fraction of the file done; fails to cancel.
+type Report<'a> = dyn FnMut(u64, f64) -> Result<(), String> + 'a;
+
+fn ingest_csv(path: &Path, report: &mut Report) -> Result<Aggregate, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let total = file.metadata().map(|m| m.len()).unwrap_or(0).max(1);
+    let read = Arc::new(AtomicU64::new(0));
+    let counting = Counting {
+        inner: BufReader::new(file),
+        read: read.clone(),
+    };
+    let gzipped = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("gz"));
+    let source: Box<dyn Read> = if gzipped {
+        Box::new(flate2::read::MultiGzDecoder::new(counting))
+    } else {
+        Box::new(counting)
+    };
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(source);
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(normalize_column)
+        .collect();
+    let index = |name: &str| headers.iter().position(|h| h == name);
+    let Some(cost) = index(COST).filter(|_| index(USAGE_START).is_some()) else {
+        return Err("Not a Cost and Usage Report: missing usage date or cost columns".into());
+    };
+    let text: Vec<(&str, usize)> = TEXT_COLUMNS
+        .iter()
+        .filter_map(|column| Some((*column, index(column)?)))
+        .collect();
+    let amounts: Vec<(&str, usize)> = AMOUNTS
+        .iter()
+        .filter_map(|column| Some((*column, index(column)?)))
+        .collect();
+
+    let mut aggregate = Aggregate::default();
+    let mut record = csv::StringRecord::new();
+    while reader.read_record(&mut record).map_err(|e| e.to_string())? {
+        let mut item = LineItem {
+            unblended_cost: record.get(cost).and_then(|v| v.parse().ok()),
+            ..LineItem::default()
+        };
+        for (column, i) in &text {
+            item.set_text(column, record.get(*i).unwrap_or_default().to_string());
+        }
+        for (column, i) in &amounts {
+            if let Some(value) = record.get(*i).and_then(|v| v.parse().ok()) {
+                item.set_amount(column, value);
+            }
+        }
+        aggregate.add(item);
+        report(
+            aggregate.rows,
+            read.load(Ordering::Relaxed) as f64 / total as f64,
+        )?;
+    }
+    Ok(aggregate)
+}
+
+// ---------------------------------------------------------------------------
+// Parquet
+// ---------------------------------------------------------------------------
+
+fn decimal_value(decimal: &parquet::data_type::Decimal) -> f64 {
+    let bytes = decimal.data();
+    // Big-endian two's complement, sign-extended into an i128.
+    let mut value: i128 = if bytes.first().is_some_and(|b| b & 0x80 != 0) {
+        -1
+    } else {
+        0
+    };
+    for b in bytes.iter().take(16) {
+        value = (value << 8) | i128::from(*b);
+    }
+    value as f64 / 10f64.powi(decimal.scale())
+}
+
+fn field_text(field: &Field) -> Option<String> {
+    match field {
+        Field::Str(s) => Some(s.clone()),
+        Field::TimestampMillis(ms) => {
+            chrono::DateTime::from_timestamp_millis(*ms).map(|t| t.to_rfc3339())
+        }
+        Field::TimestampMicros(us) => {
+            chrono::DateTime::from_timestamp_micros(*us).map(|t| t.to_rfc3339())
+        }
+        Field::Date(days) => chrono::NaiveDate::from_num_days_from_ce_opt(719_163 + days)
+            .map(|d| d.format("%Y-%m-%d").to_string()),
+        Field::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+fn field_number(field: &Field) -> Option<f64> {
+    match field {
+        Field::Double(v) => Some(*v),
+        Field::Float(v) => Some(f64::from(*v)),
+        Field::Int(v) => Some(f64::from(*v)),
+        Field::Long(v) => Some(*v as f64),
+        Field::Decimal(d) => Some(decimal_value(d)),
+        Field::Str(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn ingest_parquet(path: &Path, report: &mut Report) -> Result<Aggregate, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let reader = SerializedFileReader::new(file).map_err(|e| e.to_string())?;
+    let metadata = reader.metadata().file_metadata();
+    let total_rows = metadata.num_rows().max(1) as f64;
+
+    // Only the aggregated columns are decoded.
+    let fields: Vec<_> = metadata
+        .schema()
+        .get_fields()
+        .iter()
+        .filter(|f| wanted(&normalize_column(f.name())))
+        .cloned()
+        .collect();
+    let names: BTreeSet<String> = fields.iter().map(|f| normalize_column(f.name())).collect();
+    if !names.contains(USAGE_START) || !names.contains(COST) {
+        return Err("Not a Cost and Usage Report: missing usage date or cost columns".into());
+    }
+    let projection = parquet::schema::types::Type::group_type_builder("schema")
+        .with_fields(fields)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut aggregate = Aggregate::default();
+    for group in 0..reader.num_row_groups() {
+        let row_group = reader.get_row_group(group).map_err(|e| e.to_string())?;
+        let rows = row_group
+            .get_row_iter(Some(projection.clone()))
+            .map_err(|e| e.to_string())?;
+        for row in rows {
+            let row = row.map_err(|e| e.to_string())?;
+            let mut item = LineItem::default();
+            for (name, field) in row.get_column_iter() {
+                match normalize_column(name).as_str() {
+                    COST => item.unblended_cost = field_number(field),
+                    column if TEXT_COLUMNS.contains(&column) => {
+                        item.set_text(column, field_text(field).unwrap_or_default());
+                    }
+                    column => {
+                        if let Some(value) = field_number(field) {
+                            item.set_amount(column, value);
+                        }
+                    }
+                }
+            }
+            aggregate.add(item);
+            report(aggregate.rows, aggregate.rows as f64 / total_rows)?;
+        }
+    }
+    Ok(aggregate)
+}
+
+// ---------------------------------------------------------------------------
+// Store
+// ---------------------------------------------------------------------------
+
+fn summary_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("cur_summary.json")
+}
+
+pub fn read_summary(app: &AppHandle) -> CurSummary {
+    std::fs::read_to_string(summary_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_summary(app: &AppHandle, summary: &CurSummary) -> Result<(), String> {
+    let path = summary_path(app);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(summary).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Streams one CUR file into the stored aggregate. The months it covers
+/// replace whatever an earlier file recorded for them; a cancelled or failed
+/// ingestion leaves the store untouched.
+pub fn ingest_blocking(
+    app: &AppHandle,
+    path: &str,
+    cancel: &CancelToken,
+) -> Result<CurIngestResult, String> {
+    let file = Path::new(path);
+    let name = file
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mut progress = Progress {
+        app,
+        path,
+        cancel,
+        last: Instant::now(),
+    };
+    let mut report = |rows, fraction| progress.report(rows, fraction);
+    let aggregate = if name.ends_with(".parquet") {
+        ingest_parquet(file, &mut report)?
+    } else if name.ends_with(".csv") || name.ends_with(".csv.gz") {
+        ingest_csv(file, &mut report)?
+    } else {
+        return Err("Expected a .csv, .csv.gz or .parquet CUR file".into());
+    };
+    progress.emit(aggregate.rows, 1.0, true);
+
+    let months: BTreeSet<String> = aggregate.totals.keys().map(|k| k.0.clone()).collect();
+    let mut summary = read_summary(app);
+    summary.lines.retain(|line| !months.contains(&line.month));
+    let (mut total, mut total_amortized) = (0.0, 0.0);
+    for ((month, account_id, service), (cost, amortized_cost, line_items)) in aggregate.totals {
+        total += cost;
+        total_amortized += amortized_cost;
+        summary.lines.push(CurLine {
+            month,
+            account_id,
+            service,
+            unblended_cost: cost,
+            amortized_cost,
+            line_items,
+        });
+    }
+    let months: Vec<String> = months.into_iter().collect();
+    summary.sources.push(CurSource {
+        path: path.to_string(),
+        ingested_at: chrono::Utc::now().to_rfc3339(),
+        rows: aggregate.rows,
+        months: months.clone(),
+    });
+    write_summary(app, &summary)?;
+
+    Ok(CurIngestResult {
+        path: path.to_string(),
+        rows: aggregate.rows,
+        skipped_rows: aggregate.skipped,
+        months,
+        total_unblended_cost: total,
+        total_amortized_cost: total_amortized,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn ingest_cur_file(
+    app: AppHandle,
+    path: String,
+    task_id: Option<String>,
+) -> CommandResult<CurIngestResult> {
+    let handle = app.clone();
+    perf::measure(&app, "ingest_cur_file", async move {
+        tauri::async_runtime::spawn_blocking(perf::blocking(move || {
+            let (cancel, _task) = tasks::register(&handle, task_id)?;
+            ingest_blocking(&handle, &path, &cancel).map_err(AppError::from)
+        }))
+        .await
+        .map_err(|e| e.to_string())?
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn get_cur_summary(app: AppHandle) -> CurSummary {
+    read_summary(&app)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType};
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    use super::*;
+
+    const LEGACY_CSV: &str = "\
+identity/LineItemId,lineItem/UsageStartDate,lineItem/UsageAccountId,lineItem/ProductCode,lineItem/LineItemType,lineItem/UnblendedCost,savingsPlan/SavingsPlanEffectiveCost
+1,2024-05-01T00:00:00Z,111,AmazonEC2,SavingsPlanCoveredUsage,10,6
+2,2024-05-02T00:00:00Z,111,AmazonEC2,SavingsPlanNegation,-10,
+3,2024-05-03T00:00:00Z,111,AmazonS3,Usage,2.5,
+4,2024-06-01T00:00:00Z,222,AmazonS3,Usage,1,
+5,,222,AmazonS3,Usage,1,
+";
+
+    fn temp_file(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("cur-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn ingest(path: &Path) -> Result<Aggregate, String> {
+        let mut report = |_, _| Ok(());
+        let aggregate = if path.extension().is_some_and(|e| e == "parquet") {
+            ingest_parquet(path, &mut report)
+        } else {
+            ingest_csv(path, &mut report)
+        };
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        aggregate
+    }
+
+    fn totals(aggregate: &Aggregate) -> Vec<(&str, &str, &str, f64, f64, u64)> {
+        aggregate
+            .totals
+            .iter()
+            .map(
+                |((month, account, service), (unblended, amortized, items))| {
+                    (
+                        month.as_str(),
+                        account.as_str(),
+                        service.as_str(),
+                        *unblended,
+                        *amortized,
+                        *items,
+                    )
+                },
+            )
+            .collect()
+    }
+
+    fn expected() -> Vec<(&'static str, &'static str, &'static str, f64, f64, u64)> {
+        vec![
+            ("2024-05", "111", "AmazonEC2", 0.0, 6.0, 2),
+            ("2024-05", "111", "AmazonS3", 2.5, 2.5, 1),
+            ("2024-06", "222", "AmazonS3", 1.0, 1.0, 1),
+        ]
+    }
+
+    #[test]
+    fn legacy_and_cur2_columns_normalize_alike() {
+        for (legacy, column) in [
+            ("lineItem/UsageStartDate", USAGE_START),
+            ("lineItem/UsageAccountId", ACCOUNT),
+            ("lineItem/ProductCode", SERVICE),
+            ("lineItem/UnblendedCost", COST),
+            ("lineItem/LineItemType", LINE_ITEM_TYPE),
+            ("reservation/ReservationARN", RESERVATION_ARN),
+            ("savingsPlan/SavingsPlanEffectiveCost", SP_EFFECTIVE_COST),
+            (
+                "reservation/UnusedAmortizedUpfrontFeeForBillingPeriod",
+                RI_UNUSED_UPFRONT,
+            ),
+        ] {
+            assert_eq!(normalize_column(legacy), column);
+            assert_eq!(normalize_column(column), column);
+        }
+    }
+
+    #[test]
+    fn plain_and_gzipped_csv_aggregate_alike() {
+        let plain = temp_file("report.csv");
+        std::fs::write(&plain, LEGACY_CSV).unwrap();
+        let aggregate = ingest(&plain).unwrap();
+        assert_eq!(totals(&aggregate), expected());
+        assert_eq!((aggregate.rows, aggregate.skipped), (5, 1));
+
+        let gzipped = temp_file("report.csv.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&gzipped).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(LEGACY_CSV.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+        assert_eq!(totals(&ingest(&gzipped).unwrap()), expected());
+    }
+
+    #[test]
+    fn missing_columns_are_tolerated_unless_required() {
+        let no_account = temp_file("report.csv");
+        std::fs::write(
+            &no_account,
+            "line_item_usage_start_date,line_item_product_code,line_item_unblended_cost\n\
+             2024-05-01,AmazonS3,2\n",
+        )
+        .unwrap();
+        let aggregate = ingest(&no_account).unwrap();
+        assert_eq!(
+            totals(&aggregate),
+            vec![("2024-05", "", "AmazonS3", 2.0, 2.0, 1)]
+        );
+
+        // Blended cost only: not something the aggregate can use.
+        let no_cost = temp_file("report.csv");
+        std::fs::write(
+            &no_cost,
+            "lineItem/UsageStartDate,lineItem/BlendedCost\n2024-05-01,2\n",
+        )
+        .unwrap();
+        assert!(ingest(&no_cost).err().unwrap().contains("missing"));
+    }
+
+    #[test]
+    fn parquet_columns_map_like_csv_headers() {
+        let path = temp_file("report.parquet");
+        let schema = parse_message_type(
+            "message schema {
+                REQUIRED BYTE_ARRAY line_item_usage_start_date (UTF8);
+                REQUIRED BYTE_ARRAY line_item_usage_account_id (UTF8);
+                REQUIRED BYTE_ARRAY line_item_product_code (UTF8);
+                REQUIRED BYTE_ARRAY line_item_line_item_type (UTF8);
+                REQUIRED DOUBLE line_item_unblended_cost;
+                REQUIRED DOUBLE savings_plan_savings_plan_effective_cost;
+                REQUIRED BYTE_ARRAY product_region (UTF8);
+            }",
+        )
+        .unwrap();
+        let text = |values: [&str; 4]| -> Vec<ByteArray> {
+            values.iter().map(|v| ByteArray::from(*v)).collect()
+        };
+        let columns = [
+            text(["2024-05-01", "2024-05-02", "2024-05-03", "2024-06-01"]),
+            text(["111", "111", "111", "222"]),
+            text(["AmazonEC2", "AmazonEC2", "AmazonS3", "AmazonS3"]),
+            text([
+                "SavingsPlanCoveredUsage",
+                "SavingsPlanNegation",
+                "Usage",
+                "Usage",
+            ]),
+        ];
+        let numbers = [vec![10.0, -10.0, 2.5, 1.0], vec![6.0, 0.0, 0.0, 0.0]];
+        let file = File::create(&path).unwrap();
+        let mut writer =
+            SerializedFileWriter::new(file, Arc::new(schema), Default::default()).unwrap();
+        let mut group = writer.next_row_group().unwrap();
+        let mut index = 0;
+        while let Some(mut column) = group.next_column().unwrap() {
+            match index {
+                0..=3 => {
+                    let values = &columns[index];
+                    column
+                        .typed::<ByteArrayType>()
+                        .write_batch(values, None, None)
+                }
+                4 | 5 => column
+                    .typed::<DoubleType>()
+                    .write_batch(&numbers[index - 4], None, None),
+                _ => {
+                    let values = text(["us-east-1"; 4]);
+                    column
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, None, None)
+                }
+            }
+            .unwrap();
+            column.close().unwrap();
+            index += 1;
+        }
+        group.close().unwrap();
+        writer.close().unwrap();
+
+        let aggregate = ingest(&path).unwrap();
+        assert_eq!(totals(&aggregate), expected());
+        assert_eq!((aggregate.rows, aggregate.skipped), (4, 0));
+    }
+
+    #[test]
+    fn commitments_are_amortized_over_covered_usage() {
+        let item = |line_item_type: &str, amounts: &[(&'static str, f64)]| LineItem {
+            line_item_type: line_item_type.into(),
+            amounts: amounts.iter().copied().collect(),
+            ..LineItem::default()
+        };
+        assert_eq!(item("Usage", &[]).amortized_cost(5.0), 5.0);
+        assert_eq!(
+            item("DiscountedUsage", &[(RI_EFFECTIVE_COST, 2.0)]).amortized_cost(0.0),
+            2.0
+        );
+        let ri_fee = item(
+            "RIFee",
+            &[(RI_UNUSED_UPFRONT, 3.0), (RI_UNUSED_RECURRING, 1.0)],
+        );
+        assert_eq!(ri_fee.amortized_cost(100.0), 4.0);
+        let sp_fee = item(
+            "SavingsPlanRecurringFee",
+            &[(SP_COMMITMENT, 10.0), (SP_USED_COMMITMENT, 7.0)],
+        );
+        assert_eq!(sp_fee.amortized_cost(10.0), 3.0);
+        assert_eq!(item("SavingsPlanUpfrontFee", &[]).amortized_cost(50.0), 0.0);
+
+        // Without the amounts, fees stay as billed.
+        assert_eq!(
+            item("SavingsPlanRecurringFee", &[]).amortized_cost(10.0),
+            10.0
+        );
+        assert_eq!(item("RIFee", &[]).amortized_cost(100.0), 100.0);
+
+        let upfront = LineItem {
+            reservation_arn: "arn:aws:ec2:us-east-1:111:reserved-instances/ri".into(),
+            ..item("Fee", &[])
+        };
+        assert_eq!(upfront.amortized_cost(500.0), 0.0);
+        assert_eq!(item("Fee", &[]).amortized_cost(20.0), 20.0);
+    }
+}
@@ -0,0 +1,158 @@
+//! Optional daily submission of cost KPIs to Datadog's metrics API.
+//!
+//! The backend has no spend or forecast data yet, so the submitted metrics
+//! are the estimated cost of the analyzed S3 storage, the estimated waste,
+//! and the finding count of the most recent run.
+
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::providers::{aws::AwsProvider, CloudProvider};
+use crate::{ca_trust, keyring_entry_for, settings, webhooks};
+
+pub(crate) const API_KEY_ACCOUNT: &str = "datadog-api-key";
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+const MAX_ATTEMPTS: u32 = 3;
+/// `type` value for gauges in the v2 series API.
+const GAUGE: u8 = 3;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DatadogSettings {
+    pub enabled: bool,
+    /// Datadog site, e.g. `datadoghq.com` or `datadoghq.eu`.
+    pub site: String,
+    /// Extra tags added to every series, e.g. `env:prod`.
+    pub tags: Vec<String>,
+}
+
+impl Default for DatadogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            site: "datadoghq.com".into(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+fn read_api_key() -> Option<String> {
+    keyring_entry_for(API_KEY_ACCOUNT).ok()?.get_password().ok()
+}
+
+fn series(cfg: &DatadogSettings) -> Result<serde_json::Value, String> {
+    let provider = AwsProvider;
+    let costs = provider.query_costs()?;
+    let findings = provider.recommendations()?.len();
+    let timestamp = chrono::Utc::now().timestamp();
+    let mut tags = vec!["source:aws-cost-optimizer".to_string()];
+    tags.extend(cfg.tags.iter().cloned());
+
+    let gauge = |metric: &str, value: f64| {
+        json!({
+            "metric": metric,
+            "type": GAUGE,
+            "points": [{ "timestamp": timestamp, "value": value }],
+            "tags": tags,
+        })
+    };
+    Ok(json!({
+        "series": [
+            gauge(
+                "aws_cost_optimizer.storage.estimated_monthly_cost",
+                costs.current_monthly_cost,
+            ),
+            gauge("aws_cost_optimizer.waste.estimated_monthly", costs.monthly_savings),
+            gauge("aws_cost_optimizer.findings", findings as f64),
+        ]
+    }))
+}
+
+fn submit(cfg: &DatadogSettings, api_key: &str) -> Result<(), String> {
+    let body = series(cfg)?;
+    let url = format!("https://api.{}/api/v2/series", cfg.site);
+    for attempt in 1..=MAX_ATTEMPTS {
+        match ca_trust::agent()
+            .post(&url)
+            .set("DD-API-KEY", api_key)
+            .send_json(&body)
+        {
+            Ok(_) => return Ok(()),
+            Err(ureq::Error::Status(code, _)) if code < 500 && code != 429 => {
+                return Err(format!("Datadog rejected the metrics (HTTP {code})"));
+            }
+            Err(err) if attempt == MAX_ATTEMPTS => return Err(err.to_string()),
+            Err(_) => std::thread::sleep(webhooks::backoff(attempt)),
+        }
+    }
+    Ok(())
+}
+
+/// Submits once per local calendar day while the integration is enabled.
+pub fn spawn_daily_submission(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_day: Option<NaiveDate> = None;
+        loop {
+            let today = chrono::Local::now().date_naive();
+            let cfg = settings::load(&app).datadog;
+            if cfg.enabled && last_day != Some(today) {
+                if let Some(api_key) = read_api_key() {
+                    match submit(&cfg, &api_key) {
+                        Ok(()) => last_day = Some(today),
+                        Err(err) => eprintln!("Datadog submission failed: {err}"),
+                    }
+                }
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_datadog_settings(app: AppHandle) -> DatadogSettings {
+    settings::load(&app).datadog
+}
+
+/// Saves the configuration. A non-empty `api_key` replaces the stored one.
+#[tauri::command]
+pub fn save_datadog_settings(
+    app: AppHandle,
+    config: DatadogSettings,
+    api_key: Option<String>,
+) -> CommandResult<()> {
+    if config.site.trim().is_empty() || config.site.contains('/') {
+        return Err(AppError::InvalidInput(
+            "Datadog site must be a host name such as datadoghq.com".into(),
+        ));
+    }
+    if let Some(key) = api_key.filter(|k| !k.is_empty()) {
+        keyring_entry_for(API_KEY_ACCOUNT)?
+            .set_password(&key)
+            .map_err(|e| e.to_string())?;
+    }
+    let mut all = settings::load(&app);
+    all.datadog = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
+
+/// Submits the metrics now, e.g. to verify the API key.
+#[tauri::command]
+pub async fn submit_datadog_metrics(app: AppHandle) -> CommandResult<()> {
+    let cfg = settings::load(&app).datadog;
+    let api_key =
+        read_api_key().ok_or_else(|| AppError::NotFound("No Datadog API key saved".into()))?;
+    tauri::async_runtime::spawn_blocking(move || submit(&cfg, &api_key))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(AppError::from)
+}
@@ -0,0 +1,295 @@
+//! Downloads of large reports the backend generated, straight to a path the
+//! user chose. The reply is streamed to `<dest>.<checksum>.part` as it
+//! arrives, with `download-progress` events; a connection that breaks is
+//! picked up again with a `Range` request, and so is a later call for the
+//! same export after a failure or cancel. The file only takes its name once
+//! its SHA-256 matches the one the backend recorded.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::{AppError, CommandResult};
+use crate::tasks::{self, CancelToken};
+use crate::{app_lock, backend, perf};
+
+const PROGRESS_EVENT: &str = "download-progress";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+const BUF_BYTES: usize = 1024 * 1024;
+/// Times a broken download is picked up again within one call.
+const MAX_RESUMES: u32 = 3;
+
+/// The backend's record of a generated report.
+#[derive(Deserialize, Clone, Debug)]
+struct ExportJob {
+    size: u64,
+    sha256: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadProgress {
+    pub job_id: String,
+    pub dest_path: String,
+    pub received_bytes: u64,
+    pub total_bytes: u64,
+    pub percent: f64,
+    pub done: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadResult {
+    pub job_id: String,
+    pub dest_path: String,
+    pub size: u64,
+    pub sha256: String,
+    /// Bytes already on disk from an earlier attempt.
+    pub resumed_from: u64,
+}
+
+/// Where the download of the export with this checksum collects.
+fn partial_path(dest: &Path, sha256: &str) -> PathBuf {
+    let tag: String = sha256
+        .chars()
+        .filter(char::is_ascii_hexdigit)
+        .take(12)
+        .collect();
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{tag}.part"));
+    dest.with_file_name(name)
+}
+
+/// Hashes what an earlier attempt left, returning its length. A leftover
+/// longer than the export cannot be part of it and is dropped.
+fn read_partial(part: &Path, size: u64, hasher: &mut Sha256) -> Result<u64, AppError> {
+    let mut file = match File::open(part) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+    if file.metadata()?.len() > size {
+        File::create(part)?;
+        return Ok(0);
+    }
+    let mut buf = vec![0u8; BUF_BYTES];
+    let mut read = 0;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            return Ok(read);
+        }
+        hasher.update(&buf[..n]);
+        read += n as u64;
+    }
+}
+
+struct Progress<'a> {
+    app: &'a AppHandle,
+    job_id: &'a str,
+    dest: &'a str,
+    total: u64,
+    last: Instant,
+}
+
+impl Progress<'_> {
+    fn report(&mut self, received: u64) {
+        if self.last.elapsed() >= PROGRESS_INTERVAL {
+            self.last = Instant::now();
+            self.emit(received, false);
+        }
+    }
+
+    fn emit(&self, received: u64, done: bool) {
+        let percent = match self.total {
+            0 => 100.0,
+            total => received as f64 / total as f64 * 100.0,
+        };
+        let _ = self.app.emit(
+            PROGRESS_EVENT,
+            DownloadProgress {
+                job_id: self.job_id.to_string(),
+                dest_path: self.dest.to_string(),
+                received_bytes: received,
+                total_bytes: self.total,
+                percent: percent.clamp(0.0, 100.0),
+                done,
+            },
+        );
+    }
+}
+
+/// Appends the rest of the export to `part`, from `received` on. Returns
+/// when the stream ends, which may be early.
+fn fetch(
+    path: &str,
+    part: &Path,
+    received: &mut u64,
+    hasher: &mut Sha256,
+    cancel: &CancelToken,
+    progress: &mut Progress,
+) -> Result<(), AppError> {
+    let range = format!("bytes={received}-");
+    let headers = [("Range", range.as_str())];
+    let headers: &[(&str, &str)] = if *received > 0 { &headers } else { &[] };
+    let (status, mut body) = backend::open_stream_with(path, headers)?;
+    let mut file = match status {
+        206 => OpenOptions::new().append(true).open(part)?,
+        // The whole file, the range notwithstanding: start over.
+        200 => {
+            *received = 0;
+            *hasher = Sha256::new();
+            File::create(part)?
+        }
+        _ => {
+            let mut reply = String::new();
+            let _ = body.read_to_string(&mut reply);
+            return Err(backend::sidecar_error(status, &reply));
+        }
+    };
+    let mut buf = vec![0u8; BUF_BYTES];
+    loop {
+        cancel.check()?;
+        let read = match body.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(AppError::Network(format!("The download broke off: {err}"))),
+        };
+        file.write_all(&buf[..read])?;
+        hasher.update(&buf[..read]);
+        *received += read as u64;
+        progress.report(*received);
+    }
+}
+
+/// Downloads export `job_id` to `dest`, resuming what an earlier attempt
+/// left. A failed or cancelled download keeps its partial file.
+pub fn download_blocking(
+    app: &AppHandle,
+    job_id: &str,
+    dest: &str,
+    cancel: &CancelToken,
+) -> Result<DownloadResult, AppError> {
+    let valid = !job_id.is_empty()
+        && job_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid export id {job_id:?}"
+        )));
+    }
+    let dest_path = Path::new(dest);
+    if dest_path.file_name().is_none() || dest_path.is_dir() {
+        return Err(AppError::InvalidInput(format!("{dest} names no file")));
+    }
+    let job: ExportJob = backend::get_json(&format!("/exports/{job_id}"))?;
+    let part = partial_path(dest_path, &job.sha256);
+    let mut hasher = Sha256::new();
+    let resumed_from = read_partial(&part, job.size, &mut hasher)?;
+    let mut received = resumed_from;
+    let mut progress = Progress {
+        app,
+        job_id,
+        dest,
+        total: job.size,
+        last: Instant::now(),
+    };
+    let target = format!("/exports/{job_id}/download");
+    let mut resumes = 0;
+    // An empty export still needs its (empty) file.
+    while received < job.size || !part.exists() {
+        cancel.check()?;
+        let err = match fetch(
+            &target,
+            &part,
+            &mut received,
+            &mut hasher,
+            cancel,
+            &mut progress,
+        ) {
+            Ok(()) if received >= job.size => break,
+            Ok(()) => AppError::Network("The backend ended the download early".into()),
+            Err(err) => err,
+        };
+        if !err.retryable() || resumes >= MAX_RESUMES {
+            return Err(err);
+        }
+        resumes += 1;
+        std::thread::sleep(Duration::from_millis(500 << resumes));
+    }
+    let sha256 = hex::encode(hasher.finalize());
+    if received != job.size || !sha256.eq_ignore_ascii_case(&job.sha256) {
+        let _ = std::fs::remove_file(&part);
+        return Err(AppError::Network(format!(
+            "The download of export {job_id} did not match its checksum; download it again"
+        )));
+    }
+    std::fs::rename(&part, dest_path)?;
+    progress.emit(received, true);
+    Ok(DownloadResult {
+        job_id: job_id.to_string(),
+        dest_path: dest.to_string(),
+        size: received,
+        sha256,
+        resumed_from,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Downloads a report the backend generated to `dest_path`. Pass a `task_id`
+/// to be able to stop it with `cancel_task`; calling again with the same
+/// export and path resumes.
+#[tauri::command]
+pub async fn download_export(
+    app: AppHandle,
+    job_id: String,
+    dest_path: String,
+    task_id: Option<String>,
+) -> CommandResult<DownloadResult> {
+    app_lock::ensure_unlocked(&app)?;
+    let handle = app.clone();
+    perf::measure(&app, "download_export", async move {
+        tauri::async_runtime::spawn_blocking(perf::blocking(move || {
+            let (cancel, _task) = tasks::register(&handle, task_id)?;
+            download_blocking(&handle, &job_id, &dest_path, &cancel)
+        }))
+        .await
+        .map_err(|e| e.to_string())?
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_files_are_hashed_and_kept_per_export() {
+        let dir = std::env::temp_dir().join(format!("downloads-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let part = partial_path(&dir.join("report.csv"), "0123456789abcdef");
+        assert_eq!(part, dir.join("report.csv.0123456789ab.part"));
+
+        assert_eq!(read_partial(&part, 10, &mut Sha256::new()).unwrap(), 0);
+        std::fs::write(&part, b"abc").unwrap();
+        let mut hasher = Sha256::new();
+        assert_eq!(read_partial(&part, 10, &mut hasher).unwrap(), 3);
+        assert_eq!(
+            hasher.finalize().as_slice(),
+            Sha256::digest(b"abc").as_slice()
+        );
+
+        // More than the export holds: not a prefix of it.
+        assert_eq!(read_partial(&part, 2, &mut Sha256::new()).unwrap(), 0);
+        assert_eq!(std::fs::metadata(&part).unwrap().len(), 0);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
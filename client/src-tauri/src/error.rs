@@ -0,0 +1,190 @@
+//! Error type returned by every Tauri command. It serializes as
+//! `{ kind, message, retryable, remediation, schema_version }` so the
+//! frontend can branch on `kind` instead of parsing messages.
+//!
+//! Internal helpers may still return `Result<_, String>`; those errors reach
+//! the frontend as `internal` unless the helper builds an [`AppError`] itself.
+
+use std::fmt;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::api_version::SCHEMA_VERSION;
+use crate::tasks::CANCELLED;
+
+pub type CommandResult<T> = Result<T, AppError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    /// No credentials saved, or AWS rejected the saved ones.
+    Credentials(String),
+    /// The credentials are valid but lack a permission.
+    AccessDenied(String),
+    /// AWS or the sidecar asked us to slow down.
+    Throttled(String),
+    /// A connection failed or timed out, or the service was briefly
+    /// unavailable.
+    Network(String),
+    /// The sidecar is not running or failed the request.
+    Sidecar(String),
+    /// Any other error returned by an AWS API.
+    Aws(String),
+    NotFound(String),
+    InvalidInput(String),
+    /// The request clashes with current state, e.g. a duplicate task id.
+    Conflict(String),
+    /// A self-imposed spending cap (e.g. on Cost Explorer requests) is spent.
+    BudgetExceeded(String),
+    /// The app lock is engaged; see `app_lock`.
+    Locked(String),
+    Cancelled,
+    Io(String),
+    Internal(String),
+}
+
+impl AppError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Credentials(_) => "credentials",
+            Self::AccessDenied(_) => "access_denied",
+            Self::Throttled(_) => "throttled",
+            Self::Network(_) => "network",
+            Self::Sidecar(_) => "sidecar",
+            Self::Aws(_) => "aws",
+            Self::NotFound(_) => "not_found",
+            Self::InvalidInput(_) => "invalid_input",
+            Self::Conflict(_) => "conflict",
+            Self::BudgetExceeded(_) => "budget_exceeded",
+            Self::Locked(_) => "locked",
+            Self::Cancelled => "cancelled",
+            Self::Io(_) => "io",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// Whether repeating the same call unchanged may succeed.
+    pub fn retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::Throttled(_) | Self::Network(_) | Self::Sidecar(_)
+        )
+    }
+
+    /// What the user can do about it, when there is something to do.
+    pub fn remediation(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Credentials(_) => "Check or re-enter your AWS credentials in Settings.",
+            Self::AccessDenied(_) => {
+                "Grant the missing permission to the IAM user or role, then retry."
+            }
+            Self::Throttled(_) => "AWS is rate limiting this account; wait a minute and retry.",
+            Self::Network(_) => "Check your network connection or proxy, then retry.",
+            Self::Sidecar(_) => {
+                "The local backend is not responding; restart the app if it persists."
+            }
+            Self::InvalidInput(_) => "Correct the highlighted input and try again.",
+            Self::BudgetExceeded(_) => {
+                "Raise the monthly Cost Explorer request cap in Settings, or wait for next month."
+            }
+            Self::Locked(_) => "Unlock the app to continue.",
+            _ => return None,
+        })
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Self::Credentials(m)
+            | Self::AccessDenied(m)
+            | Self::Throttled(m)
+            | Self::Network(m)
+            | Self::Sidecar(m)
+            | Self::Aws(m)
+            | Self::NotFound(m)
+            | Self::InvalidInput(m)
+            | Self::Conflict(m)
+            | Self::BudgetExceeded(m)
+            | Self::Locked(m)
+            | Self::Io(m)
+            | Self::Internal(m) => m,
+            Self::Cancelled => CANCELLED,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("AppError", 5)?;
+        s.serialize_field("kind", self.kind())?;
+        s.serialize_field("message", self.message())?;
+        s.serialize_field("retryable", &self.retryable())?;
+        s.serialize_field("remediation", &self.remediation())?;
+        s.serialize_field("schema_version", &SCHEMA_VERSION)?;
+        s.end()
+    }
+}
+
+/// Untyped errors from helpers. Cancellation is recognised by its message.
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        if message == CANCELLED {
+            Self::Cancelled
+        } else {
+            Self::Internal(message)
+        }
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => Self::NotFound(err.to_string()),
+            std::io::ErrorKind::PermissionDenied => Self::AccessDenied(err.to_string()),
+            _ => Self::Io(err.to_string()),
+        }
+    }
+}
+
+/// Lets typed errors flow through helpers that still return `String`.
+impl From<AppError> for String {
+    fn from(err: AppError) -> Self {
+        err.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_serialize_for_the_webview() {
+        let value = serde_json::to_value(AppError::Throttled("Rate exceeded".into())).unwrap();
+        assert_eq!(value["kind"], "throttled");
+        assert_eq!(value["message"], "Rate exceeded");
+        assert_eq!(value["retryable"], true);
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn cancellation_survives_string_helpers() {
+        assert_eq!(AppError::from(CANCELLED.to_string()), AppError::Cancelled);
+        assert!(matches!(
+            AppError::from("boom".to_string()),
+            AppError::Internal(_)
+        ));
+    }
+}
@@ -0,0 +1,107 @@
+//! App-level events derived from the sidecar's run history, fanned out to the
+//! configured outbound integrations.
+//!
+//! Runs change through many doors: the webview's requests, which all go
+//! through `backend_request`, the local REST API, gRPC, scheduled jobs and
+//! worker sidecars. Rather than hook each of them, the shell learns about
+//! scans by polling the run list and diffing run statuses, skipping polls
+//! while the sidecar sleeps for idleness.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::AppHandle;
+
+use crate::backend::{self, RunSummary};
+use crate::{pagerduty, plugins, providers, sidecar_idle, webhooks, websocket};
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    #[serde(rename = "scan.completed")]
+    ScanCompleted,
+    #[serde(rename = "run.scored")]
+    RunScored,
+    #[serde(rename = "run.executed")]
+    RunExecuted,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AppEvent {
+    pub event: EventKind,
+    pub timestamp: String,
+    pub data: serde_json::Value,
+}
+
+impl AppEvent {
+    pub fn new(event: EventKind, data: serde_json::Value) -> Self {
+        Self {
+            event,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            data,
+        }
+    }
+}
+
+/// Delivers an event to every subscribed integration. Every event marks a
+/// run change, so cached findings are dropped first.
+pub fn publish(app: &AppHandle, event: AppEvent) {
+    providers::invalidate_findings();
+    webhooks::dispatch(app, &event);
+    pagerduty::dispatch(app, &event);
+    websocket::broadcast(app, &event);
+    plugins::dispatch(app, &event);
+}
+
+// ---------------------------------------------------------------------------
+// Run watcher
+// ---------------------------------------------------------------------------
+
+fn run_event(run: &RunSummary, previous: Option<&str>) -> Option<AppEvent> {
+    let kind = match (previous, run.status.as_str()) {
+        (Some(before), now) if before == now => return None,
+        (None, _) => EventKind::ScanCompleted,
+        (_, "scored") => EventKind::RunScored,
+        (_, "executed") => EventKind::RunExecuted,
+        _ => return None,
+    };
+    Some(AppEvent::new(
+        kind,
+        json!({
+            "run_id": run.run_id,
+            "status": run.status,
+            "recommendation_count": run.recommendation_count,
+            "estimated_monthly_savings": run.estimated_monthly_savings,
+        }),
+    ))
+}
+
+/// Polls the run list in the background and publishes lifecycle events.
+/// Runs that already exist on the first successful poll are not announced.
+/// Polls are skipped while the sidecar sleeps for idleness.
+pub fn spawn_run_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut known: Option<HashMap<String, String>> = None;
+        loop {
+            if let Some(Ok(runs)) = sidecar_idle::unless_asleep(backend::list_runs) {
+                let current: HashMap<String, String> = runs
+                    .iter()
+                    .map(|r| (r.run_id.clone(), r.status.clone()))
+                    .collect();
+                if let Some(previous) = &known {
+                    for run in &runs {
+                        let before = previous.get(&run.run_id).map(String::as_str);
+                        if let Some(event) = run_event(run, before) {
+                            publish(&app, event);
+                        }
+                    }
+                }
+                known = Some(current);
+            }
+            std::thread::sleep(WATCH_INTERVAL);
+        }
+    });
+}
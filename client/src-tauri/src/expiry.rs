@@ -0,0 +1,230 @@
+//! Expiry of temporary credentials. A background loop watches the current
+//! keys' expiry; shortly before it, profiles derived by AssumeRole, role
+//! chains or IAM Identity Center are renewed the way they were obtained, and
+//! `credential_process` keys by running the helper again, and instance
+//! profile or container keys by asking for them again; each restarts the
+//! sidecar with the new keys. Keys that cannot be renewed (MFA sessions,
+//! renewals that fail) raise `credentials-expiring` once per expiry instead.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::aws::sdk_config_from;
+use crate::error::AppError;
+use crate::profiles::{self, ProfileOrigin};
+use crate::{
+    credential_process, identity_center, instance_credentials, read_credentials, settings, sts,
+    AwsCredentials,
+};
+
+pub const EXPIRING_EVENT: &str = "credentials-expiring";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Renewal starts this long before expiry, leaving room for retries.
+const RENEW_AHEAD: chrono::Duration = chrono::Duration::minutes(10);
+const WARN_AHEAD: chrono::Duration = chrono::Duration::minutes(15);
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CredentialsExpiring {
+    /// Active profile, if the keys belong to one.
+    pub profile: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub expired: bool,
+    /// Why the keys were not renewed.
+    pub reason: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Nothing,
+    Renew,
+    Warn,
+}
+
+fn action(expires_at: DateTime<Utc>, now: DateTime<Utc>, renewable: bool) -> Action {
+    let left = expires_at - now;
+    // Renewable keys only warn once renewal has failed.
+    let (ahead, act) = if renewable {
+        (RENEW_AHEAD, Action::Renew)
+    } else {
+        (WARN_AHEAD, Action::Warn)
+    };
+    if left <= ahead {
+        act
+    } else {
+        Action::Nothing
+    }
+}
+
+/// Why keys with this origin cannot be renewed unattended; `None` if they can.
+fn unrenewable_reason(origin: Option<&ProfileOrigin>) -> Option<&'static str> {
+    match origin {
+        Some(ProfileOrigin::AssumeRole {
+            source_profile: Some(_),
+            ..
+        })
+        | Some(ProfileOrigin::RoleChain {
+            source_profile: Some(_),
+            ..
+        })
+        | Some(ProfileOrigin::IdentityCenter { .. }) => None,
+        Some(ProfileOrigin::AssumeRole { .. }) | Some(ProfileOrigin::RoleChain { .. }) => {
+            Some("The role was assumed with unsaved keys; assume it again")
+        }
+        Some(ProfileOrigin::MfaSession { .. }) => {
+            Some("MFA sessions need a new code; start a new session")
+        }
+        None => Some("Temporary keys without a way to renew them; save new keys"),
+    }
+}
+
+fn renewable(origin: Option<&ProfileOrigin>) -> bool {
+    unrenewable_reason(origin).is_none()
+}
+
+/// New keys for a derived profile, obtained the way the current ones were.
+async fn renewed(origin: &ProfileOrigin) -> Result<AwsCredentials, AppError> {
+    match origin {
+        ProfileOrigin::AssumeRole {
+            source_profile: Some(source),
+            role_arn,
+            external_id,
+            session_name,
+        } => {
+            let config = sdk_config_from(profiles::read_profile(source)?).await;
+            let (creds, _) =
+                sts::assume(&config, role_arn, external_id.as_deref(), session_name).await?;
+            Ok(creds)
+        }
+        ProfileOrigin::RoleChain {
+            source_profile: Some(source),
+            hops,
+        } => {
+            let config = sdk_config_from(profiles::read_profile(source)?).await;
+            let (creds, _) = sts::assume_chain(&config, hops).await?;
+            Ok(creds)
+        }
+        ProfileOrigin::IdentityCenter {
+            account_id,
+            role_name,
+            region,
+        } => identity_center::renew(account_id, role_name, region).await,
+        other => Err(AppError::Credentials(
+            unrenewable_reason(Some(other)).unwrap_or_default().into(),
+        )),
+    }
+}
+
+async fn renew(app: &AppHandle, name: String, origin: ProfileOrigin) -> Result<(), AppError> {
+    let creds = renewed(&origin).await?;
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        profiles::store_and_activate(&handle, &name, &creds, Some(origin))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// One pass of the watch; returns the expiry it warned about, if any.
+async fn check(app: &AppHandle, warned: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    let expires_at = read_credentials(app)?.expires_at?;
+    let profile_settings = settings::load(app).credential_profiles;
+    let profile = profile_settings.active;
+    let origin = profile
+        .as_ref()
+        .and_then(|name| profile_settings.origins.get(name).cloned());
+
+    let process = credential_process::active_profile(app).is_some();
+    let instance = !process && instance_credentials::active_source(app).is_some();
+
+    let now = Utc::now();
+    let can_renew = process || instance || renewable(origin.as_ref());
+    let reason = match action(expires_at, now, can_renew) {
+        Action::Nothing => return None,
+        Action::Renew => {
+            let renewal = if instance {
+                instance_credentials::refresh(app).await
+            } else if process {
+                let handle = app.clone();
+                tauri::async_runtime::spawn_blocking(move || credential_process::refresh(&handle))
+                    .await
+                    .map_err(|e| AppError::from(e.to_string()))
+                    .and_then(|r| r)
+            } else {
+                let (Some(name), Some(origin)) = (profile.clone(), origin) else {
+                    return None;
+                };
+                renew(app, name, origin).await
+            };
+            match renewal {
+                Ok(()) => return None,
+                Err(err) => {
+                    eprintln!("credential renewal failed: {err}");
+                    format!("Renewal failed: {err}")
+                }
+            }
+        }
+        Action::Warn => unrenewable_reason(origin.as_ref())
+            .unwrap_or("Renewal has not succeeded yet")
+            .into(),
+    };
+    if warned == Some(expires_at) {
+        return warned;
+    }
+    let _ = app.emit(
+        EXPIRING_EVENT,
+        CredentialsExpiring {
+            profile,
+            expires_at,
+            expired: expires_at <= now,
+            reason,
+        },
+    );
+    Some(expires_at)
+}
+
+/// Watches the current keys' expiry for the lifetime of the app.
+pub fn spawn_expiry_watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut warned = None;
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            warned = check(&app, warned).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renews_before_warning_and_warns_otherwise() {
+        let now: DateTime<Utc> = "2025-03-10T12:00:00Z".parse().unwrap();
+        let in_mins = |m| now + chrono::Duration::minutes(m);
+        assert_eq!(action(in_mins(60), now, true), Action::Nothing);
+        assert_eq!(action(in_mins(12), now, true), Action::Nothing);
+        assert_eq!(action(in_mins(12), now, false), Action::Warn);
+        assert_eq!(action(in_mins(5), now, true), Action::Renew);
+        assert_eq!(action(in_mins(-5), now, false), Action::Warn);
+    }
+
+    #[test]
+    fn only_saved_sources_and_identity_center_renew() {
+        let role = |source: Option<&str>| ProfileOrigin::AssumeRole {
+            source_profile: source.map(str::to_string),
+            role_arn: "arn:aws:iam::123456789012:role/Audit".into(),
+            external_id: None,
+            session_name: "cost-optimizer".into(),
+        };
+        assert!(renewable(Some(&role(Some("work")))));
+        assert!(!renewable(Some(&role(None))));
+        assert!(!renewable(Some(&ProfileOrigin::MfaSession {
+            source_profile: None,
+            serial_number: "arn:aws:iam::123456789012:mfa/dev".into(),
+        })));
+        assert!(!renewable(None));
+    }
+}
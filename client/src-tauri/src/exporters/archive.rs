@@ -0,0 +1,123 @@
+//! Optional central archive: rendered reports are uploaded to an S3
+//! bucket/prefix after they are written locally, using either the app's
+//! credentials or a separate AWS CLI profile.
+
+use std::path::Path;
+
+use aws_sdk_s3::primitives::ByteStream;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::ExportSummary;
+use crate::aws::{sdk_config, sdk_config_from, sdk_error, send};
+use crate::error::{AppError, CommandResult};
+use crate::tasks::CancelToken;
+use crate::{aws_cli, settings};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ReportArchiveSettings {
+    pub enabled: bool,
+    pub bucket: String,
+    /// Key prefix, e.g. `finops/reports`. Uploads land under
+    /// `<prefix>/<YYYY-MM-DD>/<file name>`.
+    pub prefix: String,
+    /// AWS CLI profile to upload with; `None` uses the app's credentials.
+    pub profile: Option<String>,
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => "application/json",
+        Some("csv") => "text/csv",
+        Some("md") => "text/markdown",
+        Some("ics") => "text/calendar",
+        _ => "application/octet-stream",
+    }
+}
+
+fn object_key(prefix: &str, path: &Path) -> String {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "report".into());
+    let date = chrono::Utc::now().format("%Y-%m-%d");
+    match prefix.trim_matches('/') {
+        "" => format!("{date}/{file_name}"),
+        prefix => format!("{prefix}/{date}/{file_name}"),
+    }
+}
+
+async fn upload(
+    app: &AppHandle,
+    cfg: &ReportArchiveSettings,
+    path: &Path,
+) -> Result<String, String> {
+    let config = match cfg.profile.as_deref().filter(|p| !p.is_empty()) {
+        Some(profile) => sdk_config_from(aws_cli::profile_credentials(app, profile)?).await,
+        None => sdk_config(app).await?,
+    };
+    let key = object_key(&cfg.prefix, path);
+    // Reports are small; holding the bytes lets each retry resend the body.
+    let body = std::fs::read(path).map_err(|e| e.to_string())?;
+    let client = aws_sdk_s3::Client::new(&config);
+    send(|| {
+        client
+            .put_object()
+            .bucket(&cfg.bucket)
+            .key(&key)
+            .content_type(content_type(path))
+            .body(ByteStream::from(body.clone()))
+            .send()
+    })
+    .await
+    .map_err(sdk_error)?;
+    Ok(format!("s3://{}/{key}", cfg.bucket))
+}
+
+/// Uploads a written export when archiving is enabled. Upload failures
+/// (including cancellation) are reported on the summary; the local file is
+/// kept either way.
+pub(crate) async fn archive(
+    app: &AppHandle,
+    mut summary: ExportSummary,
+    cancel: &CancelToken,
+) -> ExportSummary {
+    let cfg = settings::load(app).report_archive;
+    if !cfg.enabled || cfg.bucket.is_empty() {
+        return summary;
+    }
+    match cancel
+        .run(upload(app, &cfg, Path::new(&summary.path)))
+        .await
+    {
+        Ok(url) => summary.uploaded_to = Some(url),
+        Err(err) => summary.upload_error = Some(err),
+    }
+    summary
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_report_archive_settings(app: AppHandle) -> ReportArchiveSettings {
+    settings::load(&app).report_archive
+}
+
+#[tauri::command]
+pub fn save_report_archive_settings(
+    app: AppHandle,
+    config: ReportArchiveSettings,
+) -> CommandResult<()> {
+    if config.enabled && config.bucket.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "An S3 bucket is required to archive reports".into(),
+        ));
+    }
+    let mut all = settings::load(&app);
+    all.report_archive = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
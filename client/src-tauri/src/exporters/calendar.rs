@@ -0,0 +1,97 @@
+//! iCalendar export of scan schedules and the monthly cost-review reminder,
+//! as recurring events calendar apps can import.
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use tauri::AppHandle;
+
+use super::{write_export, ExportSummary};
+use crate::error::{AppError, CommandResult};
+use crate::scheduler::{Frequency, ScanSchedule};
+use crate::settings;
+
+const WEEKDAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+fn rrule(frequency: &Frequency) -> String {
+    match frequency {
+        Frequency::Daily => "FREQ=DAILY".into(),
+        Frequency::Weekly { weekday } => {
+            format!("FREQ=WEEKLY;BYDAY={}", WEEKDAYS[*weekday as usize % 7])
+        }
+        Frequency::Monthly { day } => format!("FREQ=MONTHLY;BYMONTHDAY={day}"),
+    }
+}
+
+/// First occurrence today or later, used as DTSTART (floating local time).
+fn first_occurrence(frequency: &Frequency, time: NaiveTime, today: NaiveDate) -> NaiveDateTime {
+    today
+        .iter_days()
+        .find(|date| frequency.matches(*date))
+        .unwrap_or(today)
+        .and_time(time)
+}
+
+fn event(uid: &str, summary: &str, description: &str, start: NaiveDateTime, rule: &str) -> String {
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+    [
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{uid}@aws-cost-optimizer"),
+        format!("DTSTAMP:{stamp}"),
+        format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")),
+        "DURATION:PT30M".into(),
+        format!("RRULE:{rule}"),
+        format!("SUMMARY:{summary}"),
+        format!("DESCRIPTION:{description}"),
+        "END:VEVENT".into(),
+    ]
+    .join("\r\n")
+}
+
+fn scan_event(schedule: &ScanSchedule, today: NaiveDate) -> String {
+    let scope = if schedule.include_buckets.is_empty() {
+        "all buckets".to_string()
+    } else {
+        schedule.include_buckets.join("\\, ")
+    };
+    event(
+        &format!("scan-{}", schedule.id),
+        &format!("Cost scan: {}", schedule.name),
+        &format!("Scheduled AWS Cost Optimizer scan of {scope}."),
+        first_occurrence(&schedule.frequency, schedule.time(), today),
+        &rrule(&schedule.frequency),
+    )
+}
+
+#[tauri::command]
+pub fn export_schedule_calendar(app: AppHandle, dest_path: String) -> CommandResult<ExportSummary> {
+    let cfg = settings::load(&app).schedules;
+    let today = chrono::Local::now().date_naive();
+
+    let mut events: Vec<String> = cfg
+        .scans
+        .iter()
+        .filter(|s| s.enabled)
+        .map(|s| scan_event(s, today))
+        .collect();
+    if let Some(reminder) = &cfg.review_reminder {
+        let frequency = Frequency::Monthly {
+            day: reminder.day_of_month,
+        };
+        let time = NaiveTime::from_hms_opt(reminder.hour, 0, 0).unwrap_or(NaiveTime::MIN);
+        events.push(event(
+            "monthly-cost-review",
+            "Monthly cost review",
+            "Review AWS Cost Optimizer findings and savings.",
+            first_occurrence(&frequency, time, today),
+            &rrule(&frequency),
+        ));
+    }
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".into(),
+        "PRODID:-//AWS Cost Optimizer//Schedules//EN".into(),
+    ];
+    lines.extend(events.iter().cloned());
+    lines.push("END:VCALENDAR".into());
+    write_export(&dest_path, &(lines.join("\r\n") + "\r\n"), events.len()).map_err(AppError::from)
+}
@@ -0,0 +1,173 @@
+//! FinOps FOCUS export: the storage costs priced by scored runs as a
+//! FOCUS 1.0 CSV, one charge row per analyzed resource and month.
+//!
+//! The sidecar estimates costs from object sizes at list price rather than
+//! reading the bill, so `BilledCost` and `EffectiveCost` are estimates and
+//! account and SKU columns are left null. Optimizer data rides along in
+//! `x_`-prefixed custom columns, as the spec allows.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use tauri::AppHandle;
+
+use super::archive::archive;
+use super::{write_export, ExportSummary};
+use crate::backend::{self, RunDetails};
+use crate::error::CommandResult;
+use crate::partition::Partition;
+use crate::tasks::{self, CancelToken};
+use crate::{perf, read_credentials};
+
+const COLUMNS: [&str; 22] = [
+    "BillingPeriodStart",
+    "BillingPeriodEnd",
+    "ChargePeriodStart",
+    "ChargePeriodEnd",
+    "BillingAccountId",
+    "BillingCurrency",
+    "BilledCost",
+    "EffectiveCost",
+    "ListCost",
+    "ContractedCost",
+    "ChargeCategory",
+    "ChargeDescription",
+    "ProviderName",
+    "PublisherName",
+    "InvoiceIssuerName",
+    "ServiceCategory",
+    "ServiceName",
+    "RegionId",
+    "ResourceId",
+    "ResourceName",
+    "x_RecommendationType",
+    "x_EstimatedMonthlySavings",
+];
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// `[first of month, first of next month)` as FOCUS datetimes.
+fn month_bounds(month: NaiveDate) -> (String, String) {
+    let next = if month.month() == 12 {
+        NaiveDate::from_ymd_opt(month.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(month.year(), month.month() + 1, 1)
+    }
+    .unwrap_or(month);
+    let fmt = |d: NaiveDate| format!("{}T00:00:00Z", d.format("%Y-%m-%d"));
+    (fmt(month), fmt(next))
+}
+
+fn render_rows(out: &mut String, month: NaiveDate, run: &RunDetails, region: &str) -> usize {
+    let (start, end) = month_bounds(month);
+    let mut rows = 0;
+    for estimate in &run.savings_details {
+        let Some(rec) = run
+            .recommendations
+            .iter()
+            .find(|r| r.id == estimate.recommendation_id)
+        else {
+            continue;
+        };
+        let partition = Partition::of_region(region).as_str();
+        let resource_id = match &rec.key {
+            Some(key) => format!("arn:{partition}:s3:::{}/{key}", rec.bucket),
+            None => format!("arn:{partition}:s3:::{}", rec.bucket),
+        };
+        let cost = format!("{:.6}", estimate.current_monthly_cost);
+        let fields = [
+            start.as_str(),
+            end.as_str(),
+            start.as_str(),
+            end.as_str(),
+            "",
+            "USD",
+            &cost,
+            &cost,
+            &cost,
+            &cost,
+            "Usage",
+            "Estimated S3 storage",
+            "AWS",
+            "AWS",
+            "AWS",
+            "Storage",
+            "Amazon Simple Storage Service",
+            region,
+            &resource_id,
+            &rec.bucket,
+            &rec.recommendation_type,
+            &format!("{:.6}", estimate.monthly_savings),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+        rows += 1;
+    }
+    rows
+}
+
+fn export_blocking(
+    region: &str,
+    dest_path: &str,
+    cancel: &CancelToken,
+) -> Result<ExportSummary, String> {
+    // Latest scored run per calendar month; earlier runs in the same month
+    // priced the same storage and would double count it.
+    let mut by_month: BTreeMap<NaiveDate, RunDetails> = BTreeMap::new();
+    for summary in backend::list_runs()? {
+        cancel.check()?;
+        let Ok(updated) = DateTime::parse_from_rfc3339(&summary.updated_at) else {
+            continue;
+        };
+        let updated = updated.with_timezone(&Utc);
+        let Some(month) = NaiveDate::from_ymd_opt(updated.year(), updated.month(), 1) else {
+            continue;
+        };
+        let newer = by_month
+            .get(&month)
+            .is_some_and(|kept| kept.updated_at >= summary.updated_at);
+        if newer {
+            continue;
+        }
+        let run = backend::get_run(&summary.run_id)?;
+        if !run.savings_details.is_empty() {
+            by_month.insert(month, run);
+        }
+    }
+
+    let mut out = COLUMNS.join(",") + "\n";
+    let rows = by_month
+        .iter()
+        .map(|(month, run)| render_rows(&mut out, *month, run, region))
+        .sum();
+    cancel.check()?;
+    write_export(dest_path, &out, rows)
+}
+
+/// Writes the estimated storage costs of scored runs as FOCUS CSV.
+#[tauri::command]
+pub async fn export_focus_data(
+    app: AppHandle,
+    dest_path: String,
+    task_id: Option<String>,
+) -> CommandResult<ExportSummary> {
+    let (cancel, _task) = tasks::register(&app, task_id)?;
+    let region = read_credentials(&app).map(|c| c.region).unwrap_or_default();
+    let token = cancel.clone();
+    perf::measure(&app, "export_focus_data", async {
+        let summary = tauri::async_runtime::spawn_blocking(perf::blocking(move || {
+            export_blocking(&region, &dest_path, &token)
+        }))
+        .await
+        .map_err(|e| e.to_string())??;
+        Ok(archive(&app, summary, &cancel).await)
+    })
+    .await
+}
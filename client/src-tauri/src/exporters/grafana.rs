@@ -0,0 +1,115 @@
+//! Grafana export: run history as time series for the JSON (SimpleJSON)
+//! datasource, as flat rows for the Infinity plugin, or as CSV.
+
+use serde::Deserialize;
+use serde_json::json;
+use tauri::AppHandle;
+
+use super::archive::archive;
+use super::{epoch_millis, write_export, ExportSummary};
+use crate::backend::{self, RunSummary};
+use crate::error::CommandResult;
+use crate::perf;
+use crate::tasks::{self, CancelToken};
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum GrafanaFormat {
+    /// `[{ "target", "datapoints": [[value, epoch_ms]] }]`
+    Timeseries,
+    /// Flat JSON rows with an ISO `time` column (Infinity plugin).
+    Table,
+    Csv,
+}
+
+fn render_timeseries(runs: &[(i64, &RunSummary)]) -> String {
+    let series = |target: &str, value: fn(&RunSummary) -> f64| {
+        json!({
+            "target": target,
+            "datapoints": runs
+                .iter()
+                .map(|(ts, run)| json!([value(run), ts]))
+                .collect::<Vec<_>>(),
+        })
+    };
+    json!([
+        series("estimated_monthly_savings", |r| r.estimated_monthly_savings),
+        series("recommendation_count", |r| r.recommendation_count as f64),
+    ])
+    .to_string()
+}
+
+fn render_table(runs: &[(i64, &RunSummary)]) -> String {
+    let rows: Vec<_> = runs
+        .iter()
+        .map(|(_, run)| {
+            json!({
+                "time": run.updated_at,
+                "run_id": run.run_id,
+                "status": run.status,
+                "recommendation_count": run.recommendation_count,
+                "estimated_monthly_savings": run.estimated_monthly_savings,
+            })
+        })
+        .collect();
+    serde_json::Value::Array(rows).to_string()
+}
+
+fn render_csv(runs: &[(i64, &RunSummary)]) -> String {
+    let mut out =
+        String::from("time,run_id,status,recommendation_count,estimated_monthly_savings\n");
+    for (_, run) in runs {
+        out.push_str(&format!(
+            "{},{},{},{},{:.2}\n",
+            run.updated_at,
+            run.run_id,
+            run.status,
+            run.recommendation_count,
+            run.estimated_monthly_savings
+        ));
+    }
+    out
+}
+
+fn export_blocking(
+    dest_path: &str,
+    format: GrafanaFormat,
+    cancel: &CancelToken,
+) -> Result<ExportSummary, String> {
+    let runs = backend::list_runs()?;
+    // Oldest first; runs with unparseable timestamps are dropped.
+    let mut points: Vec<(i64, &RunSummary)> = runs
+        .iter()
+        .filter_map(|run| epoch_millis(&run.updated_at).map(|ts| (ts, run)))
+        .collect();
+    points.sort_by_key(|(ts, _)| *ts);
+
+    let contents = match format {
+        GrafanaFormat::Timeseries => render_timeseries(&points),
+        GrafanaFormat::Table => render_table(&points),
+        GrafanaFormat::Csv => render_csv(&points),
+    };
+    cancel.check()?;
+    write_export(dest_path, &contents, points.len())
+}
+
+/// Writes run history to `dest_path` in a Grafana-consumable format.
+#[tauri::command]
+pub async fn export_grafana_data(
+    app: AppHandle,
+    dest_path: String,
+    format: GrafanaFormat,
+    task_id: Option<String>,
+) -> CommandResult<ExportSummary> {
+    let (cancel, _task) = tasks::register(&app, task_id)?;
+    let token = cancel.clone();
+    perf::measure(&app, "export_grafana_data", async {
+        let summary = tauri::async_runtime::spawn_blocking(perf::blocking(move || {
+            export_blocking(&dest_path, format, &token)
+        }))
+        .await
+        .map_err(|e| e.to_string())??;
+        Ok(archive(&app, summary, &cancel).await)
+    })
+    .await
+}
@@ -0,0 +1,52 @@
+//! File exporters that turn the sidecar's run history into formats other
+//! tools can ingest. Every exporter writes to a caller-chosen path.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+pub mod archive;
+pub mod calendar;
+pub mod focus;
+pub mod grafana;
+pub mod well_architected;
+
+/// Returned by every export command.
+#[derive(Serialize, Clone, Debug)]
+pub struct ExportSummary {
+    pub path: String,
+    pub records: usize,
+    /// `s3://` URL when the report was archived (see [`archive`]).
+    pub uploaded_to: Option<String>,
+    pub upload_error: Option<String>,
+}
+
+pub(crate) fn write_export(
+    path: &str,
+    contents: &str,
+    records: usize,
+) -> Result<ExportSummary, String> {
+    let path = Path::new(path);
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    // Written aside and renamed so a failed or cancelled export never leaves
+    // a truncated file at `path`.
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    std::fs::write(&partial, contents).map_err(|e| e.to_string())?;
+    std::fs::rename(&partial, path).map_err(|e| e.to_string())?;
+    Ok(ExportSummary {
+        path: path.display().to_string(),
+        records,
+        uploaded_to: None,
+        upload_error: None,
+    })
+}
+
+/// Parses the sidecar's ISO-8601 timestamps into Unix milliseconds.
+pub(crate) fn epoch_millis(timestamp: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.timestamp_millis())
+        .ok()
+}
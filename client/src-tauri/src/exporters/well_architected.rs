@@ -0,0 +1,137 @@
+//! Well-Architected export: a run's findings grouped under the Cost
+//! Optimization pillar questions and best practices they provide evidence
+//! for, as a Markdown report to attach to a WA review.
+
+use std::collections::BTreeMap;
+
+use tauri::AppHandle;
+
+use super::archive::archive;
+use super::{write_export, ExportSummary};
+use crate::backend::{self, Recommendation};
+use crate::error::CommandResult;
+use crate::perf;
+use crate::tasks::{self, CancelToken};
+
+struct BestPractice {
+    question: &'static str,
+    id: &'static str,
+    title: &'static str,
+}
+
+const QUESTIONS: [(&str, &str); 2] = [
+    ("COST04", "How do you decommission resources?"),
+    (
+        "COST06",
+        "How do you meet cost targets when you select resource type, size and number?",
+    ),
+];
+
+/// Maps a sidecar recommendation type to the best practice it addresses.
+fn best_practice(recommendation_type: &str) -> Option<BestPractice> {
+    let (question, id, title) = match recommendation_type {
+        "add_lifecycle_policy" => ("COST04", "COST04-BP05", "Enforce data retention policies"),
+        "delete_stale_object" => ("COST04", "COST04-BP03", "Decommission resources"),
+        "delete_incomplete_upload" => (
+            "COST04",
+            "COST04-BP04",
+            "Decommission resources automatically",
+        ),
+        "change_storage_class" => (
+            "COST06",
+            "COST06-BP01",
+            "Perform cost modeling and select the most cost-effective storage",
+        ),
+        _ => return None,
+    };
+    Some(BestPractice {
+        question,
+        id,
+        title,
+    })
+}
+
+fn render(run_id: &str, recommendations: &[Recommendation]) -> String {
+    // question -> best practice id -> (title, findings)
+    let mut grouped: BTreeMap<&str, BTreeMap<&str, (&str, Vec<&Recommendation>)>> = BTreeMap::new();
+    for rec in recommendations {
+        if let Some(bp) = best_practice(&rec.recommendation_type) {
+            grouped
+                .entry(bp.question)
+                .or_default()
+                .entry(bp.id)
+                .or_insert((bp.title, Vec::new()))
+                .1
+                .push(rec);
+        }
+    }
+
+    let mut out = format!(
+        "# Well-Architected Cost Optimization evidence\n\nRun `{run_id}`, generated {}.\n",
+        chrono::Utc::now().to_rfc3339()
+    );
+    for (question, prompt) in QUESTIONS {
+        out.push_str(&format!("\n## {question}: {prompt}\n"));
+        let Some(practices) = grouped.get(question) else {
+            out.push_str("\nNo findings in this run.\n");
+            continue;
+        };
+        for (id, (title, findings)) in practices {
+            let savings: f64 = findings.iter().map(|r| r.estimated_monthly_savings).sum();
+            out.push_str(&format!(
+                "\n### {id} {title}\n\n{} finding(s), est. ${savings:.2}/month.\n\n",
+                findings.len()
+            ));
+            out.push_str("| Bucket | Key | Risk | Action | Est. savings/mo |\n");
+            out.push_str("|---|---|---|---|---|\n");
+            for rec in findings {
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | ${:.2} |\n",
+                    rec.bucket,
+                    rec.key.as_deref().unwrap_or("—"),
+                    rec.risk_level,
+                    rec.recommended_action.replace('|', "\\|"),
+                    rec.estimated_monthly_savings
+                ));
+            }
+        }
+    }
+    out
+}
+
+pub(crate) fn export_blocking(
+    run_id: &str,
+    dest_path: &str,
+    cancel: &CancelToken,
+) -> Result<ExportSummary, String> {
+    let run = backend::get_run(run_id)?;
+    cancel.check()?;
+    let mapped = run
+        .recommendations
+        .iter()
+        .filter(|r| best_practice(&r.recommendation_type).is_some())
+        .count();
+    write_export(dest_path, &render(run_id, &run.recommendations), mapped)
+}
+
+/// Writes a Markdown report of a run's findings, one section per Cost
+/// Optimization pillar question.
+#[tauri::command]
+pub async fn export_well_architected_report(
+    app: AppHandle,
+    run_id: String,
+    dest_path: String,
+    task_id: Option<String>,
+) -> CommandResult<ExportSummary> {
+    let (cancel, _task) = tasks::register(&app, task_id)?;
+    let token = cancel.clone();
+    perf::measure(&app, "export_well_architected_report", async {
+        let summary = tauri::async_runtime::spawn_blocking(perf::blocking(move || {
+            export_blocking(&run_id, &dest_path, &token)
+        }))
+        .await
+        .map_err(|e| e.to_string())??;
+        Ok(archive(&app, summary, &cancel).await)
+    })
+    .await
+}
@@ -0,0 +1,128 @@
+//! Checks, once the sidecar answers, that nothing but this user's processes
+//! can reach it: a TCP sidecar must not answer on the machine's network
+//! address (as one bound to 0.0.0.0 would), and a socket sidecar's directory
+//! must be closed to other users. Problems are emitted as `backend-exposed`
+//! and kept for [`get_backend_exposure`], for a webview that loads later.
+
+use std::net::{IpAddr, SocketAddr, TcpStream, UdpSocket};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::backend::{self, Endpoint};
+
+pub const EXPOSED_EVENT: &str = "backend-exposed";
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+static LAST: Mutex<Option<BackendExposure>> = Mutex::new(None);
+
+/// Payload of `backend-exposed`.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendExposure {
+    /// Where the sidecar listens.
+    pub location: String,
+    pub problems: Vec<String>,
+}
+
+/// The address other machines reach this one on, found by routing a UDP
+/// socket (no packet is sent).
+fn network_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+fn tcp_problems(port: u16) -> Vec<String> {
+    let Some(ip) = network_address() else {
+        return Vec::new();
+    };
+    let addr = SocketAddr::new(ip, port);
+    if TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok() {
+        vec![format!(
+            "The backend answers on {addr}, so other machines on this network can reach it"
+        )]
+    } else {
+        Vec::new()
+    }
+}
+
+/// What is wrong with a socket directory of `mode` owned by `owner`.
+#[cfg(unix)]
+fn socket_dir_problems(mode: u32, owner: u32, uid: u32) -> Vec<String> {
+    let mut problems = Vec::new();
+    if mode & 0o077 != 0 {
+        problems.push(format!(
+            "The backend socket's directory is open to other users (mode {:o})",
+            mode & 0o777
+        ));
+    }
+    if owner != uid {
+        problems.push("The backend socket's directory belongs to another user".into());
+    }
+    problems
+}
+
+#[cfg(unix)]
+fn socket_problems(socket: &std::path::Path) -> Vec<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let Some(dir) = socket.parent() else {
+        return Vec::new();
+    };
+    match std::fs::metadata(dir) {
+        // SAFETY: getuid has no preconditions.
+        Ok(meta) => socket_dir_problems(meta.mode(), meta.uid(), unsafe { libc::getuid() }),
+        Err(err) => vec![format!("Cannot check the backend socket: {err}")],
+    }
+}
+
+/// Checks where the sidecar listens, announcing any exposure.
+pub(crate) fn audit(app: &AppHandle) {
+    // A remote backend is reached over the network on purpose.
+    if crate::remote_backend::active().is_some() {
+        *LAST.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        return;
+    }
+    let problems = match backend::endpoint() {
+        Endpoint::Tcp(port) => tcp_problems(port),
+        #[cfg(unix)]
+        Endpoint::Socket(socket) => socket_problems(&socket),
+    };
+    let exposure = (!problems.is_empty()).then(|| BackendExposure {
+        location: backend::location(),
+        problems,
+    });
+    *LAST.lock().unwrap_or_else(|e| e.into_inner()) = exposure.clone();
+    if let Some(exposure) = exposure {
+        eprintln!("backend exposure: {:?}", exposure.problems);
+        let _ = app.emit(EXPOSED_EVENT, exposure);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Problems found by the last check of the running sidecar, if any.
+#[tauri::command]
+pub fn get_backend_exposure() -> Option<BackendExposure> {
+    LAST.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_dirs_must_be_private() {
+        assert!(socket_dir_problems(0o40700, 501, 501).is_empty());
+        let open = socket_dir_problems(0o40755, 501, 501);
+        assert_eq!(open.len(), 1);
+        assert!(open[0].contains("755"), "{open:?}");
+        assert_eq!(socket_dir_problems(0o40700, 0, 501).len(), 1);
+    }
+}
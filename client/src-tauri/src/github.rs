@@ -0,0 +1,233 @@
+//! GitHub integration: opens one issue per selected recommendation in a
+//! configured repository and remembers the link so the same finding is never
+//! filed twice.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::backend::{self, Recommendation};
+use crate::error::{AppError, CommandResult};
+use crate::{ca_trust, keyring_entry_for, settings};
+
+const GITHUB_API: &str = "https://api.github.com";
+pub(crate) const TOKEN_ACCOUNT: &str = "github-token";
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct GithubSettings {
+    /// Target repository as `owner/name`.
+    pub repo: String,
+    /// Labels applied to every issue.
+    pub default_labels: Vec<String>,
+    /// Extra labels keyed by risk level (`low` / `medium` / `high`).
+    pub risk_labels: BTreeMap<String, String>,
+    /// Extra labels keyed by recommendation type (e.g. `delete_stale_object`).
+    pub type_labels: BTreeMap<String, String>,
+}
+
+/// Back-link between a recommendation and the issue opened for it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GithubIssueLink {
+    pub recommendation_id: String,
+    pub run_id: String,
+    pub number: u64,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct CreatedIssue {
+    number: u64,
+    html_url: String,
+}
+
+// ---------------------------------------------------------------------------
+// Storage helpers
+// ---------------------------------------------------------------------------
+
+fn links_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("github_issues.json")
+}
+
+fn read_links(app: &AppHandle) -> BTreeMap<String, GithubIssueLink> {
+    std::fs::read_to_string(links_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_links(app: &AppHandle, links: &BTreeMap<String, GithubIssueLink>) -> Result<(), String> {
+    let path = links_path(app);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(links).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+fn read_token() -> Result<String, String> {
+    keyring_entry_for(TOKEN_ACCOUNT)?
+        .get_password()
+        .map_err(|_| "No GitHub token saved".to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Issue rendering
+// ---------------------------------------------------------------------------
+
+fn issue_labels(cfg: &GithubSettings, rec: &Recommendation) -> Vec<String> {
+    let mut labels = cfg.default_labels.clone();
+    for extra in [
+        cfg.risk_labels.get(&rec.risk_level),
+        cfg.type_labels.get(&rec.recommendation_type),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !labels.contains(extra) {
+            labels.push(extra.clone());
+        }
+    }
+    labels
+}
+
+fn issue_title(rec: &Recommendation) -> String {
+    let target = match &rec.key {
+        Some(key) => format!("s3://{}/{}", rec.bucket, key),
+        None => format!("s3://{}", rec.bucket),
+    };
+    format!("[cost] {}: {}", rec.recommendation_type, target)
+}
+
+fn issue_body(run_id: &str, rec: &Recommendation) -> String {
+    format!(
+        "{reason}\n\n\
+         **Recommended action:** {action}\n\
+         **Risk level:** {risk}\n\
+         **Estimated monthly savings:** ${savings:.2}\n\n\
+         ---\n\
+         Opened by AWS Cost Optimizer from run `{run_id}`, recommendation `{id}`.",
+        reason = rec.reason,
+        action = rec.recommended_action,
+        risk = rec.risk_level,
+        savings = rec.estimated_monthly_savings,
+        id = rec.id,
+    )
+}
+
+fn create_issue(
+    token: &str,
+    cfg: &GithubSettings,
+    run_id: &str,
+    rec: &Recommendation,
+) -> Result<GithubIssueLink, String> {
+    let created: CreatedIssue = ca_trust::agent()
+        .post(&format!("{GITHUB_API}/repos/{}/issues", cfg.repo))
+        .set("Authorization", &format!("Bearer {token}"))
+        .set("Accept", "application/vnd.github+json")
+        .set("User-Agent", "aws-cost-optimizer")
+        .send_json(json!({
+            "title": issue_title(rec),
+            "body": issue_body(run_id, rec),
+            "labels": issue_labels(cfg, rec),
+        }))
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+
+    Ok(GithubIssueLink {
+        recommendation_id: rec.id.clone(),
+        run_id: run_id.to_string(),
+        number: created.number,
+        url: created.html_url,
+    })
+}
+
+fn create_issues_blocking(
+    app: &AppHandle,
+    run_id: &str,
+    recommendation_ids: &[String],
+) -> Result<Vec<GithubIssueLink>, String> {
+    let cfg = settings::load(app).github;
+    if !cfg.repo.contains('/') {
+        return Err("GitHub repository is not configured (expected owner/name)".into());
+    }
+    let token = read_token()?;
+    let run = backend::get_run(run_id)?;
+
+    let mut links = read_links(app);
+    let mut result = Vec::new();
+    for rec in run
+        .recommendations
+        .iter()
+        .filter(|r| recommendation_ids.contains(&r.id))
+    {
+        // Already filed: return the existing link instead of a duplicate issue.
+        if let Some(existing) = links.get(&rec.id) {
+            result.push(existing.clone());
+            continue;
+        }
+        let link = create_issue(&token, &cfg, run_id, rec)?;
+        links.insert(rec.id.clone(), link.clone());
+        // Persist after every issue so a mid-batch failure keeps earlier links.
+        write_links(app, &links)?;
+        result.push(link);
+    }
+    Ok(result)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_github_settings(app: AppHandle) -> GithubSettings {
+    settings::load(&app).github
+}
+
+/// Saves the GitHub configuration. A non-empty `token` replaces the stored one.
+#[tauri::command]
+pub fn save_github_settings(
+    app: AppHandle,
+    config: GithubSettings,
+    token: Option<String>,
+) -> CommandResult<()> {
+    if let Some(token) = token.filter(|t| !t.is_empty()) {
+        keyring_entry_for(TOKEN_ACCOUNT)?
+            .set_password(&token)
+            .map_err(|e| e.to_string())?;
+    }
+    let mut all = settings::load(&app);
+    all.github = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
+
+/// Opens issues for the given recommendations of a run and returns the links.
+#[tauri::command]
+pub async fn create_github_issues(
+    app: AppHandle,
+    run_id: String,
+    recommendation_ids: Vec<String>,
+) -> CommandResult<Vec<GithubIssueLink>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        create_issues_blocking(&app, &run_id, &recommendation_ids)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(AppError::from)
+}
+
+/// Returns the issues previously opened for recommendations of a run.
+#[tauri::command]
+pub fn list_github_issue_links(app: AppHandle, run_id: String) -> Vec<GithubIssueLink> {
+    read_links(&app)
+        .into_values()
+        .filter(|link| link.run_id == run_id)
+        .collect()
+}
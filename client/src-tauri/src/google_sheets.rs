@@ -0,0 +1,193 @@
+//! Google Sheets publishing: appends a cost summary row to a configured
+//! spreadsheet tab after every scheduled scan, authenticated as a Google
+//! service account (its JSON key lives in the OS keychain).
+//!
+//! Row columns: time, run ID, status, findings, estimated monthly savings.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::{backend, keyring_entry_for, settings};
+
+pub(crate) const KEY_ACCOUNT: &str = "google-service-account";
+const SHEETS_API: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+const SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+const JWT_GRANT: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SheetsSettings {
+    pub enabled: bool,
+    /// The ID from the spreadsheet URL (`/spreadsheets/d/<id>/edit`).
+    pub spreadsheet_id: String,
+    /// Tab the rows are appended to; must exist and be shared with the
+    /// service account.
+    pub sheet: String,
+}
+
+impl Default for SheetsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            spreadsheet_id: String::new(),
+            sheet: "Cost summaries".into(),
+        }
+    }
+}
+
+/// The fields of a service-account key file the app needs.
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+fn read_key() -> Result<ServiceAccountKey, String> {
+    let raw = keyring_entry_for(KEY_ACCOUNT)?
+        .get_password()
+        .map_err(|_| "No Google service account key saved".to_string())?;
+    serde_json::from_str(&raw).map_err(|e| format!("Invalid service account key: {e}"))
+}
+
+fn access_token(key: &ServiceAccountKey) -> Result<String, String> {
+    let now = chrono::Utc::now().timestamp();
+    let claims = Claims {
+        iss: &key.client_email,
+        scope: SCOPE,
+        aud: &key.token_uri,
+        iat: now,
+        exp: now + 3600,
+    };
+    let signing_key = jsonwebtoken::EncodingKey::from_rsa_pem(key.private_key.as_bytes())
+        .map_err(|e| format!("Invalid service account private key: {e}"))?;
+    let assertion = jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
+        &claims,
+        &signing_key,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let token: TokenResponse = ureq::post(&key.token_uri)
+        .send_form(&[("grant_type", JWT_GRANT), ("assertion", &assertion)])
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    Ok(token.access_token)
+}
+
+/// Percent-encodes an A1 range for use as a URL path segment.
+fn encode_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn append_row(cfg: &SheetsSettings, row: serde_json::Value) -> Result<(), String> {
+    if cfg.spreadsheet_id.is_empty() || cfg.sheet.is_empty() {
+        return Err("Google Sheets spreadsheet and tab are not configured".into());
+    }
+    let token = access_token(&read_key()?)?;
+    let range = format!("'{}'!A1", cfg.sheet.replace('\'', "''"));
+    ureq::post(&format!(
+        "{SHEETS_API}/{}/values/{}:append",
+        cfg.spreadsheet_id,
+        encode_segment(&range)
+    ))
+    .query("valueInputOption", "USER_ENTERED")
+    .query("insertDataOption", "INSERT_ROWS")
+    .set("Authorization", &format!("Bearer {token}"))
+    .send_json(json!({ "values": [row] }))
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn summary_row(run_id: &str) -> Result<serde_json::Value, String> {
+    let run = backend::get_run(run_id)?;
+    let savings: f64 = run
+        .recommendations
+        .iter()
+        .map(|r| r.estimated_monthly_savings)
+        .sum();
+    Ok(json!([
+        run.updated_at,
+        run.run_id,
+        run.status,
+        run.recommendations.len(),
+        (savings * 100.0).round() / 100.0,
+    ]))
+}
+
+/// Appends the run's summary when publishing is enabled. Called by the
+/// scheduler after each scheduled scan; failures are logged, not raised.
+pub fn publish_scheduled(app: &AppHandle, run_id: &str) {
+    let cfg = settings::load(app).google_sheets;
+    if !cfg.enabled {
+        return;
+    }
+    if let Err(err) = summary_row(run_id).and_then(|row| append_row(&cfg, row)) {
+        eprintln!("Google Sheets publish for run {run_id} failed: {err}");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_sheets_settings(app: AppHandle) -> SheetsSettings {
+    settings::load(&app).google_sheets
+}
+
+/// Saves the configuration. A non-empty `service_account_key` (the full JSON
+/// key file) replaces the stored one.
+#[tauri::command]
+pub fn save_sheets_settings(
+    app: AppHandle,
+    config: SheetsSettings,
+    service_account_key: Option<String>,
+) -> CommandResult<()> {
+    if let Some(key) = service_account_key.filter(|k| !k.is_empty()) {
+        serde_json::from_str::<ServiceAccountKey>(&key)
+            .map_err(|e| format!("Invalid service account key: {e}"))?;
+        keyring_entry_for(KEY_ACCOUNT)?
+            .set_password(&key)
+            .map_err(|e| e.to_string())?;
+    }
+    let mut all = settings::load(&app);
+    all.google_sheets = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
+
+/// Appends a run's summary now, regardless of the `enabled` flag.
+#[tauri::command]
+pub async fn publish_sheets_summary(app: AppHandle, run_id: String) -> CommandResult<()> {
+    let cfg = settings::load(&app).google_sheets;
+    tauri::async_runtime::spawn_blocking(move || append_row(&cfg, summary_row(&run_id)?))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(AppError::from)
+}
@@ -0,0 +1,278 @@
+//! Optional gRPC service (schema in `proto/cost_optimizer.proto`) offering the
+//! same data as the local REST API plus a streamed scan. It shares the REST
+//! API's bearer token and, like it, only binds to 127.0.0.1.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{Request, Response, Status};
+
+use crate::error::{AppError, CommandResult};
+use crate::{backend, local_api, settings};
+
+pub mod proto {
+    tonic::include_proto!("costoptimizer.v1");
+}
+
+use proto::cost_optimizer_server::{CostOptimizer, CostOptimizerServer};
+use proto::scan_progress::Stage;
+
+const DEFAULT_PORT: u16 = 50051;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GrpcSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for GrpcSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+/// Shutdown handle of the running server, if any.
+pub struct GrpcState(pub Mutex<Option<oneshot::Sender<()>>>);
+
+// ---------------------------------------------------------------------------
+// Conversions
+// ---------------------------------------------------------------------------
+
+impl From<backend::RunSummary> for proto::RunSummary {
+    fn from(run: backend::RunSummary) -> Self {
+        Self {
+            run_id: run.run_id,
+            status: run.status,
+            recommendation_count: run.recommendation_count,
+            estimated_monthly_savings: run.estimated_monthly_savings,
+            updated_at: run.updated_at,
+        }
+    }
+}
+
+impl From<backend::Recommendation> for proto::Recommendation {
+    fn from(rec: backend::Recommendation) -> Self {
+        Self {
+            id: rec.id,
+            bucket: rec.bucket,
+            key: rec.key,
+            recommendation_type: rec.recommendation_type,
+            risk_level: rec.risk_level,
+            reason: rec.reason,
+            recommended_action: rec.recommended_action,
+            estimated_monthly_savings: rec.estimated_monthly_savings,
+        }
+    }
+}
+
+impl From<backend::RunDetails> for proto::Run {
+    fn from(run: backend::RunDetails) -> Self {
+        Self {
+            run_id: run.run_id,
+            status: run.status,
+            recommendations: run.recommendations.into_iter().map(Into::into).collect(),
+            created_at: run.created_at,
+            updated_at: run.updated_at,
+        }
+    }
+}
+
+fn progress(stage: Stage, message: impl Into<String>) -> proto::ScanProgress {
+    proto::ScanProgress {
+        stage: stage.into(),
+        message: message.into(),
+        ..Default::default()
+    }
+}
+
+fn status(err: AppError) -> Status {
+    let message = err.to_string();
+    match err {
+        AppError::Credentials(_) => Status::unauthenticated(message),
+        AppError::AccessDenied(_) => Status::permission_denied(message),
+        AppError::Throttled(_) => Status::resource_exhausted(message),
+        AppError::Network(_) | AppError::Sidecar(_) => Status::unavailable(message),
+        AppError::NotFound(_) => Status::not_found(message),
+        AppError::InvalidInput(_) => Status::invalid_argument(message),
+        AppError::Conflict(_) => Status::failed_precondition(message),
+        AppError::BudgetExceeded(_) => Status::resource_exhausted(message),
+        AppError::Locked(_) => Status::permission_denied(message),
+        AppError::Cancelled => Status::cancelled(message),
+        AppError::Aws(_) | AppError::Io(_) | AppError::Internal(_) => Status::internal(message),
+    }
+}
+
+/// Runs a blocking sidecar call off the async executor.
+async fn blocking<T, F>(f: F) -> Result<T, Status>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(status)
+}
+
+// ---------------------------------------------------------------------------
+// Service
+// ---------------------------------------------------------------------------
+
+struct Service;
+
+#[tonic::async_trait]
+impl CostOptimizer for Service {
+    async fn list_runs(
+        &self,
+        _request: Request<proto::ListRunsRequest>,
+    ) -> Result<Response<proto::ListRunsResponse>, Status> {
+        let runs = blocking(backend::list_runs).await?;
+        Ok(Response::new(proto::ListRunsResponse {
+            runs: runs.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_run(
+        &self,
+        request: Request<proto::GetRunRequest>,
+    ) -> Result<Response<proto::Run>, Status> {
+        let run_id = request.into_inner().run_id;
+        let run = blocking(move || backend::get_run(&run_id)).await?;
+        Ok(Response::new(run.into()))
+    }
+
+    type StartScanStream = ReceiverStream<Result<proto::ScanProgress, Status>>;
+
+    async fn start_scan(
+        &self,
+        request: Request<proto::StartScanRequest>,
+    ) -> Result<Response<Self::StartScanStream>, Status> {
+        let req = request.into_inner();
+        let body = json!({
+            "include_buckets": req.include_buckets,
+            "exclude_buckets": req.exclude_buckets,
+            "max_objects_per_bucket": if req.max_objects_per_bucket == 0 { 1000 } else { req.max_objects_per_bucket },
+        });
+
+        let (tx, rx) = mpsc::channel(4);
+        tauri::async_runtime::spawn(async move {
+            let _ = tx.send(Ok(progress(Stage::Started, "Scan started"))).await;
+            let result = blocking(move || {
+                backend::post_json::<_, backend::ScanResponse>("/optimizer/scan", &body)
+            })
+            .await;
+            let update = match result {
+                Ok(scan) => {
+                    let message = match scan.errors.len() {
+                        0 => "Scan completed".to_string(),
+                        n => format!("Scan completed with {n} failed check(s)"),
+                    };
+                    proto::ScanProgress {
+                        run_id: Some(scan.run_id),
+                        recommendation_count: scan.recommendations.len() as u64,
+                        ..progress(Stage::Completed, message)
+                    }
+                }
+                Err(status) => progress(Stage::Failed, status.message()),
+            };
+            let _ = tx.send(Ok(update)).await;
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Rejects calls without the shared bearer token in `authorization` metadata.
+#[derive(Clone)]
+struct TokenAuth(String);
+
+impl tonic::service::Interceptor for TokenAuth {
+    fn call(&mut self, req: Request<()>) -> Result<Request<()>, Status> {
+        let provided = req
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        match provided {
+            Some(provided) if local_api::token_matches(&self.0, provided) => Ok(req),
+            _ => Err(Status::unauthenticated("missing or invalid bearer token")),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Server lifecycle
+// ---------------------------------------------------------------------------
+
+fn stop(state: &GrpcState) -> Result<(), String> {
+    if let Some(shutdown) = state.0.lock().map_err(|e| e.to_string())?.take() {
+        let _ = shutdown.send(());
+    }
+    Ok(())
+}
+
+/// Starts (or restarts) the service according to the saved settings.
+pub fn apply(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<GrpcState>();
+    stop(&state)?;
+
+    let cfg = settings::load(app).grpc;
+    if !cfg.enabled {
+        return Ok(());
+    }
+
+    let token = local_api::ensure_token()?;
+    // Bind synchronously so a taken port is reported to the caller.
+    let listener = std::net::TcpListener::bind(("127.0.0.1", cfg.port))
+        .map_err(|e| format!("Could not bind port {}: {e}", cfg.port))?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    *state.0.lock().map_err(|e| e.to_string())? = Some(shutdown_tx);
+
+    let service = CostOptimizerServer::with_interceptor(Service, TokenAuth(token));
+
+    tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(err) => {
+                eprintln!("gRPC listener failed: {err}");
+                return;
+            }
+        };
+        let result = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                let _ = shutdown_rx.await;
+            })
+            .await;
+        if let Err(err) = result {
+            eprintln!("gRPC server stopped: {err}");
+        }
+    });
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_grpc_settings(app: AppHandle) -> GrpcSettings {
+    settings::load(&app).grpc
+}
+
+#[tauri::command]
+pub fn save_grpc_settings(app: AppHandle, config: GrpcSettings) -> CommandResult<()> {
+    let mut all = settings::load(&app);
+    all.grpc = config;
+    settings::save(&app, &all)?;
+    apply(&app).map_err(AppError::from)
+}
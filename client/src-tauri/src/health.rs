@@ -0,0 +1,347 @@
+//! One-call health report for the settings health panel: the keychain, the
+//! stored credentials, the sidecar and its run database, network
+//! reachability of AWS, and when data was last synced. Every check runs even
+//! when an earlier one fails, so the panel can show all problems at once.
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::aws::{account_id, sdk_config};
+use crate::error::{AppError, CommandResult};
+use crate::partition::Partition;
+use crate::{backend, jobs, profiles, read_credentials, sso, AwsCredentials, SidecarState};
+
+/// Upper bound for the credential check, which may try several STS regions.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// A last sync older than this is flagged.
+const STALE_SYNC_DAYS: i64 = 7;
+const FALLBACK_REGION: &str = "us-east-1";
+
+/// Ordered by severity, so the overall status is the maximum.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    /// Not checked, because something it depends on is down.
+    Unknown,
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct HealthCheck {
+    pub status: HealthStatus,
+    pub detail: String,
+    pub remediation: Option<&'static str>,
+}
+
+impl HealthCheck {
+    fn ok(detail: impl Into<String>) -> Self {
+        Self::new(HealthStatus::Ok, detail, None)
+    }
+
+    fn unknown(detail: impl Into<String>) -> Self {
+        Self::new(HealthStatus::Unknown, detail, None)
+    }
+
+    fn warning(detail: impl Into<String>, remediation: &'static str) -> Self {
+        Self::new(HealthStatus::Warning, detail, Some(remediation))
+    }
+
+    fn error(detail: impl Into<String>, remediation: &'static str) -> Self {
+        Self::new(HealthStatus::Error, detail, Some(remediation))
+    }
+
+    fn new(
+        status: HealthStatus,
+        detail: impl Into<String>,
+        remediation: Option<&'static str>,
+    ) -> Self {
+        Self {
+            status,
+            detail: detail.into(),
+            remediation,
+        }
+    }
+}
+
+impl From<&AppError> for HealthCheck {
+    fn from(err: &AppError) -> Self {
+        Self::new(HealthStatus::Error, err.to_string(), err.remediation())
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AppHealth {
+    /// Worst status of the checks below.
+    pub status: HealthStatus,
+    pub checked_at: DateTime<Utc>,
+    pub keyring: HealthCheck,
+    pub credentials: HealthCheck,
+    /// Account the credentials resolved to.
+    pub account_id: Option<String>,
+    /// Session-token credentials, which stop working when the session ends.
+    pub temporary_credentials: bool,
+    pub sidecar: HealthCheck,
+    pub database: HealthCheck,
+    pub network: HealthCheck,
+    pub last_sync: HealthCheck,
+    /// Newest scan run or successfully finished job.
+    pub last_sync_at: Option<DateTime<Utc>>,
+}
+
+// ---------------------------------------------------------------------------
+// Checks
+// ---------------------------------------------------------------------------
+
+fn keyring_check(app: &AppHandle) -> HealthCheck {
+    match profiles::read_active(app) {
+        Ok(Some(_)) => HealthCheck::ok("Credentials are stored in the OS keychain"),
+        Ok(None) => HealthCheck::warning(
+            "No credentials in the keychain yet",
+            "Save AWS credentials in Settings.",
+        ),
+        Err(err) => HealthCheck::error(
+            format!("Keychain unavailable: {err}"),
+            "Unlock the OS keychain or allow the app to access it.",
+        ),
+    }
+}
+
+async fn credentials_check(app: &AppHandle) -> (HealthCheck, Option<String>) {
+    if read_credentials(app).is_none() {
+        return (
+            HealthCheck::warning(
+                "No AWS credentials saved",
+                "Save AWS credentials in Settings.",
+            ),
+            None,
+        );
+    }
+    let verified = tokio::time::timeout(CHECK_TIMEOUT, async {
+        account_id(&sdk_config(app).await?).await
+    })
+    .await;
+    match verified {
+        Ok(Ok(account)) => (
+            HealthCheck::ok(format!("Valid for account {account}")),
+            Some(account),
+        ),
+        Ok(Err(err)) => ((&err).into(), None),
+        Err(_) => (HealthCheck::unknown("AWS did not answer in time"), None),
+    }
+}
+
+fn sidecar_check(app: &AppHandle, up: bool, has_credentials: bool) -> HealthCheck {
+    if up {
+        return HealthCheck::ok(format!("Responding at {}", backend::location()));
+    }
+    if crate::remote_backend::active().is_some() {
+        return HealthCheck::error(
+            format!(
+                "The remote backend at {} is not answering",
+                backend::location()
+            ),
+            "Check the backend URL in Settings and your network connection.",
+        );
+    }
+    let spawned = app
+        .state::<SidecarState>()
+        .0
+        .lock()
+        .is_ok_and(|child| child.is_some());
+    if spawned {
+        HealthCheck::error(
+            "Running but not answering its health check",
+            "Restart the app if this persists.",
+        )
+    } else if !has_credentials {
+        HealthCheck::warning(
+            "Not started; it starts once credentials are saved",
+            "Save AWS credentials in Settings.",
+        )
+    } else if !sso::access_granted(app) {
+        HealthCheck::warning(
+            "Not started; waiting for single sign-on",
+            "Sign in with your organization account.",
+        )
+    } else {
+        HealthCheck::error("Not running", "Restart the app.")
+    }
+}
+
+#[derive(Deserialize)]
+struct DatabaseHealth {
+    problems: Vec<String>,
+}
+
+fn database_check(sidecar_up: bool) -> HealthCheck {
+    if !sidecar_up {
+        return HealthCheck::unknown("Not checked while the sidecar is down");
+    }
+    match backend::get_json::<DatabaseHealth>("/health/database") {
+        Ok(health) if health.problems.is_empty() => {
+            HealthCheck::ok("Run database passed its integrity check")
+        }
+        Ok(health) => HealthCheck::error(
+            format!("Run database is damaged: {}", health.problems.join("; ")),
+            "Restore the run database from a backup, or remove it to start over.",
+        ),
+        Err(err) => (&err).into(),
+    }
+}
+
+/// Whether an AWS endpoint accepts TCP connections; independent of the
+/// credentials, so it tells network trouble apart from rejected keys.
+fn network_check(region: &str) -> HealthCheck {
+    const CHECK_NETWORK: &str = "Check your network connection or proxy, then retry.";
+    let suffix = Partition::of_region(region).dns_suffix();
+    let host = format!("sts.{region}.{suffix}");
+    let addrs = match (host.as_str(), 443).to_socket_addrs() {
+        Ok(addrs) => addrs,
+        Err(err) => {
+            return HealthCheck::error(format!("Cannot resolve {host}: {err}"), CHECK_NETWORK)
+        }
+    };
+    for addr in addrs {
+        if TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok() {
+            return HealthCheck::ok(format!("{host} is reachable"));
+        }
+    }
+    HealthCheck::error(format!("Cannot connect to {host}"), CHECK_NETWORK)
+}
+
+fn last_sync_at(app: &AppHandle, sidecar_up: bool) -> Option<DateTime<Utc>> {
+    let runs = sidecar_up
+        .then(backend::list_runs)
+        .and_then(Result::ok)
+        .unwrap_or_default();
+    runs.iter()
+        .filter_map(|run| DateTime::parse_from_rfc3339(&run.updated_at).ok())
+        .map(|at| at.with_timezone(&Utc))
+        .chain(jobs::last_success(app))
+        .max()
+}
+
+fn sync_check(at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> HealthCheck {
+    const RUN_SCAN: &str = "Run a scan, or add a schedule in Settings.";
+    let Some(at) = at else {
+        return HealthCheck::warning("No successful sync yet", RUN_SCAN);
+    };
+    let days = (now - at).num_days();
+    if days >= STALE_SYNC_DAYS {
+        HealthCheck::warning(format!("Last synced {days} days ago"), RUN_SCAN)
+    } else {
+        HealthCheck::ok(format!("Last synced {}", at.format("%Y-%m-%d %H:%M UTC")))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn get_app_health(app: AppHandle) -> CommandResult<AppHealth> {
+    let creds = read_credentials(&app);
+    let temporary_credentials = creds.as_ref().is_some_and(AwsCredentials::is_temporary);
+    let region = creds
+        .as_ref()
+        .map(|c| c.region.clone())
+        .filter(|r| !r.is_empty())
+        .unwrap_or_else(|| FALLBACK_REGION.into());
+
+    let handle = app.clone();
+    let local = tauri::async_runtime::spawn_blocking(move || {
+        let up = backend::healthy();
+        (
+            keyring_check(&handle),
+            sidecar_check(&handle, up, creds.is_some()),
+            database_check(up),
+            network_check(&region),
+            last_sync_at(&handle, up),
+        )
+    });
+    let (credentials, account_id) = credentials_check(&app).await;
+    let (keyring, sidecar, database, network, last_sync_at) =
+        local.await.map_err(|e| e.to_string())?;
+
+    let now = Utc::now();
+    let last_sync = sync_check(last_sync_at, now);
+    let status = [
+        &keyring,
+        &credentials,
+        &sidecar,
+        &database,
+        &network,
+        &last_sync,
+    ]
+    .iter()
+    .map(|check| check.status)
+    .max()
+    .unwrap_or(HealthStatus::Ok);
+    Ok(AppHealth {
+        status,
+        checked_at: now,
+        keyring,
+        credentials,
+        account_id,
+        temporary_credentials,
+        sidecar,
+        database,
+        network,
+        last_sync,
+        last_sync_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn sync_age_is_flagged_after_a_week() {
+        let now: DateTime<Utc> = "2025-03-10T12:00:00Z".parse().unwrap();
+        let at = |s: &str| Some(s.parse().unwrap());
+        assert_eq!(sync_check(None, now).status, HealthStatus::Warning);
+        assert_eq!(
+            sync_check(at("2025-03-09T08:00:00Z"), now).status,
+            HealthStatus::Ok
+        );
+        let stale = sync_check(at("2025-03-01T08:00:00Z"), now);
+        assert_eq!(stale.status, HealthStatus::Warning);
+        assert_eq!(stale.detail, "Last synced 9 days ago");
+    }
+
+    #[test]
+    fn severity_orders_statuses() {
+        let worst = [
+            HealthStatus::Unknown,
+            HealthStatus::Error,
+            HealthStatus::Warning,
+        ]
+        .into_iter()
+        .max();
+        assert_eq!(worst, Some(HealthStatus::Error));
+        assert!(HealthStatus::Ok < HealthStatus::Unknown);
+    }
+
+    #[test]
+    fn database_problems_are_reported() {
+        let _sidecar = mock::install_sidecar("health/database_damaged.json");
+        let check = database_check(true);
+        assert_eq!(check.status, HealthStatus::Error);
+        assert!(
+            check.detail.contains("Page 4 is never used"),
+            "{}",
+            check.detail
+        );
+
+        assert_eq!(database_check(false).status, HealthStatus::Unknown);
+    }
+}
@@ -0,0 +1,326 @@
+//! How the shell decides the sidecar is up: the endpoint it polls, how long
+//! the sidecar gets to start, and how often the UI checks again. The values
+//! are saved with the other settings. Overrides for this machine go in the
+//! local data dir, which Windows does not roam with the user's profile. A
+//! slow laptop can then be given more time without slowing crash reports on
+//! the user's other machines.
+//!
+//! [`get_backend_status`] reports liveness (the sidecar process exists) apart
+//! from readiness (its health endpoint answers 2xx).
+
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::sidecar_watchdog::{self, BackendPhase};
+use crate::{backend, settings};
+
+const OVERRIDES_FILE: &str = "health_check.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct HealthCheckSettings {
+    /// Polled under the API prefix; ready once it answers 2xx.
+    pub path: String,
+    pub request_timeout_ms: u64,
+    /// Pause between polls while the sidecar starts.
+    pub poll_interval_ms: u64,
+    /// How long the sidecar may take to come up at launch, when the OS may
+    /// still be unpacking the bundled server.
+    pub startup_timeout_secs: u64,
+    /// How long a restart, e.g. after new credentials, may take.
+    pub restart_timeout_secs: u64,
+    /// How often the UI checks again once the app is running.
+    pub ui_interval_secs: u64,
+}
+
+impl Default for HealthCheckSettings {
+    fn default() -> Self {
+        Self {
+            path: "/health".into(),
+            request_timeout_ms: 2_000,
+            poll_interval_ms: 300,
+            startup_timeout_secs: 30,
+            restart_timeout_secs: 15,
+            ui_interval_secs: 10,
+        }
+    }
+}
+
+impl HealthCheckSettings {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        if !self.path.starts_with('/')
+            || self.path.contains("..")
+            || self
+                .path
+                .contains(|c: char| c.is_whitespace() || c == '?' || c == '#')
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Health check path must be a plain path starting with '/', got {:?}",
+                self.path
+            )));
+        }
+        let ranges = [
+            ("Check timeout", self.request_timeout_ms, 100, 60_000, "ms"),
+            ("Poll interval", self.poll_interval_ms, 50, 10_000, "ms"),
+            ("Startup timeout", self.startup_timeout_secs, 1, 600, "s"),
+            ("Restart timeout", self.restart_timeout_secs, 1, 600, "s"),
+            ("UI check interval", self.ui_interval_secs, 1, 3_600, "s"),
+        ];
+        for (name, value, min, max, unit) in ranges {
+            if !(min..=max).contains(&value) {
+                return Err(AppError::InvalidInput(format!(
+                    "{name} must be between {min} and {max} {unit}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Values for this machine only; unset ones fall back to the settings.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct HealthCheckOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poll_interval_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup_timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ui_interval_secs: Option<u64>,
+}
+
+fn merge(settings: &HealthCheckSettings, overrides: &HealthCheckOverrides) -> HealthCheckSettings {
+    HealthCheckSettings {
+        path: overrides
+            .path
+            .clone()
+            .unwrap_or_else(|| settings.path.clone()),
+        request_timeout_ms: overrides
+            .request_timeout_ms
+            .unwrap_or(settings.request_timeout_ms),
+        poll_interval_ms: overrides
+            .poll_interval_ms
+            .unwrap_or(settings.poll_interval_ms),
+        startup_timeout_secs: overrides
+            .startup_timeout_secs
+            .unwrap_or(settings.startup_timeout_secs),
+        restart_timeout_secs: overrides
+            .restart_timeout_secs
+            .unwrap_or(settings.restart_timeout_secs),
+        ui_interval_secs: overrides
+            .ui_interval_secs
+            .unwrap_or(settings.ui_interval_secs),
+    }
+}
+
+/// The values in force, read by code that has no [`AppHandle`].
+static CURRENT: RwLock<Option<HealthCheckSettings>> = RwLock::new(None);
+
+/// The values in force: settings with this machine's overrides applied.
+pub(crate) fn current() -> HealthCheckSettings {
+    CURRENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+fn overrides_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_local_data_dir()
+        .expect("could not resolve app local data dir")
+        .join(OVERRIDES_FILE)
+}
+
+fn load_overrides(app: &AppHandle) -> HealthCheckOverrides {
+    std::fs::read_to_string(overrides_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Reads the settings and overrides again, falling back to the defaults
+/// when the result is invalid, e.g. after a hand edit.
+pub(crate) fn apply(app: &AppHandle) {
+    let effective = merge(&settings::load(app).health_check, &load_overrides(app));
+    let effective = match effective.validate() {
+        Ok(()) => effective,
+        Err(err) => {
+            eprintln!("health check settings ignored: {err}");
+            HealthCheckSettings::default()
+        }
+    };
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(effective);
+}
+
+/// What [`get_health_check_settings`] returns.
+#[derive(Serialize, Clone, Debug)]
+pub struct HealthCheckConfig {
+    pub settings: HealthCheckSettings,
+    pub machine: HealthCheckOverrides,
+    /// In force: `settings` with `machine` applied.
+    pub effective: HealthCheckSettings,
+}
+
+/// Liveness and readiness of the sidecar, as [`get_backend_status`] reports
+/// them.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendStatus {
+    pub phase: BackendPhase,
+    /// The sidecar process exists. Dev builds, which use a separately run
+    /// server, and a remote backend report readiness here.
+    pub live: bool,
+    /// The health endpoint answered 2xx.
+    pub ready: bool,
+    pub pid: Option<u32>,
+    pub latency_ms: Option<u64>,
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_health_check_settings(app: AppHandle) -> HealthCheckConfig {
+    let settings = settings::load(&app).health_check;
+    let machine = load_overrides(&app);
+    HealthCheckConfig {
+        effective: merge(&settings, &machine),
+        settings,
+        machine,
+    }
+}
+
+/// Saves the health check for every machine the settings reach. Applies from
+/// the next check.
+#[tauri::command]
+pub fn save_health_check_settings(
+    app: AppHandle,
+    config: HealthCheckSettings,
+) -> CommandResult<()> {
+    merge(&config, &load_overrides(&app)).validate()?;
+    let mut all = settings::load(&app);
+    all.health_check = config;
+    settings::save(&app, &all)?;
+    apply(&app);
+    Ok(())
+}
+
+/// Saves overrides for this machine only; empty overrides remove the file.
+#[tauri::command]
+pub fn save_health_check_overrides(
+    app: AppHandle,
+    overrides: HealthCheckOverrides,
+) -> CommandResult<()> {
+    merge(&settings::load(&app).health_check, &overrides).validate()?;
+    let path = overrides_path(&app);
+    if overrides == HealthCheckOverrides::default() {
+        let _ = std::fs::remove_file(&path);
+    } else {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&overrides)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        std::fs::write(&path, json)?;
+    }
+    apply(&app);
+    Ok(())
+}
+
+/// Checks the sidecar now: whether its process exists, and whether it
+/// answers.
+#[tauri::command]
+pub async fn get_backend_status(app: AppHandle) -> CommandResult<BackendStatus> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let ready = backend::healthy();
+        let probe = backend::last_health();
+        let remote = crate::remote_backend::active().is_some();
+        let pid = if cfg!(dev) || remote {
+            None
+        } else {
+            crate::sidecar_pid::running(&app)
+        };
+        Ok(BackendStatus {
+            phase: sidecar_watchdog::get_backend_phase(),
+            live: if cfg!(dev) || remote {
+                ready
+            } else {
+                pid.is_some()
+            },
+            ready,
+            pid,
+            latency_ms: probe.map(|probe| probe.latency.as_millis() as u64),
+            checked_at: probe.map(|probe| probe.at),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn machine_overrides_win_over_settings() {
+        let settings = HealthCheckSettings::default();
+        let overrides = HealthCheckOverrides {
+            startup_timeout_secs: Some(120),
+            ..HealthCheckOverrides::default()
+        };
+        let effective = merge(&settings, &overrides);
+        assert_eq!(effective.startup_timeout_secs, 120);
+        assert_eq!(
+            effective.restart_timeout_secs,
+            settings.restart_timeout_secs
+        );
+        assert_eq!(effective.path, "/health");
+    }
+
+    #[test]
+    fn out_of_range_values_are_refused() {
+        assert!(HealthCheckSettings::default().validate().is_ok());
+        for bad in [
+            HealthCheckSettings {
+                path: "health".into(),
+                ..HealthCheckSettings::default()
+            },
+            HealthCheckSettings {
+                path: "/../runs".into(),
+                ..HealthCheckSettings::default()
+            },
+            HealthCheckSettings {
+                startup_timeout_secs: 0,
+                ..HealthCheckSettings::default()
+            },
+            HealthCheckSettings {
+                poll_interval_ms: 10,
+                ..HealthCheckSettings::default()
+            },
+        ] {
+            assert!(bad.validate().is_err(), "{bad:?}");
+        }
+    }
+}
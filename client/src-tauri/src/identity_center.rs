@@ -0,0 +1,516 @@
+//! AWS IAM Identity Center (SSO) credentials, for organizations that do not
+//! issue access keys. The login is the SSO OIDC device authorization flow:
+//! the user approves the code in the browser, the app polls for the access
+//! token and caches it in the keychain, then lists the accounts and roles
+//! it grants and exchanges one for temporary role credentials.
+//!
+//! Not to be confused with `crate::sso`, which gates opening the app behind
+//! the company IdP.
+
+use std::sync::Mutex;
+
+use aws_smithy_types::error::metadata::ProvideErrorMetadata;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::aws::{sdk_error, send, unsigned_config};
+use crate::error::{AppError, CommandResult};
+use crate::profiles::{self, ProfileOrigin};
+use crate::{app_lock, keyring_entry_for, AwsCredentials};
+
+pub(crate) const TOKEN_ACCOUNT: &str = "identity-center-token";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const CLIENT_NAME: &str = "aws-cost-optimizer";
+
+/// Device authorization waiting for approval in the browser.
+struct PendingLogin {
+    start_url: String,
+    region: String,
+    client_id: String,
+    client_secret: String,
+    device_code: String,
+}
+
+#[derive(Default)]
+pub struct IdentityCenterState(Mutex<Option<PendingLogin>>);
+
+/// Cached access token; it grants access to every assigned account.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedToken {
+    start_url: String,
+    region: String,
+    access_token: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct IdentityCenterLogin {
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: i32,
+    /// Seconds the UI should wait between polls.
+    pub interval: i32,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum IdentityCenterPoll {
+    Pending,
+    /// Identity Center asked for a longer polling interval.
+    SlowDown,
+    Complete {
+        expires_at: DateTime<Utc>,
+    },
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct IdentityCenterAccount {
+    pub account_id: String,
+    pub account_name: Option<String>,
+    pub email_address: Option<String>,
+    pub roles: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct IdentityCenterStatus {
+    pub signed_in: bool,
+    pub start_url: Option<String>,
+    pub region: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+// ---------------------------------------------------------------------------
+// Token cache
+// ---------------------------------------------------------------------------
+
+fn read_token() -> Option<CachedToken> {
+    let raw = keyring_entry_for(TOKEN_ACCOUNT).ok()?.get_password().ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+fn write_token(token: &CachedToken) -> Result<(), String> {
+    let json = serde_json::to_string(token).map_err(|e| e.to_string())?;
+    keyring_entry_for(TOKEN_ACCOUNT)?
+        .set_password(&json)
+        .map_err(|e| e.to_string())
+}
+
+fn valid_token() -> Result<CachedToken, AppError> {
+    read_token()
+        .filter(|token| token.expires_at > Utc::now())
+        .ok_or_else(|| AppError::Credentials("Identity Center sign-in required or expired".into()))
+}
+
+// ---------------------------------------------------------------------------
+// API calls
+// ---------------------------------------------------------------------------
+
+fn oidc_client(config: &aws_config::SdkConfig) -> aws_sdk_ssooidc::Client {
+    aws_sdk_ssooidc::Client::new(config)
+}
+
+fn portal_client(config: &aws_config::SdkConfig) -> aws_sdk_sso::Client {
+    aws_sdk_sso::Client::new(config)
+}
+
+/// Codes of `CreateToken` that mean "keep polling".
+fn pending_poll(code: Option<&str>) -> Option<IdentityCenterPoll> {
+    match code? {
+        "AuthorizationPendingException" => Some(IdentityCenterPoll::Pending),
+        "SlowDownException" => Some(IdentityCenterPoll::SlowDown),
+        _ => None,
+    }
+}
+
+async fn accounts(
+    config: &aws_config::SdkConfig,
+    access_token: &str,
+) -> Result<Vec<IdentityCenterAccount>, AppError> {
+    let client = portal_client(config);
+    let mut accounts = Vec::new();
+    let mut next_token: Option<String> = None;
+    loop {
+        let page = send(|| {
+            client
+                .list_accounts()
+                .access_token(access_token)
+                .set_next_token(next_token.clone())
+                .send()
+        })
+        .await
+        .map_err(sdk_error)?;
+        for info in page.account_list() {
+            let Some(account_id) = info.account_id() else {
+                continue;
+            };
+            accounts.push(IdentityCenterAccount {
+                account_id: account_id.to_string(),
+                account_name: info.account_name().map(str::to_string),
+                email_address: info.email_address().map(str::to_string),
+                roles: account_roles(&client, access_token, account_id).await?,
+            });
+        }
+        next_token = page.next_token().map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+    accounts.sort_by(|a, b| a.account_name.cmp(&b.account_name));
+    Ok(accounts)
+}
+
+async fn account_roles(
+    client: &aws_sdk_sso::Client,
+    access_token: &str,
+    account_id: &str,
+) -> Result<Vec<String>, AppError> {
+    let mut roles = Vec::new();
+    let mut next_token: Option<String> = None;
+    loop {
+        let page = send(|| {
+            client
+                .list_account_roles()
+                .access_token(access_token)
+                .account_id(account_id)
+                .set_next_token(next_token.clone())
+                .send()
+        })
+        .await
+        .map_err(sdk_error)?;
+        roles.extend(
+            page.role_list()
+                .iter()
+                .filter_map(|role| role.role_name().map(str::to_string)),
+        );
+        next_token = page.next_token().map(str::to_string);
+        if next_token.is_none() {
+            break;
+        }
+    }
+    roles.sort();
+    Ok(roles)
+}
+
+async fn role_credentials(
+    config: &aws_config::SdkConfig,
+    access_token: &str,
+    account_id: &str,
+    role_name: &str,
+    region: String,
+) -> Result<AwsCredentials, AppError> {
+    let client = portal_client(config);
+    let out = send(|| {
+        client
+            .get_role_credentials()
+            .access_token(access_token)
+            .account_id(account_id)
+            .role_name(role_name)
+            .send()
+    })
+    .await
+    .map_err(sdk_error)?;
+    let creds = out
+        .role_credentials()
+        .ok_or_else(|| AppError::Aws("Identity Center returned no role credentials".into()))?;
+    match (creds.access_key_id(), creds.secret_access_key()) {
+        (Some(access_key_id), Some(secret_access_key)) => Ok(AwsCredentials {
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.into(),
+            region,
+            session_token: creds.session_token().map(Into::into),
+            expires_at: DateTime::from_timestamp_millis(creds.expiration()),
+            partition: None,
+            endpoint_url: None,
+        }),
+        _ => Err(AppError::Aws(
+            "Identity Center returned incomplete role credentials".into(),
+        )),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_identity_center_status() -> IdentityCenterStatus {
+    let token = read_token().filter(|token| token.expires_at > Utc::now());
+    IdentityCenterStatus {
+        signed_in: token.is_some(),
+        start_url: token.as_ref().map(|t| t.start_url.clone()),
+        region: token.as_ref().map(|t| t.region.clone()),
+        expires_at: token.map(|t| t.expires_at),
+    }
+}
+
+/// Registers the app with Identity Center and starts a device login; the UI
+/// shows the code and opens the verification URL.
+#[tauri::command]
+pub async fn start_identity_center_login(
+    app: AppHandle,
+    start_url: String,
+    region: String,
+) -> CommandResult<IdentityCenterLogin> {
+    if start_url.trim().is_empty() || region.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "Start URL and region are required".into(),
+        ));
+    }
+    let config = unsigned_config(region.trim()).await;
+    let client = oidc_client(&config);
+    let registration = send(|| {
+        client
+            .register_client()
+            .client_name(CLIENT_NAME)
+            .client_type("public")
+            .send()
+    })
+    .await
+    .map_err(sdk_error)?;
+    let (Some(client_id), Some(client_secret)) =
+        (registration.client_id(), registration.client_secret())
+    else {
+        return Err(AppError::Aws(
+            "Identity Center did not register the client".into(),
+        ));
+    };
+
+    let authorization = send(|| {
+        client
+            .start_device_authorization()
+            .client_id(client_id)
+            .client_secret(client_secret)
+            .start_url(start_url.trim())
+            .send()
+    })
+    .await
+    .map_err(sdk_error)?;
+    let (Some(device_code), Some(user_code), Some(verification_uri)) = (
+        authorization.device_code(),
+        authorization.user_code(),
+        authorization.verification_uri(),
+    ) else {
+        return Err(AppError::Aws(
+            "Identity Center returned an incomplete device authorization".into(),
+        ));
+    };
+
+    *app.state::<IdentityCenterState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = Some(PendingLogin {
+        start_url: start_url.trim().to_string(),
+        region: region.trim().to_string(),
+        client_id: client_id.to_string(),
+        client_secret: client_secret.to_string(),
+        device_code: device_code.to_string(),
+    });
+    Ok(IdentityCenterLogin {
+        user_code: user_code.to_string(),
+        verification_uri: verification_uri.to_string(),
+        verification_uri_complete: authorization
+            .verification_uri_complete()
+            .map(str::to_string),
+        expires_in: authorization.expires_in(),
+        interval: authorization.interval().max(1),
+    })
+}
+
+/// Checks whether the pending login was approved; on approval the access
+/// token is cached in the keychain.
+#[tauri::command]
+pub async fn poll_identity_center_login(app: AppHandle) -> CommandResult<IdentityCenterPoll> {
+    let (start_url, region, client_id, client_secret, device_code) = {
+        let state = app.state::<IdentityCenterState>();
+        let pending = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        let pending = pending
+            .as_ref()
+            .ok_or_else(|| AppError::Conflict("No Identity Center login in progress".into()))?;
+        (
+            pending.start_url.clone(),
+            pending.region.clone(),
+            pending.client_id.clone(),
+            pending.client_secret.clone(),
+            pending.device_code.clone(),
+        )
+    };
+
+    let config = unsigned_config(&region).await;
+    let client = oidc_client(&config);
+    let result = send(|| {
+        client
+            .create_token()
+            .client_id(&client_id)
+            .client_secret(&client_secret)
+            .grant_type(DEVICE_CODE_GRANT)
+            .device_code(&device_code)
+            .send()
+    })
+    .await;
+    let token = match result {
+        Ok(token) => token,
+        Err(err) => {
+            if let Some(poll) = pending_poll(err.code()) {
+                return Ok(poll);
+            }
+            return Err(sdk_error(err));
+        }
+    };
+    let access_token = token
+        .access_token()
+        .ok_or_else(|| AppError::Aws("Identity Center returned no access token".into()))?;
+    let expires_at = Utc::now() + chrono::Duration::seconds(token.expires_in().into());
+    write_token(&CachedToken {
+        start_url,
+        region,
+        access_token: access_token.to_string(),
+        expires_at,
+    })?;
+    *app.state::<IdentityCenterState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = None;
+    Ok(IdentityCenterPoll::Complete { expires_at })
+}
+
+/// Accounts the signed-in user is assigned to, with their roles.
+#[tauri::command]
+pub async fn list_identity_center_accounts(
+    app: AppHandle,
+) -> CommandResult<Vec<IdentityCenterAccount>> {
+    app_lock::ensure_unlocked(&app)?;
+    let token = valid_token()?;
+    let config = unsigned_config(&token.region).await;
+    accounts(&config, &token.access_token).await
+}
+
+/// Fresh credentials for a role with the cached token, e.g. to renew a
+/// profile before its keys expire.
+pub(crate) async fn renew(
+    account_id: &str,
+    role_name: &str,
+    region: &str,
+) -> Result<AwsCredentials, AppError> {
+    let token = valid_token()?;
+    let config = unsigned_config(&token.region).await;
+    role_credentials(
+        &config,
+        &token.access_token,
+        account_id,
+        role_name,
+        region.to_string(),
+    )
+    .await
+}
+
+/// Exchanges the cached token for temporary credentials of one role, saves
+/// them as `profile` (default: `sso:<account>/<role>`) and makes it active.
+/// `region` is the region the app works in, defaulting to the Identity
+/// Center region.
+#[tauri::command]
+pub async fn use_identity_center_role(
+    app: AppHandle,
+    account_id: String,
+    role_name: String,
+    region: Option<String>,
+    profile: Option<String>,
+) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    let region = match region.filter(|r| !r.trim().is_empty()) {
+        Some(region) => region.trim().to_string(),
+        None => valid_token()?.region,
+    };
+    let creds = renew(&account_id, &role_name, &region).await?;
+    let name = profile
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| format!("sso:{account_id}/{role_name}"));
+    let origin = ProfileOrigin::IdentityCenter {
+        account_id,
+        role_name,
+        region,
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        profiles::store_and_activate(&app, &name, &creds, Some(origin))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Forgets the cached token and any pending login. Credentials already in
+/// use stay until they expire or are replaced.
+#[tauri::command]
+pub fn identity_center_logout(app: AppHandle) -> CommandResult<()> {
+    *app.state::<IdentityCenterState>()
+        .0
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = None;
+    if let Ok(entry) = keyring_entry_for(TOKEN_ACCOUNT) {
+        let _ = entry.delete_credential();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::ReplayClient;
+
+    async fn replay_config(client: &ReplayClient) -> aws_config::SdkConfig {
+        unsigned_config("us-east-1")
+            .await
+            .into_builder()
+            .http_client(client.clone())
+            .build()
+    }
+
+    #[test]
+    fn pending_codes_keep_polling() {
+        assert_eq!(
+            pending_poll(Some("AuthorizationPendingException")),
+            Some(IdentityCenterPoll::Pending)
+        );
+        assert_eq!(
+            pending_poll(Some("SlowDownException")),
+            Some(IdentityCenterPoll::SlowDown)
+        );
+        assert_eq!(pending_poll(Some("ExpiredTokenException")), None);
+        assert_eq!(pending_poll(None), None);
+    }
+
+    #[tokio::test]
+    async fn accounts_include_their_roles() {
+        let aws = ReplayClient::load("aws/identity_center.json");
+        let config = replay_config(&aws).await;
+        let accounts = accounts(&config, "token").await.unwrap();
+        assert_eq!(
+            accounts,
+            [IdentityCenterAccount {
+                account_id: "111122223333".into(),
+                account_name: Some("billing".into()),
+                email_address: Some("billing@example.com".into()),
+                roles: vec!["BillingReadOnly".into(), "CostOptimizer".into()],
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn role_credentials_are_temporary_keys() {
+        let aws = ReplayClient::load("aws/identity_center.json");
+        let config = replay_config(&aws).await;
+        let creds = role_credentials(
+            &config,
+            "token",
+            "111122223333",
+            "CostOptimizer",
+            "eu-west-1".into(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(creds.access_key_id, "ASIAEXAMPLE");
+        assert_eq!(creds.session_token(), Some("session"));
+        assert_eq!(creds.region, "eu-west-1");
+    }
+}
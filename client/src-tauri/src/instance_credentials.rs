@@ -0,0 +1,292 @@
+//! Credentials from the machine the app runs on: the EC2 instance metadata
+//! service (an instance profile) or an ECS container credential endpoint,
+//! for cloud workstations that have a role instead of stored keys. The keys
+//! come from the SDK's own providers and are kept in memory only, like
+//! `credential_process` keys; settings remember the source, so it is asked
+//! again at launch and whenever the keys are about to expire.
+
+use std::sync::Mutex;
+
+use aws_config::ecs::EcsCredentialsProvider;
+use aws_config::imds::credentials::ImdsCredentialsProvider;
+use aws_config::imds::region::ImdsRegionProvider;
+use aws_credential_types::provider::ProvideCredentials;
+use aws_credential_types::Credentials;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::{
+    app_lock, aws, credential_process, partition, read_credentials, reload_sidecar, settings, sso,
+    stop_sidecar, AwsCredentials,
+};
+
+/// The source's keys, while one is in use.
+static CURRENT: Mutex<Option<AwsCredentials>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceSource {
+    /// The EC2 instance metadata service (IMDSv2).
+    Imds,
+    /// `AWS_CONTAINER_CREDENTIALS_RELATIVE_URI` or `..._FULL_URI`.
+    Container,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct InstanceCredentialSettings {
+    pub source: Option<InstanceSource>,
+    /// Region for the keys; the instance's own, or the environment's for
+    /// containers, when unset.
+    pub region: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct InstanceCredentials {
+    pub source: InstanceSource,
+    pub access_key_id: String,
+    pub region: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+fn convert(creds: &Credentials, region: String) -> AwsCredentials {
+    AwsCredentials {
+        access_key_id: creds.access_key_id().to_string(),
+        secret_access_key: creds.secret_access_key().to_string().into(),
+        region,
+        session_token: creds.session_token().map(|t| t.to_string().into()),
+        expires_at: creds.expiry().map(DateTime::<Utc>::from),
+        partition: None,
+        endpoint_url: None,
+    }
+}
+
+/// Region a container's keys are used in: `AWS_REGION`, then
+/// `AWS_DEFAULT_REGION`.
+fn environment_region(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    ["AWS_REGION", "AWS_DEFAULT_REGION"]
+        .into_iter()
+        .find_map(|name| var(name).filter(|region| !region.is_empty()))
+}
+
+/// Asks `source` for keys.
+async fn fetch(source: InstanceSource, region: Option<String>) -> Result<AwsCredentials, AppError> {
+    let config = aws::provider_config();
+    let (creds, region) = match source {
+        InstanceSource::Imds => {
+            let provider = ImdsCredentialsProvider::builder()
+                .configure(&config)
+                .build();
+            let creds = provider.provide_credentials().await;
+            let region = match region {
+                Some(region) => Some(region),
+                None => ImdsRegionProvider::builder()
+                    .configure(&config)
+                    .build()
+                    .region()
+                    .await
+                    .map(|region| region.to_string()),
+            };
+            (creds, region)
+        }
+        InstanceSource::Container => {
+            let provider = EcsCredentialsProvider::builder().configure(&config).build();
+            let region = region.or_else(|| environment_region(|name| std::env::var(name).ok()));
+            (provider.provide_credentials().await, region)
+        }
+    };
+    let creds = creds.map_err(|e| {
+        AppError::Credentials(format!(
+            "No {} credentials: {}",
+            label(source),
+            aws_smithy_types::error::display::DisplayErrorContext(&e)
+        ))
+    })?;
+    let region = region.unwrap_or_else(|| "us-east-1".into());
+    partition::validate_region(&region, None)?;
+    Ok(convert(&creds, region))
+}
+
+fn label(source: InstanceSource) -> &'static str {
+    match source {
+        InstanceSource::Imds => "instance profile",
+        InstanceSource::Container => "container",
+    }
+}
+
+/// The source's keys, if one is in use.
+pub(crate) fn current() -> Option<AwsCredentials> {
+    CURRENT.lock().ok()?.clone()
+}
+
+fn set_current(creds: Option<AwsCredentials>) {
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = creds;
+    }
+}
+
+/// Source in use, if any.
+pub(crate) fn active_source(app: &AppHandle) -> Option<InstanceSource> {
+    settings::load(app).instance_credentials.source
+}
+
+/// Asks the source and points the sidecar at its keys.
+async fn load(
+    app: &AppHandle,
+    source: InstanceSource,
+    region: Option<String>,
+) -> Result<AwsCredentials, AppError> {
+    let creds = fetch(source, region).await?;
+    set_current(Some(creds.clone()));
+    if sso::access_granted(app) {
+        let handle = app.clone();
+        let sidecar_creds = creds.clone();
+        tauri::async_runtime::spawn_blocking(move || reload_sidecar(&handle, &sidecar_creds))
+            .await
+            .map_err(|e| e.to_string())??;
+    }
+    Ok(creds)
+}
+
+/// Fetches fresh keys from the source in use, e.g. before the current ones
+/// expire.
+pub(crate) async fn refresh(app: &AppHandle) -> Result<(), AppError> {
+    let instance = settings::load(app).instance_credentials;
+    let source = instance
+        .source
+        .ok_or_else(|| AppError::Credentials("No instance credential source in use".into()))?;
+    load(app, source, instance.region).await?;
+    Ok(())
+}
+
+/// Asks the remembered source at launch, before the sidecar starts.
+pub fn resume(app: &AppHandle) {
+    let instance = settings::load(app).instance_credentials;
+    if let Some(source) = instance.source {
+        match tauri::async_runtime::block_on(fetch(source, instance.region)) {
+            Ok(creds) => set_current(Some(creds)),
+            Err(err) => eprintln!("{} credentials failed: {err}", label(source)),
+        }
+    }
+}
+
+/// Stops using the source; called whenever other credentials are applied.
+pub(crate) fn clear(app: &AppHandle) -> Result<(), String> {
+    set_current(None);
+    let mut all = settings::load(app);
+    if all.instance_credentials.source.take().is_some() {
+        all.instance_credentials.region = None;
+        settings::save(app, &all)?;
+    }
+    Ok(())
+}
+
+fn summary(source: InstanceSource, creds: &AwsCredentials) -> InstanceCredentials {
+    InstanceCredentials {
+        source,
+        access_key_id: creds.access_key_id.clone(),
+        region: creds.region.clone(),
+        expires_at: creds.expires_at,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_instance_credential_status(app: AppHandle) -> Option<InstanceCredentials> {
+    let source = active_source(&app)?;
+    current().map(|creds| summary(source, &creds))
+}
+
+/// Switches the app to the instance profile's or container's keys. Stored
+/// profiles stay untouched; the active one is used again once the source is
+/// no longer.
+#[tauri::command]
+pub async fn use_instance_credentials(
+    app: AppHandle,
+    source: InstanceSource,
+    region: Option<String>,
+) -> CommandResult<InstanceCredentials> {
+    app_lock::ensure_unlocked(&app)?;
+    let region = region
+        .map(|region| region.trim().to_string())
+        .filter(|region| !region.is_empty());
+    let creds = load(&app, source, region.clone()).await?;
+    credential_process::clear(&app)?;
+    let mut all = settings::load(&app);
+    all.instance_credentials = InstanceCredentialSettings {
+        source: Some(source),
+        region,
+    };
+    settings::save(&app, &all)?;
+    Ok(summary(source, &creds))
+}
+
+/// Stops using the source and falls back to the active profile, if any.
+#[tauri::command]
+pub async fn stop_instance_credentials(app: AppHandle) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        clear(&app)?;
+        match read_credentials(&app).filter(|_| sso::access_granted(&app)) {
+            Some(creds) => reload_sidecar(&app, &creds),
+            None => stop_sidecar(&app),
+        }
+        .map_err(AppError::from)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn provider_keys_become_app_keys() {
+        let expiry = SystemTime::UNIX_EPOCH + Duration::from_secs(1_741_611_600);
+        let creds = convert(
+            &Credentials::new(
+                "ASIAEXAMPLE",
+                "secret",
+                Some("token".into()),
+                Some(expiry),
+                "imds",
+            ),
+            "eu-west-1".into(),
+        );
+        assert_eq!(creds.access_key_id, "ASIAEXAMPLE");
+        assert_eq!(creds.secret_access_key(), "secret");
+        assert_eq!(creds.session_token(), Some("token"));
+        assert_eq!(creds.region, "eu-west-1");
+        assert_eq!(
+            creds.expires_at,
+            Some("2025-03-10T13:00:00Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn containers_use_the_environment_region() {
+        let env = |region: &'static str, default: &'static str| {
+            move |name: &str| match name {
+                "AWS_REGION" => Some(region.to_string()),
+                "AWS_DEFAULT_REGION" => Some(default.to_string()),
+                _ => None,
+            }
+        };
+        assert_eq!(
+            environment_region(env("eu-west-1", "us-east-1")).as_deref(),
+            Some("eu-west-1")
+        );
+        assert_eq!(
+            environment_region(env("", "us-west-2")).as_deref(),
+            Some("us-west-2")
+        );
+        assert_eq!(environment_region(|_| None), None);
+    }
+}
@@ -0,0 +1,651 @@
+//! Persistent queue behind scheduled, provider and per-account scans. A job
+//! is written to `app_data/jobs.json` before it runs and after every step, so
+//! a crash, a laptop sleeping through a scan or an app update leaves a record
+//! the next launch resumes from its last checkpoint instead of silently
+//! losing the work. Scheduled jobs that fail with a retryable error
+//! (throttling, network, sidecar) are retried with backoff; interactive scans
+//! report their error to the caller instead.
+
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::multi_account::{self, AccountKeys};
+use crate::providers::{self, ScanOutcome, ScanScope};
+use crate::tasks::{self, CancelToken};
+use crate::{google_sheets, scheduler, settings};
+
+/// Attempts per job, including the first and any resumes.
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_SECS: i64 = 60;
+/// How often the runner looks for due retries without being woken.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Finished jobs kept for the history view.
+const KEEP_FINISHED: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobKind {
+    ScheduledScan {
+        schedule_id: String,
+    },
+    ProviderScan {
+        provider: String,
+        scope: ScanScope,
+    },
+    /// One account of a multi-account or organization scan.
+    AccountScan {
+        account_id: String,
+        keys: AccountKeys,
+        scope: ScanScope,
+    },
+}
+
+impl JobKind {
+    /// Scheduled work runs unattended, so nobody would retry it by hand.
+    fn retries(&self) -> bool {
+        matches!(self, JobKind::ScheduledScan { .. })
+    }
+
+    /// Cancellable task id while the runner executes the job.
+    fn task_id(&self, job_id: &str) -> String {
+        match self {
+            JobKind::ScheduledScan { schedule_id } => format!("schedule:{schedule_id}"),
+            JobKind::ProviderScan { .. } | JobKind::AccountScan { .. } => {
+                format!("job:{job_id}")
+            }
+        }
+    }
+}
+
+/// Steps a job has completed; a resumed job skips them.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Checkpoint {
+    /// Run recorded by the scan step.
+    pub run_id: Option<String>,
+    pub published: bool,
+    pub archived: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Job {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub attempts: u32,
+    #[serde(default)]
+    pub checkpoint: Checkpoint,
+    pub last_error: Option<String>,
+    /// Queued retries wait until then.
+    pub not_before: Option<DateTime<Utc>>,
+    /// Set when the job was interrupted and picked up again on launch.
+    #[serde(default)]
+    pub resumed: bool,
+    pub outcome: Option<ScanOutcome>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Serializes read-modify-write cycles of the job file.
+static STORE: Mutex<()> = Mutex::new(());
+/// Wakes the runner when a job is queued.
+static WAKE: (Mutex<bool>, Condvar) = (Mutex::new(false), Condvar::new());
+
+// ---------------------------------------------------------------------------
+// Storage helpers
+// ---------------------------------------------------------------------------
+
+fn jobs_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("jobs.json")
+}
+
+/// The jobs stored at `path`; none when there is no file yet. A file that
+/// does not parse is moved aside as `jobs.json.corrupt-<time>`, so the next
+/// save cannot overwrite what it held.
+fn load(path: &Path) -> Result<Vec<Job>, String> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(format!("{}: {err}", path.display())),
+    };
+    match serde_json::from_str(&content) {
+        Ok(jobs) => Ok(jobs),
+        Err(err) => {
+            let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+            let aside = path.with_extension(format!("json.corrupt-{stamp}"));
+            std::fs::rename(path, &aside).map_err(|e| format!("{}: {e}", path.display()))?;
+            eprintln!(
+                "job history unreadable ({err}); moved to {} and started afresh",
+                aside.display()
+            );
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Writes through a temporary file and a rename, so a crash mid-write
+/// leaves the previous file intact.
+fn store(path: &Path, jobs: &[Job]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_vec_pretty(jobs).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    let mut file = std::fs::File::create(&tmp).map_err(|e| e.to_string())?;
+    file.write_all(&json)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| e.to_string())?;
+    drop(file);
+    std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+fn read_jobs(app: &AppHandle) -> Vec<Job> {
+    let _store = STORE.lock().unwrap_or_else(|e| e.into_inner());
+    load(&jobs_path(app)).unwrap_or_else(|err| {
+        eprintln!("job history not read: {err}");
+        Vec::new()
+    })
+}
+
+fn update<T>(app: &AppHandle, f: impl FnOnce(&mut Vec<Job>) -> T) -> Result<T, String> {
+    let _store = STORE.lock().unwrap_or_else(|e| e.into_inner());
+    let path = jobs_path(app);
+    let mut jobs = load(&path)?;
+    let out = f(&mut jobs);
+    store(&path, &jobs)?;
+    Ok(out)
+}
+
+fn save(app: &AppHandle, job: &Job) -> Result<(), String> {
+    update(app, |jobs| match jobs.iter_mut().find(|j| j.id == job.id) {
+        Some(stored) => *stored = job.clone(),
+        None => jobs.push(job.clone()),
+    })
+}
+
+fn wake() {
+    let (pending, signal) = &WAKE;
+    *pending.lock().unwrap_or_else(|e| e.into_inner()) = true;
+    signal.notify_one();
+}
+
+// ---------------------------------------------------------------------------
+// Queue logic
+// ---------------------------------------------------------------------------
+
+fn new_job(kind: JobKind, status: JobStatus) -> Job {
+    let now = Utc::now();
+    Job {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind,
+        status,
+        attempts: 0,
+        checkpoint: Checkpoint::default(),
+        last_error: None,
+        not_before: None,
+        resumed: false,
+        outcome: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Jobs still marked running were cut off by the last shutdown. One that
+/// keeps getting cut off (e.g. it crashes the app) is given up on.
+fn recover(jobs: &mut [Job]) {
+    for job in jobs.iter_mut().filter(|j| j.status == JobStatus::Running) {
+        job.resumed = true;
+        job.not_before = None;
+        if job.attempts >= MAX_ATTEMPTS {
+            job.status = JobStatus::Failed;
+            job.last_error = Some("Interrupted on every attempt".into());
+        } else {
+            job.status = JobStatus::Queued;
+        }
+    }
+}
+
+fn next_due(jobs: &[Job], now: DateTime<Utc>) -> Option<String> {
+    jobs.iter()
+        .filter(|j| j.status == JobStatus::Queued)
+        .filter(|j| j.not_before.is_none_or(|at| at <= now))
+        .min_by_key(|j| j.created_at)
+        .map(|j| j.id.clone())
+}
+
+/// Records how an attempt ended.
+fn settle(job: &mut Job, result: &Result<(), AppError>, now: DateTime<Utc>) {
+    job.updated_at = now;
+    job.not_before = None;
+    match result {
+        Ok(()) => {
+            job.status = JobStatus::Succeeded;
+            job.last_error = None;
+        }
+        Err(AppError::Cancelled) => job.status = JobStatus::Cancelled,
+        Err(err) => {
+            job.last_error = Some(err.to_string());
+            if job.kind.retries() && err.retryable() && job.attempts < MAX_ATTEMPTS {
+                job.status = JobStatus::Queued;
+                let backoff = RETRY_BASE_SECS * 2i64.pow(job.attempts.saturating_sub(1));
+                job.not_before = Some(now + chrono::Duration::seconds(backoff));
+            } else {
+                job.status = JobStatus::Failed;
+            }
+        }
+    }
+}
+
+/// Keeps every unfinished job and the newest finished ones.
+fn prune(jobs: &mut Vec<Job>) {
+    let finished = |j: &Job| {
+        matches!(
+            j.status,
+            JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled
+        )
+    };
+    let mut done: Vec<DateTime<Utc>> = jobs
+        .iter()
+        .filter(|j| finished(j))
+        .map(|j| j.updated_at)
+        .collect();
+    if done.len() <= KEEP_FINISHED {
+        return;
+    }
+    done.sort_unstable_by_key(|at| std::cmp::Reverse(*at));
+    let cutoff = done[KEEP_FINISHED - 1];
+    jobs.retain(|j| !finished(j) || j.updated_at >= cutoff);
+}
+
+// ---------------------------------------------------------------------------
+// Execution
+// ---------------------------------------------------------------------------
+
+fn checkpoint(app: &AppHandle, job: &mut Job) {
+    job.updated_at = Utc::now();
+    if let Err(err) = save(app, job) {
+        eprintln!("job {} checkpoint not saved: {err}", job.id);
+    }
+}
+
+fn scheduled_scan(
+    app: &AppHandle,
+    job: &mut Job,
+    schedule_id: &str,
+    cancel: &CancelToken,
+) -> Result<(), AppError> {
+    let schedule = settings::load(app)
+        .schedules
+        .scans
+        .into_iter()
+        .find(|s| s.id == schedule_id)
+        .ok_or_else(|| AppError::NotFound(format!("Schedule '{schedule_id}' was removed")))?;
+    let run_id = match job.checkpoint.run_id.clone() {
+        Some(run_id) => run_id,
+        None => {
+            let scan = scheduler::trigger_scan(app, &schedule, cancel)?;
+            for err in &scan.errors {
+                eprintln!(
+                    "scheduled scan '{}' partial: {} {} in {} failed: {} ({})",
+                    schedule.name, err.service, err.operation, err.region, err.code, err.message
+                );
+            }
+            job.checkpoint.run_id = Some(scan.run_id.clone());
+            checkpoint(app, job);
+            scan.run_id
+        }
+    };
+    if !job.checkpoint.published {
+        google_sheets::publish_scheduled(app, &run_id);
+        job.checkpoint.published = true;
+        checkpoint(app, job);
+    }
+    if schedule.archive_report && !job.checkpoint.archived {
+        cancel.check()?;
+        scheduler::archive_report(app, &run_id, cancel);
+        job.checkpoint.archived = true;
+        checkpoint(app, job);
+    }
+    Ok(())
+}
+
+fn provider_scan(
+    app: &AppHandle,
+    job: &mut Job,
+    provider: &str,
+    scope: &ScanScope,
+    cancel: &CancelToken,
+) -> Result<(), AppError> {
+    if job.checkpoint.run_id.is_some() {
+        return Ok(());
+    }
+    let outcome = providers::find(provider)?.scan(app, scope, cancel)?;
+    record(job, outcome);
+    Ok(())
+}
+
+fn record(job: &mut Job, outcome: ScanOutcome) {
+    providers::invalidate_findings();
+    job.checkpoint.run_id = Some(outcome.run_id.clone());
+    job.outcome = Some(outcome);
+}
+
+/// Runs one attempt of a stored job and records its result.
+fn execute(app: &AppHandle, job: Job, cancel: &CancelToken) -> (Job, Result<(), AppError>) {
+    attempt(app, job, |job| {
+        let kind = job.kind.clone();
+        match &kind {
+            JobKind::ScheduledScan { schedule_id } => scheduled_scan(app, job, schedule_id, cancel),
+            JobKind::ProviderScan { provider, scope } => {
+                provider_scan(app, job, provider, scope, cancel)
+            }
+            JobKind::AccountScan {
+                account_id,
+                keys,
+                scope,
+            } => {
+                if job.checkpoint.run_id.is_none() {
+                    let outcome =
+                        multi_account::resume_account_scan(app, account_id, keys, scope, cancel)?;
+                    record(job, outcome);
+                }
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Runs one attempt of a stored job with `step` and records its result.
+fn attempt(
+    app: &AppHandle,
+    mut job: Job,
+    step: impl FnOnce(&mut Job) -> Result<(), AppError>,
+) -> (Job, Result<(), AppError>) {
+    job.status = JobStatus::Running;
+    job.attempts += 1;
+    checkpoint(app, &mut job);
+    let result = step(&mut job);
+    settle(&mut job, &result, Utc::now());
+    if let Err(err) = update(app, |jobs| {
+        match jobs.iter_mut().find(|j| j.id == job.id) {
+            Some(stored) => *stored = job.clone(),
+            None => jobs.push(job.clone()),
+        }
+        prune(jobs);
+    }) {
+        eprintln!("job {} result not saved: {err}", job.id);
+    }
+    (job, result)
+}
+
+/// Queues a job for the background runner.
+pub fn enqueue(app: &AppHandle, kind: JobKind) -> Result<Job, AppError> {
+    let job = new_job(kind, JobStatus::Queued);
+    save(app, &job)?;
+    wake();
+    Ok(job)
+}
+
+/// Records a job and runs it on the calling thread, for interactive work
+/// whose caller waits for the result. If the app dies meanwhile, the next
+/// launch resumes it in the background.
+pub fn run_now(app: &AppHandle, kind: JobKind, cancel: &CancelToken) -> Result<Job, AppError> {
+    let job = new_job(kind, JobStatus::Running);
+    save(app, &job)?;
+    let (job, result) = execute(app, job, cancel);
+    result.map(|()| job)
+}
+
+/// Records a scan job and runs it on the calling thread with `scan`, which
+/// already holds what the job would otherwise look up again (keys, a
+/// progress sink). Errs only when the job cannot be recorded; how the scan
+/// went is in the returned job's status, outcome and error.
+pub(crate) fn run_attached(
+    app: &AppHandle,
+    kind: JobKind,
+    cancel: &CancelToken,
+    scan: impl FnOnce() -> Result<ScanOutcome, AppError>,
+) -> Result<Job, AppError> {
+    let job = new_job(kind, JobStatus::Running);
+    save(app, &job)?;
+    let (job, _) = attempt(app, job, |job| {
+        cancel.check()?;
+        record(job, scan()?);
+        Ok(())
+    });
+    Ok(job)
+}
+
+/// When a job last finished successfully.
+pub(crate) fn last_success(app: &AppHandle) -> Option<DateTime<Utc>> {
+    read_jobs(app)
+        .iter()
+        .filter(|job| job.status == JobStatus::Succeeded)
+        .map(|job| job.updated_at)
+        .max()
+}
+
+fn run_next(app: &AppHandle) -> bool {
+    let Some(job) = update(app, |jobs| {
+        next_due(jobs, Utc::now()).and_then(|id| jobs.iter().find(|j| j.id == id).cloned())
+    })
+    .ok()
+    .flatten() else {
+        return false;
+    };
+    let (cancel, _task) = match tasks::register(app, Some(job.kind.task_id(&job.id))) {
+        Ok(task) => task,
+        Err(err) => {
+            // Its task is still running elsewhere; look again next poll.
+            eprintln!("job {} deferred: {err}", job.id);
+            return false;
+        }
+    };
+    let id = job.id.clone();
+    if let (_, Err(err)) = execute(app, job, &cancel) {
+        eprintln!("job {id} failed: {err}");
+    }
+    true
+}
+
+/// Resumes interrupted jobs, then runs queued jobs one at a time.
+pub fn spawn_job_runner(app: AppHandle) {
+    if let Err(err) = update(&app, |jobs| recover(jobs)) {
+        eprintln!("job queue not recovered: {err}");
+    }
+    std::thread::spawn(move || loop {
+        while run_next(&app) {}
+        let (pending, signal) = &WAKE;
+        let guard = pending.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut guard, _) = signal
+            .wait_timeout_while(guard, POLL_INTERVAL, |pending| !*pending)
+            .unwrap_or_else(|e| e.into_inner());
+        *guard = false;
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Every unfinished job and recent finished ones, newest first.
+#[tauri::command]
+pub fn list_jobs(app: AppHandle) -> Vec<Job> {
+    let mut jobs = read_jobs(&app);
+    jobs.sort_by_key(|job| std::cmp::Reverse(job.created_at));
+    jobs
+}
+
+/// Queues a failed or cancelled job again, continuing from its checkpoint.
+#[tauri::command]
+pub fn retry_job(app: AppHandle, job_id: String) -> CommandResult<Job> {
+    let job = update(&app, |jobs| {
+        let job = jobs
+            .iter_mut()
+            .find(|j| j.id == job_id)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown job '{job_id}'")))?;
+        if !matches!(job.status, JobStatus::Failed | JobStatus::Cancelled) {
+            return Err(AppError::Conflict(format!(
+                "Job '{job_id}' has not finished unsuccessfully"
+            )));
+        }
+        job.status = JobStatus::Queued;
+        job.attempts = 0;
+        job.not_before = None;
+        job.updated_at = Utc::now();
+        Ok(job.clone())
+    })??;
+    wake();
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduled() -> Job {
+        new_job(
+            JobKind::ScheduledScan {
+                schedule_id: "nightly".into(),
+            },
+            JobStatus::Running,
+        )
+    }
+
+    #[test]
+    fn interrupted_jobs_are_resumed() {
+        let mut jobs = vec![scheduled(), scheduled(), scheduled()];
+        jobs[0].attempts = 1;
+        jobs[1].status = JobStatus::Succeeded;
+        jobs[2].attempts = MAX_ATTEMPTS;
+        recover(&mut jobs);
+        assert_eq!(jobs[0].status, JobStatus::Queued);
+        assert!(jobs[0].resumed);
+        assert_eq!(jobs[1].status, JobStatus::Succeeded);
+        assert_eq!(jobs[2].status, JobStatus::Failed);
+        assert_eq!(next_due(&jobs, Utc::now()), Some(jobs[0].id.clone()));
+    }
+
+    #[test]
+    fn retryable_failures_back_off_until_attempts_run_out() {
+        let now = Utc::now();
+        let mut job = scheduled();
+        let throttled = Err(AppError::Throttled("Rate exceeded".into()));
+
+        job.attempts = 1;
+        settle(&mut job, &throttled, now);
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(job.not_before, Some(now + chrono::Duration::seconds(60)));
+        assert_eq!(next_due(std::slice::from_ref(&job), now), None);
+
+        job.attempts = 2;
+        settle(&mut job, &throttled, now);
+        assert_eq!(job.not_before, Some(now + chrono::Duration::seconds(120)));
+
+        job.attempts = MAX_ATTEMPTS;
+        settle(&mut job, &throttled, now);
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.last_error.as_deref(), Some("Rate exceeded"));
+    }
+
+    #[test]
+    fn interactive_and_permanent_failures_are_not_retried() {
+        let now = Utc::now();
+        let mut job = scheduled();
+        job.attempts = 1;
+        settle(&mut job, &Err(AppError::AccessDenied("denied".into())), now);
+        assert_eq!(job.status, JobStatus::Failed);
+
+        let mut interactive = new_job(
+            JobKind::ProviderScan {
+                provider: "aws".into(),
+                scope: ScanScope {
+                    include: Vec::new(),
+                    exclude: Vec::new(),
+                    max_objects_per_resource: None,
+                    regions: Vec::new(),
+                },
+            },
+            JobStatus::Running,
+        );
+        interactive.attempts = 1;
+        settle(
+            &mut interactive,
+            &Err(AppError::Network("timeout".into())),
+            now,
+        );
+        assert_eq!(interactive.status, JobStatus::Failed);
+
+        settle(&mut interactive, &Err(AppError::Cancelled), now);
+        assert_eq!(interactive.status, JobStatus::Cancelled);
+    }
+
+    #[test]
+    fn pruning_keeps_unfinished_jobs() {
+        let now = Utc::now();
+        let mut jobs: Vec<Job> = (0..KEEP_FINISHED + 5)
+            .map(|i| {
+                let mut job = scheduled();
+                job.status = JobStatus::Succeeded;
+                job.updated_at = now - chrono::Duration::seconds(i as i64);
+                job
+            })
+            .collect();
+        let mut queued = scheduled();
+        queued.status = JobStatus::Queued;
+        queued.updated_at = now - chrono::Duration::days(30);
+        jobs.push(queued.clone());
+
+        prune(&mut jobs);
+        assert_eq!(jobs.len(), KEEP_FINISHED + 1);
+        assert!(jobs.iter().any(|j| j.id == queued.id));
+    }
+
+    #[test]
+    fn corrupt_job_files_are_moved_aside_not_overwritten() {
+        let dir = std::env::temp_dir().join(format!("jobs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("jobs.json");
+        assert!(load(&path).unwrap().is_empty());
+
+        store(&path, &[scheduled()]).unwrap();
+        assert_eq!(load(&path).unwrap().len(), 1);
+        assert!(!dir.join("jobs.json.tmp").exists());
+
+        std::fs::write(&path, "[{\"id\": ").unwrap();
+        assert!(load(&path).unwrap().is_empty());
+        assert!(!path.exists());
+        let aside: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name.starts_with("jobs.json.corrupt-"))
+            .collect();
+        assert_eq!(aside.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.join(&aside[0])).unwrap(),
+            "[{\"id\": "
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
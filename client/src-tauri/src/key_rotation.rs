@@ -0,0 +1,219 @@
+//! Access key age and rotation reminders. A long-term key's age comes from
+//! `iam:ListAccessKeys` when the key may call it, and otherwise from the date
+//! the app first saved it. A daily check raises a native notification, once
+//! per key and launch, for keys older than the configured threshold.
+//! Temporary keys expire on their own and are not tracked.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::aws::{sdk_config_from, sdk_error, send};
+use crate::error::{AppError, CommandResult};
+use crate::{profiles, settings};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Leaves launch to the sidecar and the other startup work.
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct KeyRotationSettings {
+    /// Keys older than this many days are due for rotation.
+    pub max_age_days: u32,
+    /// Raise a native notification for keys that are due.
+    pub notify: bool,
+}
+
+impl Default for KeyRotationSettings {
+    fn default() -> Self {
+        Self {
+            max_age_days: 90,
+            notify: true,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAgeSource {
+    /// The key's creation date in IAM.
+    Iam,
+    /// When the app first saved the key.
+    LocalSave,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct KeyAge {
+    pub profile: String,
+    pub access_key_id: String,
+    pub created_at: DateTime<Utc>,
+    pub source: KeyAgeSource,
+    pub age_days: i64,
+    pub due: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct KeyRotationStatus {
+    pub settings: KeyRotationSettings,
+    pub keys: Vec<KeyAge>,
+}
+
+fn age_days(created_at: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    (now - created_at).num_days().max(0)
+}
+
+/// Creation date of `access_key_id` among its IAM user's keys.
+async fn iam_created_at(
+    config: &aws_config::SdkConfig,
+    access_key_id: &str,
+) -> Option<DateTime<Utc>> {
+    let client = aws_sdk_iam::Client::new(config);
+    let out = send(|| client.list_access_keys().send())
+        .await
+        .map_err(sdk_error)
+        .inspect_err(|err| eprintln!("could not list access keys: {err}"))
+        .ok()?;
+    let created = out
+        .access_key_metadata()
+        .iter()
+        .find(|key| key.access_key_id() == Some(access_key_id))?
+        .create_date()?;
+    DateTime::from_timestamp(created.secs(), 0)
+}
+
+async fn status(app: &AppHandle) -> Result<KeyRotationStatus, AppError> {
+    let all = settings::load(app);
+    let max_age = i64::from(all.key_rotation.max_age_days);
+    let now = Utc::now();
+    let mut keys = Vec::new();
+    for name in profiles::names()? {
+        let creds = match profiles::read_profile(&name) {
+            Ok(creds) if !creds.is_temporary() => creds,
+            Ok(_) => continue,
+            Err(err) => {
+                eprintln!("skipping profile '{name}': {err}");
+                continue;
+            }
+        };
+        let access_key_id = creds.access_key_id.clone();
+        let saved_at = all
+            .credential_profiles
+            .key_saved_at
+            .get(&access_key_id)
+            .copied();
+        let (created_at, source) =
+            match iam_created_at(&sdk_config_from(creds).await, &access_key_id).await {
+                Some(at) => (at, KeyAgeSource::Iam),
+                None => match saved_at {
+                    Some(at) => (at, KeyAgeSource::LocalSave),
+                    None => continue,
+                },
+            };
+        let age_days = age_days(created_at, now);
+        keys.push(KeyAge {
+            profile: name,
+            access_key_id,
+            created_at,
+            source,
+            age_days,
+            due: age_days >= max_age,
+        });
+    }
+    Ok(KeyRotationStatus {
+        settings: all.key_rotation,
+        keys,
+    })
+}
+
+/// Checks key ages daily and notifies about each due key once.
+pub fn spawn_rotation_watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut notified = HashSet::new();
+        tokio::time::sleep(FIRST_CHECK_DELAY).await;
+        loop {
+            if settings::load(&app).key_rotation.notify {
+                match status(&app).await {
+                    Ok(status) => {
+                        for key in status.keys.into_iter().filter(|k| k.due) {
+                            if notified.insert(key.access_key_id.clone()) {
+                                notify(&app, &key);
+                            }
+                        }
+                    }
+                    Err(err) => eprintln!("key rotation check failed: {err}"),
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+fn notify(app: &AppHandle, key: &KeyAge) {
+    let shown = app
+        .notification()
+        .builder()
+        .title("Rotate your AWS access key")
+        .body(format!(
+            "The key of profile '{}' is {} days old. Create a new key in IAM and save it in Settings.",
+            key.profile, key.age_days
+        ))
+        .show();
+    if let Err(err) = shown {
+        eprintln!("could not show the rotation reminder: {err}");
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn key_rotation_status(app: AppHandle) -> CommandResult<KeyRotationStatus> {
+    status(&app).await
+}
+
+#[tauri::command]
+pub fn save_key_rotation_settings(
+    app: AppHandle,
+    config: KeyRotationSettings,
+) -> CommandResult<()> {
+    if config.max_age_days == 0 {
+        return Err(AppError::InvalidInput(
+            "The rotation threshold must be at least one day".into(),
+        ));
+    }
+    let mut all = settings::load(&app);
+    all.key_rotation = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, ReplayClient};
+
+    #[test]
+    fn ages_count_whole_days() {
+        let now: DateTime<Utc> = "2025-03-10T12:00:00Z".parse().unwrap();
+        assert_eq!(age_days(now - chrono::Duration::hours(47), now), 1);
+        assert_eq!(age_days(now - chrono::Duration::days(90), now), 90);
+        assert_eq!(age_days(now + chrono::Duration::hours(1), now), 0);
+    }
+
+    #[tokio::test]
+    async fn creation_dates_come_from_iam() {
+        let aws = ReplayClient::load("aws/iam.json");
+        let config = mock::sdk_config(&aws).await;
+        assert_eq!(
+            iam_created_at(&config, "AKIDEXAMPLE").await,
+            Some("2024-11-01T09:30:00Z".parse().unwrap())
+        );
+        assert_eq!(iam_created_at(&config, "AKIAOTHER").await, None);
+    }
+}
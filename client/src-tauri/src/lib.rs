@@ -1,39 +1,254 @@
 use std::sync::Mutex;
 
-use keyring::Entry;
+mod analysis_stream;
+mod api_version;
+mod app_lock;
+mod aws;
+mod aws_cli;
+mod backend;
+mod ca_trust;
+mod cloudformation;
+mod cost_explorer;
+mod credential_export;
+mod credential_process;
+mod cur;
+mod datadog;
+mod downloads;
+mod error;
+mod events;
+mod expiry;
+mod exporters;
+// Only the spawned sidecar is checked, so outside `tauri dev`.
+#[cfg_attr(dev, allow(dead_code))]
+mod exposure;
+mod github;
+mod google_sheets;
+mod grpc;
+mod health;
+mod health_check;
+mod identity_center;
+mod instance_credentials;
+mod jobs;
+mod key_rotation;
+mod local_api;
+mod local_server;
+mod metrics;
+#[cfg(any(test, feature = "mock"))]
+mod mock;
+mod multi_account;
+mod organizations;
+mod os_auth;
+mod pagerduty;
+mod partition;
+mod perf;
+mod permissions;
+mod plugins;
+mod profile_bundle;
+mod profiles;
+mod providers;
+mod proxy;
+#[cfg_attr(dev, allow(dead_code))]
+mod readiness;
+mod regions;
+mod remote_backend;
+mod request_queue;
+mod resilience;
+mod response_cache;
+mod scan_progress;
+mod scheduler;
+mod secret_store;
+mod selftest;
+mod servicenow;
+mod settings;
+// The sidecar is only spawned, and so only watched and logged, outside
+// `tauri dev`.
+#[cfg_attr(dev, allow(dead_code))]
+mod sidecar_idle;
+#[cfg_attr(dev, allow(dead_code))]
+mod sidecar_log;
+#[cfg_attr(dev, allow(dead_code))]
+mod sidecar_memory;
+#[cfg_attr(dev, allow(dead_code))]
+mod sidecar_pid;
+#[cfg(unix)]
+mod sidecar_socket;
+mod sidecar_stats;
+#[cfg_attr(dev, allow(dead_code))]
+mod sidecar_watchdog;
+#[cfg_attr(dev, allow(dead_code))]
+mod sidecar_workers;
+mod sso;
+mod sts;
+mod tasks;
+mod terraform;
+mod uploads;
+mod warmup;
+mod webhooks;
+mod websocket;
+
+use secrecy::zeroize::Zeroizing;
+use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_shell::process::CommandChild;
 #[cfg(not(dev))]
 use tauri_plugin_shell::ShellExt;
 #[cfg(not(dev))]
 use tauri_plugin_updater::UpdaterExt;
 
+use crate::error::{AppError, CommandResult};
+use crate::partition::Partition;
+
 // ---------------------------------------------------------------------------
 // Credential types
 // ---------------------------------------------------------------------------
 
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+/// AWS keys. The secret halves are zeroized on drop and redacted from
+/// `Debug`; [`AwsCredentials::to_keyring_json`] is the only serialization
+/// that writes them out, so the type is deliberately not `Serialize`.
+#[derive(Deserialize, Clone, Debug)]
 pub struct AwsCredentials {
     pub access_key_id: String,
-    pub secret_access_key: String,
+    pub secret_access_key: SecretString,
+    pub region: String,
+    pub session_token: Option<SecretString>,
+    /// When temporary credentials stop working; `None` for long-term keys.
+    #[serde(default)]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Partition chosen with the keys; inferred from the region when unset.
+    #[serde(default)]
+    pub partition: Option<Partition>,
+    /// Endpoint used for every AWS service instead of AWS itself, e.g.
+    /// LocalStack or moto at `http://localhost:4566`.
+    #[serde(default)]
+    pub endpoint_url: Option<String>,
+}
+
+impl AwsCredentials {
+    pub fn secret_access_key(&self) -> &str {
+        self.secret_access_key.expose_secret()
+    }
+
+    /// The session token, if there is a non-empty one.
+    pub fn session_token(&self) -> Option<&str> {
+        self.session_token
+            .as_ref()
+            .map(|t| t.expose_secret())
+            .filter(|t| !t.is_empty())
+    }
+
+    pub fn is_temporary(&self) -> bool {
+        self.session_token().is_some()
+    }
+
+    /// The custom endpoint, if there is a non-empty one.
+    pub fn endpoint_url(&self) -> Option<&str> {
+        self.endpoint_url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+    }
+
+    pub fn partition(&self) -> Partition {
+        self.partition
+            .unwrap_or_else(|| Partition::of_region(&self.region))
+    }
+
+    /// Whether both hold the same keys for the same region.
+    pub fn same_keys(&self, other: &Self) -> bool {
+        self.access_key_id == other.access_key_id
+            && self.secret_access_key() == other.secret_access_key()
+            && self.session_token() == other.session_token()
+            && self.region == other.region
+            && self.endpoint_url() == other.endpoint_url()
+    }
+
+    /// The keys as stored in the keychain.
+    pub fn to_keyring_json(&self) -> Result<Zeroizing<String>, String> {
+        #[derive(Serialize)]
+        struct Stored<'a> {
+            access_key_id: &'a str,
+            secret_access_key: &'a str,
+            region: &'a str,
+            session_token: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            expires_at: Option<chrono::DateTime<chrono::Utc>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            partition: Option<Partition>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            endpoint_url: Option<&'a str>,
+        }
+        serde_json::to_string_pretty(&Stored {
+            access_key_id: &self.access_key_id,
+            secret_access_key: self.secret_access_key(),
+            region: &self.region,
+            session_token: self.session_token(),
+            expires_at: self.expires_at,
+            partition: self.partition,
+            endpoint_url: self.endpoint_url(),
+        })
+        .map(Zeroizing::new)
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// What the UI may see of the stored keys: everything but the secrets.
+#[derive(Serialize, Clone, Debug)]
+pub struct CredentialsView {
+    pub access_key_id: String,
     pub region: String,
-    pub session_token: Option<String>,
+    pub has_session_token: bool,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub partition: Partition,
+    pub endpoint_url: Option<String>,
+}
+
+impl From<&AwsCredentials> for CredentialsView {
+    fn from(creds: &AwsCredentials) -> Self {
+        Self {
+            access_key_id: creds.access_key_id.clone(),
+            region: creds.region.clone(),
+            has_session_token: creds.is_temporary(),
+            expires_at: creds.expires_at,
+            partition: creds.partition(),
+            endpoint_url: creds.endpoint_url().map(str::to_string),
+        }
+    }
+}
+
+/// Where the current credentials come from, in the order they are tried.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CredentialSource {
+    CredentialProcess {
+        profile: String,
+    },
+    /// The EC2 instance profile or the ECS container's role.
+    Instance {
+        source: instance_credentials::InstanceSource,
+    },
+    Keychain,
+    /// `AWS_ACCESS_KEY_ID` and friends in the app's environment; never saved.
+    Environment,
 }
 
 // ---------------------------------------------------------------------------
-// Managed state — holds the sidecar child so we can kill/restart it.
+// Managed state — holds the sidecar child so we can kill/restart it, and the
+// worker sidecars long scans run on.
 // ---------------------------------------------------------------------------
 
-pub struct SidecarState(pub Mutex<Option<CommandChild>>);
+pub struct SidecarState(
+    pub Mutex<Option<CommandChild>>,
+    pub Mutex<sidecar_workers::Workers>,
+);
+
+/// Emitted once `clear_credentials` has removed the saved keys.
+pub const CREDENTIALS_CLEARED_EVENT: &str = "credentials-cleared";
 
 // ---------------------------------------------------------------------------
 // Credential storage helpers (OS keychain + legacy file migration)
 // ---------------------------------------------------------------------------
 
-const KEYRING_SERVICE: &str = "aws-cost-optimizer";
-const KEYRING_ACCOUNT: &str = "aws-credentials";
-
 fn credentials_path(app: &AppHandle) -> std::path::PathBuf {
     app.path()
         .app_config_dir()
@@ -41,17 +256,10 @@ fn credentials_path(app: &AppHandle) -> std::path::PathBuf {
         .join("credentials.json")
 }
 
-fn keyring_entry() -> Result<Entry, String> {
-    Entry::new(KEYRING_SERVICE, KEYRING_ACCOUNT).map_err(|e| e.to_string())
-}
-
-fn read_credentials_from_keyring() -> Option<AwsCredentials> {
-    let entry = keyring_entry().ok()?;
-    match entry.get_password() {
-        Ok(raw) => serde_json::from_str(&raw).ok(),
-        Err(keyring::Error::NoEntry) => None,
-        Err(_) => None,
-    }
+/// Secret entry under the app's service for an arbitrary account (also used
+/// for integration tokens), in the keychain or the encrypted file fallback.
+pub(crate) fn keyring_entry_for(account: &str) -> Result<secret_store::SecretEntry, String> {
+    secret_store::entry(account)
 }
 
 fn read_credentials_from_legacy_file(app: &AppHandle) -> Option<AwsCredentials> {
@@ -69,25 +277,56 @@ fn remove_legacy_credentials_file(app: &AppHandle) -> Result<(), String> {
     }
 }
 
-fn read_credentials(app: &AppHandle) -> Option<AwsCredentials> {
-    if let Some(creds) = read_credentials_from_keyring() {
-        return Some(creds);
+/// Keys from the standard AWS environment variables, as the CLI reads them.
+fn environment_credentials(var: impl Fn(&str) -> Option<String>) -> Option<AwsCredentials> {
+    let var = |name: &str| var(name).filter(|v| !v.trim().is_empty());
+    Some(AwsCredentials {
+        access_key_id: var("AWS_ACCESS_KEY_ID")?,
+        secret_access_key: var("AWS_SECRET_ACCESS_KEY")?.into(),
+        region: var("AWS_REGION")
+            .or_else(|| var("AWS_DEFAULT_REGION"))
+            .unwrap_or_else(|| "us-east-1".into()),
+        session_token: var("AWS_SESSION_TOKEN").map(Into::into),
+        expires_at: var("AWS_CREDENTIAL_EXPIRATION").and_then(|at| at.parse().ok()),
+        partition: None,
+        endpoint_url: var("AWS_ENDPOINT_URL"),
+    })
+}
+
+/// The app's current credentials and their source: a `credential_process`
+/// helper's or the instance's keys while one is in use, then the keychain's,
+/// then the environment's.
+pub(crate) fn resolve_credentials(app: &AppHandle) -> Option<(AwsCredentials, CredentialSource)> {
+    if let Some(creds) = credential_process::current() {
+        let profile = credential_process::active_profile(app).unwrap_or_default();
+        return Some((creds, CredentialSource::CredentialProcess { profile }));
+    }
+    if let Some(creds) = instance_credentials::current() {
+        if let Some(source) = instance_credentials::active_source(app) {
+            return Some((creds, CredentialSource::Instance { source }));
+        }
+    }
+    if let Some(creds) = profiles::read_active(app).ok().flatten() {
+        return Some((creds, CredentialSource::Keychain));
     }
 
     // One-time migration path for older installations that persisted plaintext.
-    let creds = read_credentials_from_legacy_file(app)?;
-    if write_credentials(app, &creds).is_ok() {
-        return Some(creds);
+    if let Some(creds) = read_credentials_from_legacy_file(app) {
+        if write_credentials(app, &creds).is_ok() {
+            return Some((creds, CredentialSource::Keychain));
+        }
     }
 
-    None
+    environment_credentials(|name| std::env::var(name).ok())
+        .map(|creds| (creds, CredentialSource::Environment))
+}
+
+pub(crate) fn read_credentials(app: &AppHandle) -> Option<AwsCredentials> {
+    resolve_credentials(app).map(|(creds, _)| creds)
 }
 
 fn write_credentials(app: &AppHandle, creds: &AwsCredentials) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(creds).map_err(|e| e.to_string())?;
-    keyring_entry()?
-        .set_password(&json)
-        .map_err(|e| e.to_string())?;
+    profiles::write_active(app, creds)?;
     // Best-effort cleanup of old plaintext credential file.
     let _ = remove_legacy_credentials_file(app);
     Ok(())
@@ -97,99 +336,446 @@ fn write_credentials(app: &AppHandle, creds: &AwsCredentials) -> Result<(), Stri
 // Sidecar helpers
 // ---------------------------------------------------------------------------
 
-/// Spawns the FastAPI sidecar with the given credentials injected as env vars.
+/// Where the next sidecar should listen, and the arguments that tell it so:
+/// a private Unix socket where there are sockets, else a free loopback port
+/// so another local server on 8000 is never mistaken for it.
 #[cfg(not(dev))]
-fn spawn_sidecar(app: &AppHandle, creds: &AwsCredentials) -> Result<CommandChild, String> {
+fn sidecar_endpoint() -> Result<(backend::Endpoint, [String; 2]), String> {
+    #[cfg(unix)]
+    {
+        let socket = sidecar_socket::create()
+            .map_err(|e| format!("Cannot create the backend socket: {e}"))?;
+        let arg = socket.display().to_string();
+        Ok((backend::Endpoint::Socket(socket), ["--uds".into(), arg]))
+    }
+    #[cfg(not(unix))]
+    {
+        let port =
+            backend::free_port().map_err(|e| format!("No free port for the backend: {e}"))?;
+        Ok((
+            backend::Endpoint::Tcp(port),
+            ["--port".into(), port.to_string()],
+        ))
+    }
+}
+
+/// A sidecar process just spawned, on its own endpoint and token.
+#[cfg(not(dev))]
+pub(crate) struct SpawnedSidecar {
+    pub child: CommandChild,
+    pub events: tauri::async_runtime::Receiver<tauri_plugin_shell::process::CommandEvent>,
+    pub endpoint: backend::Endpoint,
+    pub token: Zeroizing<String>,
+}
+
+/// Spawns a FastAPI sidecar with the given credentials injected as env vars,
+/// without sending requests to it yet.
+#[cfg(not(dev))]
+pub(crate) fn spawn_process(
+    app: &AppHandle,
+    creds: &AwsCredentials,
+) -> Result<SpawnedSidecar, String> {
+    let (endpoint, args) = sidecar_endpoint()?;
+    let token = Zeroizing::new(hex::encode(secret_store::random::<32>()?));
     let cmd = app
         .shell()
         .sidecar("aws-cost-optimizer-api")
         .map_err(|e| e.to_string())?
+        .args(args)
+        .env("API_TOKEN", token.as_str())
         .env("AWS_ACCESS_KEY_ID", &creds.access_key_id)
-        .env("AWS_SECRET_ACCESS_KEY", &creds.secret_access_key)
+        .env("AWS_SECRET_ACCESS_KEY", creds.secret_access_key())
         .env("AWS_DEFAULT_REGION", &creds.region);
 
-    let cmd = match &creds.session_token {
-        Some(t) if !t.is_empty() => cmd.env("AWS_SESSION_TOKEN", t),
-        _ => cmd,
+    let cmd = match creds.session_token() {
+        Some(t) => cmd.env("AWS_SESSION_TOKEN", t),
+        None => cmd,
     };
+    let cmd = match creds.endpoint_url() {
+        Some(url) => cmd.env("AWS_ENDPOINT_URL", url),
+        None => cmd,
+    };
+    let cmd = cmd.envs(proxy::env_vars()).envs(ca_trust::env_vars());
+
+    let (events, child) = cmd.spawn().map_err(|e| e.to_string())?;
+    Ok(SpawnedSidecar {
+        child,
+        events,
+        endpoint,
+        token,
+    })
+}
 
-    let (_rx, child) = cmd.spawn().map_err(|e| e.to_string())?;
+/// Spawns the sidecar that requests go to.
+#[cfg(not(dev))]
+fn spawn_sidecar(app: &AppHandle, creds: &AwsCredentials) -> Result<CommandChild, String> {
+    let SpawnedSidecar {
+        child,
+        events,
+        endpoint,
+        token,
+    } = spawn_process(app, creds)?;
+    backend::set_endpoint(endpoint);
+    backend::set_api_token(token);
+    sidecar_pid::record(app, child.pid());
+    sidecar_watchdog::watch(app.clone(), child.pid(), events);
+    sidecar_watchdog::starting(app, child.pid(), backend::location());
     Ok(child)
 }
 
-/// Polls the FastAPI health endpoint until it responds or the timeout is reached.
+/// Starts the sidecar at launch and waits for it, without holding a thread
+/// while it comes up.
 #[cfg(not(dev))]
-fn wait_for_backend(timeout_secs: u64) -> bool {
-    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
-    loop {
-        if std::time::Instant::now() >= deadline {
-            return false;
-        }
-        match ureq::get("http://127.0.0.1:8000/api/v1/health").call() {
-            Ok(_) => return true,
-            Err(_) => std::thread::sleep(std::time::Duration::from_millis(300)),
+async fn launch_sidecar(app: AppHandle, creds: AwsCredentials) {
+    let handle = app.clone();
+    let spawned = tauri::async_runtime::spawn_blocking(move || {
+        // A sidecar outliving a crashed app would otherwise keep running.
+        sidecar_pid::reap_orphan(&handle, SHUTDOWN_GRACE);
+        spawn_sidecar(&handle, &creds)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|spawned| spawned);
+    let child = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+            sidecar_watchdog::start_failed(&app, err);
+            return;
         }
+    };
+
+    // Store so save_credentials can kill and restart it.
+    let pid = child.pid();
+    if let Ok(mut guard) = app.state::<SidecarState>().0.lock() {
+        *guard = Some(child);
+    }
+
+    let timeout_secs = health_check::current().startup_timeout_secs;
+    if readiness::wait(&app, pid, timeout_secs).await == readiness::Readiness::TimedOut {
+        sidecar_watchdog::start_failed(
+            &app,
+            format!("Backend did not start within {timeout_secs} seconds"),
+        );
     }
 }
 
-// ---------------------------------------------------------------------------
-// Tauri commands
-// ---------------------------------------------------------------------------
+/// The part of launch that may wait on the keychain, the network or the
+/// sidecar, run once the window is up. Settles the backend phase, which
+/// setup leaves at `Starting`, by starting the sidecar or reporting that
+/// there is nothing to start.
+async fn startup(app: AppHandle) {
+    let handle = app.clone();
+    let (creds, remote) = tauri::async_runtime::spawn_blocking(move || {
+        if let Err(err) = profiles::migrate(&handle) {
+            eprintln!("keychain migration failed: {err}");
+        }
+        sso::verify_on_startup(&handle);
+        credential_process::resume(&handle);
+        instance_credentials::resume(&handle);
+        let remote = remote_backend::apply(&handle);
+        // With an SSO policy, the sidecar waits for a valid login.
+        let creds = read_credentials(&handle).filter(|_| sso::access_granted(&handle));
+        (creds, remote)
+    })
+    .await
+    .unwrap_or((None, Ok(false)));
 
-/// Returns stored AWS credentials, or null if none have been saved yet.
-#[tauri::command]
-fn load_credentials(app: AppHandle) -> Option<AwsCredentials> {
-    read_credentials(&app)
+    // A remote backend replaces the sidecar, even in dev builds.
+    match remote {
+        Ok(true) => return remote_backend::connect(&app).await,
+        Ok(false) => {}
+        Err(err) => return sidecar_watchdog::start_failed(&app, err),
+    }
+
+    // Spawn the sidecar in production builds only. In dev mode the server
+    // is assumed to be running separately
+    // (e.g. `uvicorn app.main:app --port 8000`).
+    #[cfg(not(dev))]
+    match creds {
+        Some(creds) => launch_sidecar(app, creds).await,
+        // The UI detects this and redirects to /settings.
+        None => sidecar_watchdog::stopped(&app, "No credentials are saved"),
+    }
+    #[cfg(dev)]
+    let _ = (app, creds);
 }
 
 /// Persists credentials and (in production builds) restarts the sidecar with
 /// the new environment variables.
-#[tauri::command]
-fn save_credentials(
-    app: AppHandle,
-    creds: AwsCredentials,
-    _state: tauri::State<'_, SidecarState>,
-) -> Result<(), String> {
-    write_credentials(&app, &creds)?;
+pub(crate) fn apply_credentials(app: &AppHandle, creds: &AwsCredentials) -> Result<(), String> {
+    credential_process::clear(app)?;
+    instance_credentials::clear(app)?;
+    write_credentials(app, creds)?;
+    if !sso::access_granted(app) {
+        // The sidecar starts once the SSO login completes.
+        return Ok(());
+    }
+    reload_sidecar(app, creds)
+}
 
+/// Hands a running sidecar new credentials in place, so scans in progress
+/// survive; restarts it when that fails or none is running.
+pub(crate) fn reload_sidecar(app: &AppHandle, creds: &AwsCredentials) -> Result<(), String> {
+    // A remote backend gets the keys with each scan.
+    if remote_backend::active().is_some() {
+        return Ok(());
+    }
+    let running = app
+        .state::<SidecarState>()
+        .0
+        .lock()
+        .is_ok_and(|child| child.is_some());
+    if running {
+        match backend::push_credentials(creds) {
+            Ok(()) => {
+                sidecar_workers::push_credentials(app, creds);
+                return Ok(());
+            }
+            Err(err) => eprintln!("sidecar did not take new credentials, restarting it: {err}"),
+        }
+    }
+    restart_sidecar(app, creds)
+}
+
+/// Replaces the running sidecar (production builds only, and not with a
+/// remote backend).
+pub(crate) fn restart_sidecar(_app: &AppHandle, _creds: &AwsCredentials) -> Result<(), String> {
     #[cfg(not(dev))]
-    {
-        let mut guard = _state.0.lock().map_err(|e| e.to_string())?;
+    if remote_backend::active().is_none() {
+        sidecar_idle::reset();
+        sidecar_workers::stop_all(_app);
+        let state = _app.state::<SidecarState>();
 
         // Kill the old sidecar if one is running.
-        if let Some(old) = guard.take() {
+        let old = state.0.lock().map_err(|e| e.to_string())?.take();
+        if let Some(old) = old {
             let _ = old.kill();
         }
 
-        // Spawn a fresh sidecar with the updated credentials.
-        let child = spawn_sidecar(&app, &creds)?;
+        // Spawn a fresh sidecar with the updated credentials, and wait for
+        // it without holding the lock so stop and status calls still go
+        // through.
+        let child = spawn_sidecar(_app, _creds)?;
+        let timeout_secs = health_check::current().restart_timeout_secs;
+        let failed = match readiness::wait_blocking(_app, child.pid(), timeout_secs) {
+            readiness::Readiness::Ready => None,
+            readiness::Readiness::TimedOut => Some(format!(
+                "Backend did not start within {timeout_secs} seconds"
+            )),
+            readiness::Readiness::Cancelled => Some("Backend start was interrupted".into()),
+        };
+        if let Some(err) = failed {
+            let _ = child.kill();
+            sidecar_pid::clear(_app);
+            return Err(err);
+        }
 
-        if !wait_for_backend(15) {
-            return Err("Backend did not start within 15 seconds".into());
+        let mut guard = state.0.lock().map_err(|e| e.to_string())?;
+        // A restart that finished meanwhile loses to this one.
+        if let Some(other) = guard.replace(child) {
+            let _ = other.kill();
         }
+    }
 
-        *guard = Some(child);
+    Ok(())
+}
+
+/// Kills the running sidecar, if any.
+pub(crate) fn stop_sidecar(app: &AppHandle) -> Result<(), String> {
+    sidecar_idle::reset();
+    sidecar_workers::stop_all(app);
+    let state = app.state::<SidecarState>();
+    let mut guard = state.0.lock().map_err(|e| e.to_string())?;
+    readiness::cancel();
+    if let Some(child) = guard.take() {
+        child.kill().map_err(|e| e.to_string())?;
+        sidecar_pid::clear(app);
     }
+    sidecar_watchdog::set_phase(sidecar_watchdog::BackendPhase::Stopped);
+    Ok(())
+}
+
+/// How long a quitting app lets the sidecar exit on its own before killing it.
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(3);
 
+/// Stops the sidecar as the app quits. On Unix it is asked to exit with
+/// SIGTERM so uvicorn can finish in-flight requests, and killed if it is
+/// still running after [`SHUTDOWN_GRACE`]; elsewhere it is killed outright.
+fn shutdown_sidecar(app: &AppHandle) {
+    readiness::cancel();
+    // A suspended sidecar would not see SIGTERM.
+    sidecar_idle::reset();
+    sidecar_workers::stop_all(app);
+    let state = app.state::<SidecarState>();
+    // Taken out first so the watchdog does not restart it.
+    let Some(child) = state.0.lock().ok().and_then(|mut guard| guard.take()) else {
+        return;
+    };
+    if stop_gracefully(child) {
+        sidecar_pid::clear(app);
+        backend::release_endpoint();
+    }
+}
+
+/// Stops a sidecar already taken out of [`SidecarState`]: SIGTERM first on
+/// Unix, killed if it has not exited within [`SHUTDOWN_GRACE`]. Whether it
+/// is gone; one still running is left in the PID file for the next launch
+/// to stop.
+fn stop_gracefully(child: CommandChild) -> bool {
+    let pid = child.pid();
+    #[cfg(unix)]
+    {
+        // SAFETY: signals the sidecar this app spawned.
+        unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+        if sidecar_pid::wait_gone(pid, SHUTDOWN_GRACE) {
+            return true;
+        }
+        eprintln!("sidecar {pid} ignored SIGTERM; killing it");
+    }
+    if let Err(err) = child.kill() {
+        eprintln!("could not kill sidecar {pid}: {err}");
+        return false;
+    }
+    #[cfg(unix)]
+    {
+        if !sidecar_pid::wait_gone(pid, SHUTDOWN_GRACE) {
+            eprintln!("sidecar {pid} is still running after being killed");
+            return false;
+        }
+    }
+    true
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Signs out: deletes every saved profile from the keychain and the legacy
+/// plaintext file, stops any `credential_process` helper, instance
+/// credentials and the sidecar,
+/// then tells the UI to return to setup. Keys from the environment are not
+/// saved, so they still apply afterwards.
+#[tauri::command]
+fn clear_credentials(app: AppHandle) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    credential_process::clear(&app)?;
+    instance_credentials::clear(&app)?;
+    profiles::delete_all(&app)?;
+    remove_legacy_credentials_file(&app)?;
+    stop_sidecar(&app)?;
+    let _ = app.emit(CREDENTIALS_CLEARED_EVENT, ());
     Ok(())
 }
 
+/// Returns the stored AWS credentials without their secrets, or null if none
+/// have been saved yet.
+#[tauri::command]
+fn load_credentials(app: AppHandle) -> CommandResult<Option<CredentialsView>> {
+    app_lock::ensure_unlocked(&app)?;
+    Ok(read_credentials(&app).as_ref().map(CredentialsView::from))
+}
+
+/// The UI never receives stored secrets, so a form saved with the secret left
+/// blank means "keep the stored one" (its session token too, unless a new one
+/// was entered) for the same access key.
+pub(crate) fn with_stored_secrets(app: &AppHandle, mut creds: AwsCredentials) -> AwsCredentials {
+    if !creds.secret_access_key().trim().is_empty() {
+        return creds;
+    }
+    if let Some(stored) =
+        read_credentials(app).filter(|stored| stored.access_key_id == creds.access_key_id)
+    {
+        creds.secret_access_key = stored.secret_access_key;
+        if creds.session_token().is_none() {
+            creds.session_token = stored.session_token;
+            creds.expires_at = stored.expires_at;
+        }
+    }
+    creds
+}
+
+/// Where the credentials `load_credentials` returns come from.
+#[tauri::command]
+fn get_credential_source(app: AppHandle) -> Option<CredentialSource> {
+    resolve_credentials(&app).map(|(_, source)| source)
+}
+
+/// Persists credentials and (in production builds) restarts the sidecar.
+#[tauri::command]
+fn save_credentials(app: AppHandle, creds: AwsCredentials) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    let creds = with_stored_secrets(&app, creds);
+    partition::validate_region(&creds.region, creds.partition)?;
+    aws::validate_endpoint_url(creds.endpoint_url())?;
+    apply_credentials(&app, &creds).map_err(AppError::from)
+}
+
+/// Outcome of [`restart_backend`].
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendRestarted {
+    pub url: String,
+    /// From the old sidecar being killed to the new one answering.
+    pub startup_ms: u64,
+}
+
+/// Kills the sidecar and starts a new one with the current credentials,
+/// returning once it answers its health check. In dev builds the server runs
+/// separately, so this only checks that it answers.
+#[tauri::command]
+async fn restart_backend(app: AppHandle) -> CommandResult<BackendRestarted> {
+    app_lock::ensure_unlocked(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let creds = read_credentials(&app)
+            .ok_or_else(|| AppError::Credentials("No AWS credentials are saved".into()))?;
+        if !sso::access_granted(&app) {
+            return Err(AppError::Credentials(
+                "Sign in with SSO before starting the backend".into(),
+            ));
+        }
+        let started = std::time::Instant::now();
+        restart_sidecar(&app, &creds)?;
+        if !backend::healthy() {
+            return Err(AppError::Sidecar("The backend is not responding".into()));
+        }
+        Ok(BackendRestarted {
+            url: backend::location(),
+            startup_ms: started.elapsed().as_millis() as u64,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 // ---------------------------------------------------------------------------
 // Updater commands (production-only; dev builds skip the update check)
 // ---------------------------------------------------------------------------
 
+/// The updater, through the proxy in force for the update host and trusting
+/// any extra CA certificates.
+#[cfg(not(dev))]
+fn updater(app: &AppHandle) -> Result<tauri_plugin_updater::Updater, String> {
+    let mut builder = app.updater_builder();
+    if let Some(proxy) = proxy::for_host("github.com") {
+        builder = builder.proxy(tauri::Url::parse(&proxy).map_err(|e| e.to_string())?);
+    }
+    if let Some(pem) = ca_trust::pem() {
+        let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| e.to_string())?;
+        builder = builder.configure_client(move |client| {
+            certs.iter().fold(client, |client, cert| {
+                client.add_root_certificate(cert.clone())
+            })
+        });
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
 /// Returns the new version string if an update is available, or `None`.
 #[tauri::command]
-async fn check_for_updates(app: AppHandle) -> Result<Option<String>, String> {
+async fn check_for_updates(_app: AppHandle) -> CommandResult<Option<String>> {
     #[cfg(not(dev))]
     {
-        let update = app
-            .updater()
-            .map_err(|e| e.to_string())?
-            .check()
-            .await
-            .map_err(|e| e.to_string())?;
-        return Ok(update.map(|u| u.version.to_string()));
+        let update = updater(&_app)?.check().await.map_err(|e| e.to_string())?;
+        Ok(update.map(|u| u.version.to_string()))
     }
     #[cfg(dev)]
     Ok(None)
@@ -198,15 +784,10 @@ async fn check_for_updates(app: AppHandle) -> Result<Option<String>, String> {
 /// Downloads and installs the pending update. The app must be restarted
 /// afterwards; Tauri handles the restart automatically after install.
 #[tauri::command]
-async fn install_update(app: AppHandle) -> Result<(), String> {
+async fn install_update(_app: AppHandle) -> CommandResult<()> {
     #[cfg(not(dev))]
     {
-        let update = app
-            .updater()
-            .map_err(|e| e.to_string())?
-            .check()
-            .await
-            .map_err(|e| e.to_string())?;
+        let update = updater(&_app)?.check().await.map_err(|e| e.to_string())?;
         if let Some(u) = update {
             u.download_and_install(|_, _| {}, || {})
                 .await
@@ -222,46 +803,310 @@ async fn install_update(app: AppHandle) -> Result<(), String> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let commands = tauri::generate_handler![
+        load_credentials,
+        os_auth::has_credentials,
+        os_auth::reveal_credentials,
+        os_auth::get_reveal_settings,
+        os_auth::save_reveal_settings,
+        get_credential_source,
+        sts::get_credential_summary,
+        permissions::check_permissions,
+        credential_export::copy_credentials,
+        multi_account::scan_accounts,
+        organizations::get_organization_settings,
+        organizations::save_organization_settings,
+        organizations::list_organization_accounts,
+        organizations::scan_organization,
+        regions::list_available_regions,
+        regions::get_profile_regions,
+        regions::save_profile_regions,
+        key_rotation::key_rotation_status,
+        key_rotation::save_key_rotation_settings,
+        save_credentials,
+        restart_backend,
+        clear_credentials,
+        check_for_updates,
+        install_update,
+        github::get_github_settings,
+        github::save_github_settings,
+        github::create_github_issues,
+        github::list_github_issue_links,
+        metrics::get_metrics_settings,
+        metrics::save_metrics_settings,
+        exporters::grafana::export_grafana_data,
+        webhooks::list_webhooks,
+        webhooks::save_webhook,
+        webhooks::delete_webhook,
+        webhooks::list_webhook_deliveries,
+        webhooks::sample_webhook_payload,
+        local_api::get_local_api_settings,
+        local_api::save_local_api_settings,
+        local_api::get_local_api_token,
+        local_api::rotate_local_api_token,
+        grpc::get_grpc_settings,
+        grpc::save_grpc_settings,
+        terraform::correlate_terraform_state,
+        cloudformation::get_stack_costs,
+        pagerduty::get_pagerduty_settings,
+        pagerduty::save_pagerduty_settings,
+        scheduler::get_schedule_settings,
+        scheduler::save_schedule_settings,
+        exporters::calendar::export_schedule_calendar,
+        exporters::well_architected::export_well_architected_report,
+        exporters::focus::export_focus_data,
+        aws_cli::list_aws_cli_profiles,
+        aws_cli::get_profile_sync_settings,
+        aws_cli::save_profile_sync_settings,
+        aws_cli::write_credentials_to_cli_profile,
+        aws_cli::import_aws_cli_profiles,
+        aws_cli::list_changed_cli_profiles,
+        credential_process::get_credential_process_status,
+        credential_process::use_credential_process,
+        credential_process::stop_credential_process,
+        instance_credentials::get_instance_credential_status,
+        instance_credentials::use_instance_credentials,
+        instance_credentials::stop_instance_credentials,
+        providers::get_multi_cloud_summary,
+        providers::list_provider_recommendations,
+        providers::start_provider_scan,
+        datadog::get_datadog_settings,
+        datadog::save_datadog_settings,
+        datadog::submit_datadog_metrics,
+        servicenow::get_servicenow_settings,
+        servicenow::save_servicenow_settings,
+        servicenow::create_servicenow_change,
+        servicenow::list_servicenow_changes,
+        websocket::get_websocket_settings,
+        websocket::save_websocket_settings,
+        sso::get_sso_status,
+        sso::start_sso_login,
+        sso::poll_sso_login,
+        sso::sso_logout,
+        google_sheets::get_sheets_settings,
+        google_sheets::save_sheets_settings,
+        google_sheets::publish_sheets_summary,
+        exporters::archive::get_report_archive_settings,
+        exporters::archive::save_report_archive_settings,
+        plugins::list_plugins,
+        plugins::configure_plugin,
+        plugins::run_plugin,
+        cost_explorer::query_costs,
+        cost_explorer::clear_cost_cache,
+        cost_explorer::get_cost_forecast,
+        cost_explorer::get_cost_explorer_usage,
+        cost_explorer::get_cost_explorer_budget,
+        cost_explorer::save_cost_explorer_budget,
+        cur::ingest_cur_file,
+        cur::get_cur_summary,
+        uploads::upload_file,
+        downloads::download_export,
+        tasks::cancel_task,
+        perf::get_performance_stats,
+        perf::reset_performance_stats,
+        api_version::get_api_version,
+        jobs::list_jobs,
+        jobs::retry_job,
+        health::get_app_health,
+        remote_backend::get_remote_backend_settings,
+        remote_backend::save_remote_backend_settings,
+        proxy::get_proxy_settings,
+        ca_trust::get_ca_trust_settings,
+        ca_trust::save_ca_trust_settings,
+        resilience::get_resilience_settings,
+        resilience::save_resilience_settings,
+        response_cache::get_response_cache_settings,
+        response_cache::save_response_cache_settings,
+        response_cache::clear_response_cache,
+        request_queue::get_request_queue_settings,
+        request_queue::save_request_queue_settings,
+        proxy::save_proxy_settings,
+        selftest::run_backend_selftest,
+        backend::get_backend_url,
+        backend::backend_request,
+        backend::cancel_request,
+        analysis_stream::stream_analysis,
+        analysis_stream::stop_analysis_stream,
+        sidecar_log::get_backend_logs,
+        sidecar_stats::get_backend_stats,
+        sidecar_watchdog::get_backend_phase,
+        health_check::get_backend_status,
+        health_check::get_health_check_settings,
+        health_check::save_health_check_settings,
+        health_check::save_health_check_overrides,
+        sidecar_memory::get_sidecar_memory_settings,
+        sidecar_memory::save_sidecar_memory_settings,
+        sidecar_idle::get_power_settings,
+        sidecar_idle::save_power_settings,
+        sidecar_workers::get_worker_settings,
+        sidecar_workers::save_worker_settings,
+        exposure::get_backend_exposure,
+        profiles::list_profiles,
+        profiles::save_profile,
+        profiles::delete_profile,
+        profiles::save_profile_label,
+        profiles::refresh_profile_alias,
+        profile_bundle::export_profile_bundle,
+        profile_bundle::import_profile_bundle,
+        profiles::set_active_profile,
+        identity_center::get_identity_center_status,
+        identity_center::start_identity_center_login,
+        identity_center::poll_identity_center_login,
+        identity_center::list_identity_center_accounts,
+        identity_center::use_identity_center_role,
+        identity_center::identity_center_logout,
+        sts::validate_credentials,
+        sts::assume_role,
+        sts::assume_role_chain,
+        sts::start_mfa_session,
+        secret_store::get_secret_store_status,
+        secret_store::save_secret_store_settings,
+        secret_store::unlock_secret_store,
+        app_lock::get_app_lock_status,
+        app_lock::save_app_lock_settings,
+        app_lock::lock_app,
+        app_lock::unlock_app,
+        app_lock::record_app_activity,
+    ];
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
-        .manage(SidecarState(Mutex::new(None)))
-        .invoke_handler(tauri::generate_handler![
-            load_credentials,
-            save_credentials,
-            check_for_updates,
-            install_update,
-        ])
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(SidecarState(Mutex::new(None), Mutex::default()))
+        .manage(metrics::MetricsState(local_server::LocalServer::new()))
+        .manage(local_api::LocalApiState(local_server::LocalServer::new()))
+        .manage(grpc::GrpcState(Mutex::new(None)))
+        .manage(websocket::WebSocketState::default())
+        .manage(sso::SsoState::default())
+        .manage(identity_center::IdentityCenterState::default())
+        .manage(cost_explorer::CostCacheState::default())
+        .manage(tasks::TaskState::default())
+        .manage(app_lock::AppLockState::default())
+        .invoke_handler(move |invoke| {
+            let app = invoke.message.webview_ref().app_handle();
+            if app_lock::refuses(app, invoke.message.command()) {
+                invoke
+                    .resolver
+                    .reject(AppError::Locked("The app is locked".into()));
+                return true;
+            }
+            commands(invoke)
+        })
+        .on_window_event(|_window, event| {
+            // Back from the background: wake a sidecar put to sleep.
+            if let tauri::WindowEvent::Focused(true) = event {
+                sidecar_idle::on_focus();
+            }
+        })
         .setup(|app| {
-            // Spawn the sidecar in production builds only. In dev mode the
-            // server is assumed to be running separately
-            // (e.g. `uvicorn app.main:app --port 8000`).
+            #[cfg(feature = "mock")]
+            mock::install_from_env();
+            proxy::apply(app.handle());
+            if let Err(err) = ca_trust::apply(app.handle()) {
+                eprintln!("extra CA certificates not trusted: {err}");
+            }
+            health_check::apply(app.handle());
+            resilience::apply(app.handle());
+            response_cache::apply(app.handle());
+            request_queue::apply(app.handle());
+            secret_store::init(app.handle());
+
+            // Keychain reads, the SSO check and the sidecar all happen in
+            // the background so the window shows at once; the webview holds
+            // its requests until `backend://ready` or `backend://stopped`.
             #[cfg(not(dev))]
-            {
-                let handle = app.handle().clone();
-                if let Some(creds) = read_credentials(&handle) {
-                    let child = spawn_sidecar(&handle, &creds)
-                        .expect("failed to spawn aws-cost-optimizer-api sidecar");
-
-                    // Store so save_credentials can kill and restart it.
-                    let sidecar_state = app.state::<SidecarState>();
-                    *sidecar_state.0.lock().unwrap() = Some(child);
-
-                    if !wait_for_backend(10) {
-                        return Err("Backend did not start within 10 seconds".into());
-                    }
-                }
-                // No credentials saved yet: sidecar not started.
-                // The UI detects this and redirects to /settings.
+            sidecar_watchdog::set_phase(sidecar_watchdog::BackendPhase::Starting);
+            tauri::async_runtime::spawn(startup(app.handle().clone()));
+
+            // A taken port must not block startup.
+            if let Err(err) = metrics::apply(app.handle()) {
+                eprintln!("metrics exporter not started: {err}");
+            }
+            if let Err(err) = local_api::apply(app.handle()) {
+                eprintln!("local API not started: {err}");
+            }
+            if let Err(err) = grpc::apply(app.handle()) {
+                eprintln!("gRPC service not started: {err}");
             }
+            if let Err(err) = websocket::apply(app.handle()) {
+                eprintln!("WebSocket bridge not started: {err}");
+            }
+
+            events::spawn_run_watcher(app.handle().clone());
+            jobs::spawn_job_runner(app.handle().clone());
+            scheduler::spawn_scheduler(app.handle().clone());
+            aws_cli::spawn_profile_sync(app.handle().clone());
+            aws_cli::spawn_file_watch(app.handle().clone());
+            datadog::spawn_daily_submission(app.handle().clone());
+            warmup::spawn_warmup(app.handle().clone());
+            expiry::spawn_expiry_watch(app.handle().clone());
+            app_lock::spawn_idle_lock(app.handle().clone());
+            key_rotation::spawn_rotation_watch(app.handle().clone());
+            #[cfg(not(dev))]
+            sidecar_memory::spawn_memory_watch(app.handle().clone());
+            #[cfg(not(dev))]
+            sidecar_idle::spawn_idle_watch(app.handle().clone());
+            #[cfg(not(dev))]
+            sidecar_workers::init(app.handle().clone());
 
-            // Show the main window (created hidden in tauri.conf.json so we
-            // can wait for the backend before revealing it).
+            // Show the main window (created hidden in tauri.conf.json so it
+            // appears only once set up; nothing above waits on the sidecar).
             let window = app.get_webview_window("main").unwrap();
             window.show()?;
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Quitting must not leave the Python process behind.
+            if let tauri::RunEvent::ExitRequested { .. } | tauri::RunEvent::Exit = event {
+                shutdown_sidecar(app);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_keys_need_both_halves() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let creds = environment_credentials(env(&[
+            ("AWS_ACCESS_KEY_ID", "AKIAEXAMPLE"),
+            ("AWS_SECRET_ACCESS_KEY", "secret"),
+            ("AWS_DEFAULT_REGION", "eu-west-1"),
+            ("AWS_SESSION_TOKEN", ""),
+        ]))
+        .unwrap();
+        assert_eq!(creds.region, "eu-west-1");
+        assert_eq!(creds.session_token(), None);
+
+        assert!(environment_credentials(env(&[("AWS_ACCESS_KEY_ID", "AKIAEXAMPLE")])).is_none());
+    }
+
+    #[tokio::test]
+    async fn custom_endpoints_reach_the_sdk_and_the_keychain() {
+        let creds = AwsCredentials {
+            endpoint_url: Some(" http://localhost:4566 ".into()),
+            ..mock::credentials()
+        };
+        let config = aws::sdk_config_from(creds.clone()).await;
+        assert_eq!(config.endpoint_url(), Some("http://localhost:4566"));
+        let stored: AwsCredentials =
+            serde_json::from_str(&creds.to_keyring_json().unwrap()).unwrap();
+        assert_eq!(stored.endpoint_url(), Some("http://localhost:4566"));
+
+        assert!(aws::validate_endpoint_url(Some("localhost:4566")).is_err());
+        assert!(aws::validate_endpoint_url(None).is_ok());
+    }
 }
@@ -0,0 +1,177 @@
+//! Opt-in localhost REST API so scripts can reuse the app's run data and
+//! trigger scans through the already-authenticated sidecar. Every request
+//! must carry `Authorization: Bearer <token>`; the token lives in the keychain.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::local_server::{header, LocalServer};
+use crate::{app_lock, backend, keyring_entry_for, settings};
+
+const DEFAULT_PORT: u16 = 8765;
+pub(crate) const TOKEN_ACCOUNT: &str = "local-api-token";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LocalApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for LocalApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+pub struct LocalApiState(pub LocalServer);
+
+// ---------------------------------------------------------------------------
+// Token helpers
+// ---------------------------------------------------------------------------
+
+fn generate_token() -> String {
+    format!("aco_{}", uuid::Uuid::new_v4().simple())
+}
+
+/// Returns the stored token, creating one on first use.
+pub(crate) fn ensure_token() -> Result<String, String> {
+    let entry = keyring_entry_for(TOKEN_ACCOUNT)?;
+    match entry.get_password() {
+        Ok(token) => Ok(token),
+        Err(keyring::Error::NoEntry) => {
+            let token = generate_token();
+            entry.set_password(&token).map_err(|e| e.to_string())?;
+            Ok(token)
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Compares without short-circuiting so response timing does not leak the token.
+pub(crate) fn token_matches(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+// ---------------------------------------------------------------------------
+// Request handling
+// ---------------------------------------------------------------------------
+
+/// Maps a public route onto the sidecar path it proxies to.
+fn route(method: &tiny_http::Method, path: &str) -> Option<String> {
+    use tiny_http::Method::{Get, Post};
+
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (method, segments.as_slice()) {
+        (Get, ["v1", "runs"]) => Some("/optimizer/runs".into()),
+        (Get, ["v1", "runs", run_id]) => Some(format!("/optimizer/runs/{run_id}")),
+        (Post, ["v1", "scan"]) => Some("/optimizer/scan".into()),
+        (Post, ["v1", "score"]) => Some("/optimizer/score".into()),
+        _ => None,
+    }
+}
+
+fn json_response(status: u16, body: String) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    tiny_http::Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"))
+}
+
+fn handle(token: &str, mut request: tiny_http::Request) {
+    let authorized = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .and_then(|h| h.value.as_str().strip_prefix("Bearer "))
+        .is_some_and(|provided| token_matches(token, provided));
+    if !authorized {
+        let _ = request.respond(json_response(401, r#"{"detail":"unauthorized"}"#.into()));
+        return;
+    }
+
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let Some(target) = route(request.method(), &path) else {
+        let _ = request.respond(json_response(404, r#"{"detail":"not found"}"#.into()));
+        return;
+    };
+
+    let mut body = String::new();
+    if *request.method() == tiny_http::Method::Post {
+        let _ = request.as_reader().read_to_string(&mut body);
+    }
+    let method = request.method().as_str().to_string();
+    let body = (!body.is_empty()).then_some(body.as_str());
+    let response = match backend::forward(&method, &target, body) {
+        Ok((status, body)) => json_response(status, body),
+        Err(err) => json_response(
+            502,
+            serde_json::json!({ "detail": format!("backend unavailable: {err}") }).to_string(),
+        ),
+    };
+    let _ = request.respond(response);
+}
+
+/// Starts (or restarts) the API according to the saved settings.
+pub fn apply(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<LocalApiState>();
+    let cfg = settings::load(app).local_api;
+    if !cfg.enabled {
+        return state.0.stop();
+    }
+    let token = ensure_token()?;
+    state
+        .0
+        .restart(cfg.port, move |request| handle(&token, request))
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_local_api_settings(app: AppHandle) -> LocalApiSettings {
+    settings::load(&app).local_api
+}
+
+#[tauri::command]
+pub fn save_local_api_settings(app: AppHandle, config: LocalApiSettings) -> CommandResult<()> {
+    let mut all = settings::load(&app);
+    all.local_api = config;
+    settings::save(&app, &all)?;
+    apply(&app).map_err(AppError::from)
+}
+
+/// Returns the bearer token clients must send, creating it if needed.
+#[tauri::command]
+pub fn get_local_api_token(app: AppHandle) -> CommandResult<String> {
+    app_lock::ensure_unlocked(&app)?;
+    ensure_token().map_err(AppError::from)
+}
+
+/// Replaces the token and restarts the API so old tokens stop working.
+#[tauri::command]
+pub fn rotate_local_api_token(app: AppHandle) -> CommandResult<String> {
+    app_lock::ensure_unlocked(&app)?;
+    let token = generate_token();
+    keyring_entry_for(TOKEN_ACCOUNT)?
+        .set_password(&token)
+        .map_err(|e| e.to_string())?;
+    apply(&app)?;
+    crate::grpc::apply(&app)?;
+    crate::websocket::apply(&app)?;
+    Ok(token)
+}
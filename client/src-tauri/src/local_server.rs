@@ -0,0 +1,76 @@
+//! Shared lifecycle for the small localhost HTTP servers the shell can expose
+//! (metrics exporter, local REST API). Servers always bind to 127.0.0.1.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Requests one server handles at once; more are turned away with a 503,
+/// so a flood cannot start unbounded threads.
+const MAX_IN_FLIGHT: usize = 8;
+
+pub struct LocalServer(Mutex<Option<Arc<tiny_http::Server>>>);
+
+impl LocalServer {
+    pub fn new() -> Self {
+        Self(Mutex::new(None))
+    }
+
+    pub fn stop(&self) -> Result<(), String> {
+        let mut guard = self.0.lock().map_err(|e| e.to_string())?;
+        if let Some(server) = guard.take() {
+            server.unblock();
+        }
+        Ok(())
+    }
+
+    /// Stops any running instance, binds `port`, and serves each request on
+    /// its own background thread with `handler`, which must respond to it. A
+    /// slow request, such as a scan, does not hold up the others.
+    pub fn restart<F>(&self, port: u16, handler: F) -> Result<(), String>
+    where
+        F: Fn(tiny_http::Request) + Send + Sync + 'static,
+    {
+        self.stop()?;
+        let server = tiny_http::Server::http(("127.0.0.1", port))
+            .map(Arc::new)
+            .map_err(|e| format!("Could not bind port {port}: {e}"))?;
+        *self.0.lock().map_err(|e| e.to_string())? = Some(server.clone());
+        let handler = Arc::new(handler);
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        std::thread::spawn(move || {
+            // `incoming_requests` ends once `unblock` is called from `stop`.
+            for request in server.incoming_requests() {
+                if in_flight.fetch_add(1, Ordering::SeqCst) >= MAX_IN_FLIGHT {
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let busy = tiny_http::Response::from_string("Too many requests in flight")
+                        .with_status_code(503)
+                        .with_header(header("Retry-After", "1"));
+                    let _ = request.respond(busy);
+                    continue;
+                }
+                let handler = handler.clone();
+                let slot = InFlight(in_flight.clone());
+                std::thread::spawn(move || {
+                    let _slot = slot;
+                    handler(request);
+                });
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Frees its request's place among [`MAX_IN_FLIGHT`], even if the handler
+/// panics.
+struct InFlight(Arc<AtomicUsize>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn header(name: &str, value: &str) -> tiny_http::Header {
+    tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes())
+        .expect("header names and values are ASCII")
+}
@@ -0,0 +1,189 @@
+//! Optional Prometheus exporter bound to localhost. Run, finding, savings and
+//! sidecar health metrics are collected from the sidecar on every scrape, so
+//! the endpoint never serves stale data. Spend and forecast (`aws_cost_*`)
+//! come from Cost Explorer through its cache, which bounds how often a scrape
+//! actually calls AWS.
+
+use std::fmt::Write as _;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::cost_explorer::{self, Granularity};
+use crate::error::{AppError, CommandResult};
+use crate::local_server::{header, LocalServer};
+use crate::{backend, read_credentials, settings, sso};
+
+const DEFAULT_PORT: u16 = 9464;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+/// Holds the running exporter so it can be stopped when settings change.
+pub struct MetricsState(pub LocalServer);
+
+// ---------------------------------------------------------------------------
+// Collection
+// ---------------------------------------------------------------------------
+
+fn gauge(out: &mut String, name: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} gauge");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+/// Month-to-date spend and the forecast for the rest of the month. Left out
+/// without usable credentials or when Cost Explorer cannot answer.
+fn spend(app: &AppHandle, out: &mut String) {
+    if read_credentials(app).is_none() || !sso::access_granted(app) {
+        return;
+    }
+    let query = cost_explorer::month_to_date(Granularity::Monthly, Vec::new());
+    match tauri::async_runtime::block_on(cost_explorer::query(app, &query)) {
+        Ok(result) => gauge(
+            out,
+            "aws_cost_month_to_date_dollars",
+            "Unblended spend of the current month so far.",
+            result
+                .periods
+                .iter()
+                .filter_map(|period| period.total.get("UnblendedCost"))
+                .map(|metric| metric.amount)
+                .sum(),
+        ),
+        Err(err) => eprintln!("metrics: month-to-date spend unavailable: {err}"),
+    }
+    match tauri::async_runtime::block_on(cost_explorer::forecast(app)) {
+        Ok(forecast) => {
+            gauge(
+                out,
+                "aws_cost_forecast_rest_of_month_dollars",
+                "Forecast unblended spend from today to the end of the month.",
+                forecast.mean,
+            );
+            if let Some(lower) = forecast.lower {
+                gauge(
+                    out,
+                    "aws_cost_forecast_rest_of_month_lower_dollars",
+                    "Lower bound of the forecast's 80% prediction interval.",
+                    lower,
+                );
+            }
+            if let Some(upper) = forecast.upper {
+                gauge(
+                    out,
+                    "aws_cost_forecast_rest_of_month_upper_dollars",
+                    "Upper bound of the forecast's 80% prediction interval.",
+                    upper,
+                );
+            }
+        }
+        Err(err) => eprintln!("metrics: spend forecast unavailable: {err}"),
+    }
+}
+
+fn render(app: &AppHandle) -> String {
+    let mut out = String::new();
+
+    let started = Instant::now();
+    let backend_up = backend::healthy();
+    gauge(
+        &mut out,
+        "aws_cost_optimizer_backend_up",
+        "Whether the sidecar health check succeeded.",
+        if backend_up { 1.0 } else { 0.0 },
+    );
+    gauge(
+        &mut out,
+        "aws_cost_optimizer_backend_health_latency_seconds",
+        "Latency of the sidecar health check.",
+        started.elapsed().as_secs_f64(),
+    );
+
+    spend(app, &mut out);
+
+    // Scrapes come in the background; they do not wake a sleeping sidecar.
+    if let Some(Ok(runs)) = crate::sidecar_idle::unless_asleep(backend::list_runs) {
+        gauge(
+            &mut out,
+            "aws_cost_optimizer_runs",
+            "Number of optimizer runs in the local store.",
+            runs.len() as f64,
+        );
+        if let Some(latest) = runs.first() {
+            gauge(
+                &mut out,
+                "aws_cost_optimizer_latest_findings",
+                "Recommendations produced by the most recent run.",
+                latest.recommendation_count as f64,
+            );
+            gauge(
+                &mut out,
+                "aws_cost_optimizer_latest_estimated_monthly_savings_dollars",
+                "Estimated monthly savings of the most recent run.",
+                latest.estimated_monthly_savings,
+            );
+        }
+    }
+
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Server lifecycle
+// ---------------------------------------------------------------------------
+
+fn respond(app: &AppHandle, request: tiny_http::Request) {
+    let response = if request.url() == "/metrics" {
+        tiny_http::Response::from_string(render(app))
+            .with_header(header("Content-Type", "text/plain; version=0.0.4"))
+    } else {
+        tiny_http::Response::from_string("not found").with_status_code(404)
+    };
+    let _ = request.respond(response);
+}
+
+/// Starts (or restarts) the exporter according to the saved settings.
+pub fn apply(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<MetricsState>();
+    let cfg = settings::load(app).metrics;
+    if !cfg.enabled {
+        return state.0.stop();
+    }
+    let app = app.clone();
+    state
+        .0
+        .restart(cfg.port, move |request| respond(&app, request))
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_metrics_settings(app: AppHandle) -> MetricsSettings {
+    settings::load(&app).metrics
+}
+
+/// Saves the exporter configuration and starts or stops it immediately.
+#[tauri::command]
+pub fn save_metrics_settings(app: AppHandle, config: MetricsSettings) -> CommandResult<()> {
+    let mut all = settings::load(&app);
+    all.metrics = config;
+    settings::save(&app, &all)?;
+    apply(&app).map_err(AppError::from)
+}
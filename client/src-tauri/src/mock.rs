@@ -0,0 +1,355 @@
+//! Fixture-backed stand-ins for the two things the shell talks to: the
+//! sidecar ([`MockSidecar`]) and AWS ([`ReplayClient`], an SDK HTTP client).
+//! The test suite uses them directly; `mock` builds install both at startup
+//! when `AWS_COST_OPTIMIZER_FIXTURES` points at a fixture directory, so the
+//! UI can be driven without credentials or a running sidecar.
+//!
+//! Fixtures are recorded exchanges, one JSON array per file, under
+//! `tests/fixtures`. The first exchange matching a request answers it;
+//! unmatched requests get a 404 naming the request.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use aws_smithy_runtime_api::client::http::{
+    HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings, SharedHttpConnector,
+};
+use aws_smithy_runtime_api::client::orchestrator::{HttpRequest, HttpResponse};
+use aws_smithy_runtime_api::client::runtime_components::RuntimeComponents;
+use aws_smithy_runtime_api::http::StatusCode;
+use aws_smithy_types::body::SdkBody;
+use serde::Deserialize;
+
+use crate::backend::{self, Sidecar};
+use crate::error::AppError;
+
+const FIXTURES_ENV: &str = "AWS_COST_OPTIMIZER_FIXTURES";
+
+fn fixture_dir() -> PathBuf {
+    std::env::var_os(FIXTURES_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"))
+}
+
+fn read_exchanges<T: serde::de::DeserializeOwned>(path: &Path) -> Vec<T> {
+    let content = std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("fixture {} unreadable: {e}", path.display()));
+    serde_json::from_str(&content)
+        .unwrap_or_else(|e| panic!("fixture {} malformed: {e}", path.display()))
+}
+
+/// Exchanges of a fixture file, or of every `.json` file in a fixture
+/// directory in name order, relative to the fixture directory.
+fn exchanges<T: serde::de::DeserializeOwned>(name: &str) -> Vec<T> {
+    let path = fixture_dir().join(name);
+    if !path.is_dir() {
+        return read_exchanges(&path);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&path)
+        .unwrap_or_else(|e| panic!("fixtures {} unreadable: {e}", path.display()))
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files.iter().flat_map(|file| read_exchanges(file)).collect()
+}
+
+/// A recorded body: JSON is sent as-is, a string verbatim (e.g. XML).
+fn body_text(body: &serde_json::Value) -> String {
+    match body {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sidecar
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize, Clone, Debug)]
+struct SidecarExchange {
+    method: String,
+    /// `*` matches any one path segment, e.g. `/optimizer/scan/*/progress`.
+    path: String,
+    #[serde(default = "ok_status")]
+    status: u16,
+    #[serde(default)]
+    body: serde_json::Value,
+}
+
+fn ok_status() -> u16 {
+    200
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Option<String>,
+}
+
+/// Sidecar that answers from recorded exchanges and records every request.
+#[derive(Default)]
+pub struct MockSidecar {
+    exchanges: Vec<SidecarExchange>,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let path = path.split('?').next().unwrap_or_default();
+    let (pattern, path): (Vec<_>, Vec<_>) =
+        (pattern.split('/').collect(), path.split('/').collect());
+    pattern.len() == path.len()
+        && pattern
+            .iter()
+            .zip(&path)
+            .all(|(want, got)| *want == "*" || want == got)
+}
+
+impl MockSidecar {
+    pub fn load(name: &str) -> Self {
+        Self {
+            exchanges: exchanges(name),
+            requests: Mutex::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl Sidecar for MockSidecar {
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+    ) -> Result<(u16, String), AppError> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(RecordedRequest {
+                method: method.to_string(),
+                path: path.to_string(),
+                body: body.map(str::to_string),
+            });
+        Ok(self
+            .exchanges
+            .iter()
+            .find(|ex| ex.method.eq_ignore_ascii_case(method) && path_matches(&ex.path, path))
+            .map(|ex| (ex.status, body_text(&ex.body)))
+            .unwrap_or_else(|| {
+                let detail = format!("No fixture for {method} {path}");
+                (404, serde_json::json!({ "detail": detail }).to_string())
+            }))
+    }
+}
+
+/// The sidecar transport is process-wide, so tests that install one run
+/// one at a time; dropping the guard restores HTTP.
+#[cfg(test)]
+pub struct SidecarGuard {
+    pub sidecar: Arc<MockSidecar>,
+    _serial: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl Drop for SidecarGuard {
+    fn drop(&mut self) {
+        backend::set_transport(None);
+    }
+}
+
+#[cfg(test)]
+static SIDECAR_SERIAL: Mutex<()> = Mutex::new(());
+
+/// Routes sidecar calls to the fixture `name` until the guard is dropped.
+#[cfg(test)]
+pub fn install_sidecar(name: &str) -> SidecarGuard {
+    let serial = SIDECAR_SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    let sidecar = Arc::new(MockSidecar::load(name));
+    backend::set_transport(Some(sidecar.clone()));
+    SidecarGuard {
+        sidecar,
+        _serial: serial,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AWS
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize, Clone, Debug)]
+struct AwsExchange {
+    /// API operation, e.g. `GetCostAndUsage`; for REST APIs such as S3,
+    /// `<METHOD> <path>`.
+    operation: String,
+    /// Only answer requests whose body contains this.
+    #[serde(default, rename = "match")]
+    body_contains: Option<String>,
+    #[serde(default = "ok_status")]
+    status: u16,
+    #[serde(default)]
+    body: serde_json::Value,
+}
+
+/// SDK HTTP client that replays recorded AWS responses.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayClient {
+    exchanges: Arc<Vec<AwsExchange>>,
+    /// `(operation, body)` of every request sent.
+    requests: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+/// JSON protocols name the operation in `X-Amz-Target`, query protocols
+/// (STS) in the `Action` form field.
+fn operation(request: &HttpRequest, body: &str) -> String {
+    if let Some(target) = request.headers().get("x-amz-target") {
+        return target.rsplit('.').next().unwrap_or(target).to_string();
+    }
+    if let Some(action) = body
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("Action="))
+    {
+        return action.to_string();
+    }
+    let path = request
+        .uri()
+        .split("://")
+        .nth(1)
+        .and_then(|rest| rest.find('/').map(|at| &rest[at..]))
+        .unwrap_or("/");
+    format!(
+        "{} {}",
+        request.method(),
+        path.split('?').next().unwrap_or(path)
+    )
+}
+
+impl ReplayClient {
+    pub fn load(name: &str) -> Self {
+        Self {
+            exchanges: Arc::new(exchanges(name)),
+            requests: Arc::default(),
+        }
+    }
+
+    /// Operations requested so far, in order.
+    #[cfg(test)]
+    pub fn operations(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(operation, _)| operation.clone())
+            .collect()
+    }
+
+    fn respond(&self, request: &HttpRequest) -> HttpResponse {
+        let body = request
+            .body()
+            .bytes()
+            .map(|b| String::from_utf8_lossy(b).into_owned())
+            .unwrap_or_default();
+        let operation = operation(request, &body);
+        let exchange = self.exchanges.iter().find(|ex| {
+            ex.operation == operation
+                && ex
+                    .body_contains
+                    .as_deref()
+                    .is_none_or(|needle| body.contains(needle))
+        });
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((operation.clone(), body));
+
+        let (status, text, content_type) = match exchange {
+            Some(ex) => {
+                let content_type = if ex.body.is_string() {
+                    "text/xml"
+                } else {
+                    "application/x-amz-json-1.1"
+                };
+                (ex.status, body_text(&ex.body), content_type)
+            }
+            None => (
+                404,
+                serde_json::json!({
+                    "__type": "ResourceNotFoundException",
+                    "message": format!("No fixture for {operation}"),
+                })
+                .to_string(),
+                "application/x-amz-json-1.1",
+            ),
+        };
+        let status = StatusCode::try_from(status).expect("fixture status is not an HTTP status");
+        let mut response = HttpResponse::new(status, SdkBody::from(text));
+        response.headers_mut().insert("content-type", content_type);
+        response
+    }
+}
+
+impl HttpConnector for ReplayClient {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        HttpConnectorFuture::ready(Ok(self.respond(&request)))
+    }
+}
+
+impl HttpClient for ReplayClient {
+    fn http_connector(
+        &self,
+        _settings: &HttpConnectorSettings,
+        _components: &RuntimeComponents,
+    ) -> SharedHttpConnector {
+        SharedHttpConnector::new(self.clone())
+    }
+}
+
+/// Test credentials; the replay client never checks signatures.
+#[cfg(test)]
+pub fn credentials() -> crate::AwsCredentials {
+    crate::AwsCredentials {
+        access_key_id: "AKIDEXAMPLE".into(),
+        secret_access_key: "secret".into(),
+        region: "us-east-1".into(),
+        session_token: None,
+        expires_at: None,
+        partition: None,
+        endpoint_url: None,
+    }
+}
+
+/// SDK config whose requests are answered by `client`.
+#[cfg(test)]
+pub async fn sdk_config(client: &ReplayClient) -> aws_config::SdkConfig {
+    crate::aws::sdk_config_from(credentials())
+        .await
+        .into_builder()
+        .http_client(client.clone())
+        .build()
+}
+
+/// Installs the fixture sidecar and AWS replay client, serving every file
+/// under `sidecar/` and `aws/`, when `AWS_COST_OPTIMIZER_FIXTURES` is set.
+#[cfg(feature = "mock")]
+pub fn install_from_env() {
+    if std::env::var_os(FIXTURES_ENV).is_none() {
+        return;
+    }
+    backend::set_transport(Some(Arc::new(MockSidecar::load("sidecar"))));
+    let client = ReplayClient::load("aws");
+    crate::aws::set_http_client(Some(
+        aws_smithy_runtime_api::client::http::SharedHttpClient::new(client),
+    ));
+    eprintln!(
+        "mock mode: serving fixtures from {}",
+        fixture_dir().display()
+    );
+}
@@ -0,0 +1,604 @@
+//! One analysis across several stored profiles. Each profile's account is
+//! looked up first, so profiles for the same account are scanned once; the
+//! scans then run a few at a time, each passing its profile's keys to the
+//! sidecar with the request. Progress arrives as `account-scan-progress`, and
+//! the combined report is keyed by account ID. A failing account does not
+//! stop the others.
+//!
+//! Each account's scan is an `account_scan` job in the persistent queue, so
+//! it shows in the job history and an interrupted one is resumed on the next
+//! launch. The job names where its keys come from, never the keys.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use secrecy::zeroize::Zeroizing;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::aws::sdk_config_from;
+use crate::error::{AppError, CommandResult};
+use crate::jobs::{self, JobKind};
+use crate::providers::{aws as aws_provider, ScanOutcome, ScanScope};
+use crate::scan_progress::{self, ScanProgress};
+use crate::tasks::{self, CancelToken};
+use crate::{
+    app_lock, perf, permissions, profiles, read_credentials, remote_backend, settings, sso, sts,
+    AwsCredentials,
+};
+
+const PROGRESS_EVENT: &str = "account-scan-progress";
+/// Scans running at once; the sidecar serves each on its own thread.
+const MAX_PARALLEL: usize = 4;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AccountScanProgress {
+    pub account_id: String,
+    pub profile: String,
+    pub progress: ScanProgress,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AccountScan {
+    /// Profile whose keys were used; for organization scans, the role.
+    pub profile: String,
+    /// Other selected profiles for the same account.
+    pub duplicate_profiles: Vec<String>,
+    pub outcome: Option<ScanOutcome>,
+    pub error: Option<String>,
+    /// Job the scan ran as.
+    pub job_id: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct MultiAccountReport {
+    /// By account ID.
+    pub accounts: BTreeMap<String, AccountScan>,
+    /// Profiles whose keys could not be read or identified, with the reason.
+    pub failed_profiles: BTreeMap<String, String>,
+    pub recommendation_count: usize,
+    pub estimated_monthly_savings: f64,
+}
+
+/// Where an account's keys come from, so a resumed scan can get them again.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccountKeys {
+    /// A stored profile.
+    Profile { name: String },
+    /// The current keys, for an organization's management account.
+    Current,
+    /// A member account's role, assumed with the current keys.
+    Role { role_arn: String },
+}
+
+/// A profile with its account ID and keys, or why they are unknown.
+pub(crate) type Identified = (
+    String,
+    Result<(String, AccountKeys, AwsCredentials), AppError>,
+);
+
+/// One account to scan, with the profile whose keys are used.
+struct Target {
+    account_id: String,
+    profile: String,
+    keys: AccountKeys,
+    creds: AwsCredentials,
+    /// The profile's region selection, used when the scope names none.
+    regions: Vec<String>,
+}
+
+impl Target {
+    /// `scope` with the profile's regions when it names none.
+    fn scope(&self, scope: &ScanScope) -> ScanScope {
+        let mut scope = scope.clone();
+        if scope.regions.is_empty() {
+            scope.regions = self.regions.clone();
+        }
+        scope
+    }
+}
+
+/// `creds` as the `credentials` of a scan request, which the sidecar scans
+/// with and never stores. It only borrows the keys.
+#[derive(Serialize)]
+struct ScanCredentials<'a> {
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+    session_token: Option<&'a str>,
+    region: &'a str,
+    endpoint_url: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct ScanRequest<'a> {
+    #[serde(flatten)]
+    body: &'a serde_json::Map<String, serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    credentials: Option<ScanCredentials<'a>>,
+}
+
+/// Counts the bytes written to it.
+struct Measure(usize);
+
+impl std::io::Write for Measure {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The JSON of scan request `body` with `creds` as its `credentials`, in a
+/// buffer sized up front so no copy of the keys is left behind by a
+/// reallocation, and wiped when dropped.
+pub(crate) fn request_body(
+    body: &serde_json::Value,
+    creds: Option<&AwsCredentials>,
+) -> Result<Zeroizing<String>, AppError> {
+    let mut body = body
+        .as_object()
+        .ok_or_else(|| AppError::InvalidInput("A scan request is a JSON object".into()))?
+        .clone();
+    body.remove("credentials");
+    let request = ScanRequest {
+        body: &body,
+        credentials: creds.map(|creds| ScanCredentials {
+            access_key_id: &creds.access_key_id,
+            secret_access_key: creds.secret_access_key(),
+            session_token: creds.session_token(),
+            region: &creds.region,
+            endpoint_url: creds.endpoint_url(),
+        }),
+    };
+    let internal = |e: serde_json::Error| AppError::Internal(e.to_string());
+    let mut size = Measure(0);
+    serde_json::to_writer(&mut size, &request).map_err(internal)?;
+    let mut json = Zeroizing::new(Vec::with_capacity(size.0));
+    serde_json::to_writer(&mut *json, &request).map_err(internal)?;
+    String::from_utf8(std::mem::take(&mut *json))
+        .map(Zeroizing::new)
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+/// Reads each profile's keys and account, concurrently. With a remote
+/// backend the keys are swapped for scoped ones before they are sent.
+async fn identify(names: Vec<String>) -> Vec<Identified> {
+    let lookups: Vec<_> = names
+        .into_iter()
+        .map(|name| {
+            tauri::async_runtime::spawn(async move {
+                let result = async {
+                    let mut creds = profiles::read_profile(&name)?;
+                    let identity =
+                        sts::caller_identity(&sdk_config_from(creds.clone()).await).await?;
+                    if remote_backend::active().is_some() {
+                        creds = remote_backend::scoped_credentials(creds).await?;
+                    }
+                    let keys = AccountKeys::Profile { name: name.clone() };
+                    Ok((identity.account_id, keys, creds))
+                }
+                .await;
+                (name, result)
+            })
+        })
+        .collect();
+    let mut identified = Vec::new();
+    for lookup in lookups {
+        match lookup.await {
+            Ok(result) => identified.push(result),
+            Err(err) => eprintln!("account lookup did not finish: {err}"),
+        }
+    }
+    identified
+}
+
+/// Keeps the first profile per account; the rest are noted as duplicates.
+fn targets(identified: Vec<Identified>, report: &mut MultiAccountReport) -> Vec<Target> {
+    let mut targets: Vec<Target> = Vec::new();
+    for (profile, result) in identified {
+        match result {
+            Ok((account_id, keys, creds)) => match report.accounts.get_mut(&account_id) {
+                Some(scan) => scan.duplicate_profiles.push(profile),
+                None => {
+                    report.accounts.insert(
+                        account_id.clone(),
+                        AccountScan {
+                            profile: profile.clone(),
+                            duplicate_profiles: Vec::new(),
+                            outcome: None,
+                            error: None,
+                            job_id: None,
+                        },
+                    );
+                    targets.push(Target {
+                        account_id,
+                        profile,
+                        keys,
+                        creds,
+                        regions: Vec::new(),
+                    });
+                }
+            },
+            Err(err) => {
+                report.failed_profiles.insert(profile, err.to_string());
+            }
+        }
+    }
+    targets
+}
+
+/// Keys for `keys` again, for a resumed account scan; with a remote backend
+/// they are limited as they were the first time.
+pub(crate) async fn credentials_for(
+    app: &AppHandle,
+    keys: &AccountKeys,
+) -> Result<AwsCredentials, AppError> {
+    let remote = remote_backend::active().is_some();
+    let current = || {
+        read_credentials(app)
+            .filter(|_| sso::access_granted(app))
+            .ok_or_else(|| AppError::Credentials("No AWS credentials are saved".into()))
+    };
+    let creds = match keys {
+        AccountKeys::Profile { name } => profiles::read_profile(name)?,
+        AccountKeys::Current => current()?,
+        AccountKeys::Role { role_arn } => {
+            let cfg = settings::load(app).organization;
+            let config = sdk_config_from(current()?).await;
+            let policy = remote.then(permissions::scan_policy);
+            let (creds, _) = sts::assume_with_policy(
+                &config,
+                role_arn,
+                cfg.external_id.as_deref(),
+                &cfg.session_name,
+                policy.as_deref(),
+            )
+            .await?;
+            return Ok(creds);
+        }
+    };
+    if remote {
+        remote_backend::scoped_credentials(creds).await
+    } else {
+        Ok(creds)
+    }
+}
+
+/// Scans one account with `creds` over `scope`.
+fn scan_account(
+    account_id: &str,
+    profile: &str,
+    creds: &AwsCredentials,
+    scope: &ScanScope,
+    cancel: &CancelToken,
+    emit: impl Fn(AccountScanProgress) + Clone + Send + 'static,
+) -> Result<ScanOutcome, AppError> {
+    let (account_id, profile) = (account_id.to_string(), profile.to_string());
+    let response = scan_progress::scan_with(
+        move |progress| {
+            emit(AccountScanProgress {
+                account_id: account_id.clone(),
+                profile: profile.clone(),
+                progress,
+            })
+        },
+        cancel,
+        aws_provider::scan_body(scope),
+        Some(creds),
+    )?;
+    Ok(aws_provider::outcome(response))
+}
+
+/// Runs a resumed `account_scan` job: gets its keys again and scans.
+pub(crate) fn resume_account_scan(
+    app: &AppHandle,
+    account_id: &str,
+    keys: &AccountKeys,
+    scope: &ScanScope,
+    cancel: &CancelToken,
+) -> Result<ScanOutcome, AppError> {
+    let creds = tauri::async_runtime::block_on(credentials_for(app, keys))?;
+    let profile = match keys {
+        AccountKeys::Profile { name } => name.clone(),
+        AccountKeys::Current => "current credentials".into(),
+        AccountKeys::Role { role_arn } => role_arn.clone(),
+    };
+    let app = app.clone();
+    scan_account(
+        account_id,
+        &profile,
+        &creds,
+        scope,
+        cancel,
+        move |progress| {
+            let _ = app.emit(PROGRESS_EVENT, progress);
+        },
+    )
+}
+
+/// Scans every target with `scan`, `MAX_PARALLEL` at a time, and fills in
+/// the report. `scan` returns the job the scan ran as, if any, and its
+/// outcome or error.
+fn scan_all(
+    targets: Vec<Target>,
+    scan: impl Fn(&Target) -> (Option<String>, Result<ScanOutcome, String>) + Sync,
+    report: &mut MultiAccountReport,
+) {
+    let queue = Mutex::new(targets.into_iter());
+    let results = Mutex::new(Vec::new());
+    std::thread::scope(|threads| {
+        for _ in 0..MAX_PARALLEL {
+            threads.spawn(|| loop {
+                let Some(target) = queue.lock().unwrap_or_else(|e| e.into_inner()).next() else {
+                    break;
+                };
+                let (job_id, result) = scan(&target);
+                results.lock().unwrap_or_else(|e| e.into_inner()).push((
+                    target.account_id,
+                    job_id,
+                    result,
+                ));
+            });
+        }
+    });
+    for (account_id, job_id, result) in results.into_inner().unwrap_or_else(|e| e.into_inner()) {
+        let Some(scan) = report.accounts.get_mut(&account_id) else {
+            continue;
+        };
+        scan.job_id = job_id;
+        match result {
+            Ok(outcome) => {
+                report.recommendation_count += outcome.recommendation_count;
+                report.estimated_monthly_savings += outcome.estimated_monthly_savings;
+                scan.outcome = Some(outcome);
+            }
+            Err(err) => scan.error = Some(err),
+        }
+    }
+}
+
+/// Scans the accounts of `identified` and combines the results; also used
+/// for organization scans, whose keys come from assumed roles.
+pub(crate) async fn scan_identified(
+    app: AppHandle,
+    identified: Vec<Identified>,
+    scope: ScanScope,
+    cancel: CancelToken,
+) -> Result<MultiAccountReport, AppError> {
+    let mut report = MultiAccountReport::default();
+    let mut targets = targets(identified, &mut report);
+    let selections = settings::load(&app).credential_profiles.regions;
+    for target in &mut targets {
+        target.regions = selections.get(&target.profile).cloned().unwrap_or_default();
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let emit = {
+            let app = app.clone();
+            move |progress| {
+                let _ = app.emit(PROGRESS_EVENT, progress);
+            }
+        };
+        let scan = |target: &Target| {
+            let scope = target.scope(&scope);
+            let kind = JobKind::AccountScan {
+                account_id: target.account_id.clone(),
+                keys: target.keys.clone(),
+                scope: scope.clone(),
+            };
+            let job = jobs::run_attached(&app, kind, &cancel, || {
+                scan_account(
+                    &target.account_id,
+                    &target.profile,
+                    &target.creds,
+                    &scope,
+                    &cancel,
+                    emit.clone(),
+                )
+            });
+            match job {
+                Ok(job) => {
+                    let outcome = match (job.outcome, job.last_error) {
+                        (Some(outcome), _) => Ok(outcome),
+                        (None, Some(err)) => Err(err),
+                        (None, None) => Err(AppError::Cancelled.to_string()),
+                    };
+                    (Some(job.id), outcome)
+                }
+                Err(err) => (None, Err(err.to_string())),
+            }
+        };
+        scan_all(targets, scan, &mut report);
+        cancel.check()?;
+        Ok(report)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Runs the same scan for each of `profiles` and combines the results.
+/// Cancelling `task_id` stops every scan still running.
+#[tauri::command]
+pub async fn scan_accounts(
+    app: AppHandle,
+    profiles: Vec<String>,
+    scope: ScanScope,
+    task_id: Option<String>,
+) -> CommandResult<MultiAccountReport> {
+    app_lock::ensure_unlocked(&app)?;
+    if profiles.is_empty() {
+        return Err(AppError::InvalidInput("Select at least one profile".into()));
+    }
+    let handle = app.clone();
+    perf::measure(&app, "scan_accounts", async move {
+        let (cancel, _task) = tasks::register(&handle, task_id)?;
+        scan_identified(handle, identify(profiles).await, scope, cancel).await
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    fn keys(name: &str) -> AccountKeys {
+        AccountKeys::Profile { name: name.into() }
+    }
+
+    #[test]
+    fn profiles_for_one_account_are_scanned_once() {
+        let identified = vec![
+            (
+                "work".into(),
+                Ok(("111111111111".into(), keys("work"), mock::credentials())),
+            ),
+            (
+                "audit".into(),
+                Ok(("222222222222".into(), keys("audit"), mock::credentials())),
+            ),
+            (
+                "work-admin".into(),
+                Ok((
+                    "111111111111".into(),
+                    keys("work-admin"),
+                    mock::credentials(),
+                )),
+            ),
+            (
+                "broken".into(),
+                Err(AppError::Credentials("InvalidClientTokenId".into())),
+            ),
+        ];
+        let mut report = MultiAccountReport::default();
+        let targets = targets(identified, &mut report);
+        let profiles: Vec<&str> = targets.iter().map(|t| t.profile.as_str()).collect();
+        assert_eq!(profiles, ["work", "audit"]);
+        assert_eq!(
+            report.accounts["111111111111"].duplicate_profiles,
+            ["work-admin"]
+        );
+        assert!(report.failed_profiles.contains_key("broken"));
+    }
+
+    #[test]
+    fn request_bodies_carry_the_keys_once() {
+        let creds = mock::credentials();
+        let body = serde_json::json!({ "include_buckets": ["logs"], "credentials": null });
+        let json = request_body(&body, Some(&creds)).unwrap();
+        assert_eq!(json.len(), json.capacity());
+        let sent: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(sent["credentials"]["secret_access_key"], "secret");
+        assert_eq!(sent["include_buckets"][0], "logs");
+
+        let without: serde_json::Value =
+            serde_json::from_str(&request_body(&body, None).unwrap()).unwrap();
+        assert!(without.get("credentials").is_none());
+    }
+
+    #[test]
+    fn each_account_scans_with_its_own_keys() {
+        let sidecar = mock::install_sidecar("sidecar/scan.json");
+        let target = |account: &str, key: &str| Target {
+            account_id: account.into(),
+            profile: format!("p-{account}"),
+            keys: keys(&format!("p-{account}")),
+            creds: AwsCredentials {
+                access_key_id: key.into(),
+                ..mock::credentials()
+            },
+            regions: Vec::new(),
+        };
+        let mut report = MultiAccountReport::default();
+        let identified = vec![
+            (
+                "p-1".into(),
+                Ok(("1".into(), keys("p-1"), mock::credentials())),
+            ),
+            (
+                "p-2".into(),
+                Ok(("2".into(), keys("p-2"), mock::credentials())),
+            ),
+        ];
+        targets(identified, &mut report);
+        let scope = ScanScope {
+            include: vec!["logs-archive".into()],
+            exclude: Vec::new(),
+            max_objects_per_resource: None,
+            regions: Vec::new(),
+        };
+        let cancel = CancelToken::default();
+        scan_all(
+            vec![target("1", "AKIAACCOUNTONE"), target("2", "AKIAACCOUNTTWO")],
+            |target| {
+                let scope = target.scope(&scope);
+                let outcome = scan_account(
+                    &target.account_id,
+                    &target.profile,
+                    &target.creds,
+                    &scope,
+                    &cancel,
+                    |_| {},
+                );
+                (None, outcome.map_err(|err| err.to_string()))
+            },
+            &mut report,
+        );
+
+        assert!(report.accounts.values().all(|scan| scan.outcome.is_some()));
+        let single = report.accounts["1"].outcome.as_ref().unwrap();
+        assert_eq!(report.recommendation_count, 2 * single.recommendation_count);
+        let keys: Vec<String> = sidecar
+            .sidecar
+            .requests()
+            .into_iter()
+            .filter(|r| r.path == "/optimizer/scan")
+            .filter_map(|r| {
+                let body: serde_json::Value = serde_json::from_str(&r.body?).ok()?;
+                Some(body["credentials"]["access_key_id"].as_str()?.to_string())
+            })
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&"AKIAACCOUNTONE".to_string()));
+        assert!(keys.contains(&"AKIAACCOUNTTWO".to_string()));
+    }
+
+    #[test]
+    fn targets_fall_back_to_their_profile_regions() {
+        let mut target = Target {
+            account_id: "1".into(),
+            profile: "work".into(),
+            keys: keys("work"),
+            creds: mock::credentials(),
+            regions: vec!["eu-west-1".into()],
+        };
+        let mut scope = ScanScope {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_objects_per_resource: None,
+            regions: Vec::new(),
+        };
+        assert_eq!(target.scope(&scope).regions, ["eu-west-1"]);
+        scope.regions = vec!["us-east-1".into()];
+        assert_eq!(target.scope(&scope).regions, ["us-east-1"]);
+        target.regions.clear();
+        assert_eq!(target.scope(&scope).regions, ["us-east-1"]);
+    }
+
+    #[test]
+    fn account_keys_never_hold_secrets() {
+        let keys = AccountKeys::Role {
+            role_arn: "arn:aws:iam::222222222222:role/Audit".into(),
+        };
+        let json = serde_json::to_value(&keys).unwrap();
+        assert_eq!(json["type"], "role");
+        assert_eq!(serde_json::from_value::<AccountKeys>(json).unwrap(), keys);
+    }
+}
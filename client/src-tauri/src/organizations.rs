@@ -0,0 +1,258 @@
+//! Organization-wide scans from a management account. Member accounts come
+//! from `organizations:ListAccounts`; the app assumes a configurable role in
+//! each (by default `OrganizationAccountAccessRole`, which Organizations
+//! creates in accounts it creates) and scans them with
+//! [`crate::multi_account`]. The management account itself is scanned with
+//! the current credentials. Accounts whose role cannot be assumed are
+//! reported and skipped.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::aws::{sdk_config_from, sdk_error, send};
+use crate::error::{AppError, CommandResult};
+use crate::multi_account::{self, AccountKeys, Identified, MultiAccountReport};
+use crate::providers::ScanScope;
+use crate::{
+    app_lock, perf, permissions, read_credentials, remote_backend, settings, sts, tasks,
+    AwsCredentials,
+};
+
+const DEFAULT_ROLE: &str = "OrganizationAccountAccessRole";
+const DEFAULT_SESSION_NAME: &str = "aws-cost-optimizer-org";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct OrganizationSettings {
+    /// Role assumed in each member account, optionally with its path.
+    pub role_name: String,
+    pub external_id: Option<String>,
+    pub session_name: String,
+}
+
+impl Default for OrganizationSettings {
+    fn default() -> Self {
+        Self {
+            role_name: DEFAULT_ROLE.into(),
+            external_id: None,
+            session_name: DEFAULT_SESSION_NAME.into(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct OrganizationAccount {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    /// `ACTIVE`, `SUSPENDED`, `PENDING_CLOSURE`, ...
+    pub state: String,
+}
+
+impl OrganizationAccount {
+    fn active(&self) -> bool {
+        self.state == "ACTIVE"
+    }
+}
+
+/// Role names and paths allow `[\w+=,.@-]` plus `/` between path segments.
+fn validate_role_name(role_name: &str) -> Result<(), AppError> {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || "_+=,.@-/".contains(c);
+    let valid = !role_name.is_empty()
+        && role_name.len() <= 512
+        && role_name.chars().all(allowed)
+        && !role_name.starts_with('/')
+        && !role_name.ends_with('/');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "Not an IAM role name: {role_name}"
+        )))
+    }
+}
+
+/// `arn:aws:iam::<account>:role/<role>`, in the caller's partition.
+fn role_arn(partition: &str, account_id: &str, role_name: &str) -> String {
+    format!("arn:{partition}:iam::{account_id}:role/{role_name}")
+}
+
+pub(crate) async fn list_accounts(
+    config: &aws_config::SdkConfig,
+) -> Result<Vec<OrganizationAccount>, AppError> {
+    let client = aws_sdk_organizations::Client::new(config);
+    let mut accounts = Vec::new();
+    let mut token: Option<String> = None;
+    loop {
+        let out = send(|| client.list_accounts().set_next_token(token.clone()).send())
+            .await
+            .map_err(sdk_error)?;
+        accounts.extend(out.accounts().iter().map(|account| {
+            OrganizationAccount {
+                id: account.id().unwrap_or_default().to_string(),
+                name: account.name().unwrap_or_default().to_string(),
+                email: account.email().unwrap_or_default().to_string(),
+                state: account
+                    .state()
+                    .map(|state| state.as_str().to_string())
+                    .or_else(|| account.status().map(|status| status.as_str().to_string()))
+                    .unwrap_or_default(),
+            }
+        }));
+        match out.next_token() {
+            Some(next) if !next.is_empty() => token = Some(next.to_string()),
+            _ => break,
+        }
+    }
+    Ok(accounts)
+}
+
+fn current_credentials(app: &AppHandle) -> Result<AwsCredentials, AppError> {
+    read_credentials(app).ok_or_else(|| AppError::Credentials("No AWS credentials saved".into()))
+}
+
+/// Keys for every selected account: the current ones for the management
+/// account, an assumed role for the others, obtained concurrently. With a
+/// remote backend both are limited to the analyses' read actions.
+async fn member_credentials(
+    source: AwsCredentials,
+    cfg: OrganizationSettings,
+    account_ids: Option<Vec<String>>,
+) -> Result<Vec<Identified>, AppError> {
+    let config = sdk_config_from(source.clone()).await;
+    let caller = sts::caller_identity(&config).await?;
+    let remote = remote_backend::active().is_some();
+    let partition = caller.arn.split(':').nth(1).unwrap_or("aws").to_string();
+    let accounts: Vec<OrganizationAccount> = list_accounts(&config)
+        .await?
+        .into_iter()
+        .filter(OrganizationAccount::active)
+        .filter(|a| account_ids.as_ref().is_none_or(|ids| ids.contains(&a.id)))
+        .collect();
+
+    let lookups: Vec<_> = accounts
+        .into_iter()
+        .map(|account| {
+            let (config, source, cfg) = (config.clone(), source.clone(), cfg.clone());
+            let arn = role_arn(&partition, &account.id, &cfg.role_name);
+            let management = account.id == caller.account_id;
+            tauri::async_runtime::spawn(async move {
+                if management {
+                    let creds = if remote {
+                        remote_backend::scoped_credentials(source).await
+                    } else {
+                        Ok(source)
+                    };
+                    let creds = creds.map(|creds| (account.id, AccountKeys::Current, creds));
+                    return ("current credentials".to_string(), creds);
+                }
+                let policy = remote.then(permissions::scan_policy);
+                let assumed = sts::assume_with_policy(
+                    &config,
+                    &arn,
+                    cfg.external_id.as_deref(),
+                    &cfg.session_name,
+                    policy.as_deref(),
+                )
+                .await
+                .map(|(creds, _)| {
+                    let keys = AccountKeys::Role {
+                        role_arn: arn.clone(),
+                    };
+                    (account.id, keys, creds)
+                });
+                (arn, assumed)
+            })
+        })
+        .collect();
+    let mut identified = Vec::new();
+    for lookup in lookups {
+        match lookup.await {
+            Ok(result) => identified.push(result),
+            Err(err) => eprintln!("role lookup did not finish: {err}"),
+        }
+    }
+    Ok(identified)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_organization_settings(app: AppHandle) -> OrganizationSettings {
+    settings::load(&app).organization
+}
+
+#[tauri::command]
+pub fn save_organization_settings(
+    app: AppHandle,
+    config: OrganizationSettings,
+) -> CommandResult<()> {
+    validate_role_name(&config.role_name)?;
+    let mut all = settings::load(&app);
+    all.organization = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
+
+/// Member accounts of the current credentials' organization.
+#[tauri::command]
+pub async fn list_organization_accounts(app: AppHandle) -> CommandResult<Vec<OrganizationAccount>> {
+    app_lock::ensure_unlocked(&app)?;
+    let config = sdk_config_from(current_credentials(&app)?).await;
+    list_accounts(&config).await
+}
+
+/// Scans every active member account (or only `account_ids`) through the
+/// configured role and combines the results by account.
+#[tauri::command]
+pub async fn scan_organization(
+    app: AppHandle,
+    scope: ScanScope,
+    account_ids: Option<Vec<String>>,
+    task_id: Option<String>,
+) -> CommandResult<MultiAccountReport> {
+    app_lock::ensure_unlocked(&app)?;
+    let source = current_credentials(&app)?;
+    let cfg = settings::load(&app).organization;
+    validate_role_name(&cfg.role_name)?;
+    let handle = app.clone();
+    perf::measure(&app, "scan_organization", async move {
+        let (cancel, _task) = tasks::register(&handle, task_id)?;
+        let identified = member_credentials(source, cfg, account_ids).await?;
+        cancel.check()?;
+        multi_account::scan_identified(handle, identified, scope, cancel).await
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, ReplayClient};
+
+    #[test]
+    fn roles_are_validated_and_built_per_partition() {
+        assert!(validate_role_name(DEFAULT_ROLE).is_ok());
+        assert!(validate_role_name("cost/Audit").is_ok());
+        assert!(validate_role_name("").is_err());
+        assert!(validate_role_name("has space").is_err());
+        assert!(validate_role_name("/leading").is_err());
+        assert_eq!(
+            role_arn("aws-us-gov", "111122223333", "cost/Audit"),
+            "arn:aws-us-gov:iam::111122223333:role/cost/Audit"
+        );
+    }
+
+    #[tokio::test]
+    async fn accounts_are_listed_across_pages() {
+        let aws = ReplayClient::load("aws/organizations.json");
+        let config = mock::sdk_config(&aws).await;
+        let accounts = list_accounts(&config).await.unwrap();
+        let ids: Vec<&str> = accounts.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["123456789012", "111122223333", "444455556666"]);
+        assert!(!accounts[2].active());
+        assert_eq!(aws.operations(), ["ListAccounts", "ListAccounts"]);
+    }
+}
@@ -0,0 +1,145 @@
+//! OS-level confirmation before stored secrets go to the webview. The UI only
+//! needs to know whether credentials exist; the secret access key is returned
+//! by `reveal_credentials`, which (unless turned off) first asks the OS to
+//! confirm the user: Touch ID or the account password on macOS, Windows Hello
+//! on Windows, and a polkit agent on Linux.
+
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::{app_lock, read_credentials, settings};
+
+/// Shown in the OS prompt; polkit uses its own wording.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const REASON: &str = "AWS Cost Optimizer wants to show your saved AWS secret access key.";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RevealSettings {
+    /// Ask the OS to confirm the user before revealing secrets.
+    pub require_os_auth: bool,
+}
+
+impl Default for RevealSettings {
+    fn default() -> Self {
+        Self {
+            require_os_auth: true,
+        }
+    }
+}
+
+/// The stored keys in full, for the settings form.
+#[derive(Serialize, Clone, Debug)]
+pub struct RevealedCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+#[cfg(target_os = "macos")]
+fn prompt() -> Command {
+    // The authorization dialog offers Touch ID where it is set up.
+    let script =
+        format!("do shell script \"true\" with prompt \"{REASON}\" with administrator privileges");
+    let mut command = Command::new("osascript");
+    command.args(["-e", &script]);
+    command
+}
+
+#[cfg(target_os = "windows")]
+fn prompt() -> Command {
+    let script = format!(
+        "$null = [Windows.Security.Credentials.UI.UserConsentVerifier,Windows.Security.Credentials.UI,ContentType=WindowsRuntime]
+Add-Type -AssemblyName System.Runtime.WindowsRuntime
+$asTask = [System.WindowsRuntimeSystemExtensions].GetMethods() | Where-Object {{ $_.Name -eq 'AsTask' -and $_.GetParameters().Count -eq 1 -and $_.GetParameters()[0].ParameterType.Name -eq 'IAsyncOperation`1' }} | Select-Object -First 1
+$op = [Windows.Security.Credentials.UI.UserConsentVerifier]::RequestVerificationAsync('{REASON}')
+$task = $asTask.MakeGenericMethod([Windows.Security.Credentials.UI.UserConsentVerificationResult]).Invoke($null, @($op))
+$task.Wait()
+if ($task.Result -ne 'Verified') {{ [Console]::Error.WriteLine($task.Result); exit 1 }}"
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn prompt() -> Command {
+    let mut command = Command::new("pkcheck");
+    command.args([
+        "--action-id",
+        "org.freedesktop.policykit.exec",
+        "--process",
+        &std::process::id().to_string(),
+        "--allow-user-interaction",
+    ]);
+    command
+}
+
+/// Blocks until the OS has confirmed the user, or fails.
+pub(crate) fn confirm_user() -> Result<(), AppError> {
+    let output = prompt()
+        .output()
+        .map_err(|e| AppError::Credentials(format!("OS authentication is unavailable: {e}")))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let detail = String::from_utf8_lossy(&output.stderr);
+    Err(AppError::Credentials(match detail.trim() {
+        "" => "OS authentication was cancelled or failed".into(),
+        detail => format!("OS authentication failed: {detail}"),
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// All the UI needs in normal operation.
+#[tauri::command]
+pub fn has_credentials(app: AppHandle) -> bool {
+    read_credentials(&app).is_some()
+}
+
+#[tauri::command]
+pub fn get_reveal_settings(app: AppHandle) -> RevealSettings {
+    settings::load(&app).reveal
+}
+
+/// Saves the setting; turning the confirmation off needs a confirmation too.
+#[tauri::command]
+pub async fn save_reveal_settings(app: AppHandle, config: RevealSettings) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut all = settings::load(&app);
+        if all.reveal.require_os_auth && !config.require_os_auth {
+            confirm_user()?;
+        }
+        all.reveal = config;
+        settings::save(&app, &all)?;
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn reveal_credentials(app: AppHandle) -> CommandResult<RevealedCredentials> {
+    app_lock::ensure_unlocked(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let creds = read_credentials(&app)
+            .ok_or_else(|| AppError::Credentials("No credentials saved".into()))?;
+        if settings::load(&app).reveal.require_os_auth {
+            confirm_user()?;
+        }
+        Ok(RevealedCredentials {
+            secret_access_key: creds.secret_access_key().to_string(),
+            session_token: creds.session_token().map(str::to_string),
+            access_key_id: creds.access_key_id,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,120 @@
+//! PagerDuty Events API v2 target for severe cost anomalies.
+//!
+//! The backend has no anomaly detection of its own, so a completed scan whose
+//! estimated monthly waste reaches the configured threshold is treated as the
+//! anomaly. The run ID is the dedup key, so re-deliveries never double-page.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::events::{AppEvent, EventKind};
+use crate::{ca_trust, keyring_entry_for, settings, webhooks};
+
+const EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+pub(crate) const ROUTING_KEY_ACCOUNT: &str = "pagerduty-routing-key";
+const MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PagerDutySettings {
+    pub enabled: bool,
+    /// Page when a scan finds at least this much estimated monthly waste.
+    pub min_monthly_savings: f64,
+    /// PagerDuty severity: `critical`, `error`, `warning`, or `info`.
+    pub severity: String,
+}
+
+impl Default for PagerDutySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_monthly_savings: 500.0,
+            severity: "error".into(),
+        }
+    }
+}
+
+fn trigger(routing_key: &str, cfg: &PagerDutySettings, event: &AppEvent, savings: f64) {
+    let run_id = event.data["run_id"].as_str().unwrap_or_default();
+    let body = json!({
+        "routing_key": routing_key,
+        "event_action": "trigger",
+        "dedup_key": format!("aws-cost-optimizer:{run_id}"),
+        "payload": {
+            "summary": format!("Scan found ${savings:.2}/month of estimated S3 waste"),
+            "source": "aws-cost-optimizer",
+            "severity": cfg.severity,
+            "timestamp": event.timestamp,
+            "custom_details": event.data,
+        },
+    });
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        match ca_trust::agent().post(EVENTS_URL).send_json(&body) {
+            Ok(_) => return,
+            // 400 means a malformed event; retrying will not help.
+            Err(ureq::Error::Status(400, _)) => break,
+            Err(err) if attempt == MAX_ATTEMPTS => {
+                eprintln!("PagerDuty alert for run {run_id} failed: {err}");
+            }
+            Err(_) => std::thread::sleep(webhooks::backoff(attempt)),
+        }
+    }
+}
+
+/// Pages on completed scans above the threshold; other events are ignored.
+pub fn dispatch(app: &AppHandle, event: &AppEvent) {
+    if event.event != EventKind::ScanCompleted {
+        return;
+    }
+    let cfg = settings::load(app).pagerduty;
+    let savings = event.data["estimated_monthly_savings"]
+        .as_f64()
+        .unwrap_or_default();
+    if !cfg.enabled || savings < cfg.min_monthly_savings {
+        return;
+    }
+    let Some(routing_key) = keyring_entry_for(ROUTING_KEY_ACCOUNT)
+        .ok()
+        .and_then(|entry| entry.get_password().ok())
+    else {
+        return;
+    };
+    let event = event.clone();
+    std::thread::spawn(move || trigger(&routing_key, &cfg, &event, savings));
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_pagerduty_settings(app: AppHandle) -> PagerDutySettings {
+    settings::load(&app).pagerduty
+}
+
+/// Saves the configuration. A non-empty `routing_key` replaces the stored one.
+#[tauri::command]
+pub fn save_pagerduty_settings(
+    app: AppHandle,
+    config: PagerDutySettings,
+    routing_key: Option<String>,
+) -> CommandResult<()> {
+    if !["critical", "error", "warning", "info"].contains(&config.severity.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "Unknown PagerDuty severity '{}'",
+            config.severity
+        )));
+    }
+    if let Some(key) = routing_key.filter(|k| !k.is_empty()) {
+        keyring_entry_for(ROUTING_KEY_ACCOUNT)?
+            .set_password(&key)
+            .map_err(|e| e.to_string())?;
+    }
+    let mut all = settings::load(&app);
+    all.pagerduty = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! AWS partitions: the standard `aws` one, GovCloud (`aws-us-gov`) and China
+//! (`aws-cn`). Credentials only work in their own partition, so everything
+//! that picks a region on its own (STS failover, Cost Explorer's home region,
+//! ARNs, endpoint hosts) asks the credentials' partition first. The SDK and
+//! boto3 resolve endpoints from the region, so a region in the right
+//! partition is all they need.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Partition {
+    #[default]
+    #[serde(rename = "aws")]
+    Aws,
+    #[serde(rename = "aws-us-gov")]
+    AwsUsGov,
+    #[serde(rename = "aws-cn")]
+    AwsCn,
+}
+
+impl Partition {
+    /// The partition a region belongs to, e.g. `cn-north-1` → `aws-cn`.
+    pub fn of_region(region: &str) -> Self {
+        if region.starts_with("cn-") {
+            Self::AwsCn
+        } else if region.starts_with("us-gov-") {
+            Self::AwsUsGov
+        } else {
+            Self::Aws
+        }
+    }
+
+    /// As it appears in ARNs.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Aws => "aws",
+            Self::AwsUsGov => "aws-us-gov",
+            Self::AwsCn => "aws-cn",
+        }
+    }
+
+    pub fn dns_suffix(self) -> &'static str {
+        match self {
+            Self::Aws | Self::AwsUsGov => "amazonaws.com",
+            Self::AwsCn => "amazonaws.com.cn",
+        }
+    }
+
+    /// Regions STS calls fail over to; account-level answers are the same
+    /// in every region of a partition.
+    pub fn sts_fallback_regions(self) -> &'static [&'static str] {
+        match self {
+            Self::Aws => &["us-east-1", "us-west-2"],
+            Self::AwsUsGov => &["us-gov-west-1", "us-gov-east-1"],
+            Self::AwsCn => &["cn-north-1", "cn-northwest-1"],
+        }
+    }
+
+    /// The one region serving Cost Explorer.
+    pub fn cost_explorer_region(self) -> &'static str {
+        match self {
+            Self::Aws => "us-east-1",
+            Self::AwsUsGov => "us-gov-west-1",
+            Self::AwsCn => "cn-northwest-1",
+        }
+    }
+}
+
+/// Checks that `region` looks like a commercial, GovCloud or China region
+/// and, when a partition was chosen, that it belongs to it.
+pub fn validate_region(region: &str, partition: Option<Partition>) -> Result<Partition, AppError> {
+    let mut parts = region.split('-').peekable();
+    let well_formed = parts
+        .next()
+        .is_some_and(|geo| geo.len() == 2 && geo.chars().all(|c| c.is_ascii_lowercase()))
+        && {
+            if parts.peek() == Some(&"gov") {
+                parts.next();
+            }
+            parts.next().is_some_and(|area| {
+                !area.is_empty() && area.chars().all(|c| c.is_ascii_lowercase())
+            })
+        }
+        && parts
+            .next()
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        && parts.next().is_none();
+    if !well_formed || region.contains("-iso") {
+        return Err(AppError::InvalidInput(format!(
+            "Not a supported AWS region: {region}"
+        )));
+    }
+    let of_region = Partition::of_region(region);
+    match partition {
+        Some(chosen) if chosen != of_region => Err(AppError::InvalidInput(format!(
+            "{region} is in the {} partition, not {}",
+            of_region.as_str(),
+            chosen.as_str()
+        ))),
+        _ => Ok(of_region),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions_map_to_their_partition() {
+        assert_eq!(Partition::of_region("eu-west-1"), Partition::Aws);
+        assert_eq!(Partition::of_region("us-gov-west-1"), Partition::AwsUsGov);
+        assert_eq!(Partition::of_region("cn-northwest-1"), Partition::AwsCn);
+        assert_eq!(Partition::AwsCn.dns_suffix(), "amazonaws.com.cn");
+        assert_eq!(
+            serde_json::to_string(&Partition::AwsUsGov).unwrap(),
+            "\"aws-us-gov\""
+        );
+    }
+
+    #[test]
+    fn regions_must_match_the_chosen_partition() {
+        assert_eq!(
+            validate_region("us-gov-east-1", Some(Partition::AwsUsGov)).unwrap(),
+            Partition::AwsUsGov
+        );
+        assert_eq!(
+            validate_region("ap-southeast-2", None).unwrap(),
+            Partition::Aws
+        );
+        assert!(validate_region("cn-north-1", Some(Partition::Aws)).is_err());
+        assert!(validate_region("us-east-1", Some(Partition::AwsCn)).is_err());
+        assert!(validate_region("Frankfurt", None).is_err());
+        assert!(validate_region("us-isob-east-1", None).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! Performance counters for the commands that reach AWS or the sidecar.
+//! Each instrumented command records its latency, the AWS requests it made
+//! (retries included) and its cache hits and misses. Counters follow the
+//! command into spawned tasks and blocking sections through [`propagate`] and
+//! [`blocking`]. Totals persist in `app_data/performance_stats.json`.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::CommandResult;
+
+/// Latency samples kept per command for the percentiles.
+const SAMPLES: usize = 200;
+
+static STATS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Default)]
+struct Counters {
+    aws_calls: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+}
+
+tokio::task_local! {
+    static CURRENT: Arc<Counters>;
+}
+
+thread_local! {
+    static BLOCKING: RefCell<Option<Arc<Counters>>> = const { RefCell::new(None) };
+}
+
+fn current() -> Option<Arc<Counters>> {
+    CURRENT
+        .try_with(Arc::clone)
+        .ok()
+        .or_else(|| BLOCKING.with(|b| b.borrow().clone()))
+}
+
+/// Counts one AWS request against the running command.
+pub fn count_aws_call() {
+    if let Some(counters) = current() {
+        counters.aws_calls.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub fn count_cache(hit: bool) {
+    if let Some(counters) = current() {
+        let counter = if hit {
+            &counters.cache_hits
+        } else {
+            &counters.cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Carries the running command's counters into a spawned task.
+pub fn propagate<F: Future>(work: F) -> impl Future<Output = F::Output> {
+    let counters = current();
+    async move {
+        match counters {
+            Some(counters) => CURRENT.scope(counters, work).await,
+            None => work.await,
+        }
+    }
+}
+
+/// Carries the running command's counters into a `spawn_blocking` closure.
+pub fn blocking<T>(work: impl FnOnce() -> T + Send) -> impl FnOnce() -> T + Send {
+    let counters = current();
+    move || {
+        BLOCKING.with(|b| *b.borrow_mut() = counters);
+        let result = work();
+        BLOCKING.with(|b| *b.borrow_mut() = None);
+        result
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Store
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+struct CommandTotals {
+    calls: u64,
+    errors: u64,
+    total_ms: u64,
+    max_ms: u64,
+    aws_calls: u64,
+    cache_hits: u64,
+    cache_misses: u64,
+    last_called_at: Option<String>,
+    recent_ms: VecDeque<u64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CommandStats {
+    pub command: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub avg_ms: f64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+    pub aws_calls: u64,
+    pub aws_calls_per_call: f64,
+    /// `None` until the command has touched a cache.
+    pub cache_hit_rate: Option<f64>,
+    pub last_called_at: Option<String>,
+}
+
+fn stats_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("performance_stats.json")
+}
+
+fn read_totals(app: &AppHandle) -> BTreeMap<String, CommandTotals> {
+    std::fs::read_to_string(stats_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_totals(app: &AppHandle, totals: &BTreeMap<String, CommandTotals>) {
+    let path = stats_path(app);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string(totals) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+fn record(app: &AppHandle, command: &str, elapsed_ms: u64, failed: bool, counters: &Counters) {
+    let _guard = STATS_LOCK.lock();
+    let mut totals = read_totals(app);
+    let entry = totals.entry(command.to_string()).or_default();
+    entry.calls += 1;
+    entry.errors += u64::from(failed);
+    entry.total_ms += elapsed_ms;
+    entry.max_ms = entry.max_ms.max(elapsed_ms);
+    entry.aws_calls += counters.aws_calls.load(Ordering::Relaxed);
+    entry.cache_hits += counters.cache_hits.load(Ordering::Relaxed);
+    entry.cache_misses += counters.cache_misses.load(Ordering::Relaxed);
+    entry.last_called_at = Some(chrono::Utc::now().to_rfc3339());
+    entry.recent_ms.push_back(elapsed_ms);
+    while entry.recent_ms.len() > SAMPLES {
+        entry.recent_ms.pop_front();
+    }
+    write_totals(app, &totals);
+}
+
+/// Runs a command body and records its stats under `command`.
+pub async fn measure<T>(
+    app: &AppHandle,
+    command: &'static str,
+    work: impl Future<Output = CommandResult<T>>,
+) -> CommandResult<T> {
+    let counters = Arc::new(Counters::default());
+    let started = Instant::now();
+    let result = CURRENT.scope(counters.clone(), work).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    record(app, command, elapsed_ms, result.is_err(), &counters);
+    result
+}
+
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((sorted.len() as f64 - 1.0) * p).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+fn summarize(command: String, totals: CommandTotals) -> CommandStats {
+    let mut sorted: Vec<u64> = totals.recent_ms.iter().copied().collect();
+    sorted.sort_unstable();
+    let calls = totals.calls.max(1) as f64;
+    let lookups = totals.cache_hits + totals.cache_misses;
+    CommandStats {
+        command,
+        calls: totals.calls,
+        errors: totals.errors,
+        avg_ms: totals.total_ms as f64 / calls,
+        p50_ms: percentile(&sorted, 0.5),
+        p95_ms: percentile(&sorted, 0.95),
+        max_ms: totals.max_ms,
+        aws_calls: totals.aws_calls,
+        aws_calls_per_call: totals.aws_calls as f64 / calls,
+        cache_hit_rate: (lookups > 0).then(|| totals.cache_hits as f64 / lookups as f64),
+        last_called_at: totals.last_called_at,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Stats per instrumented command, most total time first.
+#[tauri::command]
+pub fn get_performance_stats(app: AppHandle) -> Vec<CommandStats> {
+    let mut stats: Vec<(u64, CommandStats)> = read_totals(&app)
+        .into_iter()
+        .map(|(command, totals)| (totals.total_ms, summarize(command, totals)))
+        .collect();
+    stats.sort_by_key(|(total_ms, _)| std::cmp::Reverse(*total_ms));
+    stats.into_iter().map(|(_, s)| s).collect()
+}
+
+#[tauri::command]
+pub fn reset_performance_stats(app: AppHandle) -> CommandResult<()> {
+    let _guard = STATS_LOCK.lock();
+    match std::fs::remove_file(stats_path(&app)) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
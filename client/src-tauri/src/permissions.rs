@@ -0,0 +1,292 @@
+//! Permission preflight before analyses. The read actions the app needs are
+//! simulated with `iam:SimulatePrincipalPolicy` against the caller's user or
+//! role. Where that is not possible (root keys, federated users, or no
+//! permission to simulate), a few harmless read calls are probed instead;
+//! Cost Explorer is left unchecked then, since every request costs money.
+
+use aws_config::SdkConfig;
+use aws_sdk_iam::types::PolicyEvaluationDecisionType;
+use aws_smithy_types::error::metadata::ProvideErrorMetadata;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::app_lock;
+use crate::aws::{sdk_config, sdk_error, send};
+use crate::error::{AppError, CommandResult};
+use crate::sts::caller_identity;
+
+/// IAM actions the analyses call, with what each is used for.
+const REQUIRED: &[(&str, &str)] = &[
+    ("s3:ListAllMyBuckets", "List buckets to analyse"),
+    ("s3:GetBucketLocation", "Find each bucket's region"),
+    ("s3:ListBucket", "Sample objects and storage classes"),
+    (
+        "s3:GetLifecycleConfiguration",
+        "Read existing lifecycle rules",
+    ),
+    (
+        "s3:ListBucketMultipartUploads",
+        "Find incomplete multipart uploads",
+    ),
+    ("ce:GetCostAndUsage", "Read S3 spend from Cost Explorer"),
+];
+
+/// Session policy allowing only the analyses' read actions, for keys that
+/// leave the machine.
+pub(crate) fn scan_policy() -> String {
+    let actions: Vec<&str> = REQUIRED.iter().map(|(action, _)| *action).collect();
+    serde_json::json!({
+        "Version": "2012-10-17",
+        "Statement": [{ "Effect": "Allow", "Action": actions, "Resource": "*" }],
+    })
+    .to_string()
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionStatus {
+    Allowed,
+    Denied,
+    /// Could not be checked.
+    Unknown,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PermissionCheck {
+    pub action: &'static str,
+    pub purpose: &'static str,
+    pub status: PermissionStatus,
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckMethod {
+    Simulation,
+    Probe,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PermissionReport {
+    pub method: CheckMethod,
+    /// User or role the policies were simulated for.
+    pub principal_arn: Option<String>,
+    pub checks: Vec<PermissionCheck>,
+}
+
+impl PermissionReport {
+    fn new(method: CheckMethod, principal_arn: Option<String>) -> Self {
+        let checks = REQUIRED
+            .iter()
+            .map(|&(action, purpose)| PermissionCheck {
+                action,
+                purpose,
+                status: PermissionStatus::Unknown,
+                detail: None,
+            })
+            .collect();
+        Self {
+            method,
+            principal_arn,
+            checks,
+        }
+    }
+
+    fn set(&mut self, action: &str, status: PermissionStatus, detail: Option<String>) {
+        if let Some(check) = self.checks.iter_mut().find(|c| c.action == action) {
+            check.status = status;
+            check.detail = detail;
+        }
+    }
+}
+
+/// The IAM principal policies are attached to: users as they are, assumed
+/// role sessions as their role. Role paths are not part of the session ARN,
+/// so roles with a path fail simulation and fall back to probing.
+fn simulation_principal(arn: &str) -> Option<String> {
+    let mut parts = arn.splitn(6, ':');
+    let (partition, service, account, resource) =
+        (parts.nth(1)?, parts.next()?, parts.nth(1)?, parts.next()?);
+    match (service, resource.split_once('/')?) {
+        ("iam", ("user", _)) => Some(arn.to_string()),
+        ("sts", ("assumed-role", rest)) => {
+            let role = rest.split('/').next()?;
+            Some(format!("arn:{partition}:iam::{account}:role/{role}"))
+        }
+        _ => None,
+    }
+}
+
+async fn simulate(config: &SdkConfig, principal: &str) -> Result<PermissionReport, AppError> {
+    let client = aws_sdk_iam::Client::new(config);
+    let out = send(|| {
+        client
+            .simulate_principal_policy()
+            .policy_source_arn(principal)
+            .set_action_names(Some(REQUIRED.iter().map(|(a, _)| a.to_string()).collect()))
+            .send()
+    })
+    .await
+    .map_err(sdk_error)?;
+    let mut report = PermissionReport::new(CheckMethod::Simulation, Some(principal.to_string()));
+    for result in out.evaluation_results() {
+        let (status, detail) = match result.eval_decision() {
+            PolicyEvaluationDecisionType::Allowed => (PermissionStatus::Allowed, None),
+            PolicyEvaluationDecisionType::ExplicitDeny => (
+                PermissionStatus::Denied,
+                Some("Explicitly denied by a policy".into()),
+            ),
+            _ => (PermissionStatus::Denied, Some("No policy allows it".into())),
+        };
+        report.set(result.eval_action_name(), status, detail);
+    }
+    Ok(report)
+}
+
+/// Outcome of one probe call: denied only for access-denied errors.
+fn probed<T>(result: Result<T, AppError>) -> (PermissionStatus, Option<String>) {
+    match result {
+        Ok(_) => (PermissionStatus::Allowed, None),
+        Err(AppError::AccessDenied(message)) => (PermissionStatus::Denied, Some(message)),
+        Err(err) => (PermissionStatus::Unknown, Some(err.to_string())),
+    }
+}
+
+async fn probe(config: &SdkConfig) -> PermissionReport {
+    let mut report = PermissionReport::new(CheckMethod::Probe, None);
+    let s3 = aws_sdk_s3::Client::new(config);
+    let buckets = send(|| s3.list_buckets().max_buckets(1).send())
+        .await
+        .map_err(sdk_error);
+    let bucket = buckets
+        .as_ref()
+        .ok()
+        .and_then(|out| out.buckets().first())
+        .and_then(|b| b.name())
+        .map(str::to_string);
+    let (status, detail) = probed(buckets);
+    report.set("s3:ListAllMyBuckets", status, detail);
+
+    let Some(bucket) = bucket else {
+        return report;
+    };
+    let (status, detail) = probed(
+        send(|| s3.get_bucket_location().bucket(&bucket).send())
+            .await
+            .map_err(sdk_error),
+    );
+    report.set("s3:GetBucketLocation", status, detail);
+    let (status, detail) = probed(
+        send(|| s3.list_objects_v2().bucket(&bucket).max_keys(1).send())
+            .await
+            .map_err(sdk_error),
+    );
+    report.set("s3:ListBucket", status, detail);
+    let lifecycle = send(|| {
+        s3.get_bucket_lifecycle_configuration()
+            .bucket(&bucket)
+            .send()
+    })
+    .await;
+    // A bucket without rules answers, so the read was allowed.
+    let lifecycle = match lifecycle {
+        Err(err)
+            if err.as_service_error().and_then(ProvideErrorMetadata::code)
+                == Some("NoSuchLifecycleConfiguration") =>
+        {
+            Ok(())
+        }
+        other => other.map(|_| ()).map_err(sdk_error),
+    };
+    let (status, detail) = probed(lifecycle);
+    report.set("s3:GetLifecycleConfiguration", status, detail);
+    let (status, detail) = probed(
+        send(|| {
+            s3.list_multipart_uploads()
+                .bucket(&bucket)
+                .max_uploads(1)
+                .send()
+        })
+        .await
+        .map_err(sdk_error),
+    );
+    report.set("s3:ListBucketMultipartUploads", status, detail);
+    report
+}
+
+pub(crate) async fn check(config: &SdkConfig) -> Result<PermissionReport, AppError> {
+    let identity = caller_identity(config).await?;
+    if let Some(principal) = simulation_principal(&identity.arn) {
+        match simulate(config, &principal).await {
+            Ok(report) => return Ok(report),
+            Err(err) => eprintln!("policy simulation failed, probing instead: {err}"),
+        }
+    }
+    Ok(probe(config).await)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Checks the current keys for every permission the analyses need.
+#[tauri::command]
+pub async fn check_permissions(app: AppHandle) -> CommandResult<PermissionReport> {
+    app_lock::ensure_unlocked(&app)?;
+    check(&sdk_config(&app).await?).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, ReplayClient};
+
+    #[test]
+    fn scan_policy_allows_only_the_required_actions() {
+        let policy: serde_json::Value = serde_json::from_str(&scan_policy()).unwrap();
+        let statement = &policy["Statement"][0];
+        assert_eq!(statement["Effect"], "Allow");
+        let actions = statement["Action"].as_array().unwrap();
+        assert_eq!(actions.len(), REQUIRED.len());
+        assert!(actions
+            .iter()
+            .all(|action| !action.as_str().unwrap().contains('*')));
+    }
+
+    #[test]
+    fn sessions_simulate_as_their_role() {
+        assert_eq!(
+            simulation_principal("arn:aws:iam::123456789012:user/ci").as_deref(),
+            Some("arn:aws:iam::123456789012:user/ci")
+        );
+        assert_eq!(
+            simulation_principal("arn:aws:sts::123456789012:assumed-role/Audit/me").as_deref(),
+            Some("arn:aws:iam::123456789012:role/Audit")
+        );
+        assert_eq!(simulation_principal("arn:aws:iam::123456789012:root"), None);
+        assert_eq!(
+            simulation_principal("arn:aws:sts::123456789012:federated-user/bob"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn simulation_reports_each_action() {
+        let aws = ReplayClient::load("aws/iam.json");
+        let config = mock::sdk_config(&aws).await;
+        let report = check(&config).await.unwrap();
+        assert_eq!(report.method, CheckMethod::Simulation);
+        let status = |action: &str| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.action == action)
+                .map(|c| c.status)
+        };
+        assert_eq!(
+            status("s3:ListAllMyBuckets"),
+            Some(PermissionStatus::Allowed)
+        );
+        assert_eq!(status("ce:GetCostAndUsage"), Some(PermissionStatus::Denied));
+    }
+}
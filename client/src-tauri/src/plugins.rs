@@ -0,0 +1,501 @@
+//! Custom scanner plugins: external executables with a JSON contract that
+//! the shell discovers, runs after every completed scan, and whose findings
+//! are listed alongside the built-in ones.
+//!
+//! A plugin is a directory under `app_data/plugins/` with a `plugin.json`
+//! manifest. The shell writes a [`PluginInput`] to the executable's stdin and
+//! reads a [`PluginOutput`] from its stdout. Plugins run with an empty
+//! environment (only `PATH`), inside their own directory, under a timeout
+//! and an output cap; AWS credentials are passed only to plugins the user
+//! granted them to. This limits, but is not, an OS-level sandbox.
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::events::{AppEvent, EventKind};
+use crate::providers::{self, ProviderRecommendation};
+use crate::{app_lock, read_credentials, settings, sso};
+
+const API_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "plugin.json";
+const MAX_OUTPUT_BYTES: u64 = 4 * 1024 * 1024;
+const MAX_STDERR_BYTES: u64 = 16 * 1024;
+
+/// Serializes writers of the findings store across plugin threads.
+static FINDINGS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct PluginSettings {
+    /// IDs of plugins allowed to run.
+    pub enabled: Vec<String>,
+    /// IDs of plugins that receive the app's AWS credentials.
+    pub credential_grants: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    /// Executable path relative to the plugin directory.
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+    /// Whether the plugin asks for AWS credentials; the user still has to grant them.
+    #[serde(default)]
+    pub wants_aws_credentials: bool,
+}
+
+fn default_timeout() -> u64 {
+    120
+}
+
+/// Written to the plugin's stdin.
+#[derive(Serialize)]
+struct PluginInput<'a> {
+    api_version: u32,
+    /// The completed scan that triggered the run, if any.
+    run_id: Option<&'a str>,
+    region: Option<String>,
+}
+
+/// Expected on the plugin's stdout.
+#[derive(Deserialize)]
+struct PluginOutput {
+    #[serde(default)]
+    findings: Vec<PluginFinding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PluginFinding {
+    pub id: String,
+    pub resource: String,
+    pub recommendation_type: String,
+    pub risk_level: String,
+    pub reason: String,
+    pub recommended_action: String,
+    #[serde(default)]
+    pub estimated_monthly_savings: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PluginRun {
+    pub ran_at: String,
+    pub error: Option<String>,
+    pub findings: Vec<PluginFinding>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PluginInfo {
+    pub manifest: PluginManifest,
+    pub enabled: bool,
+    pub credentials_granted: bool,
+    pub last_run: Option<PluginRun>,
+}
+
+// ---------------------------------------------------------------------------
+// Discovery and storage
+// ---------------------------------------------------------------------------
+
+fn plugins_dir(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("plugins")
+}
+
+fn discover(app: &AppHandle) -> Vec<(PathBuf, PluginManifest)> {
+    discover_in(&plugins_dir(app))
+}
+
+/// Every directory with a readable manifest; broken plugins are skipped.
+fn discover_in(root: &Path) -> Vec<(PathBuf, PluginManifest)> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut plugins: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|dir| {
+            let raw = std::fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+            match serde_json::from_str::<PluginManifest>(&raw) {
+                Ok(manifest) => Some((dir, manifest)),
+                Err(err) => {
+                    eprintln!("plugin manifest in {} is invalid: {err}", dir.display());
+                    None
+                }
+            }
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.1.id.cmp(&b.1.id));
+    plugins
+}
+
+fn findings_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("plugin_findings.json")
+}
+
+fn read_runs(app: &AppHandle) -> BTreeMap<String, PluginRun> {
+    std::fs::read_to_string(findings_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn record_run(app: &AppHandle, plugin_id: &str, run: PluginRun) {
+    let _guard = FINDINGS_LOCK.lock();
+    let mut runs = read_runs(app);
+    runs.insert(plugin_id.to_string(), run);
+    providers::invalidate_findings();
+    let path = findings_path(app);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&runs) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Execution
+// ---------------------------------------------------------------------------
+
+/// Resolves the executable and refuses paths that escape the plugin dir.
+fn executable(dir: &Path, manifest: &PluginManifest) -> Result<PathBuf, String> {
+    let dir = dir.canonicalize().map_err(|e| e.to_string())?;
+    let path = dir
+        .join(&manifest.command)
+        .canonicalize()
+        .map_err(|e| format!("Plugin executable not found: {e}"))?;
+    if !path.starts_with(&dir) {
+        return Err("Plugin executable must live inside the plugin directory".into());
+    }
+    Ok(path)
+}
+
+pub(crate) fn read_capped(
+    mut source: impl Read + Send + 'static,
+    cap: u64,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = (&mut source).take(cap + 1).read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// Whether a plugin gets the app's AWS credentials: only while it is both
+/// enabled and granted them.
+fn receives_credentials(cfg: &PluginSettings, id: &str) -> bool {
+    cfg.enabled.iter().any(|p| p == id) && cfg.credential_grants.iter().any(|p| p == id)
+}
+
+/// Runs a plugin process to completion: writes `input` to its stdin and
+/// returns its stdout, killing it once `timeout` passes.
+fn run_process(mut command: Command, input: &[u8], timeout: Duration) -> Result<Vec<u8>, String> {
+    let mut child = command.spawn().map_err(|e| e.to_string())?;
+    let stdout = read_capped(child.stdout.take().ok_or("no stdout")?, MAX_OUTPUT_BYTES);
+    let stderr = read_capped(child.stderr.take().ok_or("no stderr")?, MAX_STDERR_BYTES);
+    if let Some(mut stdin) = child.stdin.take() {
+        // A plugin that ignores its input may close stdin early; that is fine.
+        let _ = stdin.write_all(input);
+    }
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("Timed out after {}s", timeout.as_secs()));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    if !status.success() {
+        let detail = String::from_utf8_lossy(&stderr);
+        return Err(format!("Exited with {status}: {}", detail.trim()));
+    }
+    if stdout.len() as u64 > MAX_OUTPUT_BYTES {
+        return Err("Output exceeds the 4 MiB limit".into());
+    }
+    Ok(stdout)
+}
+
+fn execute(
+    app: &AppHandle,
+    dir: &Path,
+    manifest: &PluginManifest,
+    run_id: Option<&str>,
+) -> Result<Vec<PluginFinding>, String> {
+    let cfg = settings::load(app).plugins;
+    let creds = read_credentials(app).filter(|_| sso::access_granted(app));
+    let input = serde_json::to_vec(&PluginInput {
+        api_version: API_VERSION,
+        run_id,
+        region: creds.as_ref().map(|c| c.region.clone()),
+    })
+    .map_err(|e| e.to_string())?;
+
+    let mut command = Command::new(executable(dir, manifest)?);
+    command
+        .args(&manifest.args)
+        .current_dir(dir)
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(path) = std::env::var_os("PATH") {
+        command.env("PATH", path);
+    }
+    if let Some(creds) = creds.filter(|_| receives_credentials(&cfg, &manifest.id)) {
+        command
+            .env("AWS_ACCESS_KEY_ID", &creds.access_key_id)
+            .env("AWS_SECRET_ACCESS_KEY", creds.secret_access_key())
+            .env("AWS_DEFAULT_REGION", &creds.region);
+        if let Some(token) = creds.session_token() {
+            command.env("AWS_SESSION_TOKEN", token);
+        }
+        if let Some(url) = creds.endpoint_url() {
+            command.env("AWS_ENDPOINT_URL", url);
+        }
+    }
+
+    let stdout = run_process(command, &input, Duration::from_secs(manifest.timeout_secs))?;
+    let output: PluginOutput =
+        serde_json::from_slice(&stdout).map_err(|e| format!("Invalid plugin output: {e}"))?;
+    Ok(output.findings)
+}
+
+fn run_plugin_blocking(
+    app: &AppHandle,
+    dir: &Path,
+    manifest: &PluginManifest,
+    run_id: Option<&str>,
+) -> PluginRun {
+    let (findings, error) = match execute(app, dir, manifest, run_id) {
+        Ok(findings) => (findings, None),
+        Err(err) => (Vec::new(), Some(err)),
+    };
+    let run = PluginRun {
+        ran_at: chrono::Utc::now().to_rfc3339(),
+        error,
+        findings,
+    };
+    record_run(app, &manifest.id, run.clone());
+    run
+}
+
+/// Runs every enabled plugin after a completed scan, each on its own thread.
+pub fn dispatch(app: &AppHandle, event: &AppEvent) {
+    if event.event != EventKind::ScanCompleted {
+        return;
+    }
+    let enabled = settings::load(app).plugins.enabled;
+    let run_id = event.data["run_id"].as_str().map(str::to_string);
+    for (dir, manifest) in discover(app)
+        .into_iter()
+        .filter(|(_, m)| enabled.contains(&m.id))
+    {
+        let app = app.clone();
+        let run_id = run_id.clone();
+        std::thread::spawn(move || {
+            run_plugin_blocking(&app, &dir, &manifest, run_id.as_deref());
+        });
+    }
+}
+
+/// Latest findings of enabled plugins in the provider-neutral shape.
+pub fn recommendations(app: &AppHandle) -> Vec<ProviderRecommendation> {
+    let enabled = settings::load(app).plugins.enabled;
+    read_runs(app)
+        .into_iter()
+        .filter(|(id, _)| enabled.contains(id))
+        .flat_map(|(id, run)| {
+            run.findings
+                .into_iter()
+                .map(move |finding| ProviderRecommendation {
+                    provider: format!("plugin:{id}"),
+                    id: finding.id,
+                    resource: finding.resource,
+                    recommendation_type: finding.recommendation_type,
+                    risk_level: finding.risk_level,
+                    recommended_action: finding.recommended_action,
+                    estimated_monthly_savings: finding.estimated_monthly_savings,
+                })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn list_plugins(app: AppHandle) -> Vec<PluginInfo> {
+    let cfg = settings::load(&app).plugins;
+    let mut runs = read_runs(&app);
+    discover(&app)
+        .into_iter()
+        .map(|(_, manifest)| PluginInfo {
+            enabled: cfg.enabled.contains(&manifest.id),
+            credentials_granted: cfg.credential_grants.contains(&manifest.id),
+            last_run: runs.remove(&manifest.id),
+            manifest,
+        })
+        .collect()
+}
+
+/// Enables or disables a plugin and sets whether it receives AWS credentials.
+/// Only an enabled plugin can be granted them.
+#[tauri::command]
+pub fn configure_plugin(
+    app: AppHandle,
+    id: String,
+    enabled: bool,
+    grant_credentials: bool,
+) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    if grant_credentials && !enabled {
+        return Err(AppError::InvalidInput(
+            "Enable the plugin before granting it AWS credentials".into(),
+        ));
+    }
+    let mut all = settings::load(&app);
+    let cfg = &mut all.plugins;
+    cfg.enabled.retain(|p| *p != id);
+    cfg.credential_grants.retain(|p| *p != id);
+    if enabled {
+        cfg.enabled.push(id.clone());
+    }
+    if grant_credentials {
+        cfg.credential_grants.push(id);
+    }
+    settings::save(&app, &all)?;
+    providers::invalidate_findings();
+    Ok(())
+}
+
+/// Runs one enabled plugin now and returns its result.
+#[tauri::command]
+pub async fn run_plugin(app: AppHandle, id: String) -> CommandResult<PluginRun> {
+    app_lock::ensure_unlocked(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let (dir, manifest) = discover(&app)
+            .into_iter()
+            .find(|(_, m)| m.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Plugin '{id}' not found")))?;
+        if !settings::load(&app).plugins.enabled.contains(&id) {
+            return Err(AppError::InvalidInput(format!(
+                "Plugin '{id}' is disabled; enable it to run it"
+            )));
+        }
+        Ok(run_plugin_blocking(&app, &dir, &manifest, None))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("plugins-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn discovers_plugins_with_valid_manifests() {
+        let root = temp_dir();
+        for (id, manifest) in [
+            (
+                "b",
+                r#"{"id":"b","name":"B","version":"1","command":"run.sh"}"#,
+            ),
+            (
+                "a",
+                r#"{"id":"a","name":"A","version":"1","command":"run.sh","timeout_secs":5}"#,
+            ),
+            ("broken", r#"{"id":"broken"}"#),
+        ] {
+            std::fs::create_dir_all(root.join(id)).unwrap();
+            std::fs::write(root.join(id).join(MANIFEST_FILE), manifest).unwrap();
+        }
+        std::fs::create_dir_all(root.join("no-manifest")).unwrap();
+
+        let found = discover_in(&root);
+        let ids: Vec<_> = found.iter().map(|(_, m)| m.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(found[0].0, root.join("a"));
+        assert_eq!(found[0].1.timeout_secs, 5);
+        assert_eq!(found[1].1.timeout_secs, default_timeout());
+        assert!(discover_in(&root.join("missing")).is_empty());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn credentials_go_only_to_enabled_granted_plugins() {
+        let cfg = PluginSettings {
+            enabled: vec!["on".into(), "on-granted".into()],
+            credential_grants: vec!["on-granted".into(), "off-granted".into()],
+        };
+        assert!(receives_credentials(&cfg, "on-granted"));
+        assert!(!receives_credentials(&cfg, "on"));
+        assert!(!receives_credentials(&cfg, "off-granted"));
+        assert!(!receives_credentials(&cfg, "unknown"));
+    }
+
+    #[cfg(unix)]
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", script])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn processes_get_their_input_and_are_capped() {
+        let echoed = run_process(shell("cat"), b"{}", Duration::from_secs(5));
+        assert_eq!(echoed.unwrap(), b"{}");
+
+        let flood = format!("head -c {} /dev/zero", MAX_OUTPUT_BYTES + 1);
+        let err = run_process(shell(&flood), b"", Duration::from_secs(5)).unwrap_err();
+        assert!(err.contains("limit"), "{err}");
+
+        let err = run_process(shell("echo oops >&2; exit 3"), b"", Duration::from_secs(5));
+        assert!(err.unwrap_err().contains("oops"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn processes_are_killed_at_the_timeout() {
+        let started = Instant::now();
+        let err = run_process(shell("sleep 30"), b"", Duration::from_secs(1)).unwrap_err();
+        assert!(err.starts_with("Timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
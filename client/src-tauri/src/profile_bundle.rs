@@ -0,0 +1,317 @@
+//! Moving every stored profile to another machine in one file. The bundle is
+//! sealed like the encrypted secrets file (ChaCha20-Poly1305 under a
+//! PBKDF2-derived key) with a passphrase chosen at export, so it never holds
+//! plaintext secrets; it carries each profile's keys, origin, label and
+//! region selection. Importing skips profiles that already exist unless asked
+//! to replace them.
+
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use ring::aead::{self, Aad, Nonce};
+use secrecy::zeroize::Zeroizing;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::profiles::{self, ProfileLabel, ProfileOrigin};
+use crate::{app_lock, os_auth, secret_store, settings, AwsCredentials};
+
+const FORMAT: &str = "aws-cost-optimizer-profiles";
+const VERSION: u32 = 1;
+const AAD: &[u8] = b"aws-cost-optimizer profile bundle v1";
+/// Higher than the app lock's: a bundle can be attacked offline.
+const MIN_PASSPHRASE_LEN: usize = 12;
+
+/// The file as written: everything but the format header is sealed.
+#[derive(Serialize, Deserialize)]
+struct SealedBundle {
+    format: String,
+    version: u32,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// One profile going into a bundle; borrows the keychain JSON so the only
+/// plaintext copy is the buffer that is encrypted in place.
+#[derive(Serialize)]
+struct OutgoingProfile<'a> {
+    name: &'a str,
+    keys: &'a str,
+    origin: Option<&'a ProfileOrigin>,
+    label: Option<&'a ProfileLabel>,
+    regions: Option<&'a Vec<String>>,
+}
+
+/// One profile coming out of a bundle.
+#[derive(Deserialize)]
+struct IncomingProfile {
+    name: String,
+    keys: SecretString,
+    origin: Option<ProfileOrigin>,
+    label: Option<ProfileLabel>,
+    regions: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BundleExport {
+    pub path: String,
+    pub profiles: Vec<String>,
+}
+
+/// Outcome of importing one profile from a bundle.
+#[derive(Serialize, Clone, Debug)]
+pub struct BundleImport {
+    pub name: String,
+    pub imported: bool,
+    /// Why it was not; `None` when it was.
+    pub error: Option<String>,
+}
+
+fn check_passphrase(passphrase: &str) -> Result<(), AppError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Use a passphrase of at least {MIN_PASSPHRASE_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+fn seal(
+    plain: Zeroizing<Vec<u8>>,
+    passphrase: &str,
+    iterations: u32,
+) -> Result<SealedBundle, String> {
+    let salt = secret_store::random::<16>()?;
+    let nonce = secret_store::random::<{ aead::NONCE_LEN }>()?;
+    let key = Zeroizing::new(secret_store::derive_key(passphrase, &salt, iterations));
+    let mut buf = plain;
+    secret_store::aead_key(&key)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(AAD),
+            &mut *buf,
+        )
+        .map_err(|_| "Could not encrypt the bundle".to_string())?;
+    Ok(SealedBundle {
+        format: FORMAT.into(),
+        version: VERSION,
+        iterations,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(&*buf),
+    })
+}
+
+fn open(sealed: &SealedBundle, passphrase: &str) -> Result<Vec<IncomingProfile>, AppError> {
+    if sealed.format != FORMAT {
+        return Err(AppError::InvalidInput("Not a profile bundle".into()));
+    }
+    if sealed.version != VERSION {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported profile bundle version {}",
+            sealed.version
+        )));
+    }
+    let decode = |field: &str| {
+        BASE64
+            .decode(field)
+            .map_err(|e| AppError::InvalidInput(format!("Damaged profile bundle: {e}")))
+    };
+    let salt = decode(&sealed.salt)?;
+    let nonce = Nonce::try_assume_unique_for_key(&decode(&sealed.nonce)?)
+        .map_err(|_| AppError::InvalidInput("Damaged profile bundle: bad nonce".into()))?;
+    let mut buf = Zeroizing::new(decode(&sealed.ciphertext)?);
+    let key = Zeroizing::new(secret_store::derive_key(
+        passphrase,
+        &salt,
+        sealed.iterations,
+    ));
+    let plain = secret_store::aead_key(&key)
+        .open_in_place(nonce, Aad::from(AAD), &mut buf)
+        .map_err(|_| AppError::InvalidInput("Wrong passphrase".into()))?;
+    serde_json::from_slice(plain)
+        .map_err(|e| AppError::InvalidInput(format!("Damaged profile bundle: {e}")))
+}
+
+/// Every readable profile, sealed with `passphrase`.
+fn export(app: &AppHandle, passphrase: &str) -> Result<(SealedBundle, Vec<String>), AppError> {
+    let stored = settings::load(app).credential_profiles;
+    let mut names = Vec::new();
+    let mut keys = Vec::new();
+    for name in profiles::names()? {
+        let creds = profiles::read_profile(&name)?;
+        keys.push(creds.to_keyring_json()?);
+        names.push(name);
+    }
+    if names.is_empty() {
+        return Err(AppError::InvalidInput(
+            "There are no profiles to export".into(),
+        ));
+    }
+    let outgoing: Vec<OutgoingProfile> = names
+        .iter()
+        .zip(&keys)
+        .map(|(name, keys)| OutgoingProfile {
+            name,
+            keys,
+            origin: stored.origins.get(name),
+            label: stored.labels.get(name),
+            regions: stored.regions.get(name),
+        })
+        .collect();
+    let plain = Zeroizing::new(serde_json::to_vec(&outgoing).map_err(|e| e.to_string())?);
+    Ok((seal(plain, passphrase, secret_store::ITERATIONS)?, names))
+}
+
+fn import_one(app: &AppHandle, profile: IncomingProfile, replace: bool) -> Result<(), AppError> {
+    if !replace && profiles::names()?.contains(&profile.name) {
+        return Err(AppError::InvalidInput(
+            "A profile with this name exists".into(),
+        ));
+    }
+    let creds: AwsCredentials = serde_json::from_str(profile.keys.expose_secret())
+        .map_err(|e| AppError::InvalidInput(format!("Damaged profile keys: {e}")))?;
+    profiles::store(app, &profile.name, &creds, profile.origin)?;
+    let mut all = settings::load(app);
+    let stored = &mut all.credential_profiles;
+    match profile.label {
+        Some(label) => stored.labels.insert(profile.name.clone(), label),
+        None => stored.labels.remove(&profile.name),
+    };
+    match profile.regions {
+        Some(regions) => stored.regions.insert(profile.name, regions),
+        None => stored.regions.remove(&profile.name),
+    };
+    settings::save(app, &all)?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Writes every stored profile to `dest_path`, encrypted with `passphrase`.
+/// Asks for OS confirmation first when revealing secrets requires it.
+#[tauri::command]
+pub async fn export_profile_bundle(
+    app: AppHandle,
+    dest_path: String,
+    passphrase: String,
+) -> CommandResult<BundleExport> {
+    app_lock::ensure_unlocked(&app)?;
+    let passphrase = Zeroizing::new(passphrase);
+    check_passphrase(&passphrase)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        if settings::load(&app).reveal.require_os_auth {
+            os_auth::confirm_user()?;
+        }
+        let (sealed, names) = export(&app, &passphrase)?;
+        let json = serde_json::to_vec_pretty(&sealed).map_err(|e| e.to_string())?;
+        let path = Path::new(&dest_path);
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, json).map_err(|e| e.to_string())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+        }
+        Ok(BundleExport {
+            path: path.display().to_string(),
+            profiles: names,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Imports the profiles of a bundle written by [`export_profile_bundle`].
+/// Existing profiles of the same name are kept unless `replace` is set; one
+/// failure does not stop the rest.
+#[tauri::command]
+pub async fn import_profile_bundle(
+    app: AppHandle,
+    path: String,
+    passphrase: String,
+    replace: Option<bool>,
+) -> CommandResult<Vec<BundleImport>> {
+    app_lock::ensure_unlocked(&app)?;
+    let passphrase = Zeroizing::new(passphrase);
+    tauri::async_runtime::spawn_blocking(move || {
+        let raw = std::fs::read_to_string(&path)
+            .map_err(|e| AppError::InvalidInput(format!("Cannot read {path}: {e}")))?;
+        let sealed: SealedBundle = serde_json::from_str(&raw)
+            .map_err(|_| AppError::InvalidInput("Not a profile bundle".into()))?;
+        let incoming = open(&sealed, &passphrase)?;
+        Ok(incoming
+            .into_iter()
+            .map(|profile| {
+                let name = profile.name.clone();
+                let result = import_one(&app, profile, replace.unwrap_or(false));
+                BundleImport {
+                    name,
+                    imported: result.is_ok(),
+                    error: result.err().map(|e| e.to_string()),
+                }
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain() -> Zeroizing<Vec<u8>> {
+        let keys = r#"{"access_key_id":"AKIAEXAMPLE","secret_access_key":"it's secret","region":"eu-west-1","session_token":null}"#;
+        let label = ProfileLabel {
+            nickname: Some("Prod".into()),
+            ..ProfileLabel::default()
+        };
+        let outgoing = [OutgoingProfile {
+            name: "work",
+            keys,
+            origin: None,
+            label: Some(&label),
+            regions: None,
+        }];
+        Zeroizing::new(serde_json::to_vec(&outgoing).unwrap())
+    }
+
+    #[test]
+    fn bundles_open_only_with_the_passphrase() {
+        let sealed = seal(plain(), "correct horse battery", 10).unwrap();
+        let file = serde_json::to_string(&sealed).unwrap();
+        assert!(!file.contains("AKIAEXAMPLE") && !file.contains("secret"));
+
+        let opened = open(&sealed, "correct horse battery").unwrap();
+        assert_eq!(opened.len(), 1);
+        assert_eq!(opened[0].name, "work");
+        let creds: AwsCredentials = serde_json::from_str(opened[0].keys.expose_secret()).unwrap();
+        assert_eq!(creds.secret_access_key(), "it's secret");
+        assert_eq!(
+            opened[0].label.as_ref().and_then(|l| l.nickname.as_deref()),
+            Some("Prod")
+        );
+        assert!(matches!(
+            open(&sealed, "wrong horse battery"),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn other_files_are_refused() {
+        let mut sealed = seal(plain(), "correct horse battery", 10).unwrap();
+        sealed.format = "something-else".into();
+        assert!(open(&sealed, "correct horse battery").is_err());
+        assert!(check_passphrase("short").is_err());
+    }
+}
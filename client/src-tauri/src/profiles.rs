@@ -0,0 +1,593 @@
+//! Named credential profiles (e.g. work, personal, client accounts). Each
+//! profile's keys live in their own keychain entry (`profile:<name>`), and an
+//! index entry lists the profile names so nothing has to enumerate the
+//! keychain. The settings file records which profile is active; the app's
+//! current credentials are that profile's keys. Installs that kept one
+//! `aws-credentials` entry are migrated at launch. Each profile may carry a
+//! label (nickname, color, the account's IAM alias) so prod and sandbox are
+//! hard to confuse.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Utc};
+use secrecy::zeroize::Zeroizing;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::{
+    app_lock, apply_credentials, aws, keyring_entry_for, partition, settings, sts, AwsCredentials,
+};
+
+const MAX_NAME_LEN: usize = 64;
+/// Keychain account holding the JSON list of profile names.
+pub(crate) const INDEX_ACCOUNT: &str = "profile-index";
+/// Pre-migration account of the app's current credentials.
+const LEGACY_ACCOUNT: &str = "aws-credentials";
+/// Name for keys saved while no profile is active.
+const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CredentialProfile {
+    pub name: String,
+    pub creds: AwsCredentials,
+    #[serde(default)]
+    pub nickname: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ProfileSettings {
+    /// Profile names from before the keychain index; read once by the
+    /// migration.
+    #[serde(rename = "names", skip_serializing_if = "Vec::is_empty")]
+    pub legacy_names: Vec<String>,
+    pub active: Option<String>,
+    /// How derived profiles were obtained, by profile name.
+    pub origins: BTreeMap<String, ProfileOrigin>,
+    /// When each long-term access key was first saved, by access key ID;
+    /// the key's age when IAM cannot be asked.
+    pub key_saved_at: BTreeMap<String, DateTime<Utc>>,
+    /// Regions analyses cover, by profile name; absent for every region.
+    pub regions: BTreeMap<String, Vec<String>>,
+    /// Profiles imported from the AWS CLI files, watched for changes there.
+    pub imported: BTreeSet<String>,
+    /// How each profile is shown, by profile name.
+    pub labels: BTreeMap<String, ProfileLabel>,
+}
+
+/// How a profile is told apart in the UI, e.g. prod in red.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct ProfileLabel {
+    /// User-chosen name; the account alias is shown when unset.
+    pub nickname: Option<String>,
+    /// `#rrggbb`.
+    pub color: Option<String>,
+    /// The account's IAM alias, looked up when the profile is saved.
+    pub account_alias: Option<String>,
+}
+
+/// Where a derived profile's temporary keys came from, so they can be
+/// obtained again.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProfileOrigin {
+    AssumeRole {
+        /// Profile whose keys assumed the role; `None` when they were the
+        /// app's unsaved current credentials.
+        source_profile: Option<String>,
+        role_arn: String,
+        external_id: Option<String>,
+        session_name: String,
+    },
+    IdentityCenter {
+        account_id: String,
+        role_name: String,
+        /// Region the app works in with the role.
+        region: String,
+    },
+    /// Needs a fresh MFA code to renew.
+    MfaSession {
+        source_profile: Option<String>,
+        serial_number: String,
+    },
+    /// Roles assumed one after another, each with the previous hop's keys.
+    RoleChain {
+        source_profile: Option<String>,
+        hops: Vec<RoleHop>,
+    },
+}
+
+/// One `AssumeRole` call of a role chain.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RoleHop {
+    pub role_arn: String,
+    pub external_id: Option<String>,
+    /// Empty for the default session name.
+    #[serde(default)]
+    pub session_name: String,
+}
+
+/// What the profile list shows; secrets stay in the keychain.
+#[derive(Serialize, Clone, Debug)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub access_key_id: String,
+    pub region: String,
+    pub temporary: bool,
+    pub active: bool,
+    pub origin: Option<ProfileOrigin>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub label: ProfileLabel,
+}
+
+pub(crate) fn profile_account(name: &str) -> String {
+    format!("profile:{name}")
+}
+
+fn legacy_profile_account(name: &str) -> String {
+    format!("{LEGACY_ACCOUNT}:{name}")
+}
+
+fn read_entry(account: &str) -> Result<Option<String>, AppError> {
+    match keyring_entry_for(account)?.get_password() {
+        Ok(raw) => Ok(Some(raw)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(AppError::Credentials(err.to_string())),
+    }
+}
+
+fn write_entry(account: &str, value: &str) -> Result<(), String> {
+    keyring_entry_for(account)?
+        .set_password(value)
+        .map_err(|e| e.to_string())
+}
+
+fn delete_entry(account: &str) {
+    if let Ok(entry) = keyring_entry_for(account) {
+        let _ = entry.delete_credential();
+    }
+}
+
+/// Profile names from the index; `None` before the migration wrote it.
+fn read_index() -> Result<Option<Vec<String>>, AppError> {
+    read_entry(INDEX_ACCOUNT)?
+        .map(|raw| serde_json::from_str(&raw).map_err(|e| AppError::Credentials(e.to_string())))
+        .transpose()
+}
+
+fn write_index(names: &[String]) -> Result<(), String> {
+    write_entry(
+        INDEX_ACCOUNT,
+        &serde_json::to_string(names).map_err(|e| e.to_string())?,
+    )
+}
+
+/// Known profile names, sorted.
+pub(crate) fn names() -> Result<Vec<String>, AppError> {
+    Ok(read_index()?.unwrap_or_default())
+}
+
+fn add_to_index(name: &str) -> Result<(), AppError> {
+    let mut names = names()?;
+    if !names.iter().any(|n| n == name) {
+        names.push(name.to_string());
+        names.sort();
+        write_index(&names)?;
+    }
+    Ok(())
+}
+
+/// `base`, or `base-2`, `base-3`, ... if that is taken.
+fn unused_name(base: &str, names: &[String]) -> String {
+    (1..)
+        .map(|n| match n {
+            1 => base.to_string(),
+            n => format!("{base}-{n}"),
+        })
+        .find(|name| !names.contains(name))
+        .unwrap_or_else(|| base.to_string())
+}
+
+fn validate_name(name: &str) -> Result<&str, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Profile name is required".into()));
+    }
+    if name.len() > MAX_NAME_LEN || name.chars().any(char::is_control) {
+        return Err(AppError::InvalidInput(format!(
+            "Profile names are at most {MAX_NAME_LEN} printable characters"
+        )));
+    }
+    Ok(name)
+}
+
+/// Trimmed nickname and lowercase `#rrggbb` color; blanks clear them.
+fn validate_label(
+    nickname: Option<String>,
+    color: Option<String>,
+) -> Result<(Option<String>, Option<String>), AppError> {
+    let nickname = nickname
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    if let Some(nickname) = &nickname {
+        if nickname.len() > MAX_NAME_LEN || nickname.chars().any(char::is_control) {
+            return Err(AppError::InvalidInput(format!(
+                "Nicknames are at most {MAX_NAME_LEN} printable characters"
+            )));
+        }
+    }
+    let color = color
+        .map(|c| c.trim().to_ascii_lowercase())
+        .filter(|c| !c.is_empty());
+    if let Some(color) = &color {
+        let hex = color.strip_prefix('#').unwrap_or_default();
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(AppError::InvalidInput(format!(
+                "Colors are written #rrggbb, not {color}"
+            )));
+        }
+    }
+    Ok((nickname, color))
+}
+
+fn set_label(
+    app: &AppHandle,
+    name: &str,
+    nickname: Option<String>,
+    color: Option<String>,
+) -> Result<ProfileLabel, AppError> {
+    let mut all = settings::load(app);
+    let label = all
+        .credential_profiles
+        .labels
+        .entry(name.to_string())
+        .or_default();
+    label.nickname = nickname;
+    label.color = color;
+    let label = label.clone();
+    settings::save(app, &all)?;
+    Ok(label)
+}
+
+/// Looks up the profile's account alias with its own keys and records it.
+async fn refresh_alias(app: &AppHandle, name: &str) -> Result<ProfileLabel, AppError> {
+    let config = aws::sdk_config_from(read_profile(name)?).await;
+    let alias = sts::account_alias(&config).await?;
+    let mut all = settings::load(app);
+    let label = all
+        .credential_profiles
+        .labels
+        .entry(name.to_string())
+        .or_default();
+    label.account_alias = alias;
+    let label = label.clone();
+    settings::save(app, &all)?;
+    Ok(label)
+}
+
+pub(crate) fn read_profile(name: &str) -> Result<AwsCredentials, AppError> {
+    let raw = read_entry(&profile_account(name))?
+        .map(Zeroizing::new)
+        .ok_or_else(|| AppError::NotFound(format!("Profile '{name}' not found")))?;
+    serde_json::from_str(&raw).map_err(|e| AppError::Credentials(e.to_string()))
+}
+
+fn write_profile(name: &str, creds: &AwsCredentials) -> Result<(), String> {
+    let json = creds.to_keyring_json()?;
+    write_entry(&profile_account(name), &json)
+}
+
+/// Notes when a long-term key was first seen, for rotation reminders.
+fn record_saved(profiles: &mut ProfileSettings, creds: &AwsCredentials) {
+    if !creds.is_temporary() {
+        profiles
+            .key_saved_at
+            .entry(creds.access_key_id.clone())
+            .or_insert_with(Utc::now);
+    }
+}
+
+/// The active profile's keys; `None` when no profile is active.
+pub(crate) fn read_active(app: &AppHandle) -> Result<Option<AwsCredentials>, AppError> {
+    let Some(name) = settings::load(app).credential_profiles.active else {
+        return Ok(None);
+    };
+    match read_profile(&name) {
+        Ok(creds) => Ok(Some(creds)),
+        Err(AppError::NotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Saves the app's current credentials into the active profile, creating and
+/// activating a `default` profile when none is active.
+pub(crate) fn write_active(app: &AppHandle, creds: &AwsCredentials) -> Result<(), String> {
+    let mut all = settings::load(app);
+    let name = match all.credential_profiles.active.clone() {
+        Some(name) => name,
+        None => {
+            let name = unused_name(DEFAULT_PROFILE, &names()?);
+            all.credential_profiles.active = Some(name.clone());
+            name
+        }
+    };
+    record_saved(&mut all.credential_profiles, creds);
+    settings::save(app, &all)?;
+    write_profile(&name, creds)?;
+    add_to_index(&name)?;
+    Ok(())
+}
+
+/// Creates or replaces a profile; `origin` is recorded for derived profiles
+/// and cleared otherwise. Storing the active profile also applies its keys,
+/// restarting the sidecar.
+pub(crate) fn store(
+    app: &AppHandle,
+    name: &str,
+    creds: &AwsCredentials,
+    origin: Option<ProfileOrigin>,
+) -> Result<(), AppError> {
+    let name = validate_name(name)?.to_string();
+    if creds.access_key_id.trim().is_empty() || creds.secret_access_key().trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "Access key ID and secret access key are required".into(),
+        ));
+    }
+    partition::validate_region(&creds.region, creds.partition)?;
+    aws::validate_endpoint_url(creds.endpoint_url())?;
+    write_profile(&name, creds)?;
+    add_to_index(&name)?;
+
+    let mut all = settings::load(app);
+    let profiles = &mut all.credential_profiles;
+    match origin {
+        Some(origin) => profiles.origins.insert(name.clone(), origin),
+        None => profiles.origins.remove(&name),
+    };
+    record_saved(profiles, creds);
+    let active = profiles.active.as_deref() == Some(&name);
+    settings::save(app, &all)?;
+    if active {
+        apply_credentials(app, creds)?;
+    }
+    Ok(())
+}
+
+/// Stores a profile and makes it the active one, applying its keys once.
+pub(crate) fn store_and_activate(
+    app: &AppHandle,
+    name: &str,
+    creds: &AwsCredentials,
+    origin: Option<ProfileOrigin>,
+) -> Result<(), AppError> {
+    let name = validate_name(name)?;
+    let was_active = settings::load(app).credential_profiles.active.as_deref() == Some(name);
+    store(app, name, creds, origin)?;
+    if was_active {
+        Ok(())
+    } else {
+        activate(app, name)
+    }
+}
+
+/// Moves keys from the single `aws-credentials` entry and the old
+/// `aws-credentials:<name>` profile entries to per-profile entries plus the
+/// index. The old keys become the active profile, or `default` if none was
+/// active. Runs once; later launches find the index and return early.
+pub fn migrate(app: &AppHandle) -> Result<(), AppError> {
+    if read_index()?.is_some() {
+        return Ok(());
+    }
+    let mut all = settings::load(app);
+    let profiles = &mut all.credential_profiles;
+    let mut names = Vec::new();
+    let mut moved = Vec::new();
+    for name in std::mem::take(&mut profiles.legacy_names) {
+        if let Some(raw) = read_entry(&legacy_profile_account(&name))? {
+            write_entry(&profile_account(&name), &raw)?;
+            moved.push(legacy_profile_account(&name));
+            names.push(name);
+        }
+    }
+    if let Some(raw) = read_entry(LEGACY_ACCOUNT)? {
+        // With an active profile, the single entry was kept as its copy.
+        if profiles.active.as_ref().is_none_or(|a| !names.contains(a)) {
+            let name = unused_name(DEFAULT_PROFILE, &names);
+            write_entry(&profile_account(&name), &raw)?;
+            profiles.active = Some(name.clone());
+            names.push(name);
+        }
+        moved.push(LEGACY_ACCOUNT.to_string());
+    }
+    names.sort();
+    write_index(&names)?;
+    settings::save(app, &all)?;
+    for account in moved {
+        delete_entry(&account);
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Profiles whose keys are readable, in name order.
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Vec<ProfileSummary> {
+    let profiles = settings::load(&app).credential_profiles;
+    let names = match names() {
+        Ok(names) => names,
+        Err(err) => {
+            eprintln!("could not read the profile index: {err}");
+            Vec::new()
+        }
+    };
+    names
+        .iter()
+        .filter_map(|name| match read_profile(name) {
+            Ok(creds) => Some(ProfileSummary {
+                name: name.clone(),
+                temporary: creds.is_temporary(),
+                access_key_id: creds.access_key_id,
+                region: creds.region,
+                active: profiles.active.as_deref() == Some(name),
+                origin: profiles.origins.get(name).cloned(),
+                expires_at: creds.expires_at,
+                label: profiles.labels.get(name).cloned().unwrap_or_default(),
+            }),
+            Err(err) => {
+                eprintln!("skipping profile '{name}': {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Saves a profile and its label, then looks up the account alias in the
+/// background; a profile without `iam:ListAccountAliases` just has none.
+#[tauri::command]
+pub fn save_profile(app: AppHandle, profile: CredentialProfile) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    let (nickname, color) = validate_label(profile.nickname, profile.color)?;
+    store(&app, &profile.name, &profile.creds, None)?;
+    let name = validate_name(&profile.name)?.to_string();
+    set_label(&app, &name, nickname, color)?;
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = refresh_alias(&app, &name).await {
+            eprintln!("no account alias for profile '{name}': {err}");
+        }
+    });
+    Ok(())
+}
+
+/// Sets a profile's nickname and color without touching its keys.
+#[tauri::command]
+pub fn save_profile_label(
+    app: AppHandle,
+    name: String,
+    nickname: Option<String>,
+    color: Option<String>,
+) -> CommandResult<ProfileLabel> {
+    app_lock::ensure_unlocked(&app)?;
+    let (nickname, color) = validate_label(nickname, color)?;
+    if !names()?.contains(&name) {
+        return Err(AppError::NotFound(format!("Profile '{name}' not found")));
+    }
+    set_label(&app, &name, nickname, color)
+}
+
+/// Looks up the profile's account alias again, e.g. after it was renamed.
+#[tauri::command]
+pub async fn refresh_profile_alias(app: AppHandle, name: String) -> CommandResult<ProfileLabel> {
+    app_lock::ensure_unlocked(&app)?;
+    refresh_alias(&app, &name).await
+}
+
+/// Removes a profile. The active profile holds the app's current keys, so it
+/// cannot be deleted until another one is activated.
+#[tauri::command]
+pub fn delete_profile(app: AppHandle, name: String) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    let mut all = settings::load(&app);
+    let profiles = &mut all.credential_profiles;
+    let mut names = names()?;
+    if !names.contains(&name) {
+        return Err(AppError::NotFound(format!("Profile '{name}' not found")));
+    }
+    if profiles.active.as_deref() == Some(&name) {
+        return Err(AppError::InvalidInput(
+            "Switch to another profile before deleting the active one".into(),
+        ));
+    }
+    names.retain(|n| *n != name);
+    write_index(&names)?;
+    profiles.origins.remove(&name);
+    profiles.regions.remove(&name);
+    profiles.imported.remove(&name);
+    profiles.labels.remove(&name);
+    settings::save(&app, &all)?;
+    delete_entry(&profile_account(&name));
+    Ok(())
+}
+
+/// Deletes every profile's keys, leaving an empty index so the migration
+/// does not run again, and forgets the active profile.
+pub(crate) fn delete_all(app: &AppHandle) -> Result<(), AppError> {
+    let names = names()?;
+    write_index(&[])?;
+    let mut all = settings::load(app);
+    all.credential_profiles = ProfileSettings::default();
+    settings::save(app, &all)?;
+    for name in &names {
+        delete_entry(&profile_account(name));
+    }
+    delete_entry(LEGACY_ACCOUNT);
+    Ok(())
+}
+
+/// Switches the app to a profile's keys and restarts the sidecar with them.
+pub(crate) fn activate(app: &AppHandle, name: &str) -> Result<(), AppError> {
+    let mut all = settings::load(app);
+    if !names()?.iter().any(|n| n == name) {
+        return Err(AppError::NotFound(format!("Profile '{name}' not found")));
+    }
+    let creds = read_profile(name)?;
+    all.credential_profiles.active = Some(name.to_string());
+    settings::save(app, &all)?;
+    apply_credentials(app, &creds)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_active_profile(app: AppHandle, name: String) -> CommandResult<()> {
+    app_lock::ensure_unlocked(&app)?;
+    tauri::async_runtime::spawn_blocking(move || activate(&app, &name))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_trimmed_and_bounded() {
+        assert_eq!(validate_name("  work ").unwrap(), "work");
+        assert!(matches!(
+            validate_name("   "),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(validate_name(&"x".repeat(MAX_NAME_LEN + 1)).is_err());
+        assert!(validate_name("client\nadmin").is_err());
+    }
+
+    #[test]
+    fn labels_are_trimmed_and_colors_checked() {
+        assert_eq!(
+            validate_label(Some(" Prod ".into()), Some("#D93025".into())).unwrap(),
+            (Some("Prod".into()), Some("#d93025".into()))
+        );
+        assert_eq!(
+            validate_label(Some("  ".into()), Some(String::new())).unwrap(),
+            (None, None)
+        );
+        assert!(validate_label(None, Some("red".into())).is_err());
+        assert!(validate_label(None, Some("#d9302".into())).is_err());
+        assert!(validate_label(Some("x".repeat(MAX_NAME_LEN + 1)), None).is_err());
+    }
+
+    #[test]
+    fn default_name_avoids_existing_profiles() {
+        let names = |list: &[&str]| list.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(unused_name("default", &names(&["work"])), "default");
+        assert_eq!(
+            unused_name("default", &names(&["default", "default-2"])),
+            "default-3"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! AWS provider backed by the S3 optimizer sidecar.
+
+use serde_json::json;
+use tauri::AppHandle;
+
+use super::{
+    CloudProvider, CostSummary, ProviderRecommendation, ScanIssue, ScanOutcome, ScanScope,
+};
+use crate::backend::{self, RunDetails, ScanResponse};
+use crate::error::AppError;
+use crate::tasks::CancelToken;
+use crate::{regions, scan_progress};
+
+pub struct AwsProvider;
+
+/// The sidecar's scan request for `scope`.
+pub(crate) fn scan_body(scope: &ScanScope) -> serde_json::Value {
+    let mut body = json!({
+        "include_buckets": scope.include,
+        "exclude_buckets": scope.exclude,
+    });
+    if let Some(max) = scope.max_objects_per_resource {
+        body["max_objects_per_bucket"] = json!(max);
+    }
+    if !scope.regions.is_empty() {
+        body["regions"] = json!(scope.regions);
+    }
+    body
+}
+
+pub(crate) fn outcome(response: ScanResponse) -> ScanOutcome {
+    ScanOutcome {
+        run_id: response.run_id,
+        recommendation_count: response.recommendations.len(),
+        estimated_monthly_savings: response.estimated_monthly_savings,
+        errors: response
+            .errors
+            .into_iter()
+            .map(|err| ScanIssue {
+                region: err.region,
+                service: err.service,
+                resource: err.bucket,
+                operation: err.operation,
+                code: err.code,
+                message: err.message,
+            })
+            .collect(),
+    }
+}
+
+impl AwsProvider {
+    fn latest_run(&self) -> Result<Option<RunDetails>, AppError> {
+        match backend::list_runs()?.first() {
+            Some(run) => backend::get_run(&run.run_id).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl CloudProvider for AwsProvider {
+    fn id(&self) -> &'static str {
+        "aws"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Amazon Web Services"
+    }
+
+    fn available(&self) -> bool {
+        backend::healthy()
+    }
+
+    /// The sidecar only prices the S3 storage it scanned, and only once the
+    /// run has been scored.
+    fn query_costs(&self) -> Result<CostSummary, AppError> {
+        let Some(run) = self.latest_run()? else {
+            return Ok(CostSummary::default());
+        };
+        Ok(run
+            .savings_details
+            .iter()
+            .fold(CostSummary::default(), |mut sum, s| {
+                sum.current_monthly_cost += s.current_monthly_cost;
+                sum.projected_monthly_cost += s.projected_monthly_cost;
+                sum.monthly_savings += s.monthly_savings;
+                sum
+            }))
+    }
+
+    /// Emits `scan-progress` events while the sidecar scans.
+    fn scan(
+        &self,
+        app: &AppHandle,
+        scope: &ScanScope,
+        cancel: &CancelToken,
+    ) -> Result<ScanOutcome, AppError> {
+        let mut scope = scope.clone();
+        if scope.regions.is_empty() {
+            scope.regions = regions::enabled_for_active(app);
+        }
+        let response = scan_progress::scan(app, cancel, scan_body(&scope))?;
+        Ok(outcome(response))
+    }
+
+    fn recommendations(&self) -> Result<Vec<ProviderRecommendation>, AppError> {
+        let Some(run) = self.latest_run()? else {
+            return Ok(Vec::new());
+        };
+        Ok(run
+            .recommendations
+            .into_iter()
+            .map(|rec| ProviderRecommendation {
+                provider: self.id().to_string(),
+                resource: match &rec.key {
+                    Some(key) => format!("{}/{key}", rec.bucket),
+                    None => rec.bucket.clone(),
+                },
+                id: rec.id,
+                recommendation_type: rec.recommendation_type,
+                risk_level: rec.risk_level,
+                recommended_action: rec.recommended_action,
+                estimated_monthly_savings: rec.estimated_monthly_savings,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn costs_and_recommendations_come_from_the_latest_run() {
+        let _sidecar = mock::install_sidecar("sidecar/runs.json");
+        let costs = AwsProvider.query_costs().unwrap();
+        assert_eq!(costs.current_monthly_cost, 70.0);
+        assert_eq!(costs.monthly_savings, 50.0);
+
+        let recs = AwsProvider.recommendations().unwrap();
+        let resources: Vec<&str> = recs.iter().map(|r| r.resource.as_str()).collect();
+        assert_eq!(resources, ["logs-archive", "media/raw/2019.tar"]);
+        assert!(recs.iter().all(|r| r.provider == "aws"));
+    }
+
+    #[test]
+    fn health_check_goes_through_the_transport() {
+        let _sidecar = mock::install_sidecar("sidecar/scan.json");
+        assert!(AwsProvider.available());
+        drop(_sidecar);
+        let _sidecar = mock::install_sidecar("sidecar/runs.json");
+        assert!(!AwsProvider.available());
+    }
+}
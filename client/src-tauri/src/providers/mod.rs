@@ -0,0 +1,224 @@
+//! Cloud provider abstraction. Each provider answers the same three
+//! questions — what does it cost, scan it, what should change — so the UI can
+//! present every connected cloud in one dashboard. AWS (through the sidecar)
+//! is the only implementation so far.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::error::{AppError, CommandResult};
+use crate::jobs::{self, JobKind};
+use crate::tasks::{self, CancelToken};
+use crate::{perf, plugins};
+
+pub mod aws;
+
+/// Merged recommendations stay valid this long unless a run or plugin
+/// finding changes first.
+const FINDINGS_TTL: Duration = Duration::from_secs(300);
+
+static FINDINGS_CACHE: Mutex<Option<(Instant, Vec<ProviderRecommendation>)>> = Mutex::new(None);
+
+/// Cost of the resources a provider has analyzed, per month in USD.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct CostSummary {
+    pub current_monthly_cost: f64,
+    pub projected_monthly_cost: f64,
+    pub monthly_savings: f64,
+}
+
+/// Which resources a scan covers. Resource names are provider-specific
+/// containers (S3 buckets, Azure storage accounts, GCS buckets).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScanScope {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub max_objects_per_resource: Option<u32>,
+    /// Regions to cover; empty for the profile's selection, or every region
+    /// when it has none.
+    #[serde(default)]
+    pub regions: Vec<String>,
+}
+
+/// A region or service call that failed while the rest of a scan went on.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScanIssue {
+    pub region: String,
+    pub service: String,
+    /// Resource being scanned when the call failed, if any.
+    pub resource: Option<String>,
+    pub operation: String,
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScanOutcome {
+    pub run_id: String,
+    pub recommendation_count: usize,
+    pub estimated_monthly_savings: f64,
+    /// Non-empty when the outcome covers only part of the scope.
+    pub errors: Vec<ScanIssue>,
+}
+
+/// Provider-neutral view of a recommendation.
+#[derive(Serialize, Clone, Debug)]
+pub struct ProviderRecommendation {
+    /// Provider ID, or `plugin:<id>` for findings of custom scanner plugins.
+    pub provider: String,
+    pub id: String,
+    /// Human-readable resource path, e.g. `bucket/key`.
+    pub resource: String,
+    pub recommendation_type: String,
+    pub risk_level: String,
+    pub recommended_action: String,
+    pub estimated_monthly_savings: f64,
+}
+
+pub trait CloudProvider: Send + Sync {
+    /// Stable identifier, e.g. `aws`.
+    fn id(&self) -> &'static str;
+    fn display_name(&self) -> &'static str;
+    /// Whether the provider has credentials and a reachable backend.
+    fn available(&self) -> bool;
+    /// Cost of the most recently analyzed resources.
+    fn query_costs(&self) -> Result<CostSummary, AppError>;
+    fn scan(
+        &self,
+        app: &AppHandle,
+        scope: &ScanScope,
+        cancel: &CancelToken,
+    ) -> Result<ScanOutcome, AppError>;
+    /// Recommendations from the latest scan.
+    fn recommendations(&self) -> Result<Vec<ProviderRecommendation>, AppError>;
+}
+
+fn registry() -> Vec<Box<dyn CloudProvider>> {
+    vec![Box::new(aws::AwsProvider)]
+}
+
+pub(crate) fn find(id: &str) -> Result<Box<dyn CloudProvider>, AppError> {
+    registry()
+        .into_iter()
+        .find(|p| p.id() == id)
+        .ok_or_else(|| AppError::NotFound(format!("Unknown provider '{id}'")))
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProviderStatus {
+    pub id: &'static str,
+    pub display_name: &'static str,
+    pub available: bool,
+    /// `None` when the provider is unavailable or its query failed.
+    pub costs: Option<CostSummary>,
+    pub recommendation_count: Option<usize>,
+    pub error: Option<String>,
+}
+
+fn status(provider: &dyn CloudProvider) -> ProviderStatus {
+    let mut status = ProviderStatus {
+        id: provider.id(),
+        display_name: provider.display_name(),
+        available: provider.available(),
+        costs: None,
+        recommendation_count: None,
+        error: None,
+    };
+    if status.available {
+        match provider
+            .query_costs()
+            .and_then(|costs| Ok((costs, provider.recommendations()?.len())))
+        {
+            Ok((costs, count)) => {
+                status.costs = Some(costs);
+                status.recommendation_count = Some(count);
+            }
+            Err(err) => status.error = Some(err.to_string()),
+        }
+    }
+    status
+}
+
+/// Drops the cached recommendation list; the next read collects it afresh.
+pub(crate) fn invalidate_findings() {
+    *FINDINGS_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Recommendations of every available provider and enabled plugin, highest
+/// savings first, served from cache while fresh.
+pub(crate) fn collect_recommendations(
+    app: &AppHandle,
+) -> Result<Vec<ProviderRecommendation>, AppError> {
+    if let Some((at, cached)) = &*FINDINGS_CACHE.lock().unwrap_or_else(|e| e.into_inner()) {
+        if at.elapsed() < FINDINGS_TTL {
+            perf::count_cache(true);
+            return Ok(cached.clone());
+        }
+    }
+    perf::count_cache(false);
+    let mut all = plugins::recommendations(app);
+    for provider in registry().iter().filter(|p| p.available()) {
+        all.extend(provider.recommendations()?);
+    }
+    all.sort_by(|a, b| {
+        b.estimated_monthly_savings
+            .total_cmp(&a.estimated_monthly_savings)
+    });
+    *FINDINGS_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), all.clone()));
+    Ok(all)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// One entry per provider with its costs and finding count.
+#[tauri::command]
+pub async fn get_multi_cloud_summary() -> CommandResult<Vec<ProviderStatus>> {
+    tauri::async_runtime::spawn_blocking(|| registry().iter().map(|p| status(p.as_ref())).collect())
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+/// Recommendations of every available provider and enabled plugin, highest
+/// savings first.
+#[tauri::command]
+pub async fn list_provider_recommendations(
+    app: AppHandle,
+) -> CommandResult<Vec<ProviderRecommendation>> {
+    let handle = app.clone();
+    perf::measure(&app, "list_provider_recommendations", async move {
+        tauri::async_runtime::spawn_blocking(perf::blocking(move || {
+            collect_recommendations(&handle)
+        }))
+        .await
+        .map_err(|e| e.to_string())?
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn start_provider_scan(
+    app: AppHandle,
+    provider: String,
+    scope: ScanScope,
+    task_id: Option<String>,
+) -> CommandResult<ScanOutcome> {
+    let handle = app.clone();
+    perf::measure(&app, "start_provider_scan", async move {
+        tauri::async_runtime::spawn_blocking(perf::blocking(move || {
+            let (cancel, _task) = tasks::register(&handle, task_id)?;
+            let job = jobs::run_now(&handle, JobKind::ProviderScan { provider, scope }, &cancel)?;
+            job.outcome
+                .ok_or_else(|| AppError::Internal("Scan job recorded no outcome".into()))
+        }))
+        .await
+        .map_err(|e| e.to_string())?
+    })
+    .await
+}
@@ -0,0 +1,433 @@
+//! HTTP(S) proxy for corporate networks. The proxy comes from the system
+//! (the `HTTPS_PROXY` family of variables, else the macOS or Windows proxy
+//! settings) or from explicit settings, and can be turned off.
+//!
+//! It is handed to everything that talks to the internet. The sidecar gets
+//! it in its environment, which boto3 reads. The native AWS SDK and the
+//! updater's HTTP client read the same variables, set on this process at
+//! launch. A remote backend's agent gets it explicitly. Loopback is always
+//! exempt, so the checks that wait for the sidecar never go through it.
+
+#[cfg(any(target_os = "macos", windows))]
+use std::process::Command;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::{read_credentials, settings, sso};
+
+/// Never proxied, whatever the settings say.
+const LOOPBACK: &str = "localhost,127.0.0.1,::1";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    /// Whatever the environment or the OS is set to.
+    #[default]
+    System,
+    Manual,
+    /// Connect directly, ignoring any system proxy.
+    Off,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub mode: ProxyMode,
+    /// Used in `manual` mode, e.g. `http://proxy.corp.example:3128`.
+    pub https_proxy: String,
+    /// Used in `manual` mode for plain HTTP; empty uses `https_proxy`.
+    pub http_proxy: String,
+    /// Comma-separated hosts and domains that bypass the proxy.
+    pub no_proxy: String,
+}
+
+/// The proxy in force.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProxyConfig {
+    pub https_proxy: Option<String>,
+    pub http_proxy: Option<String>,
+    /// Always includes loopback.
+    pub no_proxy: String,
+}
+
+/// Where the proxy in force came from.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxySource {
+    Environment,
+    Os,
+    Settings,
+    None,
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// `proxy` with a scheme, as the OS settings give host and port only.
+fn with_scheme(proxy: &str) -> String {
+    if proxy.contains("://") {
+        proxy.to_string()
+    } else {
+        format!("http://{proxy}")
+    }
+}
+
+fn with_loopback(no_proxy: &str) -> String {
+    match non_empty(no_proxy) {
+        Some(hosts) => format!("{hosts},{LOOPBACK}"),
+        None => LOOPBACK.into(),
+    }
+}
+
+fn from_env() -> Option<ProxyConfig> {
+    let var = |names: [&str; 2]| {
+        names
+            .into_iter()
+            .find_map(|name| std::env::var(name).ok().as_deref().and_then(non_empty))
+    };
+    let https_proxy = var(["HTTPS_PROXY", "https_proxy"]);
+    let http_proxy = var(["HTTP_PROXY", "http_proxy"]);
+    if https_proxy.is_none() && http_proxy.is_none() {
+        return None;
+    }
+    Some(ProxyConfig {
+        https_proxy,
+        http_proxy,
+        no_proxy: with_loopback(&var(["NO_PROXY", "no_proxy"]).unwrap_or_default()),
+    })
+}
+
+/// Reads `scutil --proxy` output.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_scutil(output: &str) -> Option<ProxyConfig> {
+    let mut values = std::collections::HashMap::new();
+    let mut exceptions = Vec::new();
+    let mut in_exceptions = false;
+    for line in output.lines().map(str::trim) {
+        if line.starts_with("ExceptionsList") {
+            in_exceptions = true;
+            continue;
+        }
+        if in_exceptions {
+            match line.split_once(" : ") {
+                Some((_, host)) => exceptions.push(host.to_string()),
+                None => in_exceptions = false,
+            }
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(" : ") {
+            values.insert(key, value);
+        }
+    }
+    let proxy = |kind: &str| {
+        if values.get(format!("{kind}Enable").as_str()) != Some(&"1") {
+            return None;
+        }
+        let host = values.get(format!("{kind}Proxy").as_str())?;
+        Some(match values.get(format!("{kind}Port").as_str()) {
+            Some(port) => format!("http://{host}:{port}"),
+            None => format!("http://{host}"),
+        })
+    };
+    let (https_proxy, http_proxy) = (proxy("HTTPS"), proxy("HTTP"));
+    if https_proxy.is_none() && http_proxy.is_none() {
+        return None;
+    }
+    Some(ProxyConfig {
+        https_proxy,
+        http_proxy,
+        no_proxy: with_loopback(&exceptions.join(",")),
+    })
+}
+
+/// Reads `reg query` output for the WinINet proxy settings.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_wininet(output: &str) -> Option<ProxyConfig> {
+    let mut enabled = false;
+    let mut server = None;
+    let mut bypass = String::new();
+    for line in output.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(_kind)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let value = fields.collect::<Vec<_>>().join(" ");
+        match name {
+            "ProxyEnable" => enabled = value == "0x1",
+            "ProxyServer" => server = non_empty(&value),
+            "ProxyOverride" => {
+                bypass = value
+                    .split(';')
+                    .filter(|host| *host != "<local>" && !host.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(",");
+            }
+            _ => {}
+        }
+    }
+    let server = server.filter(|_| enabled)?;
+    // Either one proxy for everything or `http=host:port;https=host:port`.
+    let (https_proxy, http_proxy) = if server.contains('=') {
+        let scheme = |want: &str| {
+            server
+                .split(';')
+                .filter_map(|part| part.split_once('='))
+                .find(|(scheme, _)| *scheme == want)
+                .map(|(_, proxy)| with_scheme(proxy))
+        };
+        (scheme("https"), scheme("http"))
+    } else {
+        (Some(with_scheme(&server)), Some(with_scheme(&server)))
+    };
+    if https_proxy.is_none() && http_proxy.is_none() {
+        return None;
+    }
+    Some(ProxyConfig {
+        https_proxy,
+        http_proxy,
+        no_proxy: with_loopback(&bypass),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn from_os() -> Option<ProxyConfig> {
+    let output = Command::new("scutil").arg("--proxy").output().ok()?;
+    parse_scutil(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(windows)]
+fn from_os() -> Option<ProxyConfig> {
+    let output = Command::new("reg")
+        .args([
+            "query",
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Internet Settings",
+        ])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())?;
+    parse_wininet(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Elsewhere the environment is the system setting.
+#[cfg(not(any(target_os = "macos", windows)))]
+fn from_os() -> Option<ProxyConfig> {
+    None
+}
+
+/// The proxy `config` asks for, and where it came from.
+fn resolve(config: &ProxySettings) -> (Option<ProxyConfig>, ProxySource) {
+    match config.mode {
+        ProxyMode::Off => (None, ProxySource::None),
+        ProxyMode::Manual => {
+            let https_proxy = non_empty(&config.https_proxy);
+            let http_proxy = non_empty(&config.http_proxy).or_else(|| https_proxy.clone());
+            let proxy = ProxyConfig {
+                https_proxy,
+                http_proxy,
+                no_proxy: with_loopback(&config.no_proxy),
+            };
+            (Some(proxy), ProxySource::Settings)
+        }
+        ProxyMode::System => {
+            if let Some(proxy) = from_env() {
+                (Some(proxy), ProxySource::Environment)
+            } else if let Some(proxy) = from_os() {
+                (Some(proxy), ProxySource::Os)
+            } else {
+                (None, ProxySource::None)
+            }
+        }
+    }
+}
+
+/// Whether `host` is exempt under the comma-separated `no_proxy` list, where
+/// an entry covers the host itself and its subdomains.
+fn bypasses(no_proxy: &str, host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    no_proxy.split(',').map(str::trim).any(|entry| {
+        let domain = entry.trim_start_matches("*.").trim_start_matches('.');
+        entry == "*"
+            || (!domain.is_empty()
+                && (host.eq_ignore_ascii_case(domain)
+                    || host
+                        .to_ascii_lowercase()
+                        .ends_with(&format!(".{}", domain.to_ascii_lowercase()))))
+    })
+}
+
+static CURRENT: RwLock<Option<ProxyConfig>> = RwLock::new(None);
+
+/// The proxy in force, if any.
+pub(crate) fn current() -> Option<ProxyConfig> {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// The proxy for an HTTPS request to `host`, if it is not exempt.
+pub(crate) fn for_host(host: &str) -> Option<String> {
+    let proxy = current()?;
+    if bypasses(&proxy.no_proxy, host) {
+        return None;
+    }
+    proxy.https_proxy.or(proxy.http_proxy)
+}
+
+/// The proxy variables, in both cases, for the sidecar's environment. Empty
+/// values clear any the app itself inherited.
+pub(crate) fn env_vars() -> Vec<(&'static str, String)> {
+    let proxy = current().unwrap_or_default();
+    let https = proxy.https_proxy.unwrap_or_default();
+    let http = proxy.http_proxy.unwrap_or_default();
+    vec![
+        ("HTTPS_PROXY", https.clone()),
+        ("https_proxy", https),
+        ("HTTP_PROXY", http.clone()),
+        ("http_proxy", http),
+        ("NO_PROXY", proxy.no_proxy.clone()),
+        ("no_proxy", proxy.no_proxy),
+    ]
+}
+
+/// Resolves the proxy again and sets it on this process, for the clients
+/// that read the environment. Call before they are built.
+pub(crate) fn apply(app: &AppHandle) {
+    let (proxy, _) = resolve(&settings::load(app).proxy);
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = proxy;
+    for (name, value) in env_vars() {
+        if value.is_empty() {
+            std::env::remove_var(name);
+        } else {
+            std::env::set_var(name, value);
+        }
+    }
+}
+
+/// What [`get_proxy_settings`] returns.
+#[derive(Serialize, Clone, Debug)]
+pub struct ProxyStatus {
+    pub settings: ProxySettings,
+    pub effective: Option<ProxyConfig>,
+    pub source: ProxySource,
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_proxy_settings(app: AppHandle) -> ProxyStatus {
+    let settings = settings::load(&app).proxy;
+    let (effective, source) = resolve(&settings);
+    ProxyStatus {
+        settings,
+        effective,
+        source,
+    }
+}
+
+/// Saves the proxy and applies it, restarting the sidecar so it picks the
+/// new environment up.
+#[tauri::command]
+pub async fn save_proxy_settings(app: AppHandle, config: ProxySettings) -> CommandResult<()> {
+    if config.mode == ProxyMode::Manual {
+        let urls = [&config.https_proxy, &config.http_proxy];
+        if non_empty(&config.https_proxy).is_none() {
+            return Err(AppError::InvalidInput(
+                "Enter the proxy to use for HTTPS".into(),
+            ));
+        }
+        if let Some(bad) = urls
+            .into_iter()
+            .map(String::as_str)
+            .filter_map(non_empty)
+            .find(|url| {
+                !(url.starts_with("http://") || url.starts_with("https://"))
+                    || url.contains(char::is_whitespace)
+            })
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Proxy URLs start with http:// or https://: {bad}"
+            )));
+        }
+    }
+    let mut all = settings::load(&app);
+    all.proxy = config;
+    settings::save(&app, &all)?;
+    tauri::async_runtime::spawn_blocking(move || -> Result<(), String> {
+        apply(&app);
+        crate::remote_backend::apply(&app)?;
+        let running = app
+            .state::<crate::SidecarState>()
+            .0
+            .lock()
+            .is_ok_and(|child| child.is_some());
+        let creds = read_credentials(&app).filter(|_| sso::access_granted(&app));
+        match creds {
+            Some(creds) if running => crate::restart_sidecar(&app, &creds),
+            _ => Ok(()),
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_proxy_covers_hosts_and_subdomains() {
+        let list = "corp.example, .internal,*.lan,localhost,127.0.0.1,::1";
+        assert!(bypasses(list, "corp.example"));
+        assert!(bypasses(list, "git.corp.example"));
+        assert!(bypasses(list, "db.internal"));
+        assert!(bypasses(list, "nas.lan"));
+        assert!(bypasses(list, "[::1]"));
+        assert!(!bypasses(list, "sts.amazonaws.com"));
+        assert!(!bypasses(list, "notcorp.example"));
+        assert!(bypasses("*", "sts.amazonaws.com"));
+    }
+
+    #[test]
+    fn manual_proxy_always_exempts_loopback() {
+        let (proxy, source) = resolve(&ProxySettings {
+            mode: ProxyMode::Manual,
+            https_proxy: "http://proxy:3128".into(),
+            ..ProxySettings::default()
+        });
+        let proxy = proxy.unwrap();
+        assert_eq!(source, ProxySource::Settings);
+        assert_eq!(proxy.http_proxy.as_deref(), Some("http://proxy:3128"));
+        assert_eq!(proxy.no_proxy, LOOPBACK);
+        let off = ProxySettings {
+            mode: ProxyMode::Off,
+            ..ProxySettings::default()
+        };
+        assert_eq!(resolve(&off), (None, ProxySource::None));
+    }
+
+    #[test]
+    fn os_proxy_settings_are_read() {
+        let scutil = "<dictionary> {\n  ExceptionsList : <array> {\n    0 : *.local\n  }\n  \
+                      HTTPSEnable : 1\n  HTTPSPort : 3128\n  HTTPSProxy : proxy.corp\n}\n";
+        let proxy = parse_scutil(scutil).unwrap();
+        assert_eq!(proxy.https_proxy.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(proxy.http_proxy, None);
+        assert_eq!(proxy.no_proxy, format!("*.local,{LOOPBACK}"));
+
+        let reg = "HKEY_CURRENT_USER\\...\\Internet Settings\n    \
+                   ProxyEnable    REG_DWORD    0x1\n    \
+                   ProxyServer    REG_SZ    http=proxy:80;https=proxy:443\n    \
+                   ProxyOverride    REG_SZ    <local>;*.corp\n";
+        let proxy = parse_wininet(reg).unwrap();
+        assert_eq!(proxy.https_proxy.as_deref(), Some("http://proxy:443"));
+        assert_eq!(proxy.http_proxy.as_deref(), Some("http://proxy:80"));
+        assert_eq!(proxy.no_proxy, format!("*.corp,{LOOPBACK}"));
+        assert!(parse_wininet(&reg.replace("0x1", "0x0")).is_none());
+    }
+}
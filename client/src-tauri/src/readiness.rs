@@ -0,0 +1,114 @@
+//! Waits for a freshly spawned sidecar to answer its health check, for
+//! launch, the watchdog's restarts and `restart_backend` alike. Checks are
+//! async and spaced by exponential backoff with jitter, so a slow start costs
+//! few requests and no thread sleeps through it. Starting or stopping a
+//! sidecar cancels the wait on the one before, which then reports
+//! [`Readiness::Cancelled`] rather than a failed start.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::AppHandle;
+use tokio::sync::watch;
+
+use crate::{api_version, backend, exposure, health_check, sidecar_watchdog};
+
+/// Longest pause between checks, however long the sidecar takes.
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Cancels the wait in progress, if any.
+static CURRENT: Mutex<Option<watch::Sender<bool>>> = Mutex::new(None);
+
+/// How a wait ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Readiness {
+    Ready,
+    TimedOut,
+    /// Another sidecar was started, or this one stopped, before it answered.
+    Cancelled,
+}
+
+/// Pause before check `attempt` + 1: `base` doubled per attempt up to
+/// [`MAX_DELAY`], of which a random half is taken off so sidecars started
+/// together do not poll in step.
+fn delay(base: Duration, attempt: u32) -> Duration {
+    let cap = base
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_DELAY);
+    let half = cap.as_millis() as u64 / 2;
+    cap - Duration::from_millis(fastrand::u64(0..=half))
+}
+
+/// Cancels the wait in progress, if any.
+pub(crate) fn cancel() {
+    if let Some(sender) = CURRENT.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        let _ = sender.send(true);
+    }
+}
+
+/// Waits up to `timeout_secs` for sidecar `pid` to answer, then checks it
+/// and announces it as ready. Cancels any earlier wait.
+pub(crate) async fn wait(app: &AppHandle, pid: u32, timeout_secs: u64) -> Readiness {
+    let mut cancelled = {
+        let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = current.take() {
+            let _ = previous.send(true);
+        }
+        let (sender, receiver) = watch::channel(false);
+        *current = Some(sender);
+        receiver
+    };
+    let base = health_check::current().poll_interval();
+    let started = Instant::now();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_secs);
+    let mut attempt = 0;
+    loop {
+        if *cancelled.borrow() {
+            return Readiness::Cancelled;
+        }
+        if backend::healthy_async().await {
+            let app = app.clone();
+            let _ = tauri::async_runtime::spawn_blocking(move || {
+                api_version::check_backend(&app);
+                exposure::audit(&app);
+                sidecar_watchdog::ready(&app, pid, started);
+            })
+            .await;
+            return Readiness::Ready;
+        }
+        let now = tokio::time::Instant::now();
+        if now >= deadline {
+            return Readiness::TimedOut;
+        }
+        attempt += 1;
+        let pause = (now + delay(base, attempt)).min(deadline);
+        tokio::select! {
+            _ = tokio::time::sleep_until(pause) => {}
+            // A dropped sender was replaced by a newer wait.
+            _ = cancelled.changed() => return Readiness::Cancelled,
+        }
+    }
+}
+
+/// [`wait`] for callers on a blocking thread.
+pub(crate) fn wait_blocking(app: &AppHandle, pid: u32, timeout_secs: u64) -> Readiness {
+    tauri::async_runtime::block_on(wait(app, pid, timeout_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_grow_to_a_cap_with_jitter() {
+        let base = Duration::from_millis(300);
+        for _ in 0..100 {
+            let first = delay(base, 1);
+            assert!((150..=300).contains(&first.as_millis()), "{first:?}");
+            let third = delay(base, 3);
+            assert!((600..=1_200).contains(&third.as_millis()), "{third:?}");
+            let late = delay(base, 40);
+            assert!((1_000..=2_000).contains(&late.as_millis()), "{late:?}");
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Regions each profile's analyses cover. The available ones come from
+//! `ec2:DescribeRegions`, including opt-in regions the account has not
+//! enabled (marked as such). The selection is kept per profile in the
+//! settings file; an empty one means every region. Scans pass it to the
+//! sidecar, which skips buckets in other regions.
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::aws::{sdk_config, sdk_error, send};
+use crate::error::{AppError, CommandResult};
+use crate::{app_lock, partition, profiles, settings};
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AvailableRegion {
+    pub name: String,
+    /// `opt-in-not-required`, `opted-in` or `not-opted-in`.
+    pub opt_in_status: String,
+    /// Whether the account can use the region.
+    pub enabled: bool,
+}
+
+pub(crate) async fn available(
+    config: &aws_config::SdkConfig,
+) -> Result<Vec<AvailableRegion>, AppError> {
+    let client = aws_sdk_ec2::Client::new(config);
+    let out = send(|| client.describe_regions().all_regions(true).send())
+        .await
+        .map_err(sdk_error)?;
+    let mut regions: Vec<AvailableRegion> = out
+        .regions()
+        .iter()
+        .filter_map(|region| {
+            let opt_in_status = region.opt_in_status().unwrap_or("opt-in-not-required");
+            Some(AvailableRegion {
+                name: region.region_name()?.to_string(),
+                enabled: opt_in_status != "not-opted-in",
+                opt_in_status: opt_in_status.to_string(),
+            })
+        })
+        .collect();
+    regions.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(regions)
+}
+
+/// The regions selected for `profile`; empty for every region.
+pub(crate) fn enabled(app: &AppHandle, profile: &str) -> Vec<String> {
+    settings::load(app)
+        .credential_profiles
+        .regions
+        .get(profile)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// The regions selected for the active profile; empty for every region.
+pub(crate) fn enabled_for_active(app: &AppHandle) -> Vec<String> {
+    match settings::load(app).credential_profiles.active {
+        Some(profile) => enabled(app, &profile),
+        None => Vec::new(),
+    }
+}
+
+/// Trimmed, sorted and deduplicated, all in `partition`.
+fn normalize(
+    regions: Vec<String>,
+    partition: partition::Partition,
+) -> Result<Vec<String>, AppError> {
+    let mut regions: Vec<String> = regions
+        .into_iter()
+        .map(|region| region.trim().to_string())
+        .filter(|region| !region.is_empty())
+        .collect();
+    for region in &regions {
+        partition::validate_region(region, Some(partition))?;
+    }
+    regions.sort();
+    regions.dedup();
+    Ok(regions)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Every region of the current credentials' partition.
+#[tauri::command]
+pub async fn list_available_regions(app: AppHandle) -> CommandResult<Vec<AvailableRegion>> {
+    available(&sdk_config(&app).await?).await
+}
+
+/// Regions selected for `profile` (default: the active one).
+#[tauri::command]
+pub fn get_profile_regions(app: AppHandle, profile: Option<String>) -> Vec<String> {
+    match profile {
+        Some(profile) => enabled(&app, &profile),
+        None => enabled_for_active(&app),
+    }
+}
+
+/// Selects the regions `profile` covers; an empty list covers all of them.
+#[tauri::command]
+pub fn save_profile_regions(
+    app: AppHandle,
+    profile: String,
+    regions: Vec<String>,
+) -> CommandResult<Vec<String>> {
+    app_lock::ensure_unlocked(&app)?;
+    let partition = profiles::read_profile(&profile)?.partition();
+    let regions = normalize(regions, partition)?;
+    let mut all = settings::load(&app);
+    if regions.is_empty() {
+        all.credential_profiles.regions.remove(&profile);
+    } else {
+        all.credential_profiles
+            .regions
+            .insert(profile, regions.clone());
+    }
+    settings::save(&app, &all)?;
+    Ok(regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{self, ReplayClient};
+    use crate::partition::Partition;
+
+    #[test]
+    fn selections_stay_in_the_profile_partition() {
+        let picked = vec![
+            " eu-west-1".into(),
+            "us-east-1".into(),
+            "eu-west-1".into(),
+            String::new(),
+        ];
+        assert_eq!(
+            normalize(picked, Partition::Aws).unwrap(),
+            ["eu-west-1", "us-east-1"]
+        );
+        assert!(normalize(vec!["cn-north-1".into()], Partition::Aws).is_err());
+    }
+
+    #[tokio::test]
+    async fn opt_in_regions_are_marked() {
+        let aws = ReplayClient::load("aws/ec2.json");
+        let config = mock::sdk_config(&aws).await;
+        let regions = available(&config).await.unwrap();
+        let names: Vec<&str> = regions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["af-south-1", "eu-west-1", "us-east-1"]);
+        assert!(!regions[0].enabled);
+        assert!(regions[1].enabled && regions[2].enabled);
+        assert_eq!(aws.operations(), ["DescribeRegions"]);
+    }
+}
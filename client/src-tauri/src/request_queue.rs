@@ -0,0 +1,262 @@
+//! Limits how many webview requests reach the backend at once, so a page
+//! that fires many on mount does not swamp the sidecar. The rest wait in
+//! line, first come first served, with interactive requests ahead of
+//! background refreshes. Background requests never take every slot, so
+//! there is always room for what the user is waiting on.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tokio::sync::oneshot;
+
+use crate::error::{AppError, CommandResult};
+use crate::settings;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestPriority {
+    /// Something the user is waiting on.
+    #[default]
+    Interactive,
+    /// A refresh or prefetch nobody is waiting on.
+    Background,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct RequestQueueSettings {
+    /// Requests in flight at once.
+    pub max_concurrent: usize,
+    /// Of those, at most this many background requests.
+    pub max_background: usize,
+}
+
+impl Default for RequestQueueSettings {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            max_background: 2,
+        }
+    }
+}
+
+impl RequestQueueSettings {
+    fn validate(&self) -> Result<(), AppError> {
+        if !(1..=64).contains(&self.max_concurrent) {
+            return Err(AppError::InvalidInput(
+                "Concurrent requests must be between 1 and 64".into(),
+            ));
+        }
+        if !(1..=self.max_concurrent).contains(&self.max_background) {
+            return Err(AppError::InvalidInput(format!(
+                "Background requests must be between 1 and {}",
+                self.max_concurrent
+            )));
+        }
+        Ok(())
+    }
+}
+
+static CURRENT: RwLock<Option<RequestQueueSettings>> = RwLock::new(None);
+
+fn current() -> RequestQueueSettings {
+    CURRENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Reads the settings again, falling back to the defaults when they are
+/// invalid. Waiting requests are let through if the limit went up.
+pub(crate) fn apply(app: &AppHandle) {
+    let config = settings::load(app).request_queue;
+    let config = match config.validate() {
+        Ok(()) => config,
+        Err(err) => {
+            eprintln!("request queue settings ignored: {err}");
+            RequestQueueSettings::default()
+        }
+    };
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(config);
+    admit();
+}
+
+/// A place among the requests in flight, given back when dropped.
+pub(crate) struct Slot {
+    priority: RequestPriority,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        {
+            let mut queue = lock();
+            queue.running -= 1;
+            if self.priority == RequestPriority::Background {
+                queue.background -= 1;
+            }
+        }
+        admit();
+    }
+}
+
+/// Requests in flight and those waiting, by priority. A waiter is handed
+/// its slot; a slot sent to a waiter that gave up is dropped, and so passed
+/// on.
+#[derive(Default)]
+struct Queue {
+    running: usize,
+    background: usize,
+    interactive_waiting: VecDeque<oneshot::Sender<Slot>>,
+    background_waiting: VecDeque<oneshot::Sender<Slot>>,
+}
+
+impl Queue {
+    fn has_room(&self, priority: RequestPriority, config: &RequestQueueSettings) -> bool {
+        self.running < config.max_concurrent
+            && (priority == RequestPriority::Interactive || self.background < config.max_background)
+    }
+
+    fn start(&mut self, priority: RequestPriority) -> Slot {
+        self.running += 1;
+        if priority == RequestPriority::Background {
+            self.background += 1;
+        }
+        Slot { priority }
+    }
+
+    /// The next waiter to let in, with its slot.
+    fn next(&mut self, config: &RequestQueueSettings) -> Option<(oneshot::Sender<Slot>, Slot)> {
+        for priority in [RequestPriority::Interactive, RequestPriority::Background] {
+            if !self.has_room(priority, config) {
+                continue;
+            }
+            let waiting = match priority {
+                RequestPriority::Interactive => &mut self.interactive_waiting,
+                RequestPriority::Background => &mut self.background_waiting,
+            };
+            while let Some(waiter) = waiting.pop_front() {
+                if !waiter.is_closed() {
+                    return Some((waiter, self.start(priority)));
+                }
+            }
+        }
+        None
+    }
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue {
+    running: 0,
+    background: 0,
+    interactive_waiting: VecDeque::new(),
+    background_waiting: VecDeque::new(),
+});
+
+fn lock() -> std::sync::MutexGuard<'static, Queue> {
+    QUEUE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Hands out slots while there is room and someone waiting. Slots are sent
+/// with the lock released, as one a waiter refuses is dropped and comes back
+/// here.
+fn admit() {
+    let config = current();
+    loop {
+        let Some((waiter, slot)) = lock().next(&config) else {
+            return;
+        };
+        let _ = waiter.send(slot);
+    }
+}
+
+/// Waits for a slot. Dropping the future leaves the line.
+pub(crate) async fn acquire(priority: RequestPriority) -> Slot {
+    let granted = {
+        let mut queue = lock();
+        if queue.has_room(priority, &current()) {
+            return queue.start(priority);
+        }
+        let (waiter, granted) = oneshot::channel();
+        match priority {
+            RequestPriority::Interactive => queue.interactive_waiting.push_back(waiter),
+            RequestPriority::Background => queue.background_waiting.push_back(waiter),
+        }
+        granted
+    };
+    match granted.await {
+        Ok(slot) => slot,
+        // Only dropped unsent when the queue is gone, at exit.
+        Err(_) => lock().start(priority),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_request_queue_settings(app: AppHandle) -> RequestQueueSettings {
+    settings::load(&app).request_queue
+}
+
+#[tauri::command]
+pub fn save_request_queue_settings(
+    app: AppHandle,
+    config: RequestQueueSettings,
+) -> CommandResult<()> {
+    config.validate()?;
+    let mut all = settings::load(&app);
+    all.request_queue = config;
+    settings::save(&app, &all)?;
+    apply(&app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Slots of a queue built here are forgotten, not dropped: dropping one
+    // gives it back to the app's queue.
+    #[test]
+    fn interactive_waiters_go_first_and_background_keeps_to_its_share() {
+        let config = RequestQueueSettings {
+            max_concurrent: 2,
+            max_background: 1,
+        };
+        let mut queue = Queue::default();
+        assert!(queue.has_room(RequestPriority::Background, &config));
+        let background = queue.start(RequestPriority::Background);
+        assert!(!queue.has_room(RequestPriority::Background, &config));
+        assert!(queue.has_room(RequestPriority::Interactive, &config));
+        let interactive = queue.start(RequestPriority::Interactive);
+        assert!(!queue.has_room(RequestPriority::Interactive, &config));
+
+        let (first_bg, mut first_bg_rx) = oneshot::channel();
+        let (gone, gone_rx) = oneshot::channel();
+        let (first_ui, mut first_ui_rx) = oneshot::channel();
+        queue.background_waiting.push_back(first_bg);
+        queue.interactive_waiting.push_back(gone);
+        queue.interactive_waiting.push_back(first_ui);
+        drop(gone_rx);
+
+        // A freed slot goes to the first interactive waiter still waiting.
+        queue.running -= 1;
+        let (waiter, slot) = queue.next(&config).unwrap();
+        assert!(waiter.send(slot).is_ok());
+        std::mem::forget(first_ui_rx.try_recv().unwrap());
+        assert!(queue.next(&config).is_none());
+
+        // Background waits for a background slot even with room overall.
+        queue.running -= 1;
+        assert!(queue.next(&config).is_none());
+        queue.running -= 1;
+        queue.background -= 1;
+        let (waiter, slot) = queue.next(&config).unwrap();
+        assert!(waiter.send(slot).is_ok());
+        std::mem::forget(first_bg_rx.try_recv().unwrap());
+        std::mem::forget((background, interactive));
+    }
+}
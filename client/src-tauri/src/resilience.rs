@@ -0,0 +1,363 @@
+//! Timeouts, retries and a circuit breaker for every call the shell makes to
+//! the backend, and the timeouts and retry budget of its AWS calls.
+//!
+//! Reads and other idempotent requests that get no answer are retried with
+//! exponential backoff; other requests are sent once. After
+//! `breaker_threshold` outages in a row the breaker opens: requests fail at
+//! once for `breaker_cooldown_secs`, and the webview gets `backend-degraded`.
+//! The next request after the cooldown is let through as a trial, and the
+//! first success closes the breaker again with `backend-recovered`.
+
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::{AppError, CommandResult};
+use crate::settings;
+
+pub const DEGRADED_EVENT: &str = "backend-degraded";
+pub const RECOVERED_EVENT: &str = "backend-recovered";
+
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct ResilienceSettings {
+    /// Limit for an ordinary backend request.
+    pub request_timeout_secs: u64,
+    /// Limit for scans, scoring, execution and rollback.
+    pub long_request_timeout_secs: u64,
+    /// Retries of an idempotent backend request that got no answer.
+    pub max_retries: u32,
+    /// First retry delay; each further one doubles, with jitter.
+    pub backoff_base_ms: u64,
+    /// Outages in a row that open the breaker.
+    pub breaker_threshold: u32,
+    pub breaker_cooldown_secs: u64,
+    /// Limit for one attempt at an AWS call.
+    pub aws_attempt_timeout_secs: u64,
+    /// Attempts at an AWS call per region, including the first.
+    pub aws_max_attempts: u32,
+}
+
+impl Default for ResilienceSettings {
+    fn default() -> Self {
+        Self {
+            request_timeout_secs: 60,
+            long_request_timeout_secs: 3_600,
+            max_retries: 2,
+            backoff_base_ms: 250,
+            breaker_threshold: 5,
+            breaker_cooldown_secs: 30,
+            aws_attempt_timeout_secs: 60,
+            aws_max_attempts: 4,
+        }
+    }
+}
+
+impl ResilienceSettings {
+    fn validate(&self) -> Result<(), AppError> {
+        let ranges = [
+            ("Request timeout", self.request_timeout_secs, 1, 3_600, "s"),
+            (
+                "Long request timeout",
+                self.long_request_timeout_secs,
+                1,
+                86_400,
+                "s",
+            ),
+            ("Retries", self.max_retries.into(), 0, 10, ""),
+            ("Backoff", self.backoff_base_ms, 10, 10_000, "ms"),
+            (
+                "Breaker threshold",
+                self.breaker_threshold.into(),
+                1,
+                100,
+                "",
+            ),
+            (
+                "Breaker cooldown",
+                self.breaker_cooldown_secs,
+                1,
+                3_600,
+                "s",
+            ),
+            ("AWS timeout", self.aws_attempt_timeout_secs, 1, 3_600, "s"),
+            ("AWS attempts", self.aws_max_attempts.into(), 1, 10, ""),
+        ];
+        for (name, value, min, max, unit) in ranges {
+            if !(min..=max).contains(&value) {
+                return Err(AppError::InvalidInput(format!(
+                    "{name} must be between {min} and {max}{unit}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The limit for a request; the workflow's long POSTs get more time.
+    fn timeout(&self, method: &str, path: &str) -> Duration {
+        let long = method != "GET"
+            && path.starts_with("/optimizer/")
+            && !path.split('?').next().unwrap_or(path).ends_with("/cancel");
+        Duration::from_secs(if long {
+            self.long_request_timeout_secs
+        } else {
+            self.request_timeout_secs
+        })
+    }
+
+    pub(crate) fn aws_attempt_timeout(&self) -> Duration {
+        Duration::from_secs(self.aws_attempt_timeout_secs)
+    }
+}
+
+static CURRENT: RwLock<Option<ResilienceSettings>> = RwLock::new(None);
+/// Set by [`apply`], for the breaker's events.
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// The values in force.
+pub(crate) fn current() -> ResilienceSettings {
+    CURRENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Reads the settings again, falling back to the defaults when they are
+/// invalid, e.g. after a hand edit.
+pub(crate) fn apply(app: &AppHandle) {
+    let _ = APP.set(app.clone());
+    let config = settings::load(app).resilience;
+    let config = match config.validate() {
+        Ok(()) => config,
+        Err(err) => {
+            eprintln!("resilience settings ignored: {err}");
+            ResilienceSettings::default()
+        }
+    };
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(config);
+}
+
+/// Consecutive outages, and until when requests fail fast.
+#[derive(Default)]
+struct Breaker {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl Breaker {
+    /// How long requests still fail fast.
+    fn open_for(&self, now: Instant) -> Option<Duration> {
+        self.open_until
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|left| !left.is_zero())
+    }
+
+    /// Records an outage. Whether it opened a closed breaker.
+    fn failure(&mut self, now: Instant, threshold: u32, cooldown: Duration) -> bool {
+        self.failures = self.failures.saturating_add(1);
+        if self.failures < threshold {
+            return false;
+        }
+        self.open_until = Some(now + cooldown);
+        self.failures == threshold
+    }
+
+    /// Records an answer. Whether it closed an open breaker.
+    fn success(&mut self, threshold: u32) -> bool {
+        let was_open = self.failures >= threshold;
+        *self = Self::default();
+        was_open
+    }
+}
+
+static BREAKER: Mutex<Breaker> = Mutex::new(Breaker {
+    failures: 0,
+    open_until: None,
+});
+
+/// Payload of `backend-degraded`.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendDegraded {
+    /// Failed requests in a row.
+    pub failures: u32,
+    /// Until then requests fail at once.
+    pub retry_after_secs: u64,
+    pub reason: String,
+}
+
+/// Whether a reply means the backend is down rather than refusing the
+/// request: no answer at all, or a gateway that could not reach it.
+fn is_outage(outcome: &Result<(u16, String), AppError>) -> bool {
+    match outcome {
+        Ok((status, _)) => matches!(status, 502..=504),
+        Err(err) => matches!(err, AppError::Sidecar(_)),
+    }
+}
+
+fn idempotent(method: &str) -> bool {
+    matches!(method, "GET" | "HEAD" | "PUT" | "DELETE")
+}
+
+/// Full-jitter exponential backoff before the given 1-based retry.
+fn backoff(base: Duration, retry: u32) -> Duration {
+    let cap = base
+        .saturating_mul(2u32.saturating_pow(retry - 1))
+        .min(MAX_BACKOFF);
+    Duration::from_millis(fastrand::u64(0..=cap.as_millis() as u64))
+}
+
+fn record(outcome: &Result<(u16, String), AppError>, config: &ResilienceSettings) {
+    let mut breaker = BREAKER.lock().unwrap_or_else(|e| e.into_inner());
+    if !is_outage(outcome) {
+        if breaker.success(config.breaker_threshold) {
+            if let Some(app) = APP.get() {
+                let _ = app.emit(RECOVERED_EVENT, ());
+            }
+        }
+        return;
+    }
+    let cooldown = Duration::from_secs(config.breaker_cooldown_secs);
+    if breaker.failure(Instant::now(), config.breaker_threshold, cooldown) {
+        let reason = match outcome {
+            Ok((status, _)) => format!("The backend answered HTTP {status}"),
+            Err(err) => err.to_string(),
+        };
+        eprintln!(
+            "backend degraded after {} failures: {reason}",
+            breaker.failures
+        );
+        if let Some(app) = APP.get() {
+            let _ = app.emit(
+                DEGRADED_EVENT,
+                BackendDegraded {
+                    failures: breaker.failures,
+                    retry_after_secs: config.breaker_cooldown_secs,
+                    reason,
+                },
+            );
+        }
+    }
+}
+
+/// Closes the breaker, e.g. once a restarted sidecar is ready.
+pub(crate) fn reset() {
+    *BREAKER.lock().unwrap_or_else(|e| e.into_inner()) = Breaker::default();
+}
+
+/// Sends a backend request under the policy. `send` makes one attempt
+/// within the timeout it is given.
+pub(crate) fn call(
+    method: &str,
+    path: &str,
+    mut send: impl FnMut(Duration) -> Result<(u16, String), AppError>,
+) -> Result<(u16, String), AppError> {
+    let config = current();
+    let open_for = BREAKER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .open_for(Instant::now());
+    if let Some(left) = open_for {
+        return Err(AppError::Sidecar(format!(
+            "The backend is not answering; trying again in {}s",
+            left.as_secs().max(1)
+        )));
+    }
+    let retries = if idempotent(method) {
+        config.max_retries
+    } else {
+        0
+    };
+    let timeout = config.timeout(method, path);
+    let mut retry = 0;
+    loop {
+        let outcome = send(timeout);
+        record(&outcome, &config);
+        let open = BREAKER
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .open_for(Instant::now())
+            .is_some();
+        if !is_outage(&outcome) || retry >= retries || open {
+            return outcome;
+        }
+        retry += 1;
+        std::thread::sleep(backoff(
+            Duration::from_millis(config.backoff_base_ms),
+            retry,
+        ));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_resilience_settings(app: AppHandle) -> ResilienceSettings {
+    settings::load(&app).resilience
+}
+
+#[tauri::command]
+pub fn save_resilience_settings(app: AppHandle, config: ResilienceSettings) -> CommandResult<()> {
+    config.validate()?;
+    let mut all = settings::load(&app);
+    all.resilience = config;
+    settings::save(&app, &all)?;
+    apply(&app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_outages_of_idempotent_requests_are_retried() {
+        let unreachable = || Err(AppError::Sidecar("Backend unreachable".into()));
+        assert!(is_outage(&unreachable()));
+        assert!(is_outage(&Ok((503, String::new()))));
+        assert!(!is_outage(&Ok((500, String::new()))));
+        assert!(!is_outage(&Ok((404, String::new()))));
+        assert!(!is_outage(&Err(AppError::InvalidInput("bad".into()))));
+        assert!(idempotent("GET") && idempotent("DELETE"));
+        assert!(!idempotent("POST"));
+    }
+
+    #[test]
+    fn breaker_opens_after_the_threshold_and_closes_on_success() {
+        let now = Instant::now();
+        let cooldown = Duration::from_secs(30);
+        let mut breaker = Breaker::default();
+        assert!(!breaker.failure(now, 3, cooldown));
+        assert!(!breaker.failure(now, 3, cooldown));
+        assert_eq!(breaker.open_for(now), None);
+        assert!(breaker.failure(now, 3, cooldown));
+        assert_eq!(breaker.open_for(now), Some(cooldown));
+        // After the cooldown a trial goes through; failing it reopens
+        // without reporting the outage again.
+        let later = now + cooldown;
+        assert_eq!(breaker.open_for(later), None);
+        assert!(!breaker.failure(later, 3, cooldown));
+        assert!(breaker.open_for(later).is_some());
+        assert!(breaker.success(3));
+        assert_eq!(breaker.open_for(later), None);
+        assert!(!breaker.success(3));
+    }
+
+    #[test]
+    fn workflow_posts_get_the_long_timeout() {
+        let config = ResilienceSettings::default();
+        let long = Duration::from_secs(config.long_request_timeout_secs);
+        let short = Duration::from_secs(config.request_timeout_secs);
+        assert_eq!(config.timeout("POST", "/optimizer/scan"), long);
+        assert_eq!(config.timeout("POST", "/optimizer/execute"), long);
+        assert_eq!(config.timeout("POST", "/optimizer/scan/a/cancel"), short);
+        assert_eq!(config.timeout("GET", "/optimizer/runs"), short);
+        assert!(backoff(Duration::from_millis(250), 20) <= MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,356 @@
+//! Cache of the backend's GET replies to the webview, so going back to a page
+//! is instant. Replies are kept by request, in memory and in
+//! `app_data/response_cache.json`. A fresh one is served without asking the
+//! backend; a stale one with an ETag is revalidated with `If-None-Match`, and
+//! a 304 keeps it fresh for another TTL. TTLs are set per path prefix; paths
+//! without one are not cached. A change that goes through, such as a scan,
+//! clears the cache.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::{remote_backend, settings};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct ResponseCacheSettings {
+    pub enabled: bool,
+    /// Seconds a reply stays fresh, by path prefix; the longest matching
+    /// prefix applies.
+    pub ttl_secs: BTreeMap<String, u64>,
+    /// Replies kept; the oldest go first.
+    pub max_entries: usize,
+}
+
+impl Default for ResponseCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: BTreeMap::from([("/optimizer/runs".into(), 300), ("/version".into(), 3_600)]),
+            max_entries: 500,
+        }
+    }
+}
+
+impl ResponseCacheSettings {
+    fn validate(&self) -> Result<(), AppError> {
+        if let Some(prefix) = self.ttl_secs.keys().find(|prefix| !prefix.starts_with('/')) {
+            return Err(AppError::InvalidInput(format!(
+                "Cached path {prefix:?} must start with /"
+            )));
+        }
+        if !(1..=10_000).contains(&self.max_entries) {
+            return Err(AppError::InvalidInput(
+                "Cached replies must be between 1 and 10000".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// How long a reply to `path` stays fresh, if it is cached at all.
+    fn ttl(&self, path: &str) -> Option<u64> {
+        let route = path.split('?').next().unwrap_or(path);
+        self.ttl_secs
+            .iter()
+            .filter(|(prefix, _)| {
+                route
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, ttl)| *ttl)
+            .filter(|ttl| *ttl > 0)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedReply {
+    fetched_at: i64,
+    etag: Option<String>,
+    body: String,
+}
+
+/// Cached replies, persisted as JSON at `path` and loaded on first use.
+struct ResponseCache {
+    path: PathBuf,
+    config: ResponseCacheSettings,
+    replies: Mutex<Option<HashMap<String, CachedReply>>>,
+}
+
+impl ResponseCache {
+    fn new(path: PathBuf, config: ResponseCacheSettings) -> Self {
+        Self {
+            path,
+            config,
+            replies: Mutex::new(None),
+        }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut HashMap<String, CachedReply>) -> T) -> T {
+        let mut guard = self.replies.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = guard.get_or_insert_with(|| {
+            std::fs::read_to_string(&self.path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default()
+        });
+        f(cache)
+    }
+
+    fn put(&self, key: String, reply: CachedReply) {
+        self.with(|cache| {
+            cache.insert(key, reply);
+            while cache.len() > self.config.max_entries {
+                let oldest = cache
+                    .iter()
+                    .min_by_key(|(_, reply)| reply.fetched_at)
+                    .map(|(key, _)| key.clone());
+                match oldest {
+                    Some(key) => cache.remove(&key),
+                    None => break,
+                };
+            }
+            let _ = self.write(cache);
+        });
+    }
+
+    fn clear(&self) {
+        self.with(|cache| cache.clear());
+        let _ = std::fs::remove_file(&self.path);
+    }
+
+    fn write(&self, cache: &HashMap<String, CachedReply>) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(cache)?;
+        std::fs::write(&self.path, json)
+    }
+
+    /// The body kept under `key` while it is younger than `ttl`.
+    fn fresh(&self, key: &str, ttl: u64, now: i64) -> Option<String> {
+        self.with(|cache| {
+            cache
+                .get(key)
+                .filter(|reply| now - reply.fetched_at < ttl as i64)
+                .map(|reply| reply.body.clone())
+        })
+    }
+
+    /// Answers a GET of `path` from the cache or with `send`, which makes
+    /// the request with the `If-None-Match` value it is given.
+    fn get(
+        &self,
+        key: String,
+        path: &str,
+        now: i64,
+        send: impl FnOnce(Option<&str>) -> Result<(u16, String, Option<String>), AppError>,
+    ) -> Result<(u16, String), AppError> {
+        let Some(ttl) = self.config.ttl(path) else {
+            return send(None).map(|(status, body, _)| (status, body));
+        };
+        if let Some(body) = self.fresh(&key, ttl, now) {
+            return Ok((200, body));
+        }
+        let cached = self.with(|cache| cache.get(&key).cloned());
+        let etag = cached.as_ref().and_then(|reply| reply.etag.as_deref());
+        let (status, body, etag) = send(etag)?;
+        let reply = match (status, cached) {
+            (304, Some(cached)) => CachedReply {
+                fetched_at: now,
+                etag: etag.or(cached.etag),
+                body: cached.body,
+            },
+            (200, _) => CachedReply {
+                fetched_at: now,
+                etag,
+                body,
+            },
+            _ => return Ok((status, body)),
+        };
+        let body = reply.body.clone();
+        self.put(key, reply);
+        Ok((200, body))
+    }
+}
+
+static CURRENT: RwLock<Option<Arc<ResponseCache>>> = RwLock::new(None);
+
+fn active() -> Option<Arc<ResponseCache>> {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn cache_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("response_cache.json")
+}
+
+/// Reads the settings again, falling back to the defaults when they are
+/// invalid. Turning the cache off drops what it holds.
+pub(crate) fn apply(app: &AppHandle) {
+    let config = settings::load(app).response_cache;
+    let config = match config.validate() {
+        Ok(()) => config,
+        Err(err) => {
+            eprintln!("response cache settings ignored: {err}");
+            ResponseCacheSettings::default()
+        }
+    };
+    let cache = ResponseCache::new(cache_path(app), config);
+    if !cache.config.enabled {
+        cache.clear();
+    }
+    let cache = cache.config.enabled.then(|| Arc::new(cache));
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = cache;
+}
+
+/// Answers a webview GET of `path` from the cache where it applies, else
+/// with `send`. Replies are kept by backend too, so a remote backend never
+/// gets the sidecar's.
+pub(crate) fn get(
+    path: &str,
+    send: impl FnOnce(Option<&str>) -> Result<(u16, String, Option<String>), AppError>,
+) -> Result<(u16, String), AppError> {
+    let Some(cache) = active() else {
+        return send(None).map(|(status, body, _)| (status, body));
+    };
+    cache.get(key(path), path, chrono::Utc::now().timestamp(), send)
+}
+
+/// The cached body of a GET of `path`, if still fresh, so it can be served
+/// without waiting for a place in the request queue.
+pub(crate) fn fresh(path: &str) -> Option<String> {
+    let cache = active()?;
+    let ttl = cache.config.ttl(path)?;
+    cache.fresh(&key(path), ttl, chrono::Utc::now().timestamp())
+}
+
+fn key(path: &str) -> String {
+    let origin = remote_backend::active()
+        .map(|remote| remote.base_url().to_string())
+        .unwrap_or_else(|| "sidecar".into());
+    format!("GET {origin}{path}")
+}
+
+/// Drops every cached reply.
+pub(crate) fn clear() {
+    if let Some(cache) = active() {
+        cache.clear();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_response_cache_settings(app: AppHandle) -> ResponseCacheSettings {
+    settings::load(&app).response_cache
+}
+
+#[tauri::command]
+pub fn save_response_cache_settings(
+    app: AppHandle,
+    config: ResponseCacheSettings,
+) -> CommandResult<()> {
+    config.validate()?;
+    let mut all = settings::load(&app);
+    all.response_cache = config;
+    settings::save(&app, &all)?;
+    apply(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_response_cache() {
+    clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(max_entries: usize) -> ResponseCache {
+        ResponseCache::new(
+            std::env::temp_dir().join(format!("response-cache-{}.json", uuid::Uuid::new_v4())),
+            ResponseCacheSettings {
+                max_entries,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn reply(
+        status: u16,
+        body: &str,
+        etag: Option<&str>,
+    ) -> Result<(u16, String, Option<String>), AppError> {
+        Ok((status, body.into(), etag.map(str::to_owned)))
+    }
+
+    #[test]
+    fn the_longest_prefix_sets_the_ttl() {
+        let mut config = ResponseCacheSettings::default();
+        config.ttl_secs.insert("/optimizer/runs/a/audit".into(), 0);
+        assert_eq!(config.ttl("/optimizer/runs"), Some(300));
+        assert_eq!(config.ttl("/optimizer/runs/a?x=1"), Some(300));
+        assert_eq!(config.ttl("/optimizer/runs/a/audit"), None);
+        assert_eq!(config.ttl("/optimizer/runsx"), None);
+        assert_eq!(config.ttl("/health"), None);
+    }
+
+    #[test]
+    fn stale_replies_are_revalidated_with_their_etag() {
+        let cache = cache(10);
+        let path = "/optimizer/runs";
+        let key = || format!("GET sidecar{path}");
+        let first = cache.get(key(), path, 0, |etag| {
+            assert_eq!(etag, None);
+            reply(200, "[1]", Some("W/\"a\""))
+        });
+        assert_eq!(first.unwrap(), (200, "[1]".into()));
+
+        let fresh = cache.get(key(), path, 299, |_| {
+            panic!("fresh replies are not fetched")
+        });
+        assert_eq!(fresh.unwrap(), (200, "[1]".into()));
+
+        let revalidated = cache.get(key(), path, 300, |etag| {
+            assert_eq!(etag, Some("W/\"a\""));
+            reply(304, "", None)
+        });
+        assert_eq!(revalidated.unwrap(), (200, "[1]".into()));
+        let fresh = cache.get(key(), path, 599, |_| panic!("a 304 renews the TTL"));
+        assert_eq!(fresh.unwrap(), (200, "[1]".into()));
+
+        let failed = cache.get(key(), path, 900, |_| reply(500, "boom", None));
+        assert_eq!(failed.unwrap(), (500, "boom".into()));
+        let reloaded = ResponseCache::new(cache.path.clone(), cache.config.clone());
+        let kept = reloaded.get(key(), path, 901, |_| reply(304, "", None));
+        assert_eq!(kept.unwrap(), (200, "[1]".into()));
+        cache.clear();
+    }
+
+    #[test]
+    fn the_oldest_replies_are_evicted() {
+        let cache = cache(2);
+        for (at, path) in ["/version", "/optimizer/runs", "/optimizer/runs/a"]
+            .into_iter()
+            .enumerate()
+        {
+            let _ = cache.get(path.into(), path, at as i64, |_| reply(200, "{}", None));
+        }
+        cache.with(|replies| {
+            assert!(!replies.contains_key("/version"));
+            assert_eq!(replies.len(), 2);
+        });
+        cache.clear();
+    }
+}
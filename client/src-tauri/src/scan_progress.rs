@@ -0,0 +1,192 @@
+//! Progress of sidecar scans. A scan is a single blocking request, so the
+//! shell tags it with a `scan_id` and polls the sidecar's progress route while
+//! it runs, re-emitting each snapshot (with an ETA) as `scan-progress`.
+//! Cancelling the scan's task asks the sidecar to stop at its next bucket or
+//! page; the sidecar then records no run.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::backend::{self, ScanResponse};
+use crate::error::AppError;
+use crate::tasks::CancelToken;
+use crate::{multi_account, AwsCredentials};
+
+const PROGRESS_EVENT: &str = "scan-progress";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How often the watcher looks at the cancel token between polls.
+const CANCEL_CHECK: Duration = Duration::from_millis(200);
+/// Below this the rate is too noisy to extrapolate from.
+const MIN_PERCENT_FOR_ETA: f64 = 2.0;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RegionProgress {
+    pub bucket_count: u32,
+    pub buckets_done: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScanProgress {
+    pub scan_id: String,
+    /// `resolving_buckets`, `scanning_objects`, `checking_lifecycle`,
+    /// `checking_multipart` or `completed`.
+    pub stage: String,
+    pub bucket: Option<String>,
+    pub region: Option<String>,
+    pub bucket_count: u32,
+    pub buckets_done: u32,
+    pub percent: f64,
+    #[serde(default)]
+    pub regions: BTreeMap<String, RegionProgress>,
+    /// Filled in by the shell.
+    #[serde(default)]
+    pub eta_secs: Option<u64>,
+}
+
+fn eta(elapsed: Duration, percent: f64) -> Option<u64> {
+    (MIN_PERCENT_FOR_ETA..100.0)
+        .contains(&percent)
+        .then(|| (elapsed.as_secs_f64() * (100.0 - percent) / percent).round() as u64)
+}
+
+fn poll(scan_id: &str, started: Instant) -> Option<ScanProgress> {
+    let mut progress: ScanProgress =
+        backend::get_json(&format!("/optimizer/scan/{scan_id}/progress")).ok()?;
+    progress.eta_secs = eta(started.elapsed(), progress.percent);
+    Some(progress)
+}
+
+/// Posts a scan to the sidecar and emits its progress until it returns.
+/// `body` is the scan request; its `scan_id` is assigned here.
+pub fn scan(
+    app: &AppHandle,
+    cancel: &CancelToken,
+    body: serde_json::Value,
+) -> Result<ScanResponse, AppError> {
+    let app = app.clone();
+    scan_with(
+        move |progress| {
+            let _ = app.emit(PROGRESS_EVENT, progress);
+        },
+        cancel,
+        body,
+        None,
+    )
+}
+
+/// [`scan`] with progress handed to `emit` instead of the frontend, scanning
+/// with `credentials` instead of the sidecar's own keys when given.
+pub(crate) fn scan_with(
+    emit: impl Fn(ScanProgress) + Clone + Send + 'static,
+    cancel: &CancelToken,
+    mut body: serde_json::Value,
+    credentials: Option<&AwsCredentials>,
+) -> Result<ScanResponse, AppError> {
+    cancel.check()?;
+    let scan_id = uuid::Uuid::new_v4().to_string();
+    body["scan_id"] = serde_json::json!(scan_id);
+    let body = multi_account::request_body(&body, credentials)?;
+
+    let started = Instant::now();
+    let finished = Arc::new(AtomicBool::new(false));
+    let watcher = {
+        let (emit, scan_id, finished) = (emit.clone(), scan_id.clone(), finished.clone());
+        let cancel = cancel.clone();
+        std::thread::spawn(move || {
+            let mut last_poll = Instant::now();
+            let mut cancel_sent = false;
+            while !finished.load(Ordering::Relaxed) {
+                std::thread::sleep(CANCEL_CHECK);
+                if cancel.is_cancelled() && !cancel_sent {
+                    cancel_sent = true;
+                    let path = format!("/optimizer/scan/{scan_id}/cancel");
+                    if let Err(err) = backend::forward("POST", &path, None) {
+                        eprintln!("could not cancel scan {scan_id}: {err}");
+                    }
+                }
+                if last_poll.elapsed() >= POLL_INTERVAL {
+                    last_poll = Instant::now();
+                    if let Some(progress) = poll(&scan_id, started) {
+                        emit(progress);
+                    }
+                }
+            }
+        })
+    };
+
+    let result = backend::post_raw("/optimizer/scan", &body);
+    finished.store(true, Ordering::Relaxed);
+    let _ = watcher.join();
+    if cancel.is_cancelled() && result.is_err() {
+        return Err(AppError::Cancelled);
+    }
+    if result.is_ok() {
+        if let Some(progress) = poll(&scan_id, started) {
+            emit(progress);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::mock;
+
+    #[test]
+    fn scan_tags_the_request_and_reports_final_progress() {
+        let sidecar = mock::install_sidecar("sidecar/scan.json");
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let sink = emitted.clone();
+        let response = scan_with(
+            move |progress| sink.lock().unwrap().push(progress),
+            &CancelToken::default(),
+            serde_json::json!({ "include_buckets": ["logs-archive"] }),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(response.run_id, "run-fixture-1");
+        assert_eq!(response.errors.len(), 1);
+        assert_eq!(response.errors[0].operation, "ListMultipartUploads");
+
+        let requests = sidecar.sidecar.requests();
+        let post = requests
+            .iter()
+            .find(|r| r.method == "POST")
+            .expect("scan posted");
+        let body: serde_json::Value = serde_json::from_str(post.body.as_deref().unwrap()).unwrap();
+        let scan_id = body["scan_id"].as_str().unwrap();
+        assert_eq!(body["include_buckets"][0], "logs-archive");
+        assert!(requests
+            .iter()
+            .any(|r| r.path == format!("/optimizer/scan/{scan_id}/progress")));
+
+        let emitted = emitted.lock().unwrap();
+        let last = emitted.last().expect("final progress emitted");
+        assert_eq!(last.stage, "completed");
+        assert_eq!(last.eta_secs, None);
+    }
+
+    #[test]
+    fn sidecar_failures_keep_their_kind() {
+        let _sidecar = mock::install_sidecar("sidecar/runs.json");
+        let err =
+            scan_with(|_| {}, &CancelToken::default(), serde_json::json!({}), None).unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)), "{err:?}");
+    }
+
+    #[test]
+    fn eta_extrapolates_from_the_elapsed_rate() {
+        assert_eq!(eta(Duration::from_secs(30), 25.0), Some(90));
+        assert_eq!(eta(Duration::from_secs(30), 1.0), None);
+        assert_eq!(eta(Duration::from_secs(30), 100.0), None);
+    }
+}
@@ -0,0 +1,368 @@
+//! Recurring scans and the monthly cost-review reminder. Schedules are in
+//! local time; a background thread queues due scans as jobs (see
+//! [`crate::jobs`]), which run them through the sidecar. Occurrences missed
+//! while the app was closed are not caught up, but a scan cut off by a
+//! shutdown resumes with the job queue.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::exporters::{archive, well_architected};
+use crate::jobs::{self, JobKind};
+use crate::tasks::CancelToken;
+use crate::{backend, scan_progress, settings};
+
+const TICK: Duration = Duration::from_secs(60);
+/// A due occurrence older than this (e.g. after sleep) is skipped, not run late.
+const MAX_LATENESS_MINUTES: i64 = 60;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Frequency {
+    Daily,
+    /// `weekday`: 0 = Monday … 6 = Sunday.
+    Weekly {
+        weekday: u32,
+    },
+    /// `day`: 1–28 so every month has the day.
+    Monthly {
+        day: u32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScanSchedule {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub frequency: Frequency,
+    pub hour: u32,
+    pub minute: u32,
+    #[serde(default)]
+    pub include_buckets: Vec<String>,
+    #[serde(default)]
+    pub exclude_buckets: Vec<String>,
+    #[serde(default = "default_max_objects")]
+    pub max_objects_per_bucket: u32,
+    /// Render the run's report after the scan and upload it to the report
+    /// archive (when archiving is configured).
+    #[serde(default)]
+    pub archive_report: bool,
+}
+
+fn default_max_objects() -> u32 {
+    1000
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReviewReminder {
+    /// 1–28.
+    pub day_of_month: u32,
+    pub hour: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ScheduleSettings {
+    pub scans: Vec<ScanSchedule>,
+    pub review_reminder: Option<ReviewReminder>,
+}
+
+// ---------------------------------------------------------------------------
+// Occurrence math
+// ---------------------------------------------------------------------------
+
+impl Frequency {
+    pub fn matches(&self, date: chrono::NaiveDate) -> bool {
+        match self {
+            Frequency::Daily => true,
+            Frequency::Weekly { weekday } => date.weekday().num_days_from_monday() == *weekday,
+            Frequency::Monthly { day } => date.day() == *day,
+        }
+    }
+}
+
+impl ScanSchedule {
+    pub fn time(&self) -> NaiveTime {
+        NaiveTime::from_hms_opt(self.hour, self.minute, 0).unwrap_or(NaiveTime::MIN)
+    }
+
+    /// Latest occurrence at or before `now`, looking back at most a month.
+    pub fn previous_occurrence(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        (0..=31)
+            .map(|back| now.date() - ChronoDuration::days(back))
+            .filter(|date| self.frequency.matches(*date))
+            .map(|date| date.and_time(self.time()))
+            .find(|at| *at <= now)
+    }
+}
+
+fn validate(cfg: &ScheduleSettings) -> Result<(), String> {
+    for schedule in &cfg.scans {
+        if schedule.hour > 23 || schedule.minute > 59 {
+            return Err(format!("Schedule '{}' has an invalid time", schedule.name));
+        }
+        match schedule.frequency {
+            Frequency::Weekly { weekday } if weekday > 6 => {
+                return Err(format!(
+                    "Schedule '{}' has an invalid weekday",
+                    schedule.name
+                ));
+            }
+            Frequency::Monthly { day } if !(1..=28).contains(&day) => {
+                return Err(format!(
+                    "Schedule '{}' must use a day from 1 to 28",
+                    schedule.name
+                ));
+            }
+            _ => {}
+        }
+    }
+    if let Some(reminder) = &cfg.review_reminder {
+        if !(1..=28).contains(&reminder.day_of_month) || reminder.hour > 23 {
+            return Err(
+                "Review reminder must use a day from 1 to 28 and an hour from 0 to 23".into(),
+            );
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Runner
+// ---------------------------------------------------------------------------
+
+fn last_runs_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("schedule_runs.json")
+}
+
+fn read_last_runs(app: &AppHandle) -> HashMap<String, NaiveDateTime> {
+    std::fs::read_to_string(last_runs_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_last_runs(app: &AppHandle, runs: &HashMap<String, NaiveDateTime>) {
+    let path = last_runs_path(app);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(runs) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// The occurrence of `schedule` to run now, if any: the latest one that has
+/// not run yet and is not too late to start.
+fn due(
+    schedule: &ScanSchedule,
+    now: NaiveDateTime,
+    last_run: Option<NaiveDateTime>,
+) -> Option<NaiveDateTime> {
+    let due = schedule.previous_occurrence(now)?;
+    let already_ran = last_run.is_some_and(|last| last >= due);
+    (!already_ran && now - due <= ChronoDuration::minutes(MAX_LATENESS_MINUTES)).then_some(due)
+}
+
+fn scan_request(schedule: &ScanSchedule) -> serde_json::Value {
+    json!({
+        "include_buckets": schedule.include_buckets,
+        "exclude_buckets": schedule.exclude_buckets,
+        "max_objects_per_bucket": schedule.max_objects_per_bucket,
+    })
+}
+
+/// Starts a scheduled scan through the sidecar.
+pub fn trigger_scan(
+    app: &AppHandle,
+    schedule: &ScanSchedule,
+    cancel: &CancelToken,
+) -> Result<backend::ScanResponse, AppError> {
+    scan_progress::scan(app, cancel, scan_request(schedule))
+}
+
+/// Writes the run's Well-Architected report under `app_data/reports` and
+/// uploads it to the report archive.
+pub(crate) fn archive_report(app: &AppHandle, run_id: &str, cancel: &CancelToken) {
+    let Ok(dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let path = dir.join("reports").join(format!("cost-report-{run_id}.md"));
+    let result = well_architected::export_blocking(run_id, &path.to_string_lossy(), cancel)
+        .map(|summary| tauri::async_runtime::block_on(archive::archive(app, summary, cancel)));
+    match result {
+        Ok(summary) => {
+            if let Some(err) = summary.upload_error {
+                eprintln!("report upload for run {run_id} failed: {err}");
+            }
+        }
+        Err(err) => eprintln!("report for run {run_id} not written: {err}"),
+    }
+}
+
+fn tick(app: &AppHandle, last_runs: &mut HashMap<String, NaiveDateTime>) {
+    let now = chrono::Local::now().naive_local();
+    for schedule in settings::load(app)
+        .schedules
+        .scans
+        .iter()
+        .filter(|s| s.enabled)
+    {
+        let Some(due) = due(schedule, now, last_runs.get(&schedule.id).copied()) else {
+            continue;
+        };
+        // Record before running so a slow or failing scan is not retried every tick.
+        last_runs.insert(schedule.id.clone(), due);
+        write_last_runs(app, last_runs);
+        // The job queue runs it, cancellable as task `schedule:<id>`.
+        let kind = JobKind::ScheduledScan {
+            schedule_id: schedule.id.clone(),
+        };
+        if let Err(err) = jobs::enqueue(app, kind) {
+            eprintln!("scheduled scan '{}' not queued: {err}", schedule.name);
+        }
+    }
+}
+
+pub fn spawn_scheduler(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_runs = read_last_runs(&app);
+        loop {
+            tick(&app, &mut last_runs);
+            std::thread::sleep(TICK);
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_schedule_settings(app: AppHandle) -> ScheduleSettings {
+    settings::load(&app).schedules
+}
+
+/// Replaces all schedules. Schedules without an `id` are assigned one.
+#[tauri::command]
+pub fn save_schedule_settings(
+    app: AppHandle,
+    mut config: ScheduleSettings,
+) -> CommandResult<ScheduleSettings> {
+    validate(&config)?;
+    for schedule in config.scans.iter_mut().filter(|s| s.id.is_empty()) {
+        schedule.id = uuid::Uuid::new_v4().to_string();
+    }
+    let mut all = settings::load(&app);
+    all.schedules = config.clone();
+    settings::save(&app, &all)?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(frequency: Frequency, hour: u32, minute: u32) -> ScanSchedule {
+        ScanSchedule {
+            id: "nightly".into(),
+            name: "Nightly".into(),
+            enabled: true,
+            frequency,
+            hour,
+            minute,
+            include_buckets: vec!["logs-archive".into()],
+            exclude_buckets: Vec::new(),
+            max_objects_per_bucket: default_max_objects(),
+            archive_report: false,
+        }
+    }
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn daily_scan_is_due_once_per_occurrence() {
+        let nightly = schedule(Frequency::Daily, 2, 30);
+        let now = at("2025-03-04 02:45");
+        assert_eq!(due(&nightly, now, None), Some(at("2025-03-04 02:30")));
+        assert_eq!(due(&nightly, now, Some(at("2025-03-04 02:30"))), None);
+        assert_eq!(
+            due(&nightly, now, Some(at("2025-03-03 02:30"))),
+            Some(at("2025-03-04 02:30"))
+        );
+    }
+
+    #[test]
+    fn late_occurrences_are_skipped() {
+        let nightly = schedule(Frequency::Daily, 2, 30);
+        assert_eq!(
+            due(&nightly, at("2025-03-04 03:30"), None),
+            Some(at("2025-03-04 02:30"))
+        );
+        assert_eq!(due(&nightly, at("2025-03-04 03:31"), None), None);
+    }
+
+    #[test]
+    fn weekly_and_monthly_scans_fall_on_their_day() {
+        // 2025-03-03 is a Monday.
+        let weekly = schedule(Frequency::Weekly { weekday: 0 }, 9, 0);
+        assert_eq!(
+            due(&weekly, at("2025-03-03 09:10"), None),
+            Some(at("2025-03-03 09:00"))
+        );
+        assert_eq!(due(&weekly, at("2025-03-04 09:10"), None), None);
+
+        let monthly = schedule(Frequency::Monthly { day: 15 }, 6, 0);
+        assert_eq!(
+            monthly.previous_occurrence(at("2025-03-10 12:00")),
+            Some(at("2025-02-15 06:00"))
+        );
+        assert_eq!(
+            due(&monthly, at("2025-03-15 06:05"), None),
+            Some(at("2025-03-15 06:00"))
+        );
+    }
+
+    #[test]
+    fn invalid_schedules_are_rejected() {
+        let cfg = |frequency| ScheduleSettings {
+            scans: vec![schedule(frequency, 2, 30)],
+            review_reminder: None,
+        };
+        assert!(validate(&cfg(Frequency::Daily)).is_ok());
+        assert!(validate(&cfg(Frequency::Weekly { weekday: 7 })).is_err());
+        assert!(validate(&cfg(Frequency::Monthly { day: 29 })).is_err());
+    }
+
+    #[test]
+    fn scheduled_scan_runs_through_the_sidecar() {
+        let sidecar = crate::mock::install_sidecar("sidecar/scan.json");
+        let nightly = schedule(Frequency::Daily, 2, 30);
+        let response = scan_progress::scan_with(
+            |_| {},
+            &CancelToken::default(),
+            scan_request(&nightly),
+            None,
+        )
+        .unwrap();
+        assert_eq!(response.run_id, "run-fixture-1");
+
+        let requests = sidecar.sidecar.requests();
+        let post = requests.iter().find(|r| r.method == "POST").unwrap();
+        let body: serde_json::Value = serde_json::from_str(post.body.as_deref().unwrap()).unwrap();
+        assert_eq!(body["include_buckets"], json!(["logs-archive"]));
+        assert_eq!(body["max_objects_per_bucket"], 1000);
+    }
+}
@@ -0,0 +1,584 @@
+//! Where secrets live: the OS keychain, or an encrypted file where no keychain
+//! is available (headless Linux, minimal desktops). The file holds every
+//! secret as one JSON map sealed with ChaCha20-Poly1305 under a key derived
+//! from the user's passphrase (PBKDF2-HMAC-SHA256). The passphrase is never
+//! stored; until it is entered, the file's secrets read as locked. Call sites
+//! use [`SecretEntry`] exactly like a `keyring::Entry`.
+//!
+//! Switching between the keychain and the file moves every secret across;
+//! the file has to be unlocked for that. The keychain cannot be listed, so
+//! the accounts moved out of it are the app's known ones.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use ring::aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use secrecy::zeroize::{Zeroize, Zeroizing};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::{
+    app_lock, datadog, github, google_sheets, identity_center, local_api, pagerduty, profiles,
+    read_credentials, reload_sidecar, remote_backend, servicenow, settings, sso, webhooks,
+};
+
+const SERVICE: &str = "aws-cost-optimizer";
+const FILE_NAME: &str = "secrets.enc";
+const FILE_VERSION: u32 = 1;
+/// OWASP's 2023 recommendation for PBKDF2-HMAC-SHA256.
+pub(crate) const ITERATIONS: u32 = 600_000;
+const MIN_PASSPHRASE_LEN: usize = 8;
+const AAD: &[u8] = b"aws-cost-optimizer secrets v1";
+/// Account used to find out whether the keychain works at all.
+const PROBE_ACCOUNT: &str = "keychain-probe";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackend {
+    /// The keychain, or the encrypted file if the keychain is unavailable.
+    #[default]
+    Auto,
+    Keychain,
+    EncryptedFile,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct SecretStoreSettings {
+    /// Secrets move to the new store when this changes.
+    pub backend: SecretBackend,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SecretStoreStatus {
+    pub backend: SecretBackend,
+    /// Whether secrets currently go to the encrypted file.
+    pub using_file: bool,
+    pub keychain_available: bool,
+    pub file_exists: bool,
+    pub unlocked: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SealedFile {
+    version: u32,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+struct Unlocked {
+    key: [u8; 32],
+    salt: Vec<u8>,
+    iterations: u32,
+    secrets: BTreeMap<String, String>,
+}
+
+impl Drop for Unlocked {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.secrets.values_mut().for_each(Zeroize::zeroize);
+    }
+}
+
+struct Store {
+    path: PathBuf,
+    using_file: bool,
+    keychain_available: bool,
+    unlocked: Option<Unlocked>,
+}
+
+/// `None` until [`init`] runs (and in tests), meaning the keychain.
+static STORE: Mutex<Option<Store>> = Mutex::new(None);
+
+// ---------------------------------------------------------------------------
+// Sealing
+// ---------------------------------------------------------------------------
+
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(iterations.max(1)).expect("non-zero"),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+pub(crate) fn aead_key(key: &[u8; 32]) -> LessSafeKey {
+    LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).expect("32-byte key"))
+}
+
+pub(crate) fn random<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "No system randomness available".to_string())?;
+    Ok(bytes)
+}
+
+fn seal(unlocked: &Unlocked) -> Result<SealedFile, String> {
+    let nonce = random::<{ aead::NONCE_LEN }>()?;
+    let mut buf = serde_json::to_vec(&unlocked.secrets).map_err(|e| e.to_string())?;
+    aead_key(&unlocked.key)
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(AAD),
+            &mut buf,
+        )
+        .map_err(|_| "Could not encrypt secrets".to_string())?;
+    Ok(SealedFile {
+        version: FILE_VERSION,
+        iterations: unlocked.iterations,
+        salt: BASE64.encode(&unlocked.salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(buf),
+    })
+}
+
+/// Decrypts `sealed`, refusing one derived with fewer than
+/// `min_iterations`: a file edited to ask for fewer would make the
+/// passphrase cheap to guess from the next seal.
+fn open(sealed: &SealedFile, passphrase: &str, min_iterations: u32) -> Result<Unlocked, AppError> {
+    if sealed.version != FILE_VERSION {
+        return Err(AppError::Credentials(format!(
+            "Unsupported secrets file version {}",
+            sealed.version
+        )));
+    }
+    if sealed.iterations < min_iterations {
+        return Err(AppError::Credentials(format!(
+            "Damaged secrets file: {} key derivation rounds, fewer than {min_iterations}",
+            sealed.iterations
+        )));
+    }
+    let decode = |field: &str| {
+        BASE64
+            .decode(field)
+            .map_err(|e| AppError::Credentials(format!("Damaged secrets file: {e}")))
+    };
+    let salt = decode(&sealed.salt)?;
+    let nonce = Nonce::try_assume_unique_for_key(&decode(&sealed.nonce)?)
+        .map_err(|_| AppError::Credentials("Damaged secrets file: bad nonce".into()))?;
+    let mut buf = Zeroizing::new(decode(&sealed.ciphertext)?);
+    let key = derive_key(passphrase, &salt, sealed.iterations);
+    let plain = aead_key(&key)
+        .open_in_place(nonce, Aad::from(AAD), &mut buf)
+        .map_err(|_| AppError::InvalidInput("Wrong passphrase".into()))?;
+    let secrets = serde_json::from_slice(plain)
+        .map_err(|e| AppError::Credentials(format!("Damaged secrets file: {e}")))?;
+    Ok(Unlocked {
+        key,
+        salt,
+        iterations: sealed.iterations,
+        secrets,
+    })
+}
+
+fn write_file(path: &PathBuf, unlocked: &Unlocked) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(&seal(unlocked)?).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let tmp = path.with_extension("enc.tmp");
+    // Created afresh, so the owner-only mode applies; the rename keeps it.
+    let _ = std::fs::remove_file(&tmp);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&tmp).map_err(|e| e.to_string())?;
+    file.write_all(&json)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| e.to_string())?;
+    drop(file);
+    std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Entries
+// ---------------------------------------------------------------------------
+
+fn locked() -> keyring::Error {
+    keyring::Error::NoStorageAccess("The encrypted secrets file is locked".into())
+}
+
+/// One secret, in the keychain or the encrypted file.
+pub enum SecretEntry {
+    Keychain(keyring::Entry),
+    File(String),
+}
+
+impl SecretEntry {
+    pub fn get_password(&self) -> keyring::Result<String> {
+        match self {
+            Self::Keychain(entry) => entry.get_password(),
+            Self::File(account) => with_unlocked(|_, unlocked| {
+                unlocked
+                    .secrets
+                    .get(account)
+                    .cloned()
+                    .ok_or(keyring::Error::NoEntry)
+            }),
+        }
+    }
+
+    pub fn set_password(&self, password: &str) -> keyring::Result<()> {
+        match self {
+            Self::Keychain(entry) => entry.set_password(password),
+            Self::File(account) => with_unlocked(|path, unlocked| {
+                unlocked
+                    .secrets
+                    .insert(account.clone(), password.to_string());
+                write_file(path, unlocked).map_err(|e| keyring::Error::PlatformFailure(e.into()))
+            }),
+        }
+    }
+
+    pub fn delete_credential(&self) -> keyring::Result<()> {
+        match self {
+            Self::Keychain(entry) => entry.delete_credential(),
+            Self::File(account) => with_unlocked(|path, unlocked| {
+                if unlocked.secrets.remove(account).is_none() {
+                    return Err(keyring::Error::NoEntry);
+                }
+                write_file(path, unlocked).map_err(|e| keyring::Error::PlatformFailure(e.into()))
+            }),
+        }
+    }
+}
+
+fn with_unlocked<T>(
+    f: impl FnOnce(&PathBuf, &mut Unlocked) -> keyring::Result<T>,
+) -> keyring::Result<T> {
+    let mut guard = STORE.lock().map_err(|_| locked())?;
+    let store = guard.as_mut().ok_or_else(locked)?;
+    let unlocked = store.unlocked.as_mut().ok_or_else(locked)?;
+    f(&store.path, unlocked)
+}
+
+/// The entry for `account` in whichever store is in use.
+pub(crate) fn entry(account: &str) -> Result<SecretEntry, String> {
+    let using_file = STORE
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .is_some_and(|store| store.using_file);
+    entry_in(using_file, account)
+}
+
+/// The entry for `account` in the file or the keychain.
+fn entry_in(file: bool, account: &str) -> Result<SecretEntry, String> {
+    if file {
+        Ok(SecretEntry::File(account.to_string()))
+    } else {
+        keyring::Entry::new(SERVICE, account)
+            .map(SecretEntry::Keychain)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Every account the app keeps secrets under, as far as it can tell
+/// without listing the keychain: the fixed ones, each profile in
+/// `profile_index` and each webhook.
+fn known_accounts(app: &AppHandle, profile_index: Option<&str>) -> Vec<String> {
+    let mut accounts: Vec<String> = [
+        app_lock::VERIFIER_ACCOUNT,
+        datadog::API_KEY_ACCOUNT,
+        github::TOKEN_ACCOUNT,
+        google_sheets::KEY_ACCOUNT,
+        identity_center::TOKEN_ACCOUNT,
+        local_api::TOKEN_ACCOUNT,
+        pagerduty::ROUTING_KEY_ACCOUNT,
+        profiles::INDEX_ACCOUNT,
+        remote_backend::AUTH_ACCOUNT,
+        servicenow::PASSWORD_ACCOUNT,
+        sso::REFRESH_TOKEN_ACCOUNT,
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect();
+    let profile_names: Vec<String> = profile_index
+        .and_then(|raw| serde_json::from_str(raw).ok())
+        .unwrap_or_default();
+    accounts.extend(
+        profile_names
+            .iter()
+            .map(|name| profiles::profile_account(name)),
+    );
+    let endpoints = settings::load(app).webhooks.endpoints;
+    accounts.extend(endpoints.iter().map(|e| webhooks::secret_account(&e.id)));
+    accounts
+}
+
+/// Copies every secret out of the file (`from_file`) or the keychain into
+/// the other, returning the accounts copied. The source keeps its copies
+/// until the switch is saved.
+fn copy_secrets(app: &AppHandle, from_file: bool) -> Result<Vec<String>, AppError> {
+    let unlocked = STORE
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .is_some_and(|store| store.unlocked.is_some());
+    if !unlocked {
+        return Err(AppError::InvalidInput(
+            "Unlock (or create) the encrypted secrets file first, so the secrets can be moved"
+                .into(),
+        ));
+    }
+    let read = |account: &str| -> Result<Option<Zeroizing<String>>, AppError> {
+        match entry_in(from_file, account)?.get_password() {
+            Ok(value) => Ok(Some(Zeroizing::new(value))),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(AppError::Credentials(format!("{account}: {err}"))),
+        }
+    };
+    let accounts = if from_file {
+        with_unlocked(|_, unlocked| Ok(unlocked.secrets.keys().cloned().collect()))
+            .map_err(|e| AppError::Credentials(e.to_string()))?
+    } else {
+        let index = read(profiles::INDEX_ACCOUNT)?;
+        known_accounts(app, index.as_deref().map(String::as_str))
+    };
+    let mut copied = Vec::new();
+    for account in accounts {
+        let Some(value) = read(&account)? else {
+            continue;
+        };
+        entry_in(!from_file, &account)?
+            .set_password(&value)
+            .map_err(|e| AppError::Credentials(format!("{account}: {e}")))?;
+        copied.push(account);
+    }
+    Ok(copied)
+}
+
+/// Whether `backend` keeps secrets in the file.
+fn uses_file(backend: SecretBackend, keychain_available: bool) -> bool {
+    match backend {
+        SecretBackend::Auto => !keychain_available,
+        SecretBackend::Keychain => false,
+        SecretBackend::EncryptedFile => true,
+    }
+}
+
+fn keychain_available() -> bool {
+    match keyring::Entry::new(SERVICE, PROBE_ACCOUNT) {
+        Ok(entry) => !matches!(
+            entry.get_password(),
+            Err(keyring::Error::NoStorageAccess(_) | keyring::Error::PlatformFailure(_))
+        ),
+        Err(_) => false,
+    }
+}
+
+fn file_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_config_dir()
+        .expect("could not resolve app config dir")
+        .join(FILE_NAME)
+}
+
+/// Picks the store at launch (and after the setting changes), before
+/// anything reads secrets.
+pub fn init(app: &AppHandle) {
+    let backend = settings::load(app).secret_store.backend;
+    let keychain_available = keychain_available();
+    let using_file = uses_file(backend, keychain_available);
+    if let Ok(mut guard) = STORE.lock() {
+        let unlocked = guard.take().and_then(|store| store.unlocked);
+        *guard = Some(Store {
+            path: file_path(app),
+            using_file,
+            keychain_available,
+            unlocked,
+        });
+    }
+}
+
+fn status(app: &AppHandle) -> SecretStoreStatus {
+    let backend = settings::load(app).secret_store.backend;
+    let guard = STORE.lock().ok();
+    let store = guard.as_ref().and_then(|g| g.as_ref());
+    SecretStoreStatus {
+        backend,
+        using_file: store.is_some_and(|s| s.using_file),
+        keychain_available: store.is_some_and(|s| s.keychain_available),
+        file_exists: file_path(app).exists(),
+        unlocked: store.is_some_and(|s| s.unlocked.is_some()),
+    }
+}
+
+/// Opens the file with `passphrase`, creating it if there is none yet.
+fn unlock(app: &AppHandle, passphrase: &str) -> Result<(), AppError> {
+    let path = file_path(app);
+    let unlocked = match std::fs::read(&path) {
+        Ok(raw) => {
+            let sealed: SealedFile = serde_json::from_slice(&raw)
+                .map_err(|e| AppError::Credentials(format!("Damaged secrets file: {e}")))?;
+            open(&sealed, passphrase, ITERATIONS)?
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+                return Err(AppError::InvalidInput(format!(
+                    "Use a passphrase of at least {MIN_PASSPHRASE_LEN} characters"
+                )));
+            }
+            let salt = random::<16>()?.to_vec();
+            let unlocked = Unlocked {
+                key: derive_key(passphrase, &salt, ITERATIONS),
+                salt,
+                iterations: ITERATIONS,
+                secrets: BTreeMap::new(),
+            };
+            write_file(&path, &unlocked)?;
+            unlocked
+        }
+        Err(err) => return Err(err.to_string().into()),
+    };
+    let mut guard = STORE.lock().map_err(|e| e.to_string())?;
+    let store = guard
+        .as_mut()
+        .ok_or_else(|| AppError::Credentials("Secret store not initialized".into()))?;
+    store.unlocked = Some(unlocked);
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_secret_store_status(app: AppHandle) -> SecretStoreStatus {
+    status(&app)
+}
+
+/// Changes the store, first copying every secret into the new one; the old
+/// store's copies are removed once the switch is saved.
+#[tauri::command]
+pub fn save_secret_store_settings(
+    app: AppHandle,
+    config: SecretStoreSettings,
+) -> CommandResult<SecretStoreStatus> {
+    let was_file = STORE
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .is_some_and(|store| store.using_file);
+    let moved = if uses_file(config.backend, keychain_available()) != was_file {
+        copy_secrets(&app, was_file)?
+    } else {
+        Vec::new()
+    };
+    let mut all = settings::load(&app);
+    all.secret_store = config;
+    settings::save(&app, &all)?;
+    init(&app);
+    for account in moved {
+        if let Ok(entry) = entry_in(was_file, &account) {
+            let _ = entry.delete_credential();
+        }
+    }
+    Ok(status(&app))
+}
+
+/// Unlocks (or first creates) the encrypted file, then starts the sidecar if
+/// it was waiting for the credentials inside.
+#[tauri::command]
+pub async fn unlock_secret_store(app: AppHandle, passphrase: String) -> CommandResult<()> {
+    tauri::async_runtime::spawn_blocking(move || {
+        unlock(&app, &passphrase)?;
+        if let Err(err) = profiles::migrate(&app) {
+            eprintln!("keychain migration failed: {err}");
+        }
+        if let Some(creds) = read_credentials(&app).filter(|_| sso::access_granted(&app)) {
+            reload_sidecar(&app, &creds)?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unlocked(passphrase: &str) -> Unlocked {
+        let salt = b"0123456789abcdef".to_vec();
+        Unlocked {
+            key: derive_key(passphrase, &salt, 10),
+            salt,
+            iterations: 10,
+            secrets: BTreeMap::from([("profile:work".into(), "{\"k\":1}".into())]),
+        }
+    }
+
+    #[test]
+    fn sealed_secrets_open_only_with_the_passphrase() {
+        let sealed = seal(&unlocked("correct horse")).unwrap();
+        assert!(!sealed.ciphertext.contains("profile"));
+
+        let opened = open(&sealed, "correct horse", 10).unwrap();
+        assert_eq!(opened.secrets["profile:work"], "{\"k\":1}");
+        assert!(matches!(
+            open(&sealed, "wrong horse", 10),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn files_with_too_few_rounds_are_refused() {
+        let sealed = seal(&unlocked("correct horse")).unwrap();
+        assert!(matches!(
+            open(&sealed, "correct horse", ITERATIONS),
+            Err(AppError::Credentials(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_secrets_file_is_private_to_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("secrets-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("secrets.enc");
+        write_file(&path, &unlocked("correct horse")).unwrap();
+        // Rewritten over a stale temp file left readable by others.
+        let tmp = path.with_extension("enc.tmp");
+        std::fs::write(&tmp, "stale").unwrap();
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o644)).unwrap();
+        write_file(&path, &unlocked("correct horse")).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(!tmp.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn every_seal_uses_a_fresh_nonce() {
+        let secrets = unlocked("correct horse");
+        let (a, b) = (seal(&secrets).unwrap(), seal(&secrets).unwrap());
+        assert_ne!(a.nonce, b.nonce);
+        assert_ne!(a.ciphertext, b.ciphertext);
+    }
+
+    #[test]
+    fn auto_falls_back_to_the_file_without_a_keychain() {
+        assert!(!uses_file(SecretBackend::Auto, true));
+        assert!(uses_file(SecretBackend::Auto, false));
+        assert!(!uses_file(SecretBackend::Keychain, false));
+        assert!(uses_file(SecretBackend::EncryptedFile, true));
+    }
+}
@@ -0,0 +1,182 @@
+//! `run_backend_selftest` for the support screen: a short end-to-end pass
+//! over what every scan relies on. It checks the sidecar's health endpoint,
+//! that the sidecar refuses requests without the app's token and accepts
+//! them with it, a `GetCallerIdentity` call with the saved credentials, and a
+//! write and read-back of the Cost Explorer cache. Every step runs even when
+//! an earlier one fails, and each reports how long it took.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::aws::{account_id, sdk_config};
+use crate::error::CommandResult;
+use crate::{backend, cost_explorer, health_check, read_credentials};
+
+/// Upper bound for the STS step, which may try several regions.
+const STS_TIMEOUT: Duration = Duration::from_secs(10);
+/// Answered by any sidecar, and guarded by its token like every route.
+const TOKEN_PATH: &str = "/version";
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepOutcome {
+    Passed,
+    Failed,
+    /// Not applicable here, e.g. no credentials to call STS with.
+    Skipped,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SelfTestStep {
+    pub name: &'static str,
+    pub outcome: StepOutcome,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SelfTestReport {
+    /// No step failed.
+    pub passed: bool,
+    pub ran_at: DateTime<Utc>,
+    pub steps: Vec<SelfTestStep>,
+}
+
+fn step(
+    name: &'static str,
+    started: Instant,
+    (outcome, detail): (StepOutcome, String),
+) -> SelfTestStep {
+    SelfTestStep {
+        name,
+        outcome,
+        detail,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+fn check_health() -> (StepOutcome, String) {
+    let path = health_check::current().path;
+    if backend::healthy() {
+        (
+            StepOutcome::Passed,
+            format!("{path} answered at {}", backend::location()),
+        )
+    } else {
+        (
+            StepOutcome::Failed,
+            format!("{path} did not answer at {}", backend::location()),
+        )
+    }
+}
+
+/// Judges the statuses of a request without the token and one with it.
+fn token_outcome(without: u16, with: u16) -> (StepOutcome, String) {
+    match (without, with) {
+        (401, 200..=299) => (
+            StepOutcome::Passed,
+            "Refused without the token and accepted with it".into(),
+        ),
+        (401, _) => (
+            StepOutcome::Failed,
+            format!("Refused even with the token (HTTP {with})"),
+        ),
+        (200..=299, _) => (
+            StepOutcome::Failed,
+            "Accepted a request without the token".into(),
+        ),
+        _ => (
+            StepOutcome::Failed,
+            format!("Unexpected HTTP {without} without the token and {with} with it"),
+        ),
+    }
+}
+
+fn check_token() -> (StepOutcome, String) {
+    match backend::probe_token(TOKEN_PATH) {
+        Ok(Some((without, with))) => token_outcome(without, with),
+        Ok(None) => (
+            StepOutcome::Skipped,
+            "The backend was not started by this app and has no token".into(),
+        ),
+        Err(err) => (StepOutcome::Failed, err.to_string()),
+    }
+}
+
+async fn check_sts(app: &AppHandle) -> (StepOutcome, String) {
+    if read_credentials(app).is_none() {
+        return (StepOutcome::Skipped, "No AWS credentials saved".into());
+    }
+    let verified = tokio::time::timeout(STS_TIMEOUT, async {
+        account_id(&sdk_config(app).await?).await
+    })
+    .await;
+    match verified {
+        Ok(Ok(account)) => (
+            StepOutcome::Passed,
+            format!("GetCallerIdentity answered for account {account}"),
+        ),
+        Ok(Err(err)) => (StepOutcome::Failed, err.to_string()),
+        Err(_) => (StepOutcome::Failed, "AWS did not answer in time".into()),
+    }
+}
+
+fn check_cache(app: &AppHandle) -> (StepOutcome, String) {
+    match cost_explorer::check_cache(app) {
+        Ok(()) => (
+            StepOutcome::Passed,
+            "Cost Explorer cache written and read back".into(),
+        ),
+        Err(err) => (StepOutcome::Failed, err.to_string()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Runs the self-test. A sleeping sidecar is woken first, so it is tested
+/// rather than reported down.
+#[tauri::command]
+pub async fn run_backend_selftest(app: AppHandle) -> CommandResult<SelfTestReport> {
+    let ran_at = Utc::now();
+    let handle = app.clone();
+    let local = tauri::async_runtime::spawn_blocking(move || {
+        crate::sidecar_idle::wake();
+        let started = Instant::now();
+        let health = step("health", started, check_health());
+        let started = Instant::now();
+        let auth = step("auth_token", started, check_token());
+        let started = Instant::now();
+        let cache = step("cache", started, check_cache(&handle));
+        (health, auth, cache)
+    });
+    let started = Instant::now();
+    let sts = step("sts", started, check_sts(&app).await);
+    let (health, auth, cache) = local.await.map_err(|e| e.to_string())?;
+
+    let steps = vec![health, auth, sts, cache];
+    Ok(SelfTestReport {
+        passed: steps.iter().all(|step| step.outcome != StepOutcome::Failed),
+        ran_at,
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_must_be_required_and_accepted() {
+        assert_eq!(token_outcome(401, 200).0, StepOutcome::Passed);
+        assert_eq!(token_outcome(401, 401).0, StepOutcome::Failed);
+        assert_eq!(token_outcome(200, 200).0, StepOutcome::Failed);
+        let (outcome, detail) = token_outcome(500, 500);
+        assert_eq!(outcome, StepOutcome::Failed);
+        assert!(detail.contains("HTTP 500"), "{detail}");
+    }
+}
@@ -0,0 +1,280 @@
+//! ServiceNow integration: raises one change request per batch of selected
+//! remediations, with the remediation plan attached, for organizations whose
+//! production changes must go through ITSM.
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::backend::{self, Recommendation};
+use crate::error::{AppError, CommandResult};
+use crate::{ca_trust, keyring_entry_for, settings};
+
+pub(crate) const PASSWORD_ACCOUNT: &str = "servicenow-password";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ServiceNowSettings {
+    /// e.g. `https://acme.service-now.com`
+    pub instance_url: String,
+    pub username: String,
+    /// Change type: `normal`, `standard`, or `emergency`.
+    pub change_type: String,
+    /// Optional assignment group name or sys_id.
+    pub assignment_group: Option<String>,
+}
+
+impl Default for ServiceNowSettings {
+    fn default() -> Self {
+        Self {
+            instance_url: String::new(),
+            username: String::new(),
+            change_type: "normal".into(),
+            assignment_group: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChangeRequestLink {
+    pub run_id: String,
+    pub recommendation_ids: Vec<String>,
+    pub number: String,
+    pub sys_id: String,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct TableResponse {
+    result: CreatedRecord,
+}
+
+#[derive(Deserialize)]
+struct CreatedRecord {
+    sys_id: String,
+    number: String,
+}
+
+// ---------------------------------------------------------------------------
+// Storage helpers
+// ---------------------------------------------------------------------------
+
+fn links_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("servicenow_changes.json")
+}
+
+fn read_links(app: &AppHandle) -> Vec<ChangeRequestLink> {
+    std::fs::read_to_string(links_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_links(app: &AppHandle, links: &[ChangeRequestLink]) -> Result<(), String> {
+    let path = links_path(app);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(links).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+fn basic_auth(cfg: &ServiceNowSettings) -> Result<String, String> {
+    let password = keyring_entry_for(PASSWORD_ACCOUNT)?
+        .get_password()
+        .map_err(|_| "No ServiceNow password saved".to_string())?;
+    let encoded =
+        base64::engine::general_purpose::STANDARD.encode(format!("{}:{password}", cfg.username));
+    Ok(format!("Basic {encoded}"))
+}
+
+// ---------------------------------------------------------------------------
+// Change rendering
+// ---------------------------------------------------------------------------
+
+fn target(rec: &Recommendation) -> String {
+    match &rec.key {
+        Some(key) => format!("s3://{}/{key}", rec.bucket),
+        None => format!("s3://{}", rec.bucket),
+    }
+}
+
+/// The plan attached to the change: every action in execution order.
+fn render_plan(run_id: &str, recs: &[&Recommendation]) -> String {
+    let mut plan = format!(
+        "# Remediation plan\n\nRun `{run_id}`, {} action(s).\n\n\
+         Each action is executed through AWS Cost Optimizer, which records the \
+         pre-change state so it can be rolled back.\n",
+        recs.len()
+    );
+    for (i, rec) in recs.iter().enumerate() {
+        plan.push_str(&format!(
+            "\n## {}. {} {}\n\n- Action: {}\n- Reason: {}\n- Risk: {}\n- Est. savings: ${:.2}/month\n- Recommendation: `{}`\n",
+            i + 1,
+            rec.recommendation_type,
+            target(rec),
+            rec.recommended_action,
+            rec.reason,
+            rec.risk_level,
+            rec.estimated_monthly_savings,
+            rec.id,
+        ));
+    }
+    plan
+}
+
+fn create_change(
+    cfg: &ServiceNowSettings,
+    auth: &str,
+    run_id: &str,
+    recs: &[&Recommendation],
+) -> Result<ChangeRequestLink, String> {
+    let base = cfg.instance_url.trim_end_matches('/');
+    let savings: f64 = recs.iter().map(|r| r.estimated_monthly_savings).sum();
+    let highest_risk = ["high", "medium", "low"]
+        .into_iter()
+        .find(|level| recs.iter().any(|r| r.risk_level == *level))
+        .unwrap_or("low");
+
+    let mut body = json!({
+        "type": cfg.change_type,
+        "short_description": format!(
+            "AWS cost remediation: {} S3 change(s), est. ${savings:.2}/month",
+            recs.len()
+        ),
+        "description": format!(
+            "Planned remediations from AWS Cost Optimizer run {run_id}. \
+             Highest risk level: {highest_risk}. The full plan is attached."
+        ),
+        "justification": format!("Reduces estimated S3 spend by ${savings:.2}/month."),
+        "category": "Cloud",
+    });
+    if let Some(group) = cfg.assignment_group.as_deref().filter(|g| !g.is_empty()) {
+        body["assignment_group"] = json!(group);
+    }
+
+    let created: TableResponse = ca_trust::agent()
+        .post(&format!("{base}/api/now/table/change_request"))
+        .set("Authorization", auth)
+        .set("Accept", "application/json")
+        .send_json(body)
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    let record = created.result;
+
+    ca_trust::agent()
+        .post(&format!("{base}/api/now/attachment/file"))
+        .query("table_name", "change_request")
+        .query("table_sys_id", &record.sys_id)
+        .query("file_name", &format!("remediation-plan-{run_id}.md"))
+        .set("Authorization", auth)
+        .set("Accept", "application/json")
+        .set("Content-Type", "text/markdown")
+        .send_string(&render_plan(run_id, recs))
+        .map_err(|e| {
+            format!(
+                "Change {} created but plan upload failed: {e}",
+                record.number
+            )
+        })?;
+
+    Ok(ChangeRequestLink {
+        run_id: run_id.to_string(),
+        recommendation_ids: recs.iter().map(|r| r.id.clone()).collect(),
+        url: format!(
+            "{base}/nav_to.do?uri=change_request.do?sys_id={}",
+            record.sys_id
+        ),
+        number: record.number,
+        sys_id: record.sys_id,
+    })
+}
+
+fn create_change_blocking(
+    app: &AppHandle,
+    run_id: &str,
+    recommendation_ids: &[String],
+) -> Result<ChangeRequestLink, String> {
+    let cfg = settings::load(app).servicenow;
+    if !cfg.instance_url.starts_with("https://") {
+        return Err("ServiceNow instance URL is not configured (expected https://...)".into());
+    }
+    let auth = basic_auth(&cfg)?;
+    let run = backend::get_run(run_id)?;
+    let recs: Vec<&Recommendation> = run
+        .recommendations
+        .iter()
+        .filter(|r| recommendation_ids.contains(&r.id))
+        .collect();
+    if recs.is_empty() {
+        return Err("None of the selected recommendations belong to this run".into());
+    }
+
+    let link = create_change(&cfg, &auth, run_id, &recs)?;
+    let mut links = read_links(app);
+    links.push(link.clone());
+    write_links(app, &links)?;
+    Ok(link)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_servicenow_settings(app: AppHandle) -> ServiceNowSettings {
+    settings::load(&app).servicenow
+}
+
+/// Saves the configuration. A non-empty `password` replaces the stored one.
+#[tauri::command]
+pub fn save_servicenow_settings(
+    app: AppHandle,
+    config: ServiceNowSettings,
+    password: Option<String>,
+) -> CommandResult<()> {
+    if !["normal", "standard", "emergency"].contains(&config.change_type.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "Unknown change type '{}'",
+            config.change_type
+        )));
+    }
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        keyring_entry_for(PASSWORD_ACCOUNT)?
+            .set_password(&password)
+            .map_err(|e| e.to_string())?;
+    }
+    let mut all = settings::load(&app);
+    all.servicenow = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
+
+/// Raises one change request covering the selected recommendations of a run.
+#[tauri::command]
+pub async fn create_servicenow_change(
+    app: AppHandle,
+    run_id: String,
+    recommendation_ids: Vec<String>,
+) -> CommandResult<ChangeRequestLink> {
+    tauri::async_runtime::spawn_blocking(move || {
+        create_change_blocking(&app, &run_id, &recommendation_ids)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(AppError::from)
+}
+
+/// Returns the change requests previously raised for a run.
+#[tauri::command]
+pub fn list_servicenow_changes(app: AppHandle, run_id: String) -> Vec<ChangeRequestLink> {
+    read_links(&app)
+        .into_iter()
+        .filter(|link| link.run_id == run_id)
+        .collect()
+}
@@ -0,0 +1,101 @@
+//! Non-secret app settings persisted as JSON in the app config dir.
+//! Secrets (tokens, keys) never go here — they live in the OS keychain.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::app_lock::AppLockSettings;
+use crate::aws_cli::ProfileSyncSettings;
+use crate::ca_trust::CaTrustSettings;
+use crate::cost_explorer::CostExplorerBudgetSettings;
+use crate::credential_process::CredentialProcessSettings;
+use crate::datadog::DatadogSettings;
+use crate::exporters::archive::ReportArchiveSettings;
+use crate::github::GithubSettings;
+use crate::google_sheets::SheetsSettings;
+use crate::grpc::GrpcSettings;
+use crate::health_check::HealthCheckSettings;
+use crate::instance_credentials::InstanceCredentialSettings;
+use crate::key_rotation::KeyRotationSettings;
+use crate::local_api::LocalApiSettings;
+use crate::metrics::MetricsSettings;
+use crate::organizations::OrganizationSettings;
+use crate::os_auth::RevealSettings;
+use crate::pagerduty::PagerDutySettings;
+use crate::plugins::PluginSettings;
+use crate::profiles::ProfileSettings;
+use crate::proxy::ProxySettings;
+use crate::remote_backend::RemoteBackendSettings;
+use crate::request_queue::RequestQueueSettings;
+use crate::resilience::ResilienceSettings;
+use crate::response_cache::ResponseCacheSettings;
+use crate::scheduler::ScheduleSettings;
+use crate::secret_store::SecretStoreSettings;
+use crate::servicenow::ServiceNowSettings;
+use crate::sidecar_idle::PowerSettings;
+use crate::sidecar_memory::SidecarMemorySettings;
+use crate::sidecar_workers::WorkerSettings;
+use crate::webhooks::WebhookSettings;
+use crate::websocket::WebSocketSettings;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AppSettings {
+    pub github: GithubSettings,
+    pub metrics: MetricsSettings,
+    pub webhooks: WebhookSettings,
+    pub local_api: LocalApiSettings,
+    pub grpc: GrpcSettings,
+    pub pagerduty: PagerDutySettings,
+    pub schedules: ScheduleSettings,
+    pub profile_sync: ProfileSyncSettings,
+    pub datadog: DatadogSettings,
+    pub servicenow: ServiceNowSettings,
+    pub websocket: WebSocketSettings,
+    pub google_sheets: SheetsSettings,
+    pub report_archive: ReportArchiveSettings,
+    pub plugins: PluginSettings,
+    pub cost_explorer_budget: CostExplorerBudgetSettings,
+    pub credential_profiles: ProfileSettings,
+    pub credential_process: CredentialProcessSettings,
+    pub instance_credentials: InstanceCredentialSettings,
+    pub secret_store: SecretStoreSettings,
+    pub reveal: RevealSettings,
+    pub app_lock: AppLockSettings,
+    pub key_rotation: KeyRotationSettings,
+    pub organization: OrganizationSettings,
+    pub health_check: HealthCheckSettings,
+    pub sidecar_memory: SidecarMemorySettings,
+    pub power: PowerSettings,
+    pub workers: WorkerSettings,
+    pub remote_backend: RemoteBackendSettings,
+    pub proxy: ProxySettings,
+    pub ca_trust: CaTrustSettings,
+    pub resilience: ResilienceSettings,
+    pub response_cache: ResponseCacheSettings,
+    pub request_queue: RequestQueueSettings,
+}
+
+fn settings_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_config_dir()
+        .expect("could not resolve app config dir")
+        .join("settings.json")
+}
+
+/// Loads settings, falling back to defaults when the file is missing or unreadable.
+pub fn load(app: &AppHandle) -> AppSettings {
+    std::fs::read_to_string(settings_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let path = settings_path(app);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
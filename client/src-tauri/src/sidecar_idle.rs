@@ -0,0 +1,313 @@
+//! Puts the sidecar to sleep while the app sits in the background. Once the
+//! main window has been minimized or hidden for `idle_minutes`, with no
+//! request in flight, the sidecar is suspended (SIGSTOP, Unix only) or
+//! stopped, as `power_mode` says. Stopping frees its ~300 MB; suspending
+//! keeps its state and lets the OS page it out.
+//!
+//! It wakes when the window regains focus or when a request needs it, so
+//! scheduled scans still run: the request that finds it asleep resumes or
+//! restarts it and goes on once it answers. Background pollers run their
+//! ticks through [`unless_asleep`] instead, skipping them while it sleeps, or
+//! a poll every few seconds would keep waking it. Meanwhile the backend
+//! phase is `Idle`, so the webview does not wait for a start.
+
+use std::cell::Cell;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::sidecar_watchdog::{self, BackendPhase};
+use crate::{backend, settings, SidecarState};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerMode {
+    /// Keep the sidecar running.
+    #[default]
+    AlwaysOn,
+    /// Pause its processes; stopped instead on Windows.
+    Suspend,
+    /// Stop it, starting it again on return.
+    Stop,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PowerSettings {
+    pub power_mode: PowerMode,
+    /// Minutes the window stays minimized or hidden before the sidecar
+    /// sleeps.
+    pub idle_minutes: u32,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            power_mode: PowerMode::default(),
+            idle_minutes: 15,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Sleep {
+    Awake,
+    /// Paused sidecar processes, bootloader first.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Suspended(Vec<u32>),
+    Stopped,
+}
+
+static SLEEP: Mutex<Sleep> = Mutex::new(Sleep::Awake);
+/// Set by [`spawn_idle_watch`], for waking from a request.
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+thread_local! {
+    /// Set while this thread runs a background tick, whose requests must
+    /// not wake the sidecar.
+    static BACKGROUND: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the sidecar should sleep, the window having been in the
+/// background since `hidden_since`.
+fn due(config: &PowerSettings, hidden_since: Option<Instant>, now: Instant) -> bool {
+    let idle = Duration::from_secs(u64::from(config.idle_minutes) * 60);
+    config.power_mode != PowerMode::AlwaysOn
+        && hidden_since.is_some_and(|since| now.duration_since(since) >= idle)
+}
+
+fn window_hidden(app: &AppHandle) -> bool {
+    app.get_webview_window("main").is_some_and(|window| {
+        window.is_minimized().unwrap_or(false) || !window.is_visible().unwrap_or(true)
+    })
+}
+
+#[cfg(unix)]
+fn signal(pids: &[u32], signal: libc::c_int) {
+    for pid in pids {
+        // SAFETY: only the running sidecar's own processes are signalled.
+        unsafe { libc::kill(*pid as libc::pid_t, signal) };
+    }
+}
+
+/// Suspends or stops the running sidecar, if no request is in flight.
+fn sleep(app: &AppHandle, mode: PowerMode) {
+    let Some(_requests) = backend::try_hold_requests() else {
+        return;
+    };
+    let mut asleep = SLEEP.lock().unwrap_or_else(|e| e.into_inner());
+    if *asleep != Sleep::Awake {
+        return;
+    }
+    let state = app.state::<SidecarState>();
+    // Busy means a restart is under way, which may itself be waking it.
+    let Ok(mut child) = state.0.try_lock() else {
+        return;
+    };
+    let Some(pid) = child.as_ref().map(|child| child.pid()) else {
+        return;
+    };
+    // Workers start again with the next scan.
+    crate::sidecar_workers::stop_all(app);
+    #[cfg(unix)]
+    {
+        if mode == PowerMode::Suspend {
+            let pids = crate::sidecar_stats::pids(pid);
+            signal(&pids, libc::SIGSTOP);
+            eprintln!("sidecar {pid} suspended while the app is idle");
+            *asleep = Sleep::Suspended(pids);
+            sidecar_watchdog::set_phase(BackendPhase::Idle);
+            return;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    // Taken out first so the watchdog does not restart it.
+    let Some(old) = child.take() else {
+        return;
+    };
+    drop(child);
+    if crate::stop_gracefully(old) {
+        crate::sidecar_pid::clear(app);
+        backend::release_endpoint();
+    }
+    eprintln!("sidecar {pid} stopped while the app is idle");
+    *asleep = Sleep::Stopped;
+    sidecar_watchdog::set_phase(BackendPhase::Idle);
+}
+
+/// Resumes a suspended sidecar without restarting a stopped one; for code
+/// about to stop or replace it anyway.
+pub(crate) fn reset() {
+    let asleep = std::mem::replace(
+        &mut *SLEEP.lock().unwrap_or_else(|e| e.into_inner()),
+        Sleep::Awake,
+    );
+    #[cfg(unix)]
+    {
+        if let Sleep::Suspended(pids) = asleep {
+            signal(&pids, libc::SIGCONT);
+        }
+    }
+    #[cfg(not(unix))]
+    drop(asleep);
+}
+
+/// Brings a sleeping sidecar back, returning once it answers. Requests that
+/// come in meanwhile go ahead without waiting.
+pub(crate) fn wake() {
+    if BACKGROUND.with(Cell::get) {
+        return;
+    }
+    let asleep = std::mem::replace(
+        &mut *SLEEP.lock().unwrap_or_else(|e| e.into_inner()),
+        Sleep::Awake,
+    );
+    match asleep {
+        Sleep::Awake => {}
+        #[cfg(unix)]
+        Sleep::Suspended(pids) => {
+            signal(&pids, libc::SIGCONT);
+            sidecar_watchdog::set_phase(BackendPhase::Ready);
+        }
+        #[cfg(not(unix))]
+        Sleep::Suspended(_) => {}
+        Sleep::Stopped => {
+            let Some(app) = APP.get() else {
+                return;
+            };
+            let creds = crate::read_credentials(app).filter(|_| crate::sso::access_granted(app));
+            let Some(creds) = creds else {
+                sidecar_watchdog::set_phase(BackendPhase::Stopped);
+                return;
+            };
+            if let Err(err) = crate::restart_sidecar(app, &creds) {
+                sidecar_watchdog::start_failed(app, err);
+            }
+        }
+    }
+}
+
+/// Whether the sidecar is suspended or stopped for idleness.
+pub(crate) fn asleep() -> bool {
+    *SLEEP.lock().unwrap_or_else(|e| e.into_inner()) != Sleep::Awake
+}
+
+struct BackgroundTick;
+
+impl Drop for BackgroundTick {
+    fn drop(&mut self) {
+        BACKGROUND.with(|background| background.set(false));
+    }
+}
+
+/// Runs a background poller's tick, or skips it while the sidecar sleeps.
+/// Backend requests made by the tick do not wake it: one that finds it gone
+/// to sleep meanwhile fails, and the next tick is skipped.
+pub(crate) fn unless_asleep<T>(tick: impl FnOnce() -> T) -> Option<T> {
+    if asleep() {
+        return None;
+    }
+    BACKGROUND.with(|background| background.set(true));
+    let _tick = BackgroundTick;
+    Some(tick())
+}
+
+/// Wakes the sidecar in the background as the user returns to the window.
+pub fn on_focus() {
+    if asleep() {
+        tauri::async_runtime::spawn_blocking(wake);
+    }
+}
+
+/// Watches how long the main window stays in the background, and wakes
+/// the sidecar when it returns.
+pub fn spawn_idle_watch(app: AppHandle) {
+    let _ = APP.set(app.clone());
+    tauri::async_runtime::spawn(async move {
+        let mut hidden_since = None;
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let config = settings::load(&app).power;
+            let now = Instant::now();
+            if !window_hidden(&app) {
+                hidden_since = None;
+                on_focus();
+                continue;
+            }
+            let since = *hidden_since.get_or_insert(now);
+            if !asleep() && due(&config, Some(since), now) {
+                let app = app.clone();
+                let _ =
+                    tauri::async_runtime::spawn_blocking(move || sleep(&app, config.power_mode))
+                        .await;
+            }
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_power_settings(app: AppHandle) -> PowerSettings {
+    settings::load(&app).power
+}
+
+/// Saves the power mode; switching to `always_on` wakes a sleeping sidecar.
+#[tauri::command]
+pub async fn save_power_settings(app: AppHandle, config: PowerSettings) -> CommandResult<()> {
+    if config.idle_minutes == 0 || config.idle_minutes > 24 * 60 {
+        return Err(AppError::InvalidInput(
+            "Idle time must be between 1 minute and a day".into(),
+        ));
+    }
+    let always_on = config.power_mode == PowerMode::AlwaysOn;
+    let mut all = settings::load(&app);
+    all.power = config;
+    settings::save(&app, &all)?;
+    if always_on {
+        tauri::async_runtime::spawn_blocking(wake)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleeps_after_the_window_stays_hidden() {
+        let now = Instant::now();
+        let config = PowerSettings {
+            power_mode: PowerMode::Stop,
+            idle_minutes: 10,
+        };
+        let hidden_for = |mins: u64| Some(now - Duration::from_secs(mins * 60));
+        assert!(!due(&config, None, now));
+        assert!(!due(&config, hidden_for(9), now));
+        assert!(due(&config, hidden_for(10), now));
+        let always_on = PowerSettings {
+            power_mode: PowerMode::AlwaysOn,
+            ..config
+        };
+        assert!(!due(&always_on, hidden_for(60), now));
+    }
+
+    #[test]
+    fn background_ticks_do_not_wake_a_sleeping_sidecar() {
+        *SLEEP.lock().unwrap() = Sleep::Stopped;
+        assert_eq!(unless_asleep(|| 1), None);
+        *SLEEP.lock().unwrap() = Sleep::Awake;
+        assert_eq!(unless_asleep(|| BACKGROUND.with(Cell::get)), Some(true));
+        assert!(!BACKGROUND.with(Cell::get));
+    }
+}
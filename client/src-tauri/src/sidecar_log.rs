@@ -0,0 +1,202 @@
+//! The sidecar's stdout and stderr, kept in `backend.log` under the app log
+//! dir with each line's time and stream. At [`MAX_FILE_BYTES`] the file is
+//! renamed to `backend-<timestamp>.log` and a new one started; the newest
+//! [`KEEP_ROTATED`] rotated files are kept. `get_backend_logs` returns the
+//! tail for troubleshooting.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+
+const FILE_NAME: &str = "backend.log";
+const ROTATED_PREFIX: &str = "backend-";
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const KEEP_ROTATED: usize = 5;
+const DEFAULT_LINES: usize = 200;
+const MAX_LINES: usize = 5_000;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendLogs {
+    /// The current log file.
+    pub path: String,
+    /// The newest lines, oldest first.
+    pub lines: Vec<String>,
+}
+
+fn log_dir(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_log_dir()
+        .expect("could not resolve app log dir")
+}
+
+fn format_line(at: DateTime<Utc>, stream: &str, line: &[u8]) -> String {
+    format!(
+        "{} {stream} {}\n",
+        at.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+        String::from_utf8_lossy(line).trim_end()
+    )
+}
+
+fn rotated_name(at: DateTime<Utc>) -> String {
+    format!("{ROTATED_PREFIX}{}.log", at.format("%Y%m%dT%H%M%S%.3f"))
+}
+
+/// Rotated files, oldest first; the timestamped names sort by age.
+fn rotated_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(ROTATED_PREFIX) && name.ends_with(".log"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Appends one sidecar's output to the log, rotating it as it grows.
+pub(crate) struct SidecarLog {
+    dir: PathBuf,
+    file: Option<File>,
+}
+
+impl SidecarLog {
+    pub(crate) fn open(app: &AppHandle) -> Self {
+        Self {
+            dir: log_dir(app),
+            file: None,
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join(FILE_NAME)
+    }
+
+    /// The log file, reopened after a failed write or once another
+    /// sidecar's log has rotated it away.
+    fn file(&mut self) -> Option<&mut File> {
+        if self.file.is_some() && !self.path().exists() {
+            self.file = None;
+        }
+        if self.file.is_none() {
+            let _ = std::fs::create_dir_all(&self.dir);
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path())
+                .ok();
+        }
+        self.file.as_mut()
+    }
+
+    fn rotate(&mut self, now: DateTime<Utc>) {
+        self.file = None;
+        let _ = std::fs::rename(self.path(), self.dir.join(rotated_name(now)));
+        let rotated = rotated_files(&self.dir);
+        for old in &rotated[..rotated.len().saturating_sub(KEEP_ROTATED)] {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+
+    /// Writes one line of `stream` (`stdout`, `stderr` or `watchdog`).
+    pub(crate) fn write(&mut self, stream: &str, line: &[u8]) {
+        let now = Utc::now();
+        let full = std::fs::metadata(self.path()).is_ok_and(|meta| meta.len() >= MAX_FILE_BYTES);
+        if full {
+            self.rotate(now);
+        }
+        let text = format_line(now, stream, line);
+        if let Some(file) = self.file() {
+            if file.write_all(text.as_bytes()).is_err() {
+                self.file = None;
+            }
+        }
+    }
+}
+
+/// The last `count` lines of the current file and, if it is short, of the
+/// newest rotated one.
+fn tail(dir: &Path, count: usize) -> Vec<String> {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .map(|text| text.lines().map(str::to_string).collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    let mut lines = read(&dir.join(FILE_NAME));
+    if lines.len() < count {
+        if let Some(previous) = rotated_files(dir).last() {
+            let mut older = read(previous);
+            older.append(&mut lines);
+            lines = older;
+        }
+    }
+    let skip = lines.len().saturating_sub(count);
+    lines.split_off(skip)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// The backend's newest output lines (default 200, at most 5000).
+#[tauri::command]
+pub fn get_backend_logs(app: AppHandle, lines: Option<usize>) -> CommandResult<BackendLogs> {
+    let count = lines.unwrap_or(DEFAULT_LINES);
+    if count == 0 || count > MAX_LINES {
+        return Err(AppError::InvalidInput(format!(
+            "Ask for 1 to {MAX_LINES} lines"
+        )));
+    }
+    let dir = log_dir(&app);
+    Ok(BackendLogs {
+        path: dir.join(FILE_NAME).display().to_string(),
+        lines: tail(&dir, count),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_carry_time_and_stream() {
+        let at = "2025-03-10T13:00:00.250Z".parse().unwrap();
+        assert_eq!(
+            format_line(at, "stderr", b"Traceback (most recent call last):\r\n"),
+            "2025-03-10T13:00:00.250Z stderr Traceback (most recent call last):\n"
+        );
+        assert_eq!(rotated_name(at), "backend-20250310T130000.250.log");
+    }
+
+    #[test]
+    fn rotation_keeps_the_newest_files() {
+        let dir = std::env::temp_dir().join(format!("sidecar-log-{}", uuid::Uuid::new_v4()));
+        let mut log = SidecarLog {
+            dir: dir.clone(),
+            file: None,
+        };
+        let start: DateTime<Utc> = "2025-03-10T13:00:00Z".parse().unwrap();
+        for n in 0..KEEP_ROTATED as i64 + 2 {
+            log.write("stdout", format!("line {n}").as_bytes());
+            log.rotate(start + chrono::Duration::seconds(n));
+        }
+        log.write("stdout", b"current");
+
+        let rotated = rotated_files(&dir);
+        assert_eq!(rotated.len(), KEEP_ROTATED);
+        let lines = tail(&dir, 2);
+        assert!(lines[0].ends_with("stdout line 6"), "{lines:?}");
+        assert!(lines[1].ends_with("stdout current"), "{lines:?}");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
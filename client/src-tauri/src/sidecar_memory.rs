@@ -0,0 +1,200 @@
+//! Recycles a sidecar whose memory grows past a limit. The Python backend
+//! slowly leaks during long sessions, and only a fresh process gives the
+//! memory back. The check runs every few minutes; a sidecar over the limit
+//! is replaced at a moment with no request in flight, so no scan is cut
+//! short, after `backend://recycling` tells the UI why it is restarting.
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+#[cfg(not(dev))]
+use tauri::{Emitter, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::settings;
+
+pub const RECYCLING_EVENT: &str = "backend://recycling";
+const MB: u64 = 1024 * 1024;
+/// How long a recycle waits for the requests in flight to finish before
+/// trying again at the next check.
+#[cfg(not(dev))]
+const IDLE_WAIT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SidecarMemorySettings {
+    pub enabled: bool,
+    /// Resident memory of the sidecar and its children that triggers a
+    /// recycle.
+    pub limit_mb: u64,
+    pub check_interval_secs: u64,
+}
+
+impl Default for SidecarMemorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            limit_mb: 1024,
+            check_interval_secs: 300,
+        }
+    }
+}
+
+impl SidecarMemorySettings {
+    fn over_limit(&self, memory_bytes: u64) -> bool {
+        self.enabled && memory_bytes > self.limit_mb.saturating_mul(MB)
+    }
+}
+
+/// Payload of `backend://recycling`: sidecar `pid` is being replaced for
+/// using `memory_bytes`.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendRecycling {
+    pub pid: u32,
+    pub memory_bytes: u64,
+    pub limit_bytes: u64,
+}
+
+/// Replaces sidecar `pid` once no request is in flight. Whether it was
+/// replaced; not when it stayed busy, was replaced already, or has no
+/// credentials to restart with.
+#[cfg(not(dev))]
+fn recycle(app: &AppHandle, pid: u32, recycling: BackendRecycling) -> Result<bool, String> {
+    use crate::{backend, read_credentials, restart_sidecar, sso, SidecarState};
+
+    let Some(creds) = read_credentials(app).filter(|_| sso::access_granted(app)) else {
+        return Ok(false);
+    };
+    let deadline = std::time::Instant::now() + IDLE_WAIT;
+    let requests = loop {
+        if let Some(requests) = backend::try_hold_requests() {
+            break requests;
+        }
+        if std::time::Instant::now() >= deadline {
+            eprintln!("sidecar {pid} stayed busy; recycling it later");
+            return Ok(false);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(250));
+    };
+    let old = {
+        let state = app.state::<SidecarState>();
+        let mut guard = state.0.lock().map_err(|e| e.to_string())?;
+        if guard.as_ref().map(|child| child.pid()) != Some(pid) {
+            return Ok(false);
+        }
+        guard.take()
+    };
+    let Some(old) = old else {
+        return Ok(false);
+    };
+    eprintln!(
+        "sidecar {pid} uses {} MB; recycling it",
+        recycling.memory_bytes / MB
+    );
+    crate::sidecar_watchdog::set_phase(crate::sidecar_watchdog::BackendPhase::Starting);
+    let _ = app.emit(RECYCLING_EVENT, recycling);
+    // From the event on the webview holds its requests; the app's own are
+    // held until the old process has stopped.
+    let stopped = crate::stop_gracefully(old);
+    drop(requests);
+    if !stopped {
+        return Err(format!("Sidecar {pid} did not stop"));
+    }
+    restart_sidecar(app, &creds)?;
+    Ok(true)
+}
+
+#[cfg(not(dev))]
+fn check(app: &AppHandle, config: &SidecarMemorySettings) {
+    // A suspended sidecar cannot be stopped gracefully; wait for it to wake.
+    if crate::sidecar_idle::asleep() {
+        return;
+    }
+    let pid = app
+        .state::<crate::SidecarState>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|child| child.as_ref().map(|child| child.pid()));
+    let Some(pid) = pid else {
+        return;
+    };
+    let Some(memory_bytes) = crate::sidecar_stats::memory(pid) else {
+        return;
+    };
+    if !config.over_limit(memory_bytes) {
+        return;
+    }
+    let recycling = BackendRecycling {
+        pid,
+        memory_bytes,
+        limit_bytes: config.limit_mb * MB,
+    };
+    if let Err(err) = recycle(app, pid, recycling) {
+        crate::sidecar_watchdog::start_failed(app, format!("Recycling the backend failed: {err}"));
+    }
+}
+
+/// Checks the sidecar's memory on the configured interval.
+#[cfg(not(dev))]
+pub fn spawn_memory_watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = settings::load(&app).sidecar_memory;
+            let interval = std::time::Duration::from_secs(config.check_interval_secs.max(10));
+            tokio::time::sleep(interval).await;
+            let app = app.clone();
+            let _ = tauri::async_runtime::spawn_blocking(move || check(&app, &config)).await;
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_sidecar_memory_settings(app: AppHandle) -> SidecarMemorySettings {
+    settings::load(&app).sidecar_memory
+}
+
+/// Saves the memory limit; applies from the next check.
+#[tauri::command]
+pub fn save_sidecar_memory_settings(
+    app: AppHandle,
+    config: SidecarMemorySettings,
+) -> CommandResult<()> {
+    if !(128..=65_536).contains(&config.limit_mb) {
+        return Err(AppError::InvalidInput(
+            "Memory limit must be between 128 and 65536 MB".into(),
+        ));
+    }
+    if !(10..=86_400).contains(&config.check_interval_secs) {
+        return Err(AppError::InvalidInput(
+            "Check interval must be between 10 seconds and a day".into(),
+        ));
+    }
+    let mut all = settings::load(&app);
+    all.sidecar_memory = config;
+    settings::save(&app, &all)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recycles_only_past_an_enabled_limit() {
+        let config = SidecarMemorySettings {
+            limit_mb: 512,
+            ..SidecarMemorySettings::default()
+        };
+        assert!(!config.over_limit(512 * MB));
+        assert!(config.over_limit(512 * MB + 1));
+        let disabled = SidecarMemorySettings {
+            enabled: false,
+            ..config
+        };
+        assert!(!disabled.over_limit(u64::MAX));
+    }
+}
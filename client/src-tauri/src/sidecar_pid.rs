@@ -0,0 +1,182 @@
+//! The running sidecar's process id, kept in `sidecar.pid` under the app data
+//! dir so a sidecar outliving a crashed app can be found and stopped at the
+//! next launch. Before a pid from the file is signalled, the process is
+//! checked to still be a sidecar, so a reused pid is left alone. Worker
+//! sidecars are kept in `sidecar-workers.pid`, one pid per line.
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+const FILE_NAME: &str = "sidecar.pid";
+const WORKERS_FILE_NAME: &str = "sidecar-workers.pid";
+/// Binary name of the sidecar, as the OS lists it.
+const PROCESS_NAME: &str = "aws-cost-optimizer-api";
+
+fn data_path(app: &AppHandle, name: &str) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join(name)
+}
+
+fn pid_path(app: &AppHandle) -> PathBuf {
+    data_path(app, FILE_NAME)
+}
+
+fn parse_pid(content: &str) -> Option<u32> {
+    content.trim().parse().ok().filter(|pid| *pid > 1)
+}
+
+fn parse_pids(content: &str) -> Vec<u32> {
+    content.lines().filter_map(parse_pid).collect()
+}
+
+/// Remembers `pid` as the running sidecar.
+pub(crate) fn record(app: &AppHandle, pid: u32) {
+    let path = pid_path(app);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Err(err) = std::fs::write(&path, pid.to_string()) {
+        eprintln!("could not write {}: {err}", path.display());
+    }
+}
+
+/// Forgets the sidecar once it has been stopped.
+pub(crate) fn clear(app: &AppHandle) {
+    let _ = std::fs::remove_file(pid_path(app));
+}
+
+/// Remembers `pids` as the running worker sidecars, replacing the last list.
+pub(crate) fn record_workers(app: &AppHandle, pids: &[u32]) {
+    let path = data_path(app, WORKERS_FILE_NAME);
+    if pids.is_empty() {
+        let _ = std::fs::remove_file(&path);
+        return;
+    }
+    let content: Vec<String> = pids.iter().map(u32::to_string).collect();
+    if let Err(err) = std::fs::write(&path, content.join("\n")) {
+        eprintln!("could not write {}: {err}", path.display());
+    }
+}
+
+/// Whether process `pid` still exists.
+#[cfg(unix)]
+fn alive(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks that the process exists.
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+/// Waits up to `grace` for process `pid` to exit; whether it did.
+#[cfg(unix)]
+pub(crate) fn wait_gone(pid: u32, grace: Duration) -> bool {
+    let deadline = std::time::Instant::now() + grace;
+    while alive(pid) {
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    true
+}
+
+/// Whether process `pid` is a sidecar, by its command line (Linux truncates
+/// the bare process name).
+fn is_sidecar(pid: u32) -> bool {
+    #[cfg(unix)]
+    let output = Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "args="])
+        .output();
+    #[cfg(windows)]
+    let output = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/FO", "CSV", "/NH"])
+        .output();
+    output.is_ok_and(|out| String::from_utf8_lossy(&out.stdout).contains(PROCESS_NAME))
+}
+
+/// The recorded sidecar's pid, if that process is still running.
+pub(crate) fn running(app: &AppHandle) -> Option<u32> {
+    std::fs::read_to_string(pid_path(app))
+        .ok()
+        .as_deref()
+        .and_then(parse_pid)
+        .filter(|pid| is_sidecar(*pid))
+}
+
+/// Stops process `pid`: SIGTERM first on Unix, SIGKILL if it has not exited
+/// within `grace`. Whether it is gone.
+fn terminate(pid: u32, grace: Duration) -> bool {
+    #[cfg(unix)]
+    {
+        // SAFETY: only called for a pid just checked to be a sidecar.
+        unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+        if wait_gone(pid, grace) {
+            return true;
+        }
+        unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+        wait_gone(pid, grace)
+    }
+    #[cfg(windows)]
+    {
+        let _ = grace;
+        Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+}
+
+/// Stops the sidecar and workers left running by an earlier run of the app,
+/// if any; call before spawning a new one.
+pub(crate) fn reap_orphan(app: &AppHandle, grace: Duration) {
+    let workers = data_path(app, WORKERS_FILE_NAME);
+    let orphans = std::fs::read_to_string(&workers).unwrap_or_default();
+    let mut stopped = true;
+    for pid in parse_pids(&orphans) {
+        if is_sidecar(pid) {
+            eprintln!("stopping orphaned worker sidecar {pid} from a previous run");
+            stopped &= terminate(pid, grace);
+        }
+    }
+    if stopped {
+        let _ = std::fs::remove_file(&workers);
+    }
+
+    let Some(pid) = std::fs::read_to_string(pid_path(app))
+        .ok()
+        .as_deref()
+        .and_then(parse_pid)
+    else {
+        return;
+    };
+    if is_sidecar(pid) {
+        eprintln!("stopping orphaned sidecar {pid} from a previous run");
+        if !terminate(pid, grace) {
+            eprintln!("orphaned sidecar {pid} is still running");
+            return;
+        }
+    }
+    clear(app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_real_pids_are_read() {
+        assert_eq!(parse_pid("4242\n"), Some(4242));
+        assert_eq!(parse_pid("1"), None);
+        assert_eq!(parse_pid("0"), None);
+        assert_eq!(parse_pid("not a pid"), None);
+    }
+
+    #[test]
+    fn worker_pids_are_read_one_per_line() {
+        assert_eq!(parse_pids("4242\n4343\n1\njunk\n"), vec![4242, 4343]);
+        assert!(parse_pids("").is_empty());
+    }
+}
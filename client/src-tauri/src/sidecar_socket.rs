@@ -0,0 +1,336 @@
+//! HTTP over the Unix domain socket the sidecar listens on. The socket lives
+//! in a fresh directory only the current user can enter, so no other local
+//! user can reach the backend, and no TCP port is taken. Requests are plain
+//! HTTP/1.1 with `Connection: close`, one connection each; the API is local
+//! and small enough that pooling would not pay off.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::AppError;
+
+const SOCKET_NAME: &str = "api.sock";
+/// Replies larger than this are refused rather than buffered.
+const MAX_RESPONSE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Creates a private directory for the next sidecar's socket and returns the
+/// socket's path. Kept under the temp dir: socket paths are limited to about
+/// 100 bytes, which app data dirs can exceed.
+#[cfg_attr(dev, allow(dead_code))]
+pub(crate) fn create() -> std::io::Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!(
+        "aws-cost-optimizer-{}",
+        uuid::Uuid::new_v4().simple()
+    ));
+    std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    Ok(dir.join(SOCKET_NAME))
+}
+
+/// Removes a socket's directory once its sidecar is gone.
+pub(crate) fn remove(socket: &Path) {
+    if let Some(dir) = socket.parent() {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+/// Sends one request to the sidecar at `socket` and returns its status,
+/// body and `ETag`, including non-2xx responses.
+pub(crate) fn send_tagged(
+    socket: &Path,
+    method: &str,
+    target: &str,
+    headers: &[(&str, &str)],
+    body: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<(u16, String, Option<String>), AppError> {
+    let mut stream = UnixStream::connect(socket).map_err(unreachable)?;
+    stream.set_read_timeout(timeout).map_err(unreachable)?;
+    stream.set_write_timeout(timeout).map_err(unreachable)?;
+
+    let body = body.unwrap_or("");
+    let request = request_head(method, target, headers, body.len());
+    stream.write_all(request.as_bytes()).map_err(unreachable)?;
+    stream.write_all(body.as_bytes()).map_err(unreachable)?;
+
+    let mut raw = Vec::new();
+    stream
+        .take(MAX_RESPONSE_BYTES + 1)
+        .read_to_end(&mut raw)
+        .map_err(unreachable)?;
+    let (status, body) = read_reply(&raw)?;
+    Ok((status, body, etag(&raw)))
+}
+
+/// [`send_tagged`] for async callers: a bodyless request, abandoned after
+/// `timeout`.
+pub(crate) async fn send_async(
+    socket: &Path,
+    method: &str,
+    target: &str,
+    headers: &[(&str, &str)],
+    timeout: Duration,
+) -> Result<(u16, String), AppError> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let exchange = async {
+        let mut stream = tokio::net::UnixStream::connect(socket).await?;
+        let request = request_head(method, target, headers, 0);
+        stream.write_all(request.as_bytes()).await?;
+        let mut raw = Vec::new();
+        stream
+            .take(MAX_RESPONSE_BYTES + 1)
+            .read_to_end(&mut raw)
+            .await?;
+        Ok::<_, std::io::Error>(raw)
+    };
+    let raw = tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| AppError::Sidecar("Backend did not reply in time".into()))?
+        .map_err(unreachable)?;
+    read_reply(&raw)
+}
+
+/// Sends a bodyless request and returns the status and a reader of the body
+/// as it arrives, for streamed replies.
+pub(crate) fn open(
+    socket: &Path,
+    method: &str,
+    target: &str,
+    headers: &[(&str, &str)],
+) -> Result<(u16, Box<dyn Read + Send>), AppError> {
+    let mut stream = UnixStream::connect(socket).map_err(unreachable)?;
+    stream
+        .write_all(request_head(method, target, headers, 0).as_bytes())
+        .map_err(unreachable)?;
+    let mut reader = BufReader::new(stream);
+    let mut head = String::new();
+    loop {
+        let read = reader.read_line(&mut head).map_err(unreachable)?;
+        if read == 0 || head.len() as u64 > MAX_RESPONSE_BYTES {
+            return Err(malformed());
+        }
+        if head.ends_with("\r\n\r\n") {
+            break;
+        }
+    }
+    let (status, chunked, length) = parse_head(head.trim_end()).ok_or_else(malformed)?;
+    let body: Box<dyn Read + Send> = match (chunked, length) {
+        (true, _) => Box::new(Dechunker::new(reader)),
+        (false, Some(length)) => Box::new(reader.take(length as u64)),
+        (false, None) => Box::new(reader),
+    };
+    Ok((status, body))
+}
+
+fn unreachable(e: std::io::Error) -> AppError {
+    AppError::Sidecar(format!("Backend unreachable: {e}"))
+}
+
+fn request_head(method: &str, target: &str, headers: &[(&str, &str)], length: usize) -> String {
+    let mut request = format!(
+        "{method} {target} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+         Content-Type: application/json\r\nContent-Length: {length}\r\n"
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    request.push_str("\r\n");
+    request
+}
+
+fn read_reply(raw: &[u8]) -> Result<(u16, String), AppError> {
+    if raw.len() as u64 > MAX_RESPONSE_BYTES {
+        return Err(AppError::Sidecar("Backend reply is too large".into()));
+    }
+    parse_response(raw)
+}
+
+fn malformed() -> AppError {
+    AppError::Sidecar("Malformed reply from the backend".into())
+}
+
+/// The status of a response head, whether its body is chunked, and its
+/// length if given.
+fn parse_head(head: &str) -> Option<(u16, bool, Option<usize>)> {
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|code| code.parse().ok())?;
+
+    let mut chunked = false;
+    let mut length = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.eq_ignore_ascii_case("chunked");
+        } else if name.eq_ignore_ascii_case("content-length") {
+            length = value.parse::<usize>().ok();
+        }
+    }
+    Some((status, chunked, length))
+}
+
+/// The `ETag` in a raw response's head.
+fn etag(raw: &[u8]) -> Option<String> {
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&raw[..split]).ok()?;
+    head.split("\r\n").skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("etag")
+            .then(|| value.trim().to_string())
+    })
+}
+
+/// Splits an HTTP/1.1 response into status and body, undoing chunked
+/// transfer encoding.
+fn parse_response(raw: &[u8]) -> Result<(u16, String), AppError> {
+    let split = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(malformed)?;
+    let head = std::str::from_utf8(&raw[..split]).map_err(|_| malformed())?;
+    let payload = &raw[split + 4..];
+    let (status, chunked, length) = parse_head(head).ok_or_else(malformed)?;
+
+    let body = if chunked {
+        dechunk(payload)?
+    } else {
+        let end = length.map_or(payload.len(), |n| n.min(payload.len()));
+        payload[..end].to_vec()
+    };
+    String::from_utf8(body)
+        .map(|body| (status, body))
+        .map_err(|_| malformed())
+}
+
+/// Undoes chunked transfer encoding as the body is read.
+struct Dechunker<R> {
+    inner: R,
+    /// Bytes left in the current chunk.
+    left: usize,
+    started: bool,
+    done: bool,
+}
+
+impl<R: BufRead> Dechunker<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            left: 0,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Reads the next chunk's size line, after the CRLF ending the previous
+    /// chunk.
+    fn next_chunk(&mut self) -> std::io::Result<usize> {
+        let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidData, "bad chunk");
+        let mut line = String::new();
+        if std::mem::replace(&mut self.started, true) {
+            self.inner.read_line(&mut line)?;
+            if line != "\r\n" {
+                return Err(invalid());
+            }
+            line.clear();
+        }
+        self.inner.read_line(&mut line)?;
+        let size_hex = line.split(';').next().unwrap_or("").trim();
+        usize::from_str_radix(size_hex, 16).map_err(|_| invalid())
+    }
+}
+
+impl<R: BufRead> Read for Dechunker<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.left == 0 {
+            self.left = self.next_chunk()?;
+            if self.left == 0 {
+                self.done = true;
+                return Ok(0);
+            }
+        }
+        let want = buf.len().min(self.left);
+        let read = self.inner.read(&mut buf[..want])?;
+        if read == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        self.left -= read;
+        Ok(read)
+    }
+}
+
+fn dechunk(mut payload: &[u8]) -> Result<Vec<u8>, AppError> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = payload
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(malformed)?;
+        let size_line = std::str::from_utf8(&payload[..line_end]).map_err(|_| malformed())?;
+        let size_hex = size_line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_hex, 16).map_err(|_| malformed())?;
+        payload = &payload[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        if payload.len() < size {
+            return Err(malformed());
+        }
+        body.extend_from_slice(&payload[..size]);
+        payload = payload.get(size + 2..).ok_or_else(malformed)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replies_are_read_with_either_framing() {
+        let plain =
+            b"HTTP/1.1 404 Not Found\r\ncontent-length: 22\r\n\r\n{\"detail\":\"Not Found\"}";
+        assert_eq!(
+            parse_response(plain).unwrap(),
+            (404, r#"{"detail":"Not Found"}"#.to_string())
+        );
+
+        let chunked =
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n[1,2\r\n2\r\n,3\r\n1\r\n]\r\n0\r\n\r\n";
+        assert_eq!(
+            parse_response(chunked).unwrap(),
+            (200, "[1,2,3]".to_string())
+        );
+
+        assert!(parse_response(b"garbage").is_err());
+        assert_eq!(
+            etag(b"HTTP/1.1 304 Not Modified\r\netag: W/\"ab\"\r\n\r\n").as_deref(),
+            Some(r#"W/"ab""#)
+        );
+        assert_eq!(etag(plain), None);
+    }
+
+    #[test]
+    fn chunked_bodies_are_read_as_they_arrive() {
+        let payload = b"5\r\nevent\r\n3\r\n: a\r\n0\r\n\r\n";
+        let mut body = String::new();
+        Dechunker::new(&payload[..])
+            .read_to_string(&mut body)
+            .unwrap();
+        assert_eq!(body, "event: a");
+
+        let mut body = String::new();
+        assert!(Dechunker::new(&b"zz\r\n"[..])
+            .read_to_string(&mut body)
+            .is_err());
+    }
+}
@@ -0,0 +1,110 @@
+//! What the sidecar costs the machine: CPU, memory and uptime from the OS,
+//! and how fast it answered its last health check. The bundled server is a
+//! PyInstaller executable whose bootloader starts the Python process as a
+//! child, so the figures cover the sidecar and its children.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, Manager};
+
+use crate::error::CommandResult;
+use crate::{backend, SidecarState};
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct BackendStats {
+    /// `None` when no sidecar is running (or in dev builds, where the server
+    /// runs separately); the process fields are then `None` too.
+    pub pid: Option<u32>,
+    pub uptime_secs: Option<u64>,
+    /// Share of one core; above 100 when several are busy.
+    pub cpu_percent: Option<f32>,
+    pub memory_bytes: Option<u64>,
+    pub health_ok: Option<bool>,
+    pub health_latency_ms: Option<u64>,
+    pub health_checked_at: Option<DateTime<Utc>>,
+}
+
+/// Samples `pid` and its children twice, CPU usage being measured between
+/// refreshes.
+fn sample(pid: u32, stats: &mut BackendStats) {
+    let mut system = System::new();
+    let kind = ProcessRefreshKind::nothing().with_cpu().with_memory();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+
+    let Some(process) = system.process(Pid::from_u32(pid)) else {
+        return;
+    };
+    let (cpu, memory) = family(&system, process).fold((0.0, 0), |(cpu, memory), p| {
+        (cpu + p.cpu_usage(), memory + p.memory())
+    });
+    stats.uptime_secs = Some(process.run_time());
+    stats.cpu_percent = Some(cpu);
+    stats.memory_bytes = Some(memory);
+}
+
+/// `process` and its children.
+fn family<'a>(system: &'a System, process: &'a Process) -> impl Iterator<Item = &'a Process> {
+    let root = process.pid();
+    let children = system
+        .processes()
+        .values()
+        .filter(move |child| child.parent() == Some(root));
+    std::iter::once(process).chain(children)
+}
+
+/// Sidecar `pid` and its children's pids, the sidecar first.
+#[cfg(unix)]
+pub(crate) fn pids(pid: u32) -> Vec<u32> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+    match system.process(Pid::from_u32(pid)) {
+        Some(process) => family(&system, process).map(|p| p.pid().as_u32()).collect(),
+        None => vec![pid],
+    }
+}
+
+/// Resident memory of sidecar `pid` and its children, if it is running.
+#[cfg_attr(dev, allow(dead_code))]
+pub(crate) fn memory(pid: u32) -> Option<u64> {
+    let mut system = System::new();
+    let kind = ProcessRefreshKind::nothing().with_memory();
+    system.refresh_processes_specifics(ProcessesToUpdate::All, true, kind);
+    let process = system.process(Pid::from_u32(pid))?;
+    Some(family(&system, process).map(Process::memory).sum())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// The sidecar's resource use, with a fresh health check.
+#[tauri::command]
+pub async fn get_backend_stats(app: AppHandle) -> CommandResult<BackendStats> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let pid = app
+            .state::<SidecarState>()
+            .0
+            .lock()
+            .ok()
+            .and_then(|child| child.as_ref().map(|child| child.pid()));
+        let mut stats = BackendStats {
+            pid,
+            ..BackendStats::default()
+        };
+        if let Some(pid) = pid {
+            sample(pid, &mut stats);
+        }
+        backend::healthy();
+        if let Some(probe) = backend::last_health() {
+            stats.health_ok = Some(probe.ok);
+            stats.health_latency_ms = Some(probe.latency.as_millis() as u64);
+            stats.health_checked_at = Some(probe.at);
+        }
+        Ok(stats)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
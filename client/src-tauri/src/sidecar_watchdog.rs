@@ -0,0 +1,370 @@
+//! Restarts the FastAPI sidecar when it exits on its own. Every spawned
+//! sidecar is watched; when the one in [`SidecarState`] terminates (a replaced
+//! or stopped one exited on purpose), it is started again with the current
+//! credentials after an exponential backoff. A sidecar that keeps crashing
+//! soon after each restart keeps the backoff growing.
+//!
+//! The sidecar's lifecycle reaches the UI as `backend://starting`,
+//! `backend://ready`, `backend://crashed` and `backend://restarting` events,
+//! for every start, not just the watchdog's; `backend://stopped` tells it
+//! that launch found nothing to start. The current [`BackendPhase`] is
+//! kept too, so a webview that loads mid-start knows whether to wait.
+//!
+//! Crashes and failed restarts both count toward crash-loop detection: after
+//! [`CRASH_LIMIT`] within [`CRASH_WINDOW`] the watchdog gives up and emits
+//! `backend-failed` with the exit code and the sidecar's last stderr lines.
+//! Saving or switching credentials starts the sidecar again as usual.
+//!
+//! The watch also writes the sidecar's output to the backend log (see
+//! `sidecar_log`).
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::process::CommandEvent;
+
+use crate::sidecar_log::SidecarLog;
+use crate::{read_credentials, restart_sidecar, sso, SidecarState};
+
+pub const STARTING_EVENT: &str = "backend://starting";
+pub const READY_EVENT: &str = "backend://ready";
+pub const CRASHED_EVENT: &str = "backend://crashed";
+pub const RESTARTING_EVENT: &str = "backend://restarting";
+pub const STOPPED_EVENT: &str = "backend://stopped";
+pub const FAILED_EVENT: &str = "backend-failed";
+const CRASH_LIMIT: usize = 3;
+const CRASH_WINDOW: Duration = Duration::from_secs(60);
+/// Stderr lines kept per sidecar for diagnostics.
+const STDERR_LINES: usize = 40;
+const FIRST_DELAY: Duration = Duration::from_secs(1);
+const MAX_DELAY: Duration = Duration::from_secs(60);
+/// A sidecar up this long resets the backoff when it next crashes.
+const STABLE_AFTER: Duration = Duration::from_secs(120);
+
+/// Restarts so far in the current run of crashes, and when the last one
+/// came up.
+static RESTARTS: Mutex<Option<(u32, Instant)>> = Mutex::new(None);
+/// When the sidecar crashed or failed to restart, within [`CRASH_WINDOW`].
+static CRASHES: Mutex<VecDeque<Instant>> = Mutex::new(VecDeque::new());
+
+/// Where the sidecar is in its lifecycle.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendPhase {
+    /// Not running, and not about to be.
+    Stopped,
+    /// Starting or restarting; requests should wait for `backend://ready`.
+    Starting,
+    Ready,
+    /// Suspended or stopped while the app is idle; the next request wakes
+    /// it (see `sidecar_idle`).
+    Idle,
+}
+
+static PHASE: Mutex<BackendPhase> = Mutex::new(BackendPhase::Stopped);
+
+pub(crate) fn set_phase(phase: BackendPhase) {
+    *PHASE.lock().unwrap_or_else(|e| e.into_inner()) = phase;
+}
+
+/// Payload of `backend://starting`: the process is up, the API not yet.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendStarting {
+    pub pid: u32,
+    /// The API root it will answer on.
+    pub url: String,
+}
+
+/// Payload of `backend://ready`: the API answers its health check.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendReady {
+    pub pid: u32,
+    pub startup_ms: u64,
+}
+
+/// Payload of `backend://crashed`: the sidecar exited without being asked
+/// to, or a restart did not bring it up.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendCrashed {
+    /// The exited process; `None` for a failed restart.
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub restart_error: Option<String>,
+}
+
+/// Payload of `backend://stopped`: no sidecar was started, and none will be
+/// until credentials are saved.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendStopped {
+    pub reason: String,
+}
+
+/// Payload of `backend://restarting`: restart `attempt` follows in
+/// `delay_secs`.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendRestarting {
+    pub attempt: u32,
+    pub delay_secs: u64,
+}
+
+pub(crate) fn starting(app: &AppHandle, pid: u32, url: String) {
+    set_phase(BackendPhase::Starting);
+    let _ = app.emit(STARTING_EVENT, BackendStarting { pid, url });
+}
+
+pub(crate) fn ready(app: &AppHandle, pid: u32, since: Instant) {
+    let startup_ms = since.elapsed().as_millis() as u64;
+    set_phase(BackendPhase::Ready);
+    crate::resilience::reset();
+    let _ = app.emit(READY_EVENT, BackendReady { pid, startup_ms });
+}
+
+/// Reports that launch left the sidecar stopped on purpose.
+#[cfg_attr(dev, allow(dead_code))]
+pub(crate) fn stopped(app: &AppHandle, reason: &str) {
+    set_phase(BackendPhase::Stopped);
+    let _ = app.emit(
+        STOPPED_EVENT,
+        BackendStopped {
+            reason: reason.into(),
+        },
+    );
+}
+
+/// Reports a sidecar that did not come up at launch.
+pub(crate) fn start_failed(app: &AppHandle, error: String) {
+    eprintln!("sidecar did not start: {error}");
+    set_phase(BackendPhase::Stopped);
+    let _ = app.emit(
+        CRASHED_EVENT,
+        BackendCrashed {
+            pid: None,
+            exit_code: None,
+            restart_error: Some(error),
+        },
+    );
+}
+
+/// Payload of `backend-failed`: the watchdog stopped restarting.
+#[derive(Serialize, Clone, Debug)]
+pub struct BackendFailed {
+    pub crashes: usize,
+    pub window_secs: u64,
+    /// Exit code of the last crash, if the OS gave one.
+    pub exit_code: Option<i32>,
+    /// The last restart's error, if it was a restart that failed.
+    pub restart_error: Option<String>,
+    /// The crashed sidecar's last stderr lines, oldest first.
+    pub stderr: Vec<String>,
+}
+
+/// Records a crash at `now`; whether `crashes` now amounts to a crash loop.
+fn crash_loop(crashes: &mut VecDeque<Instant>, now: Instant) -> bool {
+    crashes.push_back(now);
+    while crashes
+        .front()
+        .is_some_and(|at| now.duration_since(*at) > CRASH_WINDOW)
+    {
+        crashes.pop_front();
+    }
+    crashes.len() >= CRASH_LIMIT
+}
+
+fn record_crash() -> Option<usize> {
+    let mut crashes = CRASHES.lock().unwrap_or_else(|e| e.into_inner());
+    crash_loop(&mut crashes, Instant::now()).then(|| {
+        let count = crashes.len();
+        crashes.clear();
+        count
+    })
+}
+
+/// Wait before restart `attempt` (1-based): 1s, 2s, 4s, … up to a minute.
+fn backoff(attempt: u32) -> Duration {
+    FIRST_DELAY
+        .saturating_mul(1u32 << attempt.saturating_sub(1).min(16))
+        .min(MAX_DELAY)
+}
+
+/// Attempt number the next restart continues from.
+fn previous_attempts(now: Instant) -> u32 {
+    match *RESTARTS.lock().unwrap_or_else(|e| e.into_inner()) {
+        Some((attempts, at)) if now.duration_since(at) < STABLE_AFTER => attempts,
+        _ => 0,
+    }
+}
+
+fn give_up(
+    app: &AppHandle,
+    crashes: usize,
+    exit_code: Option<i32>,
+    restart_error: Option<String>,
+    stderr: Vec<String>,
+) {
+    eprintln!("sidecar crashed {crashes} times within {CRASH_WINDOW:?}; not restarting it");
+    set_phase(BackendPhase::Stopped);
+    let _ = app.emit(
+        FAILED_EVENT,
+        BackendFailed {
+            crashes,
+            window_secs: CRASH_WINDOW.as_secs(),
+            exit_code,
+            restart_error,
+            stderr,
+        },
+    );
+}
+
+/// Restarts the sidecar if `pid` was the current one, retrying until it is
+/// up, credentials are gone, something else started one, or it crash-loops.
+fn on_exit(app: &AppHandle, pid: u32, code: Option<i32>, stderr: Vec<String>) {
+    let state = app.state::<SidecarState>();
+    {
+        let Ok(mut guard) = state.0.lock() else {
+            return;
+        };
+        if guard.as_ref().map(|child| child.pid()) != Some(pid) {
+            return;
+        }
+        guard.take();
+    }
+    eprintln!("sidecar exited unexpectedly (code {code:?})");
+    set_phase(BackendPhase::Starting);
+    let _ = app.emit(
+        CRASHED_EVENT,
+        BackendCrashed {
+            pid: Some(pid),
+            exit_code: code,
+            restart_error: None,
+        },
+    );
+    if let Some(crashes) = record_crash() {
+        give_up(app, crashes, code, None, stderr);
+        return;
+    }
+
+    let mut attempt = previous_attempts(Instant::now());
+    loop {
+        attempt += 1;
+        let delay = backoff(attempt);
+        let _ = app.emit(
+            RESTARTING_EVENT,
+            BackendRestarting {
+                attempt,
+                delay_secs: delay.as_secs(),
+            },
+        );
+        std::thread::sleep(delay);
+
+        let started = state.0.lock().map_or(true, |child| child.is_some());
+        let creds = read_credentials(app).filter(|_| sso::access_granted(app));
+        let Some(creds) = creds.filter(|_| !started) else {
+            if !started {
+                set_phase(BackendPhase::Stopped);
+            }
+            return;
+        };
+        match restart_sidecar(app, &creds) {
+            Ok(()) => {
+                // `restart_sidecar` has announced it as ready.
+                *RESTARTS.lock().unwrap_or_else(|e| e.into_inner()) =
+                    Some((attempt, Instant::now()));
+                return;
+            }
+            Err(error) => {
+                eprintln!("sidecar restart {attempt} failed: {error}");
+                let _ = app.emit(
+                    CRASHED_EVENT,
+                    BackendCrashed {
+                        pid: None,
+                        exit_code: None,
+                        restart_error: Some(error.clone()),
+                    },
+                );
+                if let Some(crashes) = record_crash() {
+                    give_up(app, crashes, code, Some(error), stderr);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Watches one spawned sidecar's events until it terminates, writing its
+/// output to the backend log.
+pub(crate) fn watch(
+    app: AppHandle,
+    pid: u32,
+    mut events: tauri::async_runtime::Receiver<CommandEvent>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut log = SidecarLog::open(&app);
+        log.write("watchdog", format!("sidecar {pid} started").as_bytes());
+        let mut stderr = VecDeque::with_capacity(STDERR_LINES);
+        while let Some(event) = events.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => log.write("stdout", &line),
+                CommandEvent::Error(error) => log.write("watchdog", error.as_bytes()),
+                CommandEvent::Stderr(line) => {
+                    log.write("stderr", &line);
+                    if stderr.len() == STDERR_LINES {
+                        stderr.pop_front();
+                    }
+                    stderr.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+                }
+                CommandEvent::Terminated(payload) => {
+                    let exit = format!("sidecar {pid} exited with code {:?}", payload.code);
+                    log.write("watchdog", exit.as_bytes());
+                    let stderr = stderr.into_iter().collect();
+                    let _ = tauri::async_runtime::spawn_blocking(move || {
+                        on_exit(&app, pid, payload.code, stderr)
+                    })
+                    .await;
+                    break;
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Whether the sidecar is up, starting, or not running. Dev builds use a
+/// separately run server, which is taken to be up.
+#[tauri::command]
+pub fn get_backend_phase() -> BackendPhase {
+    if cfg!(dev) {
+        return BackendPhase::Ready;
+    }
+    *PHASE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_a_minute() {
+        let secs: Vec<u64> = (1..=8).map(|n| backoff(n).as_secs()).collect();
+        assert_eq!(secs, [1, 2, 4, 8, 16, 32, 60, 60]);
+        assert_eq!(backoff(u32::MAX), MAX_DELAY);
+    }
+
+    #[test]
+    fn three_crashes_within_a_minute_are_a_loop() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut crashes = VecDeque::new();
+        assert!(!crash_loop(&mut crashes, at(0)));
+        assert!(!crash_loop(&mut crashes, at(50)));
+        // The first crash has left the window.
+        assert!(!crash_loop(&mut crashes, at(100)));
+        assert!(crash_loop(&mut crashes, at(110)));
+    }
+}
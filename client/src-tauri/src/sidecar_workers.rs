@@ -0,0 +1,503 @@
+//! Worker sidecars for long analyses. A scan of a whole organization keeps a
+//! sidecar busy for minutes, and every other request used to wait behind it.
+//! Scans now go to one of up to `max_workers` extra sidecars, each on its own
+//! endpoint with its own token, while the main sidecar answers everything
+//! else. A worker starts when a scan finds none idle, and all of them stop
+//! with the main sidecar.
+//!
+//! Progress and cancel requests follow their scan to the worker running it.
+//! Runs land in the database every sidecar shares, so the main sidecar
+//! serves them as before.
+
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::sync::{MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use secrecy::zeroize::Zeroizing;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_shell::process::CommandChild;
+
+use crate::backend::{self, Endpoint};
+use crate::error::{AppError, CommandResult};
+use crate::{health_check, settings, AwsCredentials, SidecarState};
+
+const MAX_WORKERS: u32 = 8;
+/// How long a worker gets to accept a connection before a scan goes to the
+/// main sidecar instead.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WorkerSettings {
+    /// Extra sidecars scans may run on; 0 runs them on the main sidecar.
+    pub max_workers: u32,
+}
+
+impl Default for WorkerSettings {
+    fn default() -> Self {
+        Self { max_workers: 1 }
+    }
+}
+
+struct Worker {
+    child: CommandChild,
+    endpoint: Endpoint,
+    authorization: Zeroizing<String>,
+    /// Scans running on it.
+    jobs: usize,
+}
+
+/// The worker sidecars, kept in [`SidecarState`].
+#[derive(Default)]
+pub struct Workers {
+    pool: Vec<Worker>,
+    /// Workers being started, counted against the limit.
+    starting: u32,
+    /// Bumped by [`stop_all`], so a worker that finishes starting after it
+    /// is stopped rather than kept.
+    generation: u64,
+    /// Scan id to the pid of the worker running the scan.
+    scans: HashMap<String, u32>,
+}
+
+/// Where to send a request for one worker, copied out of the lock.
+#[derive(Clone)]
+struct Target {
+    pid: u32,
+    endpoint: Endpoint,
+    authorization: Zeroizing<String>,
+}
+
+impl Target {
+    fn of(worker: &Worker) -> Self {
+        Self {
+            pid: worker.child.pid(),
+            endpoint: worker.endpoint.clone(),
+            authorization: worker.authorization.clone(),
+        }
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+        timeout: Option<Duration>,
+    ) -> Result<(u16, String), AppError> {
+        backend::send_to(
+            &self.endpoint,
+            Some(self.authorization.as_str()),
+            method,
+            path,
+            body,
+            timeout,
+        )
+    }
+
+    /// Whether the worker accepts connections. Nothing is sent, so a scan
+    /// can still go elsewhere when it does not.
+    fn reachable(&self) -> bool {
+        match &self.endpoint {
+            Endpoint::Tcp(port) => {
+                let address = SocketAddr::from((Ipv4Addr::LOCALHOST, *port));
+                TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).is_ok()
+            }
+            #[cfg(unix)]
+            Endpoint::Socket(socket) => std::os::unix::net::UnixStream::connect(socket).is_ok(),
+        }
+    }
+}
+
+/// A request a worker takes instead of the main sidecar.
+#[derive(Debug, PartialEq, Eq)]
+enum Job {
+    /// A scan, with its id when the request names one.
+    Scan(Option<String>),
+    /// Progress, events or cancellation of the scan with this id.
+    Follow(String),
+}
+
+fn classify(method: &str, path: &str, body: Option<&str>) -> Option<Job> {
+    let path = path.split('?').next().unwrap_or(path);
+    let rest = path.strip_prefix("/optimizer/scan")?;
+    if rest.is_empty() {
+        if method != "POST" {
+            return None;
+        }
+        let scan_id = body
+            .and_then(|body| serde_json::from_str::<serde_json::Value>(body).ok())
+            .and_then(|body| body.get("scan_id")?.as_str().map(str::to_owned));
+        return Some(Job::Scan(scan_id));
+    }
+    let (scan_id, action) = rest.strip_prefix('/')?.split_once('/')?;
+    match (method, action) {
+        ("GET", "progress" | "events") | ("POST", "cancel") if !scan_id.is_empty() => {
+            Some(Job::Follow(scan_id.to_owned()))
+        }
+        _ => None,
+    }
+}
+
+/// Set by [`init`], for starting workers from a request.
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Lets scans start workers; never called under `tauri dev`, whose server
+/// is run by hand.
+pub fn init(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+fn lock(app: &AppHandle) -> MutexGuard<'_, Workers> {
+    app.state::<SidecarState>()
+        .inner()
+        .1
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+fn record(app: &AppHandle, workers: &Workers) {
+    let pids: Vec<u32> = workers.pool.iter().map(|w| w.child.pid()).collect();
+    crate::sidecar_pid::record_workers(app, &pids);
+}
+
+fn least_busy(workers: &Workers) -> Option<usize> {
+    (0..workers.pool.len()).min_by_key(|&index| workers.pool[index].jobs)
+}
+
+fn stop(worker: Worker) {
+    let pid = worker.child.pid();
+    if !crate::stop_gracefully(worker.child) {
+        eprintln!("worker sidecar {pid} did not stop");
+    }
+    #[cfg(unix)]
+    {
+        if let Endpoint::Socket(path) = &worker.endpoint {
+            crate::sidecar_socket::remove(path);
+        }
+    }
+}
+
+/// Waits for a new worker to answer its health check, as long as the main
+/// sidecar may take at launch.
+fn wait_ready(target: &Target) -> bool {
+    let config = health_check::current();
+    let deadline = Instant::now() + Duration::from_secs(config.startup_timeout_secs);
+    loop {
+        let reply = target.send("GET", &config.path, None, Some(config.request_timeout()));
+        if matches!(reply, Ok((200..=299, _))) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(config.poll_interval());
+    }
+}
+
+/// Spawns a worker with the saved credentials and waits for it.
+#[cfg(not(dev))]
+fn start(app: &AppHandle) -> Result<Worker, String> {
+    let creds = crate::read_credentials(app)
+        .filter(|_| crate::sso::access_granted(app))
+        .ok_or("No credentials are saved")?;
+    let crate::SpawnedSidecar {
+        child,
+        events,
+        endpoint,
+        token,
+    } = crate::spawn_process(app, &creds)?;
+    let pid = child.pid();
+    // Logged with the main sidecar; its exit restarts nothing.
+    crate::sidecar_watchdog::watch(app.clone(), pid, events);
+    let worker = Worker {
+        child,
+        endpoint,
+        authorization: Zeroizing::new(format!("Bearer {}", token.as_str())),
+        jobs: 0,
+    };
+    if !wait_ready(&Target::of(&worker)) {
+        stop(worker);
+        return Err(format!("Worker sidecar {pid} did not answer"));
+    }
+    eprintln!("worker sidecar {pid} started");
+    Ok(worker)
+}
+
+#[cfg(dev)]
+fn start(_app: &AppHandle) -> Result<Worker, String> {
+    Err("Workers are not started under tauri dev".into())
+}
+
+/// Picks a worker for a scan: an idle one, else a new one while under
+/// `limit`, else the least busy. `None` when there is none to pick.
+fn acquire(app: &AppHandle, limit: u32, scan_id: Option<&str>) -> Option<Target> {
+    let mut workers = lock(app);
+    let idle = workers.pool.iter().position(|worker| worker.jobs == 0);
+    let index = match idle {
+        Some(index) => index,
+        None if workers.pool.len() as u32 + workers.starting < limit => {
+            workers.starting += 1;
+            let generation = workers.generation;
+            drop(workers);
+            let started = start(app);
+            workers = lock(app);
+            workers.starting -= 1;
+            match started {
+                Ok(worker) if workers.generation == generation => {
+                    workers.pool.push(worker);
+                    record(app, &workers);
+                    workers.pool.len() - 1
+                }
+                Ok(worker) => {
+                    drop(workers);
+                    stop(worker);
+                    return None;
+                }
+                Err(err) => {
+                    eprintln!("worker sidecar not started: {err}");
+                    least_busy(&workers)?
+                }
+            }
+        }
+        None => least_busy(&workers)?,
+    };
+    let worker = &mut workers.pool[index];
+    worker.jobs += 1;
+    let target = Target::of(worker);
+    if let Some(scan_id) = scan_id {
+        workers.scans.insert(scan_id.to_owned(), target.pid);
+    }
+    Some(target)
+}
+
+/// Marks a scan on `target` done, stopping the worker when it failed or is
+/// idle beyond `limit`.
+fn release(app: &AppHandle, target: &Target, scan_id: Option<&str>, limit: u32, failed: bool) {
+    let mut workers = lock(app);
+    if let Some(scan_id) = scan_id {
+        workers.scans.remove(scan_id);
+    }
+    let Some(index) = workers
+        .pool
+        .iter()
+        .position(|worker| worker.child.pid() == target.pid)
+    else {
+        return;
+    };
+    let worker = &mut workers.pool[index];
+    worker.jobs = worker.jobs.saturating_sub(1);
+    let surplus = worker.jobs == 0 && workers.pool.len() > limit as usize;
+    if failed || surplus {
+        let worker = workers.pool.remove(index);
+        record(app, &workers);
+        drop(workers);
+        stop(worker);
+    }
+}
+
+/// The worker running the scan with this id.
+fn running(app: &AppHandle, scan_id: &str) -> Option<Target> {
+    let workers = lock(app);
+    let pid = *workers.scans.get(scan_id)?;
+    workers
+        .pool
+        .iter()
+        .find(|worker| worker.child.pid() == pid)
+        .map(Target::of)
+}
+
+/// Where to open a streamed GET that follows a scan on a worker, with the
+/// worker's authorization. `None` leaves it to the main sidecar.
+pub(crate) fn stream_target(path: &str) -> Option<(Endpoint, Zeroizing<String>)> {
+    let Job::Follow(scan_id) = classify("GET", path, None)? else {
+        return None;
+    };
+    let target = running(APP.get()?, &scan_id)?;
+    Some((target.endpoint, target.authorization))
+}
+
+/// Sends a request a worker can take to one, within `timeout`. `None` leaves
+/// it to the main sidecar: not a scan, no worker available, or a worker that
+/// could not be reached. Once a scan has gone out, a failure is the caller's:
+/// the scan may have started, and sending it again would run it twice.
+pub(crate) fn dispatch(
+    method: &str,
+    path: &str,
+    body: Option<&str>,
+    timeout: Option<Duration>,
+) -> Option<Result<(u16, String), AppError>> {
+    let job = classify(method, path, body)?;
+    let app = APP.get()?;
+    match job {
+        Job::Follow(scan_id) => {
+            let target = running(app, &scan_id)?;
+            Some(target.send(method, path, body, timeout))
+        }
+        Job::Scan(scan_id) => {
+            let limit = settings::load(app).workers.max_workers;
+            if limit == 0 {
+                return None;
+            }
+            let target = acquire(app, limit, scan_id.as_deref())?;
+            if !target.reachable() {
+                release(app, &target, scan_id.as_deref(), limit, true);
+                eprintln!(
+                    "worker sidecar {} unreachable; scanning on the main sidecar",
+                    target.pid
+                );
+                return None;
+            }
+            let reply = target.send(method, path, body, timeout);
+            release(app, &target, scan_id.as_deref(), limit, reply.is_err());
+            Some(reply)
+        }
+    }
+}
+
+/// Stops every worker; scans started later start new ones.
+pub(crate) fn stop_all(app: &AppHandle) {
+    let pool = {
+        let mut workers = lock(app);
+        workers.generation += 1;
+        workers.scans.clear();
+        std::mem::take(&mut workers.pool)
+    };
+    if pool.is_empty() {
+        return;
+    }
+    crate::sidecar_pid::record_workers(app, &[]);
+    for worker in pool {
+        stop(worker);
+    }
+}
+
+/// Stops idle workers beyond `limit`; busy ones stop once their scans end.
+fn retire(app: &AppHandle, limit: u32) {
+    let surplus = {
+        let mut workers = lock(app);
+        let mut surplus = Vec::new();
+        while workers.pool.len() > limit as usize {
+            let Some(index) = workers.pool.iter().position(|worker| worker.jobs == 0) else {
+                break;
+            };
+            surplus.push(workers.pool.remove(index));
+        }
+        if !surplus.is_empty() {
+            record(app, &workers);
+        }
+        surplus
+    };
+    for worker in surplus {
+        stop(worker);
+    }
+}
+
+/// Hands the workers new credentials, as the main sidecar was; one that does
+/// not take them is stopped.
+pub(crate) fn push_credentials(app: &AppHandle, creds: &AwsCredentials) {
+    let targets: Vec<Target> = lock(app).pool.iter().map(Target::of).collect();
+    let failed: Vec<u32> = targets
+        .iter()
+        .filter(|target| {
+            match backend::push_credentials_to(&target.endpoint, &target.authorization, creds) {
+                Ok(()) => false,
+                Err(err) => {
+                    eprintln!("worker sidecar {} kept old credentials: {err}", target.pid);
+                    true
+                }
+            }
+        })
+        .map(|target| target.pid)
+        .collect();
+    if failed.is_empty() {
+        return;
+    }
+    let stale: Vec<Worker> = {
+        let mut workers = lock(app);
+        let (stale, kept) = std::mem::take(&mut workers.pool)
+            .into_iter()
+            .partition(|worker| failed.contains(&worker.child.pid()));
+        workers.pool = kept;
+        workers.scans.retain(|_, pid| !failed.contains(pid));
+        record(app, &workers);
+        stale
+    };
+    for worker in stale {
+        stop(worker);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_worker_settings(app: AppHandle) -> WorkerSettings {
+    settings::load(&app).workers
+}
+
+/// Saves the worker limit; idle workers beyond it stop now.
+#[tauri::command]
+pub async fn save_worker_settings(app: AppHandle, config: WorkerSettings) -> CommandResult<()> {
+    if config.max_workers > MAX_WORKERS {
+        return Err(AppError::InvalidInput(format!(
+            "At most {MAX_WORKERS} workers can run"
+        )));
+    }
+    let limit = config.max_workers;
+    let mut all = settings::load(&app);
+    all.workers = config;
+    settings::save(&app, &all)?;
+    tauri::async_runtime::spawn_blocking(move || retire(&app, limit))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_scans_and_their_follow_ups_go_to_workers() {
+        assert_eq!(
+            classify("POST", "/optimizer/scan", Some(r#"{"scan_id":"abc"}"#)),
+            Some(Job::Scan(Some("abc".into())))
+        );
+        assert_eq!(
+            classify("POST", "/optimizer/scan", Some("{}")),
+            Some(Job::Scan(None))
+        );
+        assert_eq!(
+            classify("GET", "/optimizer/scan/abc/progress", None),
+            Some(Job::Follow("abc".into()))
+        );
+        assert_eq!(
+            classify("GET", "/optimizer/scan/abc/events", None),
+            Some(Job::Follow("abc".into()))
+        );
+        assert_eq!(
+            classify("POST", "/optimizer/scan/abc/cancel", None),
+            Some(Job::Follow("abc".into()))
+        );
+        assert_eq!(classify("GET", "/optimizer/scan", None), None);
+        assert_eq!(classify("GET", "/optimizer/runs", None), None);
+        assert_eq!(classify("POST", "/optimizer/scan//cancel", None), None);
+        assert_eq!(classify("POST", "/optimizer/scanner", None), None);
+    }
+
+    #[test]
+    fn scans_fall_back_only_when_the_worker_cannot_be_reached() {
+        let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let target = Target {
+            pid: 0,
+            endpoint: Endpoint::Tcp(listener.local_addr().unwrap().port()),
+            authorization: Zeroizing::new(String::new()),
+        };
+        assert!(target.reachable());
+        drop(listener);
+        assert!(!target.reachable());
+    }
+}
@@ -0,0 +1,427 @@
+//! Optional organization policy that gates opening the app (not AWS access)
+//! behind an OIDC device-code login against the company IdP (Okta, Azure AD).
+//!
+//! The policy lives in `org_policy.json` in the app config dir and is meant
+//! to be deployed by admins; the app never writes it. On every start the
+//! stored refresh token is redeemed, so disabling a user at the IdP locks
+//! them out of the cached billing data on their next launch. When the IdP
+//! is unreachable, a recent successful check keeps the app usable offline.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::{keyring_entry_for, read_credentials, reload_sidecar, stop_sidecar};
+
+pub(crate) const REFRESH_TOKEN_ACCOUNT: &str = "sso-refresh-token";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct OrgPolicy {
+    pub require_sso: bool,
+    /// OIDC issuer, e.g. `https://acme.okta.com/oauth2/default` or
+    /// `https://login.microsoftonline.com/<tenant>/v2.0`.
+    pub issuer: String,
+    pub client_id: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: String,
+    /// How long a successful check stays valid while the IdP is unreachable.
+    #[serde(default = "default_offline_hours")]
+    pub max_offline_hours: i64,
+}
+
+fn default_scopes() -> String {
+    "openid profile email offline_access".into()
+}
+
+fn default_offline_hours() -> i64 {
+    72
+}
+
+#[derive(Deserialize)]
+struct Discovery {
+    device_authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+#[derive(Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenError {
+    error: String,
+}
+
+#[derive(Deserialize)]
+struct UserInfo {
+    sub: String,
+    email: Option<String>,
+    name: Option<String>,
+}
+
+/// Last successful verification, used for the offline grace period.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct VerifiedSession {
+    user: String,
+    verified_at: String,
+}
+
+#[derive(Default)]
+struct Gate {
+    user: Option<String>,
+    pending_device_code: Option<String>,
+}
+
+#[derive(Default)]
+pub struct SsoState(Mutex<Gate>);
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SsoStatus {
+    pub required: bool,
+    pub authenticated: bool,
+    pub user: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DeviceLogin {
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    /// Seconds the UI should wait between `poll_sso_login` calls.
+    pub interval: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum LoginPoll {
+    Pending,
+    /// The IdP asked for a longer polling interval.
+    SlowDown,
+    Complete {
+        user: String,
+    },
+}
+
+enum RefreshError {
+    /// The IdP rejected the token: the user was disabled or the grant revoked.
+    Revoked,
+    Unreachable(String),
+}
+
+// ---------------------------------------------------------------------------
+// Storage helpers
+// ---------------------------------------------------------------------------
+
+fn policy(app: &AppHandle) -> Option<OrgPolicy> {
+    let path = app.path().app_config_dir().ok()?.join("org_policy.json");
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<OrgPolicy>(&content)
+        .ok()
+        .filter(|p| p.require_sso)
+}
+
+fn session_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("sso_session.json")
+}
+
+fn read_session(app: &AppHandle) -> Option<VerifiedSession> {
+    let content = std::fs::read_to_string(session_path(app)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_session(app: &AppHandle, user: &str) {
+    let session = VerifiedSession {
+        user: user.to_string(),
+        verified_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let path = session_path(app);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&session) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+fn forget_session(app: &AppHandle) {
+    let _ = std::fs::remove_file(session_path(app));
+    if let Ok(entry) = keyring_entry_for(REFRESH_TOKEN_ACCOUNT) {
+        let _ = entry.delete_credential();
+    }
+}
+
+fn store_refresh_token(token: Option<&str>) -> Result<(), String> {
+    match token {
+        Some(token) => keyring_entry_for(REFRESH_TOKEN_ACCOUNT)?
+            .set_password(token)
+            .map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// OIDC
+// ---------------------------------------------------------------------------
+
+fn agent() -> ureq::Agent {
+    crate::ca_trust::agent_builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+}
+
+fn discover(policy: &OrgPolicy) -> Result<Discovery, String> {
+    let url = format!(
+        "{}/.well-known/openid-configuration",
+        policy.issuer.trim_end_matches('/')
+    );
+    agent()
+        .get(&url)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| format!("Issuer does not support device login: {e}"))
+}
+
+fn user_info(endpoint: &str, access_token: &str) -> Result<String, String> {
+    let info: UserInfo = agent()
+        .get(endpoint)
+        .set("Authorization", &format!("Bearer {access_token}"))
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    Ok(info.email.or(info.name).unwrap_or(info.sub))
+}
+
+fn refresh(policy: &OrgPolicy, refresh_token: &str) -> Result<String, RefreshError> {
+    let discovery = discover(policy).map_err(RefreshError::Unreachable)?;
+    let response = agent().post(&discovery.token_endpoint).send_form(&[
+        ("grant_type", "refresh_token"),
+        ("client_id", &policy.client_id),
+        ("refresh_token", refresh_token),
+    ]);
+    let tokens: TokenResponse = match response {
+        Ok(response) => response
+            .into_json()
+            .map_err(|e| RefreshError::Unreachable(e.to_string()))?,
+        Err(ureq::Error::Status(400 | 401, _)) => return Err(RefreshError::Revoked),
+        Err(err) => return Err(RefreshError::Unreachable(err.to_string())),
+    };
+    // Rotating IdPs invalidate the old refresh token once a new one is issued.
+    store_refresh_token(tokens.refresh_token.as_deref()).map_err(RefreshError::Unreachable)?;
+    user_info(&discovery.userinfo_endpoint, &tokens.access_token).map_err(RefreshError::Unreachable)
+}
+
+fn within_grace(policy: &OrgPolicy, session: &VerifiedSession) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&session.verified_at).is_ok_and(|at| {
+        chrono::Utc::now().signed_duration_since(at)
+            < chrono::Duration::hours(policy.max_offline_hours)
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Gate
+// ---------------------------------------------------------------------------
+
+/// Whether the app may serve data: always without a policy, otherwise only
+/// after a successful login or startup check.
+pub fn access_granted(app: &AppHandle) -> bool {
+    if policy(app).is_none() {
+        return true;
+    }
+    let state = app.state::<SsoState>();
+    let granted = state.0.lock().is_ok_and(|gate| gate.user.is_some());
+    granted
+}
+
+/// Re-validates the stored login against the IdP. Called once at startup,
+/// before the sidecar is started.
+pub fn verify_on_startup(app: &AppHandle) {
+    let Some(policy) = policy(app) else {
+        return;
+    };
+    let Some(refresh_token) = keyring_entry_for(REFRESH_TOKEN_ACCOUNT)
+        .ok()
+        .and_then(|entry| entry.get_password().ok())
+    else {
+        return;
+    };
+
+    let user = match refresh(&policy, &refresh_token) {
+        Ok(user) => {
+            write_session(app, &user);
+            Some(user)
+        }
+        Err(RefreshError::Revoked) => {
+            forget_session(app);
+            None
+        }
+        Err(RefreshError::Unreachable(err)) => {
+            eprintln!("SSO check failed, using offline grace period: {err}");
+            read_session(app)
+                .filter(|s| within_grace(&policy, s))
+                .map(|s| s.user)
+        }
+    };
+    if let Ok(mut gate) = app.state::<SsoState>().0.lock() {
+        gate.user = user;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_sso_status(app: AppHandle) -> SsoStatus {
+    let required = policy(&app).is_some();
+    let user = app
+        .state::<SsoState>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|gate| gate.user.clone());
+    SsoStatus {
+        required,
+        authenticated: !required || user.is_some(),
+        user,
+    }
+}
+
+/// Starts a device-code login; the UI shows the code and verification URL.
+#[tauri::command]
+pub async fn start_sso_login(app: AppHandle) -> CommandResult<DeviceLogin> {
+    let policy = policy(&app).ok_or_else(|| {
+        AppError::Conflict("SSO is not required by the organization policy".into())
+    })?;
+    let authorization = tauri::async_runtime::spawn_blocking(move || {
+        let discovery = discover(&policy)?;
+        agent()
+            .post(&discovery.device_authorization_endpoint)
+            .send_form(&[
+                ("client_id", policy.client_id.as_str()),
+                ("scope", &policy.scopes),
+            ])
+            .map_err(|e| e.to_string())?
+            .into_json::<DeviceAuthorization>()
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    app.state::<SsoState>()
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .pending_device_code = Some(authorization.device_code);
+    Ok(DeviceLogin {
+        user_code: authorization.user_code,
+        verification_uri: authorization.verification_uri,
+        verification_uri_complete: authorization.verification_uri_complete,
+        expires_in: authorization.expires_in,
+        interval: authorization.interval,
+    })
+}
+
+/// Polls the IdP for the pending login. On completion the sidecar is started.
+#[tauri::command]
+pub async fn poll_sso_login(app: AppHandle) -> CommandResult<LoginPoll> {
+    let policy = policy(&app).ok_or_else(|| {
+        AppError::Conflict("SSO is not required by the organization policy".into())
+    })?;
+    let device_code = app
+        .state::<SsoState>()
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .pending_device_code
+        .clone()
+        .ok_or_else(|| AppError::Conflict("No SSO login in progress".into()))?;
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let discovery = discover(&policy)?;
+        let response = agent().post(&discovery.token_endpoint).send_form(&[
+            ("grant_type", DEVICE_CODE_GRANT),
+            ("client_id", &policy.client_id),
+            ("device_code", &device_code),
+        ]);
+        let tokens: TokenResponse = match response {
+            Ok(response) => response.into_json().map_err(|e| e.to_string())?,
+            Err(ureq::Error::Status(_, response)) => {
+                let error = response
+                    .into_json::<TokenError>()
+                    .map(|e| e.error)
+                    .unwrap_or_default();
+                return match error.as_str() {
+                    "authorization_pending" => Ok(LoginPoll::Pending),
+                    "slow_down" => Ok(LoginPoll::SlowDown),
+                    "" => Err("SSO login failed".into()),
+                    other => Err(format!("SSO login failed: {other}")),
+                };
+            }
+            Err(err) => return Err(err.to_string()),
+        };
+
+        store_refresh_token(tokens.refresh_token.as_deref())?;
+        let user = user_info(&discovery.userinfo_endpoint, &tokens.access_token)?;
+        write_session(&handle, &user);
+        {
+            let state = handle.state::<SsoState>();
+            let mut gate = state.0.lock().map_err(|e| e.to_string())?;
+            gate.pending_device_code = None;
+            gate.user = Some(user.clone());
+        }
+        if let Some(creds) = read_credentials(&handle) {
+            reload_sidecar(&handle, &creds)?;
+        }
+        Ok(LoginPoll::Complete { user })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(AppError::from)
+}
+
+/// Forgets the login and stops the sidecar; the app stays locked until the
+/// next login.
+#[tauri::command]
+pub fn sso_logout(app: AppHandle) -> CommandResult<()> {
+    forget_session(&app);
+    {
+        let state = app.state::<SsoState>();
+        let mut gate = state.0.lock().map_err(|e| e.to_string())?;
+        gate.user = None;
+        gate.pending_device_code = None;
+    }
+    if policy(&app).is_some() {
+        stop_sidecar(&app)?;
+    }
+    Ok(())
+}
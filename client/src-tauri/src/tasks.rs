@@ -0,0 +1,137 @@
+//! Cancellation for long-running commands (scans, exports, CUR ingestion).
+//! The caller picks a `task_id`, passes it to the cancellable command, and
+//! may call `cancel_task` with it while the command runs. Cancelled work
+//! stops at its next checkpoint, or immediately for async AWS calls, and
+//! fails with [`AppError::Cancelled`] before writing anything partial.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+
+use crate::error::AppError;
+
+/// Message of a cancelled task's error.
+pub const CANCELLED: &str = "Cancelled";
+
+#[derive(Default)]
+struct Flag {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<Flag>);
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Checkpoint for blocking work.
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.is_cancelled() {
+            Err(AppError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Relaxed);
+        self.0.notify.notify_waiters();
+    }
+
+    async fn cancelled(&self) {
+        loop {
+            // Created before the check so a cancel in between is not missed.
+            let notified = self.0.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Runs a future, dropping it (and any AWS request in flight) on cancel.
+    pub async fn run<T, E: From<AppError>>(
+        &self,
+        work: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        tokio::select! {
+            result = work => result,
+            _ = self.cancelled() => Err(AppError::Cancelled.into()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TaskState(Mutex<HashMap<String, CancelToken>>);
+
+/// Unregisters the task when the command finishes.
+pub struct TaskGuard {
+    app: AppHandle,
+    task_id: Option<String>,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if let Some(task_id) = &self.task_id {
+            let state = self.app.state::<TaskState>();
+            let mut tasks = state.0.lock().unwrap_or_else(|e| e.into_inner());
+            tasks.remove(task_id);
+        }
+    }
+}
+
+/// Token for a command run. Without a `task_id` the run cannot be cancelled.
+pub fn register(
+    app: &AppHandle,
+    task_id: Option<String>,
+) -> Result<(CancelToken, TaskGuard), AppError> {
+    let token = CancelToken::default();
+    let task_id = task_id.filter(|id| !id.is_empty());
+    if let Some(id) = &task_id {
+        let state = app.state::<TaskState>();
+        let mut tasks = state.0.lock().unwrap_or_else(|e| e.into_inner());
+        if tasks.contains_key(id) {
+            return Err(AppError::Conflict(format!(
+                "Task '{id}' is already running"
+            )));
+        }
+        tasks.insert(id.clone(), token.clone());
+    }
+    Ok((
+        token,
+        TaskGuard {
+            app: app.clone(),
+            task_id,
+        },
+    ))
+}
+
+/// Cancels the run registered under `task_id`, if any.
+pub fn cancel(app: &AppHandle, task_id: &str) -> bool {
+    let state = app.state::<TaskState>();
+    let tasks = state.0.lock().unwrap_or_else(|e| e.into_inner());
+    match tasks.get(task_id) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Cancels a running task. Returns `false` if no task has that id.
+#[tauri::command]
+pub fn cancel_task(app: AppHandle, task_id: String) -> bool {
+    cancel(&app, &task_id)
+}
@@ -0,0 +1,145 @@
+//! Terraform state correlation: marks which findings point at buckets that
+//! are managed by Terraform (and by which module), so users know whether to
+//! fix them in code or in the console.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, CommandResult};
+use crate::{backend, ca_trust};
+
+/// Where to read the state from: a local `terraform.tfstate` file, or a
+/// remote URL (e.g. the Terraform `http` backend) fetched with an optional
+/// bearer token.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StateSource {
+    File { path: String },
+    Http { url: String, token: Option<String> },
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct IacAnnotation {
+    pub recommendation_id: String,
+    pub bucket: String,
+    pub managed: bool,
+    /// Full resource addresses, e.g. `module.logs.aws_s3_bucket.this`.
+    pub addresses: Vec<String>,
+    /// Owning module of the bucket resource; `None` for the root module.
+    pub module: Option<String>,
+}
+
+// Subset of the v4 state format.
+#[derive(Deserialize)]
+struct State {
+    #[serde(default)]
+    resources: Vec<StateResource>,
+}
+
+#[derive(Deserialize)]
+struct StateResource {
+    module: Option<String>,
+    mode: String,
+    #[serde(rename = "type")]
+    resource_type: String,
+    name: String,
+    #[serde(default)]
+    instances: Vec<StateInstance>,
+}
+
+#[derive(Deserialize)]
+struct StateInstance {
+    #[serde(default)]
+    attributes: serde_json::Value,
+}
+
+struct ManagedBucket {
+    addresses: Vec<String>,
+    module: Option<String>,
+}
+
+fn read_state(source: &StateSource) -> Result<State, String> {
+    let raw = match source {
+        StateSource::File { path } => std::fs::read_to_string(path).map_err(|e| e.to_string())?,
+        StateSource::Http { url, token } => {
+            let mut request = ca_trust::agent().get(url);
+            if let Some(token) = token.as_deref().filter(|t| !t.is_empty()) {
+                request = request.set("Authorization", &format!("Bearer {token}"));
+            }
+            request
+                .call()
+                .map_err(|e| e.to_string())?
+                .into_string()
+                .map_err(|e| e.to_string())?
+        }
+    };
+    serde_json::from_str(&raw).map_err(|e| format!("Not a Terraform state file: {e}"))
+}
+
+/// Indexes every managed `aws_s3_bucket*` resource by bucket name. Bucket
+/// sub-resources (lifecycle, versioning, …) reference it via `bucket`.
+fn managed_buckets(state: &State) -> HashMap<String, ManagedBucket> {
+    let mut buckets: HashMap<String, ManagedBucket> = HashMap::new();
+    for resource in state
+        .resources
+        .iter()
+        .filter(|r| r.mode == "managed" && r.resource_type.starts_with("aws_s3_bucket"))
+    {
+        let address = match &resource.module {
+            Some(module) => format!("{module}.{}.{}", resource.resource_type, resource.name),
+            None => format!("{}.{}", resource.resource_type, resource.name),
+        };
+        for instance in &resource.instances {
+            let Some(bucket) = instance.attributes.get("bucket").and_then(|b| b.as_str()) else {
+                continue;
+            };
+            let entry = buckets
+                .entry(bucket.to_string())
+                .or_insert_with(|| ManagedBucket {
+                    addresses: Vec::new(),
+                    module: None,
+                });
+            if !entry.addresses.contains(&address) {
+                entry.addresses.push(address.clone());
+            }
+            if resource.resource_type == "aws_s3_bucket" {
+                entry.module = resource.module.clone();
+            }
+        }
+    }
+    buckets
+}
+
+fn correlate_blocking(run_id: &str, source: &StateSource) -> Result<Vec<IacAnnotation>, String> {
+    let state = read_state(source)?;
+    let buckets = managed_buckets(&state);
+    let run = backend::get_run(run_id)?;
+
+    Ok(run
+        .recommendations
+        .into_iter()
+        .map(|rec| {
+            let managed = buckets.get(&rec.bucket);
+            IacAnnotation {
+                recommendation_id: rec.id,
+                managed: managed.is_some(),
+                addresses: managed.map(|m| m.addresses.clone()).unwrap_or_default(),
+                module: managed.and_then(|m| m.module.clone()),
+                bucket: rec.bucket,
+            }
+        })
+        .collect())
+}
+
+/// Annotates every recommendation of a run with its Terraform ownership.
+#[tauri::command]
+pub async fn correlate_terraform_state(
+    run_id: String,
+    source: StateSource,
+) -> CommandResult<Vec<IacAnnotation>> {
+    tauri::async_runtime::spawn_blocking(move || correlate_blocking(&run_id, &source))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(AppError::from)
+}
@@ -0,0 +1,291 @@
+//! Chunked uploads of large local files, such as multi-gigabyte Cost and
+//! Usage Reports, to the backend. The shell reads the file from disk a chunk
+//! at a time, so the webview never holds it, and emits `upload-progress` as
+//! it goes. Uploads in progress are recorded in `app_data/uploads.json` by
+//! path: uploading the same, unchanged file again resumes where the backend
+//! stands instead of starting over.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::tasks::{self, CancelToken};
+use crate::{app_lock, backend, perf};
+
+const PROGRESS_EVENT: &str = "upload-progress";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+/// Bytes per request, before base64.
+const CHUNK_BYTES: usize = 4 * 1024 * 1024;
+
+/// The backend's view of an upload.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UploadStatus {
+    pub upload_id: String,
+    pub file_name: String,
+    pub size: u64,
+    /// Bytes received; the next chunk starts here.
+    pub offset: u64,
+    pub complete: bool,
+    /// Where the backend keeps the finished file.
+    pub path: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct UploadProgress {
+    pub upload_id: String,
+    pub path: String,
+    pub sent_bytes: u64,
+    pub total_bytes: u64,
+    pub percent: f64,
+    pub done: bool,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct UploadResult {
+    pub upload_id: String,
+    pub path: String,
+    pub size: u64,
+    /// Where the backend keeps the file.
+    pub stored_path: Option<String>,
+    /// Bytes the backend already had from an earlier attempt.
+    pub resumed_from: u64,
+}
+
+/// An unfinished upload, and the file it was started for.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct Pending {
+    upload_id: String,
+    size: u64,
+    /// Seconds since the epoch; a file changed since is uploaded afresh.
+    modified: u64,
+}
+
+fn pending_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("uploads.json")
+}
+
+fn read_pending(app: &AppHandle) -> HashMap<String, Pending> {
+    std::fs::read_to_string(pending_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Records, or with `None` forgets, the upload of `path`.
+fn write_pending(app: &AppHandle, path: &str, pending: Option<Pending>) -> Result<(), String> {
+    let mut all = read_pending(app);
+    match pending {
+        Some(pending) => all.insert(path.to_string(), pending),
+        None => all.remove(path),
+    };
+    let file = pending_path(app);
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&all).map_err(|e| e.to_string())?;
+    std::fs::write(file, json).map_err(|e| e.to_string())
+}
+
+/// The upload to carry on with for this file, if the backend still has it.
+fn resume(recorded: Option<&Pending>, file: &Pending) -> Result<Option<UploadStatus>, AppError> {
+    let Some(recorded) = recorded
+        .filter(|recorded| recorded.size == file.size && recorded.modified == file.modified)
+    else {
+        return Ok(None);
+    };
+    match backend::get_json::<UploadStatus>(&format!("/uploads/{}", recorded.upload_id)) {
+        Ok(status) if status.size == file.size => Ok(Some(status)),
+        Ok(_) | Err(AppError::NotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[derive(Serialize)]
+struct CreateUpload<'a> {
+    file_name: &'a str,
+    size: u64,
+}
+
+#[derive(Serialize)]
+struct Chunk {
+    offset: u64,
+    data: String,
+}
+
+struct Progress<'a> {
+    app: &'a AppHandle,
+    path: &'a str,
+    upload_id: &'a str,
+    total: u64,
+    last: Instant,
+}
+
+impl Progress<'_> {
+    fn report(&mut self, sent: u64) {
+        if self.last.elapsed() >= PROGRESS_INTERVAL {
+            self.last = Instant::now();
+            self.emit(sent, false);
+        }
+    }
+
+    fn emit(&self, sent: u64, done: bool) {
+        let percent = match self.total {
+            0 => 100.0,
+            total => sent as f64 / total as f64 * 100.0,
+        };
+        let _ = self.app.emit(
+            PROGRESS_EVENT,
+            UploadProgress {
+                upload_id: self.upload_id.to_string(),
+                path: self.path.to_string(),
+                sent_bytes: sent,
+                total_bytes: self.total,
+                percent: percent.clamp(0.0, 100.0),
+                done,
+            },
+        );
+    }
+}
+
+/// Uploads `path`, resuming an earlier attempt at the same file. A cancelled
+/// or failed upload stays recorded, to be resumed next time.
+pub fn upload_blocking(
+    app: &AppHandle,
+    path: &str,
+    cancel: &CancelToken,
+) -> Result<UploadResult, AppError> {
+    let mut file = File::open(path)?;
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        return Err(AppError::InvalidInput(format!("{path} is not a file")));
+    }
+    let this = Pending {
+        upload_id: String::new(),
+        size: metadata.len(),
+        modified: metadata
+            .modified()
+            .ok()
+            .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs()),
+    };
+    let mut status = match resume(read_pending(app).get(path), &this)? {
+        Some(status) => status,
+        None => {
+            let file_name = Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .ok_or_else(|| AppError::InvalidInput(format!("{path} names no file")))?;
+            let request = CreateUpload {
+                file_name: &file_name,
+                size: this.size,
+            };
+            let status: UploadStatus = backend::post_json("/uploads", &request)?;
+            let pending = Pending {
+                upload_id: status.upload_id.clone(),
+                ..this
+            };
+            write_pending(app, path, Some(pending))?;
+            status
+        }
+    };
+    let resumed_from = status.offset;
+    let upload_id = status.upload_id.clone();
+    let mut progress = Progress {
+        app,
+        path,
+        upload_id: &upload_id,
+        total: status.size,
+        last: Instant::now(),
+    };
+    let target = format!("/uploads/{upload_id}");
+    let mut buf = vec![0u8; CHUNK_BYTES];
+    while !status.complete {
+        cancel.check()?;
+        file.seek(SeekFrom::Start(status.offset))?;
+        let want = (status.size - status.offset).min(CHUNK_BYTES as u64) as usize;
+        file.read_exact(&mut buf[..want])?;
+        let chunk = Chunk {
+            offset: status.offset,
+            data: BASE64.encode(&buf[..want]),
+        };
+        status = match backend::put_json(&target, &chunk) {
+            Ok(status) => status,
+            // A chunk whose reply was lost may have landed: carry on from
+            // where the backend stands.
+            Err(AppError::Conflict(_)) => backend::get_json(&target)?,
+            Err(err) => return Err(err),
+        };
+        progress.report(status.offset);
+    }
+    progress.emit(status.offset, true);
+    write_pending(app, path, None)?;
+    Ok(UploadResult {
+        upload_id: status.upload_id,
+        path: path.to_string(),
+        size: status.size,
+        stored_path: status.path,
+        resumed_from,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Streams a local file to the backend in chunks. Pass a `task_id` to be
+/// able to stop it with `cancel_task`; calling again with the same path
+/// resumes.
+#[tauri::command]
+pub async fn upload_file(
+    app: AppHandle,
+    path: String,
+    task_id: Option<String>,
+) -> CommandResult<UploadResult> {
+    app_lock::ensure_unlocked(&app)?;
+    let handle = app.clone();
+    perf::measure(&app, "upload_file", async move {
+        tauri::async_runtime::spawn_blocking(perf::blocking(move || {
+            let (cancel, _task) = tasks::register(&handle, task_id)?;
+            upload_blocking(&handle, &path, &cancel)
+        }))
+        .await
+        .map_err(|e| e.to_string())?
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changed_files_are_not_resumed() {
+        let recorded = Pending {
+            upload_id: "a".into(),
+            size: 10,
+            modified: 100,
+        };
+        let grown = Pending {
+            size: 11,
+            ..recorded.clone()
+        };
+        let touched = Pending {
+            modified: 101,
+            ..recorded.clone()
+        };
+        assert!(resume(Some(&recorded), &grown).unwrap().is_none());
+        assert!(resume(Some(&recorded), &touched).unwrap().is_none());
+        assert!(resume(None, &recorded).unwrap().is_none());
+    }
+}
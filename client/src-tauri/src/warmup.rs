@@ -0,0 +1,55 @@
+//! Background cache warm-up after launch. Once startup has settled, the
+//! dashboard's default reads (month-to-date costs, the month-end forecast and
+//! the latest findings) are fetched one after another, so the first render is
+//! served from the caches instead of waiting on AWS.
+
+use std::time::Duration;
+
+use tauri::AppHandle;
+
+use crate::cost_explorer::{self, Granularity, GroupBy, GroupKind};
+use crate::{providers, read_credentials, sidecar_idle, sso};
+
+/// Leaves the sidecar and UI the first moments after launch.
+const START_DELAY: Duration = Duration::from_secs(10);
+
+async fn warm(app: &AppHandle) {
+    let queries = [
+        cost_explorer::month_to_date(Granularity::Daily, Vec::new()),
+        cost_explorer::month_to_date(
+            Granularity::Monthly,
+            vec![GroupBy {
+                kind: GroupKind::Dimension,
+                key: "SERVICE".into(),
+            }],
+        ),
+    ];
+    for query in &queries {
+        if let Err(err) = cost_explorer::query(app, query).await {
+            eprintln!("cache warm-up: cost query failed: {err}");
+            // Credentials or permissions problems will fail the rest too.
+            return;
+        }
+    }
+    if let Err(err) = cost_explorer::forecast(app).await {
+        eprintln!("cache warm-up: forecast failed: {err}");
+    }
+    let handle = app.clone();
+    let findings = tauri::async_runtime::spawn_blocking(move || {
+        sidecar_idle::unless_asleep(|| providers::collect_recommendations(&handle))
+    })
+    .await;
+    if let Ok(Some(Err(err))) = findings {
+        eprintln!("cache warm-up: findings failed: {err}");
+    }
+}
+
+/// Runs the warm-up once, in the background, when credentials are usable.
+pub fn spawn_warmup(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(START_DELAY).await;
+        if read_credentials(&app).is_some() && sso::access_granted(&app) {
+            warm(&app).await;
+        }
+    });
+}
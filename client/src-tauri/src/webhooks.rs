@@ -0,0 +1,323 @@
+//! Outbound webhooks: configurable endpoints that receive signed JSON event
+//! payloads, retried with exponential backoff, with a local delivery log.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::Sha256;
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, CommandResult};
+use crate::events::{AppEvent, EventKind};
+use crate::{ca_trust, keyring_entry_for, settings};
+
+const MAX_ATTEMPTS: u32 = 4;
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+const DELIVERY_LOG_LIMIT: usize = 200;
+
+/// Serializes writers of the delivery log across delivery threads.
+static DELIVERY_LOG_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub endpoints: Vec<WebhookEndpoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub url: String,
+    pub enabled: bool,
+    /// Event names to deliver; empty means every event.
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+    #[serde(default)]
+    pub payload_format: PayloadFormat,
+}
+
+/// Shape of the delivered JSON body.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    /// `{ "event", "timestamp", "data": { ... } }`
+    #[default]
+    Standard,
+    /// One level of stable snake_case fields, for no-code tools such as
+    /// Zapier or Make that map fields by name.
+    Flat,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: EventKind,
+    pub delivered: bool,
+    pub attempts: u32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub timestamp: String,
+}
+
+pub(crate) fn secret_account(webhook_id: &str) -> String {
+    format!("webhook:{webhook_id}")
+}
+
+fn read_secret(webhook_id: &str) -> Option<String> {
+    keyring_entry_for(&secret_account(webhook_id))
+        .ok()?
+        .get_password()
+        .ok()
+}
+
+// ---------------------------------------------------------------------------
+// Delivery log
+// ---------------------------------------------------------------------------
+
+fn deliveries_path(app: &AppHandle) -> std::path::PathBuf {
+    app.path()
+        .app_data_dir()
+        .expect("could not resolve app data dir")
+        .join("webhook_deliveries.json")
+}
+
+fn read_deliveries(app: &AppHandle) -> Vec<WebhookDelivery> {
+    std::fs::read_to_string(deliveries_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn record_delivery(app: &AppHandle, delivery: WebhookDelivery) {
+    let _guard = DELIVERY_LOG_LOCK.lock();
+    let mut log = read_deliveries(app);
+    log.insert(0, delivery);
+    log.truncate(DELIVERY_LOG_LIMIT);
+    let path = deliveries_path(app);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&log) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Payloads
+// ---------------------------------------------------------------------------
+
+fn event_name(event: EventKind) -> String {
+    serde_json::to_value(event)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Copies `value` into `out`, joining nested object keys with `_`. Arrays
+/// are kept as-is since flattening them would make field names unstable.
+fn flatten_into(out: &mut Map<String, Value>, prefix: &str, value: &Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let name = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}_{key}")
+                };
+                flatten_into(out, &name, value);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other.clone());
+        }
+    }
+}
+
+fn render_payload(event: &AppEvent, format: PayloadFormat) -> String {
+    match format {
+        PayloadFormat::Standard => serde_json::to_string(event).unwrap_or_default(),
+        PayloadFormat::Flat => {
+            let mut out = Map::new();
+            out.insert("event".into(), json!(event_name(event.event)));
+            out.insert("timestamp".into(), json!(event.timestamp));
+            out.insert("source".into(), json!("aws-cost-optimizer"));
+            flatten_into(&mut out, "", &event.data);
+            Value::Object(out).to_string()
+        }
+    }
+}
+
+/// Representative event data, matching what the run watcher publishes.
+fn sample_event(event: EventKind) -> AppEvent {
+    let status = match event {
+        EventKind::ScanCompleted => "scanned",
+        EventKind::RunScored => "scored",
+        EventKind::RunExecuted => "executed",
+    };
+    AppEvent::new(
+        event,
+        json!({
+            "run_id": "00000000-0000-4000-8000-000000000000",
+            "status": status,
+            "recommendation_count": 12,
+            "estimated_monthly_savings": 148.25,
+        }),
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Delivery
+// ---------------------------------------------------------------------------
+
+fn signature(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Exponential backoff with up to 50% jitter for the given 1-based attempt.
+pub(crate) fn backoff(attempt: u32) -> Duration {
+    let base = BASE_BACKOFF * 2u32.pow(attempt - 1);
+    let jitter = Duration::from_millis(fastrand::u64(0..=base.as_millis() as u64 / 2));
+    base + jitter
+}
+
+fn deliver(endpoint: &WebhookEndpoint, secret: Option<&str>, event: &AppEvent) -> WebhookDelivery {
+    let delivery_id = uuid::Uuid::new_v4().to_string();
+    let body = render_payload(event, endpoint.payload_format);
+    let event_name = event_name(event.event);
+
+    let mut attempts = 0;
+    let mut status_code = None;
+    let mut error = None;
+    while attempts < MAX_ATTEMPTS {
+        attempts += 1;
+        let mut request = ca_trust::agent()
+            .post(&endpoint.url)
+            .set("Content-Type", "application/json")
+            .set("User-Agent", "aws-cost-optimizer")
+            .set("X-Webhook-Event", &event_name)
+            .set("X-Webhook-Delivery", &delivery_id);
+        if let Some(secret) = secret {
+            request = request.set("X-Webhook-Signature", &signature(secret, &body));
+        }
+
+        match request.send_string(&body) {
+            Ok(response) => {
+                status_code = Some(response.status());
+                error = None;
+                break;
+            }
+            Err(ureq::Error::Status(code, _)) => {
+                status_code = Some(code);
+                error = Some(format!("HTTP {code}"));
+                // Client errors other than rate limiting will not succeed on retry.
+                if code < 500 && code != 429 {
+                    break;
+                }
+            }
+            Err(err) => error = Some(err.to_string()),
+        }
+        if attempts < MAX_ATTEMPTS {
+            std::thread::sleep(backoff(attempts));
+        }
+    }
+
+    WebhookDelivery {
+        id: delivery_id,
+        webhook_id: endpoint.id.clone(),
+        event: event.event,
+        delivered: error.is_none(),
+        attempts,
+        status_code,
+        error,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// Sends the event to every enabled, subscribed endpoint on background threads.
+pub fn dispatch(app: &AppHandle, event: &AppEvent) {
+    let endpoints = settings::load(app).webhooks.endpoints;
+    for endpoint in endpoints
+        .into_iter()
+        .filter(|e| e.enabled && (e.events.is_empty() || e.events.contains(&event.event)))
+    {
+        let app = app.clone();
+        let event = event.clone();
+        std::thread::spawn(move || {
+            let secret = read_secret(&endpoint.id);
+            let delivery = deliver(&endpoint, secret.as_deref(), &event);
+            record_delivery(&app, delivery);
+        });
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn list_webhooks(app: AppHandle) -> Vec<WebhookEndpoint> {
+    settings::load(&app).webhooks.endpoints
+}
+
+/// Creates or updates an endpoint (an empty `id` creates a new one). A
+/// non-empty `secret` replaces the stored signing secret.
+#[tauri::command]
+pub fn save_webhook(
+    app: AppHandle,
+    mut endpoint: WebhookEndpoint,
+    secret: Option<String>,
+) -> CommandResult<WebhookEndpoint> {
+    if !endpoint.url.starts_with("https://") && !endpoint.url.starts_with("http://") {
+        return Err(AppError::InvalidInput(
+            "Webhook URL must start with http:// or https://".into(),
+        ));
+    }
+    if endpoint.id.is_empty() {
+        endpoint.id = uuid::Uuid::new_v4().to_string();
+    }
+    if let Some(secret) = secret.filter(|s| !s.is_empty()) {
+        keyring_entry_for(&secret_account(&endpoint.id))?
+            .set_password(&secret)
+            .map_err(|e| e.to_string())?;
+    }
+
+    let mut all = settings::load(&app);
+    let endpoints = &mut all.webhooks.endpoints;
+    match endpoints.iter_mut().find(|e| e.id == endpoint.id) {
+        Some(existing) => *existing = endpoint.clone(),
+        None => endpoints.push(endpoint.clone()),
+    }
+    settings::save(&app, &all)?;
+    Ok(endpoint)
+}
+
+#[tauri::command]
+pub fn delete_webhook(app: AppHandle, id: String) -> CommandResult<()> {
+    let mut all = settings::load(&app);
+    all.webhooks.endpoints.retain(|e| e.id != id);
+    settings::save(&app, &all)?;
+    if let Ok(entry) = keyring_entry_for(&secret_account(&id)) {
+        let _ = entry.delete_credential();
+    }
+    Ok(())
+}
+
+/// Most recent deliveries first.
+#[tauri::command]
+pub fn list_webhook_deliveries(app: AppHandle) -> Vec<WebhookDelivery> {
+    read_deliveries(&app)
+}
+
+/// Returns an example body for the event in the given format, so users can
+/// map fields in automation tools before a real event fires.
+#[tauri::command]
+pub fn sample_webhook_payload(event: EventKind, format: PayloadFormat) -> String {
+    render_payload(&sample_event(event), format)
+}
@@ -0,0 +1,221 @@
+//! Optional localhost WebSocket bridge that pushes every app event to
+//! subscribed clients (browser extensions, Stream Deck plugins, dashboards).
+//! Clients authenticate with the local API token, either as a `token` query
+//! parameter (browsers cannot set headers on WebSockets) or as a bearer
+//! `Authorization` header. Messages are the same JSON as webhook payloads.
+
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tungstenite::handshake::server::{Callback, ErrorResponse, Request, Response};
+use tungstenite::{Message, WebSocket};
+
+use crate::error::{AppError, CommandResult};
+use crate::events::AppEvent;
+use crate::{local_api, settings};
+
+const DEFAULT_PORT: u16 = 8766;
+const ACCEPT_POLL: Duration = Duration::from_millis(200);
+const CLIENT_POLL: Duration = Duration::from_millis(50);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WebSocketSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for WebSocketSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+/// Stop flag of the accept loop and the outbound queue of every client.
+#[derive(Default)]
+pub struct WebSocketState {
+    listener_stop: Mutex<Option<Arc<AtomicBool>>>,
+    clients: Mutex<Vec<Sender<String>>>,
+}
+
+/// Sends the event to every connected client; gone clients are dropped.
+pub fn broadcast(app: &AppHandle, event: &AppEvent) {
+    let Ok(text) = serde_json::to_string(event) else {
+        return;
+    };
+    if let Ok(mut clients) = app.state::<WebSocketState>().clients.lock() {
+        clients.retain(|client| client.send(text.clone()).is_ok());
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Connections
+// ---------------------------------------------------------------------------
+
+fn provided_token(request: &Request) -> Option<String> {
+    let from_query = request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+            .map(str::to_string)
+    });
+    from_query.or_else(|| {
+        request
+            .headers()
+            .get("Authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::to_string)
+    })
+}
+
+fn unauthorized() -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some("missing or invalid token".into()));
+    *response.status_mut() = tungstenite::http::StatusCode::UNAUTHORIZED;
+    response
+}
+
+/// Handshake check; a trait impl rather than a closure because the error
+/// type is a full HTTP response.
+struct TokenCheck<'a>(&'a str);
+
+impl Callback for TokenCheck<'_> {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        match provided_token(request) {
+            Some(provided) if local_api::token_matches(self.0, &provided) => Ok(response),
+            _ => Err(unauthorized()),
+        }
+    }
+}
+
+fn accept(stream: TcpStream, token: &str) -> Option<WebSocket<TcpStream>> {
+    stream.set_nonblocking(false).ok()?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).ok()?;
+    let socket = tungstenite::accept_hdr(stream, TokenCheck(token)).ok()?;
+    // Short reads let one thread interleave outbound events with control frames.
+    socket.get_ref().set_read_timeout(Some(CLIENT_POLL)).ok()?;
+    Some(socket)
+}
+
+fn serve_client(mut socket: WebSocket<TcpStream>, events: Receiver<String>) {
+    loop {
+        match events.recv_timeout(CLIENT_POLL) {
+            Ok(text) => {
+                if socket.send(Message::text(text)).is_err() {
+                    return;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            // The bridge was stopped or restarted.
+            Err(RecvTimeoutError::Disconnected) => {
+                let _ = socket.close(None);
+                let _ = socket.flush();
+                return;
+            }
+        }
+        match socket.read() {
+            // Pings are queued for a pong automatically; other input is ignored.
+            Ok(Message::Close(_)) => return,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) => {}
+            Err(_) => return,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Server lifecycle
+// ---------------------------------------------------------------------------
+
+fn stop(state: &WebSocketState) -> Result<(), String> {
+    if let Some(flag) = state
+        .listener_stop
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+    {
+        flag.store(true, Ordering::Relaxed);
+    }
+    // Dropping the senders disconnects every client thread.
+    state.clients.lock().map_err(|e| e.to_string())?.clear();
+    Ok(())
+}
+
+/// Starts (or restarts) the bridge according to the saved settings.
+pub fn apply(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<WebSocketState>();
+    stop(&state)?;
+
+    let cfg = settings::load(app).websocket;
+    if !cfg.enabled {
+        return Ok(());
+    }
+
+    let token = local_api::ensure_token()?;
+    let listener = TcpListener::bind(("127.0.0.1", cfg.port))
+        .map_err(|e| format!("Could not bind port {}: {e}", cfg.port))?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    *state.listener_stop.lock().map_err(|e| e.to_string())? = Some(stop_flag.clone());
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        while !stop_flag.load(Ordering::Relaxed) {
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(ACCEPT_POLL);
+                    continue;
+                }
+                Err(err) => {
+                    eprintln!("WebSocket bridge stopped: {err}");
+                    return;
+                }
+            };
+            let app = app.clone();
+            let token = token.clone();
+            std::thread::spawn(move || {
+                let Some(socket) = accept(stream, &token) else {
+                    return;
+                };
+                let (tx, rx) = mpsc::channel();
+                if let Ok(mut clients) = app.state::<WebSocketState>().clients.lock() {
+                    clients.push(tx);
+                }
+                serve_client(socket, rx);
+            });
+        }
+    });
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub fn get_websocket_settings(app: AppHandle) -> WebSocketSettings {
+    settings::load(&app).websocket
+}
+
+#[tauri::command]
+pub fn save_websocket_settings(app: AppHandle, config: WebSocketSettings) -> CommandResult<()> {
+    let mut all = settings::load(&app);
+    all.websocket = config;
+    settings::save(&app, &all)?;
+    apply(&app).map_err(AppError::from)
+}
@@ -1,7 +1,30 @@
 import { useEffect, useState, useCallback } from "react";
 import { Routes, Route, Navigate, Link, useNavigate } from "react-router-dom";
-import { invoke } from "@tauri-apps/api/core";
-import { api } from "./api/client";
+import { listen } from "@tauri-apps/api/event";
+import {
+  api,
+  backendIncompatible,
+  checkCompatibility,
+  command,
+  commandErrorMessage,
+} from "./api/client";
+import type {
+  BackendCrashed,
+  BackendExposure,
+  BackendFailed,
+  BackendLifecycle,
+  BackendMismatch,
+  BackendPhase,
+  BackendReady,
+  BackendRecycling,
+  BackendRestarted,
+  BackendRestarting,
+  BackendStarting,
+  BackendStatus,
+  BackendStopped,
+  CliProfilesChanged,
+  HealthCheckConfig,
+} from "./types";
 import Dashboard from "./pages/Dashboard";
 import RunDetail from "./pages/RunDetail";
 import AuditTrail from "./pages/AuditTrail";
@@ -10,6 +33,8 @@ import styles from "./App.module.css";
 
 // Check for updates a few seconds after launch so it never slows down startup.
 const UPDATE_CHECK_DELAY_MS = 5_000;
+// How often the backend is checked, unless the shell's health check settings say otherwise.
+const DEFAULT_HEALTH_INTERVAL_SECS = 10;
 
 const IS_TAURI = typeof window !== "undefined" && "__TAURI__" in window;
 
@@ -17,32 +42,76 @@ function AppShell() {
   const navigate = useNavigate();
   // null = first check still pending, true = online, false = offline
   const [backendOnline, setBackendOnline] = useState<boolean | null>(null);
+  // Whether the sidecar process exists even when it does not answer.
+  const [backendLive, setBackendLive] = useState<boolean | null>(null);
+  const [healthIntervalSecs, setHealthIntervalSecs] = useState(DEFAULT_HEALTH_INTERVAL_SECS);
   // null = not checked yet, string = new version available, false = up to date
   const [updateVersion, setUpdateVersion] = useState<string | null | false>(null);
   const [installing, setInstalling] = useState(false);
+  // Set when the shell and these assets are from different releases.
+  const [incompatible, setIncompatible] = useState<string | null>(null);
+  // Imported AWS CLI profiles whose keys changed on disk.
+  const [changedProfiles, setChangedProfiles] = useState<string[]>([]);
+  const [reimporting, setReimporting] = useState(false);
+  // Latest lifecycle event from the shell; null once the backend is ready.
+  const [lifecycle, setLifecycle] = useState<BackendLifecycle | null>(null);
+  // Set once the watchdog gives up on a crash-looping backend.
+  const [backendFailed, setBackendFailed] = useState<BackendFailed | null>(null);
+  // Set when others on the network or machine can reach the backend.
+  const [exposure, setExposure] = useState<BackendExposure | null>(null);
+  const [restartingBackend, setRestartingBackend] = useState(false);
+  const [restartError, setRestartError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    void checkCompatibility().then((mismatch) => {
+      if (mismatch) setIncompatible(`${mismatch.message} ${mismatch.remediation}`);
+    });
+    const unlisten = listen<BackendMismatch>("backend-incompatible", (event) => {
+      const error = backendIncompatible(event.payload);
+      setIncompatible(`${error.message} ${error.remediation}`);
+    });
+    return () => void unlisten.then((stop) => stop());
+  }, []);
 
   const checkHealth = useCallback(async () => {
     try {
-      await api.health();
-      setBackendOnline(true);
+      if (IS_TAURI) {
+        // The shell tells a running but unresponsive sidecar from a missing one.
+        const status = await command<BackendStatus>("get_backend_status");
+        // A backend asleep while the app was idle wakes on the next request.
+        const asleep = status.phase === "idle";
+        setBackendLive(status.live || asleep);
+        setBackendOnline(status.ready || asleep);
+      } else {
+        await api.health();
+        setBackendOnline(true);
+      }
     } catch {
       setBackendOnline(false);
     }
   }, []);
 
-  // Initial check on mount + poll every 10 s to catch crashes or recovery.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    command<HealthCheckConfig>("get_health_check_settings")
+      .then((config) => setHealthIntervalSecs(config.effective.ui_interval_secs))
+      .catch(() => {});
+  }, []);
+
+  // Initial check on mount + poll to catch crashes or recovery.
   useEffect(() => {
     void checkHealth();
-    const timer = setInterval(() => void checkHealth(), 10_000);
+    const timer = setInterval(() => void checkHealth(), healthIntervalSecs * 1_000);
     return () => clearInterval(timer);
-  }, [checkHealth]);
+  }, [checkHealth, healthIntervalSecs]);
 
   // Check for a new release once, a few seconds after launch.
   useEffect(() => {
     if (!IS_TAURI || import.meta.env.DEV) return;
     const timer = setTimeout(async () => {
       try {
-        const version = await invoke<string | null>("check_for_updates");
+        const version = await command<string | null>("check_for_updates");
         setUpdateVersion(version ?? false);
       } catch {
         // Updater not configured (missing pubkey, no network, etc.) — silently ignore.
@@ -55,7 +124,7 @@ function AppShell() {
   async function handleInstallUpdate() {
     setInstalling(true);
     try {
-      await invoke("install_update");
+      await command("install_update");
       // Tauri restarts the app automatically after installing.
     } catch (e) {
       console.error("Update install failed:", e);
@@ -67,11 +136,122 @@ function AppShell() {
   // to /settings before they can attempt a scan.
   useEffect(() => {
     if (!IS_TAURI || import.meta.env.DEV) return;
-    invoke<unknown>("load_credentials").then((creds) => {
-      if (!creds) navigate("/settings", { replace: true });
-    });
+    command<boolean>("has_credentials")
+      .then((present) => {
+        if (!present) navigate("/settings", { replace: true });
+      })
+      .catch(() => {
+        // An incompatible shell is reported by the banner below.
+      });
   }, [navigate]);
 
+  // Back to setup once the saved credentials are deleted.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    const unlisten = listen("credentials-cleared", () => navigate("/settings", { replace: true }));
+    return () => void unlisten.then((stop) => stop());
+  }, [navigate]);
+
+  // Follow the backend's lifecycle as the shell reports it, rather than
+  // waiting for the next health poll to fail or succeed. The window shows
+  // before the shell has started the backend, so catch up on that first.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    command<BackendPhase>("get_backend_phase")
+      .then((phase) => {
+        if (phase === "starting") setLifecycle((current) => current ?? { kind: "launching" });
+      })
+      .catch(() => {});
+    const listeners = [
+      listen<BackendStarting>("backend://starting", (event) =>
+        setLifecycle({ kind: "starting", ...event.payload }),
+      ),
+      listen<BackendReady>("backend://ready", () => {
+        setLifecycle(null);
+        setBackendFailed(null);
+        setBackendOnline(true);
+      }),
+      listen<BackendCrashed>("backend://crashed", (event) => {
+        setLifecycle({ kind: "crashed", ...event.payload });
+        setBackendOnline(false);
+      }),
+      listen<BackendRestarting>("backend://restarting", (event) =>
+        setLifecycle({ kind: "restarting", ...event.payload }),
+      ),
+      listen<BackendRecycling>("backend://recycling", (event) =>
+        setLifecycle({ kind: "recycling", ...event.payload }),
+      ),
+      listen<BackendStopped>("backend://stopped", () => setLifecycle(null)),
+      listen<BackendFailed>("backend-failed", (event) => {
+        setLifecycle(null);
+        setBackendFailed(event.payload);
+      }),
+    ];
+    return () => listeners.forEach((unlisten) => void unlisten.then((stop) => stop()));
+  }, []);
+
+  // Warn, loudly, when the backend can be reached by others.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    command<BackendExposure | null>("get_backend_exposure")
+      .then(setExposure)
+      .catch(() => {});
+    const unlisten = listen<BackendExposure>("backend-exposed", (event) =>
+      setExposure(event.payload),
+    );
+    return () => void unlisten.then((stop) => stop());
+  }, []);
+
+  // Offer to re-import CLI profiles whose keys were rotated outside the app,
+  // both those changed while it was closed and those changed since.
+  useEffect(() => {
+    if (!IS_TAURI) return;
+    command<string[]>("list_changed_cli_profiles")
+      .then(setChangedProfiles)
+      .catch(() => {});
+    const unlisten = listen<CliProfilesChanged>("aws-cli-profiles-changed", (event) =>
+      setChangedProfiles(event.payload.profiles),
+    );
+    return () => void unlisten.then((stop) => stop());
+  }, []);
+
+  async function handleRestartBackend() {
+    setRestartingBackend(true);
+    setRestartError(null);
+    try {
+      await command<BackendRestarted>("restart_backend");
+      setBackendFailed(null);
+      setLifecycle(null);
+      setBackendOnline(true);
+    } catch (e) {
+      setRestartError(commandErrorMessage(e));
+    } finally {
+      setRestartingBackend(false);
+    }
+  }
+
+  const restartButton = IS_TAURI && (
+    <button
+      className={styles.retryBtn}
+      disabled={restartingBackend}
+      onClick={() => void handleRestartBackend()}
+    >
+      {restartingBackend ? "Restarting…" : "Restart backend"}
+    </button>
+  );
+
+  async function handleReimport() {
+    setReimporting(true);
+    try {
+      await command("import_aws_cli_profiles", { profiles: changedProfiles });
+      setChangedProfiles(await command<string[]>("list_changed_cli_profiles"));
+    } catch (e) {
+      console.error("Re-import failed:", e);
+    } finally {
+      setReimporting(false);
+    }
+  }
+
   return (
     <div className={styles.shell}>
       <header className={styles.header}>
@@ -88,13 +268,59 @@ function AppShell() {
         </nav>
       </header>
 
-      {backendOnline === false && (
+      {incompatible && <div className={styles.offlineBanner}>{incompatible}</div>}
+
+      {exposure && (
+        <div className={styles.offlineBanner}>
+          <strong>Your backend is exposed.</strong> {exposure.problems.join(". ")}. Anyone who
+          reaches it can act with your AWS credentials; restart the app, and avoid shared
+          networks until this is fixed.
+        </div>
+      )}
+
+      {backendFailed && (
+        <div className={styles.offlineBanner}>
+          Backend crashed {backendFailed.crashes} times within {backendFailed.window_secs} s and
+          was not restarted
+          {backendFailed.exit_code !== null && ` (exit code ${backendFailed.exit_code})`}.
+          Save your credentials in <Link to="/settings">Settings</Link> or restart the app to
+          try again. {restartButton}
+          {restartError && <div>{restartError}</div>}
+          {backendFailed.stderr.length > 0 && (
+            <details>
+              <summary>Last output</summary>
+              <pre>{backendFailed.stderr.join("\n")}</pre>
+            </details>
+          )}
+        </div>
+      )}
+
+      {lifecycle && lifecycle.kind !== "ready" && (
+        <div className={styles.offlineBanner}>
+          {lifecycle.kind === "launching" || lifecycle.kind === "starting"
+            ? "Backend is starting…"
+            : lifecycle.kind === "restarting"
+              ? `Backend stopped unexpectedly; restarting in ${lifecycle.delay_secs} s (attempt ${lifecycle.attempt})…`
+              : lifecycle.kind === "recycling"
+                ? `Backend is using ${Math.round(lifecycle.memory_bytes / 2 ** 20)} MB; restarting it to free memory…`
+                : lifecycle.restart_error
+                  ? `Backend restart failed: ${lifecycle.restart_error}`
+                  : "Backend stopped unexpectedly."}
+        </div>
+      )}
+
+      {backendOnline === false && !lifecycle && !backendFailed && (
         <div className={styles.offlineBanner}>
-          Backend is unreachable. Check your AWS credentials in{" "}
-          <Link to="/settings">Settings</Link> or restart the app.{" "}
+          {backendLive
+            ? "Backend is running but not answering its health check; a long scan may be keeping it busy. "
+            : "Backend is unreachable. "}
+          Check your AWS credentials in <Link to="/settings">Settings</Link> or restart the
+          app.{" "}
           <button className={styles.retryBtn} onClick={() => void checkHealth()}>
             Retry now
-          </button>
+          </button>{" "}
+          {restartButton}
+          {restartError && <div>{restartError}</div>}
         </div>
       )}
 
@@ -111,15 +337,32 @@ function AppShell() {
         </div>
       )}
 
-      <main className={styles.main}>
-        <Routes>
-          <Route path="/" element={<Dashboard />} />
-          <Route path="/runs/:runId" element={<RunDetail />} />
-          <Route path="/runs/:runId/audit" element={<AuditTrail />} />
-          <Route path="/settings" element={<Settings />} />
-          <Route path="*" element={<Navigate to="/" replace />} />
-        </Routes>
-      </main>
+      {changedProfiles.length > 0 && (
+        <div className={styles.updateBanner}>
+          AWS CLI profile{changedProfiles.length > 1 ? "s" : ""} {changedProfiles.join(", ")}{" "}
+          changed on disk.{" "}
+          <button
+            className={styles.updateBtn}
+            disabled={reimporting}
+            onClick={() => void handleReimport()}
+          >
+            {reimporting ? "Re-importing…" : "Re-import"}
+          </button>
+        </div>
+      )}
+
+      {/* Pages from another release would misread what they are sent. */}
+      {!incompatible && (
+        <main className={styles.main}>
+          <Routes>
+            <Route path="/" element={<Dashboard />} />
+            <Route path="/runs/:runId" element={<RunDetail />} />
+            <Route path="/runs/:runId/audit" element={<AuditTrail />} />
+            <Route path="/settings" element={<Settings />} />
+            <Route path="*" element={<Navigate to="/" replace />} />
+          </Routes>
+        </main>
+      )}
     </div>
   );
 }
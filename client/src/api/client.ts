@@ -1,9 +1,20 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import type {
+  AnalysisError,
+  ApiVersion,
+  BackendMismatch,
+  BackendPhase,
+  BackendResponse,
+  CommandError,
   ExecuteRequest,
   ExecuteResponse,
   ExecutionAuditRecord,
+  ExportJob,
+  ExportRequest,
   RollbackRequest,
   RollbackResponse,
+  RequestPriority,
   RunDetails,
   RunSummary,
   ScanRequest,
@@ -12,7 +23,50 @@ import type {
   ScoreResponse,
 } from "../types";
 
-const BASE = "http://127.0.0.1:8000/api/v1";
+/** The separately run dev server, for the UI in a plain browser. */
+const DEV_BASE = "http://127.0.0.1:8000/api/v1";
+
+const IS_TAURI = typeof window !== "undefined" && "__TAURI__" in window;
+
+/** Longest a request waits for a starting backend before trying anyway. */
+const READY_TIMEOUT_MS = 30_000;
+
+let readiness: Promise<void> | null = null;
+
+/**
+ * Resolves once the backend answers. The shell starts it in the background,
+ * so the first requests may come before it is up; nothing waits when it is
+ * not starting at all (no credentials yet, or it has given up).
+ */
+function backendReady(): Promise<void> {
+  if (!IS_TAURI) return Promise.resolve();
+  readiness ??= new Promise<void>((resolve) => {
+    const stops = [
+      listen("backend://ready", () => done()),
+      listen("backend-failed", () => done()),
+      listen("backend://stopped", () => done()),
+    ];
+    const timer = setTimeout(() => done(), READY_TIMEOUT_MS);
+    function done() {
+      clearTimeout(timer);
+      stops.forEach((stop) => void stop.then((unlisten) => unlisten()));
+      resolve();
+    }
+    invoke<BackendPhase>("get_backend_phase").then(
+      (phase) => phase !== "starting" && done(),
+      () => done(),
+    );
+  });
+  return readiness;
+}
+
+if (IS_TAURI) {
+  const reset = () => {
+    readiness = null;
+  };
+  void listen("backend://starting", reset);
+  void listen("backend://recycling", reset);
+}
 
 class ApiError extends Error {
   constructor(
@@ -24,22 +78,56 @@ class ApiError extends Error {
   }
 }
 
-async function request<T>(path: string, init?: RequestInit): Promise<T> {
-  const res = await fetch(`${BASE}${path}`, {
+/**
+ * Sends one request to the backend and returns its status and body. With a
+ * `requestId` it can be cancelled with {@link cancelRequest}. Pass
+ * `"background"` as the `priority` of refreshes nobody is waiting on.
+ */
+async function send(
+  path: string,
+  init?: RequestInit,
+  requestId?: string,
+  priority?: RequestPriority,
+): Promise<BackendResponse> {
+  if (IS_TAURI) {
+    // The shell forwards it: the sidecar listens on a socket the webview
+    // cannot reach.
+    return invoke<BackendResponse>("backend_request", {
+      method: init?.method ?? "GET",
+      path,
+      body: typeof init?.body === "string" ? init.body : null,
+      requestId: requestId ?? null,
+      priority: priority ?? null,
+    });
+  }
+  const res = await fetch(`${DEV_BASE}${path}`, {
     headers: { "Content-Type": "application/json" },
     ...init,
   });
-  if (!res.ok) {
-    let detail = res.statusText;
-    try {
-      const body = await res.json();
-      detail = body.detail ?? detail;
-    } catch {
-      // ignore JSON parse errors
-    }
-    throw new ApiError(res.status, detail);
+  return { status: res.status, body: await res.text(), error: null };
+}
+
+/** The message for a non-2xx reply the shell did not already map. */
+function errorDetail(status: number, body: string): string {
+  try {
+    return JSON.parse(body).detail ?? `HTTP ${status}`;
+  } catch {
+    return `HTTP ${status}`;
   }
-  return res.json() as Promise<T>;
+}
+
+async function request<T>(
+  path: string,
+  init?: RequestInit,
+  requestId?: string,
+  priority?: RequestPriority,
+): Promise<T> {
+  await backendReady();
+  const { status, body, error } = await send(path, init, requestId, priority);
+  if (status < 200 || status >= 300) {
+    throw new ApiError(status, error?.message ?? errorDetail(status, body));
+  }
+  return JSON.parse(body) as T;
 }
 
 export const api = {
@@ -47,11 +135,16 @@ export const api = {
   health: () => request<{ status: string }>("/health"),
 
   // Optimizer workflow
-  scan: (req: ScanRequest) =>
-    request<ScanResponse>("/optimizer/scan", {
-      method: "POST",
-      body: JSON.stringify(req),
-    }),
+  /** Pass a `requestId` to be able to cancel the scan with `cancelRequest`. */
+  scan: (req: ScanRequest, requestId?: string) =>
+    request<ScanResponse>(
+      "/optimizer/scan",
+      {
+        method: "POST",
+        body: JSON.stringify(req),
+      },
+      requestId,
+    ),
 
   score: (req: ScoreRequest) =>
     request<ScoreResponse>("/optimizer/score", {
@@ -72,14 +165,160 @@ export const api = {
     }),
 
   // Run queries
-  listRuns: () => request<RunSummary[]>("/optimizer/runs"),
+  // Pass "background" for a refresh nobody is waiting on.
+  listRuns: (priority?: RequestPriority) =>
+    request<RunSummary[]>("/optimizer/runs", undefined, undefined, priority),
 
-  getRun: (runId: string) => request<RunDetails>(`/optimizer/runs/${runId}`),
+  getRun: (runId: string, priority?: RequestPriority) =>
+    request<RunDetails>(`/optimizer/runs/${runId}`, undefined, undefined, priority),
 
-  getAudit: (runId: string, executionId?: string) => {
+  getAudit: (runId: string, executionId?: string, priority?: RequestPriority) => {
     const qs = executionId ? `?execution_id=${encodeURIComponent(executionId)}` : "";
-    return request<ExecutionAuditRecord[]>(`/optimizer/runs/${runId}/audit${qs}`);
+    return request<ExecutionAuditRecord[]>(
+      `/optimizer/runs/${runId}/audit${qs}`,
+      undefined,
+      undefined,
+      priority,
+    );
   },
+
+  // Reports; download one to disk with the shell's `download_export`.
+  createExport: (req: ExportRequest) =>
+    request<ExportJob>("/exports", {
+      method: "POST",
+      body: JSON.stringify(req),
+    }),
+
+  getExport: (exportId: string) => request<ExportJob>(`/exports/${exportId}`),
 };
 
-export { ApiError };
+// ---------------------------------------------------------------------------
+// Shell commands
+// ---------------------------------------------------------------------------
+
+/**
+ * Command schema these webview assets were built against. Keep in step with
+ * `SCHEMA_VERSION` in `src-tauri/src/api_version.rs`.
+ */
+const SCHEMA_VERSION = 1;
+
+let handshake: Promise<CommandError | null> | null = null;
+
+/**
+ * Asks the shell for its schema once. Resolves to the error every command
+ * should fail with when the shell and these assets are out of step (e.g.
+ * after a partial update), or null when they are compatible.
+ */
+function checkCompatibility(): Promise<CommandError | null> {
+  handshake ??= invoke<ApiVersion>("get_api_version").then(
+    (version) =>
+      version.schema_version >= SCHEMA_VERSION &&
+      version.min_client_schema_version <= SCHEMA_VERSION
+        ? version.backend_mismatch
+          ? backendIncompatible(version.backend_mismatch)
+          : null
+        : incompatible(`The app's core is v${version.app_version}.`),
+    // Shells older than the handshake do not know the command.
+    () => incompatible("The app's core predates this interface."),
+  );
+  return handshake;
+}
+
+function incompatible(detail: string): CommandError {
+  return {
+    kind: "incompatible_version",
+    message: `This window and the app's core are from different releases. ${detail}`,
+    retryable: false,
+    remediation: "Restart the app to finish updating, or reinstall it.",
+  };
+}
+
+/** The error for a backend whose API differs from the one the shell expects. */
+function backendIncompatible(mismatch: BackendMismatch): CommandError {
+  const found =
+    mismatch.found === null
+      ? "an older API"
+      : `API v${mismatch.found}${mismatch.backend_version ? ` (${mismatch.backend_version})` : ""}`;
+  return {
+    kind: "incompatible_version",
+    message: `The app's backend speaks ${found} instead of v${mismatch.expected}.`,
+    retryable: false,
+    remediation: "Restart the app to finish updating, or reinstall it.",
+  };
+}
+
+/** Invokes a shell command once the schema handshake has passed. */
+async function command<T>(name: string, args?: Record<string, unknown>): Promise<T> {
+  const mismatch = await checkCompatibility();
+  if (mismatch) throw mismatch;
+  return invoke<T>(name, args);
+}
+
+/** Narrows a rejected `invoke` to the shell's structured error. */
+function isCommandError(e: unknown): e is CommandError {
+  return typeof e === "object" && e !== null && "kind" in e && "message" in e;
+}
+
+/** User-facing text for a failed `invoke`, with the remediation hint if any. */
+function commandErrorMessage(e: unknown): string {
+  if (!isCommandError(e)) return String(e);
+  return e.remediation ? `${e.message} ${e.remediation}` : e.message;
+}
+
+/**
+ * Cancels a backend request sent with this id; a cancelled scan also stops on
+ * the backend. Resolves to false if none is in flight.
+ */
+function cancelRequest(requestId: string): Promise<boolean> {
+  if (!IS_TAURI) return Promise.resolve(false);
+  return invoke<boolean>("cancel_request", { requestId });
+}
+
+/** Callbacks for {@link streamAnalysis}. */
+interface AnalysisHandlers<P, R> {
+  onProgress: (progress: P) => void;
+  onComplete: (result: R) => void;
+  onError: (error: AnalysisError) => void;
+}
+
+/**
+ * Relays a long analysis's progress from the backend, e.g. a scan's
+ * `/optimizer/scan/{scan_id}/events`. Resolves to a function that stops
+ * relaying; the analysis itself carries on.
+ */
+async function streamAnalysis<P, R>(
+  id: string,
+  path: string,
+  handlers: AnalysisHandlers<P, R>,
+): Promise<() => void> {
+  const unlisten = await Promise.all([
+    listen<P>(`analysis://${id}/progress`, (e) => handlers.onProgress(e.payload)),
+    listen<R>(`analysis://${id}/complete`, (e) => {
+      done();
+      handlers.onComplete(e.payload);
+    }),
+    listen<AnalysisError>(`analysis://${id}/error`, (e) => {
+      done();
+      handlers.onError(e.payload);
+    }),
+  ]);
+  function done() {
+    unlisten.forEach((stop) => stop());
+  }
+  await command("stream_analysis", { id, path });
+  return () => {
+    done();
+    void invoke("stop_analysis_stream", { id });
+  };
+}
+
+export {
+  ApiError,
+  backendIncompatible,
+  cancelRequest,
+  checkCompatibility,
+  command,
+  commandErrorMessage,
+  isCommandError,
+  streamAnalysis,
+};
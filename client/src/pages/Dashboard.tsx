@@ -47,7 +47,7 @@ export default function Dashboard() {
     try {
       const resp = await api.scan(req);
       await loadRuns();
-      navigate(`/runs/${resp.run_id}`);
+      navigate(`/runs/${resp.run_id}`, { state: { scanErrors: resp.errors } });
     } catch (e) {
       setError(e instanceof ApiError ? e.message : "Scan failed");
     } finally {
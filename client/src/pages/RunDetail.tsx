@@ -1,7 +1,7 @@
 import { useEffect, useState, useCallback } from "react";
-import { useParams, Link } from "react-router-dom";
+import { useParams, useLocation, Link } from "react-router-dom";
 import { api, ApiError } from "../api/client";
-import type { ExecutionMode, RunDetails } from "../types";
+import type { ExecutionMode, RunDetails, ScanError } from "../types";
 import StatusBadge from "../components/StatusBadge";
 import RiskBadge from "../components/RiskBadge";
 import styles from "./RunDetail.module.css";
@@ -12,6 +12,9 @@ function fmt(n: number) {
 
 export default function RunDetail() {
   const { runId } = useParams<{ runId: string }>();
+  // Set by the dashboard when the scan that created this run was partial.
+  const scanErrors =
+    (useLocation().state as { scanErrors?: ScanError[] } | null)?.scanErrors ?? [];
   const [run, setRun] = useState<RunDetails | null>(null);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
@@ -119,6 +122,21 @@ export default function RunDetail() {
         </div>
       </div>
 
+      {scanErrors.length > 0 && (
+        <div className={styles.partialBanner}>
+          <strong>Partial scan:</strong> {scanErrors.length} check
+          {scanErrors.length === 1 ? "" : "s"} failed, so these results may be incomplete.
+          <ul>
+            {scanErrors.map((err, i) => (
+              <li key={i}>
+                <span className="mono">{err.bucket ?? err.service}</span> ({err.region}) —{" "}
+                {err.operation}: {err.code}
+              </li>
+            ))}
+          </ul>
+        </div>
+      )}
+
       <div className={styles.body}>
         {/* Recommendations table */}
         <div className={styles.main}>
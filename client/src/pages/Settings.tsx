@@ -1,7 +1,17 @@
 import { useState, useEffect } from "react";
-import { invoke } from "@tauri-apps/api/core";
 import { useNavigate } from "react-router-dom";
-import { api } from "../api/client";
+import { api, command, commandErrorMessage } from "../api/client";
+import type {
+  CallerIdentity,
+  CopiedCredentials,
+  CredentialSource,
+  CredentialSummary,
+  CredentialsView,
+  ExportFormat,
+  KeyRotationStatus,
+  PermissionReport,
+  RevealedCredentials,
+} from "../types";
 import styles from "./Settings.module.css";
 
 interface AwsCredentials {
@@ -9,6 +19,7 @@ interface AwsCredentials {
   secret_access_key: string;
   region: string;
   session_token?: string | null;
+  endpoint_url?: string | null;
 }
 
 const REGIONS = [
@@ -31,6 +42,11 @@ const REGIONS = [
   "ca-central-1",
   "me-south-1",
   "af-south-1",
+  // GovCloud and China keys only work in their own partition's regions.
+  "us-gov-west-1",
+  "us-gov-east-1",
+  "cn-north-1",
+  "cn-northwest-1",
 ];
 
 const IS_TAURI = typeof window !== "undefined" && "__TAURI__" in window;
@@ -42,28 +58,45 @@ export default function Settings() {
     secret_access_key: "",
     region: "us-east-1",
     session_token: "",
+    endpoint_url: "",
   });
   const [showSecret, setShowSecret] = useState(false);
   const [showToken, setShowToken] = useState(false);
   const [saving, setSaving] = useState(false);
   const [testing, setTesting] = useState(false);
   const [saved, setSaved] = useState(false);
+  const [identity, setIdentity] = useState<CallerIdentity | null>(null);
+  const [source, setSource] = useState<CredentialSource | null>(null);
+  const [summary, setSummary] = useState<CredentialSummary | null>(null);
+  // Stored secrets never reach the UI; blank fields keep them for the same key.
+  const [stored, setStored] = useState<CredentialsView | null>(null);
+  const [rotation, setRotation] = useState<KeyRotationStatus | null>(null);
+  const [permissions, setPermissions] = useState<PermissionReport | null>(null);
+  const [checkingPermissions, setCheckingPermissions] = useState(false);
+  const [copied, setCopied] = useState<CopiedCredentials | null>(null);
   const [saveError, setSaveError] = useState<string | null>(null);
   const [testResult, setTestResult] = useState<{ ok: boolean; message: string } | null>(null);
 
   // Pre-populate form with any already-stored credentials.
   useEffect(() => {
     if (!IS_TAURI) return;
-    invoke<AwsCredentials | null>("load_credentials").then((creds) => {
+    command<CredentialsView | null>("load_credentials").then((creds) => {
       if (creds) {
+        setStored(creds);
         setForm({
           access_key_id: creds.access_key_id,
-          secret_access_key: creds.secret_access_key,
+          secret_access_key: "",
           region: creds.region,
-          session_token: creds.session_token ?? "",
+          session_token: "",
+          endpoint_url: creds.endpoint_url ?? "",
         });
       }
     });
+    command<CredentialSource | null>("get_credential_source").then(setSource);
+    command<CredentialSummary | null>("get_credential_summary").then(setSummary);
+    command<KeyRotationStatus>("key_rotation_status")
+      .then(setRotation)
+      .catch(() => setRotation(null));
   }, []);
 
   const setField = (key: keyof AwsCredentials, value: string) => {
@@ -73,32 +106,97 @@ export default function Settings() {
   };
 
   const handleSave = async () => {
-    if (!form.access_key_id.trim() || !form.secret_access_key.trim() || !form.region) {
+    const keepsSecret = stored?.access_key_id === form.access_key_id.trim();
+    if (
+      !form.access_key_id.trim() ||
+      (!form.secret_access_key.trim() && !keepsSecret) ||
+      !form.region
+    ) {
       setSaveError("Access Key ID, Secret Access Key, and Region are required.");
       return;
     }
     setSaving(true);
     setSaved(false);
     setSaveError(null);
+    setIdentity(null);
     try {
       if (IS_TAURI) {
-        await invoke("save_credentials", {
-          creds: {
-            access_key_id: form.access_key_id.trim(),
-            secret_access_key: form.secret_access_key.trim(),
-            region: form.region,
-            session_token: form.session_token?.trim() || null,
-          },
-        });
+        const creds = {
+          access_key_id: form.access_key_id.trim(),
+          secret_access_key: form.secret_access_key.trim(),
+          region: form.region,
+          session_token: form.session_token?.trim() || null,
+          endpoint_url: form.endpoint_url?.trim() || null,
+        };
+        // Reject keys AWS does not accept before they replace working ones.
+        setIdentity(await command<CallerIdentity>("validate_credentials", { creds }));
+        await command("save_credentials", { creds });
       }
       setSaved(true);
     } catch (e) {
-      setSaveError(String(e));
+      setSaveError(commandErrorMessage(e));
     } finally {
       setSaving(false);
     }
   };
 
+  const handleReveal = async () => {
+    setSaveError(null);
+    try {
+      const creds = await command<RevealedCredentials>("reveal_credentials");
+      setForm((prev) => ({
+        ...prev,
+        secret_access_key: creds.secret_access_key,
+        session_token: creds.session_token ?? "",
+      }));
+      setShowSecret(true);
+    } catch (e) {
+      setSaveError(commandErrorMessage(e));
+    }
+  };
+
+  const handleCopy = async (format: ExportFormat) => {
+    setSaveError(null);
+    try {
+      setCopied(await command<CopiedCredentials>("copy_credentials", { format }));
+    } catch (e) {
+      setSaveError(commandErrorMessage(e));
+    }
+  };
+
+  const handleSignOut = async () => {
+    if (!window.confirm("Delete all saved AWS credentials from this machine?")) return;
+    setSaveError(null);
+    try {
+      await command("clear_credentials");
+      setStored(null);
+      setSummary(null);
+      setIdentity(null);
+      setSaved(false);
+      setForm({
+        access_key_id: "",
+        secret_access_key: "",
+        region: "us-east-1",
+        session_token: "",
+        endpoint_url: "",
+      });
+    } catch (e) {
+      setSaveError(commandErrorMessage(e));
+    }
+  };
+
+  const handleCheckPermissions = async () => {
+    setCheckingPermissions(true);
+    setSaveError(null);
+    try {
+      setPermissions(await command<PermissionReport>("check_permissions"));
+    } catch (e) {
+      setSaveError(commandErrorMessage(e));
+    } finally {
+      setCheckingPermissions(false);
+    }
+  };
+
   const handleTest = async () => {
     setTesting(true);
     setTestResult(null);
@@ -121,6 +219,36 @@ export default function Settings() {
         </p>
       </div>
 
+      {summary && (
+        <p className={styles.hint}>
+          Connected as{" "}
+          <strong>
+            {summary.account_alias ?? summary.account_id ?? "an unverified account"}
+          </strong>{" "}
+          with <code>{summary.access_key_id}</code> in {summary.region}
+          {summary.expires_at &&
+            `, expiring ${new Date(summary.expires_at).toLocaleString()}`}
+          .
+        </p>
+      )}
+
+      {rotation?.keys
+        .filter((key) => key.due)
+        .map((key) => (
+          <p key={key.access_key_id} className={styles.hint}>
+            The access key of profile <strong>{key.profile}</strong> is {key.age_days} days
+            old (rotate every {rotation.settings.max_age_days} days). Create a new key in IAM
+            and save it here.
+          </p>
+        ))}
+
+      {source?.kind === "environment" && (
+        <p className={styles.hint}>
+          Using credentials from the environment (<code>AWS_ACCESS_KEY_ID</code>). Saving
+          stores them in the keychain, which then takes precedence.
+        </p>
+      )}
+
       <div className={styles.card}>
         {/* Access Key ID */}
         <div className={styles.field}>
@@ -149,13 +277,18 @@ export default function Settings() {
               className={styles.input}
               value={form.secret_access_key}
               onChange={(e) => setField("secret_access_key", e.target.value)}
-              placeholder="wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY"
+              placeholder={
+                stored ? "Saved — leave empty to keep" : "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY"
+              }
               autoComplete="new-password"
               spellCheck={false}
             />
             <button
               className={styles.revealBtn}
-              onClick={() => setShowSecret((v) => !v)}
+              onClick={() =>
+                // The saved secret is only fetched on request, after OS confirmation.
+                stored && !form.secret_access_key ? handleReveal() : setShowSecret((v) => !v)
+              }
               type="button"
             >
               {showSecret ? "Hide" : "Show"}
@@ -193,7 +326,11 @@ export default function Settings() {
               className={styles.input}
               value={form.session_token ?? ""}
               onChange={(e) => setField("session_token", e.target.value)}
-              placeholder="Leave empty if using long-term IAM credentials"
+              placeholder={
+                stored?.has_session_token
+                  ? "Saved — leave empty to keep"
+                  : "Leave empty if using long-term IAM credentials"
+              }
               autoComplete="new-password"
               spellCheck={false}
             />
@@ -207,11 +344,28 @@ export default function Settings() {
           </div>
         </div>
 
+        {/* Endpoint URL (optional) */}
+        <div className={styles.field}>
+          <label className={styles.label}>
+            Endpoint URL{" "}
+            <span className={styles.opt}>(optional — LocalStack or moto instead of AWS)</span>
+          </label>
+          <input
+            type="text"
+            className={styles.input}
+            value={form.endpoint_url ?? ""}
+            onChange={(e) => setField("endpoint_url", e.target.value)}
+            placeholder="http://localhost:4566"
+            spellCheck={false}
+          />
+        </div>
+
         {/* Feedback */}
         {saveError && <div className={styles.error}>{saveError}</div>}
         {saved && (
           <div className={styles.success}>
-            Credentials saved — backend restarted with new credentials.
+            Credentials saved{identity ? ` for ${identity.arn}` : ""} — backend restarted with
+            new credentials.
           </div>
         )}
         {testResult && (
@@ -233,9 +387,58 @@ export default function Settings() {
               Go to Dashboard →
             </button>
           )}
+          {IS_TAURI && stored && (
+            <button
+              className="btn-secondary"
+              onClick={handleCheckPermissions}
+              disabled={checkingPermissions}
+            >
+              {checkingPermissions ? "Checking…" : "Check Permissions"}
+            </button>
+          )}
+          {IS_TAURI && stored && (
+            <button className="btn-secondary" onClick={() => handleCopy("shell")}>
+              Copy for Shell
+            </button>
+          )}
+          {IS_TAURI && stored && (
+            <button className="btn-secondary" onClick={handleSignOut}>
+              Sign Out
+            </button>
+          )}
         </div>
       </div>
 
+      {copied && (
+        <p className={styles.hint}>
+          Copied{" "}
+          {copied.format === "shell" ? <code>export AWS_…</code> : "credential_process JSON"}
+          {copied.format === "shell" && " lines"}. The clipboard is cleared at{" "}
+          {new Date(copied.clears_at).toLocaleTimeString()}
+          {copied.expires_at &&
+            `; the keys expire ${new Date(copied.expires_at).toLocaleString()}`}
+          .
+        </p>
+      )}
+
+      {permissions && (
+        <div className={styles.card}>
+          <p className={styles.hint}>
+            {permissions.method === "simulation"
+              ? `Simulated policies for ${permissions.principal_arn}.`
+              : "Policy simulation unavailable; probed read calls instead."}
+          </p>
+          <ul>
+            {permissions.checks.map((check) => (
+              <li key={check.action} title={check.detail ?? undefined}>
+                {check.status === "allowed" ? "✓" : check.status === "denied" ? "✗" : "?"}{" "}
+                <code>{check.action}</code> — {check.purpose}
+              </li>
+            ))}
+          </ul>
+        </div>
+      )}
+
       <p className={styles.hint}>
         Need an IAM user?{" "}
         <span className={styles.hintCode}>
@@ -214,12 +214,23 @@ export interface RollbackRequest {
 // Response wrappers
 // ---------------------------------------------------------------------------
 
+export interface ScanError {
+  region: string;
+  service: string;
+  bucket: string | null;
+  operation: string;
+  code: string;
+  message: string;
+}
+
 export interface ScanResponse {
   run_id: string;
   status: RunStatus;
   recommendations: Recommendation[];
   estimated_monthly_savings: number;
   scanned_at: string;
+  /** Calls that failed; recommendations cover only what succeeded. */
+  errors: ScanError[];
 }
 
 export interface ScoreResponse {
@@ -253,3 +264,715 @@ export interface RollbackResponse {
   results: RollbackActionResult[];
   processed_at: string;
 }
+
+// ---------------------------------------------------------------------------
+// Tauri command errors — mirror src-tauri/src/error.rs
+// ---------------------------------------------------------------------------
+
+export type CommandErrorKind =
+  | "credentials"
+  | "access_denied"
+  | "throttled"
+  | "network"
+  | "sidecar"
+  | "aws"
+  | "not_found"
+  | "invalid_input"
+  | "conflict"
+  | "budget_exceeded"
+  | "cancelled"
+  | "io"
+  | "internal"
+  | "incompatible_version"
+  | "locked";
+
+export interface CommandError {
+  kind: CommandErrorKind;
+  message: string;
+  retryable: boolean;
+  remediation: string | null;
+  /** Shell schema that produced the error; absent on shells predating it. */
+  schema_version?: number;
+}
+
+export interface ApiVersion {
+  schema_version: number;
+  min_client_schema_version: number;
+  app_version: string;
+  /** Set when the backend is from another release. */
+  backend_mismatch: BackendMismatch | null;
+}
+
+/** Payload of `backend-incompatible`. */
+export interface BackendMismatch {
+  expected: number;
+  /** Null for a backend that predates the version check. */
+  found: number | null;
+  backend_version: string | null;
+}
+
+export type HealthStatus = "ok" | "unknown" | "warning" | "error";
+
+export interface HealthCheck {
+  status: HealthStatus;
+  detail: string;
+  remediation: string | null;
+}
+
+export interface AppHealth {
+  status: HealthStatus;
+  checked_at: string;
+  keyring: HealthCheck;
+  credentials: HealthCheck;
+  account_id: string | null;
+  temporary_credentials: boolean;
+  sidecar: HealthCheck;
+  database: HealthCheck;
+  network: HealthCheck;
+  last_sync: HealthCheck;
+  last_sync_at: string | null;
+}
+
+export type SelfTestOutcome = "passed" | "failed" | "skipped";
+
+export interface SelfTestStep {
+  name: "health" | "auth_token" | "sts" | "cache";
+  outcome: SelfTestOutcome;
+  detail: string;
+  duration_ms: number;
+}
+
+/** Returned by `run_backend_selftest`, for the support screen. */
+export interface SelfTestReport {
+  /** No step failed. */
+  passed: boolean;
+  ran_at: string;
+  steps: SelfTestStep[];
+}
+
+export interface CredentialProfileSummary {
+  name: string;
+  access_key_id: string;
+  region: string;
+  temporary: boolean;
+  active: boolean;
+  origin: ProfileOrigin | null;
+  expires_at: string | null;
+  label: ProfileLabel;
+}
+
+/** Result of `export_profile_bundle`; the file is passphrase-encrypted. */
+export interface BundleExport {
+  path: string;
+  profiles: string[];
+}
+
+/** One profile's outcome from `import_profile_bundle`. */
+export interface BundleImport {
+  name: string;
+  imported: boolean;
+  error: string | null;
+}
+
+/** How a profile is shown; the nickname falls back to the account alias. */
+export interface ProfileLabel {
+  nickname: string | null;
+  /** `#rrggbb`. */
+  color: string | null;
+  account_alias: string | null;
+}
+
+export type ProfileOrigin =
+  | {
+      kind: "assume_role";
+      source_profile: string | null;
+      role_arn: string;
+      external_id: string | null;
+      session_name: string;
+    }
+  | {
+      kind: "identity_center";
+      account_id: string;
+      role_name: string;
+      region: string;
+    }
+  | {
+      kind: "mfa_session";
+      source_profile: string | null;
+      serial_number: string;
+    }
+  | {
+      kind: "role_chain";
+      source_profile: string | null;
+      hops: RoleHop[];
+    };
+
+export interface RoleHop {
+  role_arn: string;
+  external_id: string | null;
+  /** Empty for the default session name. */
+  session_name: string;
+}
+
+/** Keys from a CLI profile's `credential_process`, held in memory only. */
+export interface ProcessCredentials {
+  profile: string;
+  access_key_id: string;
+  region: string;
+  expires_at: string | null;
+}
+
+export type InstanceSource = "imds" | "container";
+
+/** Keys from the EC2 instance profile or ECS container role, held in memory only. */
+export interface InstanceCredentials {
+  source: InstanceSource;
+  access_key_id: string;
+  region: string;
+  expires_at: string | null;
+}
+
+/** Where the sidecar is in its lifecycle (`get_backend_phase`). */
+/** `idle`: put to sleep while the app is in the background; requests wake it. */
+export type BackendPhase = "stopped" | "starting" | "ready" | "idle";
+
+/** Payload of `backend://starting`: the process is up, the API not yet. */
+export interface BackendStarting {
+  /** 0 for a remote backend. */
+  pid: number;
+  /** Where it will answer: a URL, or `unix:` and a socket path. */
+  url: string;
+}
+
+/** A sidecar reply forwarded by `backend_request`. */
+export interface BackendResponse {
+  status: number;
+  body: string;
+  /** Set for non-2xx replies, with the backend's `detail` as the message. */
+  error: CommandError | null;
+}
+
+/**
+ * Where a `backend_request` waits in the shell's queue: interactive requests
+ * go ahead of background refreshes.
+ */
+export type RequestPriority = "interactive" | "background";
+
+/** How many webview requests reach the backend at once. */
+export interface RequestQueueSettings {
+  max_concurrent: number;
+  /** Of those, at most this many background requests. */
+  max_background: number;
+}
+
+export interface ExportRequest {
+  run_id: string;
+  format?: "csv" | "json";
+}
+
+/** A run report generated by the backend, for `download_export`. */
+export interface ExportJob {
+  export_id: string;
+  run_id: string;
+  format: "csv" | "json";
+  file_name: string;
+  size: number;
+  /** Hex SHA-256 the download is checked against. */
+  sha256: string;
+  created_at: string;
+}
+
+/** Payload of `download-progress`, emitted while `download_export` runs. */
+export interface DownloadProgress {
+  job_id: string;
+  dest_path: string;
+  received_bytes: number;
+  total_bytes: number;
+  percent: number;
+  done: boolean;
+}
+
+/** Returned by `download_export`. */
+export interface DownloadResult {
+  job_id: string;
+  dest_path: string;
+  size: number;
+  sha256: string;
+  /** Bytes already on disk from an earlier, interrupted attempt. */
+  resumed_from: number;
+}
+
+/** Payload of `upload-progress`, emitted while `upload_file` runs. */
+export interface UploadProgress {
+  upload_id: string;
+  path: string;
+  sent_bytes: number;
+  total_bytes: number;
+  percent: number;
+  done: boolean;
+}
+
+/** Returned by `upload_file`. */
+export interface UploadResult {
+  upload_id: string;
+  path: string;
+  size: number;
+  /** Where the backend keeps the file. */
+  stored_path: string | null;
+  /** Bytes the backend already had from an earlier, interrupted attempt. */
+  resumed_from: number;
+}
+
+/** Payload of `analysis://{id}/error`, which ends a streamed analysis. */
+export interface AnalysisError {
+  message: string;
+}
+
+/** Payload of `backend://ready`: the API answers its health check. */
+export interface BackendReady {
+  pid: number;
+  startup_ms: number;
+}
+
+/** Payload of `backend://crashed`; `pid` is null when a restart failed. */
+export interface BackendCrashed {
+  pid: number | null;
+  exit_code: number | null;
+  restart_error: string | null;
+}
+
+/** Payload of `backend://restarting`. */
+export interface BackendRestarting {
+  attempt: number;
+  delay_secs: number;
+}
+
+/** Payload of `backend://recycling`: the sidecar outgrew its memory limit. */
+export interface BackendRecycling {
+  pid: number;
+  memory_bytes: number;
+  limit_bytes: number;
+}
+
+/** Payload of `backend://stopped`: launch found no sidecar to start. */
+export interface BackendStopped {
+  reason: string;
+}
+
+/**
+ * The latest `backend://` lifecycle event, as the UI tracks it; `launching`
+ * until the shell has spawned the sidecar.
+ */
+export type BackendLifecycle =
+  | { kind: "launching" }
+  | ({ kind: "starting" } & BackendStarting)
+  | ({ kind: "ready" } & BackendReady)
+  | ({ kind: "crashed" } & BackendCrashed)
+  | ({ kind: "restarting" } & BackendRestarting)
+  | ({ kind: "recycling" } & BackendRecycling);
+
+/** Result of `restart_backend`: the new sidecar answers at `url`. */
+export interface BackendRestarted {
+  url: string;
+  startup_ms: number;
+}
+
+/** The sidecar's resource use (`get_backend_stats`). */
+export interface BackendStats {
+  /** Null when no sidecar is running; the process fields are null too. */
+  pid: number | null;
+  uptime_secs: number | null;
+  /** Share of one core; above 100 when several are busy. */
+  cpu_percent: number | null;
+  memory_bytes: number | null;
+  health_ok: boolean | null;
+  health_latency_ms: number | null;
+  health_checked_at: string | null;
+}
+
+/** Liveness and readiness of the sidecar (`get_backend_status`). */
+export interface BackendStatus {
+  phase: BackendPhase;
+  /** The sidecar process exists; in dev builds, same as `ready`. */
+  live: boolean;
+  /** Its health endpoint answered 2xx. */
+  ready: boolean;
+  pid: number | null;
+  latency_ms: number | null;
+  checked_at: string | null;
+}
+
+export interface HealthCheckSettings {
+  /** Polled under the API prefix. */
+  path: string;
+  request_timeout_ms: number;
+  poll_interval_ms: number;
+  startup_timeout_secs: number;
+  restart_timeout_secs: number;
+  ui_interval_secs: number;
+}
+
+export interface SidecarMemorySettings {
+  enabled: boolean;
+  limit_mb: number;
+  check_interval_secs: number;
+}
+
+/** What happens to the backend while the window is in the background. */
+export interface PowerSettings {
+  power_mode: "always_on" | "suspend" | "stop";
+  idle_minutes: number;
+}
+
+/** A centrally run backend used instead of the bundled one. */
+export interface RemoteBackendSettings {
+  enabled: boolean;
+  /** Root of the service; `/api/v1` is added to it. */
+  base_url: string;
+  /** Header the stored auth value is sent in. */
+  auth_header: string;
+  /** PEM file of extra CA certificates to trust. */
+  ca_certificate_path: string | null;
+}
+
+/** Extra backend processes that scans run on. */
+export interface WorkerSettings {
+  /** 0 runs scans on the main backend, holding up other requests. */
+  max_workers: number;
+}
+
+/** `system` follows `HTTPS_PROXY` or the OS; `off` connects directly. */
+export type ProxyMode = "system" | "manual" | "off";
+
+export interface ProxySettings {
+  mode: ProxyMode;
+  /** Used in `manual` mode, e.g. `http://proxy.corp.example:3128`. */
+  https_proxy: string;
+  /** Plain HTTP proxy in `manual` mode; empty uses `https_proxy`. */
+  http_proxy: string;
+  /** Comma-separated hosts and domains that bypass the proxy. */
+  no_proxy: string;
+}
+
+export interface ProxyConfig {
+  https_proxy: string | null;
+  http_proxy: string | null;
+  /** Always includes loopback. */
+  no_proxy: string;
+}
+
+/** Returned by `get_proxy_settings`. */
+export interface ProxyStatus {
+  settings: ProxySettings;
+  effective: ProxyConfig | null;
+  source: "environment" | "os" | "settings" | "none";
+}
+
+export interface CaTrustSettings {
+  /** PEM file of CA certificates to trust besides the usual roots. */
+  ca_bundle_path: string | null;
+}
+
+/** Backend timeouts, retries and circuit breaker, and the AWS call budget. */
+export interface ResilienceSettings {
+  request_timeout_secs: number;
+  /** Scans, scoring, execution and rollback. */
+  long_request_timeout_secs: number;
+  max_retries: number;
+  backoff_base_ms: number;
+  /** Outages in a row that open the breaker. */
+  breaker_threshold: number;
+  breaker_cooldown_secs: number;
+  aws_attempt_timeout_secs: number;
+  aws_max_attempts: number;
+}
+
+/** Cache of the backend's GET replies, revalidated by ETag once stale. */
+export interface ResponseCacheSettings {
+  enabled: boolean;
+  /** Seconds a reply stays fresh, by path prefix; unlisted paths are not cached. */
+  ttl_secs: Record<string, number>;
+  max_entries: number;
+}
+
+/** Payload of `backend-degraded`; `backend-recovered` carries none. */
+export interface BackendDegraded {
+  failures: number;
+  retry_after_secs: number;
+  reason: string;
+}
+
+/** Returned by `get_health_check_settings`. */
+export interface HealthCheckConfig {
+  settings: HealthCheckSettings;
+  /** Overrides for this machine only. */
+  machine: Partial<HealthCheckSettings>;
+  effective: HealthCheckSettings;
+}
+
+/** Tail of the sidecar's log (`get_backend_logs`). */
+export interface BackendLogs {
+  path: string;
+  lines: string[];
+}
+
+/** Payload of `backend-exposed` (and `get_backend_exposure`). */
+export interface BackendExposure {
+  location: string;
+  problems: string[];
+}
+
+/** Payload of `backend-failed`: the sidecar crash-looped and is left stopped. */
+export interface BackendFailed {
+  crashes: number;
+  window_secs: number;
+  exit_code: number | null;
+  restart_error: string | null;
+  /** The sidecar's last stderr lines, oldest first. */
+  stderr: string[];
+}
+
+/** Payload of the `aws-cli-profiles-changed` event. */
+export interface CliProfilesChanged {
+  profiles: string[];
+}
+
+/** Payload of the `credentials-expiring` event. */
+export interface CredentialsExpiring {
+  profile: string | null;
+  expires_at: string;
+  expired: boolean;
+  reason: string;
+}
+
+export interface DerivedCredentials {
+  profile: string;
+  arn: string | null;
+  expires_at: string | null;
+}
+
+export interface ProfileImport {
+  name: string;
+  region: string | null;
+  imported: boolean;
+  error: string | null;
+}
+
+export interface IdentityCenterLogin {
+  user_code: string;
+  verification_uri: string;
+  verification_uri_complete: string | null;
+  expires_in: number;
+  interval: number;
+}
+
+export type IdentityCenterPoll =
+  | { status: "pending" }
+  | { status: "slow_down" }
+  | { status: "complete"; expires_at: string };
+
+export interface IdentityCenterAccount {
+  account_id: string;
+  account_name: string | null;
+  email_address: string | null;
+  roles: string[];
+}
+
+export interface IdentityCenterStatus {
+  signed_in: boolean;
+  start_url: string | null;
+  region: string | null;
+  expires_at: string | null;
+}
+
+/** Stored AWS credentials as `load_credentials` returns them: no secrets. */
+export type Partition = "aws" | "aws-us-gov" | "aws-cn";
+
+export interface CredentialsView {
+  access_key_id: string;
+  region: string;
+  has_session_token: boolean;
+  expires_at: string | null;
+  partition: Partition;
+  endpoint_url: string | null;
+}
+
+/** Full stored keys, after OS confirmation (`reveal_credentials`). */
+export interface RevealedCredentials {
+  access_key_id: string;
+  secret_access_key: string;
+  session_token: string | null;
+}
+
+export interface RevealSettings {
+  require_os_auth: boolean;
+}
+
+export type CredentialSource =
+  | { kind: "credential_process"; profile: string }
+  | { kind: "instance"; source: InstanceSource }
+  | { kind: "keychain" }
+  | { kind: "environment" };
+
+/** Non-secret details of the current keys (`get_credential_summary`). */
+export interface CredentialSummary {
+  /** Masked, e.g. `AKIA…PLE`. */
+  access_key_id: string;
+  region: string;
+  source: CredentialSource;
+  /** Null when AWS could not be reached or refused the lookup. */
+  account_id: string | null;
+  account_alias: string | null;
+  arn: string | null;
+  expires_at: string | null;
+}
+
+export interface CallerIdentity {
+  account_id: string;
+  arn: string;
+  user_id: string;
+  /** IAM user name, or the role session for assumed roles. */
+  user_name: string | null;
+}
+
+export type SecretBackend = "auto" | "keychain" | "encrypted_file";
+
+export interface SecretStoreSettings {
+  backend: SecretBackend;
+}
+
+export interface SecretStoreStatus {
+  backend: SecretBackend;
+  /** Whether secrets currently go to the encrypted file. */
+  using_file: boolean;
+  keychain_available: boolean;
+  file_exists: boolean;
+  unlocked: boolean;
+}
+
+export interface AppLockSettings {
+  enabled: boolean;
+  /** Minutes without activity before the app locks; 0 disables auto-lock. */
+  idle_minutes: number;
+  allow_os_auth: boolean;
+}
+
+export interface AppLockStatus {
+  settings: AppLockSettings;
+  locked: boolean;
+  has_passphrase: boolean;
+}
+
+/** Payload of the `locked` event. */
+export interface AppLocked {
+  reason: "launch" | "idle" | "manual";
+}
+
+export type PermissionStatus = "allowed" | "denied" | "unknown";
+
+export interface PermissionCheck {
+  action: string;
+  purpose: string;
+  status: PermissionStatus;
+  detail: string | null;
+}
+
+/** Result of `check_permissions`. */
+export interface PermissionReport {
+  /** Policy simulation, or probing read calls when simulation is unavailable. */
+  method: "simulation" | "probe";
+  principal_arn: string | null;
+  checks: PermissionCheck[];
+}
+
+export interface KeyRotationSettings {
+  max_age_days: number;
+  notify: boolean;
+}
+
+export interface KeyAge {
+  profile: string;
+  access_key_id: string;
+  created_at: string;
+  /** IAM's creation date, or when the app first saved the key. */
+  source: "iam" | "local_save";
+  age_days: number;
+  due: boolean;
+}
+
+export interface KeyRotationStatus {
+  settings: KeyRotationSettings;
+  keys: KeyAge[];
+}
+
+export type ExportFormat = "shell" | "json";
+
+/** Result of `copy_credentials`; the keys themselves stay in the backend. */
+export interface CopiedCredentials {
+  format: ExportFormat;
+  clears_at: string;
+  expires_at: string | null;
+}
+
+/** Scope of a shell-side scan (`start_provider_scan`, `scan_accounts`). */
+export interface ScanScope {
+  include: string[];
+  exclude: string[];
+  max_objects_per_resource: number | null;
+  /** Empty for the profile's selection, or every region. */
+  regions: string[];
+}
+
+export interface ScanIssue {
+  region: string;
+  service: string;
+  resource: string | null;
+  operation: string;
+  code: string;
+  message: string;
+}
+
+export interface ScanOutcome {
+  run_id: string;
+  recommendation_count: number;
+  estimated_monthly_savings: number;
+  errors: ScanIssue[];
+}
+
+export interface AccountScan {
+  /** Profile whose keys were used. */
+  profile: string;
+  /** Other selected profiles for the same account. */
+  duplicate_profiles: string[];
+  outcome: ScanOutcome | null;
+  error: string | null;
+  /** Job the scan ran as (see `list_jobs`). */
+  job_id: string | null;
+}
+
+/** Result of `scan_accounts`, keyed by account ID. */
+export interface MultiAccountReport {
+  accounts: Record<string, AccountScan>;
+  failed_profiles: Record<string, string>;
+  recommendation_count: number;
+  estimated_monthly_savings: number;
+}
+
+export interface AvailableRegion {
+  name: string;
+  opt_in_status: string;
+  enabled: boolean;
+}
+
+export interface OrganizationSettings {
+  role_name: string;
+  external_id: string | null;
+  session_name: string;
+}
+
+export interface OrganizationAccount {
+  id: string;
+  name: string;
+  email: string;
+  state: string;
+}
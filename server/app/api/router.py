@@ -1,10 +1,16 @@
 from fastapi import APIRouter
 
+from app.api.routes.exports import router as exports_router
 from app.api.routes.health import router as health_router
+from app.api.routes.internal import router as internal_router
 from app.api.routes.optimizer import router as optimizer_router
+from app.api.routes.uploads import router as uploads_router
 
 
 api_router = APIRouter()
 api_router.include_router(health_router, tags=["health"])
 api_router.include_router(optimizer_router, prefix="/optimizer", tags=["optimizer"])
+api_router.include_router(internal_router, prefix="/internal", tags=["internal"])
+api_router.include_router(uploads_router, prefix="/uploads", tags=["uploads"])
+api_router.include_router(exports_router, prefix="/exports", tags=["exports"])
 
@@ -0,0 +1,49 @@
+"""Run reports generated on the server and downloaded by clients, in ranges
+if they need to resume."""
+
+from fastapi import APIRouter, HTTPException, status
+from fastapi.responses import FileResponse
+
+from app.dependencies import export_store, run_store
+from app.models import ExportJob, ExportRequest
+
+
+router = APIRouter()
+
+
+def _job(export_id: str) -> ExportJob:
+    job = export_store.get(export_id)
+    if job is None:
+        raise HTTPException(
+            status_code=status.HTTP_404_NOT_FOUND,
+            detail=f"Export '{export_id}' was not found.",
+        )
+    return job
+
+
+@router.post("", response_model=ExportJob, status_code=status.HTTP_201_CREATED)
+def create_export(request: ExportRequest) -> ExportJob:
+    record = run_store.get(request.run_id)
+    if not record:
+        raise HTTPException(
+            status_code=status.HTTP_404_NOT_FOUND,
+            detail=f"Run '{request.run_id}' was not found.",
+        )
+    return export_store.create(record, request.format)
+
+
+@router.get("/{export_id}", response_model=ExportJob)
+def get_export(export_id: str) -> ExportJob:
+    return _job(export_id)
+
+
+@router.get("/{export_id}/download")
+def download_export(export_id: str) -> FileResponse:
+    # Served as bytes, with Range support, so clients can resume; a JSON
+    # type would also have it buffered whole to compute an ETag.
+    job = _job(export_id)
+    return FileResponse(
+        export_store.file(job),
+        media_type="application/octet-stream",
+        filename=job.file_name,
+    )
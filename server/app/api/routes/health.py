@@ -3,11 +3,17 @@ from datetime import datetime, timezone
 from fastapi import APIRouter
 from pydantic import BaseModel
 
+from app import dependencies
 from app.core.settings import get_settings
 
 
 router = APIRouter()
 
+# Version of this HTTP API. Bump on changes an older desktop shell would
+# misread, in step with BACKEND_API_VERSION in client/src-tauri/src/api_version.rs.
+API_VERSION = 1
+APP_VERSION = "1.0.0"
+
 
 class HealthResponse(BaseModel):
     status: str
@@ -16,6 +22,16 @@ class HealthResponse(BaseModel):
     timestamp: str
 
 
+class DatabaseHealthResponse(BaseModel):
+    status: str
+    problems: list[str]
+
+
+class VersionResponse(BaseModel):
+    api_version: int
+    app_version: str
+
+
 @router.get("/health", response_model=HealthResponse)
 def health_check() -> HealthResponse:
     settings = get_settings()
@@ -26,3 +42,13 @@ def health_check() -> HealthResponse:
         timestamp=datetime.now(timezone.utc).isoformat(),
     )
 
+
+@router.get("/version", response_model=VersionResponse)
+def version() -> VersionResponse:
+    return VersionResponse(api_version=API_VERSION, app_version=APP_VERSION)
+
+
+@router.get("/health/database", response_model=DatabaseHealthResponse)
+def database_health() -> DatabaseHealthResponse:
+    problems = dependencies.run_store.integrity_check()
+    return DatabaseHealthResponse(status="degraded" if problems else "ok", problems=problems)
@@ -0,0 +1,23 @@
+"""Endpoints for the desktop shell only.
+
+They exist only when the shell started the sidecar with an ``API_TOKEN``,
+which the app checks on every request (see ``app.main``).
+"""
+
+from fastapi import APIRouter, HTTPException, status
+
+from app import dependencies
+from app.core.settings import get_settings
+from app.models import ScanCredentials
+
+
+router = APIRouter()
+
+
+@router.post("/credentials", status_code=status.HTTP_204_NO_CONTENT)
+def replace_credentials(credentials: ScanCredentials) -> None:
+    """Switches the sidecar to new keys without a restart, so scans in
+    progress keep running."""
+    if not get_settings().api_token:
+        raise HTTPException(status_code=status.HTTP_404_NOT_FOUND, detail="Not Found")
+    dependencies.apply_credentials(credentials)
@@ -1,8 +1,19 @@
 from datetime import datetime, timezone
+import json
+import time
+from typing import Iterator
 
 from fastapi import APIRouter, HTTPException, Query, status
-
-from app.dependencies import execution_service, rollback_service, run_store, scanner_service, scoring_service
+from fastapi.responses import StreamingResponse
+
+from app.dependencies import (
+    execution_service,
+    rollback_service,
+    run_store,
+    scan_progress,
+    scanner_service,
+    scoring_service,
+)
 from app.models import (
     ExecutionAuditRecord,
     ExecuteRequest,
@@ -13,19 +24,42 @@ from app.models import (
     RollbackStatus,
     RunDetails,
     RunSummary,
+    ScanProgress,
     ScanRequest,
     ScanResponse,
+    ScanStage,
     ScoreRequest,
     ScoreResponse,
 )
+from app.scanner import ScanCancelled, ScannerService
 
 
 router = APIRouter()
 
+# Scan event streams: how often progress is checked, how long a scan may take
+# to appear, and how long it may go without progress before the stream ends.
+_EVENTS_POLL_SECONDS = 0.5
+_EVENTS_START_GRACE_SECONDS = 10.0
+_EVENTS_IDLE_TIMEOUT_SECONDS = 300.0
+# A comment line this often keeps idle connections from looking dead.
+_EVENTS_KEEPALIVE_SECONDS = 5.0
+
 
 @router.post("/scan", response_model=ScanResponse, status_code=status.HTTP_201_CREATED)
 def scan(request: ScanRequest) -> ScanResponse:
-    recommendations = scanner_service.scan(request)
+    scanner = (
+        ScannerService.for_credentials(request.credentials)
+        if request.credentials
+        else scanner_service
+    )
+    try:
+        recommendations, errors = scanner.scan_partial(request, progress=scan_progress)
+    except ScanCancelled:
+        # Nothing was stored; the cancelled scan leaves no run behind.
+        raise HTTPException(
+            status_code=status.HTTP_409_CONFLICT,
+            detail=f"Scan '{request.scan_id}' was cancelled.",
+        )
     record = run_store.create(recommendations)
     estimated_monthly_savings = sum(
         recommendation.estimated_monthly_savings for recommendation in recommendations
@@ -37,9 +71,73 @@ def scan(request: ScanRequest) -> ScanResponse:
         recommendations=recommendations,
         estimated_monthly_savings=estimated_monthly_savings,
         scanned_at=datetime.now(timezone.utc),
+        errors=errors,
     )
 
 
+@router.get("/scan/{scan_id}/progress", response_model=ScanProgress)
+def scan_progress_status(scan_id: str) -> ScanProgress:
+    progress = scan_progress.get(scan_id)
+    if not progress:
+        raise HTTPException(
+            status_code=status.HTTP_404_NOT_FOUND,
+            detail=f"Scan '{scan_id}' has no progress.",
+        )
+    return progress
+
+
+def _sse(event: str, data: dict) -> str:
+    return f"event: {event}\ndata: {json.dumps(data)}\n\n"
+
+
+def _scan_events(scan_id: str) -> Iterator[str]:
+    """Yields a scan's progress as server-sent events until it ends."""
+    started = time.monotonic()
+    last_change = started
+    last_sent = started
+    last_update = None
+    while True:
+        now = time.monotonic()
+        progress = scan_progress.get(scan_id)
+        if progress is None:
+            if now - started >= _EVENTS_START_GRACE_SECONDS:
+                yield _sse("error", {"detail": f"Scan '{scan_id}' has no progress."})
+                return
+        elif progress.stage == ScanStage.COMPLETED:
+            yield _sse("complete", progress.model_dump(mode="json"))
+            return
+        elif progress.stage == ScanStage.CANCELLED:
+            yield _sse("error", {"detail": f"Scan '{scan_id}' was cancelled."})
+            return
+        elif progress.updated_at != last_update:
+            last_update = progress.updated_at
+            last_change = last_sent = now
+            yield _sse("progress", progress.model_dump(mode="json"))
+        elif now - last_change >= _EVENTS_IDLE_TIMEOUT_SECONDS:
+            yield _sse("error", {"detail": f"Scan '{scan_id}' stopped making progress."})
+            return
+        if now - last_sent >= _EVENTS_KEEPALIVE_SECONDS:
+            last_sent = now
+            yield ": keepalive\n\n"
+        time.sleep(_EVENTS_POLL_SECONDS)
+
+
+@router.get("/scan/{scan_id}/events")
+def scan_events(scan_id: str) -> StreamingResponse:
+    """Streams progress as server-sent events, ending with `complete` or `error`."""
+    return StreamingResponse(
+        _scan_events(scan_id),
+        media_type="text/event-stream",
+        headers={"Cache-Control": "no-cache"},
+    )
+
+
+@router.post("/scan/{scan_id}/cancel", status_code=status.HTTP_202_ACCEPTED)
+def cancel_scan(scan_id: str) -> dict[str, str]:
+    scan_progress.cancel(scan_id)
+    return {"scan_id": scan_id, "status": "cancelling"}
+
+
 @router.post("/score", response_model=ScoreResponse)
 def score(request: ScoreRequest) -> ScoreResponse:
     record = run_store.get(request.run_id)
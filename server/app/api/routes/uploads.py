@@ -0,0 +1,57 @@
+"""Chunked, resumable uploads of large local files, such as Cost and Usage
+Reports, which the desktop shell streams here from disk."""
+
+import base64
+import binascii
+
+from fastapi import APIRouter, HTTPException, status
+
+from app.dependencies import upload_store
+from app.models import UploadChunk, UploadCreateRequest, UploadStatus
+from app.state.uploads import UploadOffsetMismatch
+
+
+router = APIRouter()
+
+
+def _not_found(upload_id: str) -> HTTPException:
+    return HTTPException(
+        status_code=status.HTTP_404_NOT_FOUND,
+        detail=f"Upload '{upload_id}' was not found.",
+    )
+
+
+@router.post("", response_model=UploadStatus, status_code=status.HTTP_201_CREATED)
+def create_upload(request: UploadCreateRequest) -> UploadStatus:
+    try:
+        return upload_store.create(request.file_name, request.size)
+    except ValueError as exc:
+        raise HTTPException(status_code=status.HTTP_422_UNPROCESSABLE_ENTITY, detail=str(exc))
+
+
+@router.get("/{upload_id}", response_model=UploadStatus)
+def get_upload(upload_id: str) -> UploadStatus:
+    upload = upload_store.get(upload_id)
+    if upload is None:
+        raise _not_found(upload_id)
+    return upload
+
+
+@router.put("/{upload_id}", response_model=UploadStatus)
+def append_chunk(upload_id: str, chunk: UploadChunk) -> UploadStatus:
+    try:
+        data = base64.b64decode(chunk.data, validate=True)
+    except binascii.Error:
+        raise HTTPException(
+            status_code=status.HTTP_422_UNPROCESSABLE_ENTITY,
+            detail="Chunk data is not valid base64.",
+        )
+    try:
+        upload = upload_store.append(upload_id, chunk.offset, data)
+    except UploadOffsetMismatch as exc:
+        raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail=str(exc))
+    except ValueError as exc:
+        raise HTTPException(status_code=status.HTTP_422_UNPROCESSABLE_ENTITY, detail=str(exc))
+    if upload is None:
+        raise _not_found(upload_id)
+    return upload
@@ -0,0 +1,35 @@
+"""Extra root certificates from the desktop shell.
+
+Behind a TLS-inspecting proxy the shell passes the path of the corporate CA
+bundle as ``EXTRA_CA_BUNDLE``. botocore and the standard library each trust
+a single bundle, so the extra certificates are appended to a copy of the
+default one and every client is pointed at the copy.
+"""
+import os
+import tempfile
+
+from botocore.httpsession import where
+
+_BUNDLE_VARS = ("AWS_CA_BUNDLE", "REQUESTS_CA_BUNDLE", "SSL_CERT_FILE")
+
+
+def install_extra_ca(environ=os.environ) -> str | None:
+    """Writes the combined bundle and points the clients at it.
+
+    Returns its path, or None when no extra bundle is configured. A bundle
+    already set with ``AWS_CA_BUNDLE`` is kept as the base.
+    """
+    extra = environ.get("EXTRA_CA_BUNDLE")
+    if not extra:
+        return None
+    base = environ.get("AWS_CA_BUNDLE") or where()
+    with open(base, "rb") as f:
+        combined = f.read().rstrip(b"\n") + b"\n"
+    with open(extra, "rb") as f:
+        combined += f.read()
+    fd, path = tempfile.mkstemp(prefix="aws-cost-optimizer-ca-", suffix=".pem")
+    with os.fdopen(fd, "wb") as f:
+        f.write(combined)
+    for name in _BUNDLE_VARS:
+        environ[name] = path
+    return path
@@ -8,6 +8,9 @@ class Settings:
         self.environment = os.getenv("ENVIRONMENT", "development")
         self.app_name = os.getenv("APP_NAME", "aws-cost-optimizer-api")
         self.cors_origins = self._parse_cors_origins()
+        # Set by the desktop shell: every request must then carry it as a
+        # bearer token, and the /internal endpoints exist.
+        self.api_token = os.getenv("API_TOKEN") or None
 
     def _parse_cors_origins(self) -> list[str]:
         raw = os.getenv(
@@ -3,9 +3,10 @@ import os
 import boto3
 
 from app.executor import ExecutionService, RollbackService
-from app.scanner import ScannerService
+from app.models import ScanCredentials
+from app.scanner import ScannerService, ScanProgressTracker
 from app.scoring import ScoringService
-from app.state import RunStore
+from app.state import ExportStore, RunStore, UploadStore
 
 # S3 is a global service — list/read operations work regardless of which
 # regional endpoint the client uses. We default to us-east-1 (the S3 global
@@ -13,7 +14,30 @@ from app.state import RunStore
 _s3 = boto3.client("s3", region_name=os.getenv("AWS_DEFAULT_REGION", "us-east-1"))
 
 run_store = RunStore(db_path=os.getenv("RUNS_DB_PATH", "data/runs.db"))
+upload_store = UploadStore(root=os.getenv("UPLOADS_DIR", "data/uploads"))
+export_store = ExportStore(root=os.getenv("EXPORTS_DIR", "data/exports"))
 scanner_service = ScannerService(s3_client=_s3)
+scan_progress = ScanProgressTracker()
 scoring_service = ScoringService()
 execution_service = ExecutionService(s3_client=_s3)
 rollback_service = RollbackService(s3_client=_s3)
+
+
+def apply_credentials(credentials: ScanCredentials) -> None:
+    """Points the shared services, and any client created later, at new keys."""
+    token = credentials.session_token
+    environment = {
+        "AWS_ACCESS_KEY_ID": credentials.access_key_id,
+        "AWS_SECRET_ACCESS_KEY": credentials.secret_access_key.get_secret_value(),
+        "AWS_SESSION_TOKEN": token.get_secret_value() if token else None,
+        "AWS_DEFAULT_REGION": credentials.region,
+        "AWS_ENDPOINT_URL": credentials.endpoint_url,
+    }
+    for name, value in environment.items():
+        if value is None:
+            os.environ.pop(name, None)
+        else:
+            os.environ[name] = value
+    client = ScannerService.for_credentials(credentials).s3
+    for service in (scanner_service, execution_service, rollback_service):
+        service._s3 = client
@@ -1,16 +1,30 @@
-from fastapi import FastAPI
+import hashlib
+import hmac
+
+from fastapi import FastAPI, Request
 from fastapi.middleware.cors import CORSMiddleware
+from fastapi.responses import JSONResponse, Response
 
 from app.api.router import api_router
+from app.api.routes.health import APP_VERSION
 from app.core.settings import get_settings
 
 
+def _etag(body: bytes) -> str:
+    return f'W/"{hashlib.sha256(body).hexdigest()[:32]}"'
+
+
+def _matches(if_none_match: str, etag: str) -> bool:
+    tags = [tag.strip() for tag in if_none_match.split(",")]
+    return "*" in tags or etag in tags or etag.removeprefix("W/") in tags
+
+
 def create_app() -> FastAPI:
     settings = get_settings()
 
     app = FastAPI(
         title="AWS Cost Optimizer API",
-        version="1.0.0",
+        version=APP_VERSION,
         description="API surface for scan, score, and execution workflows.",
     )
 
@@ -22,6 +36,47 @@ def create_app() -> FastAPI:
         allow_headers=["*"],
     )
 
+    @app.middleware("http")
+    async def require_api_token(request: Request, call_next):
+        # Without a token (tests, a dev server run by hand) the API is open.
+        token = get_settings().api_token
+        if token and request.method != "OPTIONS":
+            expected = f"Bearer {token}".encode()
+            given = request.headers.get("authorization", "").encode()
+            if not hmac.compare_digest(given, expected):
+                return JSONResponse(
+                    status_code=401, content={"detail": "Missing or invalid API token"}
+                )
+        return await call_next(request)
+
+    @app.middleware("http")
+    async def tag_json_replies(request: Request, call_next):
+        # Successful JSON GETs carry an ETag of their body, so clients that
+        # cache them can revalidate with If-None-Match and get a bodyless 304.
+        response = await call_next(request)
+        if (
+            request.method != "GET"
+            or response.status_code != 200
+            or not response.headers.get("content-type", "").startswith("application/json")
+        ):
+            return response
+        body = b"".join([chunk async for chunk in response.body_iterator])
+        etag = _etag(body)
+        headers = {
+            name: value
+            for name, value in response.headers.items()
+            if name.lower() not in ("content-length", "content-type")
+        }
+        headers["etag"] = etag
+        if _matches(request.headers.get("if-none-match", ""), etag):
+            return Response(status_code=304, headers=headers)
+        return Response(
+            content=body,
+            status_code=200,
+            headers=headers,
+            media_type=response.headers["content-type"],
+        )
+
     app.include_router(api_router, prefix=settings.api_prefix)
     return app
 
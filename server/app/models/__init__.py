@@ -5,8 +5,12 @@ from .contracts import (
     ExecutionActionResult,
     ExecutionActionStatus,
     ExecutionMode,
+    ExportFormat,
+    ExportJob,
+    ExportRequest,
     Recommendation,
     RecommendationType,
+    RegionProgress,
     RiskFactorScores,
     RiskLevel,
     RiskScore,
@@ -18,11 +22,18 @@ from .contracts import (
     RunDetails,
     RunStatus,
     RunSummary,
+    ScanCredentials,
+    ScanError,
+    ScanProgress,
     ScanRequest,
     ScanResponse,
+    ScanStage,
     SavingsEstimate,
     SavingsSummary,
     ScoreRequest,
     ScoreResponse,
     StorageClass,
+    UploadChunk,
+    UploadCreateRequest,
+    UploadStatus,
 )
@@ -3,7 +3,7 @@ from enum import Enum
 import logging
 from typing import Any, Optional
 
-from pydantic import BaseModel, Field, field_validator
+from pydantic import BaseModel, Field, SecretStr, field_validator
 
 _log = logging.getLogger(__name__)
 
@@ -125,10 +125,65 @@ class SavingsSummary(BaseModel):
     low_confidence_count: int = Field(ge=0)
 
 
+class ScanCredentials(BaseModel):
+    """Keys of another account, scanned instead of the sidecar's own."""
+
+    access_key_id: str = Field(min_length=16, max_length=128)
+    secret_access_key: SecretStr
+    session_token: Optional[SecretStr] = None
+    region: str = Field(default="us-east-1", min_length=1)
+    # LocalStack or moto instead of AWS, for demos and tests.
+    endpoint_url: Optional[str] = None
+
+
 class ScanRequest(BaseModel):
     include_buckets: list[str] = Field(default_factory=list)
     exclude_buckets: list[str] = Field(default_factory=list)
     max_objects_per_bucket: int = Field(default=1000, ge=1, le=100000)
+    # Only buckets in these regions are scanned; empty scans every region.
+    regions: list[str] = Field(default_factory=list)
+    # Client-chosen id; when set, progress is readable at /scan/{scan_id}/progress
+    # while the scan runs.
+    scan_id: Optional[str] = Field(default=None, min_length=1, max_length=64)
+    # Set by the shell's multi-account scans; never stored with the run.
+    credentials: Optional[ScanCredentials] = None
+
+
+class ScanStage(str, Enum):
+    RESOLVING_BUCKETS = "resolving_buckets"
+    SCANNING_OBJECTS = "scanning_objects"
+    CHECKING_LIFECYCLE = "checking_lifecycle"
+    CHECKING_MULTIPART = "checking_multipart"
+    COMPLETED = "completed"
+    CANCELLED = "cancelled"
+
+
+class RegionProgress(BaseModel):
+    bucket_count: int = Field(ge=0)
+    buckets_done: int = Field(ge=0)
+
+
+class ScanProgress(BaseModel):
+    scan_id: str
+    stage: ScanStage
+    bucket: Optional[str] = None
+    region: Optional[str] = None
+    bucket_count: int = Field(default=0, ge=0)
+    buckets_done: int = Field(default=0, ge=0)
+    percent: float = Field(ge=0, le=100)
+    regions: dict[str, RegionProgress] = Field(default_factory=dict)
+    started_at: datetime
+    updated_at: datetime
+
+
+class ScanError(BaseModel):
+    """A call that failed during a scan; the rest of the scan still ran."""
+    region: str
+    service: str
+    bucket: Optional[str] = None
+    operation: str
+    code: str
+    message: str
 
 
 class ScanResponse(BaseModel):
@@ -137,6 +192,9 @@ class ScanResponse(BaseModel):
     recommendations: list[Recommendation]
     estimated_monthly_savings: float
     scanned_at: datetime
+    # Non-empty when some buckets or regions could not be fully scanned; the
+    # recommendations then cover only what succeeded.
+    errors: list[ScanError] = Field(default_factory=list)
 
 
 class ScoreRequest(BaseModel):
@@ -289,3 +347,47 @@ class RunDetails(BaseModel):
     audit_records: list[ExecutionAuditRecord] = Field(default_factory=list)
     created_at: datetime
     updated_at: datetime
+
+
+class UploadCreateRequest(BaseModel):
+    file_name: str = Field(min_length=1, max_length=255)
+    size: int = Field(ge=0)
+
+
+class UploadChunk(BaseModel):
+    """A slice of the file starting at ``offset``, base64-encoded."""
+
+    offset: int = Field(ge=0)
+    data: str
+
+
+class UploadStatus(BaseModel):
+    upload_id: str
+    file_name: str
+    size: int
+    # Bytes received so far; the next chunk must start here.
+    offset: int
+    complete: bool
+    # Where the finished file is kept on the server.
+    path: Optional[str] = None
+
+
+class ExportFormat(str, Enum):
+    CSV = "csv"
+    JSON = "json"
+
+
+class ExportRequest(BaseModel):
+    run_id: str
+    format: ExportFormat = ExportFormat.CSV
+
+
+class ExportJob(BaseModel):
+    export_id: str
+    run_id: str
+    format: ExportFormat
+    file_name: str
+    size: int
+    # Hex SHA-256 of the file, for clients to check the download against.
+    sha256: str
+    created_at: datetime
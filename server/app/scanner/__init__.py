@@ -1 +1,2 @@
+from .progress import ScanCancelled, ScanProgressTracker
 from .service import ScannerService
@@ -0,0 +1,135 @@
+from datetime import datetime, timezone
+import threading
+import time
+
+from app.models import RegionProgress, ScanProgress, ScanStage
+
+_RETENTION_SECONDS = 600  # finished scans stay readable this long
+
+# (start, share) of a bucket's work for each per-bucket stage.
+_STAGE_SPANS = {
+    ScanStage.SCANNING_OBJECTS: (0.0, 0.8),
+    ScanStage.CHECKING_LIFECYCLE: (0.8, 0.1),
+    ScanStage.CHECKING_MULTIPART: (0.9, 0.1),
+}
+
+
+class ScanCancelled(Exception):
+    """Raised inside a scan whose cancellation was requested."""
+
+
+class ScanProgressTracker:
+    """
+    In-memory progress of running scans, keyed by the client's scan_id.
+
+    The scan route runs in a worker thread while clients poll the progress
+    route from another, so every access goes through a lock.
+    """
+
+    def __init__(self) -> None:
+        self._lock = threading.Lock()
+        self._scans: dict[str, ScanProgress] = {}
+        self._bucket_regions: dict[str, dict[str, str]] = {}
+        self._finished_at: dict[str, float] = {}
+        # May name scans that have not started yet, so a cancel sent right
+        # after the scan request still applies.
+        self._cancelled: set[str] = set()
+
+    def start(self, scan_id: str) -> None:
+        now = datetime.now(timezone.utc)
+        with self._lock:
+            self._prune()
+            self._scans[scan_id] = ScanProgress(
+                scan_id=scan_id,
+                stage=ScanStage.RESOLVING_BUCKETS,
+                percent=0.0,
+                started_at=now,
+                updated_at=now,
+            )
+            self._bucket_regions[scan_id] = {}
+            self._finished_at.pop(scan_id, None)
+
+    def set_buckets(self, scan_id: str, bucket_regions: dict[str, str]) -> None:
+        regions: dict[str, RegionProgress] = {}
+        for region in bucket_regions.values():
+            entry = regions.setdefault(region, RegionProgress(bucket_count=0, buckets_done=0))
+            entry.bucket_count += 1
+        with self._lock:
+            progress = self._scans.get(scan_id)
+            if progress is None:
+                return
+            self._bucket_regions[scan_id] = dict(bucket_regions)
+            progress.bucket_count = len(bucket_regions)
+            progress.regions = regions
+            progress.updated_at = datetime.now(timezone.utc)
+
+    def update(self, scan_id: str, bucket: str, stage: ScanStage, fraction: float = 0.0) -> None:
+        """Record that `bucket` is in `stage`, `fraction` (0–1) of the way through it."""
+        with self._lock:
+            progress = self._scans.get(scan_id)
+            if progress is None:
+                return
+            start, share = _STAGE_SPANS.get(stage, (0.0, 0.0))
+            bucket_fraction = start + share * min(max(fraction, 0.0), 1.0)
+            progress.stage = stage
+            progress.bucket = bucket
+            progress.region = self._bucket_regions.get(scan_id, {}).get(bucket)
+            if progress.bucket_count:
+                done = progress.buckets_done + bucket_fraction
+                progress.percent = round(min(done / progress.bucket_count, 1.0) * 100, 1)
+            progress.updated_at = datetime.now(timezone.utc)
+
+    def bucket_done(self, scan_id: str, bucket: str) -> None:
+        with self._lock:
+            progress = self._scans.get(scan_id)
+            if progress is None:
+                return
+            progress.buckets_done += 1
+            region = self._bucket_regions.get(scan_id, {}).get(bucket)
+            if region in progress.regions:
+                progress.regions[region].buckets_done += 1
+            if progress.bucket_count:
+                progress.percent = round(progress.buckets_done / progress.bucket_count * 100, 1)
+            progress.updated_at = datetime.now(timezone.utc)
+
+    def finish(self, scan_id: str) -> None:
+        with self._lock:
+            progress = self._scans.get(scan_id)
+            if progress is None:
+                return
+            progress.stage = ScanStage.COMPLETED
+            progress.bucket = None
+            progress.region = None
+            progress.percent = 100.0
+            progress.updated_at = datetime.now(timezone.utc)
+            self._finished_at[scan_id] = time.monotonic()
+
+    def cancel(self, scan_id: str) -> None:
+        with self._lock:
+            self._cancelled.add(scan_id)
+
+    def check_cancelled(self, scan_id: str) -> None:
+        """Raise ScanCancelled (and mark the scan cancelled) if requested."""
+        with self._lock:
+            if scan_id not in self._cancelled:
+                return
+            self._cancelled.discard(scan_id)
+            progress = self._scans.get(scan_id)
+            if progress is not None:
+                progress.stage = ScanStage.CANCELLED
+                progress.updated_at = datetime.now(timezone.utc)
+            self._finished_at[scan_id] = time.monotonic()
+        raise ScanCancelled(scan_id)
+
+    def get(self, scan_id: str) -> ScanProgress | None:
+        with self._lock:
+            progress = self._scans.get(scan_id)
+            return progress.model_copy(deep=True) if progress else None
+
+    def _prune(self) -> None:
+        cutoff = time.monotonic() - _RETENTION_SECONDS
+        for scan_id in [s for s, at in self._finished_at.items() if at < cutoff]:
+            self._scans.pop(scan_id, None)
+            self._bucket_regions.pop(scan_id, None)
+            self._finished_at.pop(scan_id, None)
+            self._cancelled.discard(scan_id)
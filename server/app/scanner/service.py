@@ -1,12 +1,22 @@
 from datetime import datetime, timedelta, timezone
 import logging
-from typing import Any
+from typing import Any, Callable
 import uuid
 
 import boto3
-from botocore.exceptions import ClientError
+from botocore.exceptions import BotoCoreError, ClientError
 
-from app.models import Recommendation, RecommendationType, RiskLevel, ScanRequest, StorageClass
+from app.models import (
+    Recommendation,
+    RecommendationType,
+    RiskLevel,
+    ScanCredentials,
+    ScanError,
+    ScanRequest,
+    ScanStage,
+    StorageClass,
+)
+from app.scanner.progress import ScanProgressTracker
 
 _log = logging.getLogger(__name__)
 
@@ -16,6 +26,33 @@ _MULTIPART_DAYS = 7    # Incomplete upload older than this → DELETE_INCOMPLETE
 _TARGET_CLASS = StorageClass.GLACIER_IR
 _STANDARD_PRICE = 0.023   # $/GB/month
 _GLACIER_IR_PRICE = 0.004
+_PROGRESS_EVERY = 100     # objects between progress updates
+
+StageCallback = Callable[[str, ScanStage, float], None]
+
+
+def _no_progress(bucket: str, stage: ScanStage, fraction: float) -> None:
+    pass
+
+
+def _scan_error(
+    error: Exception, region: str, bucket: str | None, operation: str
+) -> ScanError:
+    if isinstance(error, ClientError):
+        code = error.response.get("Error", {}).get("Code", "Unknown")
+        message = error.response.get("Error", {}).get("Message") or str(error)
+    else:
+        # Connection failures and other client-side errors have no AWS code.
+        code = type(error).__name__
+        message = str(error)
+    return ScanError(
+        region=region,
+        service="s3",
+        bucket=bucket,
+        operation=operation,
+        code=code,
+        message=message,
+    )
 
 
 class ScannerService:
@@ -31,13 +68,46 @@ class ScannerService:
     def __init__(self, s3_client: Any = None) -> None:
         self._s3 = s3_client
 
+    @classmethod
+    def for_credentials(cls, credentials: ScanCredentials) -> "ScannerService":
+        """A scanner for one request's keys, e.g. another account's."""
+        token = credentials.session_token
+        return cls(
+            s3_client=boto3.client(
+                "s3",
+                region_name=credentials.region,
+                aws_access_key_id=credentials.access_key_id,
+                aws_secret_access_key=credentials.secret_access_key.get_secret_value(),
+                aws_session_token=token.get_secret_value() if token else None,
+                endpoint_url=credentials.endpoint_url,
+            )
+        )
+
     @property
     def s3(self) -> Any:
         if self._s3 is None:
             self._s3 = boto3.client("s3")
         return self._s3
 
-    def scan(self, request: ScanRequest) -> list[Recommendation]:
+    def scan(
+        self, request: ScanRequest, progress: ScanProgressTracker | None = None
+    ) -> list[Recommendation]:
+        return self.scan_partial(request, progress)[0]
+
+    def scan_partial(
+        self, request: ScanRequest, progress: ScanProgressTracker | None = None
+    ) -> tuple[list[Recommendation], list[ScanError]]:
+        """
+        Like scan(), but a failing bucket check is recorded as a ScanError
+        and the scan moves on, so one disabled region or denied call does not
+        discard the findings of every other bucket.
+        """
+        errors: list[ScanError] = []
+        scan_id = request.scan_id if progress is not None else None
+        if scan_id:
+            progress.start(scan_id)
+            progress.check_cancelled(scan_id)
+
         excluded = set(request.exclude_buckets)
 
         if request.include_buckets:
@@ -48,35 +118,109 @@ class ScannerService:
                 buckets = [
                     b["Name"] for b in resp.get("Buckets", []) if b["Name"] not in excluded
                 ]
-            except ClientError:
+            except (BotoCoreError, ClientError) as e:
+                errors.append(_scan_error(e, "global", None, "ListBuckets"))
                 buckets = []
 
+        regions: dict[str, str] = {}
+        if scan_id or request.regions:
+            regions = {b: self._bucket_region(b) for b in buckets}
+        if request.regions:
+            # Buckets whose region is unknown are kept, so their errors show.
+            enabled = set(request.regions) | {"unknown"}
+            buckets = [b for b in buckets if regions[b] in enabled]
+            regions = {b: regions[b] for b in buckets}
+
+        on_stage: StageCallback = _no_progress
+        if scan_id:
+            progress.set_buckets(scan_id, regions)
+
+            def on_stage(bucket: str, stage: ScanStage, fraction: float) -> None:
+                # Every progress update doubles as a cancellation checkpoint.
+                progress.check_cancelled(scan_id)
+                progress.update(scan_id, bucket, stage, fraction)
+
         recommendations: list[Recommendation] = []
         for bucket in buckets:
-            recommendations.extend(
-                self._scan_bucket(bucket, request.max_objects_per_bucket)
+            bucket_recs, bucket_errors = self._scan_bucket(
+                bucket, request.max_objects_per_bucket, on_stage
             )
-        return recommendations
+            recommendations.extend(bucket_recs)
+            for error in bucket_errors:
+                if bucket not in regions:
+                    regions[bucket] = self._bucket_region(bucket)
+                error.region = regions[bucket]
+                errors.append(error)
+            if scan_id:
+                progress.bucket_done(scan_id, bucket)
+        if scan_id:
+            progress.finish(scan_id)
+        return recommendations, errors
+
+    def _bucket_region(self, bucket: str) -> str:
+        """Region of a bucket, for progress reporting and region filters."""
+        try:
+            location = self.s3.get_bucket_location(Bucket=bucket).get("LocationConstraint")
+        except ClientError:
+            return "unknown"
+        # us-east-1 buckets report no constraint; legacy EU buckets report "EU".
+        # GovCloud and China have no us-east-1, so the client's region is used.
+        if not location:
+            if self.s3.meta.partition != "aws":
+                return self.s3.meta.region_name
+            return "us-east-1"
+        return "eu-west-1" if location == "EU" else location
 
-    def _scan_bucket(self, bucket: str, max_objects: int) -> list[Recommendation]:
+    def _scan_bucket(
+        self, bucket: str, max_objects: int, on_stage: StageCallback = _no_progress
+    ) -> tuple[list[Recommendation], list[ScanError]]:
+        """
+        Runs each check independently; a check that fails is reported and
+        skipped. Errors carry region "unknown" until the caller resolves it.
+        """
         recommendations: list[Recommendation] = []
+        errors: list[ScanError] = []
 
-        object_recs, _total_size_bytes, standard_size_bytes = self._scan_objects(bucket, max_objects)
-        recommendations.extend(object_recs)
-        lifecycle_rec = self._check_lifecycle(bucket, total_size_bytes=standard_size_bytes)
-        if lifecycle_rec:
-            recommendations.append(lifecycle_rec)
-        recommendations.extend(self._check_multipart_uploads(bucket))
+        standard_size_bytes = 0
+        try:
+            object_recs, _total_size_bytes, standard_size_bytes = self._scan_objects(
+                bucket, max_objects, on_stage
+            )
+            recommendations.extend(object_recs)
+        except (BotoCoreError, ClientError) as e:
+            errors.append(_scan_error(e, "unknown", bucket, "ListObjectsV2"))
+        else:
+            # Without the object listing, lifecycle savings would be
+            # estimated from zero bytes, so that check only runs after it.
+            on_stage(bucket, ScanStage.CHECKING_LIFECYCLE, 0.0)
+            try:
+                lifecycle_rec = self._check_lifecycle(
+                    bucket, total_size_bytes=standard_size_bytes
+                )
+                if lifecycle_rec:
+                    recommendations.append(lifecycle_rec)
+            except (BotoCoreError, ClientError) as e:
+                errors.append(
+                    _scan_error(e, "unknown", bucket, "GetBucketLifecycleConfiguration")
+                )
+        on_stage(bucket, ScanStage.CHECKING_MULTIPART, 0.0)
+        try:
+            recommendations.extend(self._check_multipart_uploads(bucket))
+        except (BotoCoreError, ClientError) as e:
+            errors.append(_scan_error(e, "unknown", bucket, "ListMultipartUploads"))
 
-        return recommendations
+        return recommendations, errors
 
-    def _scan_objects(self, bucket: str, max_objects: int) -> tuple[list[Recommendation], int, int]:
+    def _scan_objects(
+        self, bucket: str, max_objects: int, on_stage: StageCallback = _no_progress
+    ) -> tuple[list[Recommendation], int, int]:
         recs: list[Recommendation] = []
         now = datetime.now(timezone.utc)
         count = 0
         total_size_bytes = 0
         standard_size_bytes = 0
 
+        on_stage(bucket, ScanStage.SCANNING_OBJECTS, 0.0)
         try:
             paginator = self.s3.get_paginator("list_objects_v2")
             for page in paginator.paginate(Bucket=bucket):
@@ -84,6 +228,8 @@ class ScannerService:
                     if count >= max_objects:
                         break
                     count += 1
+                    if count % _PROGRESS_EVERY == 0:
+                        on_stage(bucket, ScanStage.SCANNING_OBJECTS, count / max_objects)
 
                     key: str = obj["Key"]
                     size_bytes: int = obj.get("Size", 0)
@@ -1,2 +1,4 @@
+from app.state.exports import ExportStore
 from app.state.store import RunRecord, RunStore
+from app.state.uploads import UploadStore
 
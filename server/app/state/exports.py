@@ -0,0 +1,112 @@
+"""Generated run reports, kept on disk for clients to download.
+
+A report is written once, with its size and SHA-256 recorded beside it, so a
+client can fetch it in ranges, resume after a dropped connection and check
+what it got.
+"""
+
+from __future__ import annotations
+
+import csv
+from datetime import datetime, timezone
+import hashlib
+import io
+import json
+from pathlib import Path
+from typing import Optional
+import uuid
+
+from app.models import ExportFormat, ExportJob
+from app.state.store import RunRecord
+
+_CSV_COLUMNS = [
+    "id",
+    "bucket",
+    "key",
+    "recommendation_type",
+    "risk_level",
+    "reason",
+    "recommended_action",
+    "estimated_monthly_savings",
+    "size_bytes",
+    "storage_class",
+    "target_storage_class",
+    "last_modified",
+]
+
+
+def _csv_rows(record: RunRecord):
+    header = io.StringIO()
+    csv.writer(header).writerow(_CSV_COLUMNS)
+    yield header.getvalue()
+    for recommendation in record.recommendations:
+        values = recommendation.model_dump(mode="json")
+        line = io.StringIO()
+        csv.writer(line).writerow(
+            "" if values.get(column) is None else values[column] for column in _CSV_COLUMNS
+        )
+        yield line.getvalue()
+
+
+def _json_chunks(record: RunRecord):
+    yield json.dumps(
+        {
+            "run_id": record.run_id,
+            "status": record.status.value,
+            "recommendations": [
+                recommendation.model_dump(mode="json")
+                for recommendation in record.recommendations
+            ],
+            "scores": [score.model_dump(mode="json") for score in record.scores],
+            "savings_summary": (
+                record.savings_summary.model_dump(mode="json")
+                if record.savings_summary is not None
+                else None
+            ),
+        },
+        indent=2,
+    )
+
+
+class ExportStore:
+    def __init__(self, root: str = "data/exports") -> None:
+        self._root = Path(root)
+
+    def create(self, record: RunRecord, export_format: ExportFormat) -> ExportJob:
+        export_id = str(uuid.uuid4())
+        directory = self._root / export_id
+        directory.mkdir(parents=True)
+        file_name = f"run-{record.run_id}.{export_format.value}"
+        chunks = _csv_rows(record) if export_format == ExportFormat.CSV else _json_chunks(record)
+        digest = hashlib.sha256()
+        size = 0
+        with (directory / file_name).open("wb") as handle:
+            for chunk in chunks:
+                data = chunk.encode()
+                digest.update(data)
+                size += len(data)
+                handle.write(data)
+        job = ExportJob(
+            export_id=export_id,
+            run_id=record.run_id,
+            format=export_format,
+            file_name=file_name,
+            size=size,
+            sha256=digest.hexdigest(),
+            created_at=datetime.now(timezone.utc),
+        )
+        (directory / "export.json").write_text(job.model_dump_json())
+        return job
+
+    def get(self, export_id: str) -> Optional[ExportJob]:
+        try:
+            uuid.UUID(export_id)
+        except ValueError:
+            return None
+        meta = self._root / export_id / "export.json"
+        if not meta.exists():
+            return None
+        return ExportJob.model_validate_json(meta.read_text())
+
+    def file(self, job: ExportJob) -> Path:
+        return self._root / job.export_id / job.file_name
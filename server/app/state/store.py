@@ -276,6 +276,16 @@ class RunStore:
                     )
                 return cursor.rowcount > 0
 
+    def integrity_check(self) -> list[str]:
+        """Problems reported by SQLite's quick check; empty when healthy."""
+        try:
+            with self._connect() as conn:
+                rows = conn.execute("PRAGMA quick_check").fetchall()
+        except sqlite3.Error as exc:
+            return [str(exc)]
+        problems = [row[0] for row in rows]
+        return [] if problems == ["ok"] else problems
+
     def _initialize(self) -> None:
         with self._connect() as conn:
             conn.execute(
@@ -0,0 +1,108 @@
+"""Resumable uploads of large local files, such as Cost and Usage Reports.
+
+Each upload gets a directory holding its metadata and the bytes received so
+far. Chunks must arrive in order, each starting where the last one ended, so
+a client that lost track after a dropped connection asks for the status and
+carries on from its offset. Once every byte is in, the file takes its own
+name.
+"""
+
+from __future__ import annotations
+
+import json
+from pathlib import Path
+from threading import Lock
+from typing import Optional
+import uuid
+
+from app.models import UploadStatus
+
+
+class UploadOffsetMismatch(Exception):
+    """A chunk did not start where the upload stands."""
+
+    def __init__(self, expected: int) -> None:
+        super().__init__(f"Upload continues at byte {expected}.")
+        self.expected = expected
+
+
+class UploadStore:
+    def __init__(self, root: str = "data/uploads") -> None:
+        self._lock = Lock()
+        self._root = Path(root)
+
+    def create(self, file_name: str, size: int) -> UploadStatus:
+        name = Path(file_name).name
+        if name in ("", ".", ".."):
+            raise ValueError(f"Invalid file name {file_name!r}.")
+        upload_id = str(uuid.uuid4())
+        directory = self._root / upload_id
+        directory.mkdir(parents=True)
+        (directory / "upload.json").write_text(json.dumps({"file_name": name, "size": size}))
+        (directory / f"{name}.part").touch()
+        status = self.get(upload_id)
+        assert status is not None
+        if size == 0:
+            return self._finish(status)
+        return status
+
+    def get(self, upload_id: str) -> Optional[UploadStatus]:
+        directory = self._directory(upload_id)
+        if directory is None:
+            return None
+        meta = json.loads((directory / "upload.json").read_text())
+        name, size = meta["file_name"], meta["size"]
+        done = directory / name
+        if done.exists():
+            return UploadStatus(
+                upload_id=upload_id,
+                file_name=name,
+                size=size,
+                offset=size,
+                complete=True,
+                path=str(done.resolve()),
+            )
+        return UploadStatus(
+            upload_id=upload_id,
+            file_name=name,
+            size=size,
+            offset=(directory / f"{name}.part").stat().st_size,
+            complete=False,
+        )
+
+    def append(self, upload_id: str, offset: int, data: bytes) -> Optional[UploadStatus]:
+        """Adds a chunk starting at ``offset``. ``None`` if there is no such
+        upload."""
+        with self._lock:
+            status = self.get(upload_id)
+            if status is None:
+                return None
+            if status.complete or offset != status.offset:
+                raise UploadOffsetMismatch(status.offset)
+            if offset + len(data) > status.size:
+                raise ValueError(
+                    f"The chunk runs past the end of the {status.size}-byte file."
+                )
+            part = self._root / upload_id / f"{status.file_name}.part"
+            with part.open("ab") as handle:
+                handle.write(data)
+            status.offset += len(data)
+            if status.offset == status.size:
+                return self._finish(status)
+            return status
+
+    def _finish(self, status: UploadStatus) -> UploadStatus:
+        directory = self._root / status.upload_id
+        done = directory / status.file_name
+        (directory / f"{status.file_name}.part").replace(done)
+        status.complete = True
+        status.path = str(done.resolve())
+        return status
+
+    def _directory(self, upload_id: str) -> Optional[Path]:
+        try:
+            uuid.UUID(upload_id)
+        except ValueError:
+            return None
+        directory = self._root / upload_id
+        return directory if (directory / "upload.json").exists() else None
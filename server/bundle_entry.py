@@ -2,16 +2,29 @@
 PyInstaller entry point for the FastAPI sidecar.
 
 This file is compiled by PyInstaller into a standalone binary that Tauri
-manages as a sidecar process. It starts the uvicorn server on 127.0.0.1:8000.
+manages as a sidecar process. It starts the uvicorn server on the Unix socket
+the shell passes with ``--uds``, or else on 127.0.0.1 at ``--port`` (8000
+when run by hand). Extra CA certificates from the shell are trusted first.
 """
+import argparse
+
+from app.core.ca_bundle import install_extra_ca
 from app.main import app
 import uvicorn
 
 if __name__ == "__main__":
+    install_extra_ca()
+    parser = argparse.ArgumentParser()
+    parser.add_argument("--port", type=int, default=8000)
+    parser.add_argument("--uds", help="Unix socket to listen on instead of a port")
+    args = parser.parse_args()
+    if args.uds:
+        listen = {"uds": args.uds}
+    else:
+        listen = {"host": "127.0.0.1", "port": args.port}
     uvicorn.run(
         app,
-        host="127.0.0.1",
-        port=8000,
+        **listen,
         workers=1,
         log_level="warning",
     )
@@ -19,6 +19,14 @@ class TestHealthEndpoint:
         assert "environment" in body
         assert "timestamp" in body
 
+    def test_database_health_is_ok_for_fresh_store(self, client):
+        body = client.get("/api/v1/health/database").json()
+        assert body == {"status": "ok", "problems": []}
+
+    def test_version_reports_api_version(self, client):
+        body = client.get("/api/v1/version").json()
+        assert body == {"api_version": 1, "app_version": "1.0.0"}
+
 
 @pytest.mark.integration
 class TestMalformedRequests:
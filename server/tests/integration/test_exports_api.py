@@ -0,0 +1,61 @@
+"""Integration tests for the report export endpoints under /api/v1/exports."""
+
+import hashlib
+
+import pytest
+
+from app.api.routes import exports
+from app.state.exports import ExportStore
+
+
+@pytest.fixture(autouse=True)
+def export_store(tmp_path, tmp_store, monkeypatch):
+    store = ExportStore(root=str(tmp_path / "exports"))
+    monkeypatch.setattr(exports, "export_store", store)
+    monkeypatch.setattr(exports, "run_store", tmp_store)
+    return store
+
+
+def _export(client, export_format="csv"):
+    run_id = client.post(
+        "/api/v1/optimizer/scan", json={"include_buckets": ["test-bucket"]}
+    ).json()["run_id"]
+    resp = client.post("/api/v1/exports", json={"run_id": run_id, "format": export_format})
+    assert resp.status_code == 201
+    return resp.json()
+
+
+@pytest.mark.integration
+class TestExports:
+    def test_download_matches_the_recorded_checksum(self, client):
+        job = _export(client)
+        assert job["file_name"].endswith(".csv")
+        resp = client.get(f"/api/v1/exports/{job['export_id']}/download")
+        assert resp.status_code == 200
+        assert len(resp.content) == job["size"]
+        assert hashlib.sha256(resp.content).hexdigest() == job["sha256"]
+        assert resp.content.startswith(b"id,bucket,key,")
+
+    def test_json_exports_hold_the_recommendations(self, client):
+        job = _export(client, "json")
+        body = client.get(f"/api/v1/exports/{job['export_id']}/download").json()
+        assert body["run_id"] == job["run_id"]
+        assert body["recommendations"]
+
+    def test_ranges_resume_a_download(self, client):
+        job = _export(client)
+        full = client.get(f"/api/v1/exports/{job['export_id']}/download").content
+        resp = client.get(
+            f"/api/v1/exports/{job['export_id']}/download", headers={"Range": "bytes=10-"}
+        )
+        assert resp.status_code == 206
+        assert resp.content == full[10:]
+
+    def test_metadata_is_readable_by_id(self, client):
+        job = _export(client)
+        assert client.get(f"/api/v1/exports/{job['export_id']}").json() == job
+
+    def test_unknown_runs_and_exports_are_404(self, client):
+        resp = client.post("/api/v1/exports", json={"run_id": "missing"})
+        assert resp.status_code == 404
+        assert client.get("/api/v1/exports/not-an-export").status_code == 404
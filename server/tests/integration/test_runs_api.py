@@ -52,6 +52,29 @@ class TestListRuns:
         assert runs[0]["recommendation_count"] >= 1
 
 
+@pytest.mark.integration
+class TestRunsEtag:
+    def test_list_carries_an_etag(self, client):
+        resp = client.get("/api/v1/optimizer/runs")
+        assert resp.headers["etag"].startswith('W/"')
+
+    def test_matching_etag_gets_304_without_body(self, client):
+        _scan(client)
+        etag = client.get("/api/v1/optimizer/runs").headers["etag"]
+        resp = client.get("/api/v1/optimizer/runs", headers={"If-None-Match": etag})
+        assert resp.status_code == 304
+        assert resp.content == b""
+        assert resp.headers["etag"] == etag
+
+    def test_etag_changes_with_the_runs(self, client):
+        etag = client.get("/api/v1/optimizer/runs").headers["etag"]
+        _scan(client)
+        resp = client.get("/api/v1/optimizer/runs", headers={"If-None-Match": etag})
+        assert resp.status_code == 200
+        assert resp.headers["etag"] != etag
+        assert len(resp.json()) == 1
+
+
 @pytest.mark.integration
 class TestGetRun:
     def test_get_run_returns_200(self, client):
@@ -1,6 +1,12 @@
 """Integration tests for POST /api/v1/optimizer/scan."""
 
+import json
+
 import pytest
+from botocore.exceptions import ClientError
+
+from app.api.routes import optimizer
+from app.scanner.service import ScannerService
 
 
 @pytest.mark.integration
@@ -50,3 +56,67 @@ class TestScanEndpoint:
     def test_scan_invalid_max_objects_returns_422(self, client):
         resp = client.post("/api/v1/optimizer/scan", json={"max_objects_per_bucket": 0})
         assert resp.status_code == 422
+
+    def test_clean_scan_has_no_errors(self, client):
+        body = client.post("/api/v1/optimizer/scan", json={}).json()
+        assert body["errors"] == []
+
+    def test_failed_check_returns_partial_results(self, client, monkeypatch):
+        def fail(self, bucket):
+            raise ClientError(
+                {"Error": {"Code": "InternalError", "Message": "boom"}}, "ListMultipartUploads"
+            )
+
+        monkeypatch.setattr(ScannerService, "_check_multipart_uploads", fail)
+        resp = client.post("/api/v1/optimizer/scan", json={"include_buckets": ["test-bucket"]})
+        assert resp.status_code == 201
+        body = resp.json()
+        assert len(body["recommendations"]) >= 1
+        assert body["errors"] == [
+            {
+                "region": "us-east-1",
+                "service": "s3",
+                "bucket": "test-bucket",
+                "operation": "ListMultipartUploads",
+                "code": "InternalError",
+                "message": "boom",
+            }
+        ]
+
+
+@pytest.mark.integration
+class TestScanProgressEndpoint:
+    def test_progress_after_scan_is_completed(self, client):
+        client.post("/api/v1/optimizer/scan", json={"scan_id": "progress-1"})
+        resp = client.get("/api/v1/optimizer/scan/progress-1/progress")
+        assert resp.status_code == 200
+        body = resp.json()
+        assert body["stage"] == "completed"
+        assert body["percent"] == 100.0
+
+    def test_unknown_scan_progress_returns_404(self, client):
+        resp = client.get("/api/v1/optimizer/scan/nope/progress")
+        assert resp.status_code == 404
+
+    def test_events_end_with_complete(self, client):
+        client.post("/api/v1/optimizer/scan", json={"scan_id": "events-1"})
+        resp = client.get("/api/v1/optimizer/scan/events-1/events")
+        assert resp.status_code == 200
+        assert resp.headers["content-type"].startswith("text/event-stream")
+        assert resp.text.startswith("event: complete\ndata: ")
+        data = json.loads(resp.text.split("data: ", 1)[1])
+        assert data["scan_id"] == "events-1"
+        assert data["percent"] == 100.0
+
+    def test_events_for_unknown_scan_end_with_error(self, client, monkeypatch):
+        monkeypatch.setattr(optimizer, "_EVENTS_START_GRACE_SECONDS", 0.0)
+        resp = client.get("/api/v1/optimizer/scan/nope/events")
+        assert resp.text.startswith("event: error\n")
+        assert "has no progress" in resp.text
+
+    def test_cancelled_scan_returns_409_and_stores_no_run(self, client):
+        resp = client.post("/api/v1/optimizer/scan/cancel-1/cancel")
+        assert resp.status_code == 202
+        resp = client.post("/api/v1/optimizer/scan", json={"scan_id": "cancel-1"})
+        assert resp.status_code == 409
+        assert client.get("/api/v1/optimizer/runs").json() == []
@@ -0,0 +1,72 @@
+"""Integration tests for the chunked upload endpoints under /api/v1/uploads."""
+
+import base64
+
+import pytest
+
+from app.api.routes import uploads
+from app.state.uploads import UploadStore
+
+
+@pytest.fixture(autouse=True)
+def upload_store(tmp_path, monkeypatch):
+    store = UploadStore(root=str(tmp_path / "uploads"))
+    monkeypatch.setattr(uploads, "upload_store", store)
+    return store
+
+
+def _chunk(offset, data):
+    return {"offset": offset, "data": base64.b64encode(data).decode()}
+
+
+def _create(client, size, name="cur.csv"):
+    resp = client.post("/api/v1/uploads", json={"file_name": name, "size": size})
+    assert resp.status_code == 201
+    return resp.json()
+
+
+@pytest.mark.integration
+class TestUploads:
+    def test_chunks_in_order_complete_the_file(self, client):
+        upload = _create(client, 10)
+        assert upload["offset"] == 0
+        assert upload["complete"] is False
+
+        first = client.put(f"/api/v1/uploads/{upload['upload_id']}", json=_chunk(0, b"hello"))
+        assert first.json()["offset"] == 5
+        last = client.put(f"/api/v1/uploads/{upload['upload_id']}", json=_chunk(5, b"world"))
+        body = last.json()
+        assert body["complete"] is True
+        with open(body["path"], "rb") as handle:
+            assert handle.read() == b"helloworld"
+
+    def test_status_gives_the_offset_to_resume_from(self, client):
+        upload = _create(client, 10)
+        client.put(f"/api/v1/uploads/{upload['upload_id']}", json=_chunk(0, b"abc"))
+        status = client.get(f"/api/v1/uploads/{upload['upload_id']}").json()
+        assert status["offset"] == 3
+        assert status["complete"] is False
+
+    def test_a_chunk_at_the_wrong_offset_conflicts(self, client):
+        upload = _create(client, 10)
+        client.put(f"/api/v1/uploads/{upload['upload_id']}", json=_chunk(0, b"abc"))
+        resp = client.put(f"/api/v1/uploads/{upload['upload_id']}", json=_chunk(0, b"abc"))
+        assert resp.status_code == 409
+        assert "byte 3" in resp.json()["detail"]
+
+    def test_a_chunk_past_the_end_is_refused(self, client):
+        upload = _create(client, 2)
+        resp = client.put(f"/api/v1/uploads/{upload['upload_id']}", json=_chunk(0, b"abc"))
+        assert resp.status_code == 422
+
+    def test_file_names_cannot_leave_the_upload_directory(self, client):
+        upload = _create(client, 1, name="../../etc/cur.csv")
+        assert upload["file_name"] == "cur.csv"
+
+    def test_unknown_uploads_are_404(self, client):
+        resp = client.get("/api/v1/uploads/not-an-upload")
+        assert resp.status_code == 404
+
+    def test_empty_files_are_complete_at_once(self, client):
+        upload = _create(client, 0)
+        assert upload["complete"] is True
@@ -0,0 +1,41 @@
+"""Unit tests for combining the extra CA bundle with the default one."""
+
+import pytest
+
+from app.core.ca_bundle import install_extra_ca
+
+EXTRA = b"-----BEGIN CERTIFICATE-----\nCORP\n-----END CERTIFICATE-----\n"
+
+
+@pytest.mark.unit
+class TestInstallExtraCa:
+    def test_nothing_configured_changes_nothing(self):
+        environ = {}
+        assert install_extra_ca(environ) is None
+        assert environ == {}
+
+    def test_extra_certificates_are_appended_to_the_base(self, tmp_path):
+        base = tmp_path / "base.pem"
+        base.write_bytes(b"BASE")
+        extra = tmp_path / "corp.pem"
+        extra.write_bytes(EXTRA)
+        environ = {"AWS_CA_BUNDLE": str(base), "EXTRA_CA_BUNDLE": str(extra)}
+
+        path = install_extra_ca(environ)
+
+        with open(path, "rb") as f:
+            assert f.read() == b"BASE\n" + EXTRA
+        assert environ["AWS_CA_BUNDLE"] == path
+        assert environ["REQUESTS_CA_BUNDLE"] == path
+        assert environ["SSL_CERT_FILE"] == path
+
+    def test_default_bundle_is_the_base(self, tmp_path):
+        extra = tmp_path / "corp.pem"
+        extra.write_bytes(EXTRA)
+
+        path = install_extra_ca({"EXTRA_CA_BUNDLE": str(extra)})
+
+        with open(path, "rb") as f:
+            combined = f.read()
+        assert combined.endswith(EXTRA)
+        assert b"BEGIN CERTIFICATE" in combined[: -len(EXTRA)]
@@ -0,0 +1,96 @@
+"""Unit tests for scan progress tracking."""
+
+import pytest
+
+from app.models import ScanRequest, ScanStage
+from app.scanner.progress import ScanCancelled, ScanProgressTracker
+from app.scanner.service import ScannerService
+
+
+@pytest.fixture()
+def tracker():
+    return ScanProgressTracker()
+
+
+@pytest.fixture()
+def svc(s3_mock):
+    return ScannerService(s3_client=s3_mock)
+
+
+@pytest.mark.unit
+class TestScanProgressTracker:
+    def test_unknown_scan_has_no_progress(self, tracker):
+        assert tracker.get("missing") is None
+
+    def test_start_begins_resolving_buckets(self, tracker):
+        tracker.start("s1")
+        progress = tracker.get("s1")
+        assert progress.stage == ScanStage.RESOLVING_BUCKETS
+        assert progress.percent == 0.0
+
+    def test_regions_count_buckets(self, tracker):
+        tracker.start("s1")
+        tracker.set_buckets("s1", {"a": "us-east-1", "b": "us-east-1", "c": "eu-west-1"})
+        progress = tracker.get("s1")
+        assert progress.bucket_count == 3
+        assert progress.regions["us-east-1"].bucket_count == 2
+        assert progress.regions["eu-west-1"].bucket_count == 1
+
+    def test_update_reports_bucket_region_and_percent(self, tracker):
+        tracker.start("s1")
+        tracker.set_buckets("s1", {"a": "us-east-1", "b": "eu-west-1"})
+        tracker.bucket_done("s1", "a")
+        tracker.update("s1", "b", ScanStage.SCANNING_OBJECTS, 0.5)
+        progress = tracker.get("s1")
+        assert progress.bucket == "b"
+        assert progress.region == "eu-west-1"
+        assert progress.regions["us-east-1"].buckets_done == 1
+        assert progress.percent == 70.0
+
+    def test_finish_completes(self, tracker):
+        tracker.start("s1")
+        tracker.set_buckets("s1", {"a": "us-east-1"})
+        tracker.finish("s1")
+        progress = tracker.get("s1")
+        assert progress.stage == ScanStage.COMPLETED
+        assert progress.percent == 100.0
+
+    def test_get_returns_a_copy(self, tracker):
+        tracker.start("s1")
+        tracker.get("s1").percent = 50.0
+        assert tracker.get("s1").percent == 0.0
+
+
+@pytest.mark.unit
+class TestScannerReportsProgress:
+    def test_scan_with_id_records_completed_progress(self, svc, tracker):
+        svc.scan(ScanRequest(include_buckets=["test-bucket"], scan_id="s1"), progress=tracker)
+        progress = tracker.get("s1")
+        assert progress.stage == ScanStage.COMPLETED
+        assert progress.buckets_done == 1
+        assert progress.regions["us-east-1"].buckets_done == 1
+
+    def test_scan_without_id_records_nothing(self, svc, tracker):
+        svc.scan(ScanRequest(include_buckets=["test-bucket"]), progress=tracker)
+        assert tracker._scans == {}
+
+
+@pytest.mark.unit
+class TestScanCancellation:
+    def test_cancel_before_start_stops_scan(self, svc, tracker):
+        tracker.cancel("s1")
+        with pytest.raises(ScanCancelled):
+            svc.scan(ScanRequest(include_buckets=["test-bucket"], scan_id="s1"), progress=tracker)
+        assert tracker.get("s1").stage == ScanStage.CANCELLED
+
+    def test_cancel_is_consumed_by_one_scan(self, svc, tracker):
+        tracker.cancel("s1")
+        with pytest.raises(ScanCancelled):
+            svc.scan(ScanRequest(include_buckets=["test-bucket"], scan_id="s1"), progress=tracker)
+        svc.scan(ScanRequest(include_buckets=["test-bucket"], scan_id="s1"), progress=tracker)
+        assert tracker.get("s1").stage == ScanStage.COMPLETED
+
+    def test_check_without_cancel_is_noop(self, tracker):
+        tracker.start("s1")
+        tracker.check_cancelled("s1")
+        assert tracker.get("s1").stage == ScanStage.RESOLVING_BUCKETS
@@ -2,8 +2,9 @@
 
 import pytest
 import boto3
+from botocore.exceptions import ClientError, EndpointConnectionError
 
-from app.models import RecommendationType, ScanRequest
+from app.models import RecommendationType, ScanCredentials, ScanRequest
 from app.scanner.service import ScannerService
 
 
@@ -58,6 +59,13 @@ class TestBucketResolution:
         ids = [r.id for r in result]
         assert len(ids) == len(set(ids))
 
+    def test_regions_restrict_scan(self, svc, s3_mock):
+        s3_mock.create_bucket(
+            Bucket="eu-bucket", CreateBucketConfiguration={"LocationConstraint": "eu-west-1"}
+        )
+        result = svc.scan(ScanRequest(regions=["eu-west-1"]))
+        assert {r.bucket for r in result} == {"eu-bucket"}
+
 
 # ---------------------------------------------------------------------------
 # Lifecycle policy detection
@@ -288,3 +296,94 @@ class TestRecommendationFieldValidity:
             if r.recommendation_type == RecommendationType.CHANGE_STORAGE_CLASS
         ]
         assert len(object_recs) == 1
+
+
+# ---------------------------------------------------------------------------
+# Partial results
+# ---------------------------------------------------------------------------
+
+def _client_error(code, operation):
+    return ClientError({"Error": {"Code": code, "Message": f"{code} raised"}}, operation)
+
+
+@pytest.mark.unit
+class TestPartialResults:
+    def test_clean_scan_reports_no_errors(self, svc):
+        _, errors = svc.scan_partial(ScanRequest(include_buckets=["test-bucket"]))
+        assert errors == []
+
+    def test_failing_check_keeps_other_findings(self, svc, monkeypatch):
+        def fail(bucket):
+            raise _client_error("InternalError", "ListMultipartUploads")
+
+        monkeypatch.setattr(svc, "_check_multipart_uploads", fail)
+        recs, errors = svc.scan_partial(ScanRequest(include_buckets=["test-bucket"]))
+        types = [r.recommendation_type for r in recs]
+        assert RecommendationType.ADD_LIFECYCLE_POLICY in types
+        assert len(errors) == 1
+        assert errors[0].bucket == "test-bucket"
+        assert errors[0].operation == "ListMultipartUploads"
+        assert errors[0].code == "InternalError"
+        assert errors[0].service == "s3"
+        assert errors[0].region == "us-east-1"
+
+    def test_unreachable_bucket_does_not_stop_later_buckets(self, svc, s3_mock, monkeypatch):
+        s3_mock.create_bucket(Bucket="bucket-b")
+        scan_objects = svc._scan_objects
+
+        def flaky(bucket, max_objects, on_stage):
+            if bucket == "test-bucket":
+                raise EndpointConnectionError(endpoint_url="https://s3.ap-east-1.amazonaws.com")
+            return scan_objects(bucket, max_objects, on_stage)
+
+        monkeypatch.setattr(svc, "_scan_objects", flaky)
+        recs, errors = svc.scan_partial(
+            ScanRequest(include_buckets=["test-bucket", "bucket-b"])
+        )
+        assert [e.code for e in errors] == ["EndpointConnectionError"]
+        assert errors[0].operation == "ListObjectsV2"
+        # The lifecycle check is skipped without an object listing.
+        assert all(r.bucket == "bucket-b" for r in recs if r.key is None)
+        assert any(r.bucket == "bucket-b" for r in recs)
+
+    def test_scan_returns_only_recommendations(self, svc, monkeypatch):
+        def fail(bucket):
+            raise _client_error("SlowDown", "ListMultipartUploads")
+
+        monkeypatch.setattr(svc, "_check_multipart_uploads", fail)
+        result = svc.scan(ScanRequest(include_buckets=["test-bucket"]))
+        assert len(result) >= 1
+
+
+@pytest.mark.unit
+class TestRequestCredentials:
+    def test_scanner_uses_the_request_keys(self):
+        svc = ScannerService.for_credentials(
+            ScanCredentials(
+                access_key_id="AKIAOTHERACCOUNT01",
+                secret_access_key="other-secret",
+                session_token="other-token",
+                region="eu-west-1",
+            )
+        )
+        creds = svc.s3._request_signer._credentials
+        assert creds.access_key == "AKIAOTHERACCOUNT01"
+        assert creds.token == "other-token"
+        assert svc.s3.meta.region_name == "eu-west-1"
+
+    def test_scanner_uses_a_custom_endpoint(self):
+        svc = ScannerService.for_credentials(
+            ScanCredentials(
+                access_key_id="AKIALOCALSTACK0001",
+                secret_access_key="test",
+                endpoint_url="http://localhost:4566",
+            )
+        )
+        assert svc.s3.meta.endpoint_url == "http://localhost:4566"
+
+    def test_secrets_are_not_echoed(self):
+        request = ScanRequest(
+            credentials={"access_key_id": "AKIAOTHERACCOUNT01", "secret_access_key": "s3cr3t"}
+        )
+        assert "s3cr3t" not in repr(request)
+        assert "s3cr3t" not in request.model_dump_json()
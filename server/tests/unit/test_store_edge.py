@@ -336,3 +336,14 @@ class TestUpdateRollbackStatusEdge:
         updated_audit = store.list_execution_audit(run_id)
         # Message should be unchanged (COALESCE returns old value)
         assert updated_audit[0].message == original_message
+
+
+class TestIntegrityCheck:
+    def test_fresh_store_has_no_problems(self, store):
+        assert store.integrity_check() == []
+
+    def test_corrupt_file_reports_problem(self, tmp_path):
+        db = tmp_path / "corrupt.db"
+        store = RunStore(db_path=str(db))
+        db.write_bytes(b"not a database" * 100)
+        assert store.integrity_check()